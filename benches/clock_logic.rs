@@ -0,0 +1,60 @@
+//! Per-frame and per-resize CPU work of the clocks
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use chrono::{DateTime, FixedOffset, TimeZone};
+use clockutils::clocks::{
+    digital::{calculate_clock_data, create_blur_weights_and_offsets, ColonStyle, INDICATOR_LAYOUT},
+    mcounter::calc_wheel_angles,
+    polar::calc_angles_and_positions
+};
+
+fn sample_time(nanos: u32) -> DateTime<FixedOffset> {
+    FixedOffset::east_opt(6 * 3600).unwrap()
+        .with_ymd_and_hms(2024, 3, 15, 10, 8, 59).unwrap()
+        + chrono::TimeDelta::nanoseconds(nanos as i64)
+}
+
+fn blur_table(c: &mut Criterion) {
+    let mut group = c.benchmark_group("create_blur_weights_and_offsets");
+
+    // 40 is the radius at ~204 dpi, the rest covers hi-dpi screens and cranked up blur scales
+    for radius in [10, 20, 40, 80, 160, 320] {
+        let sigma = radius as f32 * 0.25;
+
+        group.bench_with_input(BenchmarkId::new("linear", radius), &radius, |b, &radius| {
+            b.iter(|| create_blur_weights_and_offsets(black_box(radius), sigma, true, true))
+        });
+
+        group.bench_with_input(BenchmarkId::new("discrete", radius), &radius, |b, &radius| {
+            b.iter(|| create_blur_weights_and_offsets(black_box(radius), sigma, false, true))
+        });
+    }
+
+    group.finish();
+}
+
+fn clock_data(c: &mut Criterion) {
+    let now = sample_time(250_000_000);
+
+    c.bench_function("calculate_clock_data/24h", |b| b.iter(|| calculate_clock_data(black_box(&now), false, 0, ColonStyle::Blink, &INDICATOR_LAYOUT)));
+    c.bench_function("calculate_clock_data/12h", |b| b.iter(|| calculate_clock_data(black_box(&now), true,  0, ColonStyle::Blink, &INDICATOR_LAYOUT)));
+    c.bench_function("calculate_clock_data/pulse", |b| b.iter(|| calculate_clock_data(black_box(&now), false, 0, ColonStyle::Pulse, &INDICATOR_LAYOUT)));
+}
+
+fn wheel_angles(c: &mut Criterion) {
+    // in the middle of a transition (10:08:59 -> 10:09:00, three wheels rolling) and at rest
+    let rolling = sample_time(1_000_000_000 + 250_000_000);
+    let resting = sample_time(750_000_000);
+
+    c.bench_function("calc_wheel_angles/rolling", |b| b.iter(|| calc_wheel_angles(black_box(&rolling))));
+    c.bench_function("calc_wheel_angles/resting", |b| b.iter(|| calc_wheel_angles(black_box(&resting))));
+}
+
+fn angles_and_positions(c: &mut Criterion) {
+    let now = sample_time(500_000_000);
+
+    c.bench_function("calc_angles_and_positions", |b| b.iter(|| calc_angles_and_positions(black_box(&now))));
+}
+
+criterion_group!(benches, blur_table, clock_data, wheel_angles, angles_and_positions);
+criterion_main!(benches);
@@ -0,0 +1,16 @@
+//! Packed color helpers, called several times per frame
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use clockutils::{lerp_u32_color, u32_col_to_wgpu_col, rgba32};
+
+fn colors(c: &mut Criterion) {
+    let from = 0x0F52BA_FF;
+    let to   = 0xFF7F50_80;
+
+    c.bench_function("lerp_u32_color", |b| b.iter(|| lerp_u32_color(black_box(from), black_box(to), black_box(0.37))));
+    c.bench_function("u32_col_to_wgpu_col", |b| b.iter(|| u32_col_to_wgpu_col(black_box(from))));
+    c.bench_function("rgba32", |b| b.iter(|| rgba32(black_box(15), black_box(82), black_box(186), black_box(255))));
+}
+
+criterion_group!(benches, colors);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+//! PLY parsing, on synthetic grid meshes way larger than the ones shipped with the clocks,
+//! and the levels of detail simplified from the portal's terrain
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use clockutils::PlyMesh;
+
+/// ASCII PLY of an `n` x `n` quad grid, in the layout `PlyMesh` expects (position + UV, triangulated faces).
+/// Indices are u16, so `n` must stay below 256.
+fn synthetic_grid(n: u32) -> Vec<u8> {
+    let vertex_count = (n + 1) * (n + 1);
+    let face_count   = n * n * 2;
+
+    let mut text = format!(
+        "ply\nformat ascii 1.0\n\
+         element vertex {}\nproperty float x\nproperty float y\nproperty float z\nproperty float s\nproperty float t\n\
+         element face {}\nproperty list uchar uint vertex_indices\nend_header\n",
+        vertex_count, face_count
+    );
+
+    for y in 0..=n {
+        for x in 0..=n {
+            let (u, v) = (x as f32 / n as f32, y as f32 / n as f32);
+            text.push_str(&format!("{} {} {} {} {}\n", u * 2.0 - 1.0, v * 2.0 - 1.0, (u * v).sin(), u, v));
+        }
+    }
+
+    for y in 0..n {
+        for x in 0..n {
+            let i = y * (n + 1) + x;
+            text.push_str(&format!("3 {} {} {}\n", i, i + 1, i + n + 1));
+            text.push_str(&format!("3 {} {} {}\n", i + 1, i + n + 2, i + n + 1));
+        }
+    }
+
+    text.into_bytes()
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("PlyMesh::from_reader");
+
+    for n in [16, 64, 200] {
+        let data = synthetic_grid(n);
+        group.throughput(Throughput::Bytes(data.len() as u64));
+
+        group.bench_with_input(BenchmarkId::from_parameter(n * n * 2), &data, |b, data| {
+            b.iter(|| PlyMesh::from_reader(&mut data.as_slice()).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn simplify(c: &mut Criterion) {
+    let mesh = PlyMesh::new(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/portal/meshes/terrain_geo.ply")).unwrap();
+    let mut group = c.benchmark_group("PlyMesh::simplified");
+
+    group.throughput(Throughput::Elements(mesh.vertices.len() as u64));
+
+    for ratio in [0.5, 0.25] {
+        group.bench_with_input(BenchmarkId::from_parameter(ratio), &ratio, |b, &ratio| {
+            b.iter(|| mesh.simplified(ratio))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, parse, simplify);
+criterion_main!(benches);
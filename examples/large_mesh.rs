@@ -0,0 +1,239 @@
+//! Progressive loading of a large PLY mesh with `PlyMeshStreamer`.
+//!
+//! A ~500K vertex terrain is generated into the temp folder on the first run (or pass the path of an ASCII PLY file).
+//! The vertices show up as points while they're streamed in, the surface is drawn once the faces arrived.
+//!
+//!     cargo run --release --example large_mesh [-- FILE.ply]
+
+use std::io::Write;
+use std::sync::OnceLock;
+use wgpu::{RenderPipelineDescriptor, PushConstantRange};
+use clockutils::{
+    run_with_options, cast_struct_to_u8_slice,
+    ExecDraw, FrameInfo, PlyGeoBuffers, PlyMeshStreamer, RenderTexture, RunOptions, Vtx3UV,
+    logging
+};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Quads per side of the generated terrain, (n + 1)^2 = 499 849 vertices
+const GRID_SIZE:  u32   = 706;
+const BATCH_SIZE: usize = 16 * 1024;
+
+static MESH_PATH: OnceLock<String> = OnceLock::new();
+
+/// Rolling hills out of a few sine waves, the UVs span the whole grid
+fn write_terrain(path: &std::path::Path, n: u32) -> std::io::Result<()> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    writeln!(file, "ply\nformat ascii 1.0\ncomment Generated by the large_mesh example")?;
+    writeln!(file, "element vertex {}\nproperty float x\nproperty float y\nproperty float z\nproperty float s\nproperty float t", (n + 1) * (n + 1))?;
+    writeln!(file, "element face {}\nproperty list uchar uint vertex_indices\nend_header", n * n * 2)?;
+
+    for y in 0..=n {
+        for x in 0..=n {
+            let (u, v) = (x as f32 / n as f32, y as f32 / n as f32);
+            let (px, py) = (u * 20.0 - 10.0, v * 20.0 - 10.0);
+            let height = 0.9 * (px * 0.45).sin() * (py * 0.38).cos()
+                + 0.35 * (px * 1.3 + py * 0.7).sin()
+                + 0.08 * (px * 5.1).cos() * (py * 4.7).sin();
+
+            writeln!(file, "{:.4} {:.4} {:.4} {:.5} {:.5}", px, py, height, u, v)?;
+        }
+    }
+
+    for y in 0..n {
+        for x in 0..n {
+            let i = y * (n + 1) + x;
+            writeln!(file, "3 {} {} {}", i, i + 1, i + n + 2)?;
+            writeln!(file, "3 {} {} {}", i, i + n + 2, i + n + 1)?;
+        }
+    }
+
+    file.flush()
+}
+
+struct LargeMesh {
+    point_pipeline:    wgpu::RenderPipeline,
+    triangle_pipeline: wgpu::RenderPipeline,
+    depth:             wgpu::TextureView,
+    geometry:          PlyGeoBuffers,
+    aspect:            f32,
+    started:           std::time::Instant
+}
+
+impl ExecDraw for LargeMesh {
+    fn setup(
+        config:   &wgpu::SurfaceConfiguration,
+        _adapter: &wgpu::Adapter,
+        device:   &wgpu::Device,
+        queue:    &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> {
+        let path = MESH_PATH.get().expect("The mesh path is set in main");
+        let (streamer, _loader) = PlyMeshStreamer::new(path, BATCH_SIZE).map_err(|error| format!("Can't stream {}: {}", path, error))?;
+        log::info!("Streaming {} vertices and {} faces in batches of {}", streamer.vertex_count(), streamer.face_count(), streamer.batch_size());
+
+        let geometry = PlyGeoBuffers::from_streamer(device, queue, streamer);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  None,
+            source: wgpu::ShaderSource::Wgsl(include_str!("large_mesh.wgsl").into())
+        });
+
+        // the view-projection matrix is sent via push constants
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[],
+            push_constant_ranges: &[
+                PushConstantRange {
+                    stages: wgpu::ShaderStages::VERTEX,
+                    range:  0..64
+                }
+            ]
+        });
+
+        let vertex_buffer_layouts = [
+            wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Vtx3UV>() as wgpu::BufferAddress,
+                step_mode:    wgpu::VertexStepMode::Vertex,
+                attributes:   &[
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, shader_location: 0, offset: 0 },
+                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 1, offset: std::mem::offset_of!(Vtx3UV, uv) as u64 }
+                ]
+            }
+        ];
+
+        let create_pipeline = |topology: wgpu::PrimitiveTopology| device.create_render_pipeline(&RenderPipelineDescriptor {
+            label:  None,
+            layout: Some(&pipeline_layout),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default()
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview:   None,
+            vertex: wgpu::VertexState {
+                module:      &shader,
+                entry_point: "vs_main",
+                buffers:     &vertex_buffer_layouts
+            },
+            fragment: Some(wgpu::FragmentState {
+                module:      &shader,
+                entry_point: "fs_main",
+                targets:     &[ Some(config.format.into()) ]
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology,
+                cull_mode:    None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..Default::default()
+            }
+        });
+
+        Ok(Self {
+            point_pipeline:    create_pipeline(wgpu::PrimitiveTopology::PointList),
+            triangle_pipeline: create_pipeline(wgpu::PrimitiveTopology::TriangleList),
+            depth:    RenderTexture::new((config.width, config.height), DEPTH_FORMAT, false, device).view,
+            geometry,
+            aspect:   config.width as f32 / config.height as f32,
+            started:  std::time::Instant::now()
+        })
+    }
+
+    fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, _queue: &wgpu::Queue) {
+        self.depth  = RenderTexture::new((width, height), DEPTH_FORMAT, false, device).view;
+        self.aspect = width as f32 / height as f32;
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
+        if self.geometry.update(queue) && self.geometry.is_complete() {
+            log::info!("Fully loaded after {:?}", self.started.elapsed());
+        }
+
+        // slowly orbiting the terrain, z up
+        let angle = self.started.elapsed().as_secs_f32() * 0.2;
+        let eye   = glam::Vec3::new(angle.cos() * 16.0, angle.sin() * 16.0, 9.0);
+        let view  = glam::Mat4::look_at_rh(eye, glam::Vec3::ZERO, glam::Vec3::Z);
+        let proj  = glam::Mat4::perspective_rh(45_f32.to_radians(), self.aspect, 0.1, 100.0);
+        let matrix = proj * view;
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:               None,
+                timestamp_writes:    None,
+                occlusion_query_set: None,
+                color_attachments:   &[Some(wgpu::RenderPassColorAttachment {
+                    view: texview,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load:  wgpu::LoadOp::Clear(wgpu::Color { r: 0.02, g: 0.02, b: 0.03, a: 1.0 }),
+                        store: wgpu::StoreOp::Store
+                    }
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth,
+                    depth_ops: Some(wgpu::Operations {
+                        load:  wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store
+                    }),
+                    stencil_ops: None
+                })
+            });
+
+            rpass.set_vertex_buffer(0, self.geometry.vbuffer.slice(..));
+
+            // points until the faces are there
+            if self.geometry.is_complete() {
+                rpass.set_pipeline(&self.triangle_pipeline);
+                rpass.set_push_constants(wgpu::ShaderStages::VERTEX, 0, cast_struct_to_u8_slice(&matrix));
+                rpass.set_index_buffer(self.geometry.ibuffer.slice(..), self.geometry.index_format);
+                rpass.draw_indexed(0..self.geometry.icount as u32, 0, 0..1);
+            } else {
+                rpass.set_pipeline(&self.point_pipeline);
+                rpass.set_push_constants(wgpu::ShaderStages::VERTEX, 0, cast_struct_to_u8_slice(&matrix));
+                rpass.draw(0..self.geometry.vcount as u32, 0..1);
+            }
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
+
+fn main() {
+    logging::init("large_mesh");
+
+    let path = match std::env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            let path = std::env::temp_dir().join("wgpu-clocks-large-mesh.ply");
+
+            if !path.exists() {
+                println!("Generating {}...", path.display());
+                write_terrain(&path, GRID_SIZE).expect("Failed to write the terrain mesh");
+            }
+
+            path.to_str().unwrap().to_string()
+        }
+    };
+
+    let _ = MESH_PATH.set(path);
+
+    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    let window = winit::window::WindowBuilder::new()
+        .with_inner_size(winit::dpi::LogicalSize { width: 1024.0, height: 640.0 })
+        .with_title("Large Mesh Streaming")
+        .build(&event_loop)
+        .unwrap();
+
+    pollster::block_on(run_with_options::<LargeMesh>(
+        event_loop, window,
+        Some(wgpu::Features::PUSH_CONSTANTS),
+        RunOptions::default()
+    ));
+}
@@ -0,0 +1,37 @@
+// Terrain of the large_mesh example, shaded by height, with a faint grid from the UVs
+
+struct VertexOutput {
+    @builtin(position) pos:    vec4f,
+    @location(0)       uv:     vec2f,
+    @location(1)       height: f32
+}
+
+var<push_constant> view_projection: mat4x4<f32>;
+
+const LOW_COLOR:  vec3f = vec3f(0.10, 0.22, 0.12);
+const HIGH_COLOR: vec3f = vec3f(0.78, 0.74, 0.62);
+const GRID_LINES: f32   = 32.0;
+
+@vertex
+fn vs_main(
+    @location(0) pos: vec3<f32>,
+    @location(1)  uv: vec2<f32>
+) -> VertexOutput {
+    var vto: VertexOutput;
+
+    vto.pos    = view_projection * vec4f(pos, 1.0);
+    vto.uv     = uv;
+    vto.height = pos.z;
+
+    return vto;
+}
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let t     = clamp(vto.height * 0.4 + 0.5, 0.0, 1.0);
+    let cell  = fract(vto.uv * GRID_LINES);
+    let line  = step(0.96, max(cell.x, cell.y));
+    let color = mix(LOW_COLOR, HIGH_COLOR, t) * (1.0 - 0.25 * line);
+
+    return vec4f(color, 1.0);
+}
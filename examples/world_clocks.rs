@@ -0,0 +1,162 @@
+//! A polar clock on each monitor, each one in another timezone, with `MultiMonitorScales`.
+//!
+//! A single borderless window covers all of the monitors, and every monitor's part of it gets a clock fitted into
+//! that monitor alone, so none of them straddles a bezel. The timezones are given in monitor order
+//! (`local` and `UTC` if none are), monitors past the last timezone repeat the list.
+//!
+//!     cargo run --release --example world_clocks [-- UTC+6 -05:00 ...]
+
+use std::sync::OnceLock;
+use clockutils::{
+    run_with_options, cast_struct_to_u8_slice,
+    ExecDraw, FrameInfo, FullscreenQuadPipeline, MultiMonitorScales, RunOptions,
+    logging,
+    time::{parse_timezone, TimeSource},
+    clocks::polar::{calc_angles_and_positions, EXTENT}
+};
+use chrono::FixedOffset;
+
+static TIMEZONES: OnceLock<Vec<(String, Option<FixedOffset>)>> = OnceLock::new();
+static MONITORS:  OnceLock<MultiMonitorScales> = OnceLock::new();
+
+/// Push constants of world_clocks.wgsl
+#[repr(C, align(8))]
+struct ClockParams {
+    scale:   glam::Vec2,
+    density: f32,
+    hours:   f32,
+    minutes: f32,
+    seconds: f32
+}
+
+struct WorldClocks {
+    quad:       FullscreenQuadPipeline,
+    monitors:   MultiMonitorScales,
+    clocks:     Vec<(String, TimeSource)>, // one per monitor
+    resolution: glam::Vec2
+}
+
+impl ExecDraw for WorldClocks {
+    fn setup(
+        config:   &wgpu::SurfaceConfiguration,
+        _adapter: &wgpu::Adapter,
+        device:   &wgpu::Device,
+        _queue:   &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> {
+        let monitors  = MONITORS.get().expect("The monitors are set in main").clone();
+        let timezones = TIMEZONES.get().expect("The timezones are set in main");
+
+        let clocks = timezones.iter().cycle().take(monitors.len())
+            .map(|(name, timezone)| (name.clone(), TimeSource::new(*timezone, None, None, 1.0)))
+            .collect();
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("World clocks"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("world_clocks.wgsl").into())
+        });
+
+        let quad = FullscreenQuadPipeline::new(
+            device, &shader, "fs_main", &[],
+            &[ wgpu::PushConstantRange { stages: wgpu::ShaderStages::FRAGMENT, range: 0..std::mem::size_of::<ClockParams>() as u32 } ],
+            config.format
+        );
+
+        Ok(Self { quad, monitors, clocks, resolution: glam::Vec2::new(config.width as f32, config.height as f32) })
+    }
+
+    fn resize(self: &mut Self, width: u32, height: u32, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        self.resolution = glam::Vec2::new(width as f32, height as f32);
+    }
+
+    fn title_status(self: &Self) -> Option<String> {
+        Some(self.clocks.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(" | "))
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    None,
+                depth_stencil_attachment: None,
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                    view:           texview,
+                    resolve_target: None,
+                    ops:            wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store }
+                })]
+            });
+
+            for (index, (_, source)) in self.clocks.iter().enumerate() {
+                let scales = self.monitors.for_monitor(index);
+                let ap     = calc_angles_and_positions(&source.now());
+
+                let params = ClockParams {
+                    scale:   scales.scale,
+                    density: scales.density,
+                    hours:   ap.hours_angle,
+                    minutes: ap.minutes_angle,
+                    seconds: ap.seconds_angle
+                };
+
+                let viewport = self.monitors.viewport(index, self.resolution);
+                rpass.set_viewport(viewport.x, viewport.y, viewport.z, viewport.w, 0.0, 1.0);
+                self.quad.draw(&mut rpass, &[], cast_struct_to_u8_slice(&params));
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+fn main() {
+    logging::init("world_clocks");
+
+    let mut names: Vec<String> = std::env::args().skip(1).collect();
+    if names.is_empty() {
+        names = vec!["local".to_string(), "UTC".to_string()];
+    }
+
+    let timezones = names.into_iter().map(|name| match parse_timezone(&name) {
+        Ok(timezone) => (name, timezone),
+        Err(reason) => {
+            eprintln!("invalid timezone '{}': {}", name, reason);
+            std::process::exit(2);
+        }
+    }).collect();
+
+    let _ = TIMEZONES.set(timezones);
+
+    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    let extent     = glam::Vec2::splat(EXTENT);
+
+    // the window goes where the top-left monitor is, `MultiMonitorScales` counts from there as well
+    let left = event_loop.available_monitors().map(|monitor| monitor.position().x).min().unwrap_or(0);
+    let top  = event_loop.available_monitors().map(|monitor| monitor.position().y).min().unwrap_or(0);
+
+    let mut monitors = MultiMonitorScales::from_winit(&event_loop, extent);
+    if monitors.is_empty() {
+        log::warn!("No monitors reported, showing a single clock");
+        monitors = MultiMonitorScales::new(vec![(0, 0, 800, 800)], extent);
+    } else if monitors.len() < TIMEZONES.get().map_or(0, Vec::len) {
+        log::info!("Only {} monitor(s), the timezones past those aren't shown", monitors.len());
+    }
+
+    let window = winit::window::WindowBuilder::new()
+        .with_decorations(false)
+        .with_position(winit::dpi::PhysicalPosition::new(left, top))
+        .with_inner_size(winit::dpi::PhysicalSize::new(monitors.total_resolution.x as u32, monitors.total_resolution.y as u32))
+        .with_title("World Clocks")
+        .build(&event_loop)
+        .unwrap();
+
+    let _ = MONITORS.set(monitors);
+
+    pollster::block_on(run_with_options::<WorldClocks>(
+        event_loop, window,
+        Some(wgpu::Features::PUSH_CONSTANTS),
+        RunOptions::default()
+    ));
+}
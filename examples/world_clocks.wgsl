@@ -0,0 +1,76 @@
+// Polar clock faces of the world_clocks example, one per monitor.
+// Drawn with `FullscreenQuadPipeline` into the monitor's viewport, so the UVs span the monitor.
+// The radii and thickness are the polar clock's, see `clocks::polar`
+
+struct ClockParams {
+    scale:   vec2f, // of the monitor's `DrawspaceScales`
+    density: f32,   // pixels per drawing unit
+    hours:   f32,   // angles of the arcs, in radians
+    minutes: f32,
+    seconds: f32
+}
+
+var<push_constant> params: ClockParams;
+
+const TAU: f32 = 6.28318530718;
+
+const SECONDS_RADIUS: f32 = 13.0;
+const MINUTES_RADIUS: f32 =  9.0;
+const HOURS_RADIUS:   f32 =  5.0;
+const THICKNESS:      f32 =  2.4;
+
+const BACKGROUND:    vec3f = vec3f(0.005, 0.005, 0.012);
+const TRACK:         vec3f = vec3f(0.04, 0.04, 0.06);
+const HOURS_COLOR:   vec3f = vec3f(0.44, 0.10, 0.98);
+const MINUTES_COLOR: vec3f = vec3f(0.08, 0.45, 0.98);
+const SECONDS_COLOR: vec3f = vec3f(0.05, 0.85, 0.55);
+
+// distance to the ring, in drawing units, negative inside
+fn ring_distance(pos: vec2f, radius: f32) -> f32 {
+    return abs(length(pos) - radius) - THICKNESS * 0.5;
+}
+
+// distance to the arc going clockwise from 12 o'clock, with round ends
+fn arc_distance(pos: vec2f, radius: f32, angle: f32) -> f32 {
+    var theta = atan2(pos.x, pos.y);
+    if theta < 0.0 {
+        theta += TAU;
+    }
+
+    if theta <= angle {
+        return ring_distance(pos, radius);
+    }
+
+    let start = vec2f(0.0, radius);
+    let end   = vec2f(sin(angle), cos(angle)) * radius;
+    return min(distance(pos, start), distance(pos, end)) - THICKNESS * 0.5;
+}
+
+// antialiased over a pixel
+fn coverage(distance: f32) -> f32 {
+    return clamp(0.5 - distance * params.density, 0.0, 1.0);
+}
+
+fn layer(color: vec3f, pos: vec2f, radius: f32, angle: f32) -> vec4f {
+    let track = coverage(ring_distance(pos, radius));
+    let arc   = coverage(arc_distance(pos, radius, angle));
+    return vec4f(mix(TRACK, color, arc), max(track, arc));
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    let ndc = vec2f(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+    let pos = ndc / params.scale;
+
+    var color = BACKGROUND;
+
+    let hours   = layer(HOURS_COLOR,   pos, HOURS_RADIUS,   params.hours);
+    let minutes = layer(MINUTES_COLOR, pos, MINUTES_RADIUS, params.minutes);
+    let seconds = layer(SECONDS_COLOR, pos, SECONDS_RADIUS, params.seconds);
+
+    color = mix(color, hours.rgb,   hours.a);
+    color = mix(color, minutes.rgb, minutes.a);
+    color = mix(color, seconds.rgb, seconds.a);
+
+    return vec4f(color, 1.0);
+}
@@ -0,0 +1,38 @@
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f
+}
+
+struct DrawspaceScales {
+    scale:      vec2<f32>,
+    extent:     vec2<f32>,
+    resolution: vec2<f32>,
+    density:    f32
+}
+
+@group(0) @binding(0)
+var<uniform> dscales: DrawspaceScales;
+
+@group(1) @binding(0)
+var dial_tex: texture_2d<f32>;
+
+@group(1) @binding(1)
+var tex_sampler: sampler;
+
+@vertex
+fn vs_main(@location(0) pos: vec2f, @location(1) uv: vec2f) -> VertexOutput {
+    var vto: VertexOutput;
+    vto.pos = vec4f(pos * dscales.scale, 0.0, 1.0);
+    vto.uv  = uv;
+
+    return vto;
+}
+
+#include "color.wgsl" // srgb_to_linear, the texture holds the image's sRGB bytes as they are
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(dial_tex, tex_sampler, vto.uv);
+
+    return vec4f(srgb_to_linear(color.rgb), color.a);
+}
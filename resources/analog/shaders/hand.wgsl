@@ -0,0 +1,60 @@
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f // [-1..1] across disks, 0 elsewhere
+}
+
+struct DrawspaceScales {
+    scale:      vec2<f32>,
+    extent:     vec2<f32>,
+    resolution: vec2<f32>,
+    density:    f32
+}
+
+struct HandInfo {
+    offset: vec2<f32>, // of the whole hand, for the shadows
+    angle:  f32,       // clockwise from 12 o'clock, in radians
+    color:  u32
+}
+
+var<push_constant> info: HandInfo;
+
+@group(0) @binding(0)
+var<uniform> dscales: DrawspaceScales;
+
+@vertex
+fn vs_main(@location(0) pos: vec2f, @location(1) uv: vec2f) -> VertexOutput {
+    // the hands point at 12 o'clock, y up. Turned clockwise
+    let c = cos(info.angle);
+    let s = sin(info.angle);
+    let turned = vec2f(pos.x * c + pos.y * s, pos.y * c - pos.x * s);
+
+    var vto: VertexOutput;
+    vto.pos = vec4f((turned + info.offset) * dscales.scale, 0.0, 1.0);
+    vto.uv  = uv;
+
+    return vto;
+}
+
+// 1.0 inside, 0.0 outside of the disks, smoothed over a pixel. The rest has its uv at 0, it's covered in full
+fn coverage(uv: vec2f) -> f32 {
+    let dist = length(uv);
+    let aa   = max(fwidth(dist), 0.0001);
+
+    return 1.0 - smoothstep(1.0 - aa, 1.0, dist);
+}
+
+#include "color.wgsl" // color_u32_to_vec4f, the palette's sRGB bytes decoded to linear
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let color = color_u32_to_vec4f(info.color);
+
+    return vec4f(color.rgb, color.a * coverage(vto.uv));
+}
+
+// Into the shadow's texture, blended additively: where the hands overlap, their shadows don't darken any further
+// than the target's 1.0 (it's blurred and laid over the dial by shadow.wgsl)
+@fragment
+fn fs_shadow(vto: VertexOutput) -> @location(0) vec4f {
+    return vec4f(coverage(vto.uv));
+}
@@ -0,0 +1,48 @@
+// from the library's fullscreen quad vertex shader (`FullscreenQuadPipeline`)
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f
+}
+
+struct DrawspaceScales {
+    scale:      vec2<f32>,
+    extent:     vec2<f32>,
+    resolution: vec2<f32>,
+    density:    f32
+}
+
+@group(0) @binding(0)
+var shadow_tex: texture_2d<f32>; // the hands' coverage, drawn by hand.wgsl's `fs_shadow`
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(1) @binding(0)
+var<uniform> dscales: DrawspaceScales;
+
+const SHADOW_OPACITY: f32 = 0.35;
+const SHADOW_SIGMA:   f32 = 0.3; // of the gaussian blur, in drawing units
+const TAPS:           i32 = 3;   // on either side of the center, in both directions
+
+// A single pass gaussian blur, the taps spread by the sigma rather than by the pixel, so the softness is the same
+// at any window size. The texture is of a lower resolution than the frame, its linear filtering fills in between
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let sigma = SHADOW_SIGMA * dscales.density / dscales.resolution; // in uv units
+    let step  = sigma * 0.6;
+
+    var sum    = 0.0;
+    var weight = 0.0;
+
+    for (var y = -TAPS; y <= TAPS; y++) {
+        for (var x = -TAPS; x <= TAPS; x++) {
+            let offset = vec2f(f32(x), f32(y)) * step;
+            let w      = exp(-0.5 * dot(offset / sigma, offset / sigma));
+
+            sum    += textureSampleLevel(shadow_tex, tex_sampler, vto.uv + offset, 0.0).r * w;
+            weight += w;
+        }
+    }
+
+    return vec4f(0.0, 0.0, 0.0, sum / weight * SHADOW_OPACITY);
+}
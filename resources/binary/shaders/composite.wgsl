@@ -0,0 +1,26 @@
+// from the library's fullscreen quad vertex shader (`FullscreenQuadPipeline`)
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f
+}
+
+@group(0) @binding(0)
+var leds_tex_2d: texture_2d<f32>; // the sharp LEDs over the background
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(1) @binding(0)
+var glow_tex_2d: texture_2d<f32>; // the lit LEDs blurred by filter.wgsl, both ways
+
+// the blur table's weights sum up to 1, spread this wide the glow would be too faint at full strength
+const GLOW_STRENGTH: f32 = 1.5;
+
+// The glow added over the sharp image, so that the LEDs' edges stay crisp whatever the blur radius is
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let sharp = textureSample(leds_tex_2d, tex_sampler, vto.uv).rgb;
+    let glow  = textureSample(glow_tex_2d, tex_sampler, vto.uv).rgb * GLOW_STRENGTH;
+
+    return vec4f(sharp + glow, 1.0);
+}
@@ -0,0 +1,48 @@
+struct VertexOutput {
+    @builtin(position)  pos: vec4f,
+    @location(0) powered_on: u32 // boolean, but bools can't be passed between stages
+}
+
+struct DrawspaceScales {
+    scale:      vec2<f32>,
+    extent:     vec2<f32>,
+    resolution: vec2<f32>,
+    density:    f32
+}
+
+struct BinaryData {
+    flags:     u32, // bit N lights island N, `calculate_binary_data` in clocks/binary.rs
+    on_color:  u32,
+    off_color: u32
+}
+
+var<push_constant> data: BinaryData;
+
+@group(0) @binding(0)
+var<uniform> dscales: DrawspaceScales;
+
+@vertex
+fn vs_main(
+    @location(0) pos: vec2<f32>,
+    @location(1)  id: u32
+) -> VertexOutput {
+    var vto: VertexOutput;
+
+    vto.pos        = vec4f(pos * dscales.scale, 0.0, 1.0);
+    vto.powered_on = (data.flags >> id) & 1u; // all 24 LEDs fit in a single flagset
+
+    return vto;
+}
+
+#include "color.wgsl" // color_u32_to_vec4f, the palette's sRGB bytes decoded to linear
+
+// The alpha tells filter.wgsl whether the pixel glows (1.0) or not (0.0),
+// the LEDs that are off keep their color in the sharp image only
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    if bool(vto.powered_on) {
+        return vec4f(color_u32_to_vec4f(data.on_color).rgb, 1.0);
+    } else {
+        return vec4f(color_u32_to_vec4f(data.off_color).rgb, 0.0);
+    }
+}
@@ -0,0 +1,58 @@
+// from the library's fullscreen quad vertex shader (`FullscreenQuadPipeline`)
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f
+}
+
+@group(0) @binding(0)
+var forward_tex_2d: texture_2d<f32>; // the sharp image of the forward pass
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(1) @binding(0)
+var glow_tex_2d: texture_2d<f32>; // the forward image blurred by filter.wgsl, both ways
+
+struct CompositeInfo {
+    glow_intensity: f32, // 1.0 is the default glow
+    blend_mode:     u32, // 0: additive, 1: screen
+    alpha_mode:     u32  // 0: opaque, 1: premultiplied, 2: postmultiplied (transparent window)
+}
+
+var<push_constant> info: CompositeInfo;
+
+// the blur table's weights sum up to 1, the glow would drown the segments at full strength
+const GLOW_TINT: vec3f = vec3f(0.2);
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let sharp = textureSample(forward_tex_2d, tex_sampler, vto.uv).rgb;
+    let glow  = textureSample(glow_tex_2d,    tex_sampler, vto.uv).rgb * GLOW_TINT * info.glow_intensity;
+
+    var combined: vec4f;
+    if info.blend_mode == 1u {
+        combined = vec4f(1.0 - (1.0 - clamp(sharp, vec3f(0.0), vec3f(1.0))) * (1.0 - clamp(glow, vec3f(0.0), vec3f(1.0))), 1.0);
+    } else {
+        combined = vec4f(sharp + glow, 1.0);
+    }
+
+    // The forward pass' alpha only marks glow contribution, it says nothing about coverage,
+    // so it can't be passed through. Everything here is emitted light over a black background,
+    // which is exactly a premultiplied color, with coverage being its brightest channel.
+    // The black background then stays fully transparent.
+    let coverage = clamp(max(combined.r, max(combined.g, combined.b)), 0.0, 1.0);
+
+    switch info.alpha_mode {
+        case 1u: {
+            combined.a = coverage;
+        }
+        case 2u: {
+            combined = vec4f(combined.rgb / max(coverage, 0.0001), coverage);
+        }
+        default: {
+            combined.a = 1.0;
+        }
+    }
+
+    return combined;
+}
@@ -3,13 +3,24 @@
 struct VertexOutput {
     @builtin(position)  pos: vec4f,
     @location(0)   uv_coord: vec2f,
-    @location(1) powered_on: u32
+    @location(1) powered_on: u32,
+    @location(2) brightness: f32,
+    @location(3)      group: u32 // island group, colored separately by the solid palettes
 }
 
 struct ClockData {
-    flagset:   array<u32, 2>,
-    selector:  u32,
-    timestamp: f32
+    flagset:   vec2<u32>, // not an array, the GL backend maps push constants by their types and takes arrays for one element
+    selector:    u32,
+    timestamp:   f32,
+    colon_alpha: f32,
+    use_texture_brightness: u32,
+    previous_flagset: vec2<u32>,
+    transition:  f32,
+    digits_color: u32,
+    colon_color:  u32,
+    labels_color: u32,
+    edited_group: u32, // 0 for none, otherwise one of the groups below
+    edited_brightness: f32
 }
 
 struct DrawspaceScales {
@@ -30,6 +41,13 @@ var tex_sampler: sampler;
 @group(1) @binding(0)
 var<uniform> dscales: DrawspaceScales;
 
+const COLON_ISLAND: u32 = 41u; // bit 9 of the 2nd flagset, `COLON_BIT` in clocks/digital.rs
+
+// island groups, the palette slots (+ 1) of `PALETTE_SLOTS` in clocks/digital.rs
+const DIGITS_GROUP: u32 = 1u; // the 1st flagset
+const COLON_GROUP:  u32 = 2u;
+const LABELS_GROUP: u32 = 3u; // the rest of the 2nd flagset
+
 @vertex
 fn vs_main(
     @location(0) pos: vec2<f32>,
@@ -53,15 +71,50 @@ fn vs_main(
     var island: u32 = id % 32u; // the local island ID relative to the selected flagset
     var is_on: bool = bool(flags & (1u << island)); // check if the corresponding island's bit is enabled or not
 
-    if is_on {
+    // the same for the LEDs faded from, while the minute changes
+    // the colon's brightness is animated separately (pulse, fade), it doesn't take part
+    var was_on: bool = bool(cdata.previous_flagset[id / 32u] & (1u << island));
+    if id == COLON_ISLAND {
+        was_on = is_on;
+    }
+
+    if is_on || was_on {
         vto.powered_on = 1u;
     } else {
         vto.powered_on = 0u;
     }
 
+    // turning on fades in, turning off fades out, the rest are at full brightness
+    if is_on && !was_on {
+        vto.brightness = cdata.transition;
+    } else if was_on && !is_on {
+        vto.brightness = 1.0 - cdata.transition;
+    } else {
+        vto.brightness = 1.0;
+    }
+
+    if id == COLON_ISLAND {
+        vto.brightness *= cdata.colon_alpha;
+    }
+
+    if id < 32u {
+        vto.group = DIGITS_GROUP;
+    } else if id == COLON_ISLAND {
+        vto.group = COLON_GROUP;
+    } else {
+        vto.group = LABELS_GROUP;
+    }
+
+    // the group whose color is being edited blinks
+    if vto.group == cdata.edited_group {
+        vto.brightness *= cdata.edited_brightness;
+    }
+
     return vto;
 }
 
+#include "color.wgsl" // color_u32_to_vec4f/vec3f, the palette's sRGB bytes decoded to linear
+
 fn hsv2rgb(c: vec3f) -> vec3f {
     let k: vec4f = vec4f(1.0, 2.0 / 3.0, 1.0 / 3.0, 3.0);
     let p: vec3f = abs(fract(c.xxx + k.xyz) * 6.0 - k.www);
@@ -96,19 +149,20 @@ const FADE_DURATION: f32 = 5.0;
 fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
     // Check if the current fragment is within an LED region (white color)
     // specified by the clock layout
-    let within_field: bool = textureSample(tex_2d, tex_sampler, vto.uv_coord).x > 0.1;
+    // The layout's grayscale also encodes the brightness distribution within each LED (brighter center, dimmer ends)
+    let field = textureSample(tex_2d, tex_sampler, vto.uv_coord).r;
+    let within_field: bool = field > 0.1;
 
     var color: vec3f;
 
     switch cdata.selector {
-        case 0u: {
-            color = vec3f(0.058, 0.321, 1.0); // blue
-        }
-        case 1u: {
-            color = vec3f(0.1, 0.9, 0.1); // green
-        }
-        case 2u: {
-            color = vec3f(1.0, 0.3, 0.0); // orange
+        case 0u, 1u, 2u: {
+            // blue, green and orange by default, from the palette file otherwise
+            switch vto.group {
+                case DIGITS_GROUP: { color = color_u32_to_vec3f(cdata.digits_color); }
+                case COLON_GROUP:  { color = color_u32_to_vec3f(cdata.colon_color);  }
+                default:           { color = color_u32_to_vec3f(cdata.labels_color); }
+            }
         }
         case 3u: {
             let hue = f32(cdata.timestamp % FADE_DURATION) / FADE_DURATION;
@@ -129,7 +183,11 @@ fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
     // same for turned off LED regions
     if within_field {
         if bool(vto.powered_on) {
-            return vec4f(color, 1.0); // on state, bright color
+            var intensity = vto.brightness;
+            if bool(cdata.use_texture_brightness) {
+                intensity *= field; // looks like an actual illuminated element, rather than a flat polygon
+            }
+            return vec4f(color * intensity, 1.0); // on state, bright color
         } else {
             return vec4f(0.005, 0.005, 0.005, 0.0); // off state, dark color but not fully black
         }
@@ -0,0 +1,133 @@
+#include "color.wgsl" // srgb_to_linear, the sprite sheet holds the image's sRGB bytes as they are
+#include "digits.wgsl"
+
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)  front_uv: vec2f, // the upper half of the flap's own digit
+    @location(1)   back_uv: vec2f, // the lower half of the digit it uncovers
+    @location(2)     shade: f32,
+    @location(3)    height: f32    // above the hinge, in flap heights [0..1]
+}
+
+struct MatrixData {
+    matrix: mat4x4<f32>
+}
+
+// Of each slot, 4 to a vector as uniform arrays are 16-byte strided, see `FlipState` in bin/flip.rs
+struct FlipState {
+    angles: array<vec4<f32>, 2>, // the falling flap's, 0 upright to π flat down
+    digits: array<vec4<u32>, 2>  // from | to << 8, see `SlotFlip`
+}
+
+@group(0) @binding(0)
+var<uniform> transform: MatrixData;
+
+@group(0) @binding(1)
+var<uniform> state: FlipState;
+
+const PI: f32 = 3.141592653589793238;
+
+// The slots stand where the mechanical counter's wheels do (`WHEEL_X_OFFSETS` in bin/mcounter.rs)
+const PAIR_WIDTH: f32 = 5.0;
+const SLOT_WIDTH: f32 = 2.25;
+const BASE_POS:   f32 = -6.125;
+
+// A flap is the upper half of a card (2 x 3, as the sprite sheet's cells)
+const FLAP_HALF_WIDTH: f32 = 1.0;
+const FLAP_HEIGHT:     f32 = 1.5;
+
+// The flaps resting in the stack sit this much behind the falling one, for the depth test
+const STACK_DEPTH: f32 = 0.02;
+
+// Somewhere outside of the clip volume, for the flaps hidden in the stack
+const HIDDEN: vec4f = vec4f(2.0, 2.0, 2.0, 1.0);
+
+// How dark the gap between the flaps at the hinge is, and how tall (in flap heights)
+const HINGE_SHADE:  f32 = 0.35;
+const HINGE_HEIGHT: f32 = 0.012;
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index)   VertexIndex:   u32,
+    @builtin(instance_index) InstanceIndex: u32
+) -> VertexOutput {
+    let slot_no = InstanceIndex / 10u; // 6 slots
+    let card_no = InstanceIndex % 10u; // 10 flaps each, one per digit
+
+    let angle  = state.angles[slot_no / 4u][slot_no % 4u];
+    let digits = state.digits[slot_no / 4u][slot_no % 4u];
+    let from_digit = digits & 0xFFu;
+    let to_digit   = (digits >> 8u) & 0xFFu;
+
+    // counter-clockwise facing the viewer while upright, the back faces it once fallen
+    var corners = array<vec2f, 4>(
+        vec2f(-1.0, 0.0),
+        vec2f( 1.0, 0.0),
+        vec2f(-1.0, 1.0),
+        vec2f( 1.0, 1.0)
+    );
+    let corner = corners[VertexIndex];
+
+    // Three flaps of a slot show at most: the new digit's upright one, the falling one of the old digit,
+    // and the one that fell before it, showing the lower half of the old digit on its back.
+    // Each flap's back carries the lower half of the next digit, except the falling one's, which uncovers `to_digit`
+    var flap_angle: f32;
+    var depth:      f32 = -STACK_DEPTH;
+    var back_digit: u32 = (card_no + 1u) % 10u;
+
+    if card_no == to_digit {
+        flap_angle = 0.0;
+    } else if card_no == from_digit {
+        flap_angle = angle;
+        back_digit = to_digit;
+        depth = 0.0;
+    } else if card_no == (from_digit + 9u) % 10u {
+        flap_angle = PI;
+    } else {
+        var vto: VertexOutput;
+        vto.pos = HIDDEN;
+        return vto;
+    }
+
+    // turned around the hinge, the top coming towards the viewer as it falls
+    let y = corner.y * FLAP_HEIGHT;
+    let x = BASE_POS + f32(slot_no / 2u) * PAIR_WIDTH + f32(slot_no % 2u) * SLOT_WIDTH + corner.x * FLAP_HALF_WIDTH;
+    let pos = vec4f(x, y * cos(flap_angle), y * sin(flap_angle) + depth, 1.0);
+
+    let u = corner.x * 0.5 + 0.5;
+
+    var vto: VertexOutput;
+    vto.pos      = transform.matrix * pos;
+    vto.front_uv = digit_cell_uv(vec2f(u, 0.5 - corner.y * 0.5), card_no);
+    vto.back_uv  = digit_cell_uv(vec2f(u, 0.5 + corner.y * 0.5), back_digit);
+    vto.shade    = mix(0.45, 1.0, abs(cos(flap_angle))); // darker while edge-on to the viewer
+    vto.height   = corner.y;
+
+    return vto;
+}
+
+@group(0) @binding(2)
+var tex_2d: texture_2d<f32>;
+
+@group(0) @binding(3)
+var tex_sampler: sampler;
+
+@fragment
+fn fs_main(vto: VertexOutput, @builtin(front_facing) front: bool) -> @location(0) vec4f {
+    // both sampled, textureSample can't be under the non-uniform `front`
+    let front_color = textureSample(tex_2d, tex_sampler, vto.front_uv);
+    let back_color  = textureSample(tex_2d, tex_sampler, vto.back_uv);
+    let color = select(back_color, front_color, front);
+
+    // the cards' rounded corners
+    if color.a < 0.5 {
+        discard;
+    }
+
+    var shade = vto.shade;
+    if vto.height < HINGE_HEIGHT {
+        shade *= HINGE_SHADE;
+    }
+
+    return vec4f(srgb_to_linear(color.rgb) * shade, 1.0);
+}
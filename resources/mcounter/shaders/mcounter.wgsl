@@ -1,21 +1,32 @@
+#include "color.wgsl"
+#include "digits.wgsl"
+
 struct VertexOutput {
     @builtin(position) pos: vec4f,
     @location(0)  frag_pos: vec4f,
-    @location(1)  frag_uvc: vec2f
+    @location(1)  frag_uvc: vec2f,
+    @location(2)  frag_nrm: vec3f,
+    @location(3) @interpolate(flat) wheel: u32
 }
 
 struct MatrixData {
     matrix: mat4x4<f32>
 }
 
-struct RotationAngles {
-    angles: array<f32, 6>
+struct WheelState {
+    angles:       array<f32, 6>,
+    env_strength: f32 // how much of the environment the cards reflect at grazing angles
+}
+
+// Of each wheel's cards, sRGB packed as 0xRRGGBBAA, four to a vector as uniform arrays are 16-byte strided
+struct WheelColors {
+    colors: array<vec4<u32>, 2>
 }
 
 @group(0) @binding(0)
 var<uniform> transform : MatrixData;
 
-var<push_constant> rotation: RotationAngles;
+var<push_constant> wheels: WheelState;
 
 const PI: f32 = 3.141592653589793238;
 
@@ -23,6 +34,9 @@ const PAIR_WIDTH:  f32 = 5.0;
 const WHEEL_WIDTH: f32 = 2.25;
 const BASE_POS: vec2<f32> = vec2f(-6.125, 0.0);
 
+// Mirrors the eye of the view matrix in `calc_matrix`, for the reflections
+const CAMERA_POS: vec3<f32> = vec3f(0.0, 0.0, 8.0);
+
 fn rotate_x(ng: f32) -> mat4x4<f32> {
     let cst = cos(ng);
     let snt = sin(ng);
@@ -66,22 +80,22 @@ fn vs_main(
 
     var uvs = array<vec2f, 4>(
         vec2f(0.0, 0.0),
-        vec2f(0.1, 0.0),
+        vec2f(1.0, 0.0),
         vec2f(0.0, 1.0),
-        vec2f(0.1, 1.0)
+        vec2f(1.0, 1.0)
     );
 
     var trn = translate_z(-WHEEL_RADIUS);
     // Each digit's "card" in a wheel is placed `2π/10` = `0.2π` radians apart, and are placed proportionally to their own value.
     // So, for a digit `n`, the card's angle will be `0.2πn`; this is prior to applying wheel rotation.
     // After applying parent wheel's rotation, the resultant card rotation will be `0.2πn + wheel_rotation`
-    var rtn = rotate_x(((2.0 * PI) * (f32(digit_no) / 10.0)) + -rotation.angles[pair_no * 2u + wheel_no]);
+    var rtn = rotate_x(((2.0 * PI) * (f32(digit_no) / 10.0)) + -wheels.angles[pair_no * 2u + wheel_no]);
 
     // The wheel's center is (offset.x, offset.y, WHEEL_RADIUS);
     var pos = vec4f(vertices[VertexIndex] + effective_pos, WHEEL_RADIUS, 1.0);
 
     // Sprite sheet adressing is applied to evalute the quad's UV coordinates
-    var uvc = uvs[VertexIndex] + vec2f(0.1 * f32(digit_no), 0.0);
+    var uvc = digit_cell_uv(uvs[VertexIndex], digit_no);
 
     // apply rotation and translation
     pos = (trn * rtn) * pos;
@@ -90,6 +104,9 @@ fn vs_main(
     vto.pos      = transform.matrix * pos;
     vto.frag_pos = pos;
     vto.frag_uvc = uvc;
+    // The cards face +z before they're rotated along with their wheel, the translation doesn't affect the normal
+    vto.frag_nrm = (rtn * vec4f(0.0, 0.0, 1.0, 0.0)).xyz;
+    vto.wheel    = pair_no * 2u + wheel_no;
 
     return vto;
 }
@@ -100,11 +117,27 @@ var tex_2d: texture_2d<f32>;
 @group(0) @binding(2)
 var tex_sampler: sampler;
 
+// Tiny gradient cubemap standing in for the surroundings, sky above and floor below
+@group(0) @binding(3)
+var env_cube: texture_cube<f32>;
+
+@group(0) @binding(4)
+var<uniform> wheel_colors: WheelColors;
+
 @fragment
 fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
     // Arbitrary darkening effect where the cards above and below gets progressively darker
     // based on absolute value of the y-axis
     // Try removing the part after asterisk to disable this effect
     let t = min(abs(vto.frag_pos.y), WHEEL_RADIUS) / WHEEL_RADIUS;
-    return textureSample(tex_2d, tex_sampler, vto.frag_uvc) * max(1.0 - 2.0 * pow(t, 3.0), 0.0);
+    let tint  = color_u32_to_vec3f(wheel_colors.colors[vto.wheel / 4u][vto.wheel % 4u]);
+    let color = textureSample(tex_2d, tex_sampler, vto.frag_uvc) * vec4f(tint, 1.0) * max(1.0 - 2.0 * pow(t, 3.0), 0.0);
+
+    // Glassy sheen: the surroundings reflect off the card, more so the more it's turned away from the viewer
+    let normal     = normalize(vto.frag_nrm);
+    let view_dir   = normalize(CAMERA_POS - vto.frag_pos.xyz);
+    let reflection = textureSample(env_cube, tex_sampler, reflect(-view_dir, normal));
+    let fresnel    = clamp(pow(max(1.0 - dot(normal, view_dir), 0.0), 2.0), 0.0, 1.0);
+
+    return vec4f(color.rgb + reflection.rgb * fresnel * wheels.env_strength, color.a);
 }
\ No newline at end of file
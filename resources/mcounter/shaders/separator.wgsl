@@ -0,0 +1,39 @@
+// Flat plates in front of the wheels: the dividers between the wheels, and the bezel above and below them
+
+struct MatrixData {
+    matrix: mat4x4<f32>
+}
+
+struct PlateInfo {
+    center: vec2f,
+    size:   vec2f,
+    color:  u32
+}
+
+@group(0) @binding(0)
+var<uniform> transform : MatrixData;
+
+var<push_constant> plate: PlateInfo;
+
+// Slightly in front of the frontmost cards (which are at z = 0), so that the depth test hides what's behind
+const PLATE_DEPTH: f32 = 0.05;
+
+@vertex
+fn vs_main(@builtin(vertex_index) VertexIndex: u32) -> @builtin(position) vec4f {
+    var vertices = array<vec2f, 4>(
+        vec2f(-0.5,  0.5),
+        vec2f( 0.5,  0.5),
+        vec2f(-0.5, -0.5),
+        vec2f( 0.5, -0.5)
+    );
+
+    let pos = plate.center + vertices[VertexIndex] * plate.size;
+    return transform.matrix * vec4f(pos, PLATE_DEPTH, 1.0);
+}
+
+#include "color.wgsl" // color_u32_to_vec4f/vec3f, the palette's sRGB bytes decoded to linear
+
+@fragment
+fn fs_main() -> @location(0) vec4f {
+    return color_u32_to_vec4f(plate.color);
+}
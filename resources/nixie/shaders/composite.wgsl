@@ -0,0 +1,26 @@
+// from the library's fullscreen quad vertex shader (`FullscreenQuadPipeline`)
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f
+}
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>; // the sharp tubes, or their glow
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+// the blur table's weights sum up to 1, spread this wide the glow would be too faint at full strength
+const GLOW_STRENGTH: f32 = 2.0;
+
+// The sharp tubes, as they are
+@fragment
+fn fs_sharp(vto: VertexOutput) -> @location(0) vec4f {
+    return vec4f(textureSample(src_tex_2d, tex_sampler, vto.uv).rgb, 1.0);
+}
+
+// The glow, blended premultiplied over the tubes. It's light, covering nothing: with an alpha of 0 it's added as it is
+@fragment
+fn fs_glow(vto: VertexOutput) -> @location(0) vec4f {
+    return vec4f(textureSample(src_tex_2d, tex_sampler, vto.uv).rgb * GLOW_STRENGTH, 0.0);
+}
@@ -0,0 +1,40 @@
+// from the library's fullscreen quad vertex shader (`FullscreenQuadPipeline`)
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f
+}
+
+#include "blur.wgsl"
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>; // the tubes, or their horizontal blur
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(1) @binding(0)
+var<storage, read> blur_table: array<BlurWO>;
+
+@group(1) @binding(1)
+var<uniform> blur_table_size: u32;
+
+// read by `blur`, see blur.wgsl
+fn blur_sample(uv: vec2f) -> vec4f {
+    return textureSample(src_tex_2d, tex_sampler, uv);
+}
+
+fn texel() -> vec2f {
+    return 1.0 / vec2f(textureDimensions(src_tex_2d));
+}
+
+// The two halves of the gaussian blur, as the binary clock's. The horizontal pass reads the tubes, where the lit
+// cathodes' alpha is how much they glow, the vertical one its result (alpha 1.0, so it's taken in full)
+@fragment
+fn fs_horizontal(vto: VertexOutput) -> @location(0) vec4f {
+    return blur(vec2f(1.0, 0.0), vto.uv, texel());
+}
+
+@fragment
+fn fs_vertical(vto: VertexOutput) -> @location(0) vec4f {
+    return blur(vec2f(0.0, 1.0), vto.uv, texel());
+}
@@ -0,0 +1,111 @@
+struct VertexInput {
+    @location(0) pos: vec3f,
+    @location(1)  uv: vec2f
+}
+
+struct VertexOutput {
+    @builtin(position)    pos: vec4f,
+    @location(0)           uv: vec2f,
+    @location(1)   brightness: f32
+}
+
+struct MatrixData {
+    matrix: mat4x4<f32>
+}
+
+// Of each tube, 4 to a vector as uniform arrays are 16-byte strided, see `NixieData` in clocks/nixie.rs
+struct NixieData {
+    digits:       array<vec4<u32>, 2>, // digit | previous << 8
+    since_change: array<vec4<f32>, 2>, // seconds since the digit changed, FLICKER_DURATION once settled
+    timestamp:    f32
+}
+
+@group(0) @binding(0)
+var<uniform> transform: MatrixData;
+
+@group(0) @binding(1)
+var<uniform> data: NixieData;
+
+// The tubes stand in pairs, like the mechanical counter's wheels
+const PAIR_WIDTH:   f32 = 3.1;
+const TUBE_SPACING: f32 = 1.3;
+const BASE_POS:     f32 = -3.75;
+
+// `FLICKER_DURATION` of clocks/nixie.rs, in seconds
+const FLICKER_DURATION: f32 = 0.15;
+// How fast a switched cathode flickers, in brightness changes per second
+const FLICKER_RATE: f32 = 60.0;
+
+// The numerals that aren't lit still catch some of the glow inside the glass
+const UNLIT_BRIGHTNESS:     f32 = 0.02;
+// The cathode switched off keeps glowing for a moment, this bright at first
+const AFTERGLOW_BRIGHTNESS: f32 = 0.5;
+
+fn hash(n: f32) -> f32 {
+    return fract(sin(n) * 43758.5453);
+}
+
+fn numeral_brightness(numeral: u32, tube: u32) -> f32 {
+    let digits   = data.digits[tube / 4u][tube % 4u];
+    let since    = data.since_change[tube / 4u][tube % 4u];
+    let digit    = digits & 0xFFu;
+    let previous = (digits >> 8u) & 0xFFu;
+
+    // settled, or the discharge catching on the new cathode unevenly
+    if numeral == digit {
+        if since >= FLICKER_DURATION {
+            return 1.0;
+        }
+
+        let step = floor(data.timestamp * FLICKER_RATE);
+        return mix(0.25, 1.0, hash(step + f32(tube) * 7.13));
+    }
+
+    if numeral == previous && since < FLICKER_DURATION {
+        return AFTERGLOW_BRIGHTNESS * (1.0 - since / FLICKER_DURATION);
+    }
+
+    return UNLIT_BRIGHTNESS;
+}
+
+@vertex
+fn vs_main(
+    vertex: VertexInput,
+    @builtin(vertex_index)   VertexIndex:   u32,
+    @builtin(instance_index) InstanceIndex: u32
+) -> VertexOutput {
+    let tube    = InstanceIndex;     // 6 tubes
+    let numeral = VertexIndex / 4u;  // a quad per numeral, in order, see numerals.ply
+
+    let x = BASE_POS + f32(tube / 2u) * PAIR_WIDTH + f32(tube % 2u) * TUBE_SPACING;
+
+    var vto: VertexOutput;
+    vto.pos        = transform.matrix * vec4f(vertex.pos + vec3f(x, 0.0, 0.0), 1.0);
+    vto.uv         = vertex.uv;
+    vto.brightness = numeral_brightness(numeral, tube);
+
+    return vto;
+}
+
+@group(0) @binding(2)
+var tex_2d: texture_2d<f32>;
+
+@group(0) @binding(3)
+var tex_sampler: sampler;
+
+// warm neon orange, linear
+const GLOW_COLOR: vec3f = vec3f(1.0, 0.28, 0.04);
+
+// The cathodes are added up, the ones behind show through the ones in front as they do through the wire.
+// The alpha marks how much a pixel glows, for the blur passes
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let wire = textureSample(tex_2d, tex_sampler, vto.uv);
+    let coverage = wire.a * vto.brightness;
+
+    // the wire's core burns almost white when lit
+    let core  = smoothstep(0.9, 1.0, wire.r) * vto.brightness * vto.brightness;
+    let color = mix(GLOW_COLOR, vec3f(1.0, 0.75, 0.45), core);
+
+    return vec4f(color * coverage, coverage);
+}
@@ -1,6 +1,6 @@
 struct VertexOutput {
     @builtin(position) pos: vec4f,
-    @location(0)  frag_pos: vec4f // unscaled fragment position
+    @location(0)  frag_pos: vec2f // unscaled fragment position
 }
 
 struct DrawspaceScales {
@@ -56,19 +56,12 @@ fn vs_main(
 
     var vto: VertexOutput;
     vto.pos      = vec4f(pos * dscales.scale, 0.0, 1.0);
-    vto.frag_pos = vec4f(pos, 0.0, 1.0);
+    vto.frag_pos = pos;
 
     return vto;
 }
 
-fn color_u32_to_vec4f(value: u32) -> vec4f {
-    let r = f32((value >> 24u) & 255u);
-    let g = f32((value >> 16u) & 255u);
-    let b = f32((value >>  8u) & 255u);
-    let a = f32(value          & 255u);
-    
-    return vec4f(r, g, b, a) * (1.0 / 255.0);
-}
+#include "color.wgsl" // color_u32_to_vec4f/vec3f, the palette's sRGB bytes decoded to linear
 
 @fragment
 fn fs_main(@location(0) frag_pos: vec2f) -> @location(0) vec4f {
@@ -0,0 +1,72 @@
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)     local: vec2f, // position within the sprite quad, [-1..1] in both axes
+    @location(1)     alpha: f32
+}
+
+struct DrawspaceScales {
+    scale:      vec2<f32>,
+    extent:     vec2<f32>,
+    resolution: vec2<f32>,
+    density:    f32
+}
+
+struct Star {
+    position:      vec2<f32>,
+    brightness:    f32,
+    flicker_phase: f32
+}
+
+struct StarFieldInfo {
+    timestamp: f32,
+    speed:     f32,
+    size:      f32,
+    color:     u32
+}
+
+var<push_constant> info: StarFieldInfo;
+
+@group(0) @binding(0)
+var<uniform> dscales: DrawspaceScales;
+
+@group(1) @binding(0)
+var<storage, read> stars: array<Star>;
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index)   VertexIndex  : u32,
+    @builtin(instance_index) InstanceIndex: u32
+) -> VertexOutput {
+    // unit quad, drawn as triangle strip
+    var corners = array<vec2f, 4>(
+        vec2f(-1.0,  1.0),
+        vec2f( 1.0,  1.0),
+        vec2f(-1.0, -1.0),
+        vec2f( 1.0, -1.0)
+    );
+
+    // one instance per star, the star's record is fetched from the storage buffer
+    let star   = stars[InstanceIndex];
+    let corner = corners[VertexIndex];
+    let pos    = star.position + corner * info.size * 0.5;
+
+    // sine flicker mapped from [-1..1] to [0.5..1.0]
+    let flicker = 0.75 + 0.25 * sin(info.timestamp * info.speed + star.flicker_phase);
+
+    var vto: VertexOutput;
+    vto.pos   = vec4f(pos * dscales.scale, 0.0, 1.0);
+    vto.local = corner;
+    vto.alpha = star.brightness * flicker;
+
+    return vto;
+}
+
+#include "color.wgsl" // color_u32_to_vec4f/vec3f, the palette's sRGB bytes decoded to linear
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    // round off the quad's corners with a soft falloff, so that it reads as a point rather than a square
+    var color = color_u32_to_vec4f(info.color);
+    color.a *= vto.alpha * smoothstep(1.0, 0.0, length(vto.local));
+    return color;
+}
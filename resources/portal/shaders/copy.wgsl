@@ -0,0 +1,11 @@
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>; // the post-processed scene
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+// Drawn with `FullscreenQuadPipeline` into the runner's multisampled frame, every sample of a pixel gets its texel
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return textureSampleLevel(src_tex_2d, tex_sampler, uv, 0.0);
+}
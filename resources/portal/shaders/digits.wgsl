@@ -1,3 +1,5 @@
+#include "digits.wgsl"
+
 struct VertexOutput {
     @builtin(position) pos: vec4f,
     @location(0)        uv: vec2f
@@ -12,10 +14,11 @@ struct StateData {
     digits:  u32
 }
 
-@group(0) @binding(0)
+// the digits sprite sheet, out of the scene's textures
+@group(0) @binding(2)
 var tex_2d: texture_2d<f32>;
 
-@group(0) @binding(1)
+@group(0) @binding(3)
 var tex_sampler: sampler;
 
 @group(1) @binding(0)
@@ -58,10 +61,7 @@ fn vs_main(
     var digits = array<u32, 2>((state.digits >> 16u) & 0xFFFFu, state.digits & 0xFFFFu);
 
     vto.pos = transform.matrix * (vec4f(pos, 1.0) * mult);
-    vto.uv  = vec2f(
-        mirror_odd(uv.x, InstanceIndex) * 0.1 + f32(digits[InstanceIndex % 2u]) * 0.1,
-        uv.y
-    );
+    vto.uv  = digit_cell_uv(vec2f(mirror_odd(uv.x, InstanceIndex), uv.y), digits[InstanceIndex % 2u]);
 
     return vto;
 }
@@ -0,0 +1,85 @@
+// Lightmapped mesh with a tiling normal map on top, used for the platform's stone surface.
+// The lightmap has the lighting baked already, the normal map only adds the small scale relief:
+// the lightmap is scaled by how much the perturbed normal faces the light compared to the geometric normal.
+
+struct VertexOutput {
+    @builtin(position) pos:       vec4f,
+    @location(0)        uv:        vec2f,
+    @location(1)        normal:    vec3f,
+    @location(2)        tangent:   vec3f,
+    @location(3)        bitangent: vec3f
+}
+
+struct MatrixData {
+    matrix: mat4x4<f32>
+}
+
+struct StateData {
+    rotated: u32,
+    _unused: u32
+}
+
+// Out of the scene's textures: the platform's lightmap has a layer per side, day and night
+@group(0) @binding(1)
+var lightmaps: texture_2d_array<f32>;
+
+@group(0) @binding(3)
+var tex_sampler: sampler;
+
+@group(1) @binding(0)
+var<uniform> transform : MatrixData;
+
+@group(0) @binding(9)
+var normal_tex_2d: texture_2d<f32>; // tangent space, along +u and +v
+
+@group(0) @binding(8)
+var normal_sampler: sampler; // repeating
+
+var<push_constant> state: StateData;
+
+const NORMAL_MAP_TILING: f32 = 24.0;
+const LIGHT_DIRECTION:   vec3f = vec3f(0.32, -0.48, 0.82); // towards the light, roughly where the sun is
+const RELIEF_RANGE:      vec2f = vec2f(0.55, 1.35); // limits of the lightmap's scaling
+
+@vertex
+fn vs_main(
+    @location(0)     pos: vec3<f32>,
+    @location(1)  normal: vec3<f32>,
+    @location(2)      uv: vec2<f32>,
+    @location(3) tangent: vec4<f32>
+) -> VertexOutput {
+    var vto: VertexOutput;
+
+    var mult: vec4<f32>;
+    if bool(state.rotated) {
+        mult = vec4f(-1.0, -1.0, 1.0, 1.0);
+    } else {
+        mult = vec4f(1.0, 1.0, 1.0, 1.0);
+    }
+
+    // the 180 deg rotation applies to the tangent space as well
+    let n = normal * mult.xyz;
+    let t = tangent.xyz * mult.xyz;
+
+    vto.pos       = transform.matrix * (vec4f(pos, 1.0) * mult);
+    vto.uv        = uv;
+    vto.normal    = n;
+    vto.tangent   = t;
+    vto.bitangent = cross(n, t) * tangent.w;
+
+    return vto;
+}
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let lightmap = textureSample(lightmaps, tex_sampler, vto.uv, state.rotated);
+
+    let tbn = mat3x3f(normalize(vto.tangent), normalize(vto.bitangent), normalize(vto.normal));
+    let sampled = textureSample(normal_tex_2d, normal_sampler, vto.uv * NORMAL_MAP_TILING).xyz * 2.0 - 1.0;
+    let normal  = normalize(tbn * sampled);
+
+    let light  = normalize(LIGHT_DIRECTION);
+    let relief = max(dot(normal, light), 0.0) / max(dot(tbn[2], light), 0.25);
+
+    return vec4f(lightmap.rgb * clamp(relief, RELIEF_RANGE.x, RELIEF_RANGE.y), lightmap.a);
+}
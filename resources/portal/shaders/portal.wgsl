@@ -12,10 +12,11 @@ struct StateData {
     _unused: u32
 }
 
-@group(0) @binding(0)
+// the other world, rendered in the previous pass. Group 0 is the scene's textures, none of which the portal needs
+@group(2) @binding(0)
 var tex_2d: texture_2d<f32>;
 
-@group(0) @binding(1)
+@group(2) @binding(1)
 var tex_sampler: sampler;
 
 @group(1) @binding(0)
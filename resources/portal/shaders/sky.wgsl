@@ -0,0 +1,15 @@
+// Vertical gradient behind the "other world", drawn with the library's fullscreen quad (`FullscreenQuadPipeline`)
+
+struct SkyColors {
+    horizon: vec4f, // at the bottom of the screen
+    zenith:  vec4f  // at the top
+}
+
+var<push_constant> colors: SkyColors;
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    // the horizon's glow reaches well up into the sky, the zenith's color takes over near the top
+    let height = 1.0 - uv.y;
+    return mix(colors.horizon, colors.zenith, height * height);
+}
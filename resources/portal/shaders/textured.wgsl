@@ -1,6 +1,15 @@
 struct VertexOutput {
     @builtin(position) pos: vec4f,
-    @location(0)        uv: vec2f
+    @location(0)        uv: vec2f,
+    @location(1)   world_z: f32
+}
+
+struct TerrainOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f,
+    @location(1)   world_z: f32,
+    @location(2) world_xy: vec2f,
+    @location(3)  weights: vec4f
 }
 
 struct MatrixData {
@@ -8,21 +17,98 @@ struct MatrixData {
 }
 
 struct StateData {
-    rotated: u32,
-    _unused: u32
+    rotated:        u32,
+    mirror_opacity: f32 // how much of the reflection shows on the ground, 0 => no mirror
 }
 
+// The scene's textures are shared by all of its shaders, only the ones used here are declared.
+// The terrain's lightmap has a layer per side, day and night, the sun and the moon are mapped on it too
 @group(0) @binding(0)
-var tex_2d: texture_2d<f32>;
+var lightmaps: texture_2d_array<f32>;
 
-@group(0) @binding(1)
+@group(0) @binding(3)
 var tex_sampler: sampler;
 
 @group(1) @binding(0)
 var<uniform> transform : MatrixData;
 
+// The terrain's materials (grass, rock, sand, snow), tiling, blended by the per-vertex weights
+@group(0) @binding(4)
+var grass_tex: texture_2d<f32>;
+
+@group(0) @binding(5)
+var rock_tex: texture_2d<f32>;
+
+@group(0) @binding(6)
+var sand_tex: texture_2d<f32>;
+
+@group(0) @binding(7)
+var snow_tex: texture_2d<f32>;
+
+@group(0) @binding(8)
+var material_sampler: sampler;
+
+// The night scene mirrored at the ground, rendered from the same camera (screen sized)
+@group(2) @binding(0)
+var reflection_tex: texture_2d<f32>;
+
+@group(2) @binding(1)
+var reflection_sampler: sampler;
+
 var<push_constant> state: StateData;
 
+// The terrain's flat ground lies at z = 0, the tolerance catches the interpolated fragments on it
+const GROUND_LEVEL:     f32 = 0.0;
+const GROUND_TOLERANCE: f32 = 0.05;
+
+// World units covered by one repetition of the material textures
+const MATERIAL_TILE_SIZE: f32 = 6.0;
+
+// The lightmap of the side being drawn, the night side is the rotated one
+fn sample_lightmap(uv: vec2f) -> vec4f {
+    return textureSample(lightmaps, tex_sampler, uv, state.rotated);
+}
+
+fn rotation_mult() -> vec4<f32> {
+    if bool(state.rotated) {
+        return vec4f(-1.0, -1.0, 1.0, 1.0);
+    }
+
+    return vec4f(1.0, 1.0, 1.0, 1.0);
+}
+
+// The ground mirrors the reflection texture, if there's one
+fn with_reflection(color: vec4f, frag_pos: vec4f, world_z: f32) -> vec4f {
+    // sampled outside the branch, textureSample needs uniform control flow
+    let screen_uv  = frag_pos.xy / vec2f(textureDimensions(reflection_tex));
+    let reflection = textureSample(reflection_tex, reflection_sampler, screen_uv);
+
+    if world_z < GROUND_LEVEL + GROUND_TOLERANCE {
+        return vec4f(mix(color.rgb, reflection.rgb, state.mirror_opacity), color.a);
+    }
+
+    return color;
+}
+
+// The lightmap modulated by the blended materials. They average to mid-gray, hence the doubling,
+// so they add detail and a tint without changing the baked lighting's overall brightness
+fn splatted(uv: vec2f, world_xy: vec2f, weights: vec4f) -> vec4f {
+    let lightmap = sample_lightmap(uv);
+
+    let tiled = world_xy / MATERIAL_TILE_SIZE;
+    let materials = mat4x3f(
+        textureSample(grass_tex, material_sampler, tiled).rgb,
+        textureSample(rock_tex,  material_sampler, tiled).rgb,
+        textureSample(sand_tex,  material_sampler, tiled).rgb,
+        textureSample(snow_tex,  material_sampler, tiled).rgb
+    );
+
+    // normalized, the weights might not add up to exactly 1 in the file
+    let blended = materials * (weights / max(dot(weights, vec4f(1.0)), 1e-4));
+
+    return vec4f(lightmap.rgb * blended * 2.0, lightmap.a);
+}
+
 @vertex
 fn vs_main(
     @location(0) pos: vec3<f32>,
@@ -30,20 +116,62 @@ fn vs_main(
 ) -> VertexOutput {
     var vto: VertexOutput;
 
-    var mult: vec4<f32>;
-    if bool(state.rotated) {
-        mult = vec4f(-1.0, -1.0, 1.0, 1.0);
-    } else {
-        mult = vec4f(1.0, 1.0, 1.0, 1.0);
+    vto.pos     = transform.matrix * (vec4f(pos, 1.0) * rotation_mult());
+    vto.uv      = uv;
+    vto.world_z = pos.z;
+
+    return vto;
+}
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let color = sample_lightmap(vto.uv);
+
+    return with_reflection(color, vto.pos, vto.world_z);
+}
+
+// For rendering the reflection (the transform mirrors the scene), the ground itself would cover the mirrored scenery
+@fragment
+fn fs_reflected(vto: VertexOutput) -> @location(0) vec4f {
+    let color = sample_lightmap(vto.uv);
+
+    if vto.world_z < GROUND_LEVEL + GROUND_TOLERANCE {
+        discard;
     }
 
-    vto.pos = transform.matrix * (vec4f(pos, 1.0) * mult);
-    vto.uv  = uv;
+    return color;
+}
+
+// The terrain, with texture splatting. The materials stick to the mesh, the rotation doesn't move them
+@vertex
+fn vs_terrain(
+    @location(0)     pos: vec3<f32>,
+    @location(1)      uv: vec2<f32>,
+    @location(2) weights: vec4<f32>
+) -> TerrainOutput {
+    var vto: TerrainOutput;
+
+    vto.pos      = transform.matrix * (vec4f(pos, 1.0) * rotation_mult());
+    vto.uv       = uv;
+    vto.world_z  = pos.z;
+    vto.world_xy = pos.xy;
+    vto.weights  = weights;
 
     return vto;
 }
 
 @fragment
-fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
-    return textureSample(tex_2d, tex_sampler, vto.uv);
-}
\ No newline at end of file
+fn fs_terrain(vto: TerrainOutput) -> @location(0) vec4f {
+    return with_reflection(splatted(vto.uv, vto.world_xy, vto.weights), vto.pos, vto.world_z);
+}
+
+@fragment
+fn fs_terrain_reflected(vto: TerrainOutput) -> @location(0) vec4f {
+    let color = splatted(vto.uv, vto.world_xy, vto.weights);
+
+    if vto.world_z < GROUND_LEVEL + GROUND_TOLERANCE {
+        discard;
+    }
+
+    return color;
+}
@@ -0,0 +1,74 @@
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f
+}
+
+struct TiltShiftInfo {
+    focus_center: f32, // vertical center of the sharp band, in UV space
+    focus_width:  f32, // half height of the sharp band, in UV space
+    max_radius:   f32, // blur radius at the top and bottom edges, in pixels
+    saturation:   f32  // miniatures tend to look a bit over-saturated
+}
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>; // the fully rendered scene
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+var<push_constant> info: TiltShiftInfo;
+
+@vertex
+fn vs_main(@builtin(vertex_index) VertexIndex: u32) -> VertexOutput {
+    // Simple full screen quad
+    var coords = array<vec2f, 4>(
+        vec2f(-1.0,  1.0),
+        vec2f( 1.0,  1.0),
+        vec2f(-1.0, -1.0),
+        vec2f( 1.0, -1.0)
+    );
+
+    var uvs = array<vec2f, 4>(
+        vec2f(0.0, 0.0),
+        vec2f(1.0, 0.0),
+        vec2f(0.0, 1.0),
+        vec2f(1.0, 1.0)
+    );
+
+    var vto: VertexOutput;
+    vto.pos = vec4f(coords[VertexIndex], 0.0, 1.0);
+    vto.uv  = uvs[VertexIndex];
+
+    return vto;
+}
+
+const TAP_COUNT: u32 = 24u;
+const GOLDEN_ANGLE: f32 = 2.39996323;
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    // Blur radius grows with the distance from the sharp band, like the shallow depth of field of a macro lens.
+    // Since it's mostly the ground plane that's visible, screen height is a decent stand-in for depth.
+    let distance = max(abs(vto.uv.y - info.focus_center) - info.focus_width, 0.0);
+    let radius   = info.max_radius * smoothstep(0.0, 0.5 - info.focus_width, distance);
+
+    var color = textureSample(src_tex_2d, tex_sampler, vto.uv).rgb;
+
+    // Disk blur, taps are scattered along a golden angle spiral (Vogel's method) for an even coverage
+    if radius > 0.5 {
+        let texel = 1.0 / vec2f(textureDimensions(src_tex_2d));
+        var sum = color;
+
+        for(var i = 0u; i < TAP_COUNT; i++) {
+            let r     = sqrt((f32(i) + 0.5) / f32(TAP_COUNT)) * radius;
+            let theta = f32(i) * GOLDEN_ANGLE;
+            let tap   = vto.uv + vec2f(cos(theta), sin(theta)) * r * texel;
+            sum += textureSampleLevel(src_tex_2d, tex_sampler, tap, 0.0).rgb;
+        }
+
+        color = sum / f32(TAP_COUNT + 1u);
+    }
+
+    let luma = dot(color, vec3f(0.2126, 0.7152, 0.0722));
+    return vec4f(mix(vec3f(luma), color, info.saturation), 1.0);
+}
@@ -0,0 +1,83 @@
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)      grid: vec2f // in cells, from the top-left corner of the grid
+}
+
+struct DrawspaceScales {
+    scale:      vec2<f32>,
+    extent:     vec2<f32>,
+    resolution: vec2<f32>,
+    density:    f32
+}
+
+struct WordParams {
+    highlight: u32, // the lit letters' color
+    dim:       u32, // the others'
+    timestamp: f32  // seconds within the minute, as `ClockData::timestamp`, drives the pulse
+}
+
+var<push_constant> params: WordParams;
+
+@group(0) @binding(0)
+var<uniform> dscales: DrawspaceScales;
+
+// 10 rows of 11, row by row, see `GRID` and `lit_flags` in clocks/wordclock.rs
+@group(1) @binding(0)
+var<storage, read> letters: array<u32>; // Unicode codepoints
+
+@group(1) @binding(1)
+var<storage, read> lit: array<u32>;     // 0 dim, 1 lit
+
+const GRID_SIZE: vec2u = vec2u(11u, 10u);
+
+// The sprite sheet's cells, 'A' to 'Z' row by row
+const SHEET_SIZE: vec2u = vec2u(8u, 4u);
+
+// How far the lit letters' brightness swings, and how long a pulse takes (in seconds, a minute holds a whole number)
+const PULSE_DEPTH:  f32 = 0.15;
+const PULSE_PERIOD: f32 = 3.0;
+
+const PI: f32 = 3.141592653589793238;
+
+@vertex
+fn vs_main(@builtin(vertex_index) VertexIndex: u32) -> VertexOutput {
+    // a triangle strip over the whole grid, a cell is a drawing unit wide and high
+    let corner = vec2f(f32(VertexIndex & 1u), f32(VertexIndex >> 1u));
+    let grid   = corner * vec2f(GRID_SIZE);
+    let pos    = vec2f(grid.x, -grid.y) + vec2f(-0.5, 0.5) * vec2f(GRID_SIZE);
+
+    var vto: VertexOutput;
+    vto.pos  = vec4f(pos * dscales.scale, 0.0, 1.0);
+    vto.grid = grid;
+
+    return vto;
+}
+
+#include "color.wgsl" // color_u32_to_vec4f, the palette's sRGB bytes decoded to linear
+
+@group(1) @binding(2)
+var tex_2d: texture_2d<f32>;
+
+@group(1) @binding(3)
+var tex_sampler: sampler;
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    let cell  = min(vec2u(vto.grid), GRID_SIZE - 1u);
+    let index = cell.y * GRID_SIZE.x + cell.x;
+
+    // anything but 'A' to 'Z' is left blank, past the sheet's last cell
+    let glyph = min(letters[index] - 65u, SHEET_SIZE.x * SHEET_SIZE.y - 1u);
+    let sheet = vec2f(f32(glyph % SHEET_SIZE.x), f32(glyph / SHEET_SIZE.x));
+    // half a texel in from the cell's edges, so that the neighbouring letters don't bleed in
+    let inset = 0.5 * vec2f(SHEET_SIZE) / vec2f(textureDimensions(tex_2d));
+    let uv    = (sheet + clamp(fract(vto.grid), inset, 1.0 - inset)) / vec2f(SHEET_SIZE);
+
+    // a single mip level, the jumps of `uv` from one letter to the next don't pick a smaller one at the cells' edges
+    let coverage = textureSample(tex_2d, tex_sampler, uv).a;
+
+    let pulse = 1.0 - PULSE_DEPTH * (0.5 + 0.5 * cos(params.timestamp * 2.0 * PI / PULSE_PERIOD));
+    let color = select(color_u32_to_vec4f(params.dim).rgb, color_u32_to_vec4f(params.highlight).rgb * pulse, lit[index] != 0u);
+
+    return vec4f(color, coverage);
+}
@@ -0,0 +1,209 @@
+//! Aspect ratio lock (`RunOptions::aspect_ratio`) for clocks that only look right in one shape,
+//! e.g. the polar clock in a square.
+//!
+//! winit has no way of keeping a window's aspect ratio while it's resized. The window is asked for a size with the ratio
+//! when it opens, and kept from shrinking below `MIN_HEIGHT`, but past that it can take any shape.
+//! So the clock always renders at the largest size with the ratio that fits into the window (`snap_to_aspect`),
+//! into an intermediate texture that `LetterboxPass` copies onto the middle of the surface, with bars around it
+//! in the clock's color for them (`ExecDraw::letterbox_color`), black by default.
+
+use crate::{cast_struct_to_u8_slice, RenderTexture, SingleUniformBuffer};
+use crate::viewport::{RenderPassViewportExt, ViewportRect};
+
+/// Height the window can't be shrunk below, in physical pixels, the width follows the ratio
+pub const MIN_HEIGHT: u32 = 120;
+
+/// The largest size with the given aspect ratio (width / height) that fits into `size`, at least 1x1
+pub fn snap_to_aspect(size: (u32, u32), aspect: f32) -> (u32, u32) {
+    let (width, height) = (size.0.max(1), size.1.max(1));
+
+    if width as f32 / height as f32 > aspect {
+        (((height as f32 * aspect).round() as u32).clamp(1, width), height)
+    } else {
+        (width, ((width as f32 / aspect).round() as u32).clamp(1, height))
+    }
+}
+
+/// Where `content` goes to be centered within `size`, its top-left corner
+pub fn letterbox_offset(size: (u32, u32), content: (u32, u32)) -> (u32, u32) {
+    (size.0.saturating_sub(content.0) / 2, size.1.saturating_sub(content.1) / 2)
+}
+
+/// `None` for ratios that can't be kept (zero, negative, NaN or infinite), with a warning
+pub(crate) fn validate(aspect: f32) -> Option<f32> {
+    if aspect.is_finite() && aspect > 0.0 {
+        Some(aspect)
+    } else {
+        log::warn!("Ignoring the invalid aspect ratio {}", aspect);
+        None
+    }
+}
+
+/// What the platform can do about the window's shape: a minimum size with the ratio, and a size with it to start with.
+/// Either may be ignored, e.g. by tiling window managers, the letterbox takes care of it then
+pub(crate) fn constrain_window(window: &winit::window::Window, aspect: f32) {
+    let min_size = winit::dpi::PhysicalSize::new((MIN_HEIGHT as f32 * aspect).round() as u32, MIN_HEIGHT);
+    window.set_min_inner_size(Some(min_size));
+
+    let size = window.inner_size();
+    let (width, height) = snap_to_aspect((size.width, size.height), aspect);
+
+    if (width, height) != (size.width, size.height) {
+        log::debug!("Asking for a {}x{} window, to match the {} aspect ratio", width, height, aspect);
+        let _ = window.request_inner_size(winit::dpi::PhysicalSize::new(width, height));
+    }
+}
+
+/// Copies the clock's frame, rendered at the snapped size, onto the middle of the surface
+pub(crate) struct LetterboxPass {
+    pipeline:         wgpu::RenderPipeline,
+    bindgroup_layout: wgpu::BindGroupLayout,
+    uniform:          SingleUniformBuffer,
+    target:           RenderTexture,
+    bindgroup:        wgpu::BindGroup,
+    format:           wgpu::TextureFormat, // of the surface, the clock's target has the same
+    aspect:           f32,
+    size:             (u32, u32), // of the surface
+    clear:            wgpu::Color // of the bars unless the clock has a color for them, transparent on transparent surfaces
+}
+
+impl LetterboxPass {
+    /// Draws onto textures of the surface's format, the clock gets a target of the snapped size in the same format
+    pub fn new(config: &wgpu::SurfaceConfiguration, aspect: f32, device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let uniform = SingleUniformBuffer::new::<[u32; 2]>(device, wgpu::ShaderStages::FRAGMENT);
+
+        let bindgroup_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Letterbox"),
+            entries: &[
+                RenderTexture::default_layout_entry(0),
+                SingleUniformBuffer::default_layout_entry(1, &uniform)
+            ]
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                Some("Letterbox"),
+            bind_group_layouts:   &[ &bindgroup_layout ],
+            push_constant_ranges: &[]
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Letterbox"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("aspect.wgsl").into())
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label:         Some("Letterbox"),
+            layout:        Some(&pipeline_layout),
+            depth_stencil: None,
+            multisample:   wgpu::MultisampleState::default(),
+            multiview:     None,
+            vertex: wgpu::VertexState {
+                module:      &shader,
+                entry_point: "vs_main",
+                buffers:     &[]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module:      &shader,
+                entry_point: "fs_main",
+                targets:     &[ Some(config.format.into()) ]
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology:     wgpu::PrimitiveTopology::TriangleStrip,
+                cull_mode:    None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..Default::default()
+            }
+        });
+
+        let clear = match config.alpha_mode {
+            wgpu::CompositeAlphaMode::PreMultiplied | wgpu::CompositeAlphaMode::PostMultiplied => wgpu::Color::TRANSPARENT,
+            _ => wgpu::Color::BLACK
+        };
+
+        let size = (config.width, config.height);
+        let (target, bindgroup) = Self::create_target(snap_to_aspect(size, aspect), config.format, &bindgroup_layout, &uniform, device);
+
+        let letterbox = Self { pipeline, bindgroup_layout, uniform, target, bindgroup, format: config.format, aspect, size, clear };
+        letterbox.write_offset(queue);
+
+        letterbox
+    }
+
+    fn create_target(
+        size:    (u32, u32),
+        format:  wgpu::TextureFormat,
+        layout:  &wgpu::BindGroupLayout,
+        uniform: &SingleUniformBuffer,
+        device:  &wgpu::Device
+    ) -> (RenderTexture, wgpu::BindGroup) {
+        let target = RenderTexture::new(size, format, true, device);
+
+        let bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   Some("Letterbox"),
+            layout,
+            entries: &[ target.get_entry(0), uniform.get_entry(1) ]
+        });
+
+        (target, bindgroup)
+    }
+
+    fn write_offset(self: &Self, queue: &wgpu::Queue) {
+        let (x, y) = letterbox_offset(self.size, self.content_size());
+        queue.write_buffer(&self.uniform.buffer, 0, cast_struct_to_u8_slice(&[x, y]));
+    }
+
+    /// Snaps the clock's size to the new surface size, the clock is resized to `content_size` then
+    pub fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.size = (width, height);
+
+        let content = snap_to_aspect(self.size, self.aspect);
+        if content != self.content_size() {
+            (self.target, self.bindgroup) = Self::create_target(content, self.format, &self.bindgroup_layout, &self.uniform, device);
+        }
+
+        self.write_offset(queue);
+    }
+
+    /// The size the clock renders at
+    pub fn content_size(self: &Self) -> (u32, u32) {
+        (self.target.width, self.target.height)
+    }
+
+    /// What the clock draws into instead of the surface
+    pub fn target(self: &Self) -> &wgpu::TextureView {
+        &self.target.view
+    }
+
+    /// Copy the clock's frame onto the middle of `texview`, clearing the bars around it to `bars` (or the default)
+    pub fn apply(self: &Self, texview: &wgpu::TextureView, bars: Option<wgpu::Color>, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Letterbox") });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    Some("Letterbox"),
+                depth_stencil_attachment: None,
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                    view: texview,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load:  wgpu::LoadOp::Clear(bars.unwrap_or(self.clear)),
+                        store: wgpu::StoreOp::Store
+                    }
+                })]
+            });
+
+            let (x, y) = letterbox_offset(self.size, self.content_size());
+            let (width, height) = self.content_size();
+
+            if rpass.set_viewport_rect(ViewportRect::new(x, y, width, height), self.size) {
+                rpass.set_pipeline(&self.pipeline);
+                rpass.set_bind_group(0, &self.bindgroup, &[]);
+                rpass.draw(0..4, 0..1);
+            }
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
@@ -0,0 +1,26 @@
+// Aspect ratio lock: the clock's frame copied onto the middle of the surface, the bars around it are cleared beforehand.
+// Drawn into a viewport of the frame's size, so the texels are loaded 1:1 without a sampler.
+
+@group(0) @binding(0)
+var frame: texture_2d<f32>;
+
+@group(0) @binding(1)
+var<uniform> offset: vec2<u32>; // of the frame within the surface, in pixels
+
+@vertex
+fn vs_main(@builtin(vertex_index) VertexIndex: u32) -> @builtin(position) vec4f {
+    var corners = array<vec2f, 4>(
+        vec2f(-1.0,  1.0),
+        vec2f( 1.0,  1.0),
+        vec2f(-1.0, -1.0),
+        vec2f( 1.0, -1.0)
+    );
+
+    return vec4f(corners[VertexIndex], 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4f) -> @location(0) vec4f {
+    // the position is the surface's, not the viewport's
+    return textureLoad(frame, vec2i(pos.xy) - vec2i(offset), 0);
+}
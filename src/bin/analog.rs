@@ -0,0 +1,402 @@
+#![cfg_attr(
+    all(
+        target_os = "windows",
+        not(feature = "console"),
+    ),
+    windows_subsystem = "windows"
+)]
+use wgpu::util::DeviceExt;
+use clockutils::{
+    run_with_options, cast_slice_to_u8_slice, get_resource_folder_for, load_shader, fatal_error,
+    u32_col_to_wgpu_col_linear,
+    ExecDraw, FrameInfo, Drawspace2D, FitMode, ResourceTexture, RenderTexture, BasicFilteringSampler,
+    FullscreenQuadPipeline, Vtx2UV,
+    cli::Cli, logging, time,
+    params::SmallParams,
+    font::BitmapFontRenderer,
+    keys::{self, KeyBinding},
+    target::{requested_sample_count, ColorTarget, PipelineBuilder, TargetConfig, TargetPipeline},
+    clocks::analog::{calc_hand_angles, cap_vertices, HandGeometry, HandShape, EXTENT, DIAL_RADIUS}
+};
+
+/// Properties of a hand (or the cap) drawn, see hand.wgsl
+#[repr(C, align(8))]
+struct HandInfo {
+    offset: glam::Vec2, // of the whole hand, moves the shadows away from the hands
+    angle:  f32,        // clockwise from 12 o'clock, in radians
+    color:  u32
+}
+
+/// A shape's three hands, bound as the vertex buffer while the shape is shown
+struct HandBuffer {
+    buffer:   wgpu::Buffer,
+    geometry: HandGeometry
+}
+
+/// Matched on in `onkey`, and listed by the help panel
+const KEYBINDINGS: &[KeyBinding] = &[
+    ("Space", "Next hand shape: baton, spade, lollipop"),
+    ("H",     "Show/hide this help")
+];
+
+const BACKGROUND_COLOR: u32 = 0x39414E_FF;
+const HOURS_COLOR:      u32 = 0x1D1D22_FF;
+const MINUTES_COLOR:    u32 = 0x1D1D22_FF;
+const SECONDS_COLOR:    u32 = 0xC4161C_FF;
+
+/// Where the shadows fall, in drawing units: the light comes from the upper left
+const SHADOW_OFFSET: glam::Vec2 = glam::Vec2::new(0.3, -0.45);
+
+/// The shadows are blurred anyway, their texture is this many times smaller than the window along either axis
+const SHADOW_DOWNSCALE: u32 = 2;
+const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+
+/// Draws per submission: the hands and the cap, their shadows first
+const DRAWS_PER_FRAME: u32 = 8;
+
+fn shadow_size(width: u32, height: u32) -> (u32, u32) {
+    ((width / SHADOW_DOWNSCALE).max(1), (height / SHADOW_DOWNSCALE).max(1))
+}
+
+/// The dial texture's quad as two triangles, as large as the dial
+fn dial_vertices() -> [Vtx2UV; 6] {
+    let corner = |x: f32, y: f32| Vtx2UV {
+        pos: glam::Vec2::new(x, y) * DIAL_RADIUS,
+        uv:  glam::Vec2::new(x + 1.0, 1.0 - y) * 0.5
+    };
+
+    let (bl, br, tr, tl) = (corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0));
+    [bl, br, tr, bl, tr, tl]
+}
+
+fn begin_pass<'a>(
+    encoder:    &'a mut wgpu::CommandEncoder,
+    attachment: wgpu::RenderPassColorAttachment<'a>
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label:                    None,
+        depth_stencil_attachment: None,
+        timestamp_writes:         None,
+        occlusion_query_set:      None,
+        color_attachments:        &[Some(attachment)]
+    })
+}
+
+struct AnalogClock {
+    dial_pipeline:   TargetPipeline,
+    hand_pipeline:   TargetPipeline,
+    shadow_pipeline: TargetPipeline, // the hands' coverage, into the shadow texture
+    blur_pipeline:   FullscreenQuadPipeline, // the blurred shadow texture, laid over the dial
+
+    hand_params: SmallParams<HandInfo>,
+
+    drawspace:       Drawspace2D,
+    dial_buffer:     wgpu::Buffer,
+    dial_bind_group: wgpu::BindGroup,
+    hand_buffers:    Vec<HandBuffer>, // one per shape, in the order of `HandShape::ALL`
+    cap_buffer:      wgpu::Buffer,
+
+    target_config:     TargetConfig, // multisampled as requested (--msaa), as far as supported
+    color_target:      ColorTarget,
+    shadow_config:     TargetConfig,
+    shadow:            RenderTexture, // a fraction of the window's size, see `SHADOW_DOWNSCALE`
+    shadow_layout:     wgpu::BindGroupLayout,
+    shadow_bind_group: wgpu::BindGroup, // remade along with the shadow texture
+    sampler:           BasicFilteringSampler,
+
+    shape:     HandShape,
+    show_help: bool,
+    help:      BitmapFontRenderer
+}
+
+impl AnalogClock {
+    fn shadow_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, shadow: &RenderTexture, sampler: &BasicFilteringSampler) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   Some("Hand shadows"),
+            layout,
+            entries: &[ shadow.get_entry(0), sampler.get_entry(1) ]
+        })
+    }
+
+    /// The hours', minutes' and seconds' hands of the current shape, then the cap over them.
+    /// The pipeline (either of the hands' ones) must be set already
+    fn draw_hands<'a>(self: &'a Self, rpass: &mut wgpu::RenderPass<'a>, queue: &wgpu::Queue, offset: glam::Vec2) {
+        let angles = calc_hand_angles(&time::now());
+        let hands  = &self.hand_buffers[HandShape::ALL.iter().position(|shape| *shape == self.shape).unwrap()];
+
+        rpass.set_bind_group(0, self.drawspace.bind_group(), &[]);
+        rpass.set_vertex_buffer(0, hands.buffer.slice(..));
+
+        let geometry = &hands.geometry;
+        for (range, angle, color) in [
+            (&geometry.hours,   angles.hours,   HOURS_COLOR),
+            (&geometry.minutes, angles.minutes, MINUTES_COLOR),
+            (&geometry.seconds, angles.seconds, SECONDS_COLOR)
+        ] {
+            self.hand_params.set(rpass, queue, &HandInfo { offset, angle, color });
+            rpass.draw(range.clone(), 0..1);
+        }
+
+        rpass.set_vertex_buffer(0, self.cap_buffer.slice(..));
+        self.hand_params.set(rpass, queue, &HandInfo { offset, angle: 0.0, color: SECONDS_COLOR });
+        rpass.draw(0..6, 0..1);
+    }
+}
+
+impl ExecDraw for AnalogClock {
+    fn setup(
+        config:   &wgpu::SurfaceConfiguration,
+        adapter:  &wgpu::Adapter,
+        device:   &wgpu::Device,
+        queue:    &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> {
+        let resources = get_resource_folder_for("analog").map_err(|error| error.to_string())?;
+
+        // smooths the hands' edges, the disks are smoothed by their shader
+        let target_config = TargetConfig::new(config.format, None).with_supported_sample_count(requested_sample_count(), adapter, device);
+        let color_target  = ColorTarget::new((config.width, config.height), &target_config, device);
+
+        let drawspace = Drawspace2D::new(device, glam::Vec2::new(EXTENT, EXTENT), FitMode::Contain);
+        let sampler   = BasicFilteringSampler::new(device);
+
+        ////////////////////////////////////////
+
+        let dial = ResourceTexture::new(
+            resources.join("textures/dial.png").as_path().to_str().unwrap(),
+            device,
+            queue
+        )?;
+
+        let dial_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Dial"),
+            entries: &[ ResourceTexture::default_layout_entry(0), BasicFilteringSampler::default_layout_entry(1) ]
+        });
+
+        let dial_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   Some("Dial"),
+            layout:  &dial_layout,
+            entries: &[ dial.get_entry(0), sampler.get_entry(1) ]
+        });
+
+        let dial_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ drawspace.layout(), &dial_layout ],
+            push_constant_ranges: &[]
+        });
+
+        let dial_shader = load_shader(device, &resources.join("shaders/dial.wgsl"))?;
+
+        let dial_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label:    Some("Dial"),
+            contents: cast_slice_to_u8_slice(&dial_vertices()),
+            usage:    wgpu::BufferUsages::VERTEX
+        });
+
+        ////////////////////////////////////////
+
+        // angle, offset and color of each hand sent via push constants (or right after the drawspace scales, without them)
+        let hand_params = SmallParams::<HandInfo>::new(device, wgpu::ShaderStages::VERTEX_FRAGMENT, 1, DRAWS_PER_FRAME);
+
+        let hand_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &[ drawspace.layout() ], hand_params.layout_entries().as_slice() ].concat(),
+            push_constant_ranges: &hand_params.push_constant_ranges()
+        });
+
+        let hand_shader = hand_params.load_shader(device, &resources.join("shaders/hand.wgsl"))?;
+
+        // every shape's vertices are uploaded once, switching shapes binds another buffer
+        let hand_buffers = HandShape::ALL.iter().map(|shape| {
+            let geometry = HandGeometry::new(*shape);
+            let buffer   = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label:    Some(shape.name()),
+                contents: cast_slice_to_u8_slice(geometry.vertices.as_slice()),
+                usage:    wgpu::BufferUsages::VERTEX
+            });
+
+            HandBuffer { buffer, geometry }
+        }).collect();
+
+        let cap_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label:    Some("Cap"),
+            contents: cast_slice_to_u8_slice(cap_vertices().as_slice()),
+            usage:    wgpu::BufferUsages::VERTEX
+        });
+
+        ////////////////////////////////////////
+
+        // The alpha accumulates the same way as the colors, which keeps the result premultiplied
+        let alpha_blending = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation:  wgpu::BlendOperation::Add
+            },
+            alpha: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation:  wgpu::BlendOperation::Add
+            }
+        };
+
+        // the hands' shadows add up in their texture, saturating where they overlap
+        let additive_blending = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation:  wgpu::BlendOperation::Add
+            },
+            alpha: wgpu::BlendComponent::REPLACE
+        };
+
+        let vertex_buffers = [ Vtx2UV::wgpu_layout() ];
+
+        let dial_pipeline = PipelineBuilder::new(&target_config, &dial_pipeline_layout, &dial_shader)
+            .vertex("vs_main", &vertex_buffers)
+            .blend(alpha_blending)
+            .build(device);
+
+        let hand_pipeline = PipelineBuilder::new(&target_config, &hand_pipeline_layout, &hand_shader)
+            .vertex("vs_main", &vertex_buffers)
+            .blend(alpha_blending)
+            .build(device);
+
+        let shadow_config = TargetConfig::new(SHADOW_FORMAT, None);
+
+        let shadow_pipeline = PipelineBuilder::new(&shadow_config, &hand_pipeline_layout, &hand_shader)
+            .vertex("vs_main", &vertex_buffers)
+            .fragment("fs_shadow")
+            .blend(additive_blending)
+            .build(device);
+
+        ////////////////////////////////////////
+
+        let shadow = RenderTexture::new(shadow_size(config.width, config.height), SHADOW_FORMAT, true, device);
+
+        let shadow_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Hand shadows"),
+            entries: &[ RenderTexture::default_layout_entry(0), BasicFilteringSampler::default_layout_entry(1) ]
+        });
+
+        let shadow_bind_group = Self::shadow_bind_group(device, &shadow_layout, &shadow, &sampler);
+
+        let blur_shader = load_shader(device, &resources.join("shaders/shadow.wgsl"))?;
+
+        let blur_pipeline = FullscreenQuadPipeline::blended_for_target(
+            device, &blur_shader, "fs_main",
+            &[ &shadow_layout, drawspace.layout() ],
+            &[],
+            &target_config,
+            alpha_blending
+        );
+
+        Ok(Self {
+            dial_pipeline, hand_pipeline, shadow_pipeline, blur_pipeline,
+            hand_params,
+            drawspace, dial_buffer, dial_bind_group, hand_buffers, cap_buffer,
+            target_config, color_target,
+            shadow_config, shadow, shadow_layout, shadow_bind_group, sampler,
+            shape:     HandShape::Baton,
+            show_help: false,
+            help:      keys::help_panel(KEYBINDINGS, config, device)
+        })
+    }
+
+    fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
+        // rewrite the uniform buffer containing the drawspace scales since resolution was changed
+        self.drawspace.resize(queue, width, height);
+        self.help.resize(width, height);
+        self.color_target = ColorTarget::new((width, height), &self.target_config, device);
+
+        self.shadow = RenderTexture::new(shadow_size(width, height), SHADOW_FORMAT, true, device);
+        self.shadow_bind_group = Self::shadow_bind_group(device, &self.shadow_layout, &self.shadow, &self.sampler);
+    }
+
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        match keys::pressed(&event, KEYBINDINGS) {
+            Some("Space") => {
+                self.shape = self.shape.next();
+            },
+            Some("H") => {
+                self.show_help = !self.show_help;
+            },
+            _ => {}
+        }
+    }
+
+    fn title_status(self: &Self) -> Option<String> {
+        Some(self.shape.name().to_string())
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        // the hands' shadows, sharp still
+        {
+            let mut rpass = begin_pass(&mut encoder, wgpu::RenderPassColorAttachment {
+                view:           &self.shadow.view,
+                resolve_target: None,
+                ops:            wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: wgpu::StoreOp::Store }
+            });
+
+            self.shadow_pipeline.set(&mut rpass, &self.shadow_config);
+            self.draw_hands(&mut rpass, queue, SHADOW_OFFSET);
+        }
+
+        // the dial, the blurred shadows over it and the hands on top
+        {
+            let clear = u32_col_to_wgpu_col_linear(BACKGROUND_COLOR);
+            let mut rpass = begin_pass(&mut encoder, self.color_target.attachment(&self.target_config, texview, wgpu::LoadOp::Clear(clear)));
+
+            self.dial_pipeline.set(&mut rpass, &self.target_config);
+            rpass.set_bind_group(0, self.drawspace.bind_group(), &[]);
+            rpass.set_bind_group(1, &self.dial_bind_group, &[]);
+            rpass.set_vertex_buffer(0, self.dial_buffer.slice(..));
+            rpass.draw(0..6, 0..1);
+
+            self.blur_pipeline.draw(&mut rpass, &[ &self.shadow_bind_group, self.drawspace.bind_group() ], &[]);
+
+            self.hand_pipeline.set(&mut rpass, &self.target_config);
+            self.draw_hands(&mut rpass, queue, glam::Vec2::ZERO);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        if self.show_help {
+            self.help.draw(texview, device, queue);
+        }
+    }
+}
+
+fn main() {
+    logging::init("analog");
+
+    let (args, _app) = Cli::new("analog", "Analog clock: sweeping hands over a dial").parse();
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    if args.take_snapshot::<AnalogClock>((512, 512), None, 1) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Analog Clock", &format!("Failed to initialize the windowing system: {}", error)));
+    #[allow(unused_mut)]
+    let mut builder = winit::window::WindowBuilder::new();
+    let builder = builder
+        .with_inner_size(winit::dpi::LogicalSize { width: 512.0, height: 512.0 })
+        .with_title("Analog Clock");
+    let window = args.apply_to_window(builder, &event_loop)
+        .build(&event_loop)
+        .unwrap_or_else(|error| fatal_error("Analog Clock", &format!("Failed to create the window: {}", error)));
+
+    pollster::block_on(run_with_options::<AnalogClock>(
+        event_loop, window,
+        None, // push constants are used if available
+        args.run_options() // any shape, the drawspace keeps the dial round and centered
+    ));
+}
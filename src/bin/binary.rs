@@ -0,0 +1,392 @@
+#![cfg_attr(
+    all(
+        target_os = "windows",
+        not(feature = "console"),
+    ),
+    windows_subsystem = "windows"
+)]
+use chrono::Timelike;
+use clockutils::{
+    run_with_options, create_vertex_and_index_buffers, cast_slice_to_u8_slice, get_resource_folder_for, load_shader, fatal_error,
+    create_blur_table_bindgroup, u32_col_to_wgpu_col_linear,
+    ExecDraw, FrameInfo, InputState, RedrawPolicy, BasicFilteringSampler, FullscreenQuadPipeline,
+    RenderTexture, DrawspaceScales, Drawspace2D, FitMode, Vtx2ID,
+    cli::Cli, logging, time,
+    params::SmallParams,
+    font::BitmapFontRenderer,
+    keys::{self, KeyBinding},
+    palette::parse_hex_color,
+    target::{requested_sample_count, ColorTarget, PipelineBuilder, TargetConfig, TargetPipeline},
+    clocks::binary::{self, calculate_binary_data, led_vertices_and_indices, until_next_second, BinaryData, EXTENT}
+};
+
+/// Command line settings, handed over from `main` to `setup`
+#[derive(Clone, Copy, Debug)]
+struct BinarySettings {
+    is_12_hours: bool,
+    on_color:    u32,
+    off_color:   u32,
+    glow_radius: f32 // in drawing units, an LED is `binary::LED_RADIUS` wide
+}
+
+impl Default for BinarySettings {
+    fn default() -> Self {
+        Self { is_12_hours: false, on_color: 0xFF3A24_FF, off_color: 0x2B1512_FF, glow_radius: 0.35 }
+    }
+}
+
+static STARTUP_SETTINGS: std::sync::OnceLock<BinarySettings> = std::sync::OnceLock::new();
+
+/// Behind the LEDs. Transparent, so that it doesn't glow, see led.wgsl
+const BACKGROUND_COLOR: u32 = 0x0C0B0B_00;
+
+/// Matched on in `onkey`, and listed by the help panel
+const KEYBINDINGS: &[KeyBinding] = &[
+    ("T", "Switch 12/24-hour format"),
+    ("H", "Show/hide this help")
+];
+
+/// A texture drawn into by one pass and read by the next, along with the bind group it's read through
+struct PassTexture {
+    texture:    RenderTexture,
+    bind_group: wgpu::BindGroup
+}
+
+impl PassTexture {
+    fn new(
+        size:    (u32, u32),
+        format:  wgpu::TextureFormat,
+        layout:  &wgpu::BindGroupLayout,
+        sampler: &BasicFilteringSampler,
+        device:  &wgpu::Device
+    ) -> Self {
+        let texture = RenderTexture::new(size, format, true, device);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   None,
+            layout,
+            entries: &[ texture.get_entry(0), sampler.get_entry(1) ]
+        });
+
+        Self { texture, bind_group }
+    }
+}
+
+/// The glow's blur table for the given radius in drawing units, at the drawspace's pixel density
+fn glow_blur_table(
+    glow_radius: f32,
+    density:     f32,
+    device:      &wgpu::Device,
+    queue:       &wgpu::Queue
+) -> (wgpu::BindGroup, wgpu::BindGroupLayout) {
+    let blur_radius = ((glow_radius * density) as i32).max(1);
+    let blur_sigma  = (blur_radius as f32) * 0.25;
+
+    create_blur_table_bindgroup(blur_radius, blur_sigma, true, true, device, queue)
+}
+
+fn begin_pass<'a>(
+    encoder:    &'a mut wgpu::CommandEncoder,
+    attachment: wgpu::RenderPassColorAttachment<'a>
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label:                    None,
+        depth_stencil_attachment: None,
+        timestamp_writes:         None,
+        occlusion_query_set:      None,
+        color_attachments:        &[Some(attachment)]
+    })
+}
+
+/// The Clock's mechanism:
+/// The time is shown in binary-coded decimal, a column of 4 LEDs per digit (bits 8, 4, 2 and 1, top to bottom),
+/// works the same way as the digital clock's segments: every LED is an island of its own in a single vertex buffer,
+/// and the lit ones are sent as bit flags. With 24 LEDs, a single flagset holds them all,
+/// and each column's bits are its digit as it is, see `calculate_binary_data`.
+///
+/// The glow is the digital clock's too: the lit LEDs blurred in two passes with the look-up table of
+/// `create_blur_table_bindgroup`, then added over the sharp image by the composite pass.
+struct BinaryClock {
+    led_pipeline:        TargetPipeline,
+    horizontal_pipeline: FullscreenQuadPipeline,
+    vertical_pipeline:   FullscreenQuadPipeline,
+    composite_pipeline:  FullscreenQuadPipeline,
+
+    led_params: SmallParams<BinaryData>,
+
+    vertex_buffer: wgpu::Buffer,
+    index_buffer:  wgpu::Buffer,
+    index_count:   u32,
+    drawspace:     Drawspace2D,
+
+    target_config: TargetConfig, // multisampled as requested (--msaa), as far as supported, resolved into `leds`
+    color_target:  ColorTarget,
+
+    texture_layout: wgpu::BindGroupLayout,
+    sampler:        BasicFilteringSampler,
+    leds:           PassTexture, // the sharp image
+    horizontal:     PassTexture, // its horizontal blur
+    glow:           PassTexture, // blurred both ways
+    format:         wgpu::TextureFormat, // of the surface, the pass textures have the same
+
+    blur_table_bindgroup: wgpu::BindGroup, // remade on resize, the radius is in drawing units
+
+    data:        BinaryData, // what the LED pass draws, worked out by `update`
+    settings:    BinarySettings,
+    last_second: u32, // for detecting significant changes, see `significant_change`
+    show_help:   bool,
+    help:        BitmapFontRenderer
+}
+
+impl BinaryClock {
+    fn binary_data(self: &Self, now: &chrono::DateTime<chrono::FixedOffset>) -> BinaryData {
+        calculate_binary_data(now, self.settings.is_12_hours, self.settings.on_color, self.settings.off_color)
+    }
+}
+
+impl ExecDraw for BinaryClock {
+    fn setup(
+        config:   &wgpu::SurfaceConfiguration,
+        adapter:  &wgpu::Adapter,
+        device:   &wgpu::Device,
+        queue:    &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> {
+        let resources = get_resource_folder_for("binary").map_err(|error| error.to_string())?;
+        let settings  = STARTUP_SETTINGS.get().copied().unwrap_or_default();
+        let size      = (config.width, config.height);
+
+        // smooths the LEDs' edges before they're blurred
+        let target_config = TargetConfig::new(config.format, None).with_supported_sample_count(requested_sample_count(), adapter, device);
+        let color_target  = ColorTarget::new(size, &target_config, device);
+
+        let drawspace = Drawspace2D::new(device, EXTENT, FitMode::Contain);
+        let sampler   = BasicFilteringSampler::new(device);
+
+        let (vertices, indices) = led_vertices_and_indices();
+        let (vertex_buffer, index_buffer) = create_vertex_and_index_buffers(
+            device,
+            cast_slice_to_u8_slice(&vertices),
+            cast_slice_to_u8_slice(&indices)
+        );
+
+        ////////////////////////////////////////
+
+        // the flags and colors sent via push constants (or right after the drawspace scales, without them)
+        let led_params = SmallParams::<BinaryData>::new(device, wgpu::ShaderStages::VERTEX_FRAGMENT, 1, 1);
+
+        let led_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &[ drawspace.layout() ], led_params.layout_entries().as_slice() ].concat(),
+            push_constant_ranges: &led_params.push_constant_ranges()
+        });
+
+        let led_shader = led_params.load_shader(device, &resources.join("shaders/led.wgsl"))?;
+
+        let led_pipeline = PipelineBuilder::new(&target_config, &led_pipeline_layout, &led_shader)
+            .vertex("vs_main", &[ Vtx2ID::wgpu_layout() ])
+            .build(device);
+
+        ////////////////////////////////////////
+
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[ RenderTexture::default_layout_entry(0), BasicFilteringSampler::default_layout_entry(1) ]
+        });
+
+        let leds       = PassTexture::new(size, config.format, &texture_layout, &sampler, device);
+        let horizontal = PassTexture::new(size, config.format, &texture_layout, &sampler, device);
+        let glow       = PassTexture::new(size, config.format, &texture_layout, &sampler, device);
+
+        let density = DrawspaceScales::new(glam::Vec2::new(config.width as f32, config.height as f32), EXTENT).density;
+        let (blur_table_bindgroup, blur_table_layout) = glow_blur_table(settings.glow_radius, density, device, queue);
+
+        // one pipeline per direction, the fragment shader's entry point is all that differs
+        let filter_shader = load_shader(device, &resources.join("shaders/filter.wgsl"))?;
+        let filter_pipeline = |entry| FullscreenQuadPipeline::new(
+            device, &filter_shader, entry,
+            &[ &texture_layout, drawspace.layout(), &blur_table_layout ],
+            &[],
+            config.format
+        );
+
+        let horizontal_pipeline = filter_pipeline("fs_horizontal");
+        let vertical_pipeline   = filter_pipeline("fs_vertical");
+
+        // the sharp image and the glow, both bound as pass textures (the glow's sampler goes unused)
+        let composite_shader = load_shader(device, &resources.join("shaders/composite.wgsl"))?;
+        let composite_pipeline = FullscreenQuadPipeline::new(
+            device, &composite_shader, "fs_main",
+            &[ &texture_layout, &texture_layout ],
+            &[],
+            config.format
+        );
+
+        Ok(Self {
+            led_pipeline, horizontal_pipeline, vertical_pipeline, composite_pipeline,
+            led_params,
+            vertex_buffer, index_buffer,
+            index_count: indices.len() as u32,
+            drawspace,
+            target_config, color_target,
+            texture_layout, sampler, leds, horizontal, glow,
+            format: config.format,
+            blur_table_bindgroup,
+            data:        BinaryData::default(),
+            settings,
+            last_second: u32::MAX,
+            show_help:   false,
+            help:        keys::help_panel(KEYBINDINGS, config, device)
+        })
+    }
+
+    fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let size = (width, height);
+
+        self.drawspace.resize(queue, width, height);
+        self.help.resize(width, height);
+        self.color_target = ColorTarget::new(size, &self.target_config, device);
+
+        self.leds       = PassTexture::new(size, self.format, &self.texture_layout, &self.sampler, device);
+        self.horizontal = PassTexture::new(size, self.format, &self.texture_layout, &self.sampler, device);
+        self.glow       = PassTexture::new(size, self.format, &self.texture_layout, &self.sampler, device);
+
+        // the glow keeps its size relative to the LEDs
+        self.blur_table_bindgroup = glow_blur_table(self.settings.glow_radius, self.drawspace.scales().density, device, queue).0;
+    }
+
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        match keys::pressed(&event, KEYBINDINGS) {
+            Some("T") => {
+                self.settings.is_12_hours = !self.settings.is_12_hours;
+            },
+            Some("H") => {
+                self.show_help = !self.show_help;
+            },
+            _ => {}
+        }
+    }
+
+    fn significant_change(self: &mut Self) -> bool {
+        // the LEDs only change on the second
+        let second = time::now().second();
+        let changed = second != self.last_second;
+        self.last_second = second;
+
+        changed
+    }
+
+    fn next_deadline(self: &Self, now: time::Instant) -> Option<time::Instant> {
+        time::deadline_after(now, until_next_second(&time::now()))
+    }
+
+    fn redraw_policy(self: &Self) -> RedrawPolicy {
+        RedrawPolicy::OnEvent // and on the second, see `next_deadline`
+    }
+
+    fn content_version(self: &Self, now: &chrono::DateTime<chrono::FixedOffset>) -> Option<u64> {
+        Some(binary::content_version(&self.binary_data(now)))
+    }
+
+    fn update(self: &mut Self, _dt: f32, _input: &InputState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        self.data = self.binary_data(&time::now());
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        // the LEDs, lit or not, over the background
+        {
+            let clear = u32_col_to_wgpu_col_linear(BACKGROUND_COLOR);
+            let mut rpass = begin_pass(&mut encoder, self.color_target.attachment(&self.target_config, &self.leds.texture.view, wgpu::LoadOp::Clear(clear)));
+
+            self.led_pipeline.set(&mut rpass, &self.target_config);
+            rpass.set_bind_group(0, self.drawspace.bind_group(), &[]);
+            self.led_params.set(&mut rpass, queue, &self.data);
+
+            rpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            rpass.draw_indexed(0..self.index_count, 0, 0..1);
+        }
+
+        // the blur and composite passes are all fullscreen quads, each one clearing its destination
+        let mut apply_pass = |pipeline: &FullscreenQuadPipeline, bind_groups: &[&wgpu::BindGroup], destination: &wgpu::TextureView| {
+            let mut rpass = begin_pass(&mut encoder, wgpu::RenderPassColorAttachment {
+                view:           destination,
+                resolve_target: None,
+                ops:            wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: wgpu::StoreOp::Store }
+            });
+
+            pipeline.draw(&mut rpass, bind_groups, &[]);
+        };
+
+        let blur_bind_groups = |source| [source, self.drawspace.bind_group(), &self.blur_table_bindgroup];
+
+        apply_pass(&self.horizontal_pipeline, &blur_bind_groups(&self.leds.bind_group),       &self.horizontal.texture.view);
+        apply_pass(&self.vertical_pipeline,   &blur_bind_groups(&self.horizontal.bind_group), &self.glow.texture.view);
+        apply_pass(&self.composite_pipeline,  &[ &self.leds.bind_group, &self.glow.bind_group ], texview);
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        if self.show_help {
+            self.help.draw(texview, device, queue);
+        }
+    }
+}
+
+fn main() {
+    logging::init("binary");
+
+    let (args, app) = Cli::new("binary", "Binary (BCD) clock: a column of LEDs per digit, with glow")
+        .switch("--12h", "Show the time in 12-hour format")
+        .option("--on-color", "RRGGBB", "Color of the lit LEDs")
+        .option("--off-color", "RRGGBB", "Color of the LEDs that are off")
+        .option("--glow-radius", "UNITS", "Radius of the lit LEDs' glow, an LED's radius is 0.38 (default: 0.35)")
+        .parse();
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    let mut settings = BinarySettings { is_12_hours: app.flag("--12h"), ..BinarySettings::default() };
+
+    for (option, color) in [("--on-color", &mut settings.on_color), ("--off-color", &mut settings.off_color)] {
+        if let Some(value) = app.value(option) {
+            match parse_hex_color(value) {
+                Some(parsed) => *color = parsed,
+                None         => log::warn!("Ignoring invalid color '{}' for {}", value, option)
+            }
+        }
+    }
+
+    if let Some(value) = app.value("--glow-radius") {
+        match value.parse::<f32>() {
+            Ok(radius) if radius.is_finite() && radius > 0.0 => settings.glow_radius = radius,
+            _ => log::warn!("Ignoring invalid glow radius '{}'", value)
+        }
+    }
+
+    let _ = STARTUP_SETTINGS.set(settings);
+
+    if args.take_snapshot::<BinaryClock>((800, 500), None, 1) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Binary Clock", &format!("Failed to initialize the windowing system: {}", error)));
+    #[allow(unused_mut)]
+    let mut builder = winit::window::WindowBuilder::new();
+    let builder = builder
+        .with_inner_size(winit::dpi::LogicalSize { width: 800.0, height: 500.0 })
+        .with_title("Binary Clock");
+    let window = args.apply_to_window(builder, &event_loop)
+        .build(&event_loop)
+        .unwrap_or_else(|error| fatal_error("Binary Clock", &format!("Failed to create the window: {}", error)));
+
+    pollster::block_on(run_with_options::<BinaryClock>(
+        event_loop, window,
+        None, // push constants are used if available
+        args.run_options()
+    ));
+}
@@ -0,0 +1,167 @@
+#![cfg_attr(
+    all(
+        target_os = "windows",
+        not(feature = "console"),
+    ),
+    windows_subsystem = "windows"
+)]
+use clockutils::{
+    run_with_options, fatal_error, ExecDraw, FrameInfo, FrameStats, InputState, RedrawPolicy,
+    config::ClockConfig,
+    subview::{Corner, SubView},
+    cli::Cli, logging, time
+};
+
+// the clocks as their own binaries have them, `main` and the rest only they need go unused here
+#[path = "portal.rs"]
+#[allow(dead_code, unused_attributes)]
+mod portal;
+
+#[path = "digital.rs"]
+#[allow(dead_code, unused_attributes)]
+mod digital;
+
+/// How much of the window the digital clock takes, on both axes
+const INSET_FRACTION: f32 = 0.3;
+
+/// The portal clock with the digital clock inset in its bottom right corner.
+/// The portal keeps the input (orbiting, keys) and the title, the inset follows the digital clock's saved settings.
+/// Neither clock's settings are saved on exit, the combo's window isn't theirs to remember.
+struct Combo {
+    primary: portal::Portal,
+    inset:   SubView<digital::DigiClock>
+}
+
+impl ExecDraw for Combo {
+    fn setup(
+        config:  &wgpu::SurfaceConfiguration,
+        adapter: &wgpu::Adapter,
+        device:  &wgpu::Device,
+        queue:   &wgpu::Queue,
+        theme:   winit::window::Theme) -> Result<Self, String> {
+        let primary = portal::Portal::setup(config, adapter, device, queue, theme)?;
+        let inset   = SubView::setup(config, adapter, device, queue, theme, INSET_FRACTION, Corner::BottomRight)?;
+
+        Ok(Self { primary, inset })
+    }
+
+    fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.primary.resize(width, height, device, queue);
+        self.inset.resize(width, height, device, queue);
+    }
+
+    fn update(self: &mut Self, dt: f32, input: &InputState, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.primary.update(dt, input, device, queue);
+        self.inset.inner.update(dt, input, device, queue);
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, frame: &FrameInfo) {
+        self.primary.draw(texview, device, queue, frame);
+        self.inset.draw(texview, device, queue, frame);
+    }
+
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, modifiers: winit::keyboard::ModifiersState, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.primary.onkey(event, modifiers, device, queue);
+    }
+
+    fn onmousemove(self: &mut Self, delta: (f64, f64), state: u32, modifiers: winit::keyboard::ModifiersState, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.primary.onmousemove(delta, state, modifiers, device, queue);
+    }
+
+    fn onmousescroll(self: &mut Self, delta: (f64, f64), state: u32, modifiers: winit::keyboard::ModifiersState, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.primary.onmousescroll(delta, state, modifiers, device, queue);
+    }
+
+    fn onmousebutton(self: &mut Self, state: u32, modifiers: winit::keyboard::ModifiersState, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.primary.onmousebutton(state, modifiers, device, queue);
+    }
+
+    fn onmousebutton_at(self: &mut Self, state: u32, position: Option<(f64, f64)>, modifiers: winit::keyboard::ModifiersState, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.primary.onmousebutton_at(state, position, modifiers, device, queue);
+    }
+
+    fn oncursormoved(self: &mut Self, position: (f64, f64), state: u32, modifiers: winit::keyboard::ModifiersState, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.primary.oncursormoved(position, state, modifiers, device, queue);
+    }
+
+    fn ontheme(self: &mut Self, theme: winit::window::Theme) {
+        self.primary.ontheme(theme);
+        self.inset.inner.ontheme(theme);
+    }
+
+    fn title_status(self: &Self) -> Option<String> {
+        self.primary.title_status()
+    }
+
+    fn onstats(self: &mut Self, stats: &FrameStats) {
+        self.primary.onstats(stats);
+    }
+
+    fn request_present_mode(self: &Self) -> Option<wgpu::PresentMode> {
+        self.primary.request_present_mode()
+    }
+
+    fn significant_change(self: &mut Self) -> bool {
+        // both are polled, as the digital clock keeps track of the last second it showed
+        let primary = self.primary.significant_change();
+        let inset   = self.inset.inner.significant_change();
+
+        primary || inset
+    }
+
+    /// The more frequent of the two
+    fn redraw_policy(self: &Self) -> RedrawPolicy {
+        match (self.primary.redraw_policy(), self.inset.inner.redraw_policy()) {
+            (RedrawPolicy::Continuous, _) | (_, RedrawPolicy::Continuous) => RedrawPolicy::Continuous,
+            (RedrawPolicy::EveryDuration(primary), RedrawPolicy::EveryDuration(inset)) => RedrawPolicy::EveryDuration(primary.min(inset)),
+            (RedrawPolicy::EveryDuration(interval), RedrawPolicy::OnEvent) |
+            (RedrawPolicy::OnEvent, RedrawPolicy::EveryDuration(interval)) => RedrawPolicy::EveryDuration(interval),
+            (RedrawPolicy::OnEvent, RedrawPolicy::OnEvent) => RedrawPolicy::OnEvent
+        }
+    }
+
+    fn next_deadline(self: &Self, now: time::Instant) -> Option<time::Instant> {
+        match (self.primary.next_deadline(now), self.inset.inner.next_deadline(now)) {
+            (Some(primary), Some(inset)) => Some(primary.min(inset)),
+            (primary, inset)             => primary.or(inset)
+        }
+    }
+}
+
+fn main() {
+    logging::init("combo");
+
+    let (args, _) = Cli::new("combo", "Portal clock with the digital clock inset in its corner")
+        .parse();
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    // snapshots ignore the digital clock's config file, as its own do
+    if args.snapshot.is_some() {
+        let _ = digital::STARTUP_CONFIG.set(ClockConfig::default());
+    }
+
+    if args.take_snapshot::<Combo>((768, 512), Some(wgpu::Features::PUSH_CONSTANTS), 1) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Combo Clock", &format!("Failed to initialize the windowing system: {}", error)));
+    #[allow(unused_mut)]
+    let mut builder = winit::window::WindowBuilder::new();
+    let builder = builder
+        .with_inner_size(winit::dpi::LogicalSize { width: 768.0, height: 512.0 })
+        .with_title("Combo Clock");
+    let window = args.apply_to_window(builder, &event_loop)
+        .build(&event_loop)
+        .unwrap_or_else(|error| fatal_error("Combo Clock", &format!("Failed to create the window: {}", error)));
+
+    pollster::block_on(run_with_options::<Combo>(
+        event_loop, window,
+        Some(wgpu::Features::PUSH_CONSTANTS),
+        args.run_options()
+    ));
+}
@@ -5,16 +5,64 @@
     ),
     windows_subsystem = "windows"
 )]
-use std::{borrow::Cow, fs::read_to_string};
 use wgpu::{RenderPipelineDescriptor, PushConstantRange};
 use clockutils::{
-    cast_struct_to_u8_slice, run, create_vertex_and_index_buffers, cast_slice_to_u8_slice, get_resource_folder_for,
-    ExecDraw, ResourceTexture, BasicFilteringSampler, SingleUniformBuffer,
-    RenderTexture, DrawspaceScales, ImmutableStorageBuffer, Vtx2ID,
-    SURFACE_FORMAT
+    cast_struct_to_u8_slice, run_with_options, create_vertex_and_index_buffers, cast_slice_to_u8_slice, get_resource_folder_for, load_shader, fatal_error,
+    create_blur_table_bindgroup,
+    ExecDraw, FrameInfo, InputState, RedrawPolicy, RunOptions, ResourceTexture, BasicFilteringSampler, FullscreenQuadPipeline,
+    CrtPass, CrtParams, RenderTexture, DrawspaceScales, Drawspace2D, FitMode, Vtx2ID,
+    cli::Cli, logging, time,
+    target::TargetConfig,
+    font::{BitmapFontRenderer, TextAnchor},
+    keys::{self, KeyBinding},
+    config::{ClockConfig, WindowGeometry},
+    palette::{PaletteEditor, PaletteSet},
+    clocks::digital::{self, calculate_clock_data, format_animation_speed, parse_first_day, segment_transition,
+        parse_glow_blend, step_animation_speed, until_next_half_second, lit_segments, power_mw, ClockData, ColonStyle, CompositeInfo,
+        GlowBlend, IndicatorLayout, PowerHistory, INDICATOR_LAYOUT, PALETTE_SLOTS, SOLID_PALETTES}
 };
-use chrono::{Local, Timelike, Datelike};
-use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
+use std::fmt::Write;
+use chrono::Timelike;
+use serde::{Serialize, Deserialize};
+
+/// Settings persisted in `digital.toml`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct DigitalSettings {
+    is_12_hours: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    palette:     Option<u32>, // color palette selector, picked by the OS theme until chosen
+    blur_scale:  f32, // multiplier of the glow's blur radius
+    glow_intensity: f32, // multiplier of the glow's brightness, 0 turns it off
+    glow_blend:  GlowBlend,
+    colon_style: ColonStyle,
+    texture_brightness: bool, // LEDs lit by the clock layout's brightness, rather than flat
+    animation_speed: f32, // multiplier of the segments' fade speed, 0 turns it off
+    power_display: bool, // the power estimate's panel is shown
+    crt: bool // scanlines over the glowing segments, as on a CRT
+}
+
+impl Default for DigitalSettings {
+    fn default() -> Self {
+        Self {
+            is_12_hours: false, palette: None, blur_scale: 1.0, glow_intensity: 1.0, glow_blend: GlowBlend::Add,
+            colon_style: ColonStyle::Blink, texture_brightness: false, animation_speed: 1.0, power_display: false, crt: false
+        }
+    }
+}
+
+/// Config file contents with command line overrides applied, handed over from `main` to `setup`
+pub(crate) static STARTUP_CONFIG: std::sync::OnceLock<ClockConfig<DigitalSettings>> = std::sync::OnceLock::new();
+
+/// `INDICATOR_LAYOUT` with `--first-day` applied, validated
+static STARTUP_LAYOUT: std::sync::OnceLock<IndicatorLayout> = std::sync::OnceLock::new();
+
+/// `SOLID_PALETTES` with the palette file's colors, loaded in `main`
+static STARTUP_PALETTES: std::sync::OnceLock<PaletteSet> = std::sync::OnceLock::new();
+
+fn load_palettes(path: Option<std::path::PathBuf>) -> PaletteSet {
+    PaletteSet::load(path, PALETTE_SLOTS, SOLID_PALETTES.iter().map(|palette| palette.to_vec()).collect())
+}
 
 /// Resources that are recreated on window resize
 struct DynamicResources {
@@ -41,148 +89,65 @@ struct DynamicResources {
 /// Extra two more passes are included for the glow effect using two-pass gaussian blur, this is optional to this clock.
 /// This two pass version has a time complexity of O(n), which is fine because the single pass version would have
 /// time complexity of O(n^2), which is crazy resource hungry and GPU usage goes out of the roof as you crank up the blur radius.
-struct DigiClock {
-    forward_pipeline: wgpu::RenderPipeline,
-    filter_pipeline:  wgpu::RenderPipeline,
-
-    vertex_buffer:  wgpu::Buffer,
-    index_buffer:   wgpu::Buffer,
-    uniform_buffer: wgpu::Buffer,
+/// A last composite pass lays the blurred image over the sharp one, so that the segments' edges stay as crisp as the forward
+/// pass drew them, whatever the blur radius is.
+/// With the CRT look on, the composite goes into a texture of its own instead, which a `CrtPass` draws onto the frame.
+/// Whatever draws into the frame is built for the runner's multisampling (`TargetConfig::frame`), the rest is single-sampled.
+pub(crate) struct DigiClock {
+    forward_pipeline:       wgpu::RenderPipeline,
+    filter_pipeline:        FullscreenQuadPipeline,
+    composite_pipeline:     FullscreenQuadPipeline, // into the frame
+    crt_composite_pipeline: FullscreenQuadPipeline, // into `crt_frame`
+
+    vertex_buffer: wgpu::Buffer,
+    index_buffer:  wgpu::Buffer,
+    drawspace:     Drawspace2D,
+
+    snapped_vertices: Vec<Vtx2ID>, // `VERTICES` on the current resolution's pixel grid
 
     resource_texture_bindgroup: wgpu::BindGroup,
-    uniform_buffer_bindgroup:   wgpu::BindGroup,
     blur_table_bindgroup:       wgpu::BindGroup,
 
-    dynamic_resources: [DynamicResources; 2], // two for two blur passes (horizontal and vertical)
+    dynamic_resources: [DynamicResources; 3], // the forward image, the horizontal blur and the full blur (the glow)
+    crt_pass:          CrtPass,
+    crt_frame:         RenderTexture,   // the composite, with the CRT look on
+    crt_source:        wgpu::BindGroup, // the above, for the CRT pass
+    clock_data:        ClockData, // what the forward pass draws, worked out by `update`
+    format:            wgpu::TextureFormat, // of the surface, the dynamic resources' textures have the same
 
     is_12_hours: bool,
-    selector:    u32 // color palette selector
-}
-
-#[repr(C, align(8))]
-struct ClockData {
-    flagset:   [u32; 2], // actual LED on/off states are encoded in these two
-    selector:  u32, // color palette selector, unrelated to clock
-    timestamp: f32 // for animation, unrelated to clock
-}
-
-#[derive(Debug)]
-#[repr(C, align(8))]
-struct BlurWO {
-    weight: f32,
-    offset: f32
-}
-
-/// While calculating gaussian blur, the same weights will be generated for all pixels,
-/// to cut out this redundant calc, we move that to the CPU from the fragment shader.
-/// This is only done once. Both weights and pixel offsets are calculated,
-/// and then sent to the fragment shader as a read-only storage buffer.
-/// The shader treats this buffer as a look-up table.
-/// This the rustified version of the JS code found in: https://lisyarus.github.io/blog/graphics/2023/02/24/blur-coefficients-generator.html
-/// So this function is not my code.
-fn create_blur_weights_and_offsets(
-    radius:     i32,
-    sigma:      f32,
-    linear:     bool,
-    correction: bool
-) -> Result<Vec<BlurWO>, &'static str> {
-    if radius < 1 {
-        return Err("Radius must be 1 or up");
-    }
-
-    if sigma == 0.0 {
-        return Err("Sigma cannot be 0");
-    }
-
-    // From https://hewgill.com/picomath/javascript/erf.js.html
-    fn erf(x: f32) -> f32 {
-        // constants
-        let a1: f32 =  0.254829592;
-        let a2: f32 = -0.284496736;
-        let a3: f32 =  1.421413741;
-        let a4: f32 = -1.453152027;
-        let a5: f32 =  1.061405429;
-        let  p: f32 =  0.3275911;
-    
-        // Save the sign of x
-        let mut sign: f32 = 1.0;
-        if x < 0.0 {
-            sign = -1.0;
-        }
-
-        let x = x.abs();
-    
-        // A&S formula 7.1.26
-        let t = 1.0 / (1.0 + p * x);
-        let y = 1.0 - (((((a5 * t + a4) * t) + a3) * t + a2) * t + a1) * t * (-x * x).exp();
-    
-        return sign * y;
-    }
-
-    let mut sum_weights: f32 = 0.0;
-
-    let mut weights: Vec<f32> = (-radius..radius+1).map(|i| {
-        let i = i as f32;
-
-        let w = if correction {
-            (erf((i + 0.5) / sigma / 2.0_f32.sqrt()) - erf((i - 0.5) / sigma / 2.0_f32.sqrt())) / 2.0
-        } else {
-            (-i * i / sigma / sigma).exp()
-        };
-
-        sum_weights += w;
-
-        return w;
-    }).collect();
-
-    let inv_sum_weights = 1.0 / sum_weights;
-    for i in 0..weights.len() {
-        weights[i] *= inv_sum_weights;
-    }
-
-    let weights_and_offsets: Vec<BlurWO> = if linear {
-        (-radius..radius+1).step_by(2).map(|i| {
-            if i == radius {
-                BlurWO {
-                    offset: i as f32,
-                    weight: weights[(i + radius) as usize]
-                }
-            } else {
-                let w0 = weights[(i + radius + 0) as usize];
-                let w1 = weights[(i + radius + 1) as usize];
-                let w = w0 + w1;
-
-                let o: f32 = if w > 0.0 {
-                    (i as f32) + w1 / w
-                } else {
-                    i as f32
-                };
-
-                BlurWO {
-                    offset: o,
-                    weight: w
-                }
-            }
-        }).collect()
-    } else {
-        (-radius..radius+1).enumerate().map(|(index, off)| {
-            BlurWO {
-                offset: off as f32,
-                weight: weights[index]
-            }
-        }).collect()
-    };
-
-    Ok(weights_and_offsets)
+    selector:    u32,  // color palette selector
+    chosen:      bool, // whether the selector was chosen (config, command line, Space) rather than picked by the theme
+    palettes:    PaletteSet, // colors of the solid palettes, the rest are animated by the shader
+    editor:      PaletteEditor,
+    colon_style: ColonStyle,
+    layout:      IndicatorLayout, // which bits light the weekday labels, AM/PM and the colon
+    blur_scale:  f32,
+    glow_intensity: f32,
+    glow_blend:  GlowBlend,
+    texture_brightness: bool,
+    animation_speed: f32, // 1.0 is the normal fade between minutes, 2.0 twice as fast, 0.0 switches instantly
+    alpha_mode:  u32, // how the final pass writes alpha on a transparent surface, see `composite.wgsl`
+    transparent: bool, // the black background is left out where the surface allows it, toggled with Ctrl+B
+    last_second: u32, // for detecting significant changes, see `significant_change`
+    show_help:   bool,
+    help:        BitmapFontRenderer,
+    power_display: bool,
+    power_history: PowerHistory, // recorded while hidden as well, so that the average is there once shown
+    power:         BitmapFontRenderer,
+    crt:           bool,
+
+    config: ClockConfig<DigitalSettings>
 }
 
 fn create_dynamic_resources(
     texsize: (u32, u32),
+    format:  wgpu::TextureFormat,
     sampler: &BasicFilteringSampler,
     device:  &wgpu::Device
 ) -> (DynamicResources, wgpu::BindGroupLayout) {
     let render_texture = RenderTexture::new(
-        texsize, SURFACE_FORMAT,
+        texsize, format,
         true, device
     );
 
@@ -205,142 +170,110 @@ fn create_dynamic_resources(
     (dynamic, render_texture_bindgroup_layout)
 }
 
-/// Generate the gblur look-up table:
-/// 1. The actual table storage buffer containing weights and offsets.
-/// 2. A single value uniform buffer for the count (table length).
-/// Both welded into a single bindgroup.
-/// (Could've put the count in the storage buffer at index 0, what was I thinking then? :P)
-fn create_blur_table_bindgroup(
-    radius:     i32,
-    sigma:      f32,
-    linear:     bool,
-    correction: bool,
-    device:     &wgpu::Device,
-    queue:      &wgpu::Queue
-) -> (wgpu::BindGroup, wgpu::BindGroupLayout) {
-    let weights_and_offsets = create_blur_weights_and_offsets(radius, sigma, linear, correction).unwrap();
-
-    let stages = wgpu::ShaderStages::FRAGMENT;
-
-    let storage = ImmutableStorageBuffer::new(
-        device, stages,
-        cast_slice_to_u8_slice(weights_and_offsets.as_slice())
-    );
+const SELECTOR_LENGTH: u32 = 5;
 
-    let uniform = SingleUniformBuffer::new::<u32>(device, stages);
+/// Half the size of the clock layout, in drawing units
+const EXTENT: glam::Vec2 = glam::Vec2::new(2.5, 1.40625);
 
-    let bindgroup_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: None,
-        entries: &[
-            ImmutableStorageBuffer::default_layout_entry(0, &storage),
-            SingleUniformBuffer::default_layout_entry(1, &uniform)
-        ]
-    });
+/// The segments' corners moved onto pixel boundaries, so that their edges don't shimmer at some sizes
+fn snap_vertices(dscales: &DrawspaceScales) -> Vec<Vtx2ID> {
+    VERTICES.iter().map(|vertex| Vtx2ID { pos: dscales.snap_to_pixel_grid(vertex.pos), ..*vertex }).collect()
+}
 
-    let bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label:   None,
-        layout:  &bindgroup_layout,
-        entries: &[
-            storage.get_entry(0),
-            uniform.get_entry(1)
-        ]
-    });
+/// Matched on in `onkey`, and listed by the help panel
+const KEYBINDINGS: &[KeyBinding] = &[
+    ("Space", "Next color palette"),
+    ("T",     "Switch 12/24-hour format"),
+    ("C",     "Next colon style"),
+    ("Ctrl+P", "Edit the palette: Tab, arrows, PageUp/PageDown, Ctrl+S saves"),
+    ("B",     "Toggle LED brightness from the layout"),
+    ("P",     "Show/hide the LEDs' estimated power draw"),
+    ("R",     "Toggle the CRT look (scanlines)"),
+    ("Ctrl+B", "Transparent/opaque background (transparent windows)"),
+    ("ArrowUp",   "Faster segment fade"),
+    ("ArrowDown", "Slower segment fade (down to off)"),
+    ("H",     "Show/hide this help")
+];
 
-    let data: u32 = weights_and_offsets.len() as u32;
-    queue.write_buffer(&uniform.buffer, 0, cast_struct_to_u8_slice(&data));
+/// How bright the edited island group is in the off half of its blink
+const EDIT_BLINK_BRIGHTNESS: f32 = 0.15;
 
-    (bindgroup, bindgroup_layout)
+/// Selector picked by the OS theme, orange stands out better than blue on a light desktop
+fn selector_for_theme(theme: winit::window::Theme) -> u32 {
+    match theme {
+        winit::window::Theme::Light => 2,
+        winit::window::Theme::Dark  => 0
+    }
 }
 
-/// Calculate bit flags from current time
-/// 
-/// Flagset 0:
-/// 
-///     * bits [0..6]   => hour tens
-/// 
-///     * bits [7..13]  => hour ones
-/// 
-///     * bits [14..20] => minute tens
-/// 
-///     * bits [21..27] => minute ones
-/// 
-/// Flagset 1:
-/// 
-///     * bits [0..6]   => day of week
-/// 
-///     * bit 7 => AM indicator
-/// 
-///     * bit 8 => PM indicator
-/// 
-///     * bit 9 => colon
-fn calculate_clock_data(hr12: bool, selector: u32) -> ClockData {
-    let now = Local::now();
+/// Columns of the power estimate, right-aligned, "24h avg: 12.5mW" with room for more digits
+const POWER_COLUMNS: usize = 18;
 
-    let mut hours = now.hour();
-    let minutes = now.minute();
-    let upper_half_sec = now.nanosecond() > 500_000_000;
-    let weekday = now.weekday() as usize;
+/// The power estimate in the lower-right corner, over the clock as it is
+fn power_panel(config: &wgpu::SurfaceConfiguration, device: &wgpu::Device) -> BitmapFontRenderer {
+    let mut panel = BitmapFontRenderer::for_target(&TargetConfig::frame(config.format, None), TextAnchor::BottomRight, device);
+    panel.resize(config.width, config.height);
 
-    let mut am = false;
-    let mut pm = false;
-
-    if hr12 {
-        if hours >= 12 {
-            pm = true;
-        } else {
-            am = true;
-        }
+    panel
+}
 
-        hours %= 12;
+/// How often the digital clock draws while nothing is animated, the colon blinks on the half second
+const BLINK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
 
-        if hours == 0 {
-            hours = 12;
-        }
+impl DigiClock {
+    /// The rgb fading and waves palettes are animated continuously, so are the pulsing and fading colons,
+    /// the island group being edited (it blinks) and the segments while fading into the new minute
+    fn animated(self: &Self, now: &chrono::DateTime<chrono::FixedOffset>) -> bool {
+        self.selector == 3 || self.selector == 4 || self.colon_style.is_animated() || self.editor.is_editing()
+            || segment_transition(now, self.animation_speed).is_some()
     }
 
-    let mut flags0: u32 = 0;
-    let mut flags1: u32 = 0;
-
-    // special case for hour tens digit, turn it off completely when it is zero
-    flags0 |= if (hours / 10) != 0 {
-        DIGIT_SEGMENT_FLAGS[(hours / 10) as usize] << 0
-    } else {
-        0
-    };
-    
-    flags0 |= DIGIT_SEGMENT_FLAGS[(hours % 10) as usize] << 7;
+    /// The estimated draw of the LEDs lit at the latest recorded second, and the day's average below it
+    fn update_power_panel(self: &mut Self) {
+        let average = self.power_history.average_mw();
+        let now = self.power_history.latest().map_or(0.0, power_mw);
 
-    flags0 |= DIGIT_SEGMENT_FLAGS[(minutes / 10) as usize] << 14;
-    flags0 |= DIGIT_SEGMENT_FLAGS[(minutes % 10) as usize] << 21;
+        self.power.clear(POWER_COLUMNS);
 
-    // made a mistake while designing the clock layout
-    // didn't realize chrono's week starts with different index than mine
-    flags1 |= 1 << ((weekday + 1) % 7);
-    
-    flags1 |= (if am { 1 } else { 0 }) << 7;
-    flags1 |= (if pm { 1 } else { 0 }) << 8;
-
-    flags1 |= (if upper_half_sec { 1 } else { 0 }) << 9;
+        if let Some(mut line) = self.power.push_line() {
+            let _ = write!(line, "{:>POWER_COLUMNS$}", format!("Power: {:.1}mW", now));
+        }
 
-    let timestamp = now.second() as f32 + now.nanosecond() as f32 / 1_000_000_000.0;
+        if let (Some(average), Some(mut line)) = (average, self.power.push_line()) {
+            let _ = write!(line, "{:>POWER_COLUMNS$}", format!("24h avg: {:.1}mW", average));
+        }
+    }
 
-    ClockData { flagset: [flags0, flags1], selector, timestamp }
+    /// The animation speed the Up/Down keys step to, `None` for any other key
+    fn speed_multiplier_from_key(self: &Self, event: &winit::event::KeyEvent) -> Option<f32> {
+        match keys::pressed(event, KEYBINDINGS) {
+            Some("ArrowUp")   => Some(step_animation_speed(self.animation_speed, true)),
+            Some("ArrowDown") => Some(step_animation_speed(self.animation_speed, false)),
+            _ => None
+        }
+    }
 }
 
-const SELECTOR_LENGTH: u32 = 5;
-
 impl ExecDraw for DigiClock {
     fn setup(
         config:   &wgpu::SurfaceConfiguration,
         _adapter: &wgpu::Adapter,
         device:   &wgpu::Device,
-        queue:    &wgpu::Queue
-    ) -> Self {
-        let resources = get_resource_folder_for("digital").unwrap();
+        queue:    &wgpu::Queue,
+        theme:    winit::window::Theme
+    ) -> Result<Self, String> {
+        let resources = get_resource_folder_for("digital").map_err(|error| error.to_string())?;
+        let clock_config = STARTUP_CONFIG.get().cloned().unwrap_or_else(|| ClockConfig::load("digital"));
         
+        // snapped again on every resize
+        let snapped_vertices = snap_vertices(&DrawspaceScales::new(
+            glam::Vec2::new(config.width as f32, config.height as f32),
+            EXTENT
+        ));
+
         let (vertex_buffer, index_buffer) = create_vertex_and_index_buffers(
             device,
-            cast_slice_to_u8_slice(&VERTICES),
+            cast_slice_to_u8_slice(&snapped_vertices),
             cast_slice_to_u8_slice(&INDICES)
         );
 
@@ -348,22 +281,11 @@ impl ExecDraw for DigiClock {
             resources.join("textures/clock_layout.png").as_path().to_str().unwrap(),
             device,
             queue
-        );
+        )?;
 
         let sampler = BasicFilteringSampler::new(device);
 
-        let udspace = SingleUniformBuffer::new::<DrawspaceScales>(device, wgpu::ShaderStages::VERTEX_FRAGMENT);
-
-        let uniform_buffer_bindgroup_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: None,
-            entries: &[ SingleUniformBuffer::default_layout_entry(0, &udspace) ]
-        });
-
-        let uniform_buffer_bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label:   None,
-            layout:  &uniform_buffer_bindgroup_layout,
-            entries: &[ udspace.get_entry(0) ]
-        });
+        let drawspace = Drawspace2D::new(device, EXTENT, FitMode::Contain);
 
         let resource_texture_bindgroup_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: None,
@@ -377,30 +299,28 @@ impl ExecDraw for DigiClock {
         });
 
         let (dynamic_resources_0, render_texture_bindgroup_layout) = create_dynamic_resources(
-            (config.width, config.height), &sampler, device
+            (config.width, config.height), config.format, &sampler, device
         );
         let (dynamic_resources_1, _) = create_dynamic_resources(
-            (config.width, config.height), &sampler, device
+            (config.width, config.height), config.format, &sampler, device
         );
-        let dynamic_resources = [dynamic_resources_0, dynamic_resources_1];
+        let (dynamic_resources_2, _) = create_dynamic_resources(
+            (config.width, config.height), config.format, &sampler, device
+        );
+        let dynamic_resources = [dynamic_resources_0, dynamic_resources_1, dynamic_resources_2];
 
         let (blur_table_bindgroup, blur_table_bindgroup_layout) = create_blur_table_bindgroup(40, 10.0, true, true, device, queue);
 
         let forward_pipeline = {
-            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label:  None,
-                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
-                    read_to_string(resources.join("shaders/forward.wgsl")).unwrap().as_str()
-                ))
-            });
+            let shader = load_shader(device, &resources.join("shaders/forward.wgsl"))?;
 
             let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label:                None,
-                bind_group_layouts:   &[ &resource_texture_bindgroup_layout, &uniform_buffer_bindgroup_layout ],
+                bind_group_layouts:   &[ &resource_texture_bindgroup_layout, drawspace.layout() ],
                 push_constant_ranges: &[
                     PushConstantRange {
                         stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
-                        range:  0..16
+                        range:  0..std::mem::size_of::<ClockData>() as u32
                     }
                 ]
             });
@@ -414,21 +334,12 @@ impl ExecDraw for DigiClock {
                 vertex: wgpu::VertexState {
                     module:      &shader,
                     entry_point: "vs_main",
-                    buffers:     &[
-                        wgpu::VertexBufferLayout {
-                            array_stride: std::mem::size_of::<Vtx2ID>() as wgpu::BufferAddress,
-                            step_mode:    wgpu::VertexStepMode::Vertex,
-                            attributes:   &[
-                                wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 0, offset: 0 },
-                                wgpu::VertexAttribute { format: wgpu::VertexFormat::Uint32,    shader_location: 1, offset: 2 * std::mem::size_of::<f32>() as u64 }
-                            ]
-                        }
-                    ]
+                    buffers:     &[ Vtx2ID::wgpu_layout() ]
                 },
                 fragment: Some(wgpu::FragmentState {
                     module:      &shader,
                     entry_point: "fs_main",
-                    targets:     &[ Some(SURFACE_FORMAT.into()) ]
+                    targets:     &[ Some(config.format.into()) ]
                 }),
                 primitive: wgpu::PrimitiveState {
                     topology:     wgpu::PrimitiveTopology::TriangleList,
@@ -444,107 +355,237 @@ impl ExecDraw for DigiClock {
         // same pipeline used for both horizontal and vertical blurring,
         // the selection is sent via push constant
         let filter_pipeline = {
-            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label:  None,
-                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
-                    read_to_string(resources.join("shaders/filter.wgsl")).unwrap().as_str()
-                ))
-            });
+            let shader = load_shader(device, &resources.join("shaders/filter.wgsl"))?;
 
-            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label:                None,
-                bind_group_layouts:   &[
+            FullscreenQuadPipeline::new(
+                device, &shader, "fs_main",
+                &[
                     &render_texture_bindgroup_layout,
-                    &uniform_buffer_bindgroup_layout,
-                    &blur_table_bindgroup_layout,
-                    &render_texture_bindgroup_layout
+                    drawspace.layout(),
+                    &blur_table_bindgroup_layout
                 ],
-                push_constant_ranges: &[
-                    wgpu::PushConstantRange {
+                &[
+                    PushConstantRange {
                         stages: wgpu::ShaderStages::FRAGMENT,
-                        range:  0..4
+                        range:  0..std::mem::size_of::<u32>() as u32
                     }
-                ]
-            });
+                ],
+                config.format
+            )
+        };
 
-            // fullscreen quad drawing pipeline
-            device.create_render_pipeline(&RenderPipelineDescriptor {
-                label:         None,
-                layout:        Some(&pipeline_layout),
-                depth_stencil: None,
-                multisample:   wgpu::MultisampleState::default(),
-                multiview:     None,
-                vertex: wgpu::VertexState {
-                    module:      &shader,
-                    entry_point: "vs_main",
-                    buffers:     &[]
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module:      &shader,
-                    entry_point: "fs_main",
-                    targets:     &[ Some(SURFACE_FORMAT.into()) ]
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology:     wgpu::PrimitiveTopology::TriangleStrip,
-                    cull_mode:    None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    ..Default::default()
+        // the forward image and the glow, both bound as render textures (the glow's sampler goes unused)
+        let frame_config = TargetConfig::frame(config.format, None);
+        let composite_shader = load_shader(device, &resources.join("shaders/composite.wgsl"))?;
+        let composite_pipeline = |target_config: &TargetConfig| FullscreenQuadPipeline::for_target(
+            device, &composite_shader, "fs_main",
+            &[
+                &render_texture_bindgroup_layout,
+                &render_texture_bindgroup_layout
+            ],
+            &[
+                PushConstantRange {
+                    stages: wgpu::ShaderStages::FRAGMENT,
+                    range:  0..std::mem::size_of::<CompositeInfo>() as u32
                 }
-            })
-        };
+            ],
+            target_config
+        );
 
-        Self {
+        let crt_pass   = CrtPass::for_target(device, queue, &frame_config);
+        let crt_frame  = RenderTexture::new((config.width, config.height), config.format, true, device);
+        let crt_source = crt_pass.source_bindgroup(&crt_frame, device);
+
+        Ok(Self {
             forward_pipeline,
             filter_pipeline,
+            composite_pipeline:     composite_pipeline(&frame_config),
+            crt_composite_pipeline: composite_pipeline(&TargetConfig::new(config.format, None)),
 
             vertex_buffer,
             index_buffer,
-            uniform_buffer: udspace.buffer,
+            drawspace,
+            snapped_vertices,
 
             resource_texture_bindgroup,
-            uniform_buffer_bindgroup,
             blur_table_bindgroup,
 
             dynamic_resources,
+            crt_pass,
+            crt_frame,
+            crt_source,
+            format: config.format,
+            clock_data: ClockData::default(),
+
+            is_12_hours: clock_config.settings.is_12_hours,
+            selector:    clock_config.settings.palette.unwrap_or_else(|| selector_for_theme(theme)) % SELECTOR_LENGTH,
+            chosen:      clock_config.settings.palette.is_some(),
+            palettes:    STARTUP_PALETTES.get().cloned().unwrap_or_else(|| load_palettes(None)),
+            editor:      PaletteEditor::new("Digital Clock"),
+            colon_style: clock_config.settings.colon_style,
+            layout:      STARTUP_LAYOUT.get().copied().unwrap_or(INDICATOR_LAYOUT),
+            blur_scale:  clock_config.settings.blur_scale,
+            glow_intensity: clock_config.settings.glow_intensity,
+            glow_blend:  clock_config.settings.glow_blend,
+            texture_brightness: clock_config.settings.texture_brightness,
+            animation_speed: clock_config.settings.animation_speed,
+            alpha_mode:  match config.alpha_mode {
+                wgpu::CompositeAlphaMode::PreMultiplied  => 1,
+                wgpu::CompositeAlphaMode::PostMultiplied => 2,
+                _ => 0 // opaque
+            },
+            transparent: true,
+            last_second: u32::MAX,
+            show_help:   false,
+            help:        keys::help_panel(KEYBINDINGS, config, device),
+            power_display: clock_config.settings.power_display,
+            power_history: PowerHistory::new(),
+            power:         power_panel(config, device),
+            crt:           clock_config.settings.crt,
+
+            config: clock_config
+        })
+    }
 
-            is_12_hours: false,
-            selector: 0
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        // only the solid palettes have colors to edit
+        let editable = (self.selector as usize) < self.palettes.len();
+        if self.editor.onkey(&event, &mut self.palettes, editable.then_some(self.selector as usize)) {
+            return;
         }
-    }
 
-    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _device: &wgpu::Device, _queue: &wgpu::Queue) {
-        if event.state == winit::event::ElementState::Pressed && !event.repeat {
-            match event.key_without_modifiers().as_ref() {
-                winit::keyboard::Key::Named(winit::keyboard::NamedKey::Space) => {
-                    self.selector = (self.selector + 1) % SELECTOR_LENGTH;
-                },
-                winit::keyboard::Key::Character("T") | winit::keyboard::Key::Character("t") => {
-                    self.is_12_hours = !self.is_12_hours;
+        if let Some(speed) = self.speed_multiplier_from_key(&event) {
+            self.animation_speed = speed;
+            log::debug!("Animation speed {}", format_animation_speed(speed));
+            return;
+        }
+
+        match keys::pressed(&event, KEYBINDINGS) {
+            Some("Space") => {
+                self.selector = (self.selector + 1) % SELECTOR_LENGTH;
+                self.chosen   = true;
+            },
+            Some("T") => {
+                self.is_12_hours = !self.is_12_hours;
+            },
+            Some("C") => {
+                self.colon_style = self.colon_style.next();
+            },
+            Some("B") => {
+                self.texture_brightness = !self.texture_brightness;
+            },
+            Some("P") => {
+                self.power_display = !self.power_display;
+            },
+            Some("R") => {
+                self.crt = !self.crt;
+            },
+            Some("Ctrl+B") => {
+                if self.alpha_mode != 0 {
+                    self.transparent = !self.transparent;
+                } else {
+                    log::warn!("The background can only be left out of a transparent window (--transparent)");
                 }
-                _ => {}
-            }
+            },
+            Some("H") => {
+                self.show_help = !self.show_help;
+            },
+            _ => {}
+        }
+    }
+
+    fn title_status(self: &Self) -> Option<String> {
+        self.editor.status(&self.palettes)
+            .or_else(|| Some(format!("Speed: {}", format_animation_speed(self.animation_speed))))
+    }
+
+    fn ontheme(self: &mut Self, theme: winit::window::Theme) {
+        // not while editing, the palette would change under the editor's hands
+        if !self.chosen && !self.editor.is_editing() {
+            self.selector = selector_for_theme(theme);
+        }
+    }
+
+    fn significant_change(self: &mut Self) -> bool {
+        // Nothing visible happens within a second unless animated, except the colon blinking,
+        // which the idle frame rate is more than enough for.
+        let now = time::now();
+        if self.animated(&now) {
+            return true;
+        }
+
+        let second = now.second();
+        let changed = second != self.last_second;
+        self.last_second = second;
+
+        changed
+    }
+
+    fn next_deadline(self: &Self, now: time::Instant) -> Option<time::Instant> {
+        // the colon blinks on the half second, the digits change on the whole one
+        time::deadline_after(now, until_next_half_second(&time::now()))
+    }
+
+    fn redraw_policy(self: &Self) -> RedrawPolicy {
+        if self.animated(&time::now()) { RedrawPolicy::Continuous } else { RedrawPolicy::EveryDuration(BLINK_INTERVAL) }
+    }
+
+    fn content_version(self: &Self, now: &chrono::DateTime<chrono::FixedOffset>) -> Option<u64> {
+        // the animated ones are drawn every frame
+        if self.animated(now) {
+            return None;
+        }
+
+        // the power panel's average moves on every second
+        let data = calculate_clock_data(now, self.is_12_hours, self.selector, self.colon_style, &self.layout);
+        Some(digital::content_version(&data, self.power_display.then(|| now.timestamp())))
+    }
+
+    fn onexit(self: &mut Self, window: &winit::window::Window) {
+        self.config.settings = DigitalSettings {
+            is_12_hours: self.is_12_hours,
+            palette:     self.chosen.then_some(self.selector),
+            blur_scale:  self.blur_scale,
+            glow_intensity: self.glow_intensity,
+            glow_blend:  self.glow_blend,
+            colon_style: self.colon_style,
+            texture_brightness: self.texture_brightness,
+            animation_speed: self.animation_speed,
+            power_display: self.power_display,
+            crt: self.crt
+        };
+
+        // a fullscreen window's geometry is not worth remembering
+        if window.fullscreen().is_none() {
+            self.config.window = WindowGeometry::from_window(window);
+        }
+
+        if let Err(error) = self.config.save() {
+            log::warn!("Failed to save the configuration: {}", error);
         }
     }
 
     fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
         let sampler = BasicFilteringSampler::new(device);
-        let (dynamic_resources_0, _) = create_dynamic_resources((width, height), &sampler, device);
-        let (dynamic_resources_1, _) = create_dynamic_resources((width, height), &sampler, device);
+        let (dynamic_resources_0, _) = create_dynamic_resources((width, height), self.format, &sampler, device);
+        let (dynamic_resources_1, _) = create_dynamic_resources((width, height), self.format, &sampler, device);
+        let (dynamic_resources_2, _) = create_dynamic_resources((width, height), self.format, &sampler, device);
 
-        self.dynamic_resources = [dynamic_resources_0, dynamic_resources_1];
+        self.dynamic_resources = [dynamic_resources_0, dynamic_resources_1, dynamic_resources_2];
 
-        let ubuffer = DrawspaceScales::new(
-            glam::Vec2::new(width as f32, height as f32),
-            glam::Vec2::new(2.5, 1.40625)
-        );
+        self.crt_frame  = RenderTexture::new((width, height), self.format, true, device);
+        self.crt_source = self.crt_pass.source_bindgroup(&self.crt_frame, device);
 
-        queue.write_buffer(&self.uniform_buffer, 0, cast_struct_to_u8_slice(&ubuffer));
+        self.drawspace.resize(queue, width, height);
 
-        let radius_scale: f32 = 1.0;
+        self.snapped_vertices = snap_vertices(self.drawspace.scales());
+        queue.write_buffer(&self.vertex_buffer, 0, cast_slice_to_u8_slice(&self.snapped_vertices));
+        self.help.resize(width, height);
+        self.power.resize(width, height);
 
         // adapt the blur radius according to current pixel density
         // the factors are tuned via T&E
-        let blur_radius = ((ubuffer.density as f32 / 204.0) * 40.0 * radius_scale) as i32;
+        let blur_radius = ((self.drawspace.scales().density / 204.0) * 40.0 * self.blur_scale) as i32;
         let blur_sigma  = (blur_radius as f32) * 0.25;
 
         self.blur_table_bindgroup = create_blur_table_bindgroup(
@@ -553,19 +594,69 @@ impl ExecDraw for DigiClock {
             true, true,
             device, queue
         ).0;
+
+        // the scanlines get denser along with the pixels, down to one on every other row
+        self.crt_pass.set_params(queue, &CrtParams {
+            scanline_spacing: ((self.drawspace.scales().density / 204.0) * 3.0).max(2.0),
+            ..CrtParams::default()
+        });
     }
 
-    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
+    fn update(self: &mut Self, _dt: f32, _input: &InputState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        let now = time::now();
+        let mut cdata = ClockData {
+            use_texture_brightness: self.texture_brightness as u32,
+            ..calculate_clock_data(&now, self.is_12_hours, self.selector, self.colon_style, &self.layout)
+        };
+
+        // the previous minute's LEDs fade out, the current ones in
+        if let Some((before, progress)) = segment_transition(&now, self.animation_speed) {
+            cdata.previous_flagset = calculate_clock_data(&before, self.is_12_hours, self.selector, self.colon_style, &self.layout).flagset;
+            cdata.transition = progress;
+        }
+
+        // the solid palettes' colors, as loaded and edited
+        if (self.selector as usize) < self.palettes.len() {
+            let colors = self.palettes.get(self.selector as usize);
+            cdata.digits_color = colors[0];
+            cdata.colon_color  = colors[1];
+            cdata.labels_color = colors[2];
+        }
+
+        if let Some(slot) = self.editor.slot() {
+            cdata.edited_group      = slot as u32 + 1;
+            cdata.edited_brightness = if self.editor.blink_on() { 1.0 } else { EDIT_BLINK_BRIGHTNESS };
+        }
+
+        // what's lit now, the fading LEDs don't count
+        self.power_history.record(now.timestamp(), lit_segments(cdata.flagset));
+        self.clock_data = cdata;
+
+        if self.power_display {
+            self.update_power_panel();
+        }
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
         // Data flow:
-        // [Forward Pass] => [Horizontal Blur Pass] => [Vertical Blur+Compositing Pass] => [Present]
+        //
+        //   [Forward Pass] --> forward image --> [Horizontal Blur Pass] --> [Vertical Blur Pass] --> glow
+        //                            |                                                                |
+        //                            +--> [Composite Pass] (sharp + glow, additive or screen) <-------+
+        //                                        |
+        //                                        +--> [Present] (or the CRT pass first, see `crt_frame`)
+        //
+        // The forward image is read twice, by the first blur pass and by the composite pass,
+        // hence its own texture, the blur passes' results go to the other two.
 
         let triangle_render_dst = &self.dynamic_resources[0].render_texture_view;
-        let horzblur_render_src = &self.dynamic_resources[0].render_texture_bindgroup;
+        let forward_image       = &self.dynamic_resources[0].render_texture_bindgroup;
         let horzblur_render_dst = &self.dynamic_resources[1].render_texture_view;
         let vertblur_render_src = &self.dynamic_resources[1].render_texture_bindgroup;
-        let vertblur_render_dst = texview;
+        let vertblur_render_dst = &self.dynamic_resources[2].render_texture_view;
+        let glow_image          = &self.dynamic_resources[2].render_texture_bindgroup;
 
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -585,18 +676,18 @@ impl ExecDraw for DigiClock {
 
             rpass.set_pipeline(&self.forward_pipeline);
             rpass.set_bind_group(0, &self.resource_texture_bindgroup, &[]);
-            rpass.set_bind_group(1, &self.uniform_buffer_bindgroup,   &[]);
+            rpass.set_bind_group(1, self.drawspace.bind_group(),      &[]);
 
             rpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
             rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
 
-            let cdata = calculate_clock_data(self.is_12_hours, self.selector);
-            rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&cdata));
+            rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&self.clock_data));
 
             rpass.draw_indexed(0..INDEX_COUNT as u32, 0, 0..1);
         }
 
-        let mut apply_blur_pass = |source: &wgpu::BindGroup, destination: &wgpu::TextureView, vertical: bool| {
+        // the blur and composite passes are all fullscreen quads, each one clearing its destination
+        let mut apply_pass = |pipeline: &FullscreenQuadPipeline, bind_groups: &[&wgpu::BindGroup], push_constants: &[u8], destination: &wgpu::TextureView| {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label:                    None,
                 depth_stencil_attachment: None,
@@ -612,55 +703,155 @@ impl ExecDraw for DigiClock {
                 })]
             });
 
-            let vertical: u32 = if vertical { 1 } else { 0 };
+            pipeline.draw(&mut rpass, bind_groups, push_constants);
+        };
+
+        let blur_bind_groups = |source| [source, self.drawspace.bind_group(), &self.blur_table_bindgroup];
 
-            rpass.set_pipeline(&self.filter_pipeline);
-            rpass.set_bind_group(0, source, &[]);
-            rpass.set_bind_group(1, &self.uniform_buffer_bindgroup, &[]);
-            rpass.set_bind_group(2, &self.blur_table_bindgroup, &[]);
-            rpass.set_bind_group(3, horzblur_render_src, &[]); // common for both
-            rpass.set_push_constants(wgpu::ShaderStages::FRAGMENT, 0, cast_slice_to_u8_slice(&[vertical]));
+        let horizontal: u32 = 0;
+        let vertical:   u32 = 1;
+        apply_pass(&self.filter_pipeline, &blur_bind_groups(forward_image),       cast_struct_to_u8_slice(&horizontal), horzblur_render_dst);
+        apply_pass(&self.filter_pipeline, &blur_bind_groups(vertblur_render_src), cast_struct_to_u8_slice(&vertical),   vertblur_render_dst);
 
-            rpass.draw(0..4, 0..1);
+        let composite = CompositeInfo {
+            glow_intensity: self.glow_intensity,
+            blend_mode:     self.glow_blend.shader_value(),
+            alpha_mode:     if self.transparent { self.alpha_mode } else { 0 } // opaque black otherwise
+        };
+        let (composite_pipeline, composite_dst) = if self.crt {
+            (&self.crt_composite_pipeline, &self.crt_frame.view)
+        } else {
+            (&self.composite_pipeline, texview)
         };
+        apply_pass(composite_pipeline, &[forward_image, glow_image], cast_struct_to_u8_slice(&composite), composite_dst);
 
-        apply_blur_pass(horzblur_render_src, horzblur_render_dst, false);
-        apply_blur_pass(vertblur_render_src, vertblur_render_dst, true);
+        if self.crt {
+            self.crt_pass.apply(&mut encoder, &self.crt_source, texview);
+        }
 
         queue.submit(std::iter::once(encoder.finish()));
+
+        if self.power_display {
+            self.power.draw(texview, device, queue);
+        }
+
+        if self.show_help {
+            self.help.draw(texview, device, queue);
+        }
     }
 }
 
 fn main() {
-    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    logging::init("digital");
+
+    let (args, app) = Cli::new("digital", "Seven-segment digital clock with glow")
+        .switch("--12h", "Show the time in 12-hour format")
+        .switch("--24h", "Show the time in 24-hour format")
+        .option("--color", "INDEX", "Color palette index, cycled with Space (default: picked by the OS theme)")
+        .option("--blur-scale", "FACTOR", "Multiplier of the glow's radius")
+        .option("--glow", "FACTOR", "Multiplier of the glow's brightness, 0 turns it off")
+        .option("--glow-blend", "MODE", "How the glow is laid over the segments, add or screen (default: add)")
+        .switch("--crt", "Start with the CRT look (scanlines), toggled with R")
+        .option("--first-day", "DAY", "Day of the leftmost weekday label, monday or sunday (default: sunday, as in the bundled layout)")
+        .parse();
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    // command line flags take priority over the config file
+    // snapshots ignore the config file, so that they're reproducible
+    let mut config = match args.snapshot {
+        None    => ClockConfig::<DigitalSettings>::load("digital"),
+        Some(_) => ClockConfig::<DigitalSettings>::default()
+    };
+
+    if app.flag("--12h") { config.settings.is_12_hours = true;  }
+    if app.flag("--24h") { config.settings.is_12_hours = false; }
+    if app.flag("--crt")  { config.settings.crt = true; }
+
+    if let Some(value) = app.value("--color") {
+        match value.parse() {
+            Ok(index) => config.settings.palette = Some(index),
+            Err(_)    => log::warn!("Ignoring invalid palette index '{}'", value)
+        }
+    }
+
+    if let Some(value) = app.value("--blur-scale") {
+        match value.parse::<f32>() {
+            Ok(scale) if scale >= 0.0 => config.settings.blur_scale = scale,
+            _ => log::warn!("Ignoring invalid blur scale '{}'", value)
+        }
+    }
+
+    if let Some(value) = app.value("--glow") {
+        match value.parse::<f32>() {
+            Ok(intensity) if intensity.is_finite() && intensity >= 0.0 => config.settings.glow_intensity = intensity,
+            _ => log::warn!("Ignoring invalid glow intensity '{}'", value)
+        }
+    }
+
+    if let Some(value) = app.value("--glow-blend") {
+        match parse_glow_blend(value) {
+            Some(blend) => config.settings.glow_blend = blend,
+            None        => log::warn!("Ignoring invalid glow blend '{}', expected add or screen", value)
+        }
+    }
+
+    let intensity = config.settings.glow_intensity;
+    if !(intensity.is_finite() && intensity >= 0.0) {
+        log::warn!("Ignoring invalid glow intensity {} from the config file", intensity);
+        config.settings.glow_intensity = 1.0;
+    }
+
+    let speed = config.settings.animation_speed;
+    if !(speed.is_finite() && speed >= 0.0) {
+        log::warn!("Ignoring invalid animation speed {} from the config file", speed);
+        config.settings.animation_speed = 1.0;
+    }
+
+    let mut layout = INDICATOR_LAYOUT;
+
+    if let Some(value) = app.value("--first-day") {
+        match parse_first_day(value) {
+            Some(day) => layout = layout.with_first_day(day),
+            None      => log::warn!("Ignoring invalid first day '{}', expected monday or sunday", value)
+        }
+    }
+
+    if let Err(error) = layout.validate() {
+        fatal_error("Digital Clock", &format!("Invalid indicator layout: {}", error));
+    }
+
+    let geometry = config.window;
+    let _ = STARTUP_CONFIG.set(config);
+    let _ = STARTUP_LAYOUT.set(layout);
+    let _ = STARTUP_PALETTES.set(load_palettes(args.palette_path("digital")));
+
+    if args.take_snapshot::<DigiClock>((1024, 576), Some(wgpu::Features::PUSH_CONSTANTS), args.msaa) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Digital Clock", &format!("Failed to initialize the windowing system: {}", error)));
     #[allow(unused_mut)]
     let mut builder = winit::window::WindowBuilder::new();
-    let window = builder
+    let builder = builder
         .with_inner_size(winit::dpi::LogicalSize { width: 1024.0, height: 576.0 })
-        .with_title("Digital Clock")
+        .with_title("Digital Clock");
+    let builder = geometry.apply_to_window(builder);
+    let window = args.apply_to_window(builder, &event_loop)
         .build(&event_loop)
-        .unwrap();
+        .unwrap_or_else(|error| fatal_error("Digital Clock", &format!("Failed to create the window: {}", error)));
 
-    pollster::block_on(run::<DigiClock>(
+    pollster::block_on(run_with_options::<DigiClock>(
         event_loop, window,
-        Some(wgpu::Features::PUSH_CONSTANTS)
+        Some(wgpu::Features::PUSH_CONSTANTS),
+        RunOptions { msaa_sample_count: args.msaa, ..args.run_options() }
     ));
 }
 
-// 7-segment display segment mapping table
-const DIGIT_SEGMENT_FLAGS: [u32; 10] = [
-    0b1110111,
-    0b1000100,
-    0b1011011,
-    0b1011101,
-    0b1101100,
-    0b0111101,
-    0b0111111,
-    0b1010100,
-    0b1111111,
-    0b1111101
-];
-
 // The vertex buffer containing 2D position and island ID
 const VERTEX_COUNT: usize = 160;
 const VERTICES: [Vtx2ID; VERTEX_COUNT] = [
@@ -0,0 +1,275 @@
+#![cfg_attr(
+    all(
+        target_os = "windows",
+        not(feature = "console"),
+    ),
+    windows_subsystem = "windows"
+)]
+use clockutils::{
+    run_with_options, cast_struct_to_u8_slice, get_resource_folder_for, load_shader, fatal_error,
+    u32_col_to_wgpu_col_linear,
+    ExecDraw, FrameInfo, InputState, SingleUniformBuffer, ResourceTexture, BasicFilteringSampler,
+    camera::{self, Projection},
+    cli::Cli, logging, time,
+    font::BitmapFontRenderer,
+    keys::{self, KeyBinding},
+    target::{requested_sample_count, ColorTarget, DepthTexture, PipelineBuilder, TargetConfig, TargetPipeline},
+    clocks::flip::{calc_slot_flips, is_flipping, until_next_flip}
+};
+
+#[repr(C, align(8))]
+struct MatrixData {
+    matrix: glam::Mat4
+}
+
+/// Of each slot, padded to whole vectors of the uniform arrays, see flip.wgsl
+#[repr(C, align(16))]
+struct FlipState {
+    angles: [f32; 8],
+    digits: [u32; 8] // from | to << 8
+}
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Matched on in `onkey`, and listed by the help panel
+const KEYBINDINGS: &[KeyBinding] = &[
+    ("O", "Orthographic/perspective camera"),
+    ("H", "Show/hide this help")
+];
+
+/// The camera looks at the flaps from this far, a bit above the hinges to see them fall
+const CAMERA_DISTANCE: f32 = 8.0;
+const CAMERA_HEIGHT:   f32 = 1.0;
+/// Half the size of the area that's always visible around the cards
+const EXTENT: glam::Vec2 = glam::Vec2::new(8.0, 4.0);
+
+const BACKGROUND_COLOR: u32 = 0x15171B_FF;
+
+fn calc_matrix(resolution: glam::Vec2, projection: Projection) -> MatrixData {
+    let matrix = camera::view_projection(
+        projection,
+        glam::Vec3::new(0.0, CAMERA_HEIGHT, CAMERA_DISTANCE),
+        glam::Vec3::ZERO,
+        glam::Vec3::Y,
+        resolution,
+        EXTENT,
+        (1.0, 100.0)
+    );
+
+    MatrixData { matrix }
+}
+
+/// The Clock's mechanism:
+/// A split-flap display, the cards of the mechanical counter's sprite sheet cut in half at the middle.
+/// Each of the six slots has ten flaps, one per digit, hinged at the slot's middle: a flap shows the upper half
+/// of its digit on its front, and the lower half of the next digit on its back. When a slot's digit changes,
+/// the old digit's flap falls forward around the hinge, uncovering the new digit's upper half behind it
+/// and landing with the new digit's lower half on its back.
+///
+/// As on the mechanical counter, all of it is one instanced draw: 6 slots x 10 flaps = 60 quads, their corners
+/// worked out by the vertex shader, which hides the flaps that can't be seen in the stack
+struct FlipClock {
+    pipeline:     TargetPipeline,
+    matrix:       SingleUniformBuffer,
+    state_buffer: SingleUniformBuffer,
+    bind_group:   wgpu::BindGroup,
+
+    target_config: TargetConfig, // multisampled as requested (--msaa), as far as supported
+    color_target:  ColorTarget,
+    depth_texture: DepthTexture,
+
+    resolution: glam::Vec2,
+    projection: Projection,
+
+    show_help: bool,
+    help:      BitmapFontRenderer
+}
+
+impl ExecDraw for FlipClock {
+    fn setup(
+        config:   &wgpu::SurfaceConfiguration,
+        adapter:  &wgpu::Adapter,
+        device:   &wgpu::Device,
+        queue:    &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> {
+        let resources = get_resource_folder_for("flip").map_err(|error| error.to_string())?;
+
+        // smooths the flaps' edges while they're falling
+        let target_config = TargetConfig::new(config.format, Some(DEPTH_FORMAT))
+            .with_supported_sample_count(requested_sample_count(), adapter, device);
+
+        let matrix       = SingleUniformBuffer::new::<MatrixData>(device, wgpu::ShaderStages::VERTEX);
+        let state_buffer = SingleUniformBuffer::new::<FlipState>(device, wgpu::ShaderStages::VERTEX);
+
+        // the digit cards side by side, each a tenth of the width, see digits.wgsl
+        let sprites = ResourceTexture::new(
+            resources.join("textures/haettenschweiler_digits.png").as_path().to_str().unwrap(),
+            device,
+            queue
+        )?;
+
+        let sampler = BasicFilteringSampler::new(device);
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                SingleUniformBuffer::default_layout_entry(0, &matrix),
+                SingleUniformBuffer::default_layout_entry(1, &state_buffer),
+                ResourceTexture::default_layout_entry(2),
+                BasicFilteringSampler::default_layout_entry(3)
+            ]
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   None,
+            layout:  &bind_group_layout,
+            entries: &[
+                matrix.get_entry(0),
+                state_buffer.get_entry(1),
+                sprites.get_entry(2),
+                sampler.get_entry(3)
+            ]
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &bind_group_layout ],
+            push_constant_ranges: &[]
+        });
+
+        let shader = load_shader(device, &resources.join("shaders/flip.wgsl"))?;
+
+        // both sides of the flaps show, the front facing the viewer while upright, the back once fallen
+        let pipeline = PipelineBuilder::new(&target_config, &pipeline_layout, &shader)
+            .primitive(wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            })
+            .build(device);
+
+        let resolution = glam::Vec2::new(config.width as f32, config.height as f32);
+        let projection = Projection::default();
+        queue.write_buffer(&matrix.buffer, 0, cast_struct_to_u8_slice(&calc_matrix(resolution, projection)));
+
+        Ok(Self {
+            pipeline, matrix, state_buffer, bind_group,
+            color_target:  ColorTarget::new((config.width, config.height), &target_config, device),
+            depth_texture: DepthTexture::new((config.width, config.height), &target_config, device),
+            target_config,
+            resolution, projection,
+            show_help: false,
+            help:      keys::help_panel(KEYBINDINGS, config, device)
+        })
+    }
+
+    fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.resolution = glam::Vec2::new(width as f32, height as f32);
+        queue.write_buffer(&self.matrix.buffer, 0, cast_struct_to_u8_slice(&calc_matrix(self.resolution, self.projection)));
+
+        self.color_target  = ColorTarget::new((width, height), &self.target_config, device);
+        self.depth_texture = DepthTexture::new((width, height), &self.target_config, device);
+        self.help.resize(width, height);
+    }
+
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, queue: &wgpu::Queue) {
+        match keys::pressed(&event, KEYBINDINGS) {
+            Some("O") => {
+                self.projection = self.projection.toggled();
+                queue.write_buffer(&self.matrix.buffer, 0, cast_struct_to_u8_slice(&calc_matrix(self.resolution, self.projection)));
+            },
+            Some("H") => {
+                self.show_help = !self.show_help;
+            },
+            _ => {}
+        }
+    }
+
+    fn significant_change(self: &mut Self) -> bool {
+        // the flaps lie still once they've fallen, until the next second
+        is_flipping(&time::now())
+    }
+
+    fn next_deadline(self: &Self, now: time::Instant) -> Option<time::Instant> {
+        time::deadline_after(now, until_next_flip(&time::now()))
+    }
+
+    fn update(self: &mut Self, _dt: f32, _input: &InputState, _device: &wgpu::Device, queue: &wgpu::Queue) {
+        let flips = calc_slot_flips(&time::now());
+
+        let mut state = FlipState { angles: [0.0; 8], digits: [0; 8] };
+        for (i, flip) in flips.iter().enumerate() {
+            state.angles[i] = flip.angle;
+            state.digits[i] = flip.from as u32 | (flip.to as u32) << 8;
+        }
+
+        queue.write_buffer(&self.state_buffer.buffer, 0, cast_struct_to_u8_slice(&state));
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    None,
+                depth_stencil_attachment: Some(self.depth_texture.attachment(&self.target_config, false)),
+                timestamp_writes:    None,
+                occlusion_query_set: None,
+                color_attachments:   &[Some(self.color_target.attachment(
+                    &self.target_config, texview,
+                    wgpu::LoadOp::Clear(u32_col_to_wgpu_col_linear(BACKGROUND_COLOR))
+                ))]
+            });
+
+            self.pipeline.set(&mut rpass, &self.target_config);
+            rpass.set_bind_group(0, &self.bind_group, &[]);
+
+            // 6 slots x 10 flaps, 4 vertices each, all of it in a single instanced draw
+            rpass.draw(0..4, 0..60);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        if self.show_help {
+            self.help.draw(texview, device, queue);
+        }
+    }
+}
+
+fn main() {
+    logging::init("flip");
+
+    let (mut args, _app) = Cli::new("flip", "Split-flap clock, a flap falls for every digit that changes").parse();
+
+    // the flaps settle within each second, so 3 seconds make for a nice looping GIF
+    if args.record_seconds.is_none() {
+        args.record_seconds = Some(3.0);
+    }
+
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    if args.take_snapshot::<FlipClock>((1024, 512), None, 1) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Flip Clock", &format!("Failed to initialize the windowing system: {}", error)));
+    #[allow(unused_mut)]
+    let mut builder = winit::window::WindowBuilder::new();
+    let builder = builder
+        .with_inner_size(winit::dpi::LogicalSize { width: 1024.0, height: 512.0 })
+        .with_title("Flip Clock");
+    let window = args.apply_to_window(builder, &event_loop)
+        .build(&event_loop)
+        .unwrap_or_else(|error| fatal_error("Flip Clock", &format!("Failed to create the window: {}", error)));
+
+    pollster::block_on(run_with_options::<FlipClock>(
+        event_loop, window,
+        None,
+        args.run_options()
+    ));
+}
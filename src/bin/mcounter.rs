@@ -5,14 +5,19 @@
     ),
     windows_subsystem = "windows"
 )]
-use std::{borrow::Cow, fs::read_to_string};
 use wgpu::{RenderPipelineDescriptor, PushConstantRange};
 use clockutils::{
-    run, cast_struct_to_u8_slice, get_resource_folder_for,
-    ExecDraw, SingleUniformBuffer, DrawspaceScales, RenderTexture, ResourceTexture, BasicFilteringSampler,
-    SURFACE_FORMAT
+    run_with_options, cast_struct_to_u8_slice, get_resource_folder_for, load_shader, load_png_rgba8, fatal_error,
+    ExecDraw, FrameInfo, InputState, RunOptions, SingleUniformBuffer, ResourceTexture, BasicFilteringSampler,
+    camera::{self, Projection},
+    cli::Cli, logging, time,
+    font::BitmapFontRenderer,
+    keys::{self, KeyBinding},
+    target::{ColorTarget, DepthTexture, TargetConfig},
+    clocks::mcounter::{
+        calc_wheel_angles, check_sprite_sheet, is_animating, parse_countdown, until_next_animation, urgency_color, Countdown, WHEEL_COLOR
+    }
 };
-use chrono::{Local, Timelike, DateTime, TimeDelta};
 
 #[repr(C, align(8))]
 struct MatrixData {
@@ -20,114 +25,169 @@ struct MatrixData {
 }
 
 #[repr(C, align(8))]
-struct RotationAngles {
-    angles: [f32; 6]
+struct WheelState {
+    angles:       [f32; 6],
+    env_strength: f32
 }
 
-const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
-
-/// Nanosecs. changeable but should not exceed 1s
-const ANIM_DURATION: u32 = 500_000_000;
-
-/// We calculate the beginning and ending angles for each wheel.
-/// The angles are proportional to the digit itself.
-/// 
-/// Here, the ending digit set is the digit set of current time.
-/// And the beginning digit set is of `ANIM_DURATION` from current time.
-/// The animation/transition happens at first `ANIM_DURATION` of the current second.
-/// During this time period, the resultant angle is
-/// calculated from lerping the beginning and ending angles.
-/// The rest of the time, the wheel stays at ending angle.
-/// 
-/// All wheels must rotate in one direction.
-/// To prevent reverse rotation, for high to low digit transition like `9 -> 0`,
-/// the ending digit is added with `10` to make the transition look like `9 -> 10`
-fn calc_wheel_angles() -> [f32; 6] {
-    fn extract_digits_from_time(time: &DateTime<Local>) -> [u8; 6] {
-        let hours   = time.hour();
-        let minutes = time.minute();
-        let seconds = time.second();
-    
-        [
-            (hours   / 10) as u8, (hours   % 10) as u8,
-            (minutes / 10) as u8, (minutes % 10) as u8,
-            (seconds / 10) as u8, (seconds % 10) as u8,
-        ]
-    }
+/// Of each wheel's cards, padded to whole vectors of the uniform array, see mcounter.wgsl
+#[repr(C, align(16))]
+struct WheelColors {
+    colors: [u32; 8]
+}
 
-    fn angle_for_digit(digit: u8) -> f32 {
-        (digit as f32 * 0.1) * std::f32::consts::TAU
-    }
+#[repr(C, align(8))]
+struct PlateInfo {
+    center: glam::Vec2,
+    size:   glam::Vec2,
+    color:  u32
+}
 
-    let mut angles: [f32; 6] = [0.0; 6];
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
-    let now = Local::now();
-    let now_digits = extract_digits_from_time(&now);
+/// Matched on in `onkey`, and listed by the help panel
+const KEYBINDINGS: &[KeyBinding] = &[
+    ("O", "Orthographic/perspective camera"),
+    ("H", "Show/hide this help")
+];
+
+/// The camera looks at the wheels' front from this far, mirrors `CAMERA_POS` in mcounter.wgsl
+const CAMERA_DISTANCE: f32 = 8.0;
+/// Half the size of the area that's always visible around the fronts of the cards
+const EXTENT: glam::Vec2 = glam::Vec2::new(8.0, 4.0);
+
+// Horizontal centers of the six wheels, in drawing units.
+// Mirrors `BASE_POS + pair_no * PAIR_WIDTH + wheel_no * WHEEL_WIDTH` in mcounter.wgsl, keep them in sync
+const WHEEL_X_OFFSETS: [f32; 6] = [-6.125, -3.875, -1.125, 1.125, 3.875, 6.125];
+const CARD_HALF_WIDTH: f32 = 1.0;
+
+/// How much of the environment the cards reflect at grazing angles, 0 => matte cards
+const ENV_STRENGTH: f32 = 0.15;
+
+// The surroundings the cards reflect, as RGBA: a bright sky fading to the horizon above a dim floor
+const ENV_SKY_ZENITH:    [u8; 4] = [0xE8, 0xF0, 0xFF, 0xFF];
+const ENV_SKY_HORIZON:   [u8; 4] = [0x9C, 0xAC, 0xC4, 0xFF];
+const ENV_FLOOR_HORIZON: [u8; 4] = [0x50, 0x4A, 0x44, 0xFF];
+const ENV_FLOOR_NADIR:   [u8; 4] = [0x14, 0x12, 0x10, 0xFF];
+
+const SEPARATOR_THICKNESS: f32 = 0.08;
+const SEPARATOR_HEIGHT:    f32 = 3.6; // a bit taller than the cards (3.0)
+const SEPARATOR_COLOR:     u32 = 0x2A2A2A_FF;
+
+/// `--countdown` and `--loop`, read by `setup`
+static COUNTDOWN: std::sync::OnceLock<(chrono::TimeDelta, bool)> = std::sync::OnceLock::new();
+
+/// Dividers go halfway between neighbouring wheels
+fn separator_positions() -> [f32; 5] {
+    std::array::from_fn(|i| (WHEEL_X_OFFSETS[i] + WHEEL_X_OFFSETS[i + 1]) * 0.5)
+}
 
-    let nanos = now.nanosecond();
+/// Draws a flat rectangle in front of the wheels, expects the separator pipeline to be set
+fn draw_plate(rpass: &mut wgpu::RenderPass, center: (f32, f32), size: (f32, f32), color: u32) {
+    let plate = PlateInfo {
+        center: glam::Vec2::new(center.0, center.1),
+        size:   glam::Vec2::new(size.0, size.1),
+        color
+    };
 
-    if nanos > ANIM_DURATION {
-        for (i, digit) in now_digits.iter().enumerate() {
-            angles[i] = angle_for_digit(*digit);
-        }
-        return angles;
-    }
+    rpass.set_push_constants(
+        wgpu::ShaderStages::VERTEX_FRAGMENT,
+        0,
+        cast_struct_to_u8_slice(&plate)
+    );
 
-    let ago = now - TimeDelta::nanoseconds(ANIM_DURATION as i64);
-    let ago_digits = extract_digits_from_time(&ago);
+    rpass.draw(0..4, 0..1);
+}
 
-    let t = (nanos as f32) / (ANIM_DURATION as f32);
-    // t = ease_out_bounce(t);
-    // Or use your own favorite easing
+/// Thin vertical dividing plate between two wheels
+fn draw_separator(rpass: &mut wgpu::RenderPass, x_pos: f32, height: f32, color: u32) {
+    draw_plate(rpass, (x_pos, 0.0), (SEPARATOR_THICKNESS, height), color);
+}
 
-    for i in 0..now_digits.len() {
-        let digit_ago = ago_digits[i];
-        let digit_now = if now_digits[i] < ago_digits[i] {
-            now_digits[i] + 10
-        } else {
-            now_digits[i]
-        };
+/// 2x2 cubemap faces in the +X, -X, +Y, -Y, +Z, -Z order. The side faces get the sky on their upper
+/// and the floor on their lower row, the linear filtering then blends them into gradients
+fn environment_faces() -> Vec<u8> {
+    let side = [ENV_SKY_HORIZON, ENV_SKY_HORIZON, ENV_FLOOR_HORIZON, ENV_FLOOR_HORIZON];
+    let up   = [ENV_SKY_ZENITH; 4];
+    let down = [ENV_FLOOR_NADIR; 4];
 
-        let angle_ago = angle_for_digit(digit_ago);
-        let angle_now = angle_for_digit(digit_now);
+    [side, side, up, down, side, side].iter().flatten().flatten().copied().collect()
+}
 
-        angles[i] = (1.0 - t) * angle_ago + t * angle_now; // lerp
-    }
+fn calc_matrix(resolution: glam::Vec2, projection: Projection) -> MatrixData {
+    let matrix = camera::view_projection(
+        projection,
+        glam::Vec3::new(0.0, 0.0, CAMERA_DISTANCE),
+        glam::Vec3::ZERO,
+        glam::Vec3::Y,
+        resolution,
+        EXTENT,
+        (1.0, 100.0)
+    );
 
-    angles
+    MatrixData { matrix }
 }
 
-fn calc_matrix(resolution: glam::Vec2, extent: glam::Vec2) -> MatrixData {
-    let scale = {
-        let dscales = DrawspaceScales::new(resolution, extent);
-
-        // Since we are working in 3D,
-        // apply the drawspace scales as a scaling matrix
-        // only for x, y axis
-        glam::Mat4::from_scale(glam::Vec3 {
-            x: dscales.scale.x,
-            y: dscales.scale.y,
-            z: 1.0
-        })
-    };
+/// The one bind group of both pipelines, rebuilt when another sprite sheet is dropped onto the window
+fn create_bind_group(
+    layout:      &wgpu::BindGroupLayout,
+    matrix:      &wgpu::Buffer,
+    sprites:     &ResourceTexture,
+    sampler:     &BasicFilteringSampler,
+    environment: &ResourceTexture,
+    colors:      &wgpu::Buffer,
+    device:      &wgpu::Device
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label:   None,
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: matrix.as_entire_binding() },
+            sprites.get_entry(1),
+            sampler.get_entry(2),
+            environment.get_entry(3),
+            wgpu::BindGroupEntry { binding: 4, resource: colors.as_entire_binding() }
+        ]
+    })
+}
 
-    let proj = glam::Mat4::perspective_rh(0.5_f32.atan() * 2.0, 1.0, 1.0, 100.0);
+/// Any image the image crate decodes, checked to fit before it's uploaded
+fn load_sprite_sheet(path: &std::path::Path, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<ResourceTexture, String> {
+    let path = path.to_str().ok_or_else(|| format!("Can't open {}, the path isn't valid UTF-8", path.display()))?;
+    let (width, height, data) = load_png_rgba8(path)?;
 
-    let view = glam::Mat4::look_at_rh(
-        glam::Vec3::new(0.0, 0.0, 8.0),
-        glam::Vec3::ZERO,
-        glam::Vec3::Y
-    );
+    check_sprite_sheet(width, height, device.limits().max_texture_dimension_2d)?;
 
-    MatrixData { matrix: scale * proj * view }
+    Ok(ResourceTexture::from_rgba8(width, height, &data, device, queue))
 }
 
 struct MechCounter {
-    pipeline:       wgpu::RenderPipeline,
-    uniform_buffer: wgpu::Buffer,
-    bind_group:     wgpu::BindGroup,
-
-    depth_view:     wgpu::TextureView
+    pipeline:           wgpu::RenderPipeline,
+    separator_pipeline: wgpu::RenderPipeline,
+    uniform_buffer:     wgpu::Buffer,
+    colors_buffer:      wgpu::Buffer,
+    bind_group:         wgpu::BindGroup,
+    bind_group_layout:  wgpu::BindGroupLayout, // kept with the rest of the bind group, to swap the sprite sheet
+    environment:        ResourceTexture,
+    sampler:            BasicFilteringSampler,
+
+    target_config:      TargetConfig, // the runner's frame, multisampled as requested (--msaa)
+    color_target:       ColorTarget,
+    depth_texture:      DepthTexture,
+
+    resolution:         glam::Vec2,
+    projection:         Projection,
+
+    separators:         [f32; 5],
+    separator_color:    u32,
+
+    wheel_colors:       [u32; 6],
+    wheel_angles:       [f32; 6], // where the wheels stand, worked out by `update`
+    countdown:          Option<Countdown>,
+    urgency_color:      u32, // of every wheel while counting down, see `urgency_color`
+
+    show_help:          bool,
+    help:               BitmapFontRenderer
 }
 
 impl ExecDraw for MechCounter {
@@ -135,11 +195,16 @@ impl ExecDraw for MechCounter {
         config:   &wgpu::SurfaceConfiguration,
         _adapter: &wgpu::Adapter,
         device:   &wgpu::Device,
-        queue:    &wgpu::Queue
-    ) -> Self {
-        let resources = get_resource_folder_for("mcounter").unwrap();
+        queue:    &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> {
+        let resources = get_resource_folder_for("mcounter").map_err(|error| error.to_string())?;
+
+        // the runner's multisampling smooths the cards' and the plates' edges
+        let target_config = TargetConfig::frame(config.format, Some(DEPTH_FORMAT));
 
         let umatrix = SingleUniformBuffer::new::<MatrixData>(device, wgpu::ShaderStages::VERTEX_FRAGMENT);
+        let ucolors = SingleUniformBuffer::new::<WheelColors>(device, wgpu::ShaderStages::FRAGMENT);
 
         // The digit fonts as a sprite sheet.
         // Each digit occupies 10% of the whole texture's width
@@ -148,7 +213,9 @@ impl ExecDraw for MechCounter {
             resources.join("textures/haettenschweiler_digits.png").as_path().to_str().unwrap(),
             device,
             queue
-        );
+        )?;
+
+        let environment = ResourceTexture::from_cube_faces(2, &environment_faces(), device, queue);
 
         let sampler = BasicFilteringSampler::new(device);
 
@@ -159,51 +226,34 @@ impl ExecDraw for MechCounter {
             entries: &[
                 SingleUniformBuffer::default_layout_entry(0, &umatrix),
                 ResourceTexture::default_layout_entry(1),
-                BasicFilteringSampler::default_layout_entry(2)
+                BasicFilteringSampler::default_layout_entry(2),
+                ResourceTexture::cube_layout_entry(3),
+                SingleUniformBuffer::default_layout_entry(4, &ucolors)
             ]
         });
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label:   None,
-            layout:  &bind_group_layout,
-            entries: &[
-                umatrix.get_entry(0),
-                sprites.get_entry(1),
-                sampler.get_entry(2)
-            ]
-        });
+        let bind_group = create_bind_group(&bind_group_layout, &umatrix.buffer, &sprites, &sampler, &environment, &ucolors.buffer, device);
 
         // In the push constants, we shove in the angles for all six wheels
-        // 6 x sizeof(f32) = 6 x 4 = 24
+        // and the reflection strength for the fragment shader
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label:                None,
             bind_group_layouts:   &[ &bind_group_layout ],
             push_constant_ranges: &[
                 PushConstantRange {
-                    stages: wgpu::ShaderStages::VERTEX,
-                    range:  0..24
+                    stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    range:  0..std::mem::size_of::<WheelState>() as u32
                 }
             ]
         });
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label:  None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
-                read_to_string(resources.join("shaders/mcounter.wgsl")).unwrap().as_str()
-            ))
-        });
+        let shader = load_shader(device, &resources.join("shaders/mcounter.wgsl"))?;
 
         let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
             label:         None,
             layout:        Some(&pipeline_layout),
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: DEPTH_FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default()
-            }),
-            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: target_config.depth_stencil_state(true, wgpu::CompareFunction::Less),
+            multisample:   target_config.multisample_state(),
             multiview:     None,
             vertex: wgpu::VertexState {
                 module:      &shader,
@@ -213,7 +263,7 @@ impl ExecDraw for MechCounter {
             fragment: Some(wgpu::FragmentState {
                 module:      &shader,
                 entry_point: "fs_main",
-                targets:     &[ Some(SURFACE_FORMAT.into()) ]
+                targets:     &[ Some(config.format.into()) ]
             }),
             primitive: wgpu::PrimitiveState {
                 topology:     wgpu::PrimitiveTopology::TriangleStrip,
@@ -223,76 +273,190 @@ impl ExecDraw for MechCounter {
             }
         });
 
-        let depth_texture = RenderTexture::new(
-            (config.width, config.height),
-            DEPTH_FORMAT,
-            false,
-            device
-        );
+        // The dividers and the bezel are plain colored quads, drawn with their own little pipeline.
+        // Only the matrix is needed from the bind group, the rest goes via push constants
+        let separator_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &bind_group_layout ],
+            push_constant_ranges: &[
+                PushConstantRange {
+                    stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    range:  0..std::mem::size_of::<PlateInfo>() as u32
+                }
+            ]
+        });
+
+        let separator_shader = load_shader(device, &resources.join("shaders/separator.wgsl"))?;
 
-        Self {
+        let separator_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label:         None,
+            layout:        Some(&separator_pipeline_layout),
+            depth_stencil: target_config.depth_stencil_state(true, wgpu::CompareFunction::Less),
+            multisample:   target_config.multisample_state(),
+            multiview:     None,
+            vertex: wgpu::VertexState {
+                module:      &separator_shader,
+                entry_point: "vs_main",
+                buffers:     &[]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module:      &separator_shader,
+                entry_point: "fs_main",
+                targets:     &[ Some(config.format.into()) ]
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology:     wgpu::PrimitiveTopology::TriangleStrip,
+                cull_mode:    None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..Default::default()
+            }
+        });
+
+        let color_target  = ColorTarget::frame(&target_config);
+        let depth_texture = DepthTexture::new((config.width, config.height), &target_config, device);
+
+        Ok(Self {
             pipeline,
+            separator_pipeline,
             uniform_buffer: umatrix.buffer,
+            colors_buffer: ucolors.buffer,
             bind_group,
-            depth_view: depth_texture.view
-        }
+            bind_group_layout,
+            environment,
+            sampler,
+            target_config,
+            color_target,
+            depth_texture,
+            resolution: glam::Vec2::new(config.width as f32, config.height as f32),
+            projection: Projection::default(),
+            separators: separator_positions(),
+            separator_color: SEPARATOR_COLOR,
+            wheel_colors: [WHEEL_COLOR; 6],
+            wheel_angles: [0.0; 6],
+            countdown: COUNTDOWN.get().map(|&(duration, looping)| Countdown::new(duration, looping, &time::now())),
+            urgency_color: WHEEL_COLOR,
+            show_help: false,
+            help:      keys::help_panel(KEYBINDINGS, config, device)
+        })
     }
 
     fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
-        let ubuffer = calc_matrix(
-            glam::Vec2::new(width as f32, height as f32),
-            glam::Vec2::new(2.0, 1.0)
-        );
+        self.resolution = glam::Vec2::new(width as f32, height as f32);
 
         // adapt the drawspace scales to the current resolution
+        let ubuffer = calc_matrix(self.resolution, self.projection);
         queue.write_buffer(&self.uniform_buffer, 0, cast_struct_to_u8_slice(&ubuffer));
 
         // the surface texture will be resized automatically
         // it's our duty to handle the depth buffer manually
-        let depth_texture = RenderTexture::new(
-            (width, height),
-            DEPTH_FORMAT,
-            false,
-            device
-        );
-
-        self.depth_view = depth_texture.view;
+        self.depth_texture = DepthTexture::new((width, height), &self.target_config, device);
+        self.help.resize(width, height);
+    }
+
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, queue: &wgpu::Queue) {
+        match keys::pressed(&event, KEYBINDINGS) {
+            Some("O") => {
+                self.projection = self.projection.toggled();
+
+                let ubuffer = calc_matrix(self.resolution, self.projection);
+                queue.write_buffer(&self.uniform_buffer, 0, cast_struct_to_u8_slice(&ubuffer));
+            },
+            Some("H") => {
+                self.show_help = !self.show_help;
+            },
+            _ => {}
+        }
     }
 
-    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
+    // a sprite sheet of other digits, ten side by side as in the bundled one
+    fn ondropfile(self: &mut Self, path: std::path::PathBuf, device: &wgpu::Device, queue: &wgpu::Queue) {
+        match load_sprite_sheet(&path, device, queue) {
+            Ok(sprites) => {
+                self.bind_group = create_bind_group(
+                    &self.bind_group_layout, &self.uniform_buffer, &sprites, &self.sampler, &self.environment, &self.colors_buffer, device
+                );
+                log::info!("The digits are now {}", path.display());
+            },
+            Err(error) => log::warn!("Keeping the digits: {}", error)
+        }
+    }
+
+    fn significant_change(self: &mut Self) -> bool {
+        // the wheels stand still once they've turned, until the next second
+        match &self.countdown {
+            Some(countdown) => countdown.is_animating(&time::now()),
+            None            => is_animating(&time::now())
+        }
+    }
+
+    fn next_deadline(self: &Self, now: time::Instant) -> Option<time::Instant> {
+        match &self.countdown {
+            Some(countdown) => countdown.until_next_change(&time::now()).and_then(|delta| time::deadline_after(now, delta)),
+            None            => time::deadline_after(now, until_next_animation(&time::now()))
+        }
+    }
+
+    fn update(self: &mut Self, _dt: f32, _input: &InputState, _device: &wgpu::Device, queue: &wgpu::Queue) {
+        let now = time::now();
+
+        // counting down, every wheel takes the color of the time left
+        let colors = match &self.countdown {
+            Some(countdown) => {
+                self.urgency_color = urgency_color(countdown.state(&now));
+                self.wheel_angles  = countdown.wheel_angles(&now);
+                [self.urgency_color; 6]
+            },
+            None => {
+                self.wheel_angles = calc_wheel_angles(&now);
+                self.wheel_colors
+            }
+        };
+
+        let mut wheel_colors = WheelColors { colors: [0; 8] };
+        wheel_colors.colors[..6].copy_from_slice(&colors);
+        queue.write_buffer(&self.colors_buffer, 0, cast_struct_to_u8_slice(&wheel_colors));
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
-        let rtng = RotationAngles { angles: calc_wheel_angles() };
+        let state = WheelState {
+            angles:       self.wheel_angles,
+            env_strength: ENV_STRENGTH
+        };
 
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label:                    None,
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_view,
-                    depth_ops: Some(wgpu::Operations {
-                        load:  wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store
-                    }),
-                    stencil_ops: None
-                }),
+                depth_stencil_attachment: Some(self.depth_texture.attachment(&self.target_config, false)),
                 timestamp_writes:    None,
                 occlusion_query_set: None,
-                color_attachments:   &[Some(wgpu::RenderPassColorAttachment {
-                    view: texview,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load:  wgpu::LoadOp::Clear(wgpu::Color { r: 0.0, g: 0.0, b: 0.0, a: 0.0 }),
-                        store: wgpu::StoreOp::Store
-                    }
-                })]
+                color_attachments:   &[Some(self.color_target.attachment(
+                    &self.target_config, texview,
+                    wgpu::LoadOp::Clear(wgpu::Color { r: 0.0, g: 0.0, b: 0.0, a: 0.0 })
+                ))]
             });
 
-            rpass.set_pipeline(&self.pipeline);
             rpass.set_bind_group(0, &self.bind_group, &[]);
+
+            // Dividing plates first, the cards behind them are then rejected by the depth test
+            rpass.set_pipeline(&self.separator_pipeline);
+
+            for x_pos in self.separators {
+                draw_separator(&mut rpass, x_pos, SEPARATOR_HEIGHT, self.separator_color);
+            }
+
+            // The bezel, framing the wheels from above and below
+            let bezel_width = (WHEEL_X_OFFSETS[5] - WHEEL_X_OFFSETS[0]) + 2.0 * CARD_HALF_WIDTH + SEPARATOR_THICKNESS;
+            for y_pos in [SEPARATOR_HEIGHT * 0.5, -SEPARATOR_HEIGHT * 0.5] {
+                draw_plate(&mut rpass, (0.0, y_pos), (bezel_width, SEPARATOR_THICKNESS), self.separator_color);
+            }
+
+            rpass.set_pipeline(&self.pipeline);
             rpass.set_push_constants(
-                wgpu::ShaderStages::VERTEX,
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
                 0,
-                cast_struct_to_u8_slice(&rtng)
+                cast_struct_to_u8_slice(&state)
             );
 
             // Issue a single draw call to draw everything via instancing.
@@ -305,23 +469,61 @@ impl ExecDraw for MechCounter {
         }
 
         queue.submit(std::iter::once(encoder.finish()));
+
+        if self.show_help {
+            self.help.draw(texview, device, queue);
+        }
     }
 }
 
 fn main() {
-    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    logging::init("mcounter");
+
+    let (mut args, app) = Cli::new("mcounter", "Mechanical counter clock with rotating digit wheels")
+        .option("--countdown", "HH:MM:SS", "Count down from the given duration instead of showing the time")
+        .switch("--loop", "Start the countdown over once it has reached zero, rather than stopping")
+        .parse();
+
+    // the wheels settle within each second, so 3 seconds make for a nice looping GIF
+    if args.record_seconds.is_none() {
+        args.record_seconds = Some(3.0);
+    }
+
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    if let Some(value) = app.value("--countdown") {
+        match parse_countdown(value) {
+            Some(duration) => { let _ = COUNTDOWN.set((duration, app.flag("--loop"))); },
+            None           => log::warn!("Ignoring invalid countdown '{}', expected e.g. 00:05:00", value)
+        }
+    } else if app.flag("--loop") {
+        log::warn!("Ignoring --loop without --countdown");
+    }
+
+    if args.take_snapshot::<MechCounter>((1024, 512), Some(wgpu::Features::PUSH_CONSTANTS), args.msaa) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Mechanical Counter Clock", &format!("Failed to initialize the windowing system: {}", error)));
     #[allow(unused_mut)]
     let mut builder = winit::window::WindowBuilder::new();
-    let window = builder
+    let builder = builder
         .with_inner_size(winit::dpi::LogicalSize { width: 1024.0, height: 512.0 })
-        .with_title("Mechanical Counter Clock")
+        .with_title("Mechanical Counter Clock");
+    let window = args.apply_to_window(builder, &event_loop)
         .build(&event_loop)
-        .unwrap();
+        .unwrap_or_else(|error| fatal_error("Mechanical Counter Clock", &format!("Failed to create the window: {}", error)));
 
-    pollster::block_on(run::<MechCounter>(
+    pollster::block_on(run_with_options::<MechCounter>(
         event_loop, window,
-        Some(wgpu::Features::PUSH_CONSTANTS))
-    );
+        Some(wgpu::Features::PUSH_CONSTANTS),
+        RunOptions { msaa_sample_count: args.msaa, ..args.run_options() }
+    ));
 }
 
 #[allow(dead_code)]
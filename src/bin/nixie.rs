@@ -0,0 +1,390 @@
+#![cfg_attr(
+    all(
+        target_os = "windows",
+        not(feature = "console"),
+    ),
+    windows_subsystem = "windows"
+)]
+use clockutils::{
+    run_with_options, cast_struct_to_u8_slice, get_resource_folder_for, load_shader, fatal_error,
+    create_blur_table_bindgroup, u32_col_to_wgpu_col_linear,
+    ExecDraw, FrameInfo, InputState, SingleUniformBuffer, ResourceTexture, BasicFilteringSampler,
+    FullscreenQuadPipeline, RenderTexture, PlyGeoBuffers, Vtx3UV,
+    camera::{self, Projection},
+    cli::Cli, logging, time,
+    font::BitmapFontRenderer,
+    keys::{self, KeyBinding},
+    target::{requested_sample_count, ColorTarget, PipelineBuilder, TargetConfig, TargetPipeline},
+    clocks::nixie::{calc_nixie_data, is_flickering, until_next_change, NixieData}
+};
+
+#[repr(C, align(8))]
+struct MatrixData {
+    matrix: glam::Mat4
+}
+
+/// Matched on in `onkey`, and listed by the help panel
+const KEYBINDINGS: &[KeyBinding] = &[
+    ("O", "Orthographic/perspective camera"),
+    ("H", "Show/hide this help")
+];
+
+/// The camera looks at the tubes from this far, slightly from above, so that the stacked cathodes part a bit
+const CAMERA_DISTANCE: f32 = 9.0;
+const CAMERA_HEIGHT:   f32 = 0.8;
+/// Half the size of the area that's always visible around the tubes
+const EXTENT: glam::Vec2 = glam::Vec2::new(4.6, 1.2);
+
+/// Behind the tubes. Transparent, so that it doesn't glow, see tubes.wgsl
+const BACKGROUND_COLOR: u32 = 0x0E0A08_00;
+
+/// Radius of the glow, in pixels at the default window height, it keeps its size relative to the tubes
+const GLOW_RADIUS: f32 = 14.0;
+const GLOW_REFERENCE_HEIGHT: f32 = 400.0;
+
+fn calc_matrix(resolution: glam::Vec2, projection: Projection) -> MatrixData {
+    let matrix = camera::view_projection(
+        projection,
+        glam::Vec3::new(0.0, CAMERA_HEIGHT, CAMERA_DISTANCE),
+        glam::Vec3::ZERO,
+        glam::Vec3::Y,
+        resolution,
+        EXTENT,
+        (1.0, 100.0)
+    );
+
+    MatrixData { matrix }
+}
+
+/// The glow's blur table, scaled with the window's height
+fn glow_blur_table(height: u32, device: &wgpu::Device, queue: &wgpu::Queue) -> (wgpu::BindGroup, wgpu::BindGroupLayout) {
+    let blur_radius = ((GLOW_RADIUS * height as f32 / GLOW_REFERENCE_HEIGHT) as i32).max(1);
+    let blur_sigma  = (blur_radius as f32) * 0.25;
+
+    create_blur_table_bindgroup(blur_radius, blur_sigma, true, true, device, queue)
+}
+
+/// A texture drawn into by one pass and read by the next, along with the bind group it's read through
+struct PassTexture {
+    texture:    RenderTexture,
+    bind_group: wgpu::BindGroup
+}
+
+impl PassTexture {
+    fn new(
+        size:    (u32, u32),
+        format:  wgpu::TextureFormat,
+        layout:  &wgpu::BindGroupLayout,
+        sampler: &BasicFilteringSampler,
+        device:  &wgpu::Device
+    ) -> Self {
+        let texture = RenderTexture::new(size, format, true, device);
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   None,
+            layout,
+            entries: &[ texture.get_entry(0), sampler.get_entry(1) ]
+        });
+
+        Self { texture, bind_group }
+    }
+}
+
+fn begin_pass<'a>(
+    encoder:    &'a mut wgpu::CommandEncoder,
+    attachment: wgpu::RenderPassColorAttachment<'a>
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label:                    None,
+        depth_stencil_attachment: None,
+        timestamp_writes:         None,
+        occlusion_query_set:      None,
+        color_attachments:        &[Some(attachment)]
+    })
+}
+
+/// The Clock's mechanism:
+/// Six nixie tubes, each a stack of ten cathodes, one per numeral, loaded as a single PLY mesh of ten quads
+/// and drawn once per tube (instanced). The numerals are wire outlines in a sprite sheet, the lit one glows in full,
+/// the others only catch a faint glow inside the glass. The cathodes are added up rather than depth tested,
+/// so the ones behind show through the ones in front, as they do through the wire.
+///
+/// When a digit changes, the new cathode flickers for a moment while the old one fades out, both worked out
+/// by the vertex shader from the digits of `FLICKER_DURATION` ago and the timestamp (see `calc_nixie_data`).
+///
+/// The warm glow is the digital clock's: the tubes blurred in two passes with the look-up table of
+/// `create_blur_table_bindgroup`, then blended over the sharp tubes, premultiplied.
+struct NixieClock {
+    tube_pipeline:       TargetPipeline,
+    horizontal_pipeline: FullscreenQuadPipeline,
+    vertical_pipeline:   FullscreenQuadPipeline,
+    sharp_pipeline:      FullscreenQuadPipeline,
+    glow_pipeline:       FullscreenQuadPipeline,
+
+    cathodes:     PlyGeoBuffers,
+    matrix:       SingleUniformBuffer,
+    data_buffer:  SingleUniformBuffer,
+    bind_group:   wgpu::BindGroup,
+
+    target_config: TargetConfig, // multisampled as requested (--msaa), as far as supported, resolved into `tubes`
+    color_target:  ColorTarget,
+
+    texture_layout: wgpu::BindGroupLayout,
+    sampler:        BasicFilteringSampler,
+    tubes:          PassTexture, // the sharp image
+    horizontal:     PassTexture, // its horizontal blur
+    glow:           PassTexture, // blurred both ways
+    format:         wgpu::TextureFormat, // of the surface, the pass textures have the same
+
+    blur_table_bindgroup: wgpu::BindGroup, // remade on resize, the glow scales with the window
+
+    resolution: glam::Vec2,
+    projection: Projection,
+
+    show_help: bool,
+    help:      BitmapFontRenderer
+}
+
+impl ExecDraw for NixieClock {
+    fn setup(
+        config:   &wgpu::SurfaceConfiguration,
+        adapter:  &wgpu::Adapter,
+        device:   &wgpu::Device,
+        queue:    &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> {
+        let resources = get_resource_folder_for("nixie").map_err(|error| error.to_string())?;
+        let size      = (config.width, config.height);
+
+        // smooths the quads' edges, the wires themselves are anti-aliased in the sprite sheet
+        let target_config = TargetConfig::new(config.format, None).with_supported_sample_count(requested_sample_count(), adapter, device);
+        let color_target  = ColorTarget::new(size, &target_config, device);
+
+        let cathodes = PlyGeoBuffers::new(device, resources.join("meshes/numerals.ply").as_path().to_str().unwrap())?;
+
+        let matrix      = SingleUniformBuffer::new::<MatrixData>(device, wgpu::ShaderStages::VERTEX);
+        let data_buffer = SingleUniformBuffer::new::<NixieData>(device, wgpu::ShaderStages::VERTEX);
+
+        // the numerals side by side, each a tenth of the width
+        let numerals = ResourceTexture::new(
+            resources.join("textures/numerals.png").as_path().to_str().unwrap(),
+            device,
+            queue
+        )?;
+
+        let sampler = BasicFilteringSampler::new(device);
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                SingleUniformBuffer::default_layout_entry(0, &matrix),
+                SingleUniformBuffer::default_layout_entry(1, &data_buffer),
+                ResourceTexture::default_layout_entry(2),
+                BasicFilteringSampler::default_layout_entry(3)
+            ]
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   None,
+            layout:  &bind_group_layout,
+            entries: &[
+                matrix.get_entry(0),
+                data_buffer.get_entry(1),
+                numerals.get_entry(2),
+                sampler.get_entry(3)
+            ]
+        });
+
+        let tube_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &bind_group_layout ],
+            push_constant_ranges: &[]
+        });
+
+        let tube_shader = load_shader(device, &resources.join("shaders/tubes.wgsl"))?;
+
+        // the cathodes add up, see tubes.wgsl
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation:  wgpu::BlendOperation::Add
+        };
+
+        let tube_pipeline = PipelineBuilder::new(&target_config, &tube_pipeline_layout, &tube_shader)
+            .vertex("vs_main", &[ Vtx3UV::wgpu_layout() ])
+            .blend(wgpu::BlendState { color: additive, alpha: additive })
+            .build(device);
+
+        ////////////////////////////////////////
+
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[ RenderTexture::default_layout_entry(0), BasicFilteringSampler::default_layout_entry(1) ]
+        });
+
+        let tubes      = PassTexture::new(size, config.format, &texture_layout, &sampler, device);
+        let horizontal = PassTexture::new(size, config.format, &texture_layout, &sampler, device);
+        let glow       = PassTexture::new(size, config.format, &texture_layout, &sampler, device);
+
+        let (blur_table_bindgroup, blur_table_layout) = glow_blur_table(config.height, device, queue);
+
+        // one pipeline per direction, the fragment shader's entry point is all that differs
+        let filter_shader = load_shader(device, &resources.join("shaders/filter.wgsl"))?;
+        let filter_pipeline = |entry| FullscreenQuadPipeline::new(
+            device, &filter_shader, entry,
+            &[ &texture_layout, &blur_table_layout ],
+            &[],
+            config.format
+        );
+
+        let horizontal_pipeline = filter_pipeline("fs_horizontal");
+        let vertical_pipeline   = filter_pipeline("fs_vertical");
+
+        // the sharp tubes copied into the frame, then the glow blended over them
+        let composite_shader = load_shader(device, &resources.join("shaders/composite.wgsl"))?;
+        let sharp_pipeline = FullscreenQuadPipeline::new(
+            device, &composite_shader, "fs_sharp",
+            &[ &texture_layout ],
+            &[],
+            config.format
+        );
+        let glow_pipeline = FullscreenQuadPipeline::blended(
+            device, &composite_shader, "fs_glow",
+            &[ &texture_layout ],
+            &[],
+            config.format,
+            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING
+        );
+
+        let resolution = glam::Vec2::new(config.width as f32, config.height as f32);
+        let projection = Projection::default();
+        queue.write_buffer(&matrix.buffer, 0, cast_struct_to_u8_slice(&calc_matrix(resolution, projection)));
+
+        Ok(Self {
+            tube_pipeline, horizontal_pipeline, vertical_pipeline, sharp_pipeline, glow_pipeline,
+            cathodes, matrix, data_buffer, bind_group,
+            target_config, color_target,
+            texture_layout, sampler, tubes, horizontal, glow,
+            format: config.format,
+            blur_table_bindgroup,
+            resolution, projection,
+            show_help: false,
+            help:      keys::help_panel(KEYBINDINGS, config, device)
+        })
+    }
+
+    fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let size = (width, height);
+
+        self.resolution = glam::Vec2::new(width as f32, height as f32);
+        queue.write_buffer(&self.matrix.buffer, 0, cast_struct_to_u8_slice(&calc_matrix(self.resolution, self.projection)));
+
+        self.help.resize(width, height);
+        self.color_target = ColorTarget::new(size, &self.target_config, device);
+
+        self.tubes      = PassTexture::new(size, self.format, &self.texture_layout, &self.sampler, device);
+        self.horizontal = PassTexture::new(size, self.format, &self.texture_layout, &self.sampler, device);
+        self.glow       = PassTexture::new(size, self.format, &self.texture_layout, &self.sampler, device);
+
+        self.blur_table_bindgroup = glow_blur_table(height, device, queue).0;
+    }
+
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, queue: &wgpu::Queue) {
+        match keys::pressed(&event, KEYBINDINGS) {
+            Some("O") => {
+                self.projection = self.projection.toggled();
+                queue.write_buffer(&self.matrix.buffer, 0, cast_struct_to_u8_slice(&calc_matrix(self.resolution, self.projection)));
+            },
+            Some("H") => {
+                self.show_help = !self.show_help;
+            },
+            _ => {}
+        }
+    }
+
+    fn significant_change(self: &mut Self) -> bool {
+        // the tubes glow steadily once the new cathodes have caught, until the next second
+        is_flickering(&time::now())
+    }
+
+    fn next_deadline(self: &Self, now: time::Instant) -> Option<time::Instant> {
+        time::deadline_after(now, until_next_change(&time::now()))
+    }
+
+    fn update(self: &mut Self, _dt: f32, _input: &InputState, _device: &wgpu::Device, queue: &wgpu::Queue) {
+        queue.write_buffer(&self.data_buffer.buffer, 0, cast_struct_to_u8_slice(&calc_nixie_data(&time::now())));
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        // the cathodes of all the tubes, over the background
+        {
+            let clear = u32_col_to_wgpu_col_linear(BACKGROUND_COLOR);
+            let mut rpass = begin_pass(&mut encoder, self.color_target.attachment(&self.target_config, &self.tubes.texture.view, wgpu::LoadOp::Clear(clear)));
+
+            self.tube_pipeline.set(&mut rpass, &self.target_config);
+            rpass.set_bind_group(0, &self.bind_group, &[]);
+
+            rpass.set_index_buffer(self.cathodes.ibuffer.slice(..), self.cathodes.index_format);
+            rpass.set_vertex_buffer(0, self.cathodes.vbuffer.slice(..));
+            rpass.draw_indexed(0..self.cathodes.icount as u32, 0, 0..6); // one instance per tube
+        }
+
+        // the rest are fullscreen quads, the glow is blended over the sharp tubes in the frame
+        let mut apply_pass = |pipeline: &FullscreenQuadPipeline, bind_groups: &[&wgpu::BindGroup], destination: &wgpu::TextureView, load: wgpu::LoadOp<wgpu::Color>| {
+            let mut rpass = begin_pass(&mut encoder, wgpu::RenderPassColorAttachment {
+                view:           destination,
+                resolve_target: None,
+                ops:            wgpu::Operations { load, store: wgpu::StoreOp::Store }
+            });
+
+            pipeline.draw(&mut rpass, bind_groups, &[]);
+        };
+
+        let clear = wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT);
+
+        apply_pass(&self.horizontal_pipeline, &[ &self.tubes.bind_group, &self.blur_table_bindgroup ],      &self.horizontal.texture.view, clear);
+        apply_pass(&self.vertical_pipeline,   &[ &self.horizontal.bind_group, &self.blur_table_bindgroup ], &self.glow.texture.view,       clear);
+        apply_pass(&self.sharp_pipeline,      &[ &self.tubes.bind_group ], texview, clear);
+        apply_pass(&self.glow_pipeline,       &[ &self.glow.bind_group ],  texview, wgpu::LoadOp::Load);
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        if self.show_help {
+            self.help.draw(texview, device, queue);
+        }
+    }
+}
+
+fn main() {
+    logging::init("nixie");
+
+    let (args, _app) = Cli::new("nixie", "Nixie tube clock, a stack of glowing cathodes per digit").parse();
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    if args.take_snapshot::<NixieClock>((960, 400), None, 1) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Nixie Clock", &format!("Failed to initialize the windowing system: {}", error)));
+    #[allow(unused_mut)]
+    let mut builder = winit::window::WindowBuilder::new();
+    let builder = builder
+        .with_inner_size(winit::dpi::LogicalSize { width: 960.0, height: 400.0 })
+        .with_title("Nixie Clock");
+    let window = args.apply_to_window(builder, &event_loop)
+        .build(&event_loop)
+        .unwrap_or_else(|error| fatal_error("Nixie Clock", &format!("Failed to create the window: {}", error)));
+
+    pollster::block_on(run_with_options::<NixieClock>(
+        event_loop, window,
+        None,
+        args.run_options()
+    ));
+}
@@ -5,16 +5,26 @@
     ),
     windows_subsystem = "windows"
 )]
-use std::{borrow::Cow, fs::read_to_string};
-use wgpu::{RenderPipelineDescriptor, PushConstantRange};
+use wgpu::{util::DeviceExt, RenderPipelineDescriptor};
 use clockutils::{
-    run, cast_struct_to_u8_slice, get_resource_folder_for,
-    lerp_u32_color, u32_col_to_wgpu_col,
-    ExecDraw, SingleUniformBuffer, DrawspaceScales,
-    SURFACE_FORMAT
+    launch, run_with_options, cast_slice_to_u8_slice, get_resource_folder_for, read_shader_source, fatal_error,
+    u32_col_to_wgpu_col_linear,
+    ExecDraw, FrameInfo, Drawspace2D, FitMode, RunOptions,
+    cli::Cli, logging, time,
+    params::SmallParams,
+    font::BitmapFontRenderer,
+    keys::{self, KeyBinding},
+    palette::{highlight_u32_color, PaletteEditor, PaletteSet},
+    target::{ColorTarget, TargetConfig},
+    viewport::{RenderPassViewportExt, ViewportRect},
+    clocks::polar::{
+        calc_angles_and_positions, export_svg, quantized_angles, AnglesAndPositions, ColorCombo, PALETTE_SLOTS,
+        EXTENT, SECONDS_RADIUS, MINUTES_RADIUS, HOURS_RADIUS, THICKNESS, DISK_RADIUS
+    }
 };
-use chrono::{Local, Timelike};
-use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
+use chrono::{Timelike, TimeDelta};
+#[cfg(feature = "fxaa")]
+use clockutils::{FxaaPass, RenderTexture};
 
 /// Properties of the "hollowed" n-gon on which the arc/ring will be drawn on.
 /// Used for drawing an arc with angle control
@@ -38,153 +48,472 @@ struct DiskInfo {
     color:     u32
 }
 
-struct ColorCombo {
-    hour:       u32, // color of hour ring
-    minute:     u32, // color of minute ring
-    second:     u32, // color of second ring
-    disk:       u32, // common color of all disks
-    background: u32  // background color
+/// A single background star, fetched by the star vertex shader via instance index
+#[repr(C, align(8))]
+struct Star {
+    position:      glam::Vec2,
+    brightness:    f32,
+    flicker_phase: f32 // offsets the flicker sine wave, so that the stars don't pulse in unison
+}
+
+/// Properties common to all stars of the star field
+#[repr(C, align(8))]
+struct StarFieldInfo {
+    timestamp: f32, // seconds, drives the flicker animation
+    speed:     f32, // angular speed of the flicker sine wave
+    size:      f32, // edge length of the star's quad in drawing units
+    color:     u32
+}
+
+/// Scatter the stars across the extent.
+/// A simple LCG (Numerical Recipes constants) with a fixed seed is used instead of a proper RNG,
+/// so that the star field looks the same on every run.
+fn generate_stars(count: usize, seed: u32) -> Vec<Star> {
+    use std::f32::consts::TAU;
+
+    let mut state = seed;
+    let mut next_f32 = || {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (state >> 8) as f32 / (1 << 24) as f32 // [0..1)
+    };
+
+    (0..count).map(|_| {
+        let x = (next_f32() * 2.0 - 1.0) * EXTENT;
+        let y = (next_f32() * 2.0 - 1.0) * EXTENT;
+
+        Star {
+            position:      glam::Vec2::new(x, y),
+            brightness:    0.3 + 0.7 * next_f32(),
+            flicker_phase: next_f32() * TAU
+        }
+    }).collect()
 }
 
-struct AnglesAndPositions {
-    hours_angle:   f32,
-    minutes_angle: f32,
-    seconds_angle: f32,
+/// Relative luminance (Rec. 709 coefficients) of a packed color, in [0..1]
+fn luminance_u32_color(col: u32) -> f64 {
+    let r = ((col >> 24) & 0xFF) as f64 / 255.0;
+    let g = ((col >> 16) & 0xFF) as f64 / 255.0;
+    let b = ((col >>  8) & 0xFF) as f64 / 255.0;
+
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
 
-    hours_pos:   (f32, f32),
-    minutes_pos: (f32, f32),
-    seconds_pos: (f32, f32)
+/// Whether a palette is meant for a light desktop, i.e. has a bright background
+fn is_light_palette(palettes: &PaletteSet, index: usize) -> bool {
+    luminance_u32_color(ColorCombo::from_slots(palettes.get(index)).background) > 0.5
 }
 
-fn calc_angles_and_positions() -> AnglesAndPositions {
-    use std::f32::consts::{FRAC_PI_2, TAU, PI};
-
-    let now = Local::now();
-    let seconds = now.second() as f32 + (now.nanosecond() as f32 / 1_000_000_000.0);
-    let minutes = now.minute() as f32 + seconds / 60.0;
-    let hours   = (now.hour() % 12) as f32 + minutes / 60.0;
-
-    // angles used for drawing the arcs and calculating disk centers
-    let seconds_angle = (seconds / 60.0) * TAU;
-    let minutes_angle = (minutes / 60.0) * TAU;
-    let hours_angle   = (hours   / 12.0) * TAU;
-
-    // positions of disk centers
-    let seconds_pos: (f32, f32) = (
-        SECONDS_RADIUS * ((PI + TAU - seconds_angle) - FRAC_PI_2).cos(),
-        SECONDS_RADIUS * ((PI + TAU - seconds_angle) - FRAC_PI_2).sin()
-    );
-
-    let minutes_pos: (f32, f32) = (
-        MINUTES_RADIUS * ((PI + TAU - minutes_angle) - FRAC_PI_2).cos(),
-        MINUTES_RADIUS * ((PI + TAU - minutes_angle) - FRAC_PI_2).sin()
-    );
-
-    let hours_pos: (f32, f32) = (
-        HOURS_RADIUS * ((PI + TAU - hours_angle) - FRAC_PI_2).cos(),
-        HOURS_RADIUS * ((PI + TAU - hours_angle) - FRAC_PI_2).sin()
-    );
-
-    AnglesAndPositions {
-        hours_angle, minutes_angle, seconds_angle,
-        hours_pos,   minutes_pos,   seconds_pos
+/// `PALETTE` with the palette file's colors, see `palette`
+fn load_palettes(path: Option<std::path::PathBuf>) -> PaletteSet {
+    PaletteSet::load(path, PALETTE_SLOTS, PALETTE.iter().map(ColorCombo::to_slots).collect())
+}
+
+/// The palette shown by default under the given OS theme
+fn palette_for_theme(theme: winit::window::Theme) -> usize {
+    match theme {
+        winit::window::Theme::Light => LIGHT_THEME_PALETTE,
+        winit::window::Theme::Dark  => DARK_THEME_PALETTE
     }
 }
 
+/// Palette index given with `--color`, handed over from `main` to `setup`. Overrides the theme's default
+static PALETTE_OVERRIDE: std::sync::OnceLock<usize> = std::sync::OnceLock::new();
+
+/// FXAA after the clock is drawn: the arcs' ends and the n-gons' edges go into `source` first,
+/// FXAA's pass draws them onto the frame. `source` is single-sampled, so the clock is resolved into it by `color_target`
+#[cfg(feature = "fxaa")]
+struct FxaaStage {
+    pass:         FxaaPass,
+    source:       RenderTexture,
+    bindgroup:    wgpu::BindGroup,
+    color_target: ColorTarget
+}
+
+#[cfg(feature = "fxaa")]
+impl FxaaStage {
+    fn new(size: (u32, u32), target_config: &TargetConfig, device: &wgpu::Device) -> Self {
+        let pass = FxaaPass::for_target(device, target_config);
+        let source = RenderTexture::new(size, target_config.color_format, true, device);
+        let bindgroup = pass.source_bindgroup(&source, device);
+        let color_target = ColorTarget::new(size, target_config, device);
+
+        Self { pass, source, bindgroup, color_target }
+    }
+
+    fn resize(self: &mut Self, size: (u32, u32), device: &wgpu::Device) {
+        self.source = RenderTexture::new(size, self.source.format, true, device);
+        self.bindgroup = self.pass.source_bindgroup(&self.source, device);
+        self.color_target = ColorTarget::new(size, &self.color_target.config, device);
+    }
+
+    /// Anti-alias `source` onto `destination`
+    fn apply(self: &Self, encoder: &mut wgpu::CommandEncoder, destination: &wgpu::TextureView) {
+        self.pass.apply(encoder, &self.bindgroup, destination);
+    }
+}
+
+/// The palettes loaded in `main`, handed over to `setup`
+static STARTUP_PALETTES: std::sync::OnceLock<PaletteSet> = std::sync::OnceLock::new();
+
 struct PolarClock {
     ring_pipeline: wgpu::RenderPipeline,
     disk_pipeline: wgpu::RenderPipeline,
+    star_pipeline: wgpu::RenderPipeline,
+
+    ring_params: SmallParams<RingInfo>,
+    disk_params: SmallParams<DiskInfo>,
+    star_params: SmallParams<StarFieldInfo>,
+
+    drawspace:       Drawspace2D,
+    star_bind_group: wgpu::BindGroup,
+    target_config:   TargetConfig, // the runner's frame, multisampled as requested (--msaa)
+    color_target:    ColorTarget,
+
+    palettes:       PaletteSet,
+    editor:         PaletteEditor, // the shown palette is edited live, transitions wait meanwhile
+    color_index:    usize, // palette the transition starts from
+    next_index:     usize, // palette the transition ends at, the one shown once it's over
+    follow_theme:   bool,  // switch palettes along with the OS theme, unless one was chosen on the command line
+    last_change_ts: i64,   // timestamp of the last color change transition start, from the shared time source
+    transparent:    bool,  // the background is left out, the desktop shows through instead
+    see_through:    bool,  // the surface is composited with the desktop, so the above can be toggled
+    size:           (u32, u32), // of the surface, for the SVG export
+    scroll_x:       f64,   // horizontal scrolling short of a whole line, see `onmousescroll`
+    split_screen:   bool,  // palette transitions compared: sRGB on the left half, OKLab on the right
+    show_help:      bool,
+    help:           BitmapFontRenderer,
+
+    #[cfg(feature = "fxaa")]
+    fxaa:           FxaaStage,
+    #[cfg(feature = "fxaa")]
+    fxaa_enabled:   bool
+}
 
-    uniform_buffer: wgpu::Buffer,
-    bind_group:     wgpu::BindGroup,
+impl PolarClock {
+    /// Start the eased transition from whatever is shown now towards the given palette
+    fn transition_to(self: &mut Self, index: usize) {
+        self.color_index    = self.next_index;
+        self.next_index     = index;
+        self.last_change_ts = time::now().timestamp_millis();
+    }
 
-    color_index:    usize,
-    last_change_ts: u64 // timestamp of the last color change transition start
+    /// Stars would be invisible (or look like dirt) on a bright background.
+    /// They're part of the background, so transparent windows go without them too
+    fn shows_stars(self: &Self, colors: &ColorCombo) -> bool {
+        !self.transparent && luminance_u32_color(colors.background) < STAR_MAX_LUMINANCE
+    }
+
+    /// The palette as of now, somewhere between the two palettes while transitioning
+    fn current_colors(self: &Self) -> ColorCombo {
+        self.transition_colors(ColorCombo::lerp)
+    }
+
+    /// `current_colors` with another interpolation, e.g. `ColorCombo::lerp_oklab`
+    fn transition_colors(self: &Self, lerp: fn(&ColorCombo, &ColorCombo, f64) -> ColorCombo) -> ColorCombo {
+        if self.editor.is_editing() {
+            return ColorCombo::from_slots(self.palettes.get(self.next_index));
+        }
+
+        // calculate the diff between current timestamp and the last recorded transition start.
+        // The time source may have been stepped back (--debug-time) since, hence the clamping
+        let timestamp_diff = (time::now().timestamp_millis() - self.last_change_ts).max(0) as f64;
+        // no transition past the ANIM_DURATION so clamp it. Noe divide the resultant diff by ANIM_DURATION to get t
+        let t = timestamp_diff.min(ANIM_DURATION) / ANIM_DURATION;
+
+        // use your own fav easing function
+        fn ease_out_quint(t: f64) -> f64 {
+            return 1.0 - (1.0 - t).powf(5.0);
+        }
+
+        // starting and ending palette for linear interpolation
+        let from = ColorCombo::from_slots(self.palettes.get(self.color_index));
+        let to   = ColorCombo::from_slots(self.palettes.get(self.next_index));
+
+        lerp(&from, &to, ease_out_quint(t))
+    }
+
+    /// `colors` with the edited one pulsing
+    fn pulse(self: &Self, mut colors: ColorCombo) -> ColorCombo {
+        if let Some(slot) = self.editor.slot() {
+            let mut slots = colors.to_slots();
+            slots[slot] = highlight_u32_color(slots[slot], self.editor.pulse() * EDIT_PULSE_AMOUNT);
+            colors = ColorCombo::from_slots(&slots);
+        }
+        colors
+    }
+
+    /// What the frame is cleared to: the background, unless it's left out of a transparent window
+    fn background_clear(self: &Self, colors: &ColorCombo) -> wgpu::Color {
+        if self.transparent { wgpu::Color::TRANSPARENT } else { u32_col_to_wgpu_col_linear(colors.background) }
+    }
+
+    /// The star field, the rings and the disks, in the colors given
+    fn draw_clock<'a>(
+        self:   &'a Self,
+        rpass:  &mut wgpu::RenderPass<'a>,
+        queue:  &wgpu::Queue,
+        ap:     &AnglesAndPositions,
+        colors: &ColorCombo
+    ) {
+        fn draw_ring<'a>(
+            rpass:  &mut wgpu::RenderPass<'a>,
+            params: &'a SmallParams<RingInfo>,
+            queue:  &wgpu::Queue,
+            center: (f32, f32), radius: f32, angle: f32, color: u32
+        ) {
+            let ring = RingInfo {
+                center:    glam::Vec2::new(center.0, center.1),
+                thickness: THICKNESS,
+                divisions: DIVISION_COUNT,
+                radius,
+                angle,
+                color
+            };
+        
+            params.set(rpass, queue, &ring);
+        
+            rpass.draw(0..(DIVISION_COUNT * 2 + 2), 0..1); // vertex count = 2n + 2
+        }
+
+        // The star field goes first, so that the rings are drawn on top of it, see `shows_stars` for when it's left out
+        if self.shows_stars(colors) {
+            let now = time::now();
+            let star_field = StarFieldInfo {
+                timestamp: now.num_seconds_from_midnight() as f32 + (now.nanosecond() as f32 / 1_000_000_000.0),
+                speed:     STAR_FLICKER_SPEED,
+                size:      STAR_SIZE,
+                color:     STAR_COLOR
+            };
+
+            rpass.set_pipeline(&self.star_pipeline);
+            rpass.set_bind_group(0, self.drawspace.bind_group(), &[]);
+            rpass.set_bind_group(1, &self.star_bind_group, &[]);
+            self.star_params.set(rpass, queue, &star_field);
+
+            rpass.draw(0..4, 0..STAR_COUNT); // one quad per star
+        }
+
+        ////////////////////////////////////////
+
+        rpass.set_pipeline(&self.ring_pipeline);
+        rpass.set_bind_group(0, self.drawspace.bind_group(), &[]);
+
+        draw_ring(rpass, &self.ring_params, queue, (0.0, 0.0), HOURS_RADIUS,   ap.hours_angle,   colors.hour);
+        draw_ring(rpass, &self.ring_params, queue, (0.0, 0.0), MINUTES_RADIUS, ap.minutes_angle, colors.minute);
+        draw_ring(rpass, &self.ring_params, queue, (0.0, 0.0), SECONDS_RADIUS, ap.seconds_angle, colors.second);
+
+        ////////////////////////////////////////
+
+        rpass.set_pipeline(&self.disk_pipeline);
+        rpass.set_bind_group(0, self.drawspace.bind_group(), &[]);
+
+        draw_disk(rpass, &self.disk_params, queue, ap.hours_pos,   DISK_RADIUS, colors.disk);
+        draw_disk(rpass, &self.disk_params, queue, ap.minutes_pos, DISK_RADIUS, colors.disk);
+        draw_disk(rpass, &self.disk_params, queue, ap.seconds_pos, DISK_RADIUS, colors.disk);
+
+        // Performance improvement notes:
+        // This implementation is done via multiple push constant calls (or uniform slots), one call for each shape.
+        // A better implementation would be uploading the ring and disk properties into one or two instance buffers
+        // (see `InstanceBuffer`) and draw from those buffers, reducing draw calls.
+        // Also, move the constant properties (e.g. radius, thickness) to the shader's (this kills flexibility however)
+    }
+}
+
+fn begin_pass<'a>(
+    encoder:    &'a mut wgpu::CommandEncoder,
+    attachment: wgpu::RenderPassColorAttachment<'a>
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label:                    None,
+        depth_stencil_attachment: None,
+        timestamp_writes:         None,
+        occlusion_query_set:      None,
+        color_attachments:        &[Some(attachment)]
+    })
+}
+
+fn draw_disk<'a>(
+    rpass:  &mut wgpu::RenderPass<'a>,
+    params: &'a SmallParams<DiskInfo>,
+    queue:  &wgpu::Queue,
+    center: (f32, f32), radius: f32, color: u32
+) {
+    let disk = DiskInfo {
+        center:    glam::Vec2::new(center.0, center.1),
+        divisions: DIVISION_COUNT,
+        radius,
+        color
+    };
+
+    params.set(rpass, queue, &disk);
+
+    rpass.draw(0..DIVISION_COUNT, 0..1); // vertex count = n
+}
+
+/// What's on screen as an SVG file, for presentations or wallpapers. Needs no GPU, it's drawn from the clock's state
+fn polar_export_svg(clock: &PolarClock, path: &std::path::Path, width: u32, height: u32) -> std::io::Result<()> {
+    export_svg(
+        path,
+        &calc_angles_and_positions(&time::now()),
+        &clock.current_colors(),
+        width, height,
+        !clock.transparent
+    )
 }
 
-const EXTENT: f32 = 16.0;
+/// Matched on in `onkey`, and listed by the help panel
+const KEYBINDINGS: &[KeyBinding] = &[
+    ("Space",  "Next color palette"),
+    ("Ctrl+E", "Export an SVG snapshot"),
+    ("Ctrl+P", "Edit the palette: Tab, arrows, PageUp/PageDown, Ctrl+S saves"),
+    ("S",      "Split screen: sRGB (left) vs OKLab (right) transitions"),
+    ("Ctrl+B", "Transparent/opaque background (transparent windows)"),
+    #[cfg(feature = "fxaa")]
+    ("A",      "Toggle FXAA"),
+    ("H",      "Show/hide this help")
+];
+
 /// Note: cranking up the division count will increase vertex count, resulting in smoother n-gon,
 /// thus reducing wasted pixel shader invocation. But it'll also result in thin/small triangles,
 /// which are bad and will drastically reduce performance if set to a too high figure.
 /// But it's fine for a small value like 12 (dodecagon).
 /// More info: https://www.humus.name/index.php?page=News&ID=228
 const DIVISION_COUNT: u32 = 12;
-const THICKNESS:      f32 = 2.4;
-
-const SECONDS_RADIUS: f32 = 13.0;
-const MINUTES_RADIUS: f32 =  9.0;
-const HOURS_RADIUS:   f32 =  5.0;
-const DISK_RADIUS:    f32 =  0.8;
 
 const ANIM_DURATION: f64 = 500.0;
 
+/// How far a line of horizontal scrolling moves the `--timezone`
+const TIMEZONE_SCROLL_MINUTES: i32 = 15;
+
+/// How far the edited color pulses towards white (or black, if it's bright)
+const EDIT_PULSE_AMOUNT: f32 = 0.6;
+
+/// Covers the whole drawing space with the 12-gon of a disk, for backgrounds that can't be cleared (see `draw`)
+const BACKGROUND_RADIUS: f32 = EXTENT * 2.0;
+
+const DARK_THEME_PALETTE:  usize = 0;
+const LIGHT_THEME_PALETTE: usize = 2;
+
+const STAR_COUNT:         u32 = 200;
+const STAR_SEED:          u32 = 42;
+const STAR_SIZE:          f32 = 0.05;
+const STAR_FLICKER_SPEED: f32 = 2.0;
+const STAR_COLOR:         u32 = 0xFFFFFF_FF;
+/// Stars are only visible against backgrounds darker than this
+const STAR_MAX_LUMINANCE: f64 = 0.1;
+
 impl ExecDraw for PolarClock {
     fn setup(
-        _config:  &wgpu::SurfaceConfiguration,
+        config:   &wgpu::SurfaceConfiguration,
         _adapter: &wgpu::Adapter,
         device:   &wgpu::Device,
-        _queue:   &wgpu::Queue
-    ) -> Self {
-        let resources = get_resource_folder_for("polar").unwrap();
+        _queue:   &wgpu::Queue,
+        theme:    winit::window::Theme
+    ) -> Result<Self, String> {
+        let resources = get_resource_folder_for("polar").map_err(|error| error.to_string())?;
+
+        // the runner's multisampling smooths the arcs' ends and the n-gons' edges, their shaders only soften the round edges
+        let target_config = TargetConfig::frame(config.format, None);
+        let color_target  = ColorTarget::frame(&target_config);
+
+        let drawspace = Drawspace2D::new(device, glam::Vec2::new(EXTENT, EXTENT), FitMode::Contain);
+
+        // angle, position, color data sent via push constants (or right after the drawspace scales, without them),
+        // one slot per shape drawn in a frame
+        let ring_params = SmallParams::<RingInfo>::new(device, wgpu::ShaderStages::VERTEX_FRAGMENT, 1, 3);
+        let disk_params = SmallParams::<DiskInfo>::new(device, wgpu::ShaderStages::VERTEX_FRAGMENT, 1, 4);
+
+        let ring_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &[ drawspace.layout() ], ring_params.layout_entries().as_slice() ].concat(),
+            push_constant_ranges: &ring_params.push_constant_ranges()
+        });
 
-        let udspace = SingleUniformBuffer::new::<DrawspaceScales>(device, wgpu::ShaderStages::VERTEX_FRAGMENT);
+        let disk_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &[ drawspace.layout() ], disk_params.layout_entries().as_slice() ].concat(),
+            push_constant_ranges: &disk_params.push_constant_ranges()
+        });
+        
+        let ring_shader = ring_params.load_shader(device, &resources.join("shaders/ring.wgsl"))?;
 
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        let disk_shader = disk_params.load_shader(device, &resources.join("shaders/disk.wgsl"))?;
+
+        // the star records never change, so they're uploaded once as a read-only storage buffer.
+        // WebGL2 has no storage buffers, the records go into a uniform buffer there (200 of them fit easily),
+        // declared as a fixed-size array in the shader instead
+        let stars = generate_stars(STAR_COUNT as usize, STAR_SEED);
+        let stars_in_uniform = device.limits().max_storage_buffers_per_shader_stage == 0;
+
+        let star_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label:    None,
+            contents: cast_slice_to_u8_slice(stars.as_slice()),
+            usage:    if stars_in_uniform { wgpu::BufferUsages::UNIFORM } else { wgpu::BufferUsages::STORAGE }
+        });
+
+        let star_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: None,
-            entries: &[ SingleUniformBuffer::default_layout_entry(0, &udspace) ]
+            entries: &[ wgpu::BindGroupLayoutEntry {
+                binding:    0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty:                 if stars_in_uniform { wgpu::BufferBindingType::Uniform } else { wgpu::BufferBindingType::Storage { read_only: true } },
+                    has_dynamic_offset: false,
+                    min_binding_size:   None
+                },
+                count: None
+            } ]
         });
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        let star_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label:   None,
-            layout:  &bind_group_layout,
-            entries: &[ udspace.get_entry(0) ]
+            layout:  &star_bind_group_layout,
+            entries: &[ wgpu::BindGroupEntry { binding: 0, resource: star_buffer.as_entire_binding() } ]
         });
 
-        // angle, position, color data sent via push constants
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        // star field properties are sent via push constants, the stars themselves via the storage buffer
+        let star_params = SmallParams::<StarFieldInfo>::new(device, wgpu::ShaderStages::VERTEX_FRAGMENT, 2, 1);
+
+        let star_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label:                None,
-            bind_group_layouts:   &[ &bind_group_layout ],
-            push_constant_ranges: &[
-                PushConstantRange {
-                    stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
-                    range:  0..32
-                }
-            ]
-        });
-        
-        let ring_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label:  None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
-                read_to_string(resources.join("shaders/ring.wgsl")).unwrap().as_str()
-            ))
+            bind_group_layouts:   &[ &[ drawspace.layout(), &star_bind_group_layout ], star_params.layout_entries().as_slice() ].concat(),
+            push_constant_ranges: &star_params.push_constant_ranges()
         });
 
-        let disk_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label:  None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
-                read_to_string(resources.join("shaders/disk.wgsl")).unwrap().as_str()
-            ))
+        let mut star_source = read_shader_source(&resources.join("shaders/star.wgsl"))?;
+        if stars_in_uniform {
+            star_source = star_source.replace("var<storage, read> stars: array<Star>", &format!("var<uniform> stars: array<Star, {}>", STAR_COUNT));
+        }
+
+        let star_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("star.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(star_params.shader_source(&star_source).into())
         });
 
-        // use proper blending, otherwise overlapping shapes won't display correctly
+        // use proper blending, otherwise overlapping shapes won't display correctly.
+        // The alpha accumulates the same way, which keeps the result premultiplied on transparent windows
         let color_target_state = wgpu::ColorTargetState {
-            format: SURFACE_FORMAT,
+            format: config.format,
             blend:  Some(wgpu::BlendState {
                 color: wgpu::BlendComponent {
                     src_factor: wgpu::BlendFactor::SrcAlpha,
                     dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
                     operation:  wgpu::BlendOperation::Add
                 },
-                alpha: wgpu::BlendComponent::REPLACE
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation:  wgpu::BlendOperation::Add
+                }
             }),
             write_mask: wgpu::ColorWrites::ALL
         };
 
         let ring_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
             label:         None,
-            layout:        Some(&pipeline_layout),
+            layout:        Some(&ring_pipeline_layout),
             depth_stencil: None,
-            multisample:   wgpu::MultisampleState::default(),
+            multisample:   target_config.multisample_state(),
             multiview:     None,
             vertex: wgpu::VertexState {
                 module:      &ring_shader,
@@ -206,9 +535,9 @@ impl ExecDraw for PolarClock {
 
         let disk_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
             label:         None,
-            layout:        Some(&pipeline_layout),
+            layout:        Some(&disk_pipeline_layout),
             depth_stencil: None,
-            multisample:   wgpu::MultisampleState::default(),
+            multisample:   target_config.multisample_state(),
             multiview:     None,
             vertex: wgpu::VertexState {
                 module:      &disk_shader,
@@ -218,6 +547,30 @@ impl ExecDraw for PolarClock {
             fragment: Some(wgpu::FragmentState {
                 module:      &disk_shader,
                 entry_point: "fs_main",
+                targets:     &[ Some(color_target_state.clone()) ]
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology:     wgpu::PrimitiveTopology::TriangleStrip,
+                cull_mode:    None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..Default::default()
+            }
+        });
+
+        let star_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label:         None,
+            layout:        Some(&star_pipeline_layout),
+            depth_stencil: None,
+            multisample:   target_config.multisample_state(),
+            multiview:     None,
+            vertex: wgpu::VertexState {
+                module:      &star_shader,
+                entry_point: "vs_main",
+                buffers:     &[]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module:      &star_shader,
+                entry_point: "fs_main",
                 targets:     &[ Some(color_target_state) ]
             }),
             primitive: wgpu::PrimitiveState {
@@ -228,160 +581,265 @@ impl ExecDraw for PolarClock {
             }
         });
 
-        Self {
-            ring_pipeline, disk_pipeline,
-            bind_group,
-            uniform_buffer: udspace.buffer,
+        let palette_override = PALETTE_OVERRIDE.get().copied();
+        let color_index = palette_override.unwrap_or_else(|| palette_for_theme(theme));
+
+        // the background starts out left out of a transparent window, Ctrl+B brings it back
+        let see_through = config.alpha_mode != wgpu::CompositeAlphaMode::Opaque && config.alpha_mode != wgpu::CompositeAlphaMode::Auto;
+
+        Ok(Self {
+            ring_pipeline, disk_pipeline, star_pipeline,
+            ring_params, disk_params, star_params,
+            drawspace, star_bind_group,
+            target_config, color_target,
             last_change_ts: 0,
-            color_index: PALETTE.len() - 1
-        }
+            palettes:       STARTUP_PALETTES.get().cloned().unwrap_or_else(|| load_palettes(None)),
+            editor:         PaletteEditor::new("Polar Clock"),
+            color_index,
+            next_index:     color_index,
+            follow_theme:   palette_override.is_none(),
+            transparent:    see_through,
+            see_through,
+            size:           (config.width, config.height),
+            scroll_x:       0.0,
+            split_screen:   false,
+            show_help:      false,
+            help:           keys::help_panel(KEYBINDINGS, config, device),
+
+            #[cfg(feature = "fxaa")]
+            fxaa: FxaaStage::new((config.width, config.height), &target_config, device),
+            #[cfg(feature = "fxaa")]
+            fxaa_enabled:   false
+        })
     }
 
-    fn resize(self: &mut Self, width: u32, height: u32, _device: &wgpu::Device, queue: &wgpu::Queue) {
+    #[cfg_attr(not(feature = "fxaa"), allow(unused_variables))] // the device is for FXAA's textures only
+    fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
         // rewrite the uniform buffer containing the drawspace scales since resolution was changed
-        let ubuffer = DrawspaceScales::new(
-            glam::Vec2::new(width as f32, height as f32),
-            glam::Vec2::new(EXTENT, EXTENT)
-        );
+        self.drawspace.resize(queue, width, height);
+        self.help.resize(width, height);
+        self.size = (width, height);
 
-        queue.write_buffer(&self.uniform_buffer, 0, cast_struct_to_u8_slice(&ubuffer));
+        #[cfg(feature = "fxaa")]
+        self.fxaa.resize(self.size, device);
     }
 
-    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _device: &wgpu::Device, _queue: &wgpu::Queue) {
-        if event.state == winit::event::ElementState::Pressed && !event.repeat {
-            match event.key_without_modifiers().as_ref() {
-                winit::keyboard::Key::Named(winit::keyboard::NamedKey::Space) => {
-                    // goto the next color index (wrapping) and record the current timestamp as transition starts now
-                    self.color_index    = (self.color_index + 1) % PALETTE.len();
-                    self.last_change_ts = Local::now().timestamp_millis() as u64;
-                },
-                _ => {}
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        if self.editor.onkey(&event, &mut self.palettes, Some(self.next_index)) {
+            // an in-flight transition is cut short, the edited palette is shown as is
+            if self.editor.is_editing() {
+                self.color_index = self.next_index;
             }
+            return;
+        }
+
+        match keys::pressed(&event, KEYBINDINGS) {
+            Some("Space") => {
+                // goto the next color index (wrapping), the transition starts now
+                self.transition_to((self.next_index + 1) % self.palettes.len());
+            },
+            Some("Ctrl+E") => {
+                // into the working directory, named after the time shown
+                let path = std::path::PathBuf::from(time::now().format("polar-%Y%m%d-%H%M%S.svg").to_string());
+
+                match polar_export_svg(self, &path, self.size.0, self.size.1) {
+                    Ok(()) => log::info!("Exported {}", path.display()),
+                    Err(error) => log::error!("Failed to export {}: {}", path.display(), error)
+                }
+            },
+            Some("S") => {
+                self.split_screen = !self.split_screen;
+            },
+            Some("Ctrl+B") => {
+                if self.see_through {
+                    self.transparent = !self.transparent;
+                } else {
+                    log::warn!("The background can only be left out of a transparent window (--transparent)");
+                }
+            },
+            #[cfg(feature = "fxaa")]
+            Some("A") => {
+                self.fxaa_enabled = !self.fxaa_enabled;
+            },
+            Some("H") => {
+                self.show_help = !self.show_help;
+            },
+            _ => {}
         }
     }
 
-    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    fn onmousescroll(self: &mut Self, delta: (f64, f64), _state: u32, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        // touchpads scroll by fractions of a line, a step is taken per whole one
+        self.scroll_x += delta.0;
+        let steps = self.scroll_x.trunc() as i32;
+        if steps == 0 {
+            return;
+        }
+        self.scroll_x -= steps as f64;
+
+        // with a --timezone, scrolling right moves it east, otherwise it cycles the palettes
+        match time::control(|time| time.timezone()) {
+            Some(offset) => {
+                let shifted = time::shift_timezone(offset, steps * TIMEZONE_SCROLL_MINUTES);
+                time::control(|time| time.set_timezone(shifted));
+                log::info!("Timezone: UTC{}", shifted);
+            },
+            None if !self.editor.is_editing() => {
+                let count = self.palettes.len() as i32;
+                self.transition_to((self.next_index as i32 + steps).rem_euclid(count) as usize);
+            },
+            None => {}
+        }
+    }
 
-        let ap = calc_angles_and_positions();
+    fn ontheme(self: &mut Self, theme: winit::window::Theme) {
+        // a palette cycled to by hand is kept, as long as it suits the new theme
+        // not while editing, the palette would change under the editor's hands
+        let wants_light = theme == winit::window::Theme::Light;
+        if self.follow_theme && !self.editor.is_editing() && is_light_palette(&self.palettes, self.next_index) != wants_light {
+            self.transition_to(palette_for_theme(theme));
+        }
+    }
 
-        // calculate the diff between current timestamp and the last recorded transition start
-        let timestamp_diff = ((Local::now().timestamp_millis() as u64) - self.last_change_ts) as f64;
-        // no transition past the ANIM_DURATION so clamp it. Noe divide the resultant diff by ANIM_DURATION to get t
-        let t = timestamp_diff.min(ANIM_DURATION) / ANIM_DURATION;
+    fn letterbox_color(self: &Self) -> Option<wgpu::Color> {
+        // the background goes on into the bars, as if the clock filled the window
+        Some(self.background_clear(&self.pulse(self.current_colors())))
+    }
 
-        // starting and ending palette for linear interpolation
-        let (palette0, palette1) = {
-            let cindex0 = self.color_index;
-            let cindex1 = (self.color_index + 1) % PALETTE.len();
+    fn title_status(self: &Self) -> Option<String> {
+        self.editor.status(&self.palettes)
+            .or_else(|| self.split_screen.then(|| "sRGB | OKLab".to_string()))
+    }
 
-            (&PALETTE[cindex0], &PALETTE[cindex1])
-        };
+    fn next_deadline(self: &Self, now: time::Instant) -> Option<time::Instant> {
+        // the end of an in-flight palette transition, the arcs move on continuously anyway
+        let remaining = self.last_change_ts + ANIM_DURATION as i64 - time::now().timestamp_millis();
 
-        // use your own fav easing function
-        fn ease_out_quint(t: f64) -> f64 {
-            return 1.0 - (1.0 - t).powf(5.0);
+        if remaining > 0 {
+            time::deadline_after(now, TimeDelta::milliseconds(remaining))
+        } else {
+            None
         }
+    }
 
-        let hh_color = lerp_u32_color(palette0.hour,       palette1.hour,       ease_out_quint(t));
-        let mm_color = lerp_u32_color(palette0.minute,     palette1.minute,     ease_out_quint(t));
-        let ss_color = lerp_u32_color(palette0.second,     palette1.second,     ease_out_quint(t));
-        let cr_color = lerp_u32_color(palette0.disk,       palette1.disk,       ease_out_quint(t));
-        let bg_color = lerp_u32_color(palette0.background, palette1.background, ease_out_quint(t));
+    fn content_version(self: &Self, now: &chrono::DateTime<chrono::FixedOffset>) -> Option<u64> {
+        use std::hash::{Hash, Hasher};
 
-        fn draw_ring(rpass: &mut wgpu::RenderPass, center: (f32, f32), radius: f32, angle: f32, color: u32) {
-            let ring = RingInfo {
-                center:    glam::Vec2::new(center.0, center.1),
-                thickness: THICKNESS,
-                divisions: DIVISION_COUNT,
-                radius,
-                angle,
-                color
-            };
-        
-            rpass.set_push_constants(
-                wgpu::ShaderStages::VERTEX_FRAGMENT,
-                0,
-                cast_struct_to_u8_slice(&ring)
-            );
-        
-            rpass.draw(0..(DIVISION_COUNT * 2 + 2), 0..1); // vertex count = 2n + 2
+        // the stars flicker, the edited color pulses and transitions fade, every frame
+        let transitioning = now.timestamp_millis() < self.last_change_ts + ANIM_DURATION as i64;
+        if self.shows_stars(&self.current_colors()) || self.editor.is_editing() || transitioning {
+            return None;
         }
-        
-        fn draw_disk(rpass: &mut wgpu::RenderPass, center: (f32, f32), radius: f32, color: u32) {
-            let disk = DiskInfo {
-                center:    glam::Vec2::new(center.0, center.1),
-                divisions: DIVISION_COUNT,
-                radius,
-                color
-            };
-        
-            rpass.set_push_constants(
-                wgpu::ShaderStages::VERTEX_FRAGMENT,
-                0,
-                cast_struct_to_u8_slice(&disk)
-            );
-        
-            rpass.draw(0..DIVISION_COUNT, 0..1); // vertex count = n
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        (quantized_angles(&calc_angles_and_positions(now), self.drawspace.scales().density), self.next_index).hash(&mut hasher);
+
+        Some(hasher.finish())
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
+        let ap = calc_angles_and_positions(&time::now());
+
+        let colors = self.pulse(self.current_colors());
+        let clear  = self.background_clear(&colors);
+
+        // With FXAA on, the clock goes into its texture instead, FXAA's pass draws it onto the frame before the help
+        #[cfg(feature = "fxaa")]
+        let fxaa = self.fxaa_enabled.then_some(&self.fxaa);
+        #[cfg(feature = "fxaa")]
+        let (color_target, output) = fxaa.map_or((&self.color_target, texview), |fxaa| (&fxaa.color_target, &fxaa.source.view));
+        #[cfg(not(feature = "fxaa"))]
+        let (color_target, output) = (&self.color_target, texview);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        if !self.split_screen {
+            let mut rpass = begin_pass(&mut encoder, color_target.attachment(&self.target_config, output, wgpu::LoadOp::Clear(clear)));
+            self.draw_clock(&mut rpass, queue, &ap, &colors);
+        } else {
+            let (left, right) = ViewportRect::full(self.size).split_horizontally();
+
+            {
+                // the right half is drawn over the samples of the left one, they're resolved again along with it
+                let mut rpass = begin_pass(&mut encoder, color_target.attachment_kept(&self.target_config, output, wgpu::LoadOp::Clear(clear)));
+                if rpass.set_scissor(left, self.size) {
+                    self.draw_clock(&mut rpass, queue, &ap, &colors);
+                }
+            }
+
+            // each half takes a submission, the uniform slots of the fallback path are only enough for one
+            queue.submit(std::iter::once(encoder.finish()));
+            encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+            let colors = self.pulse(self.transition_colors(ColorCombo::lerp_oklab));
+            let mut rpass = begin_pass(&mut encoder, color_target.attachment(&self.target_config, output, wgpu::LoadOp::Load));
+
+            if rpass.set_scissor(right, self.size) {
+                // clears ignore the scissor rect, the right half's background is filled in with a disk covering it
+                if !self.transparent {
+                    rpass.set_pipeline(&self.disk_pipeline);
+                    rpass.set_bind_group(0, self.drawspace.bind_group(), &[]);
+                    draw_disk(&mut rpass, &self.disk_params, queue, (0.0, 0.0), BACKGROUND_RADIUS, colors.background);
+                }
+
+                self.draw_clock(&mut rpass, queue, &ap, &colors);
+            }
         }
 
-        {
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label:                    None,
-                depth_stencil_attachment: None,
-                timestamp_writes:         None,
-                occlusion_query_set:      None,
-                color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
-                    view: texview,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load:  wgpu::LoadOp::Clear(u32_col_to_wgpu_col(bg_color)),
-                        store: wgpu::StoreOp::Store
-                    }
-                })]
-            });
-
-            rpass.set_pipeline(&self.ring_pipeline);
-            rpass.set_bind_group(0, &self.bind_group, &[]);
-
-            draw_ring(&mut rpass, (0.0, 0.0), HOURS_RADIUS,   ap.hours_angle,   hh_color);
-            draw_ring(&mut rpass, (0.0, 0.0), MINUTES_RADIUS, ap.minutes_angle, mm_color);
-            draw_ring(&mut rpass, (0.0, 0.0), SECONDS_RADIUS, ap.seconds_angle, ss_color);
-
-            ////////////////////////////////////////
-
-            rpass.set_pipeline(&self.disk_pipeline);
-            rpass.set_bind_group(0, &self.bind_group, &[]);
-
-            draw_disk(&mut rpass, ap.hours_pos,   DISK_RADIUS, cr_color);
-            draw_disk(&mut rpass, ap.minutes_pos, DISK_RADIUS, cr_color);
-            draw_disk(&mut rpass, ap.seconds_pos, DISK_RADIUS, cr_color);
-
-            // Performance improvement notes:
-            // This implementation is done via multiple push constant calls, one call for each shape.
-            // A better implementation would be uploading the ring and disk properties into one or two instance buffers
-            // and draw from those buffers, reducing draw calls.
-            // Also, move the constant properties (e.g. radius, thickness) to the shader's (this kills flexibility however)
+        #[cfg(feature = "fxaa")]
+        if let Some(fxaa) = fxaa {
+            fxaa.apply(&mut encoder, texview);
         }
 
         queue.submit(std::iter::once(encoder.finish()));
+
+        if self.show_help {
+            self.help.draw(texview, device, queue);
+        }
     }
 }
 
 fn main() {
-    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    logging::init("polar");
+
+    let (args, app) = Cli::new("polar", "Polar clock: time as concentric arcs")
+        .option("--color", "INDEX", "Color palette index, cycled with Space (default: picked by the OS theme)")
+        .parse();
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    if let Some(value) = app.value("--color") {
+        match value.parse::<usize>() {
+            Ok(index) => { let _ = PALETTE_OVERRIDE.set(index % PALETTE.len()); },
+            Err(_)    => log::warn!("Ignoring invalid palette index '{}'", value)
+        }
+    }
+
+    let _ = STARTUP_PALETTES.set(load_palettes(args.palette_path("polar")));
+
+    if args.take_snapshot::<PolarClock>((512, 512), None, args.msaa) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Polar Clock", &format!("Failed to initialize the windowing system: {}", error)));
     #[allow(unused_mut)]
     let mut builder = winit::window::WindowBuilder::new();
-    let window = builder
+    let builder = builder
         .with_inner_size(winit::dpi::LogicalSize { width: 512.0, height: 512.0 })
-        .with_title("Polar Clock")
+        .with_title("Polar Clock");
+    let window = args.apply_to_window(builder, &event_loop)
         .build(&event_loop)
-        .unwrap();
+        .unwrap_or_else(|error| fatal_error("Polar Clock", &format!("Failed to create the window: {}", error)));
 
-    pollster::block_on(run::<PolarClock>(
+    launch(run_with_options::<PolarClock>(
         event_loop, window,
-        Some(wgpu::Features::PUSH_CONSTANTS))
-    );
+        None, // push constants are used if available
+        RunOptions { aspect_ratio: Some(1.0), msaa_sample_count: args.msaa, ..args.run_options() } // the rings are drawn in a square
+    ));
 }
 
 // Generated using: https://coolors.co/
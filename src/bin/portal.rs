@@ -6,15 +6,22 @@
     windows_subsystem = "windows"
 )]
 #![allow(non_snake_case)]
-use std::{borrow::Cow, fs::read_to_string};
-use wgpu::RenderPipelineDescriptor;
 use clockutils::{
-    run, cast_struct_to_u8_slice, get_resource_folder_for, 
-    ExecDraw, SingleUniformBuffer, DrawspaceScales, RenderTexture,
-    ResourceTexture, BasicFilteringSampler, Vtx3UV, PlyGeoBuffers,
-    SURFACE_FORMAT
+    run_with_options, cast_struct_to_u8_slice, get_resource_folder_for, load_shader, fatal_error, parse_normal_weighting,
+    ExecDraw, FrameInfo, InputState, RunOptions, SingleUniformBuffer, RenderTexture, FullscreenQuadPipeline,
+    ResourceTexture, BasicFilteringSampler, Vtx3UV, Vtx3ColorUV, Vtx3NUVT, PlyGeoBuffers, PlyGeoBuffersNUVT, PlyMesh, PlyWriter, NormalWeighting,
+    camera::{self, Projection},
+    clocks::portal::{parse_location, sky_gradient, solar_altitude, timezone_longitude, DEFAULT_LATITUDE},
+    target::{frame_sample_count, requested_sample_count, ColorTarget, DepthTexture, PipelineBuilder, TargetConfig, TargetPipeline},
+    cli::Cli, logging, time
 };
-use chrono::{Local, Timelike};
+#[cfg(feature = "fxaa")]
+use clockutils::FxaaPass;
+#[cfg(feature = "god-rays")]
+use clockutils::GodRaysPass;
+#[cfg(feature = "taa")]
+use clockutils::{keys, TaaPass, HALTON_JITTER};
+use chrono::{Timelike};
 use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
 
 #[repr(C, align(8))]
@@ -22,18 +29,26 @@ struct MatrixData {
     matrix: glam::Mat4
 }
 
+/// cam_pos /= world_scale, done as scaling the world around the orbit center instead
+fn world_matrix(center: glam::Vec3, world_scale: f32) -> glam::Mat4 {
+    glam::Mat4::from_translation(center)
+        * glam::Mat4::from_scale(glam::Vec3::splat(1.0 / world_scale))
+        * glam::Mat4::from_translation(-center)
+}
+
+/// `world_scale` shrinks the world around the orbit center, as if the camera were that many times farther away
+/// in world units while keeping its distance on screen. The scenery then reads as a tabletop model.
 fn calc_matrix_and_facing(
-    phi: f32, theta: f32, dist: f32, elevation: f32,
-    resolution: glam::Vec2, extent: glam::Vec2
+    phi: f32, theta: f32, dist: f32, center: glam::Vec3, world_scale: f32,
+    projection: Projection, resolution: glam::Vec2
 ) -> (MatrixData, bool) {
-    // Generic orbital camera setup, centered at (0.0, 0.0, elevation)
+    // Generic orbital camera setup
     let rotation = glam::Mat4::from_euler(
         glam::EulerRot::ZXY,
         phi.to_radians(),
         theta.to_radians(),
         0.0
     );
-    let center = glam::Vec3::new(0.0, 0.0, elevation);
 
     let cam_pos = rotation * glam::Vec4::new(0.0, -dist, 0.0, 1.0);
     let cam_up  = rotation * glam::Vec4::new(0.0,   0.0, 1.0, 1.0);
@@ -41,43 +56,428 @@ fn calc_matrix_and_facing(
     let cam_pos = glam::Vec3::new(cam_pos.x, cam_pos.y, cam_pos.z) + center;
     let cam_up  = glam::Vec3::new(cam_up.x,  cam_up.y,  cam_up.z);
 
-    let scale = {
-        let dscales = DrawspaceScales::new(resolution, extent);
+    // Half the distance is visible around the orbit center, whichever the projection
+    let viewproj = camera::view_projection(
+        projection,
+        cam_pos, center, cam_up,
+        resolution,
+        glam::Vec2::splat(dist * 0.5),
+        (1.0, 200.0)
+    );
 
-        glam::Mat4::from_scale(glam::Vec3 {
-            x: dscales.scale[0],
-            y: dscales.scale[1],
-            z: 1.0
-        })
-    };
+    let mat = MatrixData { matrix: viewproj * world_matrix(center, world_scale) };
+    // Do we need to render the day scene or the night scene? (true = day)
+    // Going by where the camera looks rather than where it is, an orthographic one is nowhere in particular
+    let day = (center - cam_pos).y > 0.0;
 
-    let proj = glam::Mat4::perspective_rh(0.5_f32.atan() * 2.0, 1.0, 1.0, 200.0);
+    (mat, day)
+}
 
-    let view = glam::Mat4::look_at_rh(
-        cam_pos,
-        center,
-        cam_up
-    );
+/// Push constants of sky.wgsl, linear RGBA
+#[repr(C, align(16))]
+struct SkyColors {
+    horizon: [f32; 4],
+    zenith:  [f32; 4]
+}
 
-    let mat = MatrixData { matrix: scale * proj * view };
-    let day = cam_pos.y < 0.0; // Do we need to render the day scene or the night scene? (true = day)
+impl SkyColors {
+    fn new(gradient: (wgpu::Color, wgpu::Color)) -> Self {
+        let rgba = |color: wgpu::Color| [color.r as f32, color.g as f32, color.b as f32, color.a as f32];
+        Self { horizon: rgba(gradient.0), zenith: rgba(gradient.1) }
+    }
+}
 
-    (mat, day)
+/// Push constants of textured.wgsl
+#[repr(C, align(8))]
+struct TexturedState {
+    rotated:        u32,
+    mirror_opacity: f32
 }
 
 struct DynamicResources {
-    rtexture_bindgroup: wgpu::BindGroup, // render texture as shader resource (for reading from shader)
+    rtexture_bindgroup:   wgpu::BindGroup, // render texture as shader resource (for reading from shader)
+    reflection_bindgroup: wgpu::BindGroup, // the night scene's reflection, for reading from textured.wgsl
+
+    color_target:     ColorTarget,       // multisampled buffer of all the passes (if multisampling), resolved into their outputs
+    rtexture_color:   wgpu::TextureView, // render-texture color target (for writing on as attachment)
+    rtexture_depth:   DepthTexture,      // render-texture depth target, shared by the reflection pass
+    reflection_color: wgpu::TextureView, // the night scene mirrored at the ground, same size as the render-texture
+    surface_depth:    DepthTexture       // surface/swapchain depth target
+}
+
+#[repr(C, align(8))]
+struct TiltShiftInfo {
+    focus_center: f32, // vertical center of the sharp band, in UV space
+    focus_width:  f32, // half height of the sharp band, in UV space
+    max_radius:   f32, // blur radius at the top and bottom edges, in pixels
+    saturation:   f32
+}
+
+/// Tilt-shift post-processing: the scene is rendered into `target`,
+/// then copied to the surface with a blur that grows towards the top and bottom edges.
+/// Imitates the shallow depth of field of macro photography, which makes things look like miniatures.
+struct TiltShiftPass {
+    pipeline:         TargetPipeline,
+    bindgroup_layout: wgpu::BindGroupLayout,
+    sampler:          BasicFilteringSampler,
+    target:           RenderTexture,
+    bindgroup:        wgpu::BindGroup
+}
+
+impl TiltShiftPass {
+    fn new(size: (u32, u32), format: wgpu::TextureFormat, resources: &std::path::Path, device: &wgpu::Device) -> Result<Self, String> {
+        let bindgroup_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                RenderTexture::default_layout_entry(0),
+                BasicFilteringSampler::default_layout_entry(1)
+            ]
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts:   &[ &bindgroup_layout ],
+            push_constant_ranges: &[
+                wgpu::PushConstantRange {
+                    stages: wgpu::ShaderStages::FRAGMENT,
+                    range:  0..std::mem::size_of::<TiltShiftInfo>() as u32
+                }
+            ]
+        });
+
+        let shader = load_shader(device, &resources.join("shaders/tiltshift.wgsl"))?;
+
+        // always single-sampled, straight onto the surface
+        let pipeline = PipelineBuilder::new(&TargetConfig::new(format, None), &pipeline_layout, &shader)
+            .primitive(wgpu::PrimitiveState {
+                topology:     wgpu::PrimitiveTopology::TriangleStrip,
+                cull_mode:    None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..Default::default()
+            })
+            .build(device);
+
+        let sampler = BasicFilteringSampler::new(device);
+        let (target, bindgroup) = Self::create_target(size, format, &bindgroup_layout, &sampler, device);
+
+        Ok(Self { pipeline, bindgroup_layout, sampler, target, bindgroup })
+    }
+
+    fn create_target(
+        size:    (u32, u32),
+        format:  wgpu::TextureFormat,
+        layout:  &wgpu::BindGroupLayout,
+        sampler: &BasicFilteringSampler,
+        device:  &wgpu::Device
+    ) -> (RenderTexture, wgpu::BindGroup) {
+        let target = RenderTexture::new(size, format, true, device);
+
+        let bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   None,
+            layout,
+            entries: &[ target.get_entry(0), sampler.get_entry(1) ]
+        });
+
+        (target, bindgroup)
+    }
+
+    fn resize(self: &mut Self, size: (u32, u32), device: &wgpu::Device) {
+        (self.target, self.bindgroup) = Self::create_target(size, self.target.format, &self.bindgroup_layout, &self.sampler, device);
+    }
+
+    /// Blur `target` onto `destination`
+    fn apply(self: &Self, encoder: &mut wgpu::CommandEncoder, destination: &wgpu::TextureView) {
+        // scale the blur with the resolution, so that it looks the same regardless of the window size
+        let info = TiltShiftInfo {
+            focus_center: 0.5,
+            focus_width:  0.12,
+            max_radius:   self.target.height as f32 / 80.0,
+            saturation:   1.25
+        };
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label:                    None,
+            depth_stencil_attachment: None,
+            timestamp_writes:         None,
+            occlusion_query_set:      None,
+            color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                view: destination,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load:  wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store
+                }
+            })]
+        });
+
+        rpass.set_pipeline(&self.pipeline.pipeline);
+        rpass.set_bind_group(0, &self.bindgroup, &[]);
+        rpass.set_push_constants(wgpu::ShaderStages::FRAGMENT, 0, cast_struct_to_u8_slice(&info));
+        rpass.draw(0..4, 0..1);
+    }
+}
+
+/// FXAA as the final pass: whatever would go to the surface (the scene, or its tilt-shift blur) goes into `source` first
+#[cfg(feature = "fxaa")]
+struct FxaaStage {
+    pass:      FxaaPass,
+    source:    RenderTexture,
+    bindgroup: wgpu::BindGroup
+}
+
+#[cfg(feature = "fxaa")]
+impl FxaaStage {
+    fn new(size: (u32, u32), format: wgpu::TextureFormat, device: &wgpu::Device) -> Self {
+        let pass = FxaaPass::new(device, format);
+        let source = RenderTexture::new(size, format, true, device);
+        let bindgroup = pass.source_bindgroup(&source, device);
+
+        Self { pass, source, bindgroup }
+    }
+
+    fn resize(self: &mut Self, size: (u32, u32), device: &wgpu::Device) {
+        self.source = RenderTexture::new(size, self.source.format, true, device);
+        self.bindgroup = self.pass.source_bindgroup(&self.source, device);
+    }
+
+    /// Anti-alias `source` onto `destination`
+    fn apply(self: &Self, encoder: &mut wgpu::CommandEncoder, destination: &wgpu::TextureView) {
+        self.pass.apply(encoder, &self.bindgroup, destination);
+    }
+}
+
+/// While the runner multisamples the frame, the post-processing passes (single-sampled) draw into `source` instead,
+/// which is then copied onto the frame. Without post-processing, the scene is drawn onto the frame directly
+struct FrameCopyStage {
+    quad:      FullscreenQuadPipeline, // for the frame's sample count
+    layout:    wgpu::BindGroupLayout,
+    sampler:   BasicFilteringSampler,
+    source:    RenderTexture,
+    bindgroup: wgpu::BindGroup
+}
+
+impl FrameCopyStage {
+    fn new(size: (u32, u32), frame_config: &TargetConfig, shader: &wgpu::ShaderModule, device: &wgpu::Device) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[ RenderTexture::default_layout_entry(0), BasicFilteringSampler::default_layout_entry(1) ]
+        });
+
+        let quad    = FullscreenQuadPipeline::for_target(device, shader, "fs_main", &[ &layout ], &[], frame_config);
+        let sampler = BasicFilteringSampler::new(device);
+        let (source, bindgroup) = Self::create_source(size, frame_config.color_format, &layout, &sampler, device);
+
+        Self { quad, layout, sampler, source, bindgroup }
+    }
+
+    fn create_source(
+        size:    (u32, u32),
+        format:  wgpu::TextureFormat,
+        layout:  &wgpu::BindGroupLayout,
+        sampler: &BasicFilteringSampler,
+        device:  &wgpu::Device
+    ) -> (RenderTexture, wgpu::BindGroup) {
+        let source = RenderTexture::new(size, format, true, device);
+
+        let bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   None,
+            layout,
+            entries: &[ source.get_entry(0), sampler.get_entry(1) ]
+        });
+
+        (source, bindgroup)
+    }
+
+    fn resize(self: &mut Self, size: (u32, u32), device: &wgpu::Device) {
+        (self.source, self.bindgroup) = Self::create_source(size, self.source.format, &self.layout, &self.sampler, device);
+    }
+
+    /// `source` onto the frame
+    fn apply(self: &Self, encoder: &mut wgpu::CommandEncoder, frame: &wgpu::TextureView) {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label:                    None,
+            depth_stencil_attachment: None,
+            timestamp_writes:         None,
+            occlusion_query_set:      None,
+            color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                view:           frame,
+                resolve_target: None,
+                ops:            wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store }
+            })]
+        });
+
+        self.quad.draw(&mut rpass, &[ &self.bindgroup ], &[]);
+    }
+}
 
-    rtexture_color: wgpu::TextureView, // render-texture color target (for writing on as attachment)
-    rtexture_depth: wgpu::TextureView, // render-texture depth target
-    surface_depth:  wgpu::TextureView  // surface/swapchain depth target
+/// The sun's altitude, in degrees, below which the day side gets the god rays, stronger the lower the sun is
+#[cfg(feature = "god-rays")]
+const GOD_RAYS_ALTITUDE: f32 = 15.0;
+
+/// God rays from the day side's sun while it's low, after the tilt-shift blur and before FXAA.
+/// Whatever would go to the next stage goes into `source` first
+#[cfg(feature = "god-rays")]
+struct GodRaysStage {
+    pass:       GodRaysPass,
+    source:     RenderTexture,
+    bindgroup:  wgpu::BindGroup,
+    sun_center: glam::Vec3 // of the sun's mesh, where the rays come from
 }
 
-const DAY_SKY_COLOR:   wgpu::Color = wgpu::Color { r: 1.0,      g: 0.463917, b: 0.125578, a: 1.0 };
-const NIGHT_SKY_COLOR: wgpu::Color = wgpu::Color { r: 0.002352, g: 0.003925, b: 0.021981, a: 1.0 };
+#[cfg(feature = "god-rays")]
+impl GodRaysStage {
+    fn new(size: (u32, u32), format: wgpu::TextureFormat, sun_center: glam::Vec3, device: &wgpu::Device) -> Self {
+        let pass = GodRaysPass::new(device, format);
+        let source = RenderTexture::new(size, format, true, device);
+        let bindgroup = pass.source_bindgroup(&source, device);
+
+        Self { pass, source, bindgroup, sun_center }
+    }
+
+    fn resize(self: &mut Self, size: (u32, u32), device: &wgpu::Device) {
+        self.source = RenderTexture::new(size, self.source.format, true, device);
+        self.bindgroup = self.pass.source_bindgroup(&self.source, device);
+    }
+
+    /// The rays' intensity and the sun's position on the screen in UV space,
+    /// `None` on the night side, while the sun is high, or while it's behind the camera
+    fn rays(self: &Self, matrix: glam::Mat4, altitude: f32, facing_day: bool) -> Option<(f32, glam::Vec2)> {
+        if !facing_day || altitude >= GOD_RAYS_ALTITUDE {
+            return None;
+        }
+
+        let clip = matrix * self.sun_center.extend(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        let ndc = clip.truncate().truncate() / clip.w;
+        let intensity = 1.0 - altitude.max(0.0) / GOD_RAYS_ALTITUDE;
+
+        Some((intensity, glam::Vec2::new(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5)))
+    }
+
+    /// `source` with the rays onto `destination`
+    fn apply(self: &Self, encoder: &mut wgpu::CommandEncoder, destination: &wgpu::TextureView, intensity: f32, sun: glam::Vec2) {
+        self.pass.apply(encoder, &self.bindgroup, destination, sun, intensity * 0.8, 0.94, 0.9);
+    }
+}
+
+/// Temporal anti-aliasing of the scene, before the tilt-shift blur and the other post-processing:
+/// the scene is drawn into `source` with the camera jittered, `apply` blends it into the history
+#[cfg(feature = "taa")]
+struct TaaStage {
+    pass:      TaaPass,
+    source:    RenderTexture,
+    bindgroup: wgpu::BindGroup
+}
+
+#[cfg(feature = "taa")]
+impl TaaStage {
+    fn new(size: (u32, u32), format: wgpu::TextureFormat, device: &wgpu::Device) -> Self {
+        let pass = TaaPass::new(size, device, format);
+        let source = RenderTexture::new(size, format, true, device);
+        let bindgroup = pass.source_bindgroup(&source, device);
+
+        Self { pass, source, bindgroup }
+    }
+
+    fn resize(self: &mut Self, size: (u32, u32), device: &wgpu::Device) {
+        self.pass.resize(size, device);
+        self.source = RenderTexture::new(size, self.source.format, true, device);
+        self.bindgroup = self.pass.source_bindgroup(&self.source, device);
+    }
+
+    /// `source` blended with the previous frames onto `destination`
+    fn apply(self: &mut Self, encoder: &mut wgpu::CommandEncoder, destination: &wgpu::TextureView) {
+        self.pass.apply(encoder, &self.bindgroup, destination);
+    }
+}
+
+/// Bounds of `Portal::world_scale`, and the factor each key press changes it by
+const WORLD_SCALE_MIN:  f32 = 1.0;
+const WORLD_SCALE_MAX:  f32 = 20.0;
+const WORLD_SCALE_STEP: f32 = 1.25;
+
+/// Degrees per second `Portal::angle_phi` turns by while auto-rotating, a full turn every 6 seconds
+const AUTO_ROTATION_SPEED: f32 = 60.0;
+
+/// Units per second `Portal::elevation` changes by while an arrow key is held
+const ELEVATION_SPEED: f32 = 20.0;
+
+/// How far `Portal::pan` takes the orbit center from the portal, so that the scene can't be lost
+const PAN_LIMIT: f32 = 60.0;
+
+/// The orbit center's offset on the ground after dragging by `delta` pixels, seen from `phi` with `units_per_pixel` on screen.
+/// The ground follows the cursor: dragging right moves the center to the left of the view, dragging down moves it ahead
+fn panned(pan: glam::Vec2, delta: (f64, f64), phi: f32, units_per_pixel: f32) -> glam::Vec2 {
+    let (sin, cos) = phi.to_radians().sin_cos();
+    let right      = glam::Vec2::new(cos, sin);
+    let ahead      = glam::Vec2::new(-sin, cos);
+
+    let pan = pan + (-right * delta.0 as f32 + ahead * delta.1 as f32) * units_per_pixel;
+    pan.clamp_length_max(PAN_LIMIT)
+}
+
+/// Where the sun's altitude is computed for (`--location`), the timezone's meridian unless given
+static LOCATION: std::sync::OnceLock<(f64, f64)> = std::sync::OnceLock::new();
+
+/// How the platform's smooth normals weigh the faces, `--normals`
+static NORMAL_WEIGHTING: std::sync::OnceLock<NormalWeighting> = std::sync::OnceLock::new();
+
+/// Folder of `--record-mesh`
+static RECORD_MESH: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+
+/// `--record-mesh`: the terrain as it's shown (scaled with the world, turned around for the night side),
+/// in world space, written into a numbered PLY file once per second
+struct MeshRecorder {
+    folder: std::path::PathBuf,
+    mesh:   PlyMesh,
+    frame:  u32,
+    next:   std::time::Instant
+}
+
+impl MeshRecorder {
+    const INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+    fn new(folder: &std::path::Path, mesh_path: &str) -> Result<Self, String> {
+        std::fs::create_dir_all(folder).map_err(|error| format!("Can't create the mesh recording folder {}: {}", folder.display(), error))?;
+        log::info!("Recording the terrain into {} once per second", folder.display());
+
+        Ok(Self { folder: folder.to_path_buf(), mesh: PlyMesh::new(mesh_path)?, frame: 0, next: std::time::Instant::now() })
+    }
+
+    /// Writes the next file if it's due, `transform` takes the mesh into world space
+    fn record(self: &mut Self, transform: glam::Mat4) -> std::io::Result<()> {
+        if std::time::Instant::now() < self.next {
+            return Ok(());
+        }
+
+        let path = self.folder.join(format!("terrain_{:05}.ply", self.frame));
+        let vertices: Vec<Vtx3UV> = self.mesh.vertices.iter()
+            .map(|vertex| Vtx3UV { pos: transform.transform_point3(vertex.pos), uv: vertex.uv })
+            .collect();
+        let indices: Vec<u32> = self.mesh.indices.iter().map(|index| *index as u32).collect();
+
+        let mut writer = PlyWriter::new(&path, vertices.len(), indices.len() / 3)?;
+        writer.write_vertex_batch(&vertices)?;
+        writer.write_face_batch(&indices)?;
+        writer.finalize()?;
+
+        log::debug!("Recorded {}", path.display());
+        self.frame += 1;
+        self.next   = std::time::Instant::now() + Self::INTERVAL;
+        Ok(())
+    }
+}
 
 const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
+/// Height of the terrain's flat ground, mirrors `GROUND_LEVEL` in textured.wgsl
+const GROUND_LEVEL:   f32 = 0.0;
+/// How much of the reflection shows on the night side's ground
+const MIRROR_OPACITY: f32 = 0.6;
+
 /// Portals in video games are usually drawn by aligning the secondary camera according to the primary(screen) camera.
 /// So that the relative distance and orientation between (primary cam and entry portal) and (secondary cam and leaving portal) are the same.
 /// In this implementation, it is quite simpler cause we're using only one transformation matrix and the camera distance and orientation are already synced.
@@ -88,22 +488,31 @@ const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 /// The UV coordinates of it is based on its screen space coordinates.
 /// 
 /// In this implementation however, no mechanism for teleportation is introduced.
-struct Portal {
-    textured_pipeline: wgpu::RenderPipeline,
-    digits_pipeline:   wgpu::RenderPipeline,
-    portal_pipeline:   wgpu::RenderPipeline,
+pub(crate) struct Portal {
+    pipeline_sources: PipelineSources,
+    pipelines:        ScenePipelines,
+    target_config:    TargetConfig, // of all the scene's passes, the tilt-shift blur is single-sampled on its own
+    sample_counts:    Vec<u32>,     // supported, cycled through with M, the runner's frame follows (see `request_msaa_sample_count`)
 
-    matrix_bindgroup:    wgpu::BindGroup,
-    terrain_bindgroups:  Vec<wgpu::BindGroup>,
-    platform_bindgroups: Vec<wgpu::BindGroup>,
-    digits_bindgroup:    wgpu::BindGroup,
+    scene_bindgroup:         wgpu::BindGroup, // every texture of the scene, see `setup`
+    matrix_bindgroup:        wgpu::BindGroup,
+    mirror_matrix_bindgroup: wgpu::BindGroup, // mirrored at the ground, for the night side's reflection
 
     dynamic_resources: DynamicResources,
-
-    matrix_ubuffer: wgpu::Buffer,
+    tilt_shift_pass:   TiltShiftPass,
+    #[cfg(feature = "fxaa")]
+    fxaa:              FxaaStage,
+    #[cfg(feature = "god-rays")]
+    god_rays:          GodRaysStage,
+    #[cfg(feature = "taa")]
+    taa:               TaaStage,
+    frame_copy:        Option<FrameCopyStage>, // while the runner's frame is multisampled
+
+    matrix_ubuffer:        wgpu::Buffer,
+    mirror_matrix_ubuffer: wgpu::Buffer,
 
     terrain_geometry:  PlyGeoBuffers,
-    platform_geometry: PlyGeoBuffers,
+    platform_geometry: PlyGeoBuffersNUVT,
     sun_geometry:      PlyGeoBuffers,
     moon_geometry:     PlyGeoBuffers,
     digits_geometry:   PlyGeoBuffers,
@@ -113,28 +522,35 @@ struct Portal {
     angle_theta:   f32,
     distance:      f32,
     elevation:     f32,
+    pan:           glam::Vec2, // of the orbit center on the ground, Shift+drag
     auto_rotation: bool,
-    window_size:   (u32, u32)
+    focused:       bool, // auto-rotation pauses while another window has the focus
+    window_size:   (u32, u32),
+    projection:    Projection,
+
+    world_scale:     f32,  // 1.0 => full-sized landscape, larger => tabletop model
+    tilt_shift_blur: bool,
+    mirror_enabled:  bool, // the night side's ground reflects the scenery
+    #[cfg(feature = "fxaa")]
+    fxaa_enabled:    bool, // smooth the edges in screen space, an alternative to multisampling
+    #[cfg(feature = "taa")]
+    taa_enabled:     bool, // accumulate the frames drawn with a jittered camera
+    #[cfg(feature = "taa")]
+    jitter_index:    u32,  // into `HALTON_JITTER`, the next frame's camera offset
+    mesh_recorder:   Option<MeshRecorder>
 }
 
-/// called when scene is resized
-fn create_dynamic_resources(texsize: (u32, u32), device: &wgpu::Device) -> DynamicResources {
+/// called when scene is resized, or multisampling is switched
+fn create_dynamic_resources(texsize: (u32, u32), target_config: &TargetConfig, device: &wgpu::Device) -> DynamicResources {
     let fsampler = BasicFilteringSampler::new(device);
+    // the portal is looked through from any distance, its view should never get blurred by a smaller mip
+    let psampler = BasicFilteringSampler::new_fixed_lod(device, 0.0);
 
-    let rtexture_color = RenderTexture::new(
-        texsize, SURFACE_FORMAT,
-        true, device
-    );
-
-    let rtexture_depth = RenderTexture::new(
-        texsize, DEPTH_FORMAT,
-        false, device
-    );
-
-    let surface_depth = RenderTexture::new(
-        texsize, DEPTH_FORMAT,
-        false, device
-    );
+    let color_target     = ColorTarget::new(texsize, target_config, device);
+    let rtexture_color   = RenderTexture::for_target(texsize, target_config, device);
+    let rtexture_depth   = DepthTexture::new(texsize, target_config, device);
+    let reflection_color = RenderTexture::for_target(texsize, target_config, device);
+    let surface_depth    = DepthTexture::new(texsize, target_config, device);
 
     let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         label: None,
@@ -149,42 +565,217 @@ fn create_dynamic_resources(texsize: (u32, u32), device: &wgpu::Device) -> Dynam
         layout:  &bind_group_layout,
         entries: &[
             rtexture_color.get_entry(0),
+            psampler.get_entry(1)
+        ]
+    });
+
+    let reflection_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label:   None,
+        layout:  &bind_group_layout,
+        entries: &[
+            reflection_color.get_entry(0),
             fsampler.get_entry(1)
         ]
     });
 
     DynamicResources {
-        rtexture_bindgroup: bind_group,
+        rtexture_bindgroup:   bind_group,
+        reflection_bindgroup: reflection_bind_group,
+
+        color_target,
+        rtexture_color:   rtexture_color.view,
+        rtexture_depth,
+        reflection_color: reflection_color.view,
+        surface_depth
+    }
+}
+
+/// What the scene's pipelines are built from, kept for rebuilding them when multisampling is switched
+struct PipelineSources {
+    textured_shader:     wgpu::ShaderModule,
+    normalmapped_shader: wgpu::ShaderModule,
+    digits_shader:       wgpu::ShaderModule,
+    portal_shader:       wgpu::ShaderModule,
+    sky_shader:          wgpu::ShaderModule,
+    copy_shader:         wgpu::ShaderModule, // for `FrameCopyStage`
+
+    // the pipelines share the bindgroups of the layout they have in common, so they're bound once per pass
+    primary_layout:  wgpu::PipelineLayout, // scene textures, matrix
+    textured_layout: wgpu::PipelineLayout  // scene textures, matrix, texture+sampler
+}
+
+/// All of them are drawn with the same `TargetConfig`
+struct ScenePipelines {
+    textured:           TargetPipeline,
+    terrain:            TargetPipeline,
+    normalmapped:       TargetPipeline,
+    digits:             TargetPipeline,
+    portal:             TargetPipeline,
+    reflection:         TargetPipeline,
+    terrain_reflection: TargetPipeline,
+    sky:                FullscreenQuadPipeline
+}
+
+/// called at setup, and when multisampling is switched
+fn create_scene_pipelines(sources: &PipelineSources, target_config: &TargetConfig, device: &wgpu::Device) -> ScenePipelines {
+    // used for the terrain, platform, sun and moon
+    let primitive_state_culling = wgpu::PrimitiveState {
+        topology:     wgpu::PrimitiveTopology::TriangleList,
+        cull_mode:    Some(wgpu::Face::Back),
+        polygon_mode: wgpu::PolygonMode::Fill,
+        ..Default::default()
+    };
+
+    // used for the reflections, the mirroring flips the winding order
+    let primitive_state_mirrored = wgpu::PrimitiveState {
+        cull_mode: Some(wgpu::Face::Front),
+        ..primitive_state_culling
+    };
+
+    // used for the portal and the digits
+    let primitive_state_nocull = wgpu::PrimitiveState {
+        topology:     wgpu::PrimitiveTopology::TriangleList,
+        cull_mode:    None,
+        polygon_mode: wgpu::PolygonMode::Fill,
+        ..Default::default()
+    };
+
+    let vertex_buffer_layouts          = [ Vtx3UV::wgpu_layout() ];
+    let color_uv_vertex_buffer_layouts = [ Vtx3ColorUV::wgpu_layout() ];
+    let nuvt_vertex_buffer_layouts     = [ Vtx3NUVT::wgpu_layout() ];
+
+    ScenePipelines {
+        // Simple pipeline for drawing basic textured meshes (sun/moon)
+        // Supports 180 deg rotation
+        // The night side's ground mirrors the reflection texture (as the 3rd bindgroup)
+        textured: PipelineBuilder::new(target_config, &sources.textured_layout, &sources.textured_shader)
+            .vertex("vs_main", &vertex_buffer_layouts)
+            .primitive(primitive_state_culling)
+            .build(device),
+
+        // The terrain: like the textured pipeline, with the materials splatted onto the lightmap
+        terrain: PipelineBuilder::new(target_config, &sources.textured_layout, &sources.textured_shader)
+            .vertex("vs_terrain", &color_uv_vertex_buffer_layouts)
+            .fragment("fs_terrain")
+            .primitive(primitive_state_culling)
+            .build(device),
+
+        // The platform: lightmap plus a tiling stone normal map
+        normalmapped: PipelineBuilder::new(target_config, &sources.primary_layout, &sources.normalmapped_shader)
+            .vertex("vs_main", &nuvt_vertex_buffer_layouts)
+            .primitive(primitive_state_culling)
+            .build(device),
+
+        // Draw digits on quad by addressing into the sprite sheet. Multi instance.
+        // instance=0 gets drawn normally,
+        // instance=1 gets flipped on x-axis
+        // Supports UV flipping
+        // Supports 180 deg rotation
+        digits: PipelineBuilder::new(target_config, &sources.primary_layout, &sources.digits_shader)
+            .vertex("vs_main", &vertex_buffer_layouts)
+            .primitive(primitive_state_nocull)
+            .blend(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation:  wgpu::BlendOperation::Add
+                },
+                alpha: wgpu::BlendComponent::REPLACE
+            })
+            .build(device),
 
-        rtexture_color: rtexture_color.view,
-        rtexture_depth: rtexture_depth.view,
-        surface_depth:  surface_depth.view
+        // Main portal drawing pipeline
+        // Supports obtaining UV coordinates from screen-space coordinates
+        // The other world's render texture is the 3rd bindgroup
+        portal: PipelineBuilder::new(target_config, &sources.textured_layout, &sources.portal_shader)
+            .vertex("vs_main", &vertex_buffer_layouts)
+            .primitive(primitive_state_nocull)
+            .build(device),
+
+        // The sun/moon mirrored at the ground, for the reflection texture. The ground itself is left out
+        reflection: PipelineBuilder::new(target_config, &sources.primary_layout, &sources.textured_shader)
+            .vertex("vs_main", &vertex_buffer_layouts)
+            .fragment("fs_reflected")
+            .primitive(primitive_state_mirrored)
+            .build(device),
+
+        // The terrain's counterpart of the reflection pipeline
+        terrain_reflection: PipelineBuilder::new(target_config, &sources.primary_layout, &sources.textured_shader)
+            .vertex("vs_terrain", &color_uv_vertex_buffer_layouts)
+            .fragment("fs_terrain_reflected")
+            .primitive(primitive_state_mirrored)
+            .build(device),
+
+        // The other world's sky, a vertical gradient behind everything else
+        sky: FullscreenQuadPipeline::for_target(
+            device, &sources.sky_shader, "fs_main",
+            &[],
+            &[
+                wgpu::PushConstantRange {
+                    stages: wgpu::ShaderStages::FRAGMENT,
+                    range:  0..std::mem::size_of::<SkyColors>() as u32
+                }
+            ],
+            target_config
+        )
+    }
+}
+
+impl Portal {
+    /// What the camera orbits around and the world is scaled around, above the portal unless panned
+    fn orbit_center(self: &Self) -> glam::Vec3 {
+        glam::Vec3::new(self.pan.x, self.pan.y, self.elevation)
+    }
+
+    /// Rebuilds everything the sample count is baked into: the scene's pipelines and its targets
+    fn set_sample_count(self: &mut Self, sample_count: u32, device: &wgpu::Device) {
+        self.target_config     = self.target_config.with_sample_count(sample_count);
+        self.pipelines         = create_scene_pipelines(&self.pipeline_sources, &self.target_config, device);
+        self.dynamic_resources = create_dynamic_resources(self.window_size, &self.target_config, device);
+
+        log::info!("{}x multisampling", sample_count);
     }
 }
 
 impl ExecDraw for Portal {
     fn setup(
         config:   &wgpu::SurfaceConfiguration,
-        _adapter: &wgpu::Adapter,
+        adapter:  &wgpu::Adapter,
         device:   &wgpu::Device,
-        queue:    &wgpu::Queue
-    ) -> Self where Self: Sized {
-        // In this implementation, bindgroups are fragmented (1 or 2 resources per bindgroup) to increase flexibility
-        let resources = get_resource_folder_for("portal").unwrap();
+        queue:    &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> where Self: Sized {
+        // In this implementation, the static textures share a single bindgroup, so that the passes hardly switch bindgroups
+        let resources = get_resource_folder_for("portal").map_err(|error| error.to_string())?;
         
         // load the 3D meshes
-        let terrain_geometry  = PlyGeoBuffers::new(device, resources.join("meshes/terrain_geo.ply").as_path().to_str().unwrap());
-        let platform_geometry = PlyGeoBuffers::new(device, resources.join("meshes/platform_geo.ply").as_path().to_str().unwrap());
-
-        let sun_geometry  = PlyGeoBuffers::new(device, resources.join("meshes/sun_geo.ply").as_path().to_str().unwrap());
-        let moon_geometry = PlyGeoBuffers::new(device, resources.join("meshes/moon_geo.ply").as_path().to_str().unwrap());
+        let terrain_path      = resources.join("meshes/terrain_geo_blended.ply");
+        let terrain_geometry  = PlyGeoBuffers::new_colored(device, terrain_path.as_path().to_str().unwrap())?;
+        let mesh_recorder     = RECORD_MESH.get().map(|folder| MeshRecorder::new(folder, terrain_path.as_path().to_str().unwrap())).transpose()?;
+        let platform_geometry = PlyGeoBuffersNUVT::new_with_weighting(
+            device,
+            resources.join("meshes/platform_geo.ply").as_path().to_str().unwrap(),
+            NORMAL_WEIGHTING.get().copied().unwrap_or_default()
+        )?;
+
+        // the plain meshes can come from OBJ files as well: `ObjGeoBuffers::new` in place of `PlyGeoBuffers::new`,
+        // drawn through `Vtx3NUV::wgpu_layout_without_normals()` in place of `Vtx3UV::wgpu_layout()`
+        let sun_geometry  = PlyGeoBuffers::new(device, resources.join("meshes/sun_geo.ply").as_path().to_str().unwrap())?;
+        #[cfg(feature = "god-rays")]
+        let sun_center = {
+            let sun = PlyMesh::new(resources.join("meshes/sun_geo.ply").as_path().to_str().unwrap())?;
+            sun.vertices.iter().map(|vertex| vertex.pos).sum::<glam::Vec3>() / sun.vertices.len().max(1) as f32
+        };
+        let moon_geometry = PlyGeoBuffers::new(device, resources.join("meshes/moon_geo.ply").as_path().to_str().unwrap())?;
 
-        let digits_geometry = PlyGeoBuffers::new(device, resources.join("meshes/digit_geo.ply").as_path().to_str().unwrap());
+        let digits_geometry = PlyGeoBuffers::new(device, resources.join("meshes/digit_geo.ply").as_path().to_str().unwrap())?;
 
-        let portal_geometry = PlyGeoBuffers::new(device, resources.join("meshes/portal_geo.ply").as_path().to_str().unwrap());
+        let portal_geometry = PlyGeoBuffers::new(device, resources.join("meshes/portal_geo.ply").as_path().to_str().unwrap())?;
 
         let fsampler = BasicFilteringSampler::new(device);
+        let rsampler = BasicFilteringSampler::with_address_mode(device, wgpu::AddressMode::Repeat);
 
+        // a texture+sampler pair, for the screen sized textures of the dynamic resources
         let common_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: None,
             entries: &[
@@ -193,30 +784,57 @@ impl ExecDraw for Portal {
             ]
         });
 
-        // load a texture and form a single bindgroup from it
-        let texture_to_bindgroup = |path: &str| {
-            let texture = ResourceTexture::new(path, device, queue);
-            device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label:   None,
-                layout:  &common_bind_group_layout,
-                entries: &[ texture.get_entry(0), fsampler.get_entry(1) ]
-            })
-        };
+        // Every texture of the scene in a single bindgroup, bound once per pass. The day and night lightmaps are
+        // the layers of an array each, the shaders pick the layer by the side they're drawing (the `rotated` flag).
+        // The terrain's materials (grass, rock, sand, snow) are splatted onto its lightmap by the vertices' blend weights,
+        // they tile with the repeating sampler, and so does the platform's stone normal map
+        let scene_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Portal scene"),
+            entries: &[
+                ResourceTexture::array_layout_entry(0),
+                ResourceTexture::array_layout_entry(1),
+                ResourceTexture::default_layout_entry(2),
+                BasicFilteringSampler::default_layout_entry(3),
+                ResourceTexture::default_layout_entry(4),
+                ResourceTexture::default_layout_entry(5),
+                ResourceTexture::default_layout_entry(6),
+                ResourceTexture::default_layout_entry(7),
+                BasicFilteringSampler::default_layout_entry(8),
+                ResourceTexture::default_layout_entry(9)
+            ]
+        });
 
-        // [day, night]
-        let terrain_bindgroups: Vec<wgpu::BindGroup> = [
-            resources.join("textures/terrain_lightmap_day.png").as_path().to_str().unwrap(),
-            resources.join("textures/terrain_lightmap_night.png").as_path().to_str().unwrap()
-        ].into_iter().map(texture_to_bindgroup).collect();
+        let texture_path = |name: &str| resources.join("textures").join(name).as_path().to_str().unwrap().to_string();
 
         // [day, night]
-        let platform_bindgroups: Vec<wgpu::BindGroup> = [
-            resources.join("textures/portal_lightmap_day.png").as_path().to_str().unwrap(),
-            resources.join("textures/portal_lightmap_night.png").as_path().to_str().unwrap()
-        ].into_iter().map(texture_to_bindgroup).collect();
+        let terrain_lightmaps  = ResourceTexture::array(&[ &texture_path("terrain_lightmap_day.png"), &texture_path("terrain_lightmap_night.png") ], device, queue)?;
+        let platform_lightmaps = ResourceTexture::array(&[ &texture_path("portal_lightmap_day.png"),  &texture_path("portal_lightmap_night.png")  ], device, queue)?;
 
         // digits sprite sheet
-        let digits_bindgroup = texture_to_bindgroup(resources.join("textures/beurmon_digits.png").as_path().to_str().unwrap());
+        let digits = ResourceTexture::new(&texture_path("beurmon_digits.png"), device, queue)?;
+
+        let materials: Vec<ResourceTexture> = ["grass", "rock", "sand", "snow"]
+            .map(|name| ResourceTexture::new(&texture_path(&format!("terrain_{}.png", name)), device, queue))
+            .into_iter().collect::<Result<_, _>>()?;
+
+        let stone_normal = ResourceTexture::new(&texture_path("stone_normal.png"), device, queue)?;
+
+        let scene_bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   Some("Portal scene"),
+            layout:  &scene_bind_group_layout,
+            entries: &[
+                terrain_lightmaps.get_entry(0),
+                platform_lightmaps.get_entry(1),
+                digits.get_entry(2),
+                fsampler.get_entry(3),
+                materials[0].get_entry(4),
+                materials[1].get_entry(5),
+                materials[2].get_entry(6),
+                materials[3].get_entry(7),
+                rsampler.get_entry(8),
+                stone_normal.get_entry(9)
+            ]
+        });
 
         // the transformation matrix
         let (matrix_ubuffer, matrix_bindgroup, matrix_bindgroup_layout) = {
@@ -236,50 +854,33 @@ impl ExecDraw for Portal {
             (umatrix.buffer, bind_group, bind_group_layout)
         };
 
-        let dynamic_resources = create_dynamic_resources((config.width, config.height), device);
+        // the transformation matrix mirrored at the ground, for rendering the night scene's reflection
+        let (mirror_matrix_ubuffer, mirror_matrix_bindgroup) = {
+            let umatrix = SingleUniformBuffer::new::<MatrixData>(device, wgpu::ShaderStages::VERTEX_FRAGMENT);
 
-        let depth_stencil_state = wgpu::DepthStencilState {
-            format: DEPTH_FORMAT,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default()
-        };
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label:   None,
+                layout:  &matrix_bindgroup_layout,
+                entries: &[ umatrix.get_entry(0) ]
+            });
 
-        // used for the terrain, platform, sun and moon
-        let primitive_state_culling = wgpu::PrimitiveState {
-            topology:     wgpu::PrimitiveTopology::TriangleList,
-            cull_mode:    Some(wgpu::Face::Back),
-            polygon_mode: wgpu::PolygonMode::Fill,
-            ..Default::default()
+            (umatrix.buffer, bind_group)
         };
 
-        // used for the portal
-        let primitive_state_nocull = wgpu::PrimitiveState {
-            topology:     wgpu::PrimitiveTopology::TriangleList,
-            cull_mode:    None,
-            polygon_mode: wgpu::PolygonMode::Fill,
-            ..Default::default()
-        };
+        // Multisampling as requested (--msaa), as far as supported, switchable at runtime with M.
+        // The runner's frame, which the scene is drawn onto, is multisampled along with it
+        let base_config  = TargetConfig::new(config.format, Some(DEPTH_FORMAT));
+        let sample_counts: Vec<u32> = [1, 2, 4, 8].into_iter().filter(|count| base_config.supports(*count, adapter, device)).collect();
+        let target_config = base_config.with_supported_sample_count(requested_sample_count(), adapter, device);
 
-        // { pos: vec3, uv: vec2 }
-        let vertex_buffer_layouts = [
-            wgpu::VertexBufferLayout {
-                array_stride: std::mem::size_of::<Vtx3UV>() as wgpu::BufferAddress,
-                step_mode:    wgpu::VertexStepMode::Vertex,
-                attributes:   &[
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, shader_location: 0, offset: 0 },
-                    wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 1, offset: 3 * std::mem::size_of::<f32>() as u64 }
-                ]
-            }
-        ];
+        let dynamic_resources = create_dynamic_resources((config.width, config.height), &target_config, device);
+        let tilt_shift_pass   = TiltShiftPass::new((config.width, config.height), config.format, &resources, device)?;
 
-        // same layout for all
-        // takes in one texture+sampler pair, and one transformation matrix uniform buffer as bindgroup.
+        // takes in the scene's textures, and one transformation matrix uniform buffer as bindgroups.
         // Also room for max 8 bytes of push constants
-        let primary_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        let primary_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
-            bind_group_layouts:   &[ &common_bind_group_layout, &matrix_bindgroup_layout ],
+            bind_group_layouts:   &[ &scene_bind_group_layout, &matrix_bindgroup_layout ],
             push_constant_ranges: &[
                 wgpu::PushConstantRange {
                     stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
@@ -288,126 +889,54 @@ impl ExecDraw for Portal {
             ]
         });
 
-        let color_target_state = wgpu::ColorTargetState {
-            format: SURFACE_FORMAT,
-            blend:  Some(wgpu::BlendState {
-                color: wgpu::BlendComponent {
-                    src_factor: wgpu::BlendFactor::SrcAlpha,
-                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                    operation:  wgpu::BlendOperation::Add
-                },
-                alpha: wgpu::BlendComponent::REPLACE
-            }),
-            write_mask: wgpu::ColorWrites::ALL
-        };
-
-        // Simple pipeline for drawing basic textured meshes (terrain, platform)
-        // Supports 180 deg rotation
-        let textured_pipeline = {
-            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label:  None,
-                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
-                    read_to_string(resources.join("shaders/textured.wgsl")).unwrap().as_str()
-                ))
-            });
-
-            device.create_render_pipeline(&RenderPipelineDescriptor {
-                label:  None,
-                layout: Some(&primary_pipeline_layout),
-                depth_stencil: Some(depth_stencil_state.clone()),
-                multisample: wgpu::MultisampleState::default(),
-                multiview:   None,
-                vertex: wgpu::VertexState {
-                    module:      &shader,
-                    entry_point: "vs_main",
-                    buffers:     &vertex_buffer_layouts
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module:      &shader,
-                    entry_point: "fs_main",
-                    targets:     &[ Some(SURFACE_FORMAT.into()) ]
-                }),
-                primitive: primitive_state_culling
-            })
-        };
-
-        // Draw digits on quad by addressing into the sprite sheet. Multi instance.
-        // instance=0 gets drawn normally,
-        // instance=1 gets flipped on x-axis
-        // Supports UV flipping
-        // Supports 180 deg rotation
-        let digits_pipeline = {
-            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label:  None,
-                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
-                    read_to_string(resources.join("shaders/digits.wgsl")).unwrap().as_str()
-                ))
-            });
-
-            device.create_render_pipeline(&RenderPipelineDescriptor {
-                label:  None,
-                layout: Some(&primary_pipeline_layout),
-                depth_stencil: Some(depth_stencil_state.clone()),
-                multisample: wgpu::MultisampleState::default(),
-                multiview:   None,
-                vertex: wgpu::VertexState {
-                    module:      &shader,
-                    entry_point: "vs_main",
-                    buffers:     &vertex_buffer_layouts
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module:      &shader,
-                    entry_point: "fs_main",
-                    targets:     &[ Some(color_target_state) ]
-                }),
-                primitive: primitive_state_nocull
-            })
-        };
+        // plus a texture+sampler pair as the 3rd bindgroup (the reflection, or the other world for the portal)
+        let textured_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts:   &[ &scene_bind_group_layout, &matrix_bindgroup_layout, &common_bind_group_layout ],
+            push_constant_ranges: &[
+                wgpu::PushConstantRange {
+                    stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    range:  0..8
+                }
+            ]
+        });
 
-        // Main portal drawing pipeline
-        // Supports obtaining UV coordinates from screen-space coordinates
-        let portal_pipeline = {
-            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label:  None,
-                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
-                    read_to_string(resources.join("shaders/portal.wgsl")).unwrap().as_str()
-                ))
-            });
+        let pipeline_sources = PipelineSources {
+            textured_shader:     load_shader(device, &resources.join("shaders/textured.wgsl"))?,
+            normalmapped_shader: load_shader(device, &resources.join("shaders/normalmapped.wgsl"))?,
+            digits_shader:       load_shader(device, &resources.join("shaders/digits.wgsl"))?,
+            portal_shader:       load_shader(device, &resources.join("shaders/portal.wgsl"))?,
+            sky_shader:          load_shader(device, &resources.join("shaders/sky.wgsl"))?,
+            copy_shader:         load_shader(device, &resources.join("shaders/copy.wgsl"))?,
 
-            device.create_render_pipeline(&RenderPipelineDescriptor {
-                label:  None,
-                layout: Some(&primary_pipeline_layout),
-                depth_stencil: Some(depth_stencil_state.clone()),
-                multisample: wgpu::MultisampleState::default(),
-                multiview:   None,
-                vertex: wgpu::VertexState {
-                    module:      &shader,
-                    entry_point: "vs_main",
-                    buffers:     &vertex_buffer_layouts
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module:      &shader,
-                    entry_point: "fs_main",
-                    targets:     &[ Some(SURFACE_FORMAT.into()) ]
-                }),
-                primitive: primitive_state_nocull
-            })
+            primary_layout,
+            textured_layout
         };
 
+        let pipelines = create_scene_pipelines(&pipeline_sources, &target_config, device);
 
-        Self {
-            textured_pipeline,
-            digits_pipeline,
-            portal_pipeline,
+        Ok(Self {
+            pipeline_sources,
+            pipelines,
+            target_config,
+            sample_counts,
 
+            scene_bindgroup,
             matrix_bindgroup,
-            terrain_bindgroups,
-            platform_bindgroups,
-            digits_bindgroup,
+            mirror_matrix_bindgroup,
             
             dynamic_resources,
+            tilt_shift_pass,
+            #[cfg(feature = "fxaa")]
+            fxaa: FxaaStage::new((config.width, config.height), config.format, device),
+            #[cfg(feature = "god-rays")]
+            god_rays: GodRaysStage::new((config.width, config.height), config.format, sun_center, device),
+            #[cfg(feature = "taa")]
+            taa: TaaStage::new((config.width, config.height), config.format, device),
+            frame_copy: None, // made for the frame's sample count when drawing
             
             matrix_ubuffer,
+            mirror_matrix_ubuffer,
             
             terrain_geometry,
             platform_geometry,
@@ -421,37 +950,102 @@ impl ExecDraw for Portal {
             angle_theta:   0.0,
             distance:      70.0,
             elevation:     10.0,
+            pan:           glam::Vec2::ZERO,
             auto_rotation: true,
-            window_size:   (config.width, config.height)
-        }
+            focused:       true,
+            window_size:   (config.width, config.height),
+            projection:    Projection::default(),
+
+            world_scale:     1.0,
+            tilt_shift_blur: false,
+            mirror_enabled:  false,
+            #[cfg(feature = "fxaa")]
+            fxaa_enabled:    false,
+            #[cfg(feature = "taa")]
+            taa_enabled:     false,
+            #[cfg(feature = "taa")]
+            jitter_index:    0,
+            mesh_recorder
+        })
     }
 
     fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, _queue: &wgpu::Queue) {
-        let dynamic_resources = create_dynamic_resources((width, height), device);
+        let dynamic_resources = create_dynamic_resources((width, height), &self.target_config, device);
         
         self.dynamic_resources = dynamic_resources;
+        self.tilt_shift_pass.resize((width, height), device);
+        #[cfg(feature = "fxaa")]
+        self.fxaa.resize((width, height), device);
+        #[cfg(feature = "god-rays")]
+        self.god_rays.resize((width, height), device);
+        #[cfg(feature = "taa")]
+        self.taa.resize((width, height), device);
+        if let Some(frame_copy) = &mut self.frame_copy {
+            frame_copy.resize((width, height), device);
+        }
         self.window_size = (width, height);
     }
 
-    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _device: &wgpu::Device, _queue: &wgpu::Queue) {
-        let ELEVATION_SHIFT: f32 = 1.0;
+    /// The scene's, so that its last pass can draw onto the frame directly
+    fn request_msaa_sample_count(self: &Self) -> Option<u32> {
+        Some(self.target_config.sample_count)
+    }
+
+    fn update(self: &mut Self, dt: f32, input: &InputState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        // held rather than pressed, smooth whatever the OS key repeat rate
+        if input.is_down(winit::keyboard::KeyCode::ArrowUp) {
+            self.elevation -= ELEVATION_SPEED * dt;
+        }
+        if input.is_down(winit::keyboard::KeyCode::ArrowDown) {
+            self.elevation += ELEVATION_SPEED * dt;
+        }
+    }
+
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, device: &wgpu::Device, _queue: &wgpu::Queue) {
+        // T alone is the tilt-shift blur
+        #[cfg(feature = "taa")]
+        if keys::pressed(&event, &[("Ctrl+T", "Toggle temporal anti-aliasing")]).is_some() {
+            self.taa_enabled = !self.taa_enabled;
+            self.taa.pass.reset();
+            return;
+        }
+
         if event.state == winit::event::ElementState::Pressed {
             match event.key_without_modifiers().as_ref() {
-                winit::keyboard::Key::Named(winit::keyboard::NamedKey::ArrowUp) => {
-                    self.elevation -= ELEVATION_SHIFT;
+                winit::keyboard::Key::Character("+") | winit::keyboard::Key::Character("=") => {
+                    self.world_scale = (self.world_scale * WORLD_SCALE_STEP).min(WORLD_SCALE_MAX);
+                },
+                winit::keyboard::Key::Character("-") => {
+                    self.world_scale = (self.world_scale / WORLD_SCALE_STEP).max(WORLD_SCALE_MIN);
+                },
+                winit::keyboard::Key::Character("T") | winit::keyboard::Key::Character("t") if !event.repeat => {
+                    self.tilt_shift_blur = !self.tilt_shift_blur;
+                },
+                winit::keyboard::Key::Character("R") | winit::keyboard::Key::Character("r") if !event.repeat => {
+                    self.mirror_enabled = !self.mirror_enabled;
+                },
+                #[cfg(feature = "fxaa")]
+                winit::keyboard::Key::Character("A") | winit::keyboard::Key::Character("a") if !event.repeat => {
+                    self.fxaa_enabled = !self.fxaa_enabled;
+                },
+                winit::keyboard::Key::Character("O") | winit::keyboard::Key::Character("o") if !event.repeat => {
+                    self.projection = self.projection.toggled();
                 },
-                winit::keyboard::Key::Named(winit::keyboard::NamedKey::ArrowDown) => {
-                    self.elevation += ELEVATION_SHIFT;
+                winit::keyboard::Key::Character("M") | winit::keyboard::Key::Character("m") if !event.repeat => {
+                    // the next supported sample count, wrapping around
+                    let current = self.sample_counts.iter().position(|count| *count == self.target_config.sample_count).unwrap_or(0);
+                    let next    = self.sample_counts[(current + 1) % self.sample_counts.len()];
+                    self.set_sample_count(next, device);
                 },
                 _ => {}
             }
         }
     }
 
-    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, frame: &FrameInfo) {
 
         fn draw_geometry<'a, 'b>(rpass: &mut wgpu::RenderPass<'a>, geo: &'b PlyGeoBuffers, instances: u32) where 'b: 'a {
-            rpass.set_index_buffer(geo.ibuffer.slice(..), wgpu::IndexFormat::Uint16);
+            rpass.set_index_buffer(geo.ibuffer.slice(..), geo.index_format);
             rpass.set_vertex_buffer(0, geo.vbuffer.slice(..));
 
             rpass.draw_indexed(0..geo.icount as u32, 0, 0..instances);
@@ -459,20 +1053,54 @@ impl ExecDraw for Portal {
 
         // As the transformation matrix updates very frequently (e.g. every frame)
         // The updating of its uniform buffer is moved to the draw function
+        let resolution = glam::Vec2::new(self.window_size.0 as f32, self.window_size.1 as f32);
         let (matdata, facing_day) = calc_matrix_and_facing(
-            self.angle_phi, self.angle_theta, self.distance, self.elevation,
-            glam::Vec2::new(self.window_size.0 as f32, self.window_size.1 as f32),
-            glam::Vec2::new(1.0, 1.0)
+            self.angle_phi, self.angle_theta, self.distance, self.orbit_center(), self.world_scale,
+            self.projection,
+            resolution
         );
 
+        // with TAA, each frame is seen from a slightly different sub-pixel position
+        #[cfg(feature = "taa")]
+        let matdata = if self.taa_enabled {
+            let jitter = TaaPass::jitter(self.jitter_index, resolution);
+            self.jitter_index = (self.jitter_index + 1) % HALTON_JITTER.len() as u32;
+
+            MatrixData { matrix: jitter * matdata.matrix }
+        } else {
+            matdata
+        };
+
         queue.write_buffer(&self.matrix_ubuffer, 0, cast_struct_to_u8_slice(&matdata));
 
+        // only the night side has the mirror
+        let mirroring = self.mirror_enabled && !facing_day;
+
+        let orbit_center = self.orbit_center();
+        if let Some(recorder) = &mut self.mesh_recorder {
+            // the night side's scenery is turned around, like the shaders do
+            let rotation = if facing_day { glam::Mat4::IDENTITY } else { glam::Mat4::from_scale(glam::Vec3::new(-1.0, -1.0, 1.0)) };
+
+            if let Err(error) = recorder.record(world_matrix(orbit_center, self.world_scale) * rotation) {
+                log::warn!("Stopped recording the terrain: {}", error);
+                self.mesh_recorder = None;
+            }
+        }
+
+        if mirroring {
+            let mirror = glam::Mat4::from_translation(glam::Vec3::Z * GROUND_LEVEL)
+                * glam::Mat4::from_scale(glam::Vec3::new(1.0, 1.0, -1.0))
+                * glam::Mat4::from_translation(glam::Vec3::Z * -GROUND_LEVEL);
+
+            let mirrored = MatrixData { matrix: matdata.matrix * mirror };
+            queue.write_buffer(&self.mirror_matrix_ubuffer, 0, cast_struct_to_u8_slice(&mirrored));
+        }
+
         // Obtained the two digits of current time, packed into a single u32
         // day scene => hour digits
         // night scene => minute digits
+        let now = time::now();
         let digits: u32 = {
-            let now = Local::now();
-
             let selected = if facing_day { now.hour() } else { now.minute() };
 
             let tens = selected / 10;
@@ -481,45 +1109,89 @@ impl ExecDraw for Portal {
             tens << 16 | ones
         };
 
+        // The sky follows the real sun: the day side shows it as far above the horizon as it is from it (either way),
+        // the night side as far below. Around sunrise and sunset, both get the horizon's glow
+        let (latitude, longitude) = LOCATION.get().copied()
+            .unwrap_or_else(|| (DEFAULT_LATITUDE, timezone_longitude(&now)));
+        let altitude = solar_altitude(&now, latitude, longitude).abs();
+
+        let day_sky   = sky_gradient(altitude);
+        let night_sky = sky_gradient(-altitude);
+        let sky = if facing_day { day_sky } else { night_sky };
+
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
+        let config    = &self.target_config;
+        let pipelines = &self.pipelines;
+        let resources = &self.dynamic_resources;
+
+        // The night scene's reflection: the same scene as below, mirrored at the ground and without it.
+        // It's seen from the same camera, so the ground samples it at its own screen space coordinates
+        if mirroring {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    None,
+                depth_stencil_attachment: Some(resources.rtexture_depth.attachment(config, false)),
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(resources.color_target.attachment(
+                    config, &resources.reflection_color, wgpu::LoadOp::Clear(night_sky.0) // the mirrored sky, mostly near the horizon
+                ))]
+            });
+
+            rpass.set_bind_group(0, &self.scene_bindgroup, &[]);
+            rpass.set_bind_group(1, &self.mirror_matrix_bindgroup, &[]);
+
+            pipelines.terrain_reflection.set(&mut rpass, config);
+            rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&TexturedState { rotated: 1, mirror_opacity: 0.0 }));
+            draw_geometry(&mut rpass, &self.terrain_geometry, 1);
+
+            pipelines.reflection.set(&mut rpass, config);
+            rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&TexturedState { rotated: 1, mirror_opacity: 0.0 }));
+            draw_geometry(&mut rpass, &self.moon_geometry, 1);
+
+            pipelines.digits.set(&mut rpass, config);
+            rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&[1_u32, digits]));
+            draw_geometry(&mut rpass, &self.digits_geometry, 2);
+        }
+
         // 1st Render pass, draw the terrain+sun/moon+digits, a.k.a. the "other world"
         // For the night scene, the terrain+moon+digits are rotated 180 degs so that we don't need to move the camera or used a 2nd camera
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label:                    None,
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.dynamic_resources.rtexture_depth,
-                    depth_ops: Some(wgpu::Operations {
-                        load:  wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store
-                    }),
-                    stencil_ops: None
-                }),
-                timestamp_writes:    None,
-                occlusion_query_set: None,
-                color_attachments:   &[Some(wgpu::RenderPassColorAttachment {
-                    view: &self.dynamic_resources.rtexture_color,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load:  wgpu::LoadOp::Clear(if facing_day { DAY_SKY_COLOR } else { NIGHT_SKY_COLOR }),
-                        store: wgpu::StoreOp::Store
-                    }
-                })]
+                depth_stencil_attachment: Some(resources.rtexture_depth.attachment(config, true)),
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(resources.color_target.attachment(
+                    config, &resources.rtexture_color, wgpu::LoadOp::Clear(sky.0)
+                ))]
             });
 
-            // Draw terrain, sun/moon
-            rpass.set_pipeline(&self.textured_pipeline);
-            rpass.set_bind_group(0, &self.terrain_bindgroups[if facing_day { 0 } else { 1 }], &[]);
+            // Draw the sky
+            pipelines.sky.draw(&mut rpass, &[], cast_struct_to_u8_slice(&SkyColors::new(sky)));
+
+            let textured_state = TexturedState {
+                rotated:        !facing_day as u32,
+                mirror_opacity: if mirroring { MIRROR_OPACITY } else { 0.0 }
+            };
+
+            // the bindgroups stay across the pipelines, they share their layout (the push constants don't)
+            rpass.set_bind_group(0, &self.scene_bindgroup, &[]);
             rpass.set_bind_group(1, &self.matrix_bindgroup, &[]);
-            rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&[!facing_day as u32, 0]));
+            rpass.set_bind_group(2, &resources.reflection_bindgroup, &[]);
+
+            // Draw the terrain
+            pipelines.terrain.set(&mut rpass, config);
+            rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&textured_state));
             draw_geometry(&mut rpass, &self.terrain_geometry, 1);
+
+            // Draw the sun/moon
+            pipelines.textured.set(&mut rpass, config);
+            rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&textured_state));
             draw_geometry(&mut rpass, if facing_day { &self.sun_geometry } else { &self.moon_geometry }, 1);
 
             // Draw the digits
-            rpass.set_pipeline(&self.digits_pipeline);
-            rpass.set_bind_group(0, &self.digits_bindgroup, &[]);
-            rpass.set_bind_group(1, &self.matrix_bindgroup, &[]);
+            pipelines.digits.set(&mut rpass, config);
             rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&[!facing_day as u32, digits]));
             draw_geometry(&mut rpass, &self.digits_geometry, 2);
         }
@@ -529,98 +1201,210 @@ impl ExecDraw for Portal {
         // The portal texture is the rendered frame of the "other world" (the render texture of the previous pass)
         // The platform of drawn twice, once with the daytime side lightmap texture,
         // and another time rotated 180 deg with the nighttime side lightmap texture.
+        // With tilt-shift on, this goes into an intermediate texture first.
+        // With FXAA on, so does the final image (the scene or its blur), FXAA's pass draws it onto the surface.
+        // With the god rays shown, the image before FXAA goes into their texture first, in the same way.
+        // With TAA on, the scene itself goes into its texture, before any of the above.
+        // With the runner's frame multisampled, what's post-processed goes into the frame copy's texture rather than the frame
+        #[cfg(feature = "god-rays")]
+        let rays = self.god_rays.rays(matdata.matrix, altitude, facing_day);
+
+        #[allow(unused_mut)]
+        let mut post_processed = self.tilt_shift_blur;
+        #[cfg(feature = "fxaa")]
+        { post_processed |= self.fxaa_enabled; }
+        #[cfg(feature = "god-rays")]
+        { post_processed |= rays.is_some(); }
+        #[cfg(feature = "taa")]
+        { post_processed |= self.taa_enabled; }
+
+        let frame_samples = frame_sample_count();
+        if self.frame_copy.as_ref().map_or(1, |frame_copy| frame_copy.quad.config.sample_count) != frame_samples {
+            let frame_config = TargetConfig::frame(config.color_format, None);
+            self.frame_copy = (frame_samples > 1).then(|| FrameCopyStage::new(self.window_size, &frame_config, &self.pipeline_sources.copy_shader, device));
+        }
+
+        let frame_copy = self.frame_copy.as_ref().filter(|_| post_processed);
+        let frame_view = frame_copy.map_or(texview, |frame_copy| &frame_copy.source.view);
+
+        #[cfg(feature = "fxaa")]
+        let output = if self.fxaa_enabled { &self.fxaa.source.view } else { frame_view };
+        #[cfg(not(feature = "fxaa"))]
+        let output = frame_view;
+        #[cfg(feature = "god-rays")]
+        let rays_source = if rays.is_some() { &self.god_rays.source.view } else { output };
+        #[cfg(not(feature = "god-rays"))]
+        let rays_source = output;
+
+        let blur_source = if self.tilt_shift_blur { &self.tilt_shift_pass.target.view } else { rays_source };
+        #[cfg(feature = "taa")]
+        let scene_target = if self.taa_enabled { &self.taa.source.view } else { blur_source };
+        #[cfg(not(feature = "taa"))]
+        let scene_target = blur_source;
+
+        // onto the multisampled frame, the scene is drawn as is, it's resolved by the runner
+        let frame_target = ColorTarget::frame(config);
+        let scene_color  = if frame_samples > 1 && std::ptr::eq(scene_target, texview) { &frame_target } else { &resources.color_target };
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label:                    None,
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.dynamic_resources.surface_depth,
-                    depth_ops: Some(wgpu::Operations {
-                        load:  wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store
-                    }),
-                    stencil_ops: None
-                }),
-                timestamp_writes:    None,
-                occlusion_query_set: None,
-                color_attachments:   &[Some(wgpu::RenderPassColorAttachment {
-                    view: texview,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load:  wgpu::LoadOp::Clear(wgpu::Color { r: 0.0, g: 0.0, b: 0.0, a: 0.0 }),
-                        store: wgpu::StoreOp::Store
-                    }
-                })]
+                depth_stencil_attachment: Some(resources.surface_depth.attachment(config, false)),
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(scene_color.attachment(
+                    config, scene_target, wgpu::LoadOp::Clear(wgpu::Color { r: 0.0, g: 0.0, b: 0.0, a: 0.0 })
+                ))]
             });
 
-            // Draw the portal
-            rpass.set_pipeline(&self.portal_pipeline);
-            rpass.set_bind_group(0, &self.dynamic_resources.rtexture_bindgroup, &[]);
+            rpass.set_bind_group(0, &self.scene_bindgroup, &[]);
             rpass.set_bind_group(1, &self.matrix_bindgroup, &[]);
+            rpass.set_bind_group(2, &resources.rtexture_bindgroup, &[]);
+
+            // Draw the portal
+            pipelines.portal.set(&mut rpass, config);
             rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&[!facing_day as u32, 0]));
             draw_geometry(&mut rpass, &self.portal_geometry, 1);
 
-            rpass.set_pipeline(&self.textured_pipeline);
-            rpass.set_bind_group(1, &self.matrix_bindgroup, &[]);
+            pipelines.normalmapped.set(&mut rpass, config);
 
-            // Draw the daytime side platform
-            rpass.set_bind_group(0, &self.platform_bindgroups[0], &[]);
+            rpass.set_index_buffer(self.platform_geometry.ibuffer.slice(..), wgpu::IndexFormat::Uint32);
+            rpass.set_vertex_buffer(0, self.platform_geometry.vbuffer.slice(..));
+
+            // Draw the daytime side platform, its lightmap is the array's first layer
             rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&[0_u32, 0]));
-            draw_geometry(&mut rpass, &self.platform_geometry, 1);
+            rpass.draw_indexed(0..self.platform_geometry.icount as u32, 0, 0..1);
 
-            // Draw the nighttime side platform
-            rpass.set_bind_group(0, &self.platform_bindgroups[1], &[]);
+            // Draw the nighttime side platform, with the second layer
             rpass.set_push_constants(wgpu::ShaderStages::VERTEX_FRAGMENT, 0, cast_struct_to_u8_slice(&[1_u32, 0]));
-            draw_geometry(&mut rpass, &self.platform_geometry, 1);
+            rpass.draw_indexed(0..self.platform_geometry.icount as u32, 0, 0..1);
+        }
+
+        #[cfg(feature = "taa")]
+        if self.taa_enabled {
+            self.taa.apply(&mut encoder, blur_source);
+        }
+
+        if self.tilt_shift_blur {
+            self.tilt_shift_pass.apply(&mut encoder, rays_source);
+        }
+
+        #[cfg(feature = "god-rays")]
+        if let Some((intensity, sun)) = rays {
+            self.god_rays.apply(&mut encoder, output, intensity, sun);
+        }
+
+        #[cfg(feature = "fxaa")]
+        if self.fxaa_enabled {
+            self.fxaa.apply(&mut encoder, frame_view);
+        }
+
+        if let Some(frame_copy) = frame_copy {
+            frame_copy.apply(&mut encoder, texview);
         }
 
         queue.submit(std::iter::once(encoder.finish()));
 
-        if self.auto_rotation {
-            self.angle_phi = (self.angle_phi + 1.0) % 360.0;
+        if self.auto_rotation && self.focused {
+            self.angle_phi = (self.angle_phi + AUTO_ROTATION_SPEED * frame.delta) % 360.0;
         }
     }
 
-    fn onmousemove(self: &mut Self, delta: (f64, f64), state: u32, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+    fn onmousemove(self: &mut Self, delta: (f64, f64), state: u32, modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
         let THETA_SHIFT: f32 = 0.5;
         let PHI_SHIFT:   f32 = 0.5;
 
         let dx = -delta.0 as f32;
         let dy = -delta.1 as f32;
 
-        if state & 1 << 2 != 0 {
+        if state & 1 << 2 != 0 && modifiers.shift_key() {
+            // half the distance is visible above and below the center, in the world's units before scaling it down
+            let units_per_pixel = self.distance * self.world_scale / self.window_size.1.max(1) as f32;
+            self.pan = panned(self.pan, delta, self.angle_phi, units_per_pixel);
+        } else if state & (1 << 2 | 1) != 0 {
+            // the left button too, that's what a finger dragging on a touch screen holds
             self.angle_theta   = (self.angle_theta + dy * THETA_SHIFT).clamp(-90.0, 90.0);
-            self.angle_phi     = self.angle_phi    + dx * PHI_SHIFT;
+            self.angle_phi    += dx * PHI_SHIFT;
             self.auto_rotation = false;
         }
     }
 
-    fn onmousescroll(self: &mut Self, delta: (f64, f64), _state: u32, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+    fn onmousescroll(self: &mut Self, delta: (f64, f64), _state: u32, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
         let DIST_SHIFT: f32 = 3.0;
+        let PHI_SHIFT:  f32 = 5.0;
 
+        let dx = -delta.0 as f32;
         let dy = -delta.1 as f32;
 
         self.distance = (self.distance + dy * DIST_SHIFT).clamp(0.0, 1000.0);
+
+        // orbits the same way as dragging does
+        if dx != 0.0 {
+            self.angle_phi    += dx * PHI_SHIFT;
+            self.auto_rotation = false;
+        }
     }
 
-    fn onmousebutton(self: &mut Self, state: u32, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+    fn onmousebutton(self: &mut Self, state: u32, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
         if state & 1 != 0 {
             self.auto_rotation = !self.auto_rotation;
         }
     }
+
+    fn onfocus(self: &mut Self, focused: bool, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        self.focused = focused;
+    }
 }
 
 fn main() {
-    let event_loop = winit::event_loop::EventLoop::new().unwrap();
+    logging::init("portal");
+
+    let (args, app) = Cli::new("portal", "Portal clock: hours on the day side, minutes on the night side")
+        .option("--location", "LAT,LON", "Where the sky follows the sun for, in degrees (default: the timezone's meridian at 45N)")
+        .option("--record-mesh", "DIR", "Write the terrain as shown into a PLY file per second, for offline processing")
+        .option("--normals", "MODE", "How the platform's smooth normals weigh the faces: area, angle or uniform (default: area)")
+        .parse();
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    if let Some(value) = app.value("--location") {
+        match parse_location(value) {
+            Some(location) => { let _ = LOCATION.set(location); },
+            None           => log::warn!("Ignoring invalid location '{}', expected e.g. 51.5,-0.1", value)
+        }
+    }
+
+    if let Some(value) = app.value("--normals") {
+        match parse_normal_weighting(value) {
+            Some(weighting) => { let _ = NORMAL_WEIGHTING.set(weighting); },
+            None            => log::warn!("Ignoring invalid normal weighting '{}', expected area, angle or uniform", value)
+        }
+    }
+
+    if let Some(folder) = app.value("--record-mesh") {
+        let _ = RECORD_MESH.set(std::path::PathBuf::from(folder));
+    }
+
+    if args.take_snapshot::<Portal>((512, 512), Some(wgpu::Features::PUSH_CONSTANTS), args.msaa) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Portal Clock", &format!("Failed to initialize the windowing system: {}", error)));
     #[allow(unused_mut)]
     let mut builder = winit::window::WindowBuilder::new();
-    let window = builder
+    let builder = builder
         .with_inner_size(winit::dpi::LogicalSize { width: 512.0, height: 512.0 })
-        .with_title("Portal Clock")
+        .with_title("Portal Clock");
+    let window = args.apply_to_window(builder, &event_loop)
         .build(&event_loop)
-        .unwrap();
+        .unwrap_or_else(|error| fatal_error("Portal Clock", &format!("Failed to create the window: {}", error)));
 
-    pollster::block_on(run::<Portal>(
+    pollster::block_on(run_with_options::<Portal>(
         event_loop, window,
-        Some(wgpu::Features::PUSH_CONSTANTS))
-    );
+        Some(wgpu::Features::PUSH_CONSTANTS),
+        RunOptions { msaa_sample_count: args.msaa, ..args.run_options() }
+    ));
 }
\ No newline at end of file
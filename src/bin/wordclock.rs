@@ -0,0 +1,255 @@
+#![cfg_attr(
+    all(
+        target_os = "windows",
+        not(feature = "console"),
+    ),
+    windows_subsystem = "windows"
+)]
+use chrono::Timelike;
+use clockutils::{
+    run_with_options, cast_slice_to_u8_slice, get_resource_folder_for, fatal_error, u32_col_to_wgpu_col_linear,
+    ExecDraw, FrameInfo, InputState, ResourceTexture, BasicFilteringSampler,
+    ImmutableStorageBuffer, MutableStorageBuffer, Drawspace2D, FitMode,
+    cli::Cli, logging, time,
+    params::SmallParams,
+    font::BitmapFontRenderer,
+    keys::{self, KeyBinding},
+    palette::parse_hex_color,
+    target::{PipelineBuilder, TargetConfig, TargetPipeline},
+    clocks::wordclock::{grid_codepoints, lit_flags, WordParams, CELL_COUNT, COLUMNS, ROWS}
+};
+
+/// Command line settings, handed over from `main` to `setup`
+#[derive(Clone, Copy, Debug)]
+struct WordSettings {
+    highlight: u32,
+    dim:       u32
+}
+
+impl Default for WordSettings {
+    fn default() -> Self {
+        Self { highlight: 0xFFD88A_FF, dim: 0x2A2622_FF }
+    }
+}
+
+static STARTUP_SETTINGS: std::sync::OnceLock<WordSettings> = std::sync::OnceLock::new();
+
+const BACKGROUND_COLOR: u32 = 0x0D0C0B_FF;
+
+/// Matched on in `onkey`, and listed by the help panel
+const KEYBINDINGS: &[KeyBinding] = &[
+    ("H", "Show/hide this help")
+];
+
+/// Half the width/height of the drawing area that's always visible, a cell of the grid is a unit wide and high
+const EXTENT: glam::Vec2 = glam::Vec2::new(COLUMNS as f32 * 0.5 + 0.5, ROWS as f32 * 0.5 + 0.5);
+
+/// The Clock's mechanism:
+/// A grid of letters, some of which spell out the time in words ("IT IS TWENTY PAST FIVE PM"), drawn as a single quad.
+/// The fragment shader finds the cell it's in, and reads the cell's letter (a codepoint) from one storage buffer
+/// and whether it's lit from another, then samples the letter's cell of the sprite sheet.
+///
+/// The letters never change, their buffer is written once. The flags follow the words, rewritten whenever the minute
+/// changes (see `lit_flags`), the highlight color and the timestamp the lit words pulse with go via push constants.
+struct WordClock {
+    pipeline:      TargetPipeline,
+    target_config: TargetConfig,
+
+    params:     SmallParams<WordParams>,
+    flags:      MutableStorageBuffer,
+    bind_group: wgpu::BindGroup,
+    drawspace:  Drawspace2D,
+
+    settings:    WordSettings,
+    last_minute: Option<(u32, u32)>, // hour and minute the flags were written for
+    show_help:   bool,
+    help:        BitmapFontRenderer
+}
+
+impl ExecDraw for WordClock {
+    fn setup(
+        config:   &wgpu::SurfaceConfiguration,
+        _adapter: &wgpu::Adapter,
+        device:   &wgpu::Device,
+        queue:    &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> {
+        let resources = get_resource_folder_for("wordclock").map_err(|error| error.to_string())?;
+        let settings  = STARTUP_SETTINGS.get().copied().unwrap_or_default();
+
+        if device.limits().max_storage_buffers_per_shader_stage < 2 {
+            return Err("The word clock needs storage buffers, which the GPU (or WebGL2) doesn't have".to_string());
+        }
+
+        let target_config = TargetConfig::new(config.format, None);
+        let drawspace     = Drawspace2D::new(device, EXTENT, FitMode::Contain);
+
+        let stages  = wgpu::ShaderStages::FRAGMENT;
+        let letters = ImmutableStorageBuffer::new(device, stages, cast_slice_to_u8_slice(&grid_codepoints()));
+        let flags   = MutableStorageBuffer::new::<u32>(device, stages, CELL_COUNT);
+
+        // 'A' to 'Z' in cells of 8 by 4
+        let sheet = ResourceTexture::new(
+            resources.join("textures/letters.png").as_path().to_str().unwrap(),
+            device,
+            queue
+        )?;
+
+        let sampler = BasicFilteringSampler::new(device);
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                ImmutableStorageBuffer::default_layout_entry(0, &letters),
+                MutableStorageBuffer::read_only_layout_entry(1, &flags),
+                ResourceTexture::default_layout_entry(2),
+                BasicFilteringSampler::default_layout_entry(3)
+            ]
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   None,
+            layout:  &bind_group_layout,
+            entries: &[
+                letters.get_entry(0),
+                flags.get_entry(1),
+                sheet.get_entry(2),
+                sampler.get_entry(3)
+            ]
+        });
+
+        // the colors and the timestamp sent via push constants (or after the grid's bind group, without them)
+        let params = SmallParams::<WordParams>::new(device, wgpu::ShaderStages::FRAGMENT, 2, 1);
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &[ drawspace.layout(), &bind_group_layout ], params.layout_entries().as_slice() ].concat(),
+            push_constant_ranges: &params.push_constant_ranges()
+        });
+
+        let shader = params.load_shader(device, &resources.join("shaders/grid.wgsl"))?;
+
+        // the letters' edges are the sprite sheet's alpha
+        let pipeline = PipelineBuilder::new(&target_config, &pipeline_layout, &shader)
+            .primitive(wgpu::PrimitiveState { topology: wgpu::PrimitiveTopology::TriangleStrip, ..Default::default() })
+            .blend(wgpu::BlendState::ALPHA_BLENDING)
+            .build(device);
+
+        Ok(Self {
+            pipeline, target_config,
+            params, flags, bind_group, drawspace,
+            settings,
+            last_minute: None,
+            show_help:   false,
+            help:        keys::help_panel(KEYBINDINGS, config, device)
+        })
+    }
+
+    fn resize(self: &mut Self, width: u32, height: u32, _device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.drawspace.resize(queue, width, height);
+        self.help.resize(width, height);
+    }
+
+    fn onkey(self: &mut Self, event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {
+        if let Some("H") = keys::pressed(&event, KEYBINDINGS) {
+            self.show_help = !self.show_help;
+        }
+    }
+
+    fn update(self: &mut Self, _dt: f32, _input: &InputState, _device: &wgpu::Device, queue: &wgpu::Queue) {
+        let now    = time::now();
+        let minute = (now.hour(), now.minute());
+
+        // the words only change with the minute
+        if self.last_minute != Some(minute) {
+            self.flags.write(queue, cast_slice_to_u8_slice(&lit_flags(minute.0, minute.1)));
+            self.last_minute = Some(minute);
+        }
+    }
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
+        let now = time::now();
+        let params = WordParams {
+            highlight: self.settings.highlight,
+            dim:       self.settings.dim,
+            timestamp: now.second() as f32 + now.nanosecond() as f32 / 1_000_000_000.0
+        };
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        {
+            let clear = u32_col_to_wgpu_col_linear(BACKGROUND_COLOR);
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view:           texview,
+                    resolve_target: None,
+                    ops:            wgpu::Operations { load: wgpu::LoadOp::Clear(clear), store: wgpu::StoreOp::Store }
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes:         None,
+                occlusion_query_set:      None
+            });
+
+            self.pipeline.set(&mut rpass, &self.target_config);
+            rpass.set_bind_group(0, self.drawspace.bind_group(), &[]);
+            rpass.set_bind_group(1, &self.bind_group, &[]);
+            self.params.set(&mut rpass, queue, &params);
+            rpass.draw(0..4, 0..1);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        if self.show_help {
+            self.help.draw(texview, device, queue);
+        }
+    }
+}
+
+fn main() {
+    logging::init("wordclock");
+
+    let (args, app) = Cli::new("wordclock", "Word clock: a grid of letters, the words telling the time light up")
+        .option("--color", "RRGGBB", "Color of the lit words")
+        .option("--dim-color", "RRGGBB", "Color of the other letters")
+        .parse();
+    args.apply_globals();
+
+    if args.print_gpu_info() {
+        return;
+    }
+
+    let mut settings = WordSettings::default();
+
+    for (option, color) in [("--color", &mut settings.highlight), ("--dim-color", &mut settings.dim)] {
+        if let Some(value) = app.value(option) {
+            match parse_hex_color(value) {
+                Some(parsed) => *color = parsed,
+                None         => log::warn!("Ignoring invalid color '{}' for {}", value, option)
+            }
+        }
+    }
+
+    let _ = STARTUP_SETTINGS.set(settings);
+
+    if args.take_snapshot::<WordClock>((660, 600), None, 1) {
+        return;
+    }
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .unwrap_or_else(|error| fatal_error("Word Clock", &format!("Failed to initialize the windowing system: {}", error)));
+    #[allow(unused_mut)]
+    let mut builder = winit::window::WindowBuilder::new();
+    let builder = builder
+        .with_inner_size(winit::dpi::LogicalSize { width: 660.0, height: 600.0 })
+        .with_title("Word Clock");
+    let window = args.apply_to_window(builder, &event_loop)
+        .build(&event_loop)
+        .unwrap_or_else(|error| fatal_error("Word Clock", &format!("Failed to create the window: {}", error)));
+
+    pollster::block_on(run_with_options::<WordClock>(
+        event_loop, window,
+        None, // push constants are used if available
+        args.run_options()
+    ));
+}
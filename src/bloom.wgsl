@@ -0,0 +1,42 @@
+// Bloom, the bright parts of the frame bleeding light into their surroundings.
+// Drawn with `FullscreenQuadPipeline`, the UVs come from its vertex shader.
+// `fs_threshold` keeps what's brighter than the threshold, bloom_blur.wgsl blurs that both ways,
+// and `fs_composite` adds the result over the frame.
+
+struct BloomParams {
+    threshold: f32, // in linear luminance, only what's brighter blooms
+    intensity: f32  // brightness of the blurred highlights over the frame, 0 for none
+}
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>; // the frame
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(1) @binding(0)
+var bloom_tex_2d: texture_2d<f32>; // the highlights, blurred
+
+@group(1) @binding(1)
+var bloom_sampler: sampler;
+
+var<push_constant> params: BloomParams;
+
+// The part of the color above the threshold, the hue kept
+@fragment
+fn fs_threshold(@location(0) uv: vec2f) -> @location(0) vec4f {
+    let color = textureSample(src_tex_2d, tex_sampler, uv).rgb;
+    let luma  = dot(color, vec3f(0.2126, 0.7152, 0.0722));
+
+    let excess = max(luma - params.threshold, 0.0) / max(luma, 0.0001);
+
+    return vec4f(color * excess, 1.0);
+}
+
+@fragment
+fn fs_composite(@location(0) uv: vec2f) -> @location(0) vec4f {
+    let sharp = textureSample(src_tex_2d,   tex_sampler,   uv);
+    let bloom = textureSample(bloom_tex_2d, bloom_sampler, uv).rgb;
+
+    return vec4f(sharp.rgb + bloom * params.intensity, sharp.a);
+}
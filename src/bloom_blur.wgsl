@@ -0,0 +1,35 @@
+// Either half of `BloomPass`' two-pass gaussian blur, of the highlights bloom.wgsl's `fs_threshold` kept.
+// Drawn with `FullscreenQuadPipeline`, the UVs come from its vertex shader.
+
+#include "blur.wgsl"
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>;
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(1) @binding(0)
+var<storage, read> blur_table: array<BlurWO>;
+
+@group(1) @binding(1)
+var<uniform> blur_table_size: u32;
+
+// read by `blur`, see blur.wgsl
+fn blur_sample(uv: vec2f) -> vec4f {
+    return textureSample(src_tex_2d, tex_sampler, uv);
+}
+
+fn texel() -> vec2f {
+    return 1.0 / vec2f(textureDimensions(src_tex_2d));
+}
+
+@fragment
+fn fs_horizontal(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return blur(vec2f(1.0, 0.0), uv, texel());
+}
+
+@fragment
+fn fs_vertical(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return blur(vec2f(0.0, 1.0), uv, texel());
+}
@@ -0,0 +1,25 @@
+// One direction of the separable gaussian blur, for shaders to `#include "blur.wgsl"` (see `preprocess_shader`).
+// The including shader declares the lookup table made by `create_blur_weights_and_offsets`, as `blur_table`
+// and `blur_table_size`, and how the image is read, as `fn blur_sample(uv: vec2f) -> vec4f`.
+// With the linear offsets, the taps fall between two pixels, `blur_sample` has to interpolate them linearly.
+
+struct BlurWO {
+    weight: f32,
+    offset: f32
+}
+
+// The blurred color at `uv` along `dir`, with `texel` the size of a pixel in uv units.
+// The colors count by their alpha, what's transparent doesn't glow
+fn blur(dir: vec2f, uv: vec2f, texel: vec2f) -> vec4f {
+    var result: vec3f = vec3f(0.0);
+
+    for(var i = 0u; i < blur_table_size; i++) {
+        let offset = dir * blur_table[i].offset * texel;
+        let weight = blur_table[i].weight;
+        let color  = blur_sample(uv + offset);
+
+        result += color.rgb * color.a * weight;
+    }
+
+    return vec4f(result, 1.0);
+}
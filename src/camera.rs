@@ -0,0 +1,61 @@
+//! View-projection matrices of the 3D clocks, fitted into the window with `DrawspaceScales` like the 2D ones.
+//!
+//! The extent is the half size of the area that's always visible around the camera's target, in world units.
+//! An orthographic camera shows exactly that at any depth. A perspective one shows it on the plane of the target,
+//! nearer things look bigger and farther ones smaller, so switching between them keeps the framing of the target.
+
+use crate::DrawspaceScales;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Projection {
+    #[default]
+    Perspective,
+    /// Parallel rays, no foreshortening. Flat, crisp renders and faces usable as 2D widgets
+    Orthographic
+}
+
+impl Projection {
+    pub fn toggled(self: &Self) -> Self {
+        match self {
+            Projection::Perspective  => Projection::Orthographic,
+            Projection::Orthographic => Projection::Perspective
+        }
+    }
+}
+
+/// Projection alone, for a camera `distance` away from its target (only the perspective one cares)
+pub fn projection_matrix(
+    projection: Projection,
+    resolution: glam::Vec2,
+    extent:     glam::Vec2,
+    distance:   f32,
+    depth:      (f32, f32) // near and far planes
+) -> glam::Mat4 {
+    let (near, far) = depth;
+
+    // The projections map view space onto [-1..1] per unit (of depth, for the perspective one),
+    // then the drawspace scales fit the extent in, keeping the aspect ratio
+    let (unit, fitted) = match projection {
+        Projection::Perspective  => (glam::Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, near, far), extent / distance),
+        Projection::Orthographic => (glam::Mat4::orthographic_rh(-1.0, 1.0, -1.0, 1.0, near, far), extent)
+    };
+
+    let dscales = DrawspaceScales::new(resolution, fitted);
+
+    glam::Mat4::from_scale(glam::Vec3::new(dscales.scale.x, dscales.scale.y, 1.0)) * unit
+}
+
+/// Camera at `eye` looking at `target`, with `extent` visible around the target
+pub fn view_projection(
+    projection: Projection,
+    eye:        glam::Vec3,
+    target:     glam::Vec3,
+    up:         glam::Vec3,
+    resolution: glam::Vec2,
+    extent:     glam::Vec2,
+    depth:      (f32, f32) // near and far planes
+) -> glam::Mat4 {
+    let view = glam::Mat4::look_at_rh(eye, target, up);
+
+    projection_matrix(projection, resolution, extent, eye.distance(target), depth) * view
+}
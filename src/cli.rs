@@ -0,0 +1,533 @@
+//! Command line options shared by all the clocks.
+//!
+//! Each binary describes itself (and any flags of its own) via the `Cli` builder,
+//! and gets back the common options as `CommonArgs` plus its own ones as `AppArgs`.
+//! Unknown flags are rejected instead of being silently ignored.
+
+use std::path::PathBuf;
+use chrono::{FixedOffset, NaiveDate, NaiveTime};
+use crate::{time::{parse_timezone, TimeSource}, widget::{self, ScreenCorner, WidgetModifier, WidgetOptions}, ExecDraw, RunOptions};
+
+struct FlagSpec {
+    name:  &'static str,
+    value: Option<&'static str>, // name of the value shown in help, `None` for switches
+    help:  &'static str
+}
+
+const COMMON_FLAGS: &[FlagSpec] = &[
+    FlagSpec { name: "--size",           value: Some("WxH"),        help: "Initial window size in logical pixels" },
+    FlagSpec { name: "--title",          value: Some("TEXT"),       help: "Window title" },
+    FlagSpec { name: "--fullscreen",     value: None,               help: "Start in borderless fullscreen" },
+    FlagSpec { name: "--transparent",    value: None,               help: "Transparent, always on top window for desktop overlay use" },
+    FlagSpec { name: "--widget",         value: None,               help: "Borderless, transparent, always on top desktop widget" },
+    FlagSpec { name: "--widget-key",     value: Some("MODIFIER"),   help: "Hold to drag the widget: alt, ctrl, shift, super (default: alt)" },
+    FlagSpec { name: "--click-through",  value: None,               help: "Let clicks pass through the widget, except while --widget-key is held" },
+    FlagSpec { name: "--corner",         value: Some("CORNER"),     help: "Pin the window to a corner of its monitor: top-left, top-right, bottom-left, bottom-right" },
+    FlagSpec { name: "--opacity",        value: Some("X"),          help: "Opacity of a transparent window, e.g. 0.7 to ghost the widget (default: 1)" },
+    FlagSpec { name: "--vsync",          value: Some("on|off"),     help: "Synchronize presentation with the display's refresh rate (default: on)" },
+    FlagSpec { name: "--power",          value: Some("low|high"),   help: "Prefer the integrated (low) or the discrete (high) GPU (default: the system's choice)" },
+    FlagSpec { name: "--frame-latency",  value: Some("N"),          help: "Frames queued ahead of the display, 1 to 3, fewer lag less (default: driver's)" },
+    FlagSpec { name: "--max-fps",        value: Some("N"),          help: "Draw at most N frames per second, whatever the vsync (default: no cap)" },
+    FlagSpec { name: "--monitor",        value: Some("N"),          help: "Open the window on the N-th monitor (0-based)" },
+    FlagSpec { name: "--timezone",       value: Some("TZ"),         help: "UTC offset to show the time in, e.g. UTC+6, -05:00 (default: local)" },
+    FlagSpec { name: "--time",           value: Some("HH:MM:SS"),   help: "Start the clock at the given time of day instead of the current time" },
+    FlagSpec { name: "--date",           value: Some("YYYY-MM-DD"), help: "Start the clock at the given date instead of today" },
+    FlagSpec { name: "--time-scale",     value: Some("X"),          help: "Speed multiplier for the clock's time (default: 1)" },
+    FlagSpec { name: "--debug-time",     value: None,               help: "Time controls: P pause, ,/. step 1 s, </> halve/double speed, 0 real time" },
+    FlagSpec { name: "--palette",        value: Some("FILE"),       help: "Palette file to load colors from" },
+    FlagSpec { name: "--resources",      value: Some("DIR"),        help: "Folder containing the clock's resources (or WGPU_CLOCKS_RESOURCES)" },
+    FlagSpec { name: "--no-push-constants", value: None,            help: "Send draw parameters via uniform buffers, like on GPUs without push constants (polar only)" },
+    FlagSpec { name: "--msaa",           value: Some("N"),          help: "Multisampling (antialiasing) sample count, 1 turns it off (default: 4)" },
+    FlagSpec { name: "--stats",          value: None,               help: "Show the frame rate and frame times in the top-left corner" },
+    FlagSpec { name: "--gpu-info",       value: None,               help: "Print what the graphics adapters support and exit" },
+    FlagSpec { name: "--snapshot",       value: Some("FILE"),       help: "Render a single frame offscreen into a PNG file and exit" },
+    FlagSpec { name: "--record",         value: Some("FILE|DIR"),   help: "Record with F9 into a GIF (*.gif) or a PNG sequence folder" },
+    FlagSpec { name: "--record-fps",     value: Some("N"),          help: "Frames per second to record (default: 15)" },
+    FlagSpec { name: "--record-scale",   value: Some("X"),          help: "Size of the recording relative to the window (default: 0.5)" },
+    FlagSpec { name: "--record-seconds", value: Some("SECS"),       help: "Stop recording automatically after this long" },
+    FlagSpec { name: "--help",           value: None,               help: "Print this help and exit" }
+];
+
+/// Options understood by every clock
+#[derive(Clone, Debug)]
+pub struct CommonArgs {
+    pub size:       Option<(u32, u32)>,
+    pub title:      Option<String>,
+    pub fullscreen: bool,
+    pub transparent: bool,
+    pub widget:     bool,
+    pub widget_key: WidgetModifier,
+    pub click_through: bool,
+    pub corner:     Option<ScreenCorner>,
+    pub opacity:    f32,
+    pub vsync:      bool,
+    pub frame_latency: Option<u32>,
+    pub max_fps:    Option<u32>,
+    pub power:      wgpu::PowerPreference,
+    pub monitor:    Option<usize>,
+    pub timezone:   Option<FixedOffset>,
+    pub time:       Option<NaiveTime>,
+    pub date:       Option<NaiveDate>,
+    pub time_scale: f64,
+    pub palette:    Option<PathBuf>,
+    pub resources:  Option<PathBuf>,
+    pub stats:      bool,
+    pub no_push_constants: bool,
+    pub msaa:       u32,
+    pub debug_time: bool,
+    pub gpu_info:   bool,
+    pub snapshot:   Option<PathBuf>,
+    pub record:         Option<PathBuf>,
+    pub record_fps:     u32,
+    pub record_scale:   f32,
+    pub record_seconds: Option<f64>
+}
+
+impl Default for CommonArgs {
+    fn default() -> Self {
+        Self {
+            size:       None,
+            title:      None,
+            fullscreen:  false,
+            transparent: false,
+            widget:      false,
+            widget_key:  WidgetModifier::default(),
+            click_through: false,
+            corner:      None,
+            opacity:     1.0,
+            vsync:       true,
+            frame_latency: None,
+            max_fps:     None,
+            power:      wgpu::PowerPreference::default(),
+            monitor:    None,
+            timezone:   None,
+            time:       None,
+            date:       None,
+            time_scale: 1.0,
+            palette:    None,
+            resources:  None,
+            stats:      false,
+            no_push_constants: false,
+            msaa:       4,
+            debug_time: false,
+            gpu_info:   false,
+            snapshot:   None,
+            record:         None,
+            record_fps:     15,
+            record_scale:   0.5,
+            record_seconds: None
+        }
+    }
+}
+
+impl CommonArgs {
+    /// Override the binary's default window attributes with the ones given in command line
+    pub fn apply_to_window(
+        self: &Self,
+        mut builder: winit::window::WindowBuilder,
+        event_loop:  &winit::event_loop::EventLoop<()>
+    ) -> winit::window::WindowBuilder {
+        if let Some((width, height)) = self.size {
+            builder = builder.with_inner_size(winit::dpi::LogicalSize { width: width as f64, height: height as f64 });
+        }
+
+        if let Some(title) = &self.title {
+            builder = builder.with_title(title);
+        }
+
+        let monitor = match self.monitor {
+            None => None,
+            Some(index) => {
+                let monitor = event_loop.available_monitors().nth(index);
+                if monitor.is_none() {
+                    log::warn!("Monitor {} not found, using the default one", index);
+                }
+                monitor
+            }
+        };
+
+        if self.widget {
+            builder = widget::apply_to_window(builder);
+        } else if self.transparent {
+            builder = builder
+                .with_transparent(true)
+                .with_window_level(winit::window::WindowLevel::AlwaysOnTop);
+
+            // OLE drag and drop conflicts with the overlay's own input handling on Windows
+            #[cfg(target_os = "windows")]
+            {
+                use winit::platform::windows::WindowBuilderExtWindows;
+                builder = builder.with_drag_and_drop(false);
+            }
+        }
+
+        if self.fullscreen {
+            builder = builder.with_fullscreen(Some(winit::window::Fullscreen::Borderless(monitor)));
+        } else if let Some(monitor) = monitor {
+            builder = builder.with_position(monitor.position());
+        }
+
+        builder
+    }
+
+    pub fn run_options(self: &Self) -> RunOptions {
+        #[cfg(not(feature = "recorder"))]
+        if self.record.is_some() {
+            log::warn!("--record is ignored, recording is not compiled in (build with `--features recorder`)");
+        }
+
+        if self.click_through && !self.widget {
+            log::warn!("--click-through is ignored, it's for --widget windows");
+        }
+
+        RunOptions {
+            present_mode: if self.vsync { wgpu::PresentMode::Fifo } else { wgpu::PresentMode::AutoNoVsync },
+            transparent:  self.transparent || self.widget,
+            widget:       self.widget.then_some(WidgetOptions { modifier: self.widget_key, click_through: self.click_through }),
+            corner:       self.corner,
+            opacity:      self.opacity,
+            frame_latency: self.frame_latency,
+            max_fps:      self.max_fps,
+            power_preference: self.power,
+            stats:        self.stats,
+            debug_time:   self.debug_time,
+            enable_fullscreen_toggle: !self.widget, // a desktop widget stays where it is
+            #[cfg(feature = "recorder")]
+            recorder: self.record.as_ref().map(|output| crate::recorder::RecorderOptions {
+                output:       output.clone(),
+                fps:          self.record_fps,
+                scale:        self.record_scale,
+                max_duration: self.record_seconds.map(std::time::Duration::from_secs_f64)
+            }),
+            ..RunOptions::default()
+        }
+    }
+
+    pub fn time_source(self: &Self) -> TimeSource {
+        TimeSource::new(self.timezone, self.date, self.time, self.time_scale)
+    }
+
+    /// Handles `--gpu-info`: prints the adapters' capabilities (see `gpu_info`) instead of opening a window.
+    /// Returns `true` if the report was printed and the process should end
+    pub fn print_gpu_info(self: &Self) -> bool {
+        if self.gpu_info {
+            crate::gpu_info::print_gpu_info();
+        }
+
+        self.gpu_info
+    }
+
+    /// The palette file of the given clock: `--palette`, or `<clock>-palette.toml` in the configuration folder.
+    /// Snapshots stick to the built-in palettes unless given one, so that they're reproducible
+    pub fn palette_path(self: &Self, clock: &str) -> Option<PathBuf> {
+        match (&self.palette, &self.snapshot) {
+            (Some(path), _) => Some(path.clone()),
+            (None, Some(_)) => None,
+            (None, None)    => crate::config::file_path(&format!("{}-palette.toml", clock))
+        }
+    }
+
+    /// Handles `--snapshot`: renders a single frame offscreen instead of opening a window.
+    /// Returns `true` if the snapshot was taken and the process should end, exits with an error if it failed.
+    /// `--size` is taken as is in physical pixels here, `default_size` is used without it.
+    /// `msaa_sample_count` is the one the clock is run with, see `RunOptions::msaa_sample_count`
+    pub fn take_snapshot<T: ExecDraw>(
+        self:              &Self,
+        default_size:      (u32, u32),
+        features:          Option<wgpu::Features>,
+        msaa_sample_count: u32
+    ) -> bool {
+        let Some(path) = &self.snapshot else {
+            return false;
+        };
+
+        let (width, height) = self.size.unwrap_or(default_size);
+
+        if let Err(error) = crate::headless::save_frame::<T>(path, width, height, features, msaa_sample_count) {
+            log::error!("Snapshot failed: {}", error);
+            std::process::exit(1);
+        }
+
+        return true;
+    }
+
+    /// Install the process-wide settings: the shared time source, the resources folder override,
+    /// whether push constants are requested and the multisampling sample count
+    pub fn apply_globals(self: &Self) {
+        crate::time::set_source(self.time_source());
+        crate::target::set_requested_sample_count(self.msaa);
+
+        if self.no_push_constants {
+            crate::params::disable_push_constants();
+        }
+
+        if let Some(folder) = &self.resources {
+            crate::set_resource_folder_override(folder.clone());
+        }
+    }
+}
+
+/// Clock specific options, whatever is left after the common ones are taken out
+#[derive(Clone, Debug, Default)]
+pub struct AppArgs {
+    values: Vec<(&'static str, Option<String>)>
+}
+
+impl AppArgs {
+    /// Whether the given flag (switch or option) was supplied
+    pub fn flag(self: &Self, name: &str) -> bool {
+        self.values.iter().any(|(n, _)| *n == name)
+    }
+
+    /// Value of the given option, the last one wins if supplied multiple times
+    pub fn value(self: &Self, name: &str) -> Option<&str> {
+        self.values.iter().rev().find(|(n, _)| *n == name).and_then(|(_, v)| v.as_deref())
+    }
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    /// `--help` was requested, contains the generated help text
+    Help(String),
+    Unknown { flag: String, suggestion: Option<&'static str> },
+    MissingValue(&'static str),
+    InvalidValue { flag: &'static str, value: String, reason: String },
+    Unexpected(String)
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Help(text) => write!(f, "{}", text),
+            CliError::Unknown { flag, suggestion: Some(s) } => write!(f, "unknown option '{}', did you mean '{}'?", flag, s),
+            CliError::Unknown { flag, suggestion: None }    => write!(f, "unknown option '{}', see --help", flag),
+            CliError::MissingValue(flag) => write!(f, "option '{}' requires a value", flag),
+            CliError::InvalidValue { flag, value, reason } => write!(f, "invalid value '{}' for '{}': {}", value, flag, reason),
+            CliError::Unexpected(arg) => write!(f, "unexpected argument '{}', see --help", arg)
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Command line description of a clock binary.
+/// Clock specific flags are registered via `switch` and `option`.
+pub struct Cli {
+    name:   &'static str,
+    about:  &'static str,
+    extras: Vec<FlagSpec>
+}
+
+impl Cli {
+    pub fn new(name: &'static str, about: &'static str) -> Self {
+        Self { name, about, extras: Vec::new() }
+    }
+
+    /// Register a clock specific flag without value
+    pub fn switch(mut self, name: &'static str, help: &'static str) -> Self {
+        self.extras.push(FlagSpec { name, value: None, help });
+        self
+    }
+
+    /// Register a clock specific flag that takes a value
+    pub fn option(mut self, name: &'static str, value: &'static str, help: &'static str) -> Self {
+        self.extras.push(FlagSpec { name, value: Some(value), help });
+        self
+    }
+
+    pub fn help_text(self: &Self) -> String {
+        fn write_flags(text: &mut String, flags: &[FlagSpec]) {
+            for flag in flags {
+                let usage = match flag.value {
+                    Some(value) => format!("{} {}", flag.name, value),
+                    None        => flag.name.to_string()
+                };
+                text.push_str(&format!("  {:<24} {}\n", usage, flag.help));
+            }
+        }
+
+        let mut text = format!("{}\n\nUsage: {} [OPTIONS]\n\nOptions:\n", self.about, self.name);
+        write_flags(&mut text, COMMON_FLAGS);
+
+        if !self.extras.is_empty() {
+            text.push_str("\nClock options:\n");
+            write_flags(&mut text, &self.extras);
+        }
+
+        text
+    }
+
+    /// Parse the process arguments.
+    /// Prints the help or the error and exits the process if the arguments can't be used as is.
+    pub fn parse(self: &Self) -> (CommonArgs, AppArgs) {
+        match self.try_parse(std::env::args().skip(1)) {
+            Ok(parsed) => parsed,
+            Err(CliError::Help(text)) => {
+                println!("{}", text);
+                std::process::exit(0);
+            },
+            Err(error) => {
+                eprintln!("{}: {}", self.name, error);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    pub fn try_parse<I: IntoIterator<Item = String>>(self: &Self, args: I) -> Result<(CommonArgs, AppArgs), CliError> {
+        let mut common = CommonArgs::default();
+        let mut app    = AppArgs::default();
+
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                return Err(CliError::Unexpected(arg));
+            }
+
+            // both `--flag value` and `--flag=value` are accepted
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None)
+            };
+
+            let spec = COMMON_FLAGS.iter().chain(self.extras.iter()).find(|spec| spec.name == flag);
+            let spec = match spec {
+                Some(spec) => spec,
+                None => return Err(CliError::Unknown { suggestion: self.suggest(&flag), flag })
+            };
+
+            let value = match (spec.value, inline_value) {
+                (None, None) => None,
+                (None, Some(value)) => return Err(CliError::InvalidValue {
+                    flag: spec.name, value, reason: "this option takes no value".to_string()
+                }),
+                (Some(_), Some(value)) => Some(value),
+                (Some(_), None) => Some(args.next().ok_or(CliError::MissingValue(spec.name))?)
+            };
+
+            let invalid = |value: &str, reason: &str| CliError::InvalidValue {
+                flag: spec.name, value: value.to_string(), reason: reason.to_string()
+            };
+
+            match (spec.name, value) {
+                ("--help", _) => return Err(CliError::Help(self.help_text())),
+                ("--fullscreen", _) => common.fullscreen = true,
+                ("--transparent", _) => common.transparent = true,
+                ("--widget", _) => common.widget = true,
+                ("--click-through", _) => common.click_through = true,
+                ("--stats", _) => common.stats = true,
+                ("--no-push-constants", _) => common.no_push_constants = true,
+                ("--debug-time", _) => common.debug_time = true,
+                ("--gpu-info", _) => common.gpu_info = true,
+                ("--size", Some(value)) => {
+                    let size = value.split_once(['x', 'X'])
+                        .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)))
+                        .filter(|(w, h)| *w > 0 && *h > 0);
+                    common.size = Some(size.ok_or_else(|| invalid(&value, "expected WIDTHxHEIGHT, e.g. 800x600"))?);
+                },
+                ("--title", Some(value)) => common.title = Some(value),
+                ("--vsync", Some(value)) => {
+                    common.vsync = match value.as_str() {
+                        "on"  => true,
+                        "off" => false,
+                        _ => return Err(invalid(&value, "expected 'on' or 'off'"))
+                    };
+                },
+                ("--power", Some(value)) => {
+                    common.power = match value.as_str() {
+                        "low"  => wgpu::PowerPreference::LowPower,
+                        "high" => wgpu::PowerPreference::HighPerformance,
+                        _ => return Err(invalid(&value, "expected 'low' or 'high'"))
+                    };
+                },
+                ("--msaa", Some(value)) => {
+                    let count = value.parse::<u32>().ok().filter(|n| n.is_power_of_two() && *n <= 16);
+                    common.msaa = count.ok_or_else(|| invalid(&value, "expected 1, 2, 4, 8 or 16"))?;
+                },
+                ("--frame-latency", Some(value)) => {
+                    // out of range is clamped with a warning by the runner, see `clamp_frame_latency`
+                    common.frame_latency = Some(value.parse().map_err(|_| invalid(&value, "expected a number of frames, 1 to 3"))?);
+                },
+                ("--max-fps", Some(value)) => {
+                    let fps = value.parse::<u32>().ok().filter(|fps| *fps > 0);
+                    common.max_fps = Some(fps.ok_or_else(|| invalid(&value, "expected a positive number of frames per second"))?);
+                },
+                ("--monitor", Some(value)) => {
+                    common.monitor = Some(value.parse().map_err(|_| invalid(&value, "expected a monitor index"))?);
+                },
+                ("--timezone", Some(value)) => {
+                    common.timezone = parse_timezone(&value).map_err(|reason| invalid(&value, &reason))?;
+                },
+                ("--time", Some(value)) => {
+                    let time = NaiveTime::parse_from_str(&value, "%H:%M:%S")
+                        .or_else(|_| NaiveTime::parse_from_str(&value, "%H:%M"));
+                    common.time = Some(time.map_err(|_| invalid(&value, "expected HH:MM:SS"))?);
+                },
+                ("--date", Some(value)) => {
+                    let date = NaiveDate::parse_from_str(&value, "%Y-%m-%d");
+                    common.date = Some(date.map_err(|_| invalid(&value, "expected YYYY-MM-DD"))?);
+                },
+                ("--time-scale", Some(value)) => {
+                    let scale = value.parse::<f64>().ok().filter(|s| s.is_finite() && *s >= 0.0);
+                    common.time_scale = scale.ok_or_else(|| invalid(&value, "expected a non-negative number"))?;
+                },
+                ("--widget-key", Some(value)) => {
+                    common.widget_key = WidgetModifier::parse(&value).ok_or_else(|| invalid(&value, "expected alt, ctrl, shift or super"))?;
+                },
+                ("--corner", Some(value)) => {
+                    common.corner = Some(ScreenCorner::parse(&value).ok_or_else(|| invalid(&value, "expected top-left, top-right, bottom-left or bottom-right"))?);
+                },
+                ("--opacity", Some(value)) => {
+                    let opacity = value.parse::<f32>().ok().filter(|o| *o > 0.0 && *o <= 1.0);
+                    common.opacity = opacity.ok_or_else(|| invalid(&value, "expected a number within (0, 1]"))?;
+                },
+                ("--palette", Some(value))   => common.palette   = Some(PathBuf::from(value)),
+                ("--resources", Some(value)) => common.resources = Some(PathBuf::from(value)),
+                ("--snapshot", Some(value))  => common.snapshot  = Some(PathBuf::from(value)),
+                ("--record", Some(value))    => common.record    = Some(PathBuf::from(value)),
+                ("--record-fps", Some(value)) => {
+                    let fps = value.parse::<u32>().ok().filter(|fps| (1..=50).contains(fps));
+                    common.record_fps = fps.ok_or_else(|| invalid(&value, "expected a number between 1 and 50"))?;
+                },
+                ("--record-scale", Some(value)) => {
+                    let scale = value.parse::<f32>().ok().filter(|s| *s > 0.0 && *s <= 1.0);
+                    common.record_scale = scale.ok_or_else(|| invalid(&value, "expected a number within (0, 1]"))?;
+                },
+                ("--record-seconds", Some(value)) => {
+                    let seconds = value.parse::<f64>().ok().filter(|s| s.is_finite() && *s > 0.0);
+                    common.record_seconds = Some(seconds.ok_or_else(|| invalid(&value, "expected a positive number"))?);
+                },
+                (name, value) => app.values.push((name, value))
+            }
+        }
+
+        Ok((common, app))
+    }
+
+    /// Closest known flag to the unknown one, if it's close enough to be a typo
+    fn suggest(self: &Self, flag: &str) -> Option<&'static str> {
+        fn edit_distance(a: &str, b: &str) -> usize {
+            let b: Vec<char> = b.chars().collect();
+            let mut row: Vec<usize> = (0..=b.len()).collect();
+
+            for (i, ca) in a.chars().enumerate() {
+                let mut diagonal = row[0];
+                row[0] = i + 1;
+
+                for (j, cb) in b.iter().enumerate() {
+                    let above = row[j + 1];
+                    row[j + 1] = if ca == *cb { diagonal } else { 1 + diagonal.min(above).min(row[j]) };
+                    diagonal = above;
+                }
+            }
+
+            row[b.len()]
+        }
+
+        COMMON_FLAGS.iter().chain(self.extras.iter())
+            .map(|spec| (edit_distance(flag, spec.name), spec.name))
+            .filter(|(distance, _)| *distance <= 3)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, name)| name)
+    }
+}
@@ -0,0 +1,12 @@
+//! The clocks' CPU side logic, kept apart from the binaries' GPU setup
+//! so that it can be benchmarked (and reused) without a device.
+
+pub mod analog;
+pub mod binary;
+pub mod digital;
+pub mod flip;
+pub mod mcounter;
+pub mod nixie;
+pub mod polar;
+pub mod portal;
+pub mod wordclock;
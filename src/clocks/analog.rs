@@ -0,0 +1,169 @@
+//! Hand angles and hand shapes of the analog clock
+
+use std::ops::Range;
+use chrono::{DateTime, FixedOffset, Timelike};
+use crate::Vtx2UV;
+
+/// Half the width/height of the drawing area that's always visible, in drawing units
+pub const EXTENT: f32 = 16.0;
+
+/// Of the dial texture's quad, the dial is as wide as the texture
+pub const DIAL_RADIUS: f32 = 15.0;
+
+/// Lengths of the hands from the center, and how far they stick out behind it
+pub const HOURS_LENGTH:   f32 =  8.5;
+pub const MINUTES_LENGTH: f32 = 12.5;
+pub const SECONDS_LENGTH: f32 = 13.0;
+pub const SECONDS_TAIL:   f32 =  3.0;
+pub const HOURS_TAIL:     f32 =  1.2;
+pub const MINUTES_TAIL:   f32 =  1.2;
+
+/// Half widths of the hands' shafts
+pub const HOURS_HALF_WIDTH:   f32 = 0.45;
+pub const MINUTES_HALF_WIDTH: f32 = 0.3;
+pub const SECONDS_HALF_WIDTH: f32 = 0.1;
+
+/// The cap covering the hands' pivot
+pub const CAP_RADIUS: f32 = 0.55;
+
+/// Clockwise from 12 o'clock, in radians
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HandAngles {
+    pub hours:   f32,
+    pub minutes: f32,
+    pub seconds: f32
+}
+
+/// The angles at the given time. Every hand sweeps on continuously, the seconds' one included,
+/// down to the nanosecond (held at the end of a leap second rather than running past 12)
+pub fn calc_hand_angles(now: &DateTime<FixedOffset>) -> HandAngles {
+    use std::f32::consts::TAU;
+
+    let nanos   = now.nanosecond().min(999_999_999);
+    let seconds = now.second() as f32 + nanos as f32 / 1_000_000_000.0;
+    let minutes = now.minute() as f32 + seconds / 60.0;
+    let hours   = (now.hour() % 12) as f32 + minutes / 60.0;
+
+    HandAngles {
+        hours:   (hours   / 12.0) * TAU,
+        minutes: (minutes / 60.0) * TAU,
+        seconds: (seconds / 60.0) * TAU
+    }
+}
+
+/// The hands' outlines, cycled with Space
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandShape {
+    /// Straight bars
+    Baton,
+    /// Bars ending in a diamond
+    Spade,
+    /// Thin bars with a disk near the tip
+    Lollipop
+}
+
+impl HandShape {
+    pub const ALL: [HandShape; 3] = [HandShape::Baton, HandShape::Spade, HandShape::Lollipop];
+
+    pub fn next(self: &Self) -> Self {
+        match self {
+            HandShape::Baton    => HandShape::Spade,
+            HandShape::Spade    => HandShape::Lollipop,
+            HandShape::Lollipop => HandShape::Baton
+        }
+    }
+
+    pub fn name(self: &Self) -> &'static str {
+        match self {
+            HandShape::Baton    => "Baton",
+            HandShape::Spade    => "Spade",
+            HandShape::Lollipop => "Lollipop"
+        }
+    }
+}
+
+/// The three hands of a shape in one vertex buffer, pointing at 12 o'clock, in drawing units around the pivot.
+/// Triangle lists made of quads, the UVs span [-1..1] across the round ones (disks) and are 0 elsewhere,
+/// so that the fragment shader can cut the disks out of their quads with `length(uv)`
+pub struct HandGeometry {
+    pub vertices: Vec<Vtx2UV>,
+    pub hours:    Range<u32>,
+    pub minutes:  Range<u32>,
+    pub seconds:  Range<u32>
+}
+
+impl HandGeometry {
+    pub fn new(shape: HandShape) -> Self {
+        let mut vertices = Vec::new();
+
+        let mut hand = |length: f32, tail: f32, half_width: f32| {
+            let start = vertices.len() as u32;
+
+            match shape {
+                HandShape::Baton => {
+                    push_bar(&mut vertices, -tail, length, half_width);
+                },
+                HandShape::Spade => {
+                    // the shaft ends where the diamond is widest, so that no seam shows between them
+                    let head = length * 0.3;
+                    push_bar(&mut vertices, -tail, length - head * 0.5, half_width);
+                    push_diamond(&mut vertices, length - head, length, half_width * 2.4);
+                },
+                HandShape::Lollipop => {
+                    let radius = half_width * 2.4;
+                    push_bar(&mut vertices, -tail, length, half_width * 0.6);
+                    push_disk(&mut vertices, glam::Vec2::new(0.0, length * 0.8), radius);
+                }
+            }
+
+            start..vertices.len() as u32
+        };
+
+        let hours   = hand(HOURS_LENGTH,   HOURS_TAIL,   HOURS_HALF_WIDTH);
+        let minutes = hand(MINUTES_LENGTH, MINUTES_TAIL, MINUTES_HALF_WIDTH);
+        let seconds = hand(SECONDS_LENGTH, SECONDS_TAIL, SECONDS_HALF_WIDTH);
+
+        Self { vertices, hours, minutes, seconds }
+    }
+}
+
+/// The cap's disk, shared by all shapes
+pub fn cap_vertices() -> Vec<Vtx2UV> {
+    let mut vertices = Vec::with_capacity(6);
+    push_disk(&mut vertices, glam::Vec2::ZERO, CAP_RADIUS);
+
+    vertices
+}
+
+/// Two triangles from the four corners, given counter-clockwise
+fn push_quad(vertices: &mut Vec<Vtx2UV>, corners: [Vtx2UV; 4]) {
+    vertices.extend_from_slice(&[corners[0], corners[1], corners[2], corners[0], corners[2], corners[3]]);
+}
+
+/// A solid rectangle along the hand, from `from` to `to` on the y axis
+fn push_bar(vertices: &mut Vec<Vtx2UV>, from: f32, to: f32, half_width: f32) {
+    let corner = |x: f32, y: f32| Vtx2UV { pos: glam::Vec2::new(x, y), uv: glam::Vec2::ZERO };
+
+    push_quad(vertices, [
+        corner(-half_width, from), corner(half_width, from),
+        corner( half_width, to),   corner(-half_width, to)
+    ]);
+}
+
+/// A solid rhombus along the hand, widest halfway between `from` and `to`
+fn push_diamond(vertices: &mut Vec<Vtx2UV>, from: f32, to: f32, half_width: f32) {
+    let corner = |x: f32, y: f32| Vtx2UV { pos: glam::Vec2::new(x, y), uv: glam::Vec2::ZERO };
+    let middle = (from + to) * 0.5;
+
+    push_quad(vertices, [
+        corner(0.0, from), corner(half_width, middle),
+        corner(0.0, to),   corner(-half_width, middle)
+    ]);
+}
+
+/// A disk, cut out of its bounding square by the fragment shader
+fn push_disk(vertices: &mut Vec<Vtx2UV>, center: glam::Vec2, radius: f32) {
+    let corner = |x: f32, y: f32| Vtx2UV { pos: center + glam::Vec2::new(x, y) * radius, uv: glam::Vec2::new(x, y) };
+
+    push_quad(vertices, [corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0)]);
+}
@@ -0,0 +1,137 @@
+//! Bit flags and LED layout of the binary (BCD) clock
+
+use chrono::{DateTime, FixedOffset, Timelike, TimeDelta};
+use crate::Vtx2ID;
+
+/// Hours tens, hours ones, minutes tens, minutes ones, seconds tens and seconds ones, left to right
+pub const COLUMNS: u32 = 6;
+
+/// Bits 1, 2, 4 and 8 of each column's digit, bottom to top
+pub const ROWS: u32 = 4;
+
+pub const LED_COUNT: u32 = COLUMNS * ROWS;
+
+/// Half the width/height of the drawing area that's always visible, in drawing units
+pub const EXTENT: glam::Vec2 = glam::Vec2::new(4.0, 2.5);
+
+/// Distance between neighbouring LEDs' centers, and the extra room between the hours, minutes and seconds
+pub const LED_SPACING: f32 = 1.0;
+pub const GROUP_GAP:   f32 = 0.5;
+
+pub const LED_RADIUS: f32 = 0.38;
+
+/// Rim vertices of each LED's disk
+pub const LED_SEGMENTS: u32 = 32;
+
+/// What the LED pass draws, sent via push constants (or a uniform buffer without them), see led.wgsl
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct BinaryData {
+    pub flags:     u32, // bit `column * 4 + row` lights the LED of the same island ID, see `island_id`
+    pub on_color:  u32,
+    pub off_color: u32
+}
+
+/// The island ID of an LED, which is also its bit in `BinaryData::flags`.
+/// Each column's four bits are in a row, so the column's digit is shifted in as it is
+pub fn island_id(column: u32, row: u32) -> u32 {
+    column * ROWS + row
+}
+
+/// The six digits shown at the given time, hours first.
+/// In 12-hour format midnight and noon are 12, as on the digital clock
+pub fn bcd_digits(now: &DateTime<FixedOffset>, hr12: bool) -> [u32; COLUMNS as usize] {
+    let mut hours = now.hour();
+    let minutes = now.minute();
+    let seconds = now.second().min(59); // held at 59 during a leap second
+
+    if hr12 {
+        hours %= 12;
+
+        if hours == 0 {
+            hours = 12;
+        }
+    }
+
+    [hours / 10, hours % 10, minutes / 10, minutes % 10, seconds / 10, seconds % 10]
+}
+
+/// Calculate the bit flags from the given time
+///
+/// * bits [0..3]   => hour tens
+///
+/// * bits [4..7]   => hour ones
+///
+/// * bits [8..11]  => minute tens
+///
+/// * bits [12..15] => minute ones
+///
+/// * bits [16..19] => second tens
+///
+/// * bits [20..23] => second ones
+///
+/// Unlike the 7-segment digits, every digit is its own bit pattern already, lowest bit at the bottom row
+pub fn calculate_binary_data(now: &DateTime<FixedOffset>, hr12: bool, on_color: u32, off_color: u32) -> BinaryData {
+    let flags = bcd_digits(now, hr12).iter().enumerate()
+        .fold(0, |flags, (column, digit)| flags | digit << island_id(column as u32, 0));
+
+    BinaryData { flags, on_color, off_color }
+}
+
+/// Time left until the LEDs change next, i.e. until the next whole second
+pub fn until_next_second(now: &DateTime<FixedOffset>) -> TimeDelta {
+    // past 1s during a leap second
+    let nanos = now.nanosecond() % 1_000_000_000;
+    TimeDelta::nanoseconds((1_000_000_000 - nanos) as i64)
+}
+
+/// Center of an LED, the columns centered horizontally, the rows vertically
+pub fn led_center(column: u32, row: u32) -> glam::Vec2 {
+    let width = (COLUMNS - 1) as f32 * LED_SPACING + (COLUMNS / 2 - 1) as f32 * GROUP_GAP;
+    let height = (ROWS - 1) as f32 * LED_SPACING;
+
+    glam::Vec2::new(
+        column as f32 * LED_SPACING + (column / 2) as f32 * GROUP_GAP - width * 0.5,
+        row as f32 * LED_SPACING - height * 0.5
+    )
+}
+
+/// Every LED as a triangle fan of its own island (around a center vertex), for an indexed triangle list.
+/// Drawn all at once, the vertex shader tells the lit ones apart by their island IDs
+pub fn led_vertices_and_indices() -> (Vec<Vtx2ID>, Vec<u16>) {
+    use std::f32::consts::TAU;
+
+    let mut vertices = Vec::with_capacity((LED_COUNT * (LED_SEGMENTS + 1)) as usize);
+    let mut indices  = Vec::with_capacity((LED_COUNT * LED_SEGMENTS * 3) as usize);
+
+    for column in 0..COLUMNS {
+        for row in 0..ROWS {
+            let id     = island_id(column, row);
+            let center = led_center(column, row);
+            let first  = vertices.len() as u16;
+
+            vertices.push(Vtx2ID { pos: center, id });
+            vertices.extend((0..LED_SEGMENTS).map(|segment| {
+                let angle = segment as f32 / LED_SEGMENTS as f32 * TAU;
+                Vtx2ID { pos: center + glam::Vec2::from_angle(angle) * LED_RADIUS, id }
+            }));
+
+            for segment in 0..LED_SEGMENTS as u16 {
+                let next = (segment + 1) % LED_SEGMENTS as u16;
+                indices.extend_from_slice(&[first, first + 1 + segment, first + 1 + next]);
+            }
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Identifies what a frame drawn from `data` looks like, see `ExecDraw::content_version`
+pub fn content_version(data: &BinaryData) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (data.flags, data.on_color, data.off_color).hash(&mut hasher);
+
+    hasher.finish()
+}
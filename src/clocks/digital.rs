@@ -0,0 +1,548 @@
+//! Time to LED state logic of the digital clock, and its glow's blur table
+
+use chrono::{DateTime, FixedOffset, Timelike, Datelike, TimeDelta, Weekday};
+use serde::{Serialize, Deserialize};
+
+#[repr(C, align(8))]
+#[derive(Default)]
+pub struct ClockData {
+    pub flagset:   [u32; 2], // actual LED on/off states are encoded in these two
+    pub selector:    u32, // color palette selector, unrelated to clock
+    pub timestamp:   f32, // for animation, unrelated to clock
+    pub colon_alpha: f32, // brightness multiplier of the colon, see `ColonStyle`
+    pub use_texture_brightness: u32, // bool, modulate the lit LEDs by the clock layout's brightness
+    pub previous_flagset: [u32; 2], // the LEDs faded from, see `segment_transition`
+    pub transition:  f32, // how far along the fade is [0..1], 1 when there's none
+    pub digits_color: u32, // colors of the island groups under the solid palettes, see `SOLID_PALETTES`
+    pub colon_color:  u32,
+    pub labels_color: u32,
+    pub edited_group: u32, // island group being edited, 0 for none, otherwise the palette slot + 1
+    pub edited_brightness: f32 // brightness multiplier of the edited group, for blinking it
+}
+
+/// Names of the island groups colored separately, in palette files and `SOLID_PALETTES`.
+/// The digits' segments, the colon, and the weekday/AM/PM labels
+pub const PALETTE_SLOTS: &[&str] = &["digits", "colon", "labels"];
+
+/// The palettes of one color per island group (`PALETTE_SLOTS`), selectors 0 to 2: blue, green and orange.
+/// The palettes past these are animated by forward.wgsl
+pub const SOLID_PALETTES: [[u32; 3]; 3] = [
+    [0x0F52FF_FF; 3],
+    [0x1AE61A_FF; 3],
+    [0xFF4D00_FF; 3]
+];
+
+/// How the colon between hours and minutes behaves over each second
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColonStyle {
+    /// Off for the first half of the second, on for the other (the classic one)
+    #[default]
+    Blink,
+    /// Always on, brightness follows a sine wave within [0.2..1.0]
+    Pulse,
+    /// Always on, full brightness
+    Solid,
+    /// Brightness ramps up from 0 to 1 over the second, then drops
+    Fade
+}
+
+impl ColonStyle {
+    pub const ALL: [ColonStyle; 4] = [ColonStyle::Blink, ColonStyle::Pulse, ColonStyle::Solid, ColonStyle::Fade];
+
+    pub fn next(self: &Self) -> Self {
+        let index = Self::ALL.iter().position(|style| style == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Whether the colon changes continuously, rather than twice a second at most
+    pub fn is_animated(self: &Self) -> bool {
+        matches!(self, ColonStyle::Pulse | ColonStyle::Fade)
+    }
+
+    /// Colon's on/off state and brightness at the given fraction [0..1) of the second
+    fn state(self: &Self, fraction: f32) -> (bool, f32) {
+        use std::f32::consts::TAU;
+
+        match self {
+            ColonStyle::Blink => (fraction > 0.5, 1.0),
+            ColonStyle::Pulse => (true, 0.6 + 0.4 * (TAU * fraction).sin()),
+            ColonStyle::Solid => (true, 1.0),
+            ColonStyle::Fade  => (true, fraction)
+        }
+    }
+}
+
+/// How the glow is laid over the sharp forward image by the composite pass
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GlowBlend {
+    /// Forward + glow, bright segments clip to white under a strong glow
+    #[default]
+    Add,
+    /// 1 - (1 - forward) * (1 - glow), never brighter than white, keeps the segments' hue
+    Screen
+}
+
+impl GlowBlend {
+    /// `blend_mode` of composite.wgsl
+    pub fn shader_value(self: &Self) -> u32 {
+        match self {
+            GlowBlend::Add    => 0,
+            GlowBlend::Screen => 1
+        }
+    }
+}
+
+/// `add` or `screen`, as given to `--glow-blend`
+pub fn parse_glow_blend(value: &str) -> Option<GlowBlend> {
+    match value.to_ascii_lowercase().as_str() {
+        "add" | "additive" => Some(GlowBlend::Add),
+        "screen"           => Some(GlowBlend::Screen),
+        _ => None
+    }
+}
+
+/// Push constants of the composite pass, see composite.wgsl
+#[repr(C)]
+pub struct CompositeInfo {
+    pub glow_intensity: f32, // multiplier of the blurred image, 1.0 is the default glow, 0.0 none
+    pub blend_mode:     u32, // `GlowBlend::shader_value`
+    pub alpha_mode:     u32  // 0: opaque, 1: premultiplied, 2: postmultiplied (transparent window)
+}
+
+/// The colon's bit in the 2nd flagset, `COLON_ISLAND` in forward.wgsl (32 + 9) depends on it
+pub const COLON_BIT: u32 = 9;
+
+/// Which bits of the 2nd flagset light the clock layout's indicators, i.e. the island IDs (minus 32) of
+/// `clock_layout.png`'s labels in the digital clock's `VERTICES`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndicatorLayout {
+    pub first_day:    Weekday,  // the day of the leftmost weekday label
+    pub weekday_bits: [u32; 7], // the weekday labels, left to right
+    pub am_bit:       u32,
+    pub pm_bit:       u32,
+    pub colon_bit:    u32
+}
+
+/// SUN MON TUE WED THU FRI SAT, then AM, PM and the colon
+pub const INDICATOR_LAYOUT: IndicatorLayout = IndicatorLayout {
+    first_day:    Weekday::Sun,
+    weekday_bits: [0, 1, 2, 3, 4, 5, 6],
+    am_bit:       7,
+    pm_bit:       8,
+    colon_bit:    COLON_BIT
+};
+
+impl IndicatorLayout {
+    /// For layouts whose weekday labels start on another day, e.g. Monday
+    pub fn with_first_day(self: &Self, first_day: Weekday) -> Self {
+        Self { first_day, ..*self }
+    }
+
+    /// Bit of the label showing the given day
+    pub fn weekday_bit(self: &Self, weekday: Weekday) -> u32 {
+        let position = (weekday.num_days_from_monday() + 7 - self.first_day.num_days_from_monday()) % 7;
+        self.weekday_bits[position as usize]
+    }
+
+    /// Every indicator needs a bit of its own within the 2nd flagset (the digits take up the 1st one),
+    /// and the colon's has to be the one the shader animates
+    pub fn validate(self: &Self) -> Result<(), String> {
+        let named = self.weekday_bits.iter().enumerate()
+            .map(|(position, bit)| (format!("weekday label {}", position + 1), *bit))
+            .chain([("AM".to_string(), self.am_bit), ("PM".to_string(), self.pm_bit), ("colon".to_string(), self.colon_bit)]);
+
+        let mut used: u32 = 0;
+        for (name, bit) in named {
+            if bit >= 32 {
+                return Err(format!("The {}'s bit {} is out of the flagset", name, bit));
+            }
+            if used & (1 << bit) != 0 {
+                return Err(format!("The {}'s bit {} is already taken", name, bit));
+            }
+            used |= 1 << bit;
+        }
+
+        if self.colon_bit != COLON_BIT {
+            return Err(format!("The colon's bit must be {} (see forward.wgsl), not {}", COLON_BIT, self.colon_bit));
+        }
+
+        Ok(())
+    }
+}
+
+/// `monday` or `sunday`, as given to `--first-day`
+pub fn parse_first_day(value: &str) -> Option<Weekday> {
+    match value.to_ascii_lowercase().as_str() {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None
+    }
+}
+
+/// Duration of the segments' fade from one minute to the next, at the normal animation speed
+pub const SEGMENT_TRANSITION_MS: f32 = 200.0;
+
+/// Animation speeds stepped through with the Up/Down keys, 0 turns the fade off
+pub const ANIMATION_SPEEDS: [f32; 7] = [0.0, 0.25, 0.5, 1.0, 1.5, 2.0, 4.0];
+
+/// `SEGMENT_TRANSITION_MS` at the given speed (2.0 is twice as fast), `None` when the fade is off
+pub fn transition_duration_ms(animation_speed: f32) -> Option<f32> {
+    (animation_speed > 0.0).then(|| SEGMENT_TRANSITION_MS / animation_speed)
+}
+
+/// The next faster or slower step of `ANIMATION_SPEEDS`, speeds in between (from the config file) snap to it.
+/// Stays put at either end
+pub fn step_animation_speed(animation_speed: f32, faster: bool) -> f32 {
+    let next = if faster {
+        ANIMATION_SPEEDS.iter().find(|speed| **speed > animation_speed)
+    } else {
+        ANIMATION_SPEEDS.iter().rev().find(|speed| **speed < animation_speed)
+    };
+
+    next.copied().unwrap_or(animation_speed)
+}
+
+/// As shown in the window title, e.g. `1.0x` or `0.25x`
+pub fn format_animation_speed(animation_speed: f32) -> String {
+    if animation_speed.fract() == 0.0 {
+        format!("{:.1}x", animation_speed)
+    } else {
+        format!("{}x", animation_speed)
+    }
+}
+
+/// While the segments fade from the previous minute's LEDs to the current ones, the moment right before the minute
+/// changed (to calculate the previous LEDs at) and how far along the fade is [0..1).
+/// `None` past the fade's duration, or with the fade off
+pub fn segment_transition(now: &DateTime<FixedOffset>, animation_speed: f32) -> Option<(DateTime<FixedOffset>, f32)> {
+    let duration_ms = transition_duration_ms(animation_speed)?;
+
+    let since_minute = TimeDelta::seconds(now.second() as i64) + TimeDelta::nanoseconds(now.nanosecond() as i64);
+    let elapsed_ms = since_minute.num_microseconds().unwrap_or(i64::MAX) as f32 / 1000.0;
+
+    if elapsed_ms >= duration_ms {
+        return None;
+    }
+
+    Some((*now - since_minute - TimeDelta::nanoseconds(1), elapsed_ms / duration_ms))
+}
+
+/// Time left until the colon blinks next, i.e. until the next half or whole second
+pub fn until_next_half_second(now: &DateTime<FixedOffset>) -> TimeDelta {
+    const HALF_SECOND: u32 = 500_000_000;
+
+    // past 1s during a leap second
+    let nanos = now.nanosecond() % 1_000_000_000;
+    TimeDelta::nanoseconds((HALF_SECOND - nanos % HALF_SECOND) as i64)
+}
+
+#[derive(Debug)]
+#[repr(C, align(8))]
+pub struct BlurWO {
+    pub weight: f32,
+    pub offset: f32
+}
+
+/// While calculating gaussian blur, the same weights will be generated for all pixels,
+/// to cut out this redundant calc, we move that to the CPU from the fragment shader.
+/// This is only done once. Both weights and pixel offsets are calculated,
+/// and then sent to the fragment shader as a read-only storage buffer.
+/// The shader treats this buffer as a look-up table.
+/// This the rustified version of the JS code found in: https://lisyarus.github.io/blog/graphics/2023/02/24/blur-coefficients-generator.html
+/// So this function is not my code.
+pub fn create_blur_weights_and_offsets(
+    radius:     i32,
+    sigma:      f32,
+    linear:     bool,
+    correction: bool
+) -> Result<Vec<BlurWO>, &'static str> {
+    if radius < 1 {
+        return Err("Radius must be 1 or up");
+    }
+
+    if sigma == 0.0 {
+        return Err("Sigma cannot be 0");
+    }
+
+    // From https://hewgill.com/picomath/javascript/erf.js.html
+    fn erf(x: f32) -> f32 {
+        // constants
+        let a1: f32 =  0.254829592;
+        let a2: f32 = -0.284496736;
+        let a3: f32 =  1.421413741;
+        let a4: f32 = -1.453152027;
+        let a5: f32 =  1.061405429;
+        let  p: f32 =  0.3275911;
+    
+        // Save the sign of x
+        let mut sign: f32 = 1.0;
+        if x < 0.0 {
+            sign = -1.0;
+        }
+
+        let x = x.abs();
+    
+        // A&S formula 7.1.26
+        let t = 1.0 / (1.0 + p * x);
+        let y = 1.0 - (((((a5 * t + a4) * t) + a3) * t + a2) * t + a1) * t * (-x * x).exp();
+    
+        return sign * y;
+    }
+
+    let mut sum_weights: f32 = 0.0;
+
+    let mut weights: Vec<f32> = (-radius..radius+1).map(|i| {
+        let i = i as f32;
+
+        let w = if correction {
+            (erf((i + 0.5) / sigma / 2.0_f32.sqrt()) - erf((i - 0.5) / sigma / 2.0_f32.sqrt())) / 2.0
+        } else {
+            (-i * i / sigma / sigma).exp()
+        };
+
+        sum_weights += w;
+
+        return w;
+    }).collect();
+
+    let inv_sum_weights = 1.0 / sum_weights;
+    for weight in weights.iter_mut() {
+        *weight *= inv_sum_weights;
+    }
+
+    let weights_and_offsets: Vec<BlurWO> = if linear {
+        (-radius..radius+1).step_by(2).map(|i| {
+            if i == radius {
+                BlurWO {
+                    offset: i as f32,
+                    weight: weights[(i + radius) as usize]
+                }
+            } else {
+                let w0 = weights[(i + radius + 0) as usize];
+                let w1 = weights[(i + radius + 1) as usize];
+                let w = w0 + w1;
+
+                let o: f32 = if w > 0.0 {
+                    (i as f32) + w1 / w
+                } else {
+                    i as f32
+                };
+
+                BlurWO {
+                    offset: o,
+                    weight: w
+                }
+            }
+        }).collect()
+    } else {
+        (-radius..radius+1).enumerate().map(|(index, off)| {
+            BlurWO {
+                offset: off as f32,
+                weight: weights[index]
+            }
+        }).collect()
+    };
+
+    Ok(weights_and_offsets)
+}
+
+/// Calculate bit flags from the given time
+/// 
+/// Flagset 0:
+/// 
+/// * bits [0..6]   => hour tens
+/// 
+/// * bits [7..13]  => hour ones
+/// 
+/// * bits [14..20] => minute tens
+/// 
+/// * bits [21..27] => minute ones
+/// 
+/// Flagset 1:
+/// 
+/// * day of week, AM indicator, PM indicator and colon, at the bits given by the `layout`
+///   (`INDICATOR_LAYOUT`: bits [0..6], 7, 8 and 9)
+/// 
+/// The colon's brightness is returned separately, as `colon_alpha`.
+/// There's no fade, `previous_flagset` is the same as `flagset`, see `segment_transition` for setting one up
+pub fn calculate_clock_data(
+    now:         &DateTime<FixedOffset>,
+    hr12:        bool,
+    selector:    u32,
+    colon_style: ColonStyle,
+    layout:      &IndicatorLayout
+) -> ClockData {
+    let mut hours = now.hour();
+    let minutes = now.minute();
+    let (colon_on, colon_alpha) = colon_style.state(now.nanosecond().min(999_999_999) as f32 / 1_000_000_000.0);
+
+    let mut am = false;
+    let mut pm = false;
+
+    if hr12 {
+        if hours >= 12 {
+            pm = true;
+        } else {
+            am = true;
+        }
+
+        hours %= 12;
+
+        if hours == 0 {
+            hours = 12;
+        }
+    }
+
+    let mut flags0: u32 = 0;
+    let mut flags1: u32 = 0;
+
+    // special case for hour tens digit, turn it off completely when it is zero
+    flags0 |= if (hours / 10) != 0 {
+        DIGIT_SEGMENT_FLAGS[(hours / 10) as usize] << 0
+    } else {
+        0
+    };
+    
+    flags0 |= DIGIT_SEGMENT_FLAGS[(hours % 10) as usize] << 7;
+
+    flags0 |= DIGIT_SEGMENT_FLAGS[(minutes / 10) as usize] << 14;
+    flags0 |= DIGIT_SEGMENT_FLAGS[(minutes % 10) as usize] << 21;
+
+    flags1 |= 1 << layout.weekday_bit(now.weekday());
+    
+    flags1 |= (if am { 1 } else { 0 }) << layout.am_bit;
+    flags1 |= (if pm { 1 } else { 0 }) << layout.pm_bit;
+
+    flags1 |= (if colon_on { 1 } else { 0 }) << layout.colon_bit;
+
+    let timestamp = now.second() as f32 + now.nanosecond() as f32 / 1_000_000_000.0;
+
+    // the animated palettes don't use these
+    let colors = SOLID_PALETTES.get(selector as usize).copied().unwrap_or([0; 3]);
+
+    ClockData {
+        flagset: [flags0, flags1], selector, timestamp, colon_alpha, use_texture_brightness: 0,
+        previous_flagset: [flags0, flags1], transition: 1.0,
+        digits_color: colors[0], colon_color: colors[1], labels_color: colors[2],
+        edited_group: 0, edited_brightness: 1.0
+    }
+}
+
+/// Identifies what a frame drawn from `data` looks like: the LEDs lit (and faded from), the palette and the colon's brightness,
+/// plus the given `second` for whatever else changes with it. Equal for frames that look alike, as long as the palette
+/// and the colon aren't animated, see `ExecDraw::content_version`
+pub fn content_version(data: &ClockData, second: Option<i64>) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (data.flagset, data.previous_flagset, data.transition.to_bits(), data.selector, data.colon_alpha.to_bits(), second).hash(&mut hasher);
+
+    hasher.finish()
+}
+
+// 7-segment display segment mapping table
+pub const DIGIT_SEGMENT_FLAGS: [u32; 10] = [
+    0b1110111,
+    0b1000100,
+    0b1011011,
+    0b1011101,
+    0b1101100,
+    0b0111101,
+    0b0111111,
+    0b1010100,
+    0b1111111,
+    0b1111101
+];
+
+/// Rough draw of a single lit segment (or indicator) LED, in milliwatts
+pub const SEGMENT_POWER_MW: f32 = 0.5;
+
+/// Seconds in the power estimate's rolling average, a day
+pub const POWER_HISTORY_SECONDS: usize = 86400;
+
+/// LEDs lit by the given flagsets, segments and indicators alike
+pub fn lit_segments(flagset: [u32; 2]) -> u32 {
+    flagset[0].count_ones() + flagset[1].count_ones()
+}
+
+/// Estimated draw of the lit LEDs, in milliwatts
+pub fn power_mw(lit_segments: u32) -> f32 {
+    lit_segments as f32 * SEGMENT_POWER_MW
+}
+
+/// Lit LEDs of each second over the last day, for the average power draw.
+/// One entry per second of clock time, a ring buffer with a running sum, so that nothing is summed up per frame
+pub struct PowerHistory {
+    counts: Vec<u8>,  // up to 64 LEDs each, `POWER_HISTORY_SECONDS` long once full
+    next:   usize,    // where the next second goes once full
+    sum:    u64,
+    last:   Option<i64> // timestamp of the latest recorded second
+}
+
+impl PowerHistory {
+    pub fn new() -> Self {
+        Self { counts: Vec::new(), next: 0, sum: 0, last: None }
+    }
+
+    /// Record the LEDs lit at the given second (Unix timestamp). Repeats of the same second are ignored,
+    /// and the seconds skipped since the previous one (e.g. while the window was hidden) get the same count.
+    /// A jump back in time, from the time controls, starts over
+    pub fn record(self: &mut Self, second: i64, lit_segments: u32) {
+        let skipped = match self.last {
+            Some(last) if second == last => return,
+            Some(last) if second > last  => (second - last).min(POWER_HISTORY_SECONDS as i64) as usize,
+            Some(_) => {
+                *self = Self::new();
+                1
+            },
+            None => 1
+        };
+
+        let count = lit_segments.min(u8::MAX as u32) as u8;
+
+        for _ in 0..skipped {
+            if self.counts.len() < POWER_HISTORY_SECONDS {
+                self.counts.push(count);
+            } else {
+                self.sum -= self.counts[self.next] as u64;
+                self.counts[self.next] = count;
+                self.next = (self.next + 1) % POWER_HISTORY_SECONDS;
+            }
+
+            self.sum += count as u64;
+        }
+
+        self.last = Some(second);
+    }
+
+    /// LEDs lit at the latest recorded second
+    pub fn latest(self: &Self) -> Option<u32> {
+        let count = if self.counts.len() < POWER_HISTORY_SECONDS {
+            self.counts.last()
+        } else {
+            self.counts.get((self.next + POWER_HISTORY_SECONDS - 1) % POWER_HISTORY_SECONDS)
+        };
+
+        count.map(|&count| count as u32)
+    }
+
+    /// Seconds recorded, up to a day
+    pub fn len(self: &Self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(self: &Self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Average draw over the recorded seconds, in milliwatts, `None` before the first one
+    pub fn average_mw(self: &Self) -> Option<f32> {
+        (!self.is_empty()).then(|| self.sum as f32 / self.len() as f32 * SEGMENT_POWER_MW)
+    }
+}
+
+impl Default for PowerHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,59 @@
+//! Flap angles of the split-flap clock
+
+use chrono::{Timelike, DateTime, FixedOffset, TimeDelta};
+use crate::clocks::mcounter::digits_of;
+
+/// Nanosecs it takes a flap to fall, should not exceed 1s
+pub const FLIP_DURATION: u32 = 350_000_000;
+
+/// Where a slot's flaps are at. While flipping, the flap of `from` falls from upright (0) to flat down (π),
+/// uncovering the upper half of `to` behind it and showing the lower half of `to` on its back.
+/// Standing still, `from` and `to` are the same and the angle is π, the flap having fallen already
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SlotFlip {
+    pub from:  u8,
+    pub to:    u8,
+    pub angle: f32 // in radians, around the slot's horizontal axis
+}
+
+/// Whether any flap can be falling at the given time, they stand still for the rest of the second
+pub fn is_flipping(now: &DateTime<FixedOffset>) -> bool {
+    now.nanosecond() <= FLIP_DURATION
+}
+
+/// Time left until the flaps fall next, i.e. until the next whole second
+pub fn until_next_flip(now: &DateTime<FixedOffset>) -> TimeDelta {
+    // past 1s during a leap second
+    let nanos = now.nanosecond() % 1_000_000_000;
+    TimeDelta::nanoseconds((1_000_000_000 - nanos) as i64)
+}
+
+/// A falling flap: pushed over, it's fast right away, and slows down as it comes to rest against the stack below.
+/// Cubic ease-out, [0..1] to [0..1]
+pub fn flip_ease(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Like `calc_wheel_angles` of the mechanical counter: the digits of the given time are where the flaps end,
+/// and the digits of `FLIP_DURATION` before it where they begin. Only the slots whose digit changed flip,
+/// one flap each, however far the digit jumped (e.g. `23 -> 00`), the flap behind it is the new digit right away
+pub fn calc_slot_flips(now: &DateTime<FixedOffset>) -> [SlotFlip; 6] {
+    let now_digits = digits_of(now.hour(), now.minute(), now.second());
+
+    if !is_flipping(now) {
+        return now_digits.map(|digit| SlotFlip { from: digit, to: digit, angle: std::f32::consts::PI });
+    }
+
+    let ago = *now - TimeDelta::nanoseconds(FLIP_DURATION as i64);
+    let ago_digits = digits_of(ago.hour(), ago.minute(), ago.second());
+
+    let t = (now.nanosecond() as f32) / (FLIP_DURATION as f32);
+    let angle = flip_ease(t) * std::f32::consts::PI;
+
+    std::array::from_fn(|i| SlotFlip {
+        from:  ago_digits[i],
+        to:    now_digits[i],
+        angle: if ago_digits[i] == now_digits[i] { std::f32::consts::PI } else { angle }
+    })
+}
@@ -0,0 +1,242 @@
+//! Wheel rotation logic of the mechanical counter clock
+
+use chrono::{Timelike, DateTime, FixedOffset, TimeDelta};
+use crate::lerp_u32_color;
+
+/// Nanosecs. changeable but should not exceed 1s
+pub const ANIM_DURATION: u32 = 500_000_000;
+
+/// Whether the wheels are turning at the given time, they stand still for the rest of the second
+pub fn is_animating(now: &DateTime<FixedOffset>) -> bool {
+    now.nanosecond() <= ANIM_DURATION
+}
+
+/// Time left until the wheels start turning next, i.e. until the next whole second
+pub fn until_next_animation(now: &DateTime<FixedOffset>) -> TimeDelta {
+    // past 1s during a leap second
+    let nanos = now.nanosecond() % 1_000_000_000;
+    TimeDelta::nanoseconds((1_000_000_000 - nanos) as i64)
+}
+
+/// We calculate the beginning and ending angles for each wheel.
+/// The angles are proportional to the digit itself.
+/// 
+/// Here, the ending digit set is the digit set of the given time.
+/// And the beginning digit set is of `ANIM_DURATION` before that.
+/// The animation/transition happens at first `ANIM_DURATION` of the current second.
+/// During this time period, the resultant angle is
+/// calculated from lerping the beginning and ending angles.
+/// The rest of the time, the wheel stays at ending angle.
+/// 
+/// All wheels must rotate in one direction.
+/// To prevent reverse rotation, for high to low digit transition like `9 -> 0`,
+/// the ending digit is added with `10` to make the transition look like `9 -> 10`
+pub fn calc_wheel_angles(now: &DateTime<FixedOffset>) -> [f32; 6] {
+    let now_digits = digits_of(now.hour(), now.minute(), now.second());
+
+    if !is_animating(now) {
+        return lerp_wheel_angles(now_digits, now_digits, 1.0, false);
+    }
+
+    let ago = *now - TimeDelta::nanoseconds(ANIM_DURATION as i64);
+    let ago_digits = digits_of(ago.hour(), ago.minute(), ago.second());
+
+    let t = (now.nanosecond() as f32) / (ANIM_DURATION as f32);
+    // t = ease_out_bounce(t);
+    // Or use your own favorite easing
+
+    lerp_wheel_angles(ago_digits, now_digits, t, false)
+}
+
+/// The six digits of the given time (or duration), hours first
+pub fn digits_of(hours: u32, minutes: u32, seconds: u32) -> [u8; 6] {
+    [
+        (hours   / 10) as u8, (hours   % 10) as u8,
+        (minutes / 10) as u8, (minutes % 10) as u8,
+        (seconds / 10) as u8, (seconds % 10) as u8,
+    ]
+}
+
+fn angle_for_digit(digit: f32) -> f32 {
+    (digit * 0.1) * std::f32::consts::TAU
+}
+
+/// Lerps every wheel from its digit in `ago` to the one in `now`, turning forwards (`9 -> 10`)
+/// or, for counting down, backwards (`0 -> -1`)
+fn lerp_wheel_angles(ago: [u8; 6], now: [u8; 6], t: f32, backwards: bool) -> [f32; 6] {
+    std::array::from_fn(|i| {
+        let digit_ago = ago[i] as f32;
+        let digit_now = match backwards {
+            false if now[i] < ago[i] => now[i] as f32 + 10.0,
+            true  if now[i] > ago[i] => now[i] as f32 - 10.0,
+            _                        => now[i] as f32
+        };
+
+        (1.0 - t) * angle_for_digit(digit_ago) + t * angle_for_digit(digit_now) // lerp
+    })
+}
+
+/// Whether an image of the given size can be the digits' sprite sheet: ten digits side by side, each at least a pixel wide,
+/// and no bigger than the GPU's textures can be (`max_texture_dimension_2d`)
+pub fn check_sprite_sheet(width: u32, height: u32, max_dimension: u32) -> Result<(), String> {
+    if width < 10 || height == 0 {
+        return Err(format!("A sprite sheet of {}x{} can't hold ten digits side by side", width, height));
+    }
+
+    if width > max_dimension || height > max_dimension {
+        return Err(format!("A sprite sheet of {}x{} is too big for the GPU, {} pixels at most", width, height, max_dimension));
+    }
+
+    Ok(())
+}
+
+/// Cards of every wheel, unless a countdown is running out, as RGBA
+pub const WHEEL_COLOR: u32 = 0xFFFFFF_FF;
+/// Halfway through the last minute of a countdown, the cards are this yellow
+pub const URGENCY_YELLOW: u32 = 0xFFD020_FF;
+/// The cards at the end of a countdown, and while they flash at zero
+pub const URGENCY_RED: u32 = 0xFF2020_FF;
+
+/// The cards turn from white through yellow to red over the last this many seconds of a countdown
+pub const URGENCY_SECONDS: f64 = 60.0;
+/// ..and pulse over the last this many
+pub const PULSE_SECONDS: f64 = 10.0;
+/// ..this many times a second
+pub const PULSE_HZ: f64 = 10.0;
+/// How long the cards flash red at zero, before the countdown stops or starts over
+pub const FLASH_SECONDS: i64 = 3;
+
+/// Where a countdown is at, see `Countdown::state`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CountdownState {
+    /// Time left
+    Running(TimeDelta),
+    /// Reached zero this long ago, the cards are red
+    Flashing(TimeDelta),
+    /// Done flashing, stays at zero
+    Stopped
+}
+
+/// The counter counting down from a duration instead of showing the time, see `--countdown`.
+/// Driven by the shared time source, so it follows the time scale and pauses with it
+#[derive(Clone, Copy, Debug)]
+pub struct Countdown {
+    pub duration: TimeDelta, // in whole seconds
+    pub looping:  bool,      // starts over after flashing rather than stopping
+    started:      DateTime<FixedOffset>
+}
+
+impl Countdown {
+    pub fn new(duration: TimeDelta, looping: bool, now: &DateTime<FixedOffset>) -> Self {
+        Self { duration, looping, started: *now }
+    }
+
+    /// Time since the start, of the current round when looping
+    fn elapsed(self: &Self, now: &DateTime<FixedOffset>) -> TimeDelta {
+        let elapsed = (*now - self.started).max(TimeDelta::zero());
+        let round   = self.duration + TimeDelta::seconds(FLASH_SECONDS);
+
+        match (self.looping, elapsed.num_nanoseconds(), round.num_nanoseconds()) {
+            (true, Some(elapsed), Some(round)) if round > 0 => TimeDelta::nanoseconds(elapsed % round),
+            _ => elapsed
+        }
+    }
+
+    pub fn state(self: &Self, now: &DateTime<FixedOffset>) -> CountdownState {
+        let elapsed = self.elapsed(now);
+
+        if elapsed < self.duration {
+            CountdownState::Running(self.duration - elapsed)
+        } else if elapsed < self.duration + TimeDelta::seconds(FLASH_SECONDS) {
+            CountdownState::Flashing(elapsed - self.duration)
+        } else {
+            CountdownState::Stopped
+        }
+    }
+
+    /// Like `calc_wheel_angles`, for the time left. The wheels turn backwards at the start of each second,
+    /// from the time left a second ago, and stand still at zero once it's reached
+    pub fn wheel_angles(self: &Self, now: &DateTime<FixedOffset>) -> [f32; 6] {
+        let total   = self.duration.num_seconds();
+        let elapsed = self.elapsed(now);
+        let passed  = elapsed.num_seconds().min(total);
+        let into    = elapsed - TimeDelta::seconds(passed); // of the current second, past it once at zero
+
+        let digits = |seconds: i64| {
+            let seconds = seconds.clamp(0, 99 * 3600 + 59 * 60 + 59) as u32;
+            digits_of(seconds / 3600, seconds / 60 % 60, seconds % 60)
+        };
+
+        let now_digits = digits(total - passed);
+        let t = into.num_nanoseconds().unwrap_or(i64::MAX) as f32 / ANIM_DURATION as f32;
+
+        if passed == 0 || t >= 1.0 {
+            return lerp_wheel_angles(now_digits, now_digits, 1.0, true);
+        }
+
+        lerp_wheel_angles(digits(total - passed + 1), now_digits, t, true)
+    }
+
+    /// Whether the picture changes at the given time: the wheels turning, or the colors during the last minute
+    pub fn is_animating(self: &Self, now: &DateTime<FixedOffset>) -> bool {
+        match self.state(now) {
+            CountdownState::Running(left) => {
+                left.num_seconds() < URGENCY_SECONDS as i64 || self.elapsed(now).subsec_nanos() as u32 <= ANIM_DURATION
+            },
+            CountdownState::Flashing(since) => since.num_nanoseconds().unwrap_or(i64::MAX) <= ANIM_DURATION as i64,
+            CountdownState::Stopped => false
+        }
+    }
+
+    /// Time left until the next second of the countdown, `None` once it's stopped
+    pub fn until_next_change(self: &Self, now: &DateTime<FixedOffset>) -> Option<TimeDelta> {
+        match self.state(now) {
+            CountdownState::Stopped => None,
+            _ => Some(TimeDelta::seconds(1) - TimeDelta::nanoseconds(self.elapsed(now).subsec_nanos() as i64))
+        }
+    }
+}
+
+/// Color of every wheel's cards in the given state of a countdown: white until the last minute,
+/// turning yellow and then red over it, pulsing over the last seconds, solid red while flashing at zero
+pub fn urgency_color(state: CountdownState) -> u32 {
+    match state {
+        CountdownState::Running(left) => {
+            let seconds = left.num_nanoseconds().unwrap_or(i64::MAX) as f64 / 1_000_000_000.0;
+            if seconds >= URGENCY_SECONDS {
+                return WHEEL_COLOR;
+            }
+
+            let t = 1.0 - seconds / URGENCY_SECONDS;
+            let color = match t < 0.5 {
+                true  => lerp_u32_color(WHEEL_COLOR, URGENCY_YELLOW, t * 2.0),
+                false => lerp_u32_color(URGENCY_YELLOW, URGENCY_RED, (t - 0.5) * 2.0)
+            };
+
+            if seconds >= PULSE_SECONDS {
+                return color;
+            }
+
+            // between half and full brightness, the alpha is kept
+            let brightness = 0.75 + 0.25 * (std::f64::consts::TAU * PULSE_HZ * seconds).sin();
+            lerp_u32_color(color, color & 0xFF, 1.0 - brightness)
+        },
+        CountdownState::Flashing(_) => URGENCY_RED,
+        CountdownState::Stopped     => WHEEL_COLOR
+    }
+}
+
+/// Parses `--countdown`: `HH:MM:SS`, up to 99 hours, `None` for anything else and for zero
+pub fn parse_countdown(value: &str) -> Option<TimeDelta> {
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    let [hours, minutes, seconds] = parts.as_slice() else {
+        return None;
+    };
+
+    let hours:   i64 = hours.parse().ok().filter(|hours| (0..100).contains(hours))?;
+    let minutes: i64 = minutes.parse().ok().filter(|minutes| (0..60).contains(minutes))?;
+    let seconds: i64 = seconds.parse().ok().filter(|seconds| (0..60).contains(seconds))?;
+
+    let total = hours * 3600 + minutes * 60 + seconds;
+    (total > 0).then(|| TimeDelta::seconds(total))
+}
@@ -0,0 +1,55 @@
+//! Digits and cathode flicker of the nixie tube clock
+
+use chrono::{Timelike, DateTime, FixedOffset, TimeDelta};
+use crate::clocks::mcounter::digits_of;
+
+/// Nanosecs a freshly switched cathode flickers for, before it glows steadily. Matches nixie.wgsl
+pub const FLICKER_DURATION: u32 = 150_000_000;
+
+/// What the tubes show, sent to nixie.wgsl as a uniform. Arrays are padded to whole vectors, as the uniform ones are
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C, align(16))]
+pub struct NixieData {
+    pub digits:       [u32; 8], // digit | previous << 8, per tube, the previous one differs while flickering
+    pub since_change: [f32; 8], // seconds since the tube's digit changed, `FLICKER_DURATION` at most
+    pub timestamp:    f32,      // seconds within the minute, as `ClockData::timestamp`, drives the flicker
+    pub _padding:     [f32; 3]
+}
+
+/// Whether any tube can be flickering at the given time, the digits only change on whole seconds
+pub fn is_flickering(now: &DateTime<FixedOffset>) -> bool {
+    now.nanosecond() < FLICKER_DURATION
+}
+
+/// Time left until the digits change next, i.e. until the next whole second
+pub fn until_next_change(now: &DateTime<FixedOffset>) -> TimeDelta {
+    // past 1s during a leap second
+    let nanos = now.nanosecond() % 1_000_000_000;
+    TimeDelta::nanoseconds((1_000_000_000 - nanos) as i64)
+}
+
+/// The digits of the given time, and of `FLICKER_DURATION` before it, which they just changed from where they differ
+pub fn calc_nixie_data(now: &DateTime<FixedOffset>) -> NixieData {
+    let now_digits = digits_of(now.hour(), now.minute(), now.second());
+
+    let ago = *now - TimeDelta::nanoseconds(FLICKER_DURATION as i64);
+    let ago_digits = digits_of(ago.hour(), ago.minute(), ago.second());
+
+    let settled = FLICKER_DURATION as f32 / 1_000_000_000.0;
+    let since   = (now.nanosecond() as f32 / 1_000_000_000.0).min(settled);
+
+    let mut data = NixieData {
+        timestamp: now.second() as f32 + now.nanosecond() as f32 / 1_000_000_000.0,
+        ..NixieData::default()
+    };
+
+    for i in 0..6 {
+        let changed = is_flickering(now) && ago_digits[i] != now_digits[i];
+        let previous = if changed { ago_digits[i] } else { now_digits[i] };
+
+        data.digits[i]       = now_digits[i] as u32 | (previous as u32) << 8;
+        data.since_change[i] = if changed { since } else { settled };
+    }
+
+    data
+}
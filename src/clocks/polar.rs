@@ -0,0 +1,206 @@
+//! Arc angles and disk positions of the polar clock, and its SVG export
+
+use std::fmt::Write;
+use chrono::{DateTime, FixedOffset, Timelike};
+use crate::{lerp_u32_color, lerp_u32_color_oklab, DrawspaceScales};
+
+/// Half the width/height of the drawing area that's always visible, in drawing units
+pub const EXTENT: f32 = 16.0;
+
+pub const SECONDS_RADIUS: f32 = 13.0;
+pub const MINUTES_RADIUS: f32 =  9.0;
+pub const HOURS_RADIUS:   f32 =  5.0;
+
+pub const THICKNESS:   f32 = 2.4; // of the rings
+pub const DISK_RADIUS: f32 = 0.8;
+
+/// Packed RGBA colors of the clock's parts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorCombo {
+    pub hour:       u32, // color of hour ring
+    pub minute:     u32, // color of minute ring
+    pub second:     u32, // color of second ring
+    pub disk:       u32, // common color of all disks
+    pub background: u32  // background color
+}
+
+/// Names of `ColorCombo`'s parts in palette files, in the order of `to_slots`
+pub const PALETTE_SLOTS: &[&str] = &["hour", "minute", "second", "disk", "background"];
+
+impl ColorCombo {
+    /// The colors in the order of `PALETTE_SLOTS`
+    pub fn to_slots(self: &Self) -> Vec<u32> {
+        vec![self.hour, self.minute, self.second, self.disk, self.background]
+    }
+
+    /// Back from `to_slots`
+    pub fn from_slots(slots: &[u32]) -> Self {
+        Self { hour: slots[0], minute: slots[1], second: slots[2], disk: slots[3], background: slots[4] }
+    }
+
+    /// Each part's color interpolated separately, `t` in [0..1]
+    pub fn lerp(self: &Self, other: &Self, t: f64) -> Self {
+        Self {
+            hour:       lerp_u32_color(self.hour,       other.hour,       t),
+            minute:     lerp_u32_color(self.minute,     other.minute,     t),
+            second:     lerp_u32_color(self.second,     other.second,     t),
+            disk:       lerp_u32_color(self.disk,       other.disk,       t),
+            background: lerp_u32_color(self.background, other.background, t)
+        }
+    }
+
+    /// `lerp` through OKLab, see `lerp_u32_color_oklab`
+    pub fn lerp_oklab(self: &Self, other: &Self, t: f64) -> Self {
+        Self {
+            hour:       lerp_u32_color_oklab(self.hour,       other.hour,       t),
+            minute:     lerp_u32_color_oklab(self.minute,     other.minute,     t),
+            second:     lerp_u32_color_oklab(self.second,     other.second,     t),
+            disk:       lerp_u32_color_oklab(self.disk,       other.disk,       t),
+            background: lerp_u32_color_oklab(self.background, other.background, t)
+        }
+    }
+}
+
+pub struct AnglesAndPositions {
+    pub hours_angle:   f32,
+    pub minutes_angle: f32,
+    pub seconds_angle: f32,
+
+    pub hours_pos:   (f32, f32),
+    pub minutes_pos: (f32, f32),
+    pub seconds_pos: (f32, f32)
+}
+
+pub fn calc_angles_and_positions(now: &DateTime<FixedOffset>) -> AnglesAndPositions {
+    use std::f32::consts::{FRAC_PI_2, TAU, PI};
+
+    let seconds = now.second() as f32 + (now.nanosecond() as f32 / 1_000_000_000.0);
+    let minutes = now.minute() as f32 + seconds / 60.0;
+    let hours   = (now.hour() % 12) as f32 + minutes / 60.0;
+
+    // angles used for drawing the arcs and calculating disk centers
+    let seconds_angle = (seconds / 60.0) * TAU;
+    let minutes_angle = (minutes / 60.0) * TAU;
+    let hours_angle   = (hours   / 12.0) * TAU;
+
+    // positions of disk centers
+    let seconds_pos: (f32, f32) = (
+        SECONDS_RADIUS * ((PI + TAU - seconds_angle) - FRAC_PI_2).cos(),
+        SECONDS_RADIUS * ((PI + TAU - seconds_angle) - FRAC_PI_2).sin()
+    );
+
+    let minutes_pos: (f32, f32) = (
+        MINUTES_RADIUS * ((PI + TAU - minutes_angle) - FRAC_PI_2).cos(),
+        MINUTES_RADIUS * ((PI + TAU - minutes_angle) - FRAC_PI_2).sin()
+    );
+
+    let hours_pos: (f32, f32) = (
+        HOURS_RADIUS * ((PI + TAU - hours_angle) - FRAC_PI_2).cos(),
+        HOURS_RADIUS * ((PI + TAU - hours_angle) - FRAC_PI_2).sin()
+    );
+
+    AnglesAndPositions {
+        hours_angle, minutes_angle, seconds_angle,
+        hours_pos,   minutes_pos,   seconds_pos
+    }
+}
+
+/// Where the arcs end, in whole pixels along the rings ([hours, minutes, seconds]) at `density` pixels per drawing unit.
+/// The arcs sweep on continuously, but the sweep only shows once it has moved by a pixel or so
+pub fn quantized_angles(ap: &AnglesAndPositions, density: f32) -> [u32; 3] {
+    let pixels = |angle: f32, radius: f32| (angle * radius * density).round() as u32;
+
+    [pixels(ap.hours_angle, HOURS_RADIUS), pixels(ap.minutes_angle, MINUTES_RADIUS), pixels(ap.seconds_angle, SECONDS_RADIUS)]
+}
+
+/// `stroke`/`fill` attribute pair for a packed color, the alpha goes into the matching opacity attribute.
+/// The clock's colors are linear, written to an sRGB surface, so they're encoded the same way to look alike
+fn svg_paint(property: &str, color: u32) -> String {
+    fn encode(channel: u32) -> u32 {
+        let linear = channel as f32 / 255.0;
+        let srgb = if linear <= 0.0031308 { linear * 12.92 } else { 1.055 * linear.powf(1.0 / 2.4) - 0.055 };
+        (srgb * 255.0).round() as u32
+    }
+
+    let rgb   = (encode((color >> 24) & 0xFF) << 16) | (encode((color >> 16) & 0xFF) << 8) | encode((color >> 8) & 0xFF);
+    let alpha = color & 0xFF;
+
+    if alpha == 0xFF {
+        format!("{}=\"#{:06X}\"", property, rgb)
+    } else {
+        format!("{}=\"#{:06X}\" {}-opacity=\"{:.3}\"", property, rgb, property, alpha as f32 / 255.0)
+    }
+}
+
+/// A ring's arc, starting at 12 o'clock and going clockwise, with rounded ends like on screen.
+/// SVG's y axis points down, so the drawing space's y is flipped
+fn svg_arc(svg: &mut String, radius: f32, angle: f32, color: u32) {
+    use std::f32::consts::{PI, TAU};
+
+    // an arc can't start and end at the same point, the ends of an empty or a full one are drawn separately
+    if angle <= f32::EPSILON {
+        let _ = writeln!(svg, r#"  <circle cx="0" cy="{:.4}" r="{:.4}" {}/>"#, -radius, THICKNESS * 0.5, svg_paint("fill", color));
+    } else if angle >= TAU - f32::EPSILON {
+        let _ = writeln!(
+            svg, r#"  <circle cx="0" cy="0" r="{:.4}" fill="none" stroke-width="{:.4}" {}/>"#,
+            radius, THICKNESS, svg_paint("stroke", color)
+        );
+    } else {
+        let _ = writeln!(
+            svg, r#"  <path d="M 0 {:.4} A {:.4} {:.4} 0 {} 1 {:.4} {:.4}" fill="none" stroke-width="{:.4}" stroke-linecap="round" {}/>"#,
+            -radius, radius, radius, if angle > PI { 1 } else { 0 }, radius * angle.sin(), -radius * angle.cos(),
+            THICKNESS, svg_paint("stroke", color)
+        );
+    }
+}
+
+/// The clock at the given moment as an SVG 1.1 document, showing the same area as a `width` x `height` window.
+/// The viewBox is in drawing units, so the shapes are resolution independent.
+/// Without the `background`, whatever the SVG is placed on shows through
+pub fn svg_document(ap: &AnglesAndPositions, colors: &ColorCombo, width: u32, height: u32, background: bool) -> String {
+    let dscales = DrawspaceScales::new(
+        glam::Vec2::new(width.max(1) as f32, height.max(1) as f32),
+        glam::Vec2::new(EXTENT, EXTENT)
+    );
+    // the scales map the visible half-size to 1
+    let half = dscales.scale.recip();
+
+    let mut svg = String::new();
+
+    let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        svg, r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{}" height="{}" viewBox="{:.4} {:.4} {:.4} {:.4}">"#,
+        width, height, -half.x, -half.y, half.x * 2.0, half.y * 2.0
+    );
+
+    if background {
+        let _ = writeln!(
+            svg, r#"  <rect x="{:.4}" y="{:.4}" width="{:.4}" height="{:.4}" {}/>"#,
+            -half.x, -half.y, half.x * 2.0, half.y * 2.0, svg_paint("fill", colors.background)
+        );
+    }
+
+    svg_arc(&mut svg, HOURS_RADIUS,   ap.hours_angle,   colors.hour);
+    svg_arc(&mut svg, MINUTES_RADIUS, ap.minutes_angle, colors.minute);
+    svg_arc(&mut svg, SECONDS_RADIUS, ap.seconds_angle, colors.second);
+
+    for (x, y) in [ap.hours_pos, ap.minutes_pos, ap.seconds_pos] {
+        let _ = writeln!(svg, r#"  <circle cx="{:.4}" cy="{:.4}" r="{:.4}" {}/>"#, x, -y, DISK_RADIUS, svg_paint("fill", colors.disk));
+    }
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Write `svg_document` into a file
+pub fn export_svg(
+    path:       &std::path::Path,
+    ap:         &AnglesAndPositions,
+    colors:     &ColorCombo,
+    width:      u32,
+    height:     u32,
+    background: bool
+) -> std::io::Result<()> {
+    std::fs::write(path, svg_document(ap, colors, width, height, background))
+}
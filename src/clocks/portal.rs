@@ -0,0 +1,82 @@
+//! The portal clock's sky, colored by where the sun actually is
+
+use chrono::{DateTime, Datelike, FixedOffset, Timelike};
+use crate::{lerp_u32_color, rgba32, u32_col_to_wgpu_col_linear};
+
+/// Sky colors from the sun's altitude upwards, linear RGB like `wgpu::Color`
+pub const NIGHT_SKY:  u32 = rgba32(  1,   1,   6, 255); // deep blue, nearly black
+pub const SUNSET_SKY: u32 = rgba32(255, 118,  32, 255); // orange
+pub const DAY_SKY:    u32 = rgba32( 62, 159, 211, 255); // light blue
+
+/// Altitudes (in degrees) of the gradient's three stops, below the first it's night, above the last it's day
+pub const NIGHT_ALTITUDE:  f32 = -6.0; // end of the civil twilight
+pub const SUNSET_ALTITUDE: f32 =  2.0;
+pub const DAY_ALTITUDE:    f32 = 15.0;
+
+/// The zenith looks like the horizon would with the sun this much higher
+pub const ZENITH_OFFSET: f32 = 15.0;
+
+/// Where the sun is assumed to be seen from without `--location`: the timezone's meridian, at this latitude
+pub const DEFAULT_LATITUDE: f64 = 45.0;
+
+/// The sun's altitude above the horizon in degrees, negative below it.
+/// NOAA's low precision formulas (fractional year, equation of time and declination), within a degree or so
+pub fn solar_altitude(now: &DateTime<FixedOffset>, latitude: f64, longitude: f64) -> f32 {
+    let utc = now.naive_utc();
+    let minutes = utc.hour() as f64 * 60.0 + utc.minute() as f64 + utc.second() as f64 / 60.0;
+
+    // radians
+    let year_length = if utc.date().leap_year() { 366.0 } else { 365.0 };
+    let gamma = std::f64::consts::TAU / year_length * (utc.ordinal0() as f64 + (minutes / 60.0 - 12.0) / 24.0);
+
+    // minutes
+    let equation_of_time = 229.18 * (
+        0.000075 + 0.001868 * gamma.cos() - 0.032077 * gamma.sin()
+        - 0.014615 * (2.0 * gamma).cos() - 0.040849 * (2.0 * gamma).sin()
+    );
+
+    // radians
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos() + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos() + 0.00148  * (3.0 * gamma).sin();
+
+    let true_solar_minutes = minutes + equation_of_time + 4.0 * longitude;
+    let hour_angle = (true_solar_minutes / 4.0 - 180.0).to_radians();
+
+    let latitude = latitude.to_radians();
+    let cos_zenith = latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+
+    (90.0 - cos_zenith.clamp(-1.0, 1.0).acos().to_degrees()) as f32
+}
+
+/// Longitude of the timezone's meridian, 15 degrees per hour east of UTC
+pub fn timezone_longitude(now: &DateTime<FixedOffset>) -> f64 {
+    now.offset().local_minus_utc() as f64 / 240.0
+}
+
+/// Night below the horizon, sunrise/sunset colors just above it, blue once the sun is up high. Linear, as clears take it
+pub fn sky_color_from_altitude(altitude_deg: f32) -> wgpu::Color {
+    let t = |from: f32, to: f32| ((altitude_deg - from) / (to - from)).clamp(0.0, 1.0) as f64;
+
+    let color = if altitude_deg < SUNSET_ALTITUDE {
+        lerp_u32_color(NIGHT_SKY, SUNSET_SKY, t(NIGHT_ALTITUDE, SUNSET_ALTITUDE))
+    } else {
+        lerp_u32_color(SUNSET_SKY, DAY_SKY, t(SUNSET_ALTITUDE, DAY_ALTITUDE))
+    };
+
+    u32_col_to_wgpu_col_linear(color)
+}
+
+/// Horizon and zenith colors of the sky's vertical gradient
+pub fn sky_gradient(altitude_deg: f32) -> (wgpu::Color, wgpu::Color) {
+    (sky_color_from_altitude(altitude_deg), sky_color_from_altitude(altitude_deg + ZENITH_OFFSET))
+}
+
+/// `"LAT,LON"` in degrees, north and east positive
+pub fn parse_location(value: &str) -> Option<(f64, f64)> {
+    let (latitude, longitude) = value.split_once(',')?;
+    let latitude:  f64 = latitude.trim().parse().ok()?;
+    let longitude: f64 = longitude.trim().parse().ok()?;
+
+    ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)).then_some((latitude, longitude))
+}
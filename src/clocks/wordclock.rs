@@ -0,0 +1,129 @@
+//! Letter grid and lit words of the word clock
+
+pub const ROWS:    usize = 10;
+pub const COLUMNS: usize = 11;
+
+pub const CELL_COUNT: usize = ROWS * COLUMNS;
+
+/// The letters, row by row. The words are read left to right, the letters in between fill the gaps
+pub const GRID: [&str; ROWS] = [
+    "ITLISASAMPM",
+    "ACQUARTERDC",
+    "TWENTYFIVEX",
+    "HALFSTENFTO",
+    "PASTERUNINE",
+    "ONESIXTHREE",
+    "FOURFIVETWO",
+    "EIGHTELEVEN",
+    "SEVENTWELVE",
+    "TENSEOCLOCK"
+];
+
+/// Sent to grid.wgsl via push constants (or a uniform buffer without them), the flags go into a storage buffer
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct WordParams {
+    pub highlight: u32, // the lit letters' color
+    pub dim:       u32, // the others'
+    pub timestamp: f32  // seconds within the minute, as `ClockData::timestamp`, drives the lit words' pulse
+}
+
+/// A run of letters in the grid that lights up as a whole
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Word {
+    pub row:    usize,
+    pub column: usize,
+    pub len:    usize
+}
+
+const fn word(row: usize, column: usize, len: usize) -> Word {
+    Word { row, column, len }
+}
+
+pub const IT:      Word = word(0, 0, 2);
+pub const IS:      Word = word(0, 3, 2);
+pub const AM:      Word = word(0, 7, 2);
+pub const PM:      Word = word(0, 9, 2);
+pub const A:       Word = word(1, 0, 1);
+pub const QUARTER: Word = word(1, 2, 7);
+pub const TWENTY:  Word = word(2, 0, 6);
+pub const FIVE:    Word = word(2, 6, 4);
+pub const HALF:    Word = word(3, 0, 4);
+pub const TEN:     Word = word(3, 5, 3);
+pub const TO:      Word = word(3, 9, 2);
+pub const PAST:    Word = word(4, 0, 4);
+pub const OCLOCK:  Word = word(9, 5, 6); // O'CLOCK, the apostrophe left out
+
+/// The hours' words, ONE to TWELVE
+pub const HOURS: [Word; 12] = [
+    word(5, 0, 3),  // ONE
+    word(6, 8, 3),  // TWO
+    word(5, 6, 5),  // THREE
+    word(6, 0, 4),  // FOUR
+    word(6, 4, 4),  // FIVE
+    word(5, 3, 3),  // SIX
+    word(8, 0, 5),  // SEVEN
+    word(7, 0, 5),  // EIGHT
+    word(4, 7, 4),  // NINE
+    word(9, 0, 3),  // TEN
+    word(7, 5, 6),  // ELEVEN
+    word(8, 5, 6)   // TWELVE
+];
+
+/// Index of a cell, row by row, as in the grid's buffers
+pub fn cell_index(row: usize, column: usize) -> usize {
+    row * COLUMNS + column
+}
+
+/// The grid's letters as Unicode codepoints, row by row, for the storage buffer the shader reads them from
+pub fn grid_codepoints() -> Vec<u32> {
+    GRID.iter().flat_map(|row| row.chars().map(|c| c as u32)).collect()
+}
+
+/// The words that tell the given time, in the order they're read.
+/// The minutes are rounded down to five, past the half hour they count down to the next hour: "IT IS TWENTY FIVE TO SIX PM"
+pub fn words_for(hour: u32, minute: u32) -> Vec<Word> {
+    let mut words = vec![IT, IS];
+
+    let minutes: &[Word] = match minute / 5 {
+        0      => &[],
+        1 | 11 => &[FIVE],
+        2 | 10 => &[TEN],
+        3 | 9  => &[A, QUARTER],
+        4 | 8  => &[TWENTY],
+        5 | 7  => &[TWENTY, FIVE],
+        _      => &[HALF]
+    };
+    words.extend_from_slice(minutes);
+
+    // the hour it's counting down to, past the half hour, which can be on the next day already
+    let hour = if minute >= 35 { (hour + 1) % 24 } else { hour % 24 };
+
+    match minute / 5 {
+        0     => {},
+        1..=6 => words.push(PAST),
+        _     => words.push(TO)
+    }
+
+    words.push(HOURS[((hour + 11) % 12) as usize]);
+
+    if minute < 5 {
+        words.push(OCLOCK);
+    }
+
+    words.push(if hour < 12 { AM } else { PM });
+
+    words
+}
+
+/// One flag per cell (row by row), 1 for the letters of the words that tell the given time and 0 for the rest
+pub fn lit_flags(hour: u32, minute: u32) -> [u32; CELL_COUNT] {
+    let mut flags = [0; CELL_COUNT];
+
+    for word in words_for(hour, minute) {
+        let first = cell_index(word.row, word.column);
+        flags[first..first + word.len].fill(1);
+    }
+
+    flags
+}
@@ -0,0 +1,24 @@
+// Color helpers, for shaders to `#include "color.wgsl"` (see `preprocess_shader`).
+// Colors are packed as 0xRRGGBBAA, the way palettes and image editors have them: sRGB encoded bytes, alpha linear.
+// The targets are sRGB, they encode what the shaders output, so the colors are decoded to linear here,
+// and come out as the same bytes again. Clear colors are done the same way on the CPU, see `u32_col_to_wgpu_col_linear`.
+
+fn srgb_to_linear(color: vec3f) -> vec3f {
+    let low  = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3f(2.4));
+    return select(high, low, color <= vec3f(0.04045));
+}
+
+fn color_u32_to_vec3f(value: u32) -> vec3f {
+    let r = f32((value >> 24u) & 255u);
+    let g = f32((value >> 16u) & 255u);
+    let b = f32((value >>  8u) & 255u);
+
+    return srgb_to_linear(vec3f(r, g, b) * (1.0 / 255.0));
+}
+
+fn color_u32_to_vec4f(value: u32) -> vec4f {
+    let a = f32(value & 255u);
+
+    return vec4f(color_u32_to_vec3f(value), a * (1.0 / 255.0));
+}
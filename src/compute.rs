@@ -0,0 +1,180 @@
+//! Running WGSL snippets on the GPU and reading their results back, for testing the math of the shaders.
+//!
+//! `ComputeRunner` takes a compute shader with a fixed set of bindings: the input buffers come first, as
+//! `@group(0) @binding(0)`, `@binding(1)` and so on, read-only storage buffers. The output follows them at the next binding,
+//! a read-write storage buffer of as many elements as there are invocations. The entry point is `main`, with a workgroup
+//! size of `WORKGROUP_SIZE` on x only; invocations past the output's length have to return early:
+//!
+//! ```wgsl
+//! @group(0) @binding(0) var<storage, read>       input:  array<f32>;
+//! @group(0) @binding(1) var<storage, read_write> output: array<f32>;
+//!
+//! @compute @workgroup_size(64)
+//! fn main(@builtin(global_invocation_id) id: vec3u) {
+//!     if id.x >= arrayLength(&output) { return; }
+//!     output[id.x] = input[id.x] * 2.0;
+//! }
+//! ```
+//!
+//! The source goes through `preprocess_shader`, so the shared snippets (e.g. `#include "color.wgsl"`) are tested
+//! as the clocks compile them. Everything runs headlessly, without any window or surface.
+
+use crate::preprocess_shader;
+
+/// Invocations per workgroup the shaders have to be declared with, `@workgroup_size(64)`
+pub const WORKGROUP_SIZE: u32 = 64;
+
+/// A device for dispatching test shaders, see the module's description
+pub struct ComputeRunner {
+    pub device: wgpu::Device,
+    pub queue:  wgpu::Queue
+}
+
+impl ComputeRunner {
+    /// On the default adapter, an error when there's none, or when it can't run compute shaders
+    pub fn new() -> Result<Self, String> {
+        let adapter = pollster::block_on(wgpu::Instance::default().request_adapter(&wgpu::RequestAdapterOptions::default()))
+            .ok_or("No adapter")?;
+
+        if !adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS) {
+            return Err(format!("\"{}\" can't run compute shaders", adapter.get_info().name));
+        }
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label:    Some("Compute runner"),
+                features: wgpu::Features::empty(),
+                limits:   wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits())
+            },
+            None
+        )).map_err(|error| error.to_string())?;
+
+        Ok(Self { device, queue })
+    }
+
+    /// Runs `source` once per element of the output, `output_len` elements of `O`, with the given inputs bound in order
+    /// (e.g. `cast_slice_to_u8_slice` of slices of plain values).
+    /// `O` has to match the layout of the shader's output element, e.g. `f32` for `array<f32>` or `[f32; 2]` for `array<vec2f>`.
+    /// Compilation and validation errors are returned rather than panicking
+    pub fn run<O: Copy>(self: &Self, source: &str, inputs: &[&[u8]], output_len: usize) -> Result<Vec<O>, String> {
+        let device = &self.device;
+        let output_size = (output_len * std::mem::size_of::<O>()) as u64;
+
+        let source = preprocess_shader(source)?;
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Compute runner"),
+            source: wgpu::ShaderSource::Wgsl(source.into())
+        });
+
+        let storage_entry = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty:                 wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size:   None
+            },
+            count: None
+        };
+
+        let mut layout_entries: Vec<_> = (0..inputs.len() as u32).map(|binding| storage_entry(binding, true)).collect();
+        layout_entries.push(storage_entry(inputs.len() as u32, false));
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label:   Some("Compute runner"),
+            entries: &layout_entries
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label:       Some("Compute runner"),
+            layout:      Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label:                None,
+                bind_group_layouts:   &[ &layout ],
+                push_constant_ranges: &[]
+            })),
+            module:      &module,
+            entry_point: "main"
+        });
+
+        // storage buffers can't be empty, nor sized other than in multiples of 4
+        let padded = |size: u64| size.max(4).div_ceil(4) * 4;
+
+        let input_buffers: Vec<wgpu::Buffer> = inputs.iter().map(|input| {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label:              Some("Compute input"),
+                size:               padded(input.len() as u64),
+                usage:              wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: true
+            });
+
+            buffer.slice(..).get_mapped_range_mut()[..input.len()].copy_from_slice(input);
+            buffer.unmap();
+
+            buffer
+        }).collect();
+
+        let output = device.create_buffer(&wgpu::BufferDescriptor {
+            label:              Some("Compute output"),
+            size:               padded(output_size),
+            usage:              wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false
+        });
+
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label:              Some("Compute readback"),
+            size:               padded(output_size),
+            usage:              wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false
+        });
+
+        let mut entries: Vec<_> = input_buffers.iter().enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry { binding: binding as u32, resource: buffer.as_entire_binding() })
+            .collect();
+        entries.push(wgpu::BindGroupEntry { binding: inputs.len() as u32, resource: output.as_entire_binding() });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   Some("Compute runner"),
+            layout:  &layout,
+            entries: &entries
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Compute runner") });
+
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            cpass.set_pipeline(&pipeline);
+            cpass.set_bind_group(0, &bind_group, &[]);
+            cpass.dispatch_workgroups((output_len as u32).div_ceil(WORKGROUP_SIZE).max(1), 1, 1);
+        }
+
+        encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, padded(output_size));
+
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(error.to_string());
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| { let _ = sender.send(result); });
+        device.poll(wgpu::Maintain::Wait);
+
+        receiver
+            .recv()
+            .map_err(|error| error.to_string())?
+            .map_err(|error| format!("Failed to read the output back: {}", error))?;
+
+        let results = slice.get_mapped_range()[..output_size as usize]
+            .chunks_exact(std::mem::size_of::<O>())
+            .map(|element| unsafe { std::ptr::read_unaligned(element.as_ptr() as *const O) })
+            .collect();
+
+        readback.unmap();
+
+        Ok(results)
+    }
+}
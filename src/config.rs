@@ -0,0 +1,131 @@
+//! Persistent per-clock configuration.
+//!
+//! Each clock gets its own TOML file in the platform's configuration folder,
+//! e.g. `~/.config/wgpu-clocks/digital.toml` on Linux.
+//! A missing file simply means defaults, and so does a broken one (with a warning),
+//! so a bad config can never prevent a clock from starting.
+
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+
+const CONFIG_FOLDER: &str = "wgpu-clocks";
+
+/// Path of a file in the configuration folder, `None` if the platform has no configuration folder
+pub fn file_path(file_name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|folder| folder.join(CONFIG_FOLDER).join(file_name))
+}
+
+/// Window placement, remembered between runs
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowGeometry {
+    /// Inner size in physical pixels
+    pub size:     Option<(u32, u32)>,
+    /// Outer position in physical pixels
+    pub position: Option<(i32, i32)>
+}
+
+impl WindowGeometry {
+    pub fn from_window(window: &winit::window::Window) -> Self {
+        let size     = window.inner_size();
+        let position = window.outer_position().ok();
+
+        Self {
+            size:     Some((size.width, size.height)),
+            position: position.map(|p| (p.x, p.y))
+        }
+    }
+
+    /// Move and resize a window that's already open back to the remembered placement, e.g. after leaving fullscreen
+    pub fn restore(self: &Self, window: &winit::window::Window) {
+        if let Some((width, height)) = self.size {
+            let _ = window.request_inner_size(winit::dpi::PhysicalSize { width, height });
+        }
+
+        if let Some((x, y)) = self.position {
+            window.set_outer_position(winit::dpi::PhysicalPosition { x, y });
+        }
+    }
+
+    /// Apply the remembered placement to the binary's default window attributes
+    pub fn apply_to_window(self: &Self, mut builder: winit::window::WindowBuilder) -> winit::window::WindowBuilder {
+        if let Some((width, height)) = self.size {
+            builder = builder.with_inner_size(winit::dpi::PhysicalSize { width, height });
+        }
+
+        if let Some((x, y)) = self.position {
+            builder = builder.with_position(winit::dpi::PhysicalPosition { x, y });
+        }
+
+        builder
+    }
+}
+
+/// A clock's configuration file.
+/// `T` is the clock's own typed settings struct,
+/// anything that doesn't deserve a typed field can go to the free-form `extras` table.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, bound(deserialize = "T: DeserializeOwned + Default"))]
+pub struct ClockConfig<T> {
+    pub window:   WindowGeometry,
+    pub settings: T,
+    pub extras:   toml::Table,
+
+    #[serde(skip)]
+    path: Option<PathBuf>
+}
+
+impl<T: Serialize + DeserializeOwned + Default> ClockConfig<T> {
+    /// Path of the configuration file of the given clock, `None` if the platform has no configuration folder
+    pub fn path_for(name: &str) -> Option<PathBuf> {
+        file_path(&format!("{}.toml", name))
+    }
+
+    /// Load the configuration of the given clock, never fails
+    pub fn load(name: &str) -> Self {
+        match Self::path_for(name) {
+            Some(path) => Self::load_from(path),
+            None => {
+                log::debug!("No configuration folder on this platform, using defaults");
+                Self::default()
+            }
+        }
+    }
+
+    /// Load the configuration from the given file, which `save` writes back to. Never fails either
+    pub fn load_from(path: PathBuf) -> Self {
+        let mut config = match std::fs::read_to_string(&path) {
+            Err(_) => {
+                log::debug!("No config file at {}, using defaults", path.display());
+                Self::default()
+            },
+            Ok(text) => toml::from_str::<Self>(&text).unwrap_or_else(|error| {
+                log::warn!("Ignoring malformed config file {}: {}", path.display(), error);
+                Self::default()
+            })
+        };
+
+        config.path = Some(path);
+        config
+    }
+
+    /// Write the configuration back to where it was loaded from
+    pub fn save(self: &Self) -> std::io::Result<()> {
+        use std::io::{Error, ErrorKind};
+
+        let path = self.path.as_ref().ok_or_else(|| Error::new(
+            ErrorKind::NotFound,
+            "No configuration folder available on this platform"
+        ))?;
+
+        if let Some(folder) = path.parent() {
+            std::fs::create_dir_all(folder)?;
+        }
+
+        let text = toml::to_string_pretty(self).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+        std::fs::write(path, text)?;
+
+        log::debug!("Saved the configuration to {}", path.display());
+        return Ok(());
+    }
+}
@@ -0,0 +1,46 @@
+// A CRT's look: dark gaps between the scanlines, the phosphors' glow lingering into the lines above and below,
+// and the red and blue guns slightly off. Drawn with `FullscreenQuadPipeline`, the UVs come from its vertex shader.
+
+struct CrtParams {
+    scanline_spacing:   f32, // from one scanline to the next, in pixels
+    scanline_intensity: f32, // how dark the gaps between them are, 0 for none, 1 for black
+    blur_amount:        f32, // how far the glow reaches up and down, in pixels
+    rgb_shift:          f32  // how far red and blue are off to either side, in pixels
+}
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>;
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(0) @binding(2)
+var<uniform> params: CrtParams;
+
+const PI: f32 = 3.14159265359;
+
+// red to the right, blue to the left, green where it is
+fn sample_separated(uv: vec2f, shift: vec2f) -> vec4f {
+    let center = textureSample(src_tex_2d, tex_sampler, uv);
+    let red    = textureSample(src_tex_2d, tex_sampler, uv - shift).r;
+    let blue   = textureSample(src_tex_2d, tex_sampler, uv + shift).b;
+
+    return vec4f(red, center.g, blue, center.a);
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    let size  = vec2f(textureDimensions(src_tex_2d));
+    let shift = vec2f(params.rgb_shift / size.x, 0.0);
+    let reach = vec2f(0.0, params.blur_amount / size.y);
+
+    let color = sample_separated(uv, shift) * 0.5
+        + sample_separated(uv - reach, shift) * 0.25
+        + sample_separated(uv + reach, shift) * 0.25;
+
+    // bright in the middle of each scanline, darkest halfway to the next
+    let line = fract(uv.y * size.y / max(params.scanline_spacing, 1.0));
+    let gap  = params.scanline_intensity * pow(sin(line * PI), 2.0);
+
+    return vec4f(color.rgb * (1.0 - gap), color.a);
+}
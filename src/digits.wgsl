@@ -0,0 +1,7 @@
+// Digit sprite sheets, for shaders to `#include "digits.wgsl"` (see `preprocess_shader`).
+// The sheets have the ten digits side by side, 0 to 9 from the left, each cell a tenth of the width and all of the height.
+
+// Where `uv` within a digit's cell (0..1 on both axes) is on the whole sheet
+fn digit_cell_uv(uv: vec2f, digit: u32) -> vec2f {
+    return vec2f((uv.x + f32(digit)) * 0.1, uv.y);
+}
@@ -0,0 +1,252 @@
+//! Text panels drawn with a 5x7 bitmap font.
+//!
+//! `BitmapFontRenderer` draws a grid of printable ASCII on top of an already rendered frame, in a pass of its own,
+//! so it can be used by the runner (the stats overlay) and by the clocks alike (the keyboard shortcut help).
+//! The font is baked into font.wgsl and the text goes into a fixed-size uniform, so nothing is allocated per frame.
+
+use std::fmt::Write;
+use crate::{cast_struct_to_u8_slice, target::TargetConfig, SingleUniformBuffer};
+
+pub const MAX_COLUMNS: usize = 64;
+pub const MAX_LINES:   usize = 16;
+
+/// Font pixels per character cell, mirrors `CELL` in font.wgsl
+const CELL:   (u32, u32) = (6, 9);
+/// Font pixels between the panel and the edges of the target, when anchored to a corner
+const MARGIN: u32 = 2;
+
+/// Where the panel is placed in the target
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextAnchor {
+    TopLeft,
+    Center,
+    BottomRight
+}
+
+/// Mirrors `TextInfo` in font.wgsl
+#[repr(C, align(16))]
+struct TextInfo {
+    resolution: glam::Vec2,
+    origin:     glam::Vec2,
+    scale:      f32,
+    columns:    u32,
+    lines:      u32,
+    backdrop:   f32,
+    glyphs:     [u8; MAX_COLUMNS * MAX_LINES] // read as packed u32s by the shader
+}
+
+/// Writes text into one line of the grid, whatever doesn't fit is cut off
+pub struct LineWriter<'a> {
+    cells: &'a mut [u8],
+    len:   usize
+}
+
+impl Write for LineWriter<'_> {
+    fn write_str(self: &mut Self, text: &str) -> std::fmt::Result {
+        for c in text.chars() {
+            if self.len == self.cells.len() {
+                break;
+            }
+
+            // the font has printable ASCII only, anything else shows up as a space
+            self.cells[self.len] = if c.is_ascii_graphic() { c as u8 } else { b' ' };
+            self.len += 1;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct BitmapFontRenderer {
+    pipeline:   wgpu::RenderPipeline,
+    uniform:    SingleUniformBuffer,
+    bind_group: wgpu::BindGroup,
+    anchor:     TextAnchor,
+    info:       TextInfo
+}
+
+impl BitmapFontRenderer {
+    pub fn new(format: wgpu::TextureFormat, anchor: TextAnchor, device: &wgpu::Device) -> Self {
+        Self::for_target(&TargetConfig::new(format, None), anchor, device)
+    }
+
+    /// For multisampled targets, e.g. the runner's frame (`TargetConfig::frame`). The depth format is ignored
+    pub fn for_target(config: &TargetConfig, anchor: TextAnchor, device: &wgpu::Device) -> Self {
+        let uniform = SingleUniformBuffer::new::<TextInfo>(device, wgpu::ShaderStages::VERTEX_FRAGMENT);
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bitmap text"),
+            entries: &[ SingleUniformBuffer::default_layout_entry(0, &uniform) ]
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   Some("Bitmap text"),
+            layout:  &bind_group_layout,
+            entries: &[ uniform.get_entry(0) ]
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                Some("Bitmap text"),
+            bind_group_layouts:   &[ &bind_group_layout ],
+            push_constant_ranges: &[]
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Bitmap text"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("font.wgsl").into())
+        });
+
+        // The alpha is blended too, so that the panel stays visible on transparent windows
+        let color_target_state = wgpu::ColorTargetState {
+            format: config.color_format,
+            blend:  Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation:  wgpu::BlendOperation::Add
+                },
+                alpha: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation:  wgpu::BlendOperation::Add
+                }
+            }),
+            write_mask: wgpu::ColorWrites::ALL
+        };
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label:         Some("Bitmap text"),
+            layout:        Some(&pipeline_layout),
+            depth_stencil: None,
+            multisample:   config.multisample_state(),
+            multiview:     None,
+            vertex: wgpu::VertexState {
+                module:      &shader,
+                entry_point: "vs_main",
+                buffers:     &[]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module:      &shader,
+                entry_point: "fs_main",
+                targets:     &[ Some(color_target_state) ]
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology:     wgpu::PrimitiveTopology::TriangleStrip,
+                cull_mode:    None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..Default::default()
+            }
+        });
+
+        let info = TextInfo {
+            resolution: glam::Vec2::ONE,
+            origin:     glam::Vec2::ZERO,
+            scale:      1.0,
+            columns:    1,
+            lines:      0,
+            backdrop:   0.0,
+            glyphs:     [0; MAX_COLUMNS * MAX_LINES]
+        };
+
+        Self { pipeline, uniform, bind_group, anchor, info }
+    }
+
+    pub fn resize(self: &mut Self, width: u32, height: u32) {
+        self.info.resolution = glam::Vec2::new(width as f32, height as f32);
+    }
+
+    /// Dim everything behind the panel, 0 (the default) to leave it as it is
+    pub fn set_backdrop(self: &mut Self, opacity: f32) {
+        self.info.backdrop = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Empty the grid and make it `columns` wide, for filling it line by line with `push_line`
+    pub fn clear(self: &mut Self, columns: usize) {
+        self.info.glyphs.fill(0);
+        self.info.columns = columns.clamp(1, MAX_COLUMNS) as u32;
+        self.info.lines   = 0;
+    }
+
+    /// The next line of the grid, `None` once all `MAX_LINES` are used
+    pub fn push_line(self: &mut Self) -> Option<LineWriter<'_>> {
+        let (columns, line) = (self.info.columns as usize, self.info.lines as usize);
+
+        if (line + 1) * columns > self.info.glyphs.len() {
+            return None;
+        }
+
+        self.info.lines += 1;
+
+        Some(LineWriter { cells: &mut self.info.glyphs[line * columns..(line + 1) * columns], len: 0 })
+    }
+
+    /// Replace the text, the grid is made as wide as the longest line
+    pub fn set_lines<S: AsRef<str>>(self: &mut Self, lines: &[S]) {
+        let columns = lines.iter().map(|line| line.as_ref().chars().count()).max().unwrap_or(0);
+        self.clear(columns);
+
+        for text in lines {
+            match self.push_line() {
+                Some(mut line) => { let _ = line.write_str(text.as_ref()); },
+                None => break
+            }
+        }
+    }
+
+    /// Whole screen pixels per font pixel keep the glyphs crisp. Grows with the height,
+    /// but a centered panel is kept narrower than the target
+    fn layout(self: &mut Self) {
+        let (width, height) = (self.info.resolution.x as u32, self.info.resolution.y as u32);
+        let panel = (self.info.columns * CELL.0, self.info.lines * CELL.1);
+
+        let mut scale = (height / 240).max(1);
+
+        let origin = match self.anchor {
+            TextAnchor::TopLeft => glam::Vec2::splat((MARGIN * scale) as f32),
+            TextAnchor::Center  => {
+                scale = scale.min(width / panel.0.max(1)).max(1);
+                let offset = |space: u32, size: u32| (space.saturating_sub(size * scale) / 2) as f32;
+                glam::Vec2::new(offset(width, panel.0), offset(height, panel.1))
+            },
+            TextAnchor::BottomRight => {
+                let offset = |space: u32, size: u32| space.saturating_sub((size + MARGIN) * scale) as f32;
+                glam::Vec2::new(offset(width, panel.0), offset(height, panel.1))
+            }
+        };
+
+        self.info.scale  = scale as f32;
+        self.info.origin = origin;
+    }
+
+    /// Draw on top of the already rendered frame
+    pub fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.layout();
+        queue.write_buffer(&self.uniform.buffer, 0, cast_struct_to_u8_slice(&self.info));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Bitmap text") });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    Some("Bitmap text"),
+                depth_stencil_attachment: None,
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                    view: texview,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load:  wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store
+                    }
+                })]
+            });
+
+            // instance 0 is the backdrop (collapsed by the shader when there's none), then one quad per character cell
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_bind_group(0, &self.bind_group, &[]);
+            rpass.draw(0..4, 0..(1 + self.info.lines * self.info.columns));
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
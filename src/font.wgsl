@@ -0,0 +1,195 @@
+// Bitmap text panel, drawn on top of whatever is already in the target.
+// Every character cell is an instanced quad, the glyphs come from the 5x7 bitmap font below.
+// Instance 0 is the optional backdrop, a quad over the whole target that dims what's behind the panel.
+
+struct TextInfo {
+    resolution: vec2f,
+    origin:     vec2f, // top-left corner of the panel, in screen pixels
+    scale:      f32,   // size of a font pixel in screen pixels
+    columns:    u32,
+    lines:      u32,
+    backdrop:   f32,   // opacity of the backdrop
+    glyphs:     array<vec4u, 64> // character codes, 4 per u32, 16 per element (uniform arrays have a 16 byte stride)
+}
+
+struct VertexOutput {
+    @builtin(position)              pos:   vec4f,
+    @location(0)                    local: vec2f, // position within the cell, in font pixels
+    @location(1) @interpolate(flat) glyph: u32
+}
+
+@group(0) @binding(0)
+var<uniform> info: TextInfo;
+
+const CELL:     vec2f = vec2f(6.0, 9.0); // 5x7 glyph + spacing
+const BACKDROP: u32   = 0xFFFFFFFFu;
+
+const TEXT_COLOR:       vec4f = vec4f(1.0, 1.0, 1.0, 1.0);
+const BACKGROUND_COLOR: vec4f = vec4f(0.0, 0.0, 0.0, 0.6);
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index)   VertexIndex  : u32,
+    @builtin(instance_index) InstanceIndex: u32
+) -> VertexOutput {
+    var corners = array<vec2f, 4>(
+        vec2f(0.0, 0.0),
+        vec2f(1.0, 0.0),
+        vec2f(0.0, 1.0),
+        vec2f(1.0, 1.0)
+    );
+
+    var vto: VertexOutput;
+
+    if InstanceIndex == 0u {
+        // collapsed to nothing without a backdrop, it's always drawn as GL can't start from another instance everywhere
+        let corner = select(corners[VertexIndex], vec2f(0.0), info.backdrop <= 0.0);
+        vto.pos   = vec4f(corner * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+        vto.local = vec2f(0.0);
+        vto.glyph = BACKDROP;
+        return vto;
+    }
+
+    let index  = InstanceIndex - 1u;
+    let cell   = vec2f(f32(index % info.columns), f32(index / info.columns));
+    let local  = corners[VertexIndex] * CELL;
+    let pixels = info.origin + (cell * CELL + local) * info.scale;
+
+    // screen pixels (y down) => clip space (y up)
+    let ndc = vec2f(pixels.x / info.resolution.x * 2.0 - 1.0, 1.0 - pixels.y / info.resolution.y * 2.0);
+
+    let word   = info.glyphs[index / 16u][(index / 4u) % 4u];
+
+    vto.pos   = vec4f(ndc, 0.0, 1.0);
+    vto.local = local;
+    vto.glyph = (word >> ((index % 4u) * 8u)) & 0xFFu;
+
+    return vto;
+}
+
+@fragment
+fn fs_main(vto: VertexOutput) -> @location(0) vec4f {
+    if vto.glyph == BACKDROP {
+        return vec4f(0.0, 0.0, 0.0, info.backdrop);
+    }
+
+    // Printable ASCII, starting from the space. Each glyph is 7 rows of 5 bits, most significant bit on the left.
+    // x holds rows 0..3, y holds rows 4..6
+    var font = array<vec2u, 95>(
+        vec2u(0x00000u, 0x0000u), // ' '
+        vec2u(0x21084u, 0x1004u), // '!'
+        vec2u(0x52940u, 0x0000u), // '"'
+        vec2u(0x52BEAu, 0x7D4Au), // '#'
+        vec2u(0x23E8Eu, 0x17C4u), // '$'
+        vec2u(0xC6444u, 0x2263u), // '%'
+        vec2u(0x64A88u, 0x564Du), // '&'
+        vec2u(0x21100u, 0x0000u), // '''
+        vec2u(0x11108u, 0x2082u), // '('
+        vec2u(0x41042u, 0x0888u), // ')'
+        vec2u(0x012AEu, 0x5480u), // '*'
+        vec2u(0x0109Fu, 0x1080u), // '+'
+        vec2u(0x00000u, 0x3088u), // ','
+        vec2u(0x0001Fu, 0x0000u), // '-'
+        vec2u(0x00000u, 0x018Cu), // '.'
+        vec2u(0x00444u, 0x2200u), // '/'
+        vec2u(0x74675u, 0x662Eu), // '0'
+        vec2u(0x23084u, 0x108Eu), // '1'
+        vec2u(0x74422u, 0x111Fu), // '2'
+        vec2u(0xF8882u, 0x062Eu), // '3'
+        vec2u(0x11952u, 0x7C42u), // '4'
+        vec2u(0xFC3C1u, 0x062Eu), // '5'
+        vec2u(0x3221Eu, 0x462Eu), // '6'
+        vec2u(0xF8444u, 0x2108u), // '7'
+        vec2u(0x7462Eu, 0x462Eu), // '8'
+        vec2u(0x7462Fu, 0x044Cu), // '9'
+        vec2u(0x03180u, 0x3180u), // ':'
+        vec2u(0x03180u, 0x3088u), // ';'
+        vec2u(0x11110u, 0x2082u), // '<'
+        vec2u(0x003E0u, 0x7C00u), // '='
+        vec2u(0x41041u, 0x0888u), // '>'
+        vec2u(0x74422u, 0x1004u), // '?'
+        vec2u(0x7442Du, 0x56AEu), // '@'
+        vec2u(0x7463Fu, 0x4631u), // 'A'
+        vec2u(0xF463Eu, 0x463Eu), // 'B'
+        vec2u(0x74610u, 0x422Eu), // 'C'
+        vec2u(0xE4A31u, 0x465Cu), // 'D'
+        vec2u(0xFC21Eu, 0x421Fu), // 'E'
+        vec2u(0xFC21Eu, 0x4210u), // 'F'
+        vec2u(0x74617u, 0x462Fu), // 'G'
+        vec2u(0x8C63Fu, 0x4631u), // 'H'
+        vec2u(0x71084u, 0x108Eu), // 'I'
+        vec2u(0x38842u, 0x0A4Cu), // 'J'
+        vec2u(0x8CA98u, 0x5251u), // 'K'
+        vec2u(0x84210u, 0x421Fu), // 'L'
+        vec2u(0x8EEB5u, 0x4631u), // 'M'
+        vec2u(0x8C735u, 0x4E31u), // 'N'
+        vec2u(0x74631u, 0x462Eu), // 'O'
+        vec2u(0xF463Eu, 0x4210u), // 'P'
+        vec2u(0x74631u, 0x564Du), // 'Q'
+        vec2u(0xF463Eu, 0x5251u), // 'R'
+        vec2u(0x7C20Eu, 0x043Eu), // 'S'
+        vec2u(0xF9084u, 0x1084u), // 'T'
+        vec2u(0x8C631u, 0x462Eu), // 'U'
+        vec2u(0x8C631u, 0x4544u), // 'V'
+        vec2u(0x8C635u, 0x56AAu), // 'W'
+        vec2u(0x8C544u, 0x2A31u), // 'X'
+        vec2u(0x8C62Au, 0x1084u), // 'Y'
+        vec2u(0xF8444u, 0x221Fu), // 'Z'
+        vec2u(0x72108u, 0x210Eu), // '['
+        vec2u(0x04104u, 0x0820u), // '\\'
+        vec2u(0x70842u, 0x084Eu), // ']'
+        vec2u(0x22A20u, 0x0000u), // '^'
+        vec2u(0x00000u, 0x001Fu), // '_'
+        vec2u(0x41040u, 0x0000u), // '`'
+        vec2u(0x001C1u, 0x3E2Fu), // 'a'
+        vec2u(0x842D9u, 0x463Eu), // 'b'
+        vec2u(0x001D0u, 0x422Eu), // 'c'
+        vec2u(0x085B3u, 0x462Fu), // 'd'
+        vec2u(0x001D1u, 0x7E0Eu), // 'e'
+        vec2u(0x3251Cu, 0x2108u), // 'f'
+        vec2u(0x03E31u, 0x3C2Eu), // 'g'
+        vec2u(0x842D9u, 0x4631u), // 'h'
+        vec2u(0x20184u, 0x108Eu), // 'i'
+        vec2u(0x100C2u, 0x0A4Cu), // 'j'
+        vec2u(0x84254u, 0x6292u), // 'k'
+        vec2u(0x61084u, 0x108Eu), // 'l'
+        vec2u(0x00355u, 0x5631u), // 'm'
+        vec2u(0x002D9u, 0x4631u), // 'n'
+        vec2u(0x001D1u, 0x462Eu), // 'o'
+        vec2u(0x003D1u, 0x7A10u), // 'p'
+        vec2u(0x001B3u, 0x3C21u), // 'q'
+        vec2u(0x002D9u, 0x4210u), // 'r'
+        vec2u(0x001D0u, 0x383Eu), // 's'
+        vec2u(0x42388u, 0x2126u), // 't'
+        vec2u(0x00231u, 0x466Du), // 'u'
+        vec2u(0x00231u, 0x4544u), // 'v'
+        vec2u(0x00231u, 0x56AAu), // 'w'
+        vec2u(0x0022Au, 0x1151u), // 'x'
+        vec2u(0x00231u, 0x3C2Eu), // 'y'
+        vec2u(0x003E2u, 0x111Fu), // 'z'
+        vec2u(0x11088u, 0x1082u), // '{'
+        vec2u(0x21084u, 0x1084u), // '|'
+        vec2u(0x41082u, 0x1088u), // '}'
+        vec2u(0x00115u, 0x0800u)  // '~'
+    );
+
+    let p = vec2u(floor(vto.local));
+
+    if p.x >= 5u || p.y >= 7u || vto.glyph < 32u || vto.glyph > 126u {
+        return BACKGROUND_COLOR;
+    }
+
+    let bitmap = font[vto.glyph - 32u];
+    var row: u32;
+    if p.y < 4u {
+        row = bitmap.x >> ((3u - p.y) * 5u);
+    } else {
+        row = bitmap.y >> ((6u - p.y) * 5u);
+    }
+
+    if ((row >> (4u - p.x)) & 1u) != 0u {
+        return TEXT_COLOR;
+    }
+
+    return BACKGROUND_COLOR;
+}
@@ -0,0 +1,177 @@
+// FXAA 3.11 (Timothy Lottes), the quality variant with the 39 preset's search steps, ported to WGSL.
+// Drawn with `FullscreenQuadPipeline`, the UVs come from its vertex shader.
+// Everything is sampled at level 0, the edge search samples within non-uniform control flow.
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>;
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+// fraction of the sub-pixel aliasing removed, 0.75 is the default, 1.0 is softer
+const SUBPIX: f32 = 0.75;
+// minimum local contrast to be processed, relative to the brightest neighbor
+const EDGE_THRESHOLD: f32 = 0.166;
+// minimum contrast to be processed in the dark, in luma
+const EDGE_THRESHOLD_MIN: f32 = 0.0833;
+
+const SEARCH_STEPS: u32 = 12u;
+
+// distance of each step along the edge, in pixels
+fn search_step(index: u32) -> f32 {
+    switch index {
+        case 0u, 1u, 2u, 3u, 4u: { return 1.0; }
+        case 5u:                 { return 1.5; }
+        case 10u:                { return 4.0; }
+        case 11u:                { return 8.0; }
+        default:                 { return 2.0; }
+    }
+}
+
+// perceptual luma, the texture holds linear colors
+fn luma(color: vec4f) -> f32 {
+    return sqrt(dot(color.rgb, vec3f(0.299, 0.587, 0.114)));
+}
+
+// size of a pixel in UV space, 1 / resolution
+fn rcp_frame() -> vec2f {
+    return 1.0 / vec2f(textureDimensions(src_tex_2d, 0));
+}
+
+fn sample_color(uv: vec2f) -> vec4f {
+    return textureSampleLevel(src_tex_2d, tex_sampler, uv, 0.0);
+}
+
+fn sample_luma(uv: vec2f) -> f32 {
+    return luma(sample_color(uv));
+}
+
+fn sample_luma_offset(uv: vec2f, x: f32, y: f32) -> f32 {
+    return luma(sample_color(uv + vec2f(x, y) * rcp_frame()));
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    let rcp_frame = rcp_frame();
+    var pos_m = uv;
+
+    let color_m = sample_color(pos_m);
+    let luma_m  = luma(color_m);
+
+    var luma_s = sample_luma_offset(pos_m,  0.0,  1.0);
+    let luma_e = sample_luma_offset(pos_m,  1.0,  0.0);
+    var luma_n = sample_luma_offset(pos_m,  0.0, -1.0);
+    let luma_w = sample_luma_offset(pos_m, -1.0,  0.0);
+
+    // early exit where the local contrast is too low to be an edge
+    let range_max = max(max(luma_n, luma_w), max(max(luma_e, luma_s), luma_m));
+    let range_min = min(min(luma_n, luma_w), min(min(luma_e, luma_s), luma_m));
+    let range = range_max - range_min;
+
+    if range < max(EDGE_THRESHOLD_MIN, range_max * EDGE_THRESHOLD) {
+        return color_m;
+    }
+
+    let luma_nw = sample_luma_offset(pos_m, -1.0, -1.0);
+    let luma_se = sample_luma_offset(pos_m,  1.0,  1.0);
+    let luma_ne = sample_luma_offset(pos_m,  1.0, -1.0);
+    let luma_sw = sample_luma_offset(pos_m, -1.0,  1.0);
+
+    // horizontal or vertical edge
+    let luma_ns = luma_n + luma_s;
+    let luma_we = luma_w + luma_e;
+    let luma_nese = luma_ne + luma_se;
+    let luma_nwne = luma_nw + luma_ne;
+    let luma_nwsw = luma_nw + luma_sw;
+    let luma_swse = luma_sw + luma_se;
+
+    let edge_horz = abs(-2.0 * luma_w + luma_nwsw) + abs(-2.0 * luma_m + luma_ns) * 2.0 + abs(-2.0 * luma_e + luma_nese);
+    let edge_vert = abs(-2.0 * luma_s + luma_swse) + abs(-2.0 * luma_m + luma_we) * 2.0 + abs(-2.0 * luma_n + luma_nwne);
+    let horz_span = edge_horz >= edge_vert;
+
+    // sub-pixel aliasing amount, from the average of the neighborhood
+    let subpix_a = (luma_ns + luma_we) * 2.0 + luma_nwsw + luma_nese;
+    let subpix_b = subpix_a / 12.0 - luma_m;
+    let subpix_c = clamp(abs(subpix_b) / range, 0.0, 1.0);
+    let subpix_d = -2.0 * subpix_c + 3.0;
+    let subpix_f = subpix_d * subpix_c * subpix_c;
+
+    // the neighbors across the edge, and on which side the steeper gradient is
+    if !horz_span {
+        luma_n = luma_w;
+        luma_s = luma_e;
+    }
+
+    var length_sign = select(rcp_frame.x, rcp_frame.y, horz_span);
+
+    let gradient_n = luma_n - luma_m;
+    let gradient_s = luma_s - luma_m;
+    let pair_n = abs(gradient_n) >= abs(gradient_s);
+    let gradient = max(abs(gradient_n), abs(gradient_s));
+
+    if pair_n {
+        length_sign = -length_sign;
+    }
+
+    let luma_nn = select(luma_s + luma_m, luma_n + luma_m, pair_n);
+
+    // search both ways along the edge, from half a pixel towards the steeper side
+    var pos_b = pos_m;
+    if horz_span {
+        pos_b.y += length_sign * 0.5;
+    } else {
+        pos_b.x += length_sign * 0.5;
+    }
+
+    let off_np = select(vec2f(0.0, rcp_frame.y), vec2f(rcp_frame.x, 0.0), horz_span);
+
+    var pos_n = pos_b - off_np * search_step(0u);
+    var pos_p = pos_b + off_np * search_step(0u);
+
+    let gradient_scaled = gradient * 0.25;
+    let luma_mm = luma_m - luma_nn * 0.5;
+    let luma_m_lt_zero = luma_mm < 0.0;
+
+    var luma_end_n = sample_luma(pos_n) - luma_nn * 0.5;
+    var luma_end_p = sample_luma(pos_p) - luma_nn * 0.5;
+    var done_n = abs(luma_end_n) >= gradient_scaled;
+    var done_p = abs(luma_end_p) >= gradient_scaled;
+
+    for (var step = 1u; step < SEARCH_STEPS && !(done_n && done_p); step++) {
+        if !done_n {
+            pos_n -= off_np * search_step(step);
+            luma_end_n = sample_luma(pos_n) - luma_nn * 0.5;
+            done_n = abs(luma_end_n) >= gradient_scaled;
+        }
+        if !done_p {
+            pos_p += off_np * search_step(step);
+            luma_end_p = sample_luma(pos_p) - luma_nn * 0.5;
+            done_p = abs(luma_end_p) >= gradient_scaled;
+        }
+    }
+
+    // the closer end of the edge decides how far the pixel is shifted across it
+    var dst_n = pos_m.x - pos_n.x;
+    var dst_p = pos_p.x - pos_m.x;
+    if !horz_span {
+        dst_n = pos_m.y - pos_n.y;
+        dst_p = pos_p.y - pos_m.y;
+    }
+
+    let good_span_n = (luma_end_n < 0.0) != luma_m_lt_zero;
+    let good_span_p = (luma_end_p < 0.0) != luma_m_lt_zero;
+    let direction_n = dst_n < dst_p;
+    let good_span = select(good_span_p, good_span_n, direction_n);
+
+    let pixel_offset = -min(dst_n, dst_p) / (dst_n + dst_p) + 0.5;
+    let pixel_offset_good = select(0.0, pixel_offset, good_span);
+    let pixel_offset_subpix = max(pixel_offset_good, subpix_f * subpix_f * SUBPIX);
+
+    if horz_span {
+        pos_m.y += pixel_offset_subpix * length_sign;
+    } else {
+        pos_m.x += pixel_offset_subpix * length_sign;
+    }
+
+    return vec4f(sample_color(pos_m).rgb, color_m.a);
+}
@@ -0,0 +1,62 @@
+// God rays (volumetric light scattering as a post-process, GPU Gems 3, chapter 13).
+// Drawn with `FullscreenQuadPipeline`, the UVs come from its vertex shader.
+// Every pixel samples the frame on its way towards the sun, the bright samples around the sun (the sun itself,
+// the glowing sky) stream out of it as rays, fading with each step. Dark scenery in between blocks them, as it samples dark.
+// There's no occlusion buffer, so bright scenery far from the sun is kept from shedding rays by the distance alone.
+// With this few samples, the steps are far apart away from the sun. They start at a different fraction of a step
+// for each pixel, so that they add up to a grain rather than to copies of the sun.
+
+struct GodRaysParams {
+    sun_pos:   vec2f, // the sun's position in UV space, may be off the screen
+    intensity: f32,   // brightness of the rays, 0 for none
+    decay:     f32,   // falloff of each step away from the pixel, within (0..1]
+    density:   f32    // how far towards the sun the samples reach, 1 is all the way
+}
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>;
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+var<push_constant> params: GodRaysParams;
+
+const NUM_SAMPLES: u32 = 16u;
+
+// only what's brighter than this sheds rays, in linear luminance, mostly the sun itself
+const THRESHOLD: f32 = 0.8;
+
+// how far from the sun the bright parts shed rays, in UV space
+const SUN_REACH: f32 = 0.15;
+
+// interleaved gradient noise (Jorge Jimenez), a fraction per pixel
+fn dither(pixel: vec2f) -> f32 {
+    return fract(52.9829189 * fract(dot(pixel, vec2f(0.06711056, 0.00583715))));
+}
+
+fn luminance(color: vec3f) -> f32 {
+    return dot(color, vec3f(0.2126, 0.7152, 0.0722));
+}
+
+@fragment
+fn fs_main(@builtin(position) pixel: vec4f, @location(0) uv: vec2f) -> @location(0) vec4f {
+    let color = textureSampleLevel(src_tex_2d, tex_sampler, uv, 0.0);
+    let step  = (uv - params.sun_pos) * params.density / f32(NUM_SAMPLES);
+
+    var coord  = uv + step * dither(pixel.xy);
+    var weight = 1.0;
+    var rays   = vec3f(0.0);
+
+    for (var i = 0u; i < NUM_SAMPLES; i++) {
+        coord -= step;
+
+        let sample = textureSampleLevel(src_tex_2d, tex_sampler, coord, 0.0).rgb;
+        let bright = smoothstep(THRESHOLD, 1.0, luminance(sample));
+        let near   = 1.0 - smoothstep(0.0, SUN_REACH, distance(coord, params.sun_pos));
+
+        rays   += sample * bright * near * weight;
+        weight *= params.decay;
+    }
+
+    return vec4f(color.rgb + rays * params.intensity / f32(NUM_SAMPLES), color.a);
+}
@@ -0,0 +1,144 @@
+//! What the graphics adapters on this machine can do, for telling why a clock doesn't start.
+//!
+//! `--gpu-info` prints the report and exits, and the runner logs it at debug level on every start
+//! (so it ends up in the log file of Windows GUI builds with `RUST_LOG=clockutils=debug`).
+
+use std::fmt::Write;
+use crate::{SURFACE_FORMATS, PUSH_CONSTANT_SIZE, STORAGE_BUFFERS_PER_STAGE, STORAGE_BUFFER_BINDING_SIZE};
+
+/// What keeps the clocks from running on an adapter with the given features and limits, empty if nothing.
+/// `surface_formats` are the formats of the window's surface on the adapter, `None` if there's no surface to tell
+pub fn adapter_issues(
+    features:        wgpu::Features,
+    limits:          &wgpu::Limits,
+    surface_formats: Option<&[wgpu::TextureFormat]>
+) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    if !features.contains(wgpu::Features::PUSH_CONSTANTS) {
+        issues.push("No push constants, only the polar clock can run (they're a Vulkan, Metal and DirectX 12 feature)".to_string());
+    } else if limits.max_push_constant_size < PUSH_CONSTANT_SIZE {
+        issues.push(format!("Push constants are limited to {} bytes, {} are needed", limits.max_push_constant_size, PUSH_CONSTANT_SIZE));
+    }
+
+    if limits.max_storage_buffers_per_shader_stage < STORAGE_BUFFERS_PER_STAGE {
+        issues.push(format!(
+            "Only {} storage buffers per shader stage, {} are needed",
+            limits.max_storage_buffers_per_shader_stage, STORAGE_BUFFERS_PER_STAGE
+        ));
+    }
+
+    if limits.max_storage_buffer_binding_size < STORAGE_BUFFER_BINDING_SIZE {
+        issues.push(format!(
+            "Storage buffer bindings are limited to {} bytes, {} are needed",
+            limits.max_storage_buffer_binding_size, STORAGE_BUFFER_BINDING_SIZE
+        ));
+    }
+
+    match surface_formats {
+        Some([]) => issues.push("Can't present to the window's surface".to_string()),
+        Some(formats) if !SURFACE_FORMATS.iter().any(|format| formats.contains(format)) => {
+            issues.push(format!("The surface lacks all of the {:?} formats", SURFACE_FORMATS))
+        },
+        _ => {}
+    }
+
+    issues
+}
+
+/// Every adapter of every backend, with the limits relevant to the clocks
+pub fn gpu_report(instance: &wgpu::Instance) -> String {
+    gpu_report_for_surface(instance, None)
+}
+
+/// Same as `gpu_report`, plus what each adapter can present to the given surface,
+/// and which adapter the clocks would pick for it
+pub fn gpu_report_for_surface(instance: &wgpu::Instance, surface: Option<&wgpu::Surface>) -> String {
+    let adapters: Vec<wgpu::Adapter> = instance.enumerate_adapters(wgpu::Backends::all()).collect();
+
+    // the same request as the runner's
+    let preferred = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference:       wgpu::PowerPreference::default(),
+        force_fallback_adapter: false,
+        compatible_surface:     surface
+    })).map(|adapter| adapter.get_info());
+
+    if adapters.is_empty() {
+        return "No graphics adapters found, make sure the graphics drivers are installed\n".to_string();
+    }
+
+    let mut report = String::new();
+
+    for (index, adapter) in adapters.iter().enumerate() {
+        let info     = adapter.get_info();
+        let features = adapter.features();
+        let limits   = adapter.limits();
+        let yes_no   = |feature: wgpu::Features| if features.contains(feature) { "yes" } else { "no" };
+
+        let chosen = if preferred.as_ref() == Some(&info) { " (used by the clocks)" } else { "" };
+
+        // writing into a String can't fail
+        let _ = writeln!(report, "Adapter {}: {}{}", index, info.name, chosen);
+        let _ = writeln!(report, "  Backend:           {:?}", info.backend);
+        let _ = writeln!(report, "  Device type:       {:?}", info.device_type);
+        let driver = format!("{} {}", info.driver, info.driver_info);
+        let _ = writeln!(report, "  Driver:            {}", if driver.trim().is_empty() { "unknown" } else { driver.trim() });
+        let _ = writeln!(report, "  Push constants:    {} (max {} bytes)", yes_no(wgpu::Features::PUSH_CONSTANTS), limits.max_push_constant_size);
+        let _ = writeln!(report, "  Timestamp queries: {}", yes_no(wgpu::Features::TIMESTAMP_QUERY));
+        let _ = writeln!(report, "  Storage buffers:   {} per stage, bindings up to {} bytes", limits.max_storage_buffers_per_shader_stage, limits.max_storage_buffer_binding_size);
+        let _ = writeln!(report, "  Max texture size:  {}", limits.max_texture_dimension_2d);
+
+        let capabilities = surface.map(|surface| surface.get_capabilities(adapter));
+        match &capabilities {
+            Some(capabilities) if capabilities.formats.is_empty() => {
+                let _ = writeln!(report, "  Surface:           not supported");
+            },
+            Some(capabilities) => {
+                let _ = writeln!(report, "  Surface formats:   {:?}", capabilities.formats);
+                let _ = writeln!(report, "  Present modes:     {:?}", capabilities.present_modes);
+                let _ = writeln!(report, "  Alpha modes:       {:?}", capabilities.alpha_modes);
+            },
+            None => {
+                let _ = writeln!(report, "  Surface:           unknown, no window to check against");
+            }
+        }
+
+        let issues = adapter_issues(features, &limits, capabilities.as_ref().map(|capabilities| capabilities.formats.as_slice()));
+        if issues.is_empty() {
+            let _ = writeln!(report, "  Issues:            none");
+        } else {
+            let _ = writeln!(report, "  Issues:");
+            for issue in issues {
+                let _ = writeln!(report, "    - {}", issue);
+            }
+        }
+    }
+
+    report
+}
+
+/// Handles `--gpu-info`: prints the report without showing a window.
+/// The surface formats and present modes come from an invisible window's surface, if one can be made
+pub fn print_gpu_info() {
+    let instance = wgpu::Instance::default();
+
+    let event_loop = winit::event_loop::EventLoop::new()
+        .map_err(|error| log::debug!("No event loop for a throwaway surface: {}", error))
+        .ok();
+
+    let window = event_loop.as_ref().and_then(|event_loop| {
+        winit::window::WindowBuilder::new()
+            .with_visible(false)
+            .build(event_loop)
+            .map_err(|error| log::debug!("No window for a throwaway surface: {}", error))
+            .ok()
+    });
+
+    let surface = window.as_ref().and_then(|window| {
+        unsafe { instance.create_surface(window) }
+            .map_err(|error| log::debug!("No throwaway surface: {}", error))
+            .ok()
+    });
+
+    print!("{}", gpu_report_for_surface(&instance, surface.as_ref()));
+}
@@ -0,0 +1,179 @@
+//! Rendering a single frame of a clock without any window or surface.
+//!
+//! Used by the `--snapshot` command line option, which is in turn what the golden-image tests drive.
+//! The clock is set up exactly like in the windowed runner, except that it draws into an offscreen texture
+//! of the same format as the surface would have, which is then read back to the CPU.
+
+use crate::{ExecDraw, FrameInfo, InputState, SURFACE_FORMAT, request_device};
+use crate::target::{set_frame_sample_count, MultisampledFrame, TargetConfig};
+
+/// wgpu requires the rows of a texture-to-buffer copy to be aligned to this
+const ROW_ALIGNMENT: u32 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+/// Render one frame of the clock at the given resolution, returned as RGBA8.
+/// `msaa_sample_count` is the runner's, see `RunOptions::msaa_sample_count`
+pub async fn render_frame<T: ExecDraw>(
+    width:             u32,
+    height:            u32,
+    features:          Option<wgpu::Features>,
+    msaa_sample_count: u32
+) -> Result<image::RgbaImage, String> {
+    let instance = wgpu::Instance::default();
+
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference:       wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            compatible_surface:     None
+        })
+        .await
+        .ok_or("Failed to find an appropriate adapter")?;
+
+    if let Some(features) = features {
+        if !adapter.features().contains(features) {
+            return Err(format!("The adapter lacks the required features: {:?}", features - adapter.features()));
+        }
+    }
+
+    let (device, queue) = request_device(&adapter, features, None).await?;
+    log::debug!("Rendering a {}x{} snapshot", width, height);
+
+    // only the fields the clocks actually read matter here, there's no surface to configure
+    let config = wgpu::SurfaceConfiguration {
+        usage:        wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format:       SURFACE_FORMAT,
+        width,
+        height,
+        present_mode: wgpu::PresentMode::Fifo,
+        alpha_mode:   wgpu::CompositeAlphaMode::Opaque,
+        view_formats: vec![SURFACE_FORMAT]
+    };
+
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label:           Some("Snapshot target"),
+        size:            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count:    1,
+        dimension:       wgpu::TextureDimension::D2,
+        format:          SURFACE_FORMAT,
+        usage:           wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats:    &[]
+    });
+
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let msaa_sample_count = TargetConfig::new(SURFACE_FORMAT, None)
+        .with_supported_sample_count(msaa_sample_count.max(1), &adapter, &device)
+        .sample_count;
+    set_frame_sample_count(msaa_sample_count);
+    let msaa_frame = MultisampledFrame::new((width, height), SURFACE_FORMAT, msaa_sample_count, &device);
+
+    // always the dark theme, so that snapshots don't depend on the desktop they were taken on
+    let mut execdraw = T::setup(&config, &adapter, &device, &queue, winit::window::Theme::Dark)?;
+    execdraw.resize(width, height, &device, &queue);
+    execdraw.update(0.0, &InputState::new(), &device, &queue);
+
+    let frame = FrameInfo::first();
+    execdraw.update(frame.delta, &InputState::new(), &device, &queue);
+    execdraw.draw(msaa_frame.as_ref().map_or(&view, |msaa_frame| msaa_frame.view()), &device, &queue, &frame);
+
+    if let Some(msaa_frame) = &msaa_frame {
+        msaa_frame.resolve(&view, &device, &queue);
+    }
+
+    return read_texture_rgba(&target, &device, &queue);
+}
+
+/// Copy a 2D BGRA8 or RGBA8 texture (the surface formats) back to the CPU as RGBA8, blocks until the GPU is done.
+/// The texture needs `COPY_SRC` usage. Alpha is dropped, as an opaque surface would do
+pub fn read_texture_rgba(
+    texture: &wgpu::Texture,
+    device:  &wgpu::Device,
+    queue:   &wgpu::Queue
+) -> Result<image::RgbaImage, String> {
+    read_texture_region_rgba(texture, texture.width(), texture.height(), device, queue)
+}
+
+/// `read_texture_rgba` of the texture's top-left `width` x `height` pixels only
+pub(crate) fn read_texture_region_rgba(
+    texture: &wgpu::Texture,
+    width:   u32,
+    height:  u32,
+    device:  &wgpu::Device,
+    queue:   &wgpu::Queue
+) -> Result<image::RgbaImage, String> {
+    if width > texture.width() || height > texture.height() {
+        return Err(format!("{}x{} is larger than the {}x{} texture", width, height, texture.width(), texture.height()));
+    }
+
+    let bgra = match texture.format() {
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        format => return Err(format!("Can't read back {:?} textures", format))
+    };
+
+    let padded_row = (width * 4).div_ceil(ROW_ALIGNMENT) * ROW_ALIGNMENT;
+
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label:              Some("Texture readback"),
+        size:               (padded_row * height) as u64,
+        usage:              wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &readback,
+            layout: wgpu::ImageDataLayout {
+                offset:         0,
+                bytes_per_row:  Some(padded_row),
+                rows_per_image: Some(height)
+            }
+        },
+        wgpu::Extent3d { width, height, depth_or_array_layers: 1 }
+    );
+
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = readback.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| { let _ = sender.send(result); });
+    device.poll(wgpu::Maintain::Wait);
+
+    receiver
+        .recv()
+        .map_err(|error| error.to_string())?
+        .map_err(|error| format!("Failed to read the frame back: {}", error))?;
+
+    // strip the row padding, and swizzle BGRA into RGBA
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+
+    for row in slice.get_mapped_range().chunks_exact(padded_row as usize) {
+        for texel in row[..(width * 4) as usize].chunks_exact(4) {
+            if bgra {
+                pixels.extend_from_slice(&[texel[2], texel[1], texel[0], 255]);
+            } else {
+                pixels.extend_from_slice(&[texel[0], texel[1], texel[2], 255]);
+            }
+        }
+    }
+
+    readback.unmap();
+
+    return image::RgbaImage::from_raw(width, height, pixels).ok_or("Frame size mismatch".to_string());
+}
+
+/// Render one frame of the clock and save it as a PNG file
+pub fn save_frame<T: ExecDraw>(
+    path:              &std::path::Path,
+    width:             u32,
+    height:            u32,
+    features:          Option<wgpu::Features>,
+    msaa_sample_count: u32
+) -> Result<(), String> {
+    let frame = pollster::block_on(render_frame::<T>(width, height, features, msaa_sample_count))?;
+    frame.save(path).map_err(|error| format!("Failed to save {}: {}", path.display(), error))
+}
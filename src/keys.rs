@@ -0,0 +1,72 @@
+//! Keyboard shortcuts of the clocks.
+//!
+//! Each clock lists its shortcuts in a `KEYBINDINGS` table of (key, action) pairs. `onkey` looks the pressed key up
+//! with `pressed` and matches on the returned name, and the help panel is generated from the same table.
+//! The runner keeps track of the held modifiers, as key events don't carry them.
+
+use std::sync::RwLock;
+use winit::keyboard::{Key, ModifiersState};
+#[cfg(not(target_arch = "wasm32"))]
+use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
+use crate::font::{BitmapFontRenderer, TextAnchor};
+use crate::target::TargetConfig;
+
+/// Key name and what it does, the name is a named key ("Space", "ArrowUp") or an uppercase character,
+/// prefixed with "Ctrl+" for a combination with the Ctrl key
+pub type KeyBinding = (&'static str, &'static str);
+
+/// Modifiers held as of the latest change, see `set_modifiers`
+static MODIFIERS: RwLock<ModifiersState> = RwLock::new(ModifiersState::empty());
+
+/// Called by the runner whenever the held modifiers change
+pub(crate) fn set_modifiers(state: ModifiersState) {
+    *MODIFIERS.write().unwrap() = state;
+}
+
+/// How much the clock is dimmed behind the help panel
+const HELP_BACKDROP: f32 = 0.5;
+
+/// The binding the key press belongs to, if any. Repeats and releases are ignored
+pub fn pressed(event: &winit::event::KeyEvent, bindings: &[KeyBinding]) -> Option<&'static str> {
+    if event.state != winit::event::ElementState::Pressed || event.repeat {
+        return None;
+    }
+
+    // the web has no key without the modifiers, Shift+A is "A" there either way
+    #[cfg(not(target_arch = "wasm32"))]
+    let key = event.key_without_modifiers();
+    #[cfg(target_arch = "wasm32")]
+    let key = event.logical_key.clone();
+
+    let name = match key {
+        Key::Named(named)    => format!("{:?}", named),
+        Key::Character(text) => text.to_uppercase(),
+        _ => return None
+    };
+
+    // a combination doesn't trigger the plain key's action
+    let name = if MODIFIERS.read().unwrap().control_key() { format!("Ctrl+{}", name) } else { name };
+
+    bindings.iter().find(|(key, _)| *key == name).map(|(key, _)| *key)
+}
+
+/// Two columns, the keys on the left and the actions on the right, padded by a space and a blank line
+pub fn help_lines(bindings: &[KeyBinding]) -> Vec<String> {
+    let width = bindings.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+
+    let mut lines = vec![" Keyboard shortcuts ".to_string(), String::new()];
+    lines.extend(bindings.iter().map(|(key, action)| format!(" {:<width$}   {} ", key, action)));
+    lines.push(String::new());
+
+    lines
+}
+
+/// The help panel, centered over a dimmed clock, drawn into the frame the clock gets (see `TargetConfig::frame`)
+pub fn help_panel(bindings: &[KeyBinding], config: &wgpu::SurfaceConfiguration, device: &wgpu::Device) -> BitmapFontRenderer {
+    let mut panel = BitmapFontRenderer::for_target(&TargetConfig::frame(config.format, None), TextAnchor::Center, device);
+    panel.set_lines(&help_lines(bindings));
+    panel.set_backdrop(HELP_BACKDROP);
+    panel.resize(config.width, config.height);
+
+    panel
+}
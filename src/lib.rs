@@ -1,554 +1,4578 @@
 use wgpu::util::DeviceExt;
 use image::{io::Reader as ImageReader, EncodableLayout};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::num::NonZeroU64;
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
 
+pub mod aspect;
+pub mod camera;
+pub mod cli;
+pub mod clocks;
+pub mod compute;
+pub mod config;
+pub mod font;
+pub mod gpu_info;
+pub mod headless;
+pub mod keys;
+pub mod logging;
+pub mod overlay;
+pub mod palette;
+pub mod params;
+#[cfg(feature = "recorder")]
+pub mod recorder;
+pub mod subview;
+pub mod target;
+pub mod time;
+pub mod viewport;
+pub mod web;
+pub mod widget;
+
+pub use gpu_info::{gpu_report, gpu_report_for_surface};
+
+/// The preferred surface format, the first of `SURFACE_FORMATS`, which headless renders use.
+/// The runner's may differ, the clocks take it from the configuration given to `ExecDraw::setup`
 pub const SURFACE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+/// Surface formats the runner picks from, most preferred first. The sRGB ones encode the clocks' linear colors themselves,
+/// on the others the colors come out darker
+pub const SURFACE_FORMATS: [wgpu::TextureFormat; 4] = [
+    SURFACE_FORMAT,
+    wgpu::TextureFormat::Rgba8UnormSrgb,
+    wgpu::TextureFormat::Bgra8Unorm,
+    wgpu::TextureFormat::Rgba8Unorm
+];
+
+/// The first of `SURFACE_FORMATS` the surface supports, failing that whatever it prefers itself
+pub fn select_surface_format(caps: &wgpu::SurfaceCapabilities) -> wgpu::TextureFormat {
+    SURFACE_FORMATS
+        .into_iter()
+        .find(|format| caps.formats.contains(format))
+        .or_else(|| caps.formats.first().copied())
+        .unwrap_or(SURFACE_FORMAT)
+}
+
+/// Device limits the clocks rely on, see `request_device`
+pub(crate) const PUSH_CONSTANT_SIZE:          u32 = 64;
+pub(crate) const STORAGE_BUFFERS_PER_STAGE:   u32 = 8;
+pub(crate) const STORAGE_BUFFER_BINDING_SIZE: u32 = 64 * 1024;
+
 pub trait ExecDraw {
+    /// Errors are meant for the user's eyes (missing resources etc.), the runner shows them with `fatal_error`
     fn setup(
         config:  &wgpu::SurfaceConfiguration,
         adapter: &wgpu::Adapter,
         device:  &wgpu::Device,
-        queue:   &wgpu::Queue) -> Self where Self: Sized;
+        queue:   &wgpu::Queue,
+        theme:   winit::window::Theme) -> Result<Self, String> where Self: Sized;
 
     fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue);
 
-    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue);
+    /// Called by the runner once per presented frame right before `draw`, and once after every `resize` with a `dt` of 0.0.
+    /// The place for the clock's state changes and buffer writes, so that `draw` is left with encoding the passes.
+    /// `dt` is the frame's `FrameInfo::delta`, `input` what's held down at the time
+    fn update(self: &mut Self, _dt: f32, _input: &InputState, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
 
-    fn onkey(self: &mut Self, _event: winit::event::KeyEvent, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
+    /// `frame` tells how long the previous frame took on the wall clock, see `FrameInfo`.
+    /// What the clock shows follows the shared time source (`time::now`) rather than adding up deltas, so that it pauses and scales
+    /// along with it, `frame.delta` is for what runs in real time regardless, e.g. easing towards the mouse or spinning the view
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, frame: &FrameInfo);
 
-    fn onmousemove(self: &mut Self, _delta: (f64, f64), _state: u32, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
+    /// `modifiers` are the ones held when the key was pressed, as for the mouse callbacks. None are held while the window is unfocused
+    fn onkey(self: &mut Self, _event: winit::event::KeyEvent, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
 
-    fn onmousescroll(self: &mut Self, _delta: (f64, f64), _state: u32, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
+    fn onmousemove(self: &mut Self, _delta: (f64, f64), _state: u32, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
 
-    fn onmousebutton(self: &mut Self, _state: u32, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
-}
+    /// `delta` is in lines, x positive scrolling right and y positive scrolling up, see `scroll_delta_in_lines`
+    fn onmousescroll(self: &mut Self, _delta: (f64, f64), _state: u32, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
 
-/// App runner.
-/// Modified version of WGPU sample boilerplate.
-/// Takes in an `ExecDraw` derived struct and calls necessary functions
-pub async fn run<T: ExecDraw>(
-    event_loop: winit::event_loop::EventLoop<()>,
-    window:     winit::window::Window,
-    features:   Option<wgpu::Features>
-) {
-    let mut size = window.inner_size();
-    size.width   = size.width.max(1);
-    size.height  = size.height.max(1);
+    fn onmousebutton(self: &mut Self, _state: u32, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
 
-    let instance = wgpu::Instance::default();
+    /// `onmousebutton` with where the cursor was last seen in the window, for hit-testing clicks.
+    /// `None` until it's moved over the window. Calls `onmousebutton` by default
+    fn onmousebutton_at(
+        self:      &mut Self,
+        state:     u32,
+        _position: Option<(f64, f64)>,
+        modifiers: winit::keyboard::ModifiersState,
+        device:    &wgpu::Device,
+        queue:     &wgpu::Queue
+    ) {
+        self.onmousebutton(state, modifiers, device, queue);
+    }
 
-    let surface = unsafe { instance.create_surface(&window).unwrap() };
+    /// `position` is in the window's pixels from its top-left corner, `state` the buttons held as for `onmousemove`
+    fn oncursormoved(self: &mut Self, _position: (f64, f64), _state: u32, _modifiers: winit::keyboard::ModifiersState, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
 
-    let adapter = instance
-        .request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference:       wgpu::PowerPreference::default(),
-            force_fallback_adapter: false,
-            compatible_surface:     Some(&surface)
-        })
-        .await
-        .expect("Failed to find an appropriate adapter");
+    /// Called when the window's scale factor changes, e.g. dragged onto a monitor of another DPI, with the new one
+    /// (1.0 at 96 DPI, 1.5 at 150% scaling). The window keeps its logical size, so its pixels change, `resize` follows
+    /// right after with the new physical size, which is where pixel sizes (blur radii, line widths) are re-derived
+    /// from the drawspace's density. This is for whatever follows the scale factor itself, e.g. text sizes
+    fn onscalefactorchanged(self: &mut Self, _scale: f64, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
 
-    let swapchain_capabilities = surface.get_capabilities(&adapter);
-    if !swapchain_capabilities.formats.into_iter().any(|format| { format == SURFACE_FORMAT }) {
-        panic!("Seeking for support of surface format \"wgpu::TextureFormat::Bgra8UnormSrgb\", but not found");
-    }
-    
-    let swapchain_format = SURFACE_FORMAT;
+    /// Called when the OS switches between dark and light themes while the clock is running.
+    /// The theme at startup is handed to `setup` instead
+    fn ontheme(self: &mut Self, _theme: winit::window::Theme) {}
 
-    let mut device_limits = wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits());
-    device_limits.max_push_constant_size = 64;  // Needed for push constants
-    device_limits.max_storage_buffers_per_shader_stage = 8; // Needed for storage buffers
-    device_limits.max_storage_buffer_binding_size = 64 * 1024;  // Needed for storage buffers
+    /// Called when the window gains or loses the keyboard focus, e.g. to pause what's only worth watching up front.
+    /// The runner has let go of the keys and mouse buttons held by then, see `InputState::focus_lost`
+    fn onfocus(self: &mut Self, _focused: bool, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
 
-    let (device, queue) = adapter
-        .request_device(
-            &wgpu::DeviceDescriptor {
-                label: None,
-                features: match features {
-                    None => wgpu::Features::empty(),
-                    Some(f) => f
-                },
-                limits: device_limits
-            },
-            None,
-        )
-        .await
-        .expect("Failed to create device");
+    /// Called when a file is dropped onto the window, e.g. an image or a palette to use instead of the clock's own.
+    /// What can't be used is the clock's to reject (and log), keeping what it had
+    fn ondropfile(self: &mut Self, _path: std::path::PathBuf, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
 
-    let mut config = wgpu::SurfaceConfiguration {
-        usage:        wgpu::TextureUsages::RENDER_ATTACHMENT,
-        format:       swapchain_format,
-        width:        size.width,
-        height:       size.height,
-        present_mode: wgpu::PresentMode::Fifo,
-        alpha_mode:   wgpu::CompositeAlphaMode::Auto,
-        view_formats: vec![swapchain_format]
-    };
+    /// Shown in the window title after the clock's name, as in `Digital Clock | Speed: 1.0x`.
+    /// Polled once after `setup` and after every key press
+    fn title_status(self: &Self) -> Option<String> { None }
 
-    surface.configure(&device, &config);
+    /// Called by the runner once per frame, right before drawing, with the latest frame timings
+    fn onstats(self: &mut Self, _stats: &FrameStats) {}
 
-    let mut execdraw = T::setup(&config, &adapter, &device, &queue);
+    /// Polled by the runner once per frame, before drawing. A mode other than the surface's current one reconfigures it,
+    /// as far as it's supported (see `RunOptions::present_mode`), e.g. `Immediate` to measure the uncapped frame rate.
+    /// `None` keeps whatever the surface has, which is the default
+    fn request_present_mode(self: &Self) -> Option<wgpu::PresentMode> { None }
 
-    let mut cursor_in_window = false;
-    let mut mouse_button_state = 0_u32;
+    /// Polled by the runner once per frame, before drawing. A sample count other than the frame's current one
+    /// (see `RunOptions::msaa_sample_count`) recreates the frame with it, whatever the clock draws into it must be built
+    /// for that count already. It has to be supported (see `target::TargetConfig::supports`), `None` keeps the frame as it is
+    fn request_msaa_sample_count(self: &Self) -> Option<u32> { None }
 
-    let _ = event_loop.run(move |event, target| {
-        // Have the closure take ownership of the resources.
-        // `event_loop.run` never returns, therefore we must do this to ensure
-        // the resources are properly cleaned up.
-        let _ = (&instance, &adapter, &execdraw);
+    /// Polled by the runner once per frame while it letterboxes the clock (see `RunOptions::aspect_ratio`), after drawing.
+    /// The color of the bars around the clock, usually its background. `None` leaves them black
+    /// (transparent on transparent surfaces), which is the default
+    fn letterbox_color(self: &Self) -> Option<wgpu::Color> { None }
 
-        if let winit::event::Event::WindowEvent { window_id: _, event, } = event {
-            match event {
-                winit::event::WindowEvent::Resized(new_size) => {
-                    // Reconfigure the surface with the new size
-                    config.width = new_size.width.max(1);
-                    config.height = new_size.height.max(1);
-                    surface.configure(&device, &config);
-                    // On macos the window needs to be redrawn manually after resizing
-                    execdraw.resize(new_size.width.max(1), new_size.height.max(1), &device, &queue);
-                    window.request_redraw();
-                },
-                winit::event::WindowEvent::CloseRequested => target.exit(),
-                winit::event::WindowEvent::RedrawRequested => {
-                    let frame = surface.get_current_texture().expect("Failed to acquire next swap chain texture");
-                    let view  = frame.texture.create_view(&wgpu::TextureViewDescriptor {
-                        format: Some(swapchain_format),
-                        ..wgpu::TextureViewDescriptor::default()
-                    });
+    /// Polled by the runner after each frame, how soon it draws the next one. `Continuous` by default,
+    /// clocks that only change a few times per second can save the GPU (and the battery) with `EveryDuration`
+    fn redraw_policy(self: &Self) -> RedrawPolicy { RedrawPolicy::Continuous }
 
-                    execdraw.draw(&view, &device, &queue);
-                    frame.present();
-                    window.request_redraw();
-                },
-                winit::event::WindowEvent::KeyboardInput { event, .. } => {
-                    execdraw.onkey(event, &device, &queue);
-                },
-                winit::event::WindowEvent::CursorLeft { .. } => {
-                    cursor_in_window = false;
-                },
-                winit::event::WindowEvent::CursorEntered { .. } => {
-                    cursor_in_window = true;
-                }
-                _ => {}
-            }
-        } else if let winit::event::Event::DeviceEvent { device_id: _, event } = event {
-            match event {
-                winit::event::DeviceEvent::MouseMotion { delta } => {
-                    if cursor_in_window {
-                        execdraw.onmousemove(delta, mouse_button_state, &device, &queue);
-                    }
-                },
-                winit::event::DeviceEvent::MouseWheel { delta } => {
-                    if cursor_in_window {
-                        if let winit::event::MouseScrollDelta::LineDelta(dx, dy) = delta {
-                            let delta = (dx as f64, dy as f64);
-                            execdraw.onmousescroll(delta, mouse_button_state, &device, &queue);
-                        }
-                    }
-                },
-                winit::event::DeviceEvent::Button { button, state } => {
-                    match state {
-                        winit::event::ElementState::Pressed => {
-                            mouse_button_state |= 1 << button;
-                        },
-                        winit::event::ElementState::Released => {
-                            mouse_button_state &= !(1 << button);
-                        },
-                    }
-                    
-                    if cursor_in_window {
-                        execdraw.onmousebutton(mouse_button_state, &device, &queue);
-                    }
-                }
-                _ => {}
-            }
+    /// Polled by the runner once per frame, before drawing.
+    /// Return `false` when the clock would render the same frame as before (e.g. still the same second),
+    /// so that the runner can slow down to the idle frame rate. By default, clocks are always animating.
+    fn significant_change(self: &mut Self) -> bool { true }
+
+    /// Polled by the runner while idle, the next moment the clock has something to show (a digit flipping,
+    /// an animation starting or ending etc.), so that the frame isn't delayed until the next idle interval.
+    /// `now` is when the runner is about to go idle. By default, the idle interval is all there is
+    fn next_deadline(self: &Self, _now: time::Instant) -> Option<time::Instant> { None }
+
+    /// Polled by the runner before each frame, identifies what the clock would draw at `now` (the shared time source's).
+    /// While it's the same as the last presented frame's, the frame is skipped altogether: neither drawn nor presented.
+    /// Changes through input, resizes and the theme needn't be covered, the runner draws after those regardless.
+    /// `None` draws every frame, which is the default, and is what animating clocks should return
+    fn content_version(self: &Self, _now: &chrono::DateTime<chrono::FixedOffset>) -> Option<u64> { None }
+
+    /// Called when `SCREENSHOT_KEY` is pressed, with the next frame once it's drawn, right before it's presented.
+    /// Saves it into the working directory by default, see `screenshot_path`
+    fn on_screenshot_key(self: &mut Self, frame: &wgpu::Texture, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let path = screenshot_path(&chrono::Local::now());
+
+        match pollster::block_on(capture_frame_to_png(device, queue, frame, frame.width(), frame.height(), &path)) {
+            Ok(()) => log::info!("Saved a screenshot to {}", path),
+            Err(error) => show_error("Screenshot", &format!("Failed to save {}: {}", path, error))
         }
-    });
+    }
+
+    /// Called once when the window is about to close, while it still exists.
+    /// The place to persist settings and window geometry.
+    fn onexit(self: &mut Self, _window: &winit::window::Window) {}
 }
 
-pub fn load_png_rgba8(path: &str) -> (u32, u32, Vec<u8>) {
-    let dynimage = ImageReader::open(path).unwrap().decode().unwrap();
-    let rgba8 = dynimage.to_rgba8();
-    let raw = rgba8.as_raw();
+/// Timing of the frame about to be drawn, handed to `ExecDraw::draw`.
+/// Anything moving on its own should move by a speed times `delta`, so that it's as fast at 60 Hz as at 144 Hz
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameInfo {
+    /// Seconds since the previous frame was drawn, `FIRST_FRAME_DELTA` for the first one
+    pub delta:       f32,
+    /// Seconds since the first frame was drawn
+    pub elapsed:     f32,
+    /// Frames drawn before this one
+    pub frame_index: u64
+}
+
+/// Delta of the first frame (and of snapshots), where there's no previous frame to measure from
+pub const FIRST_FRAME_DELTA: f32 = 1.0 / 60.0;
 
-    (rgba8.width(), rgba8.height(), raw.clone())
+impl FrameInfo {
+    pub fn first() -> Self {
+        Self { delta: FIRST_FRAME_DELTA, elapsed: 0.0, frame_index: 0 }
+    }
+
+    /// The frame after this one, drawn `delta` seconds later
+    pub fn next(self: &Self, delta: f32) -> Self {
+        Self { delta, elapsed: self.elapsed + delta, frame_index: self.frame_index + 1 }
+    }
 }
 
-/// Basic read-only texture resource made from pixel data
-pub struct ResourceTexture {
-    pub texture: wgpu::Texture,
-    pub view:    wgpu::TextureView,
-    pub width:   u32,
-    pub height:  u32
+/// Frame timings measured by the runner, see `ExecDraw::onstats`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameStats {
+    /// Frames per second, derived from the smoothed frame time
+    pub fps: f32,
+    /// Time between consecutive frames, exponentially smoothed
+    pub frame_time: std::time::Duration,
+    /// GPU time of the clock's `draw`, `None` unless the device supports timestamp queries and `RunOptions::stats` is set
+    pub gpu_time: Option<std::time::Duration>,
+    /// Time between consecutive presents, exponentially smoothed. Unlike `frame_time`, it includes
+    /// any blocking in `present` and in waiting for the frames in flight, see `RunOptions::frame_latency`
+    pub present_interval: std::time::Duration,
+    /// Frames allowed in flight, `None` when left to the driver
+    pub frame_latency: Option<u32>
 }
 
-impl ResourceTexture {
-    pub fn new(path: &str, device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
-        let (width, height, data) = load_png_rgba8(path);
+impl FrameStats {
+    /// Weight of the newest frame in the smoothed frame time
+    const SMOOTHING: f64 = 0.1;
 
-        let texture = device.create_texture_with_data(queue, &wgpu::TextureDescriptor {
-            label:           None,
-            size:            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-            mip_level_count: 1,
-            sample_count:    1,
-            dimension:       wgpu::TextureDimension::D2,
-            format:          wgpu::TextureFormat::Rgba8Unorm,
-            usage:           wgpu::TextureUsages::TEXTURE_BINDING,
-            view_formats:    &[]
-        }, data.as_bytes());
+    fn push_frame(self: &mut Self, delta: std::time::Duration) {
+        let smoothed = if self.frame_time.is_zero() {
+            delta.as_secs_f64()
+        } else {
+            self.frame_time.as_secs_f64() * (1.0 - Self::SMOOTHING) + delta.as_secs_f64() * Self::SMOOTHING
+        };
 
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.frame_time = std::time::Duration::from_secs_f64(smoothed);
+        self.fps = if smoothed > 0.0 { (1.0 / smoothed) as f32 } else { 0.0 };
+    }
 
-        Self { texture, view, width, height }
+    fn push_present(self: &mut Self, interval: std::time::Duration) {
+        self.present_interval = if self.present_interval.is_zero() {
+            interval
+        } else {
+            self.present_interval.mul_f64(1.0 - Self::SMOOTHING) + interval.mul_f64(Self::SMOOTHING)
+        };
     }
+}
 
-    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry {
-        wgpu::BindGroupEntry {
-            binding,
-            resource: wgpu::BindingResource::TextureView(&self.view)
-        }
+/// Most frames `RunOptions::frame_latency` allows in flight, what wgpu's swapchains hold at most
+pub const MAX_FRAME_LATENCY: u32 = 3;
+
+/// The frame latency within [1..MAX_FRAME_LATENCY], with a warning if it wasn't
+pub fn clamp_frame_latency(latency: u32) -> u32 {
+    let clamped = latency.clamp(1, MAX_FRAME_LATENCY);
+
+    if clamped != latency {
+        log::warn!("A frame latency of {} isn't possible, using {} instead (1 to {} frames)", latency, clamped, MAX_FRAME_LATENCY);
     }
 
-    pub fn default_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
-        wgpu::BindGroupLayoutEntry {
-            binding,
-            visibility: wgpu::ShaderStages::FRAGMENT,
-            ty: wgpu::BindingType::Texture {
-                multisampled:   false,
-                sample_type:    wgpu::TextureSampleType::Float { filterable: true },
-                view_dimension: wgpu::TextureViewDimension::D2
-            },
-            count: None
+    clamped
+}
+
+/// Keeps at most `latency` frames in flight, by waiting for the oldest one's work before acquiring the next frame.
+/// wgpu 0.18's `SurfaceConfiguration` has no `desired_maximum_frame_latency` (the swapchain always asks for 3 images),
+/// so the runner limits it itself, which works the same on every backend and survives reconfiguring the surface
+struct FramePacer {
+    latency:   usize,
+    in_flight: std::collections::VecDeque<wgpu::SubmissionIndex>
+}
+
+impl FramePacer {
+    fn new(latency: u32) -> Self {
+        Self { latency: latency as usize, in_flight: std::collections::VecDeque::with_capacity(latency as usize) }
+    }
+
+    /// Block until there's room for another frame
+    fn wait(self: &mut Self, device: &wgpu::Device) {
+        while self.in_flight.len() >= self.latency {
+            if let Some(index) = self.in_flight.pop_front() {
+                device.poll(wgpu::Maintain::WaitForSubmissionIndex(index));
+            }
         }
     }
+
+    /// Count the frame just presented as in flight, until everything submitted so far is done
+    fn presented(self: &mut Self, queue: &wgpu::Queue) {
+        // an empty submission is done right after the frame's own ones
+        self.in_flight.push_back(queue.submit(std::iter::empty()));
+    }
 }
 
-/// Basic Linear filtering sampler with edge clipping
-pub struct BasicFilteringSampler {
-    pub sampler: wgpu::Sampler
+/// Draws at most a given number of frames per second, whatever the present mode. Each frame's slot follows the previous one's
+/// rather than when it was actually drawn, so that the rate doesn't drift down by the event loop's latencies
+#[derive(Clone, Copy, Debug)]
+pub struct FrameLimiter {
+    interval: std::time::Duration,
+    next:     Option<time::Instant> // earliest the next frame may be drawn, `None` before the first one
 }
 
-impl BasicFilteringSampler {
-    pub fn new(device: &wgpu::Device) -> Self {
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: None,
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter:     wgpu::FilterMode::Linear,
-            min_filter:     wgpu::FilterMode::Linear,
-            mipmap_filter:  wgpu::FilterMode::Linear,
-            ..Default::default()
-        });
+impl FrameLimiter {
+    /// 0 is taken as 1
+    pub fn new(max_fps: u32) -> Self {
+        Self { interval: std::time::Duration::from_secs(1) / max_fps.max(1), next: None }
+    }
 
-        Self { sampler }
+    /// `None` when a frame may be drawn at `now`, when it may be otherwise
+    pub fn wait_until(self: &Self, now: time::Instant) -> Option<time::Instant> {
+        self.next.filter(|next| now < *next)
     }
 
-    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry {
-        wgpu::BindGroupEntry {
-            binding,
-            resource: wgpu::BindingResource::Sampler(&self.sampler)
-        }
+    /// Called once the frame is drawn at `now`. After falling behind by more than a frame (e.g. while idle),
+    /// the slots start over from `now` instead of catching up with a burst of frames
+    pub fn drawn(self: &mut Self, now: time::Instant) {
+        self.next = match self.next {
+            Some(next) if now < next + self.interval => Some(next + self.interval),
+            _ => Some(now + self.interval)
+        };
     }
+}
 
-    pub fn default_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
-        wgpu::BindGroupLayoutEntry {
-            binding,
-            visibility: wgpu::ShaderStages::FRAGMENT,
-            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-            count: None
+/// Runner settings those are independent of the clock being run
+#[derive(Clone, Debug)]
+pub struct RunOptions {
+    /// `Fifo` (the default) waits for the vertical blank, capping the frame rate at the monitor's refresh rate.
+    /// `Immediate` doesn't wait, for an uncapped frame rate (and tearing), `Mailbox` neither but without tearing.
+    /// `AutoVsync` and `AutoNoVsync` pick whichever of those with or without waiting the hardware supports.
+    /// Falls back to `Fifo` if not supported by the surface, clocks can switch while running, see `ExecDraw::request_present_mode`
+    pub present_mode: wgpu::PresentMode,
+    /// Which GPU to pick when there are several, e.g. `LowPower` for the integrated one on laptops
+    pub power_preference: wgpu::PowerPreference,
+    /// Device limits to request instead of the ones all the clocks rely on (see `request_device`),
+    /// e.g. for clocks that need less than those. Requesting more than the adapter has fails the start
+    pub required_limits: Option<wgpu::Limits>,
+    /// Usages of the surface textures besides `RENDER_ATTACHMENT`, which they always have.
+    /// Those the surface doesn't support are dropped with a warning
+    pub surface_usage: wgpu::TextureUsages,
+    /// Once nothing significant has changed (see `ExecDraw::significant_change`) and no input arrived for this long,
+    /// the runner stops redrawing continuously and redraws once per this interval instead
+    pub idle_threshold_ms: u64,
+    /// Composite the surface with what's behind the window, the window itself must be created transparent as well.
+    /// The clock sees the outcome via `SurfaceConfiguration::alpha_mode`, it's left `Auto` (i.e. opaque) if not supported.
+    /// Where it's supported, clearing to `wgpu::Color::TRANSPARENT` lets the desktop through, and on `PreMultiplied`
+    /// surfaces whatever is drawn over it has to be premultiplied by its alpha
+    pub transparent: bool,
+    /// Draw the frame statistics overlay on top of the clock, see `overlay`.
+    /// Also measures the GPU time, if the adapter supports timestamp queries
+    pub stats: bool,
+    /// Binds the time controls to keys, see `time::ControllableTime`. Those keys don't reach the clock then
+    pub debug_time: bool,
+    /// Run as a desktop widget: dragged by the preset's modifier, and click-through if it says so, see `widget`.
+    /// The window has to be made with `widget::apply_to_window`, and the surface `transparent`
+    pub widget: Option<widget::WidgetOptions>,
+    /// Move the window into this corner of its monitor once it's open, `widget::CORNER_MARGIN` away from the edges.
+    /// Meant for widgets, in place of wherever the window manager (or the remembered geometry) put it
+    pub corner: Option<widget::ScreenCorner>,
+    /// Fade the whole frame, below 1.0 it costs an extra pass. Only effective on transparent surfaces
+    pub opacity: f32,
+    /// Most frames queued up ahead of the display, fewer means less input lag but more risk of missed frames.
+    /// Clamped to [1..MAX_FRAME_LATENCY] with a warning, `None` leaves it to the driver. Enforced by the runner
+    /// rather than the surface, so it's not in the `SurfaceConfiguration` the clock gets, see `FrameStats::frame_latency`
+    pub frame_latency: Option<u32>,
+    /// Draw at most this many frames per second, regardless of the present mode, see `FrameLimiter`.
+    /// Not while the window is being resized, so that the contents keep up with it
+    pub max_fps: Option<u32>,
+    /// Keep the clock at this aspect ratio (width / height), see `aspect`. The clock is set up and resized at the largest
+    /// size with the ratio that fits into the window, and centered within it with bars around, see `ExecDraw::letterbox_color`
+    pub aspect_ratio: Option<f32>,
+    /// Multisample the frame the clock draws into with this many samples (as far as supported), resolved by the runner
+    /// before anything else of its own draws over it. 1 (the default) hands the clock the destination itself.
+    /// Every pipeline drawing into the frame has to be built for the resulting count, see `target::frame_sample_count`.
+    /// Clocks can switch while running, see `ExecDraw::request_msaa_sample_count`
+    pub msaa_sample_count: u32,
+    /// Switch between borderless fullscreen and the window with `FULLSCREEN_KEY` or Alt+Enter, those don't reach the clock then.
+    /// The clock is resized either way, leaving fullscreen brings back the window's size and position from before
+    pub enable_fullscreen_toggle: bool,
+    /// Enables recording with the F9 key, see `recorder`
+    #[cfg(feature = "recorder")]
+    pub recorder: Option<recorder::RecorderOptions>
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            present_mode:      wgpu::PresentMode::Fifo,
+            power_preference:  wgpu::PowerPreference::default(),
+            required_limits:   None,
+            surface_usage:     wgpu::TextureUsages::RENDER_ATTACHMENT,
+            idle_threshold_ms: 33, // ~30 fps while idle
+            transparent:       false,
+            stats:             false,
+            debug_time:        false,
+            widget:            None,
+            corner:            None,
+            opacity:           1.0,
+            frame_latency:     None,
+            max_fps:           None,
+            aspect_ratio:      None,
+            msaa_sample_count: 1,
+            enable_fullscreen_toggle: true,
+            #[cfg(feature = "recorder")]
+            recorder:          None
         }
     }
 }
 
-/// Rewritable uniform buffer for a single struct/variable
-pub struct SingleUniformBuffer {
-    pub buffer: wgpu::Buffer,
-    pub stages: wgpu::ShaderStages
+/// `requested` if the surface supports it, `Fifo` (which every surface does) otherwise.
+/// The Auto* modes are resolved by wgpu itself, they're always taken
+pub fn supported_present_mode(requested: wgpu::PresentMode, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+    match requested {
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync => requested,
+        mode if supported.contains(&mode) => mode,
+        mode => {
+            log::warn!("Present mode {:?} isn't supported (supported: {:?}), falling back to Fifo", mode, supported);
+            wgpu::PresentMode::Fifo
+        }
+    }
 }
 
-impl SingleUniformBuffer {
-    pub fn new<T>(device: &wgpu::Device, stages: wgpu::ShaderStages) -> Self {
-        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
-            size:  std::mem::size_of::<T>() as u64,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false
-        });
+/// When the idle runner draws the next frame: after the idle `interval`, or at the clock's deadline if that's sooner
+/// (see `ExecDraw::next_deadline`). A deadline that has already passed means right away
+pub fn next_wakeup(now: time::Instant, interval: std::time::Duration, deadline: Option<time::Instant>) -> time::Instant {
+    let paced = now + interval;
 
-        Self { buffer, stages }
+    match deadline {
+        Some(deadline) => deadline.clamp(now, paced),
+        None => paced
     }
+}
 
-    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry {
-        wgpu::BindGroupEntry {
-            binding,
-            resource: self.buffer.as_entire_binding()
-        }
-    }
+/// How often the runner draws, see `ExecDraw::redraw_policy`. Input, resizes, exposes and the theme changing
+/// are drawn right away whichever the policy, and so are the clock's deadlines (`ExecDraw::next_deadline`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedrawPolicy {
+    /// Every frame the display takes, slowing down to the idle interval (`RunOptions::idle_threshold_ms`)
+    /// while nothing significant changes
+    Continuous,
+    /// Once per this interval, e.g. for clocks that only change a few times per second
+    EveryDuration(std::time::Duration),
+    /// Only when something happens, for clocks that only change when told to
+    OnEvent
+}
 
-    pub fn default_layout_entry(binding: u32, sub: &Self) -> wgpu::BindGroupLayoutEntry {
-        wgpu::BindGroupLayoutEntry {
-            binding,
-            visibility: sub.stages,
-            ty: wgpu::BindingType::Buffer {
-                ty:                 wgpu::BufferBindingType::Uniform,
-                has_dynamic_offset: false,
-                min_binding_size:   None
-            },
-            count: None
+impl RedrawPolicy {
+    /// How the runner waits for the next frame after one drawn (or skipped) at `now`, `None` to draw it right away.
+    /// `idle` tells whether nothing significant changed for the idle threshold, `idle_interval` is the one in `RunOptions`
+    pub fn control_flow(
+        self:          Self,
+        now:           time::Instant,
+        idle:          bool,
+        idle_interval: std::time::Duration,
+        deadline:      Option<time::Instant>
+    ) -> Option<winit::event_loop::ControlFlow> {
+        match self {
+            Self::Continuous if !idle => None,
+            Self::Continuous => Some(winit::event_loop::ControlFlow::WaitUntil(next_wakeup(now, idle_interval, deadline))),
+            Self::EveryDuration(interval) => Some(winit::event_loop::ControlFlow::WaitUntil(next_wakeup(now, interval, deadline))),
+            Self::OnEvent => Some(match deadline {
+                Some(deadline) => winit::event_loop::ControlFlow::WaitUntil(deadline.max(now)),
+                None => winit::event_loop::ControlFlow::Wait
+            })
         }
     }
 }
 
-/// Read only storage buffer for array data
-pub struct ImmutableStorageBuffer {
-    pub buffer: wgpu::Buffer,
-    pub stages: wgpu::ShaderStages
+/// Decides whether the runner draws the frame it was asked for, or skips it as nothing changed, see `ExecDraw::content_version`.
+/// Skipping is only safe while the window still shows the last presented frame, which isn't the case after a resize,
+/// after it was uncovered (some compositors don't keep the contents of hidden windows) and the like. The runner
+/// `invalidate`s on those, and redraws requested by the windowing system rather than the runner (exposes) aren't skipped.
+/// Just in case that's missed somewhere, no frame is skipped over `MAX_SKIPPED` after the last one presented
+#[derive(Debug)]
+pub struct RedrawTracker {
+    presented:   Option<(u64, time::Instant)>, // version of the last presented frame and when, `None` if unversioned
+    invalidated: bool, // the window's contents may be outdated or gone, the next frame is drawn regardless
+    requested:   bool  // the runner asked for the redraw, see `request`
 }
 
-impl ImmutableStorageBuffer {
-    pub fn new(device: &wgpu::Device, stages: wgpu::ShaderStages, init: &[u8]) -> Self {
-        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label:    None,
-            contents: init,
-            usage:    wgpu::BufferUsages::STORAGE
-        });
+impl Default for RedrawTracker {
+    fn default() -> Self {
+        Self { presented: None, invalidated: true, requested: false }
+    }
+}
 
-        Self { buffer, stages }
+impl RedrawTracker {
+    /// Longest time without a presented frame while skipping
+    pub const MAX_SKIPPED: std::time::Duration = std::time::Duration::from_secs(1);
+
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry {
-        wgpu::BindGroupEntry {
-            binding,
-            resource: self.buffer.as_entire_binding()
-        }
+    /// Called along with `request_redraw` by the runner, i.e. for redraws that may be skipped
+    pub fn request(self: &mut Self) {
+        self.requested = true;
     }
 
-    pub fn default_layout_entry(binding: u32, sub: &Self) -> wgpu::BindGroupLayoutEntry {
-        wgpu::BindGroupLayoutEntry {
-            binding,
-            visibility: sub.stages,
-            ty: wgpu::BindingType::Buffer {
-                ty:                 wgpu::BufferBindingType::Storage { read_only: true },
-                has_dynamic_offset: false,
-                min_binding_size:   None
-            },
-            count: None
+    /// The next frame has to be drawn, whatever its version
+    pub fn invalidate(self: &mut Self) {
+        self.invalidated = true;
+    }
+
+    /// Called on each redraw, with the clock's version of the frame. `false` when it's the presented one
+    pub fn should_draw(self: &mut Self, version: Option<u64>, now: time::Instant) -> bool {
+        let requested = std::mem::take(&mut self.requested);
+
+        if !requested || self.invalidated {
+            return true;
+        }
+
+        match (version, self.presented) {
+            (Some(version), Some((presented, at))) => version != presented || now.saturating_duration_since(at) >= Self::MAX_SKIPPED,
+            _ => true
         }
     }
+
+    /// Called once the frame of the given version is presented
+    pub fn presented(self: &mut Self, version: Option<u64>, now: time::Instant) {
+        self.presented   = version.map(|version| (version, now));
+        self.invalidated = false;
+    }
 }
 
-/// Texture that can be rendered on in a pass and sampled from in a subsequent pass
-/// Usable for both color or depth targets
-/// Single sample
+/// What the runner does when it can't get the surface's next texture
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SurfaceRecovery {
+    /// Configure the surface again as it was and redraw, e.g. after the window moved to another monitor or the GPU was reset
+    Reconfigure,
+    /// Leave this frame out and try again with the next one
+    SkipFrame,
+    /// Nothing left to do but exit
+    Abort
+}
+
+impl SurfaceRecovery {
+    pub fn for_error(error: &wgpu::SurfaceError) -> Self {
+        match error {
+            wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => Self::Reconfigure,
+            wgpu::SurfaceError::Timeout => Self::SkipFrame,
+            wgpu::SurfaceError::OutOfMemory => Self::Abort
+        }
+    }
+}
+
+/// Whether the window can be seen at all: while it's minimized (resized to nothing) or entirely covered,
+/// the runner neither acquires frames nor draws them. A surface can't be configured with a zero size either,
+/// it keeps the last one meanwhile. Anything may have changed while hidden, so the clock is resized before the
+/// first frame after, unless the `Resized` event bringing the window back has done that already
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowVisibility {
+    minimized:      bool,
+    occluded:       bool,
+    resize_pending: bool
+}
+
+impl WindowVisibility {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// On `Resized`, `false` for a zero size, which the surface and the clock must not be resized to.
+    /// Otherwise the runner resizes everything right away
+    pub fn resized(self: &mut Self, width: u32, height: u32) -> bool {
+        self.minimized      = width == 0 || height == 0;
+        self.resize_pending = self.minimized;
+
+        !self.minimized
+    }
+
+    /// On `Occluded`
+    pub fn occluded(self: &mut Self, occluded: bool) {
+        self.occluded = occluded;
+
+        if occluded {
+            self.resize_pending = true;
+        }
+    }
+
+    pub fn is_hidden(self: &Self) -> bool {
+        self.minimized || self.occluded
+    }
+
+    /// Whether the clock has to be resized before the frame about to be drawn, once after it's back
+    pub fn take_pending_resize(self: &mut Self) -> bool {
+        !self.is_hidden() && std::mem::take(&mut self.resize_pending)
+    }
+}
+
+/// Where the cursor is over the window, from its `CursorMoved` events. Forgotten when the cursor leaves,
+/// so that the first move after it's back isn't taken for a jump from where it left
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CursorTracker {
+    position: Option<(f64, f64)>
+}
+
+impl CursorTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The last position seen, `None` while the cursor is outside the window or hasn't moved since it entered
+    pub fn position(self: &Self) -> Option<(f64, f64)> {
+        self.position
+    }
+
+    /// Takes the new position, returns how far the cursor moved since the last one, none at all for the first one
+    pub fn moved(self: &mut Self, position: (f64, f64)) -> (f64, f64) {
+        let delta = self.position.map_or((0.0, 0.0), |last| (position.0 - last.0, position.1 - last.1));
+        self.position = Some(position);
+        delta
+    }
+
+    pub fn left(self: &mut Self) {
+        self.position = None;
+    }
+}
+
+/// How far a finger may wander from where it touched down and still make a tap, in pixels
+pub const TAP_SLOP: f64 = 10.0;
+
+/// What the fingers on the screen amount to, passed on as the mouse would do it, see `TouchTracker`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TouchGesture {
+    /// A single finger moved this far, in pixels: the mouse moving with the left button held
+    Drag((f64, f64)),
+    /// Two fingers moved apart (positive) or together, in lines: scrolling up or down
+    Pinch(f64),
+    /// A single finger lifted close to where it touched down, there: a left click
+    Tap((f64, f64))
+}
+
+/// The fingers on the screen, from the window's `Touch` events, made into mouse-like gestures.
+/// A second finger turns a drag into a pinch (a third one is ignored), lifting either of them hands the drag
+/// to the one that stays, from where it is. Only a single finger that never strayed further than `TAP_SLOP` taps
+#[derive(Clone, Debug, Default)]
+pub struct TouchTracker {
+    fingers:  Vec<(u64, (f64, f64))>, // by id, in the order they touched down, where they were seen last
+    start:    (f64, f64),             // of the gesture's first finger
+    strayed:  bool,                   // the first finger went further than `TAP_SLOP`
+    multiple: bool                    // more than a finger touched since the gesture began
+}
+
+impl TouchTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The fingers on the screen
+    pub fn count(self: &Self) -> usize {
+        self.fingers.len()
+    }
+
+    /// Takes a finger's event, `position` in the window's pixels, returns what it amounts to, if anything
+    pub fn touch(self: &mut Self, phase: winit::event::TouchPhase, id: u64, position: (f64, f64)) -> Option<TouchGesture> {
+        use winit::event::TouchPhase;
+
+        match phase {
+            TouchPhase::Started => {
+                if self.fingers.is_empty() {
+                    *self = Self { start: position, ..Self::default() };
+                }
+
+                self.fingers.push((id, position));
+                self.multiple |= self.fingers.len() > 1;
+
+                None
+            },
+            TouchPhase::Moved => {
+                let index = self.fingers.iter().position(|(finger, _)| *finger == id)?;
+                let last  = std::mem::replace(&mut self.fingers[index].1, position);
+
+                match self.fingers.len() {
+                    1 => {
+                        self.strayed |= distance(self.start, position) > TAP_SLOP;
+                        Some(TouchGesture::Drag((position.0 - last.0, position.1 - last.1)))
+                    },
+                    _ if index < 2 => {
+                        let other  = self.fingers[1 - index].1;
+                        let spread = distance(position, other) - distance(last, other);
+                        (spread != 0.0).then_some(TouchGesture::Pinch(spread / SCROLL_PIXELS_PER_LINE))
+                    },
+                    _ => None
+                }
+            },
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                let index = self.fingers.iter().position(|(finger, _)| *finger == id)?;
+                self.fingers.remove(index);
+
+                let tapped = phase == TouchPhase::Ended && self.fingers.is_empty() && !self.multiple && !self.strayed;
+                tapped.then_some(TouchGesture::Tap(position))
+            }
+        }
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// What's held down as of the latest input events, kept by the runner and handed to `ExecDraw::update`,
+/// for what goes on for as long as a key is held rather than once per press (or per OS key repeat)
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    keys:    std::collections::HashSet<winit::keyboard::KeyCode>,
+    buttons: u32,
+    cursor:  CursorTracker
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// By the key's place on the keyboard, whatever the layout makes of it, e.g. `KeyCode::KeyW` is Z on AZERTY.
+    /// Any number of keys can be down at once
+    pub fn is_down(self: &Self, code: winit::keyboard::KeyCode) -> bool {
+        self.keys.contains(&code)
+    }
+
+    /// The mouse buttons held, a bit per button as for `ExecDraw::onmousemove`
+    pub fn buttons(self: &Self) -> u32 {
+        self.buttons
+    }
+
+    /// See `CursorTracker::position`
+    pub fn cursor(self: &Self) -> Option<(f64, f64)> {
+        self.cursor.position()
+    }
+
+    pub fn set_key(self: &mut Self, code: winit::keyboard::KeyCode, pressed: bool) {
+        if pressed {
+            self.keys.insert(code);
+        } else {
+            self.keys.remove(&code);
+        }
+    }
+
+    pub fn set_button(self: &mut Self, button: u32, pressed: bool) {
+        if pressed {
+            self.buttons |= 1 << button;
+        } else {
+            self.buttons &= !(1 << button);
+        }
+    }
+
+    /// See `CursorTracker::moved`
+    pub fn cursor_moved(self: &mut Self, position: (f64, f64)) -> (f64, f64) {
+        self.cursor.moved(position)
+    }
+
+    pub fn cursor_left(self: &mut Self) {
+        self.cursor.left();
+    }
+
+    /// Keys released while another window has the focus are never reported, so none are taken as held anymore.
+    /// Neither are the buttons, a drag started before Alt+Tab would go on once the window is back otherwise
+    pub fn focus_lost(self: &mut Self) {
+        self.keys.clear();
+        self.buttons = 0;
+    }
+}
+
+/// Pixels worth a line of scrolling, for touchpads and the like that scroll by pixels
+pub const SCROLL_PIXELS_PER_LINE: f64 = 20.0;
+
+/// The physical size a window of `size` pixels has at the scale factor `to`, keeping its logical size from `from`.
+/// What winit suggests on `ScaleFactorChanged`, and what the surface is reconfigured to right away
+pub fn rescaled_size(size: winit::dpi::PhysicalSize<u32>, from: f64, to: f64) -> winit::dpi::PhysicalSize<u32> {
+    size.to_logical::<f64>(from).to_physical(to)
+}
+
+/// A window's wheel event in lines, the same way on every platform: x positive when scrolling right (tilting the wheel right),
+/// y positive when scrolling up (away from the user). winit's deltas move the content instead, so positive x reveals
+/// what's on the left, hence x is flipped. Device events are left alone, their signs differ between the platforms
+pub fn scroll_delta_in_lines(delta: winit::event::MouseScrollDelta) -> (f64, f64) {
+    let (x, y) = match delta {
+        winit::event::MouseScrollDelta::LineDelta(x, y) => (x as f64, y as f64),
+        winit::event::MouseScrollDelta::PixelDelta(position) => (position.x / SCROLL_PIXELS_PER_LINE, position.y / SCROLL_PIXELS_PER_LINE)
+    };
+
+    (-x, y)
+}
+
+/// Device with the limits all the clocks rely on, or with the given ones instead
+pub(crate) async fn request_device(
+    adapter:  &wgpu::Adapter,
+    features: Option<wgpu::Features>,
+    limits:   Option<wgpu::Limits>
+) -> Result<(wgpu::Device, wgpu::Queue), String> {
+    // push constants are taken whenever the adapter has them, the clocks that can do without fall back to uniforms
+    let features = features.unwrap_or(wgpu::Features::empty()) | params::optional_features(adapter);
+
+    let device_limits = limits.unwrap_or_else(|| {
+        let mut device_limits = wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits());
+        if features.contains(wgpu::Features::PUSH_CONSTANTS) {
+            device_limits.max_push_constant_size = PUSH_CONSTANT_SIZE;  // Needed for push constants
+        }
+        // WebGL2 has none, the clocks that can do without fall back to uniform buffers (e.g. the polar clock's stars)
+        if adapter.limits().max_storage_buffers_per_shader_stage > 0 {
+            device_limits.max_storage_buffers_per_shader_stage = STORAGE_BUFFERS_PER_STAGE; // Needed for storage buffers
+            device_limits.max_storage_buffer_binding_size = STORAGE_BUFFER_BINDING_SIZE;  // Needed for storage buffers
+        }
+        device_limits
+    });
+
+    let info = adapter.get_info();
+    log::info!("Using adapter \"{}\" ({:?}, {:?}, driver: {} {})", info.name, info.backend, info.device_type, info.driver, info.driver_info);
+    log::debug!("Requested features: {:?}, limits: {:?}", features, device_limits);
+
+    let trace_path = logging::trace_path();
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                features,
+                limits: device_limits
+            },
+            trace_path.as_deref(),
+        )
+        .await
+        .map_err(|error| format!(
+            "Failed to create a device on \"{}\" ({:?}): {}.\nMost of the clocks need push constants, which are only available with Vulkan, Metal and DirectX 12, make sure the graphics drivers are up to date.",
+            info.name, info.backend, error
+        ))?;
+
+    return Ok((device, queue));
+}
+
+/// Report an error the program can't go on after, and exit. See `show_error` for where it's shown
+pub fn fatal_error(title: &str, message: &str) -> ! {
+    show_error(title, message);
+    std::process::exit(1);
+}
+
+/// Report an error to the user without exiting, e.g. a file that couldn't be written.
+/// GUI builds have no console on Windows, so the message is shown in a native message box there (and on macOS),
+/// elsewhere it's printed to stderr. It's logged as well, so it also ends up in the log file
+pub fn show_error(title: &str, message: &str) {
+    log::error!("{}: {}", title, message);
+    eprintln!("{}: {}", title, message);
+
+    #[cfg(target_os = "windows")]
+    {
+        #[link(name = "user32")]
+        extern "system" {
+            fn MessageBoxW(hwnd: *mut std::ffi::c_void, text: *const u16, caption: *const u16, utype: u32) -> i32;
+        }
+
+        const MB_ICONERROR: u32 = 0x10;
+        let wide = |text: &str| text.encode_utf16().chain(std::iter::once(0)).collect::<Vec<u16>>();
+
+        unsafe {
+            MessageBoxW(std::ptr::null_mut(), wide(message).as_ptr(), wide(title).as_ptr(), MB_ICONERROR);
+        }
+    }
+
+    // passed as arguments, so that nothing in the message needs escaping
+    #[cfg(target_os = "macos")]
+    let _ = std::process::Command::new("osascript")
+        .args(["-e", "on run argv", "-e", "display alert (item 1 of argv) message (item 2 of argv) as critical", "-e", "end run"])
+        .args([title, message])
+        .status();
+}
+
+/// Run the clock's future to the end: blocked on natively, spawned on the browser's event loop on the web,
+/// where blocking isn't possible (the runner never returns either way, the event loop takes over)
+pub fn launch(future: impl std::future::Future<Output = ()> + 'static) {
+    #[cfg(not(target_arch = "wasm32"))]
+    pollster::block_on(future);
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(future);
+}
+
+/// App runner.
+/// Modified version of WGPU sample boilerplate.
+/// Takes in an `ExecDraw` derived struct and calls necessary functions.
+/// The clock draws into a frame with `msaa_sample_count` samples, see `RunOptions::msaa_sample_count`
+pub async fn run<T: ExecDraw>(
+    event_loop:        winit::event_loop::EventLoop<()>,
+    window:            winit::window::Window,
+    features:          Option<wgpu::Features>,
+    msaa_sample_count: u32
+) {
+    run_with_options::<T>(event_loop, window, features, RunOptions { msaa_sample_count, ..RunOptions::default() }).await;
+}
+
+/// The clock's name, followed by its status if it has one, see `ExecDraw::title_status`
+fn title_with_status(name: &str, status: Option<String>) -> String {
+    match status {
+        Some(status) => format!("{} | {}", name, status),
+        None         => name.to_string()
+    }
+}
+
+/// The clock and the runner's passes resized to the surface as configured, then the clock updated for it
+#[allow(clippy::too_many_arguments)]
+fn resize_all<T: ExecDraw>(
+    execdraw:      &mut T,
+    msaa_frame:    &mut Option<target::MultisampledFrame>,
+    letterbox:     &mut Option<aspect::LetterboxPass>,
+    stats_overlay: &mut Option<overlay::StatsOverlay>,
+    opacity_pass:  &mut Option<widget::OpacityPass>,
+    config:        &wgpu::SurfaceConfiguration,
+    input:         &InputState,
+    device:        &wgpu::Device,
+    queue:         &wgpu::Queue
+) {
+    let (width, height) = match letterbox {
+        Some(letterbox) => {
+            letterbox.resize(config.width, config.height, device, queue);
+            letterbox.content_size()
+        },
+        None => (config.width, config.height)
+    };
+
+    if let Some(msaa_frame) = msaa_frame {
+        msaa_frame.resize((width, height), device);
+    }
+
+    execdraw.resize(width, height, device, queue);
+    execdraw.update(0.0, input, device, queue);
+
+    if let Some(stats_overlay) = stats_overlay {
+        stats_overlay.resize(config.width, config.height);
+    }
+
+    if let Some(opacity_pass) = opacity_pass {
+        opacity_pass.resize(config.width, config.height, device);
+    }
+}
+
+/// Same as `run`, but with the runner settings supplied
+pub async fn run_with_options<T: ExecDraw>(
+    event_loop: winit::event_loop::EventLoop<()>,
+    window:     winit::window::Window,
+    features:   Option<wgpu::Features>,
+    options:    RunOptions
+) {
+    let aspect_ratio = options.aspect_ratio.and_then(aspect::validate);
+    if let Some(aspect_ratio) = aspect_ratio {
+        aspect::constrain_window(&window, aspect_ratio);
+    }
+
+    let mut size = window.inner_size();
+    size.width   = size.width.max(1);
+    size.height  = size.height.max(1);
+
+    let instance = wgpu::Instance::default();
+
+    let title = window.title();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let surface = unsafe { instance.create_surface(&window) };
+    #[cfg(target_arch = "wasm32")]
+    let surface = instance.create_surface_from_canvas(web::attach_canvas(&window).unwrap_or_else(|error| fatal_error(&title, &error)));
+
+    let surface = surface
+        .unwrap_or_else(|error| fatal_error(&title, &format!("Failed to create a surface for the window: {}", error)));
+
+    // enumerating every adapter isn't free, only done when it's going to be seen
+    if log::log_enabled!(log::Level::Debug) {
+        log::debug!("Graphics adapters:\n{}", gpu_report_for_surface(&instance, Some(&surface)));
+    }
+
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference:       options.power_preference,
+            force_fallback_adapter: false,
+            compatible_surface:     Some(&surface)
+        })
+        .await
+        .unwrap_or_else(|| fatal_error(&title, "No compatible graphics adapter was found.\nThe clocks need a GPU supporting Vulkan, Metal or DirectX 12, make sure the graphics drivers are up to date."));
+
+    let swapchain_capabilities = surface.get_capabilities(&adapter);
+
+    let present_modes = swapchain_capabilities.present_modes.clone();
+    let present_mode  = supported_present_mode(options.present_mode, &present_modes);
+
+    let alpha_mode = if options.transparent {
+        [wgpu::CompositeAlphaMode::PreMultiplied, wgpu::CompositeAlphaMode::PostMultiplied]
+            .into_iter()
+            .find(|mode| swapchain_capabilities.alpha_modes.contains(mode))
+            .unwrap_or_else(|| {
+                log::warn!("Transparent surfaces aren't supported here (supported: {:?}), falling back to opaque", swapchain_capabilities.alpha_modes);
+                wgpu::CompositeAlphaMode::Auto
+            })
+    } else {
+        wgpu::CompositeAlphaMode::Auto
+    };
+
+    let swapchain_format = select_surface_format(&swapchain_capabilities);
+    if !swapchain_format.is_srgb() {
+        log::warn!("No sRGB surface format (supported: {:?}), colors will look darker", swapchain_capabilities.formats);
+    }
+
+    // timestamp queries are only needed for the GPU time shown in the stats overlay
+    let features = if options.stats && adapter.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+        Some(features.unwrap_or(wgpu::Features::empty()) | wgpu::Features::TIMESTAMP_QUERY)
+    } else {
+        features
+    };
+
+    let (device, queue) = request_device(&adapter, features, options.required_limits.clone())
+        .await
+        .unwrap_or_else(|error| fatal_error(&title, &error));
+
+    // the recorder copies the presented frames, which the surface textures must allow
+    #[cfg(feature = "recorder")]
+    let mut recorder = options.recorder.clone().and_then(|recorder_options| {
+        if swapchain_capabilities.usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            Some(recorder::Recorder::new(recorder_options))
+        } else {
+            log::warn!("The surface doesn't allow copying frames, recording is unavailable");
+            None
+        }
+    });
+
+    let unsupported_usage = options.surface_usage - swapchain_capabilities.usages;
+    if !unsupported_usage.is_empty() {
+        log::warn!("The surface doesn't support the texture usages {:?}, left out", unsupported_usage);
+    }
+
+    let mut config = wgpu::SurfaceConfiguration {
+        usage:        (options.surface_usage & swapchain_capabilities.usages) | wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format:       swapchain_format,
+        width:        size.width,
+        height:       size.height,
+        present_mode,
+        alpha_mode,
+        view_formats: vec![swapchain_format]
+    };
+
+    #[cfg(feature = "recorder")]
+    if recorder.is_some() {
+        config.usage |= wgpu::TextureUsages::COPY_SRC;
+    }
+
+    // screenshots copy the frames as well
+    let can_screenshot = swapchain_capabilities.usages.contains(wgpu::TextureUsages::COPY_SRC);
+    if can_screenshot {
+        config.usage |= wgpu::TextureUsages::COPY_SRC;
+    }
+
+    let frame_latency = options.frame_latency.map(clamp_frame_latency);
+
+    log::debug!(
+        "Configuring the surface: {}x{}, {:?}, {:?}, {:?}, {}",
+        config.width, config.height, config.format, config.present_mode, config.alpha_mode,
+        frame_latency.map_or("frame latency left to the driver".to_string(), |latency| format!("at most {} frame(s) in flight", latency))
+    );
+    surface.configure(&device, &config);
+
+    // platforms that can't tell get the dark theme, which is what the clocks were designed for
+    let theme = window.theme().unwrap_or(winit::window::Theme::Dark);
+    log::debug!("Starting with the {:?} theme", theme);
+
+    // the clock sees the snapped size only, the bars around it are the runner's
+    let mut letterbox = aspect_ratio.map(|aspect_ratio| aspect::LetterboxPass::new(&config, aspect_ratio, &device, &queue));
+    let clock_config = match &letterbox {
+        Some(letterbox) => {
+            let (width, height) = letterbox.content_size();
+            log::debug!("Rendering the clock at {}x{}, letterboxed", width, height);
+            wgpu::SurfaceConfiguration { width, height, ..config.clone() }
+        },
+        None => config.clone()
+    };
+
+    // the clock's pipelines are built for the frame's sample count in `setup`, it's fixed from then on
+    let mut msaa_sample_count = target::TargetConfig::new(swapchain_format, None)
+        .with_supported_sample_count(options.msaa_sample_count.max(1), &adapter, &device)
+        .sample_count;
+    target::set_frame_sample_count(msaa_sample_count);
+    let mut msaa_frame = target::MultisampledFrame::new((clock_config.width, clock_config.height), swapchain_format, msaa_sample_count, &device);
+    if msaa_frame.is_some() {
+        log::debug!("Multisampling the clock's frame {}x", msaa_sample_count);
+    }
+
+    let mut execdraw = T::setup(&clock_config, &adapter, &device, &queue, theme).unwrap_or_else(|error| fatal_error(&title, &error));
+
+    let name = title;
+    let mut title = title_with_status(&name, execdraw.title_status());
+    window.set_title(&title);
+
+    // after `setup`, which might've enabled the overlay itself
+    let mut stats_overlay = (options.stats || overlay::is_enabled()).then(|| overlay::StatsOverlay::new(&config, &device));
+    let mut gpu_timer = if options.stats { overlay::GpuTimer::new(&device, &queue) } else { None };
+
+    let mut opacity_pass = if options.opacity < 1.0 { widget::OpacityPass::new(&config, options.opacity, &device, &queue) } else { None };
+
+    if let Some(corner) = options.corner {
+        widget::pin_to_corner(&window, corner);
+    }
+
+    // the modifier has to be seen while the window is click-through, i.e. never focused
+    let mut widget_input = options.widget.map(|widget| {
+        event_loop.listen_device_events(winit::event_loop::DeviceEvents::Always);
+        widget::WidgetInput::new(&window, widget)
+    });
+    let mut frame_stats = FrameStats { frame_latency, ..FrameStats::default() };
+    let mut last_frame = time::Instant::now();
+    let mut frame_info: Option<FrameInfo> = None;
+    let mut last_present = None;
+    let mut frame_pacer = frame_latency.map(FramePacer::new);
+    let mut frame_limiter = options.max_fps.map(FrameLimiter::new);
+    let mut resized = false;
+    let mut visibility = WindowVisibility::new();
+    let mut last_requested_present_mode = None;
+    let mut screenshot_requested = false;
+
+    let mut scale_factor = window.scale_factor();
+
+    let mut cursor_in_window = false;
+    let mut input = InputState::new();
+    let mut touches = TouchTracker::new();
+    let mut windowed_geometry = None; // before going fullscreen, see `toggle_fullscreen`
+    let mut modifiers = winit::keyboard::ModifiersState::empty();
+
+    let idle_threshold = std::time::Duration::from_millis(options.idle_threshold_ms);
+    let mut last_significant_change = time::Instant::now();
+    let mut redraw_tracker = RedrawTracker::new();
+
+    let _ = event_loop.run(move |event, target| {
+        // Have the closure take ownership of the resources.
+        // `event_loop.run` never returns, therefore we must do this to ensure
+        // the resources are properly cleaned up.
+        let _ = (&instance, &adapter, &execdraw);
+
+        // any user input counts as a significant change, the clock may react to it visually
+        let is_key_input = matches!(
+            event,
+            winit::event::Event::WindowEvent { event: winit::event::WindowEvent::KeyboardInput { .. }, .. }
+        );
+        let is_mouse_input = matches!(
+            event,
+            winit::event::Event::DeviceEvent { event: winit::event::DeviceEvent::MouseMotion { .. }, .. } |
+            winit::event::Event::WindowEvent { event: winit::event::WindowEvent::MouseWheel  { .. }, .. } |
+            winit::event::Event::WindowEvent { event: winit::event::WindowEvent::CursorMoved { .. }, .. } |
+            winit::event::Event::DeviceEvent { event: winit::event::DeviceEvent::Button      { .. }, .. }
+        );
+
+        if is_key_input || (is_mouse_input && cursor_in_window) {
+            last_significant_change = time::Instant::now();
+            redraw_tracker.invalidate();
+            window.request_redraw();
+        }
+
+        if let winit::event::Event::NewEvents(winit::event::StartCause::ResumeTimeReached { .. }) = event {
+            // the idle interval has passed, time for the next frame
+            target.set_control_flow(winit::event_loop::ControlFlow::Wait);
+            redraw_tracker.request();
+            window.request_redraw();
+        } else if let winit::event::Event::WindowEvent { window_id: _, event, } = event {
+            match event {
+                winit::event::WindowEvent::Resized(new_size) => {
+                    // minimized, there's nothing to draw into until the window is back
+                    if !visibility.resized(new_size.width, new_size.height) {
+                        log::debug!("Minimized, drawing paused");
+                        return;
+                    }
+
+                    // Reconfigure the surface with the new size
+                    config.width = new_size.width;
+                    config.height = new_size.height;
+                    log::debug!("Reconfiguring the surface: {}x{}", config.width, config.height);
+                    surface.configure(&device, &config);
+                    // On macos the window needs to be redrawn manually after resizing
+                    resize_all(&mut execdraw, &mut msaa_frame, &mut letterbox, &mut stats_overlay, &mut opacity_pass, &config, &input, &device, &queue);
+
+                    resized = true;
+                    redraw_tracker.invalidate();
+                    window.request_redraw();
+                },
+                winit::event::WindowEvent::ScaleFactorChanged { scale_factor: new_scale_factor, mut inner_size_writer } => {
+                    // the window keeps its logical size, the image would be stretched until the next resize otherwise
+                    let new_size = rescaled_size(winit::dpi::PhysicalSize::new(config.width, config.height), scale_factor, new_scale_factor);
+                    let _ = inner_size_writer.request_inner_size(new_size);
+
+                    log::debug!("Scale factor changed from {} to {}", scale_factor, new_scale_factor);
+                    scale_factor = new_scale_factor;
+                    execdraw.onscalefactorchanged(scale_factor, &device, &queue);
+
+                    if visibility.resized(new_size.width, new_size.height) {
+                        config.width = new_size.width;
+                        config.height = new_size.height;
+                        log::debug!("Reconfiguring the surface: {}x{}", config.width, config.height);
+                        surface.configure(&device, &config);
+                        resize_all(&mut execdraw, &mut msaa_frame, &mut letterbox, &mut stats_overlay, &mut opacity_pass, &config, &input, &device, &queue);
+
+                        resized = true;
+                        redraw_tracker.invalidate();
+                        window.request_redraw();
+                    }
+                },
+                winit::event::WindowEvent::Occluded(occluded) => {
+                    visibility.occluded(occluded);
+
+                    // uncovered, the compositor may have dropped what was shown
+                    if !occluded {
+                        redraw_tracker.invalidate();
+                        window.request_redraw();
+                    }
+                },
+                winit::event::WindowEvent::CloseRequested => {
+                    #[cfg(feature = "recorder")]
+                    if let Some(recorder) = &mut recorder {
+                        recorder.stop();
+                    }
+
+                    execdraw.onexit(&window);
+                    target.exit();
+                },
+                winit::event::WindowEvent::RedrawRequested => {
+                    // nothing would be seen, the next frame comes once the window is back (see `WindowVisibility`)
+                    if visibility.is_hidden() {
+                        log::trace!("Hidden, frame skipped");
+                        target.set_control_flow(winit::event_loop::ControlFlow::Wait);
+                        return;
+                    }
+
+                    if visibility.take_pending_resize() {
+                        log::debug!("Visible again, resizing to {}x{}", config.width, config.height);
+                        resize_all(&mut execdraw, &mut msaa_frame, &mut letterbox, &mut stats_overlay, &mut opacity_pass, &config, &input, &device, &queue);
+
+                        // the time spent hidden isn't a frame, neither for the animations nor for the stats
+                        last_frame = time::Instant::now();
+                        last_present = None;
+                        resized = true;
+                        redraw_tracker.invalidate();
+                    }
+
+                    // The same frame as the one on the screen isn't drawn again, the runner waits like while idle instead.
+                    // Not while the stats overlay changes every frame or the frames are recorded
+                    #[allow(unused_mut)]
+                    let mut draws_every_frame = stats_overlay.is_some();
+                    #[cfg(feature = "recorder")]
+                    if recorder.as_ref().is_some_and(|recorder| recorder.is_recording()) {
+                        draws_every_frame = true;
+                    }
+
+                    let version = if draws_every_frame { None } else { execdraw.content_version(&time::now()) };
+                    if !redraw_tracker.should_draw(version, time::Instant::now()) {
+                        let now = time::Instant::now();
+                        let control_flow = execdraw.redraw_policy()
+                            .control_flow(now, true, idle_threshold, execdraw.next_deadline(now))
+                            .unwrap_or(winit::event_loop::ControlFlow::Wait);
+
+                        log::trace!("Unchanged, frame skipped, next one {:?}", control_flow);
+                        target.set_control_flow(control_flow);
+                        return;
+                    }
+
+                    // the frame right after a resize isn't held back, the contents would lag behind the window's edges
+                    let after_resize = std::mem::take(&mut resized);
+                    if let Some(limiter) = &mut frame_limiter {
+                        let now = time::Instant::now();
+                        match limiter.wait_until(now) {
+                            Some(wakeup) if !after_resize => {
+                                // it's due, not to be skipped once it's time
+                                redraw_tracker.invalidate();
+                                target.set_control_flow(winit::event_loop::ControlFlow::WaitUntil(wakeup));
+                                return;
+                            },
+                            _ => limiter.drawn(now)
+                        }
+                    }
+
+                    if let Some(pacer) = &mut frame_pacer {
+                        pacer.wait(&device);
+                    }
+
+                    // only new requests are looked into, an unsupported one would warn on every frame otherwise
+                    let requested = execdraw.request_present_mode();
+                    if let Some(requested) = requested.filter(|_| requested != last_requested_present_mode) {
+                        last_requested_present_mode = Some(requested);
+                        let mode = supported_present_mode(requested, &present_modes);
+                        if mode != config.present_mode {
+                            log::info!("Switching the present mode from {:?} to {:?}", config.present_mode, mode);
+                            config.present_mode = mode;
+                            surface.configure(&device, &config);
+                        }
+                    }
+
+                    if let Some(requested) = execdraw.request_msaa_sample_count().filter(|count| *count != msaa_sample_count) {
+                        log::info!("Switching the frame's multisampling from {}x to {}x", msaa_sample_count, requested);
+                        msaa_sample_count = requested;
+                        target::set_frame_sample_count(requested);
+
+                        let clock_size = letterbox.as_ref().map_or((config.width, config.height), |letterbox| letterbox.content_size());
+                        msaa_frame = target::MultisampledFrame::new(clock_size, swapchain_format, requested, &device);
+                    }
+
+                    let frame = match surface.get_current_texture() {
+                        Ok(frame) => frame,
+                        Err(error) => {
+                            match SurfaceRecovery::for_error(&error) {
+                                SurfaceRecovery::Reconfigure => {
+                                    log::warn!("{}, reconfiguring the surface", error);
+                                    surface.configure(&device, &config);
+                                },
+                                SurfaceRecovery::SkipFrame => log::warn!("{}, frame skipped", error),
+                                SurfaceRecovery::Abort => fatal_error(&name, &format!("Failed to acquire the next frame: {}", error))
+                            }
+
+                            redraw_tracker.invalidate();
+                            window.request_redraw();
+                            return;
+                        }
+                    };
+                    let view  = frame.texture.create_view(&wgpu::TextureViewDescriptor {
+                        format: Some(swapchain_format),
+                        ..wgpu::TextureViewDescriptor::default()
+                    });
+
+                    if execdraw.significant_change() {
+                        last_significant_change = time::Instant::now();
+                    }
+
+                    let now = time::Instant::now();
+                    let info = frame_info.map_or_else(FrameInfo::first, |previous| previous.next((now - last_frame).as_secs_f32()));
+                    frame_info = Some(info);
+                    frame_stats.push_frame(now - last_frame);
+                    frame_stats.gpu_time = gpu_timer.as_ref().and_then(|timer| timer.last());
+                    last_frame = now;
+
+                    execdraw.onstats(&frame_stats);
+                    execdraw.update(info.delta, &input, &device, &queue);
+
+                    if let Some(timer) = &mut gpu_timer {
+                        timer.begin(&device, &queue);
+                    }
+
+                    // with the opacity applied, the clock draws into an intermediate texture, letterboxed into another one.
+                    // Multisampled, it draws into the runner's frame first, resolved into whichever of those it would've drawn into
+                    let output      = opacity_pass.as_ref().map_or(&view, |pass| pass.target());
+                    let clock_frame = letterbox.as_ref().map_or(output, |letterbox| letterbox.target());
+                    execdraw.draw(msaa_frame.as_ref().map_or(clock_frame, |frame| frame.view()), &device, &queue, &info);
+
+                    if let Some(msaa_frame) = &msaa_frame {
+                        msaa_frame.resolve(clock_frame, &device, &queue);
+                    }
+
+                    if let Some(timer) = &mut gpu_timer {
+                        timer.end(&device, &queue);
+                    }
+
+                    if let Some(letterbox) = &letterbox {
+                        letterbox.apply(output, execdraw.letterbox_color(), &device, &queue);
+                    }
+
+                    if let Some(opacity_pass) = &opacity_pass {
+                        opacity_pass.apply(&view, &device, &queue);
+                    }
+
+                    #[cfg(feature = "recorder")]
+                    if let Some(recorder) = &mut recorder {
+                        let was_recording = recorder.is_recording();
+                        recorder.capture(&frame.texture, &device, &queue);
+
+                        // stopped by the duration limit
+                        if was_recording && !recorder.is_recording() {
+                            window.set_title(&title);
+                        }
+                    }
+
+                    if std::mem::take(&mut screenshot_requested) {
+                        execdraw.on_screenshot_key(&frame.texture, &device, &queue);
+                    }
+
+                    // after the capture, the stats are not meant to end up in recordings or screenshots
+                    if let Some(stats_overlay) = &mut stats_overlay {
+                        stats_overlay.update(&frame_stats);
+                        stats_overlay.draw(&view, &device, &queue);
+                    }
+
+                    frame.present();
+
+                    if let Some(pacer) = &mut frame_pacer {
+                        pacer.presented(&queue);
+                    }
+
+                    let presented = time::Instant::now();
+                    if let Some(last_present) = last_present {
+                        frame_stats.push_present(presented - last_present);
+                    }
+                    last_present = Some(presented);
+                    redraw_tracker.presented(version, presented);
+
+                    // While idle (or not continuous), wait for the next frame instead of spinning at the display's refresh rate
+                    let now  = time::Instant::now();
+                    let idle = last_significant_change.elapsed() > idle_threshold;
+                    match execdraw.redraw_policy().control_flow(now, idle, idle_threshold, execdraw.next_deadline(now)) {
+                        Some(control_flow) => {
+                            log::trace!("Next frame {:?}", control_flow);
+                            target.set_control_flow(control_flow);
+                        },
+                        None => {
+                            redraw_tracker.request();
+                            window.request_redraw();
+                        }
+                    }
+                },
+                winit::event::WindowEvent::KeyboardInput { event, .. } => {
+                    // before anything takes the key for itself, its release must always get through
+                    if let winit::keyboard::PhysicalKey::Code(code) = event.physical_key {
+                        input.set_key(code, event.state == winit::event::ElementState::Pressed);
+                    }
+
+                    #[cfg(feature = "recorder")]
+                    if let Some(recorder) = &mut recorder {
+                        let is_hotkey = event.state == winit::event::ElementState::Pressed && !event.repeat
+                            && event.logical_key == winit::keyboard::Key::Named(recorder::HOTKEY);
+
+                        if is_hotkey {
+                            recorder.toggle();
+                            if recorder.is_recording() {
+                                window.set_title(&format!("{} [REC]", title));
+                            } else {
+                                window.set_title(&title);
+                            }
+                        }
+                    }
+
+                    if options.debug_time && time::handle_debug_key(&event) {
+                        return;
+                    }
+
+                    if options.enable_fullscreen_toggle && is_fullscreen_toggle(&event, modifiers) {
+                        toggle_fullscreen(&window, &mut windowed_geometry);
+                        return;
+                    }
+
+                    if keys::pressed(&event, &[SCREENSHOT_KEY]).is_some() {
+                        if can_screenshot {
+                            screenshot_requested = true;
+                            redraw_tracker.invalidate();
+                        } else {
+                            log::warn!("The surface doesn't allow copying frames, screenshots are unavailable");
+                        }
+                    }
+
+                    execdraw.onkey(event, modifiers, &device, &queue);
+
+                    let status_title = title_with_status(&name, execdraw.title_status());
+                    if status_title != title {
+                        title = status_title;
+
+                        #[allow(unused_mut)]
+                        let mut shown = title.clone();
+                        #[cfg(feature = "recorder")]
+                        if recorder.as_ref().is_some_and(|recorder| recorder.is_recording()) {
+                            shown = format!("{} [REC]", title);
+                        }
+                        window.set_title(&shown);
+                    }
+                },
+                winit::event::WindowEvent::ModifiersChanged(changed) => {
+                    modifiers = changed.state();
+                    keys::set_modifiers(modifiers);
+                },
+                winit::event::WindowEvent::Focused(false) => {
+                    // the releases happen elsewhere, e.g. Alt+Tab's, and aren't always reported when it comes back
+                    modifiers = winit::keyboard::ModifiersState::empty();
+                    keys::set_modifiers(modifiers);
+                    input.focus_lost();
+                    execdraw.onfocus(false, &device, &queue);
+                },
+                winit::event::WindowEvent::Focused(true) => {
+                    execdraw.onfocus(true, &device, &queue);
+                },
+                winit::event::WindowEvent::HoveredFile(path) => {
+                    log::debug!("{} is dragged over the window", path.display());
+                },
+                winit::event::WindowEvent::DroppedFile(path) => {
+                    log::info!("Dropped {} onto the window", path.display());
+                    execdraw.ondropfile(path, &device, &queue);
+
+                    last_significant_change = time::Instant::now();
+                    redraw_tracker.invalidate();
+                    window.request_redraw();
+                },
+                winit::event::WindowEvent::ThemeChanged(theme) => {
+                    log::debug!("Switched to the {:?} theme", theme);
+                    execdraw.ontheme(theme);
+
+                    last_significant_change = time::Instant::now();
+                    redraw_tracker.invalidate();
+                    window.request_redraw();
+                },
+                winit::event::WindowEvent::MouseInput { state: winit::event::ElementState::Pressed, button: winit::event::MouseButton::Left, .. } => {
+                    // a borderless widget has no title bar to be moved by
+                    if widget_input.as_ref().is_some_and(|widget_input| widget_input.is_modifier_held()) {
+                        let _ = window.drag_window();
+                    }
+                },
+                winit::event::WindowEvent::MouseWheel { delta, .. } => {
+                    // only sent while the cursor is over the window
+                    execdraw.onmousescroll(scroll_delta_in_lines(delta), input.buttons(), modifiers, &device, &queue);
+                },
+                winit::event::WindowEvent::CursorMoved { position, .. } => {
+                    input.cursor_moved((position.x, position.y));
+                    execdraw.oncursormoved((position.x, position.y), input.buttons(), modifiers, &device, &queue);
+                },
+                winit::event::WindowEvent::Touch(touch) => {
+                    // the fingers stand in for the mouse, the left button held while dragging
+                    match touches.touch(touch.phase, touch.id, (touch.location.x, touch.location.y)) {
+                        Some(TouchGesture::Drag(delta)) => {
+                            execdraw.onmousemove(delta, input.buttons() | 1, modifiers, &device, &queue);
+                        },
+                        Some(TouchGesture::Pinch(lines)) => {
+                            execdraw.onmousescroll((0.0, lines), input.buttons(), modifiers, &device, &queue);
+                        },
+                        Some(TouchGesture::Tap(position)) => {
+                            input.cursor_moved(position);
+                            execdraw.onmousebutton_at(input.buttons() | 1, Some(position), modifiers, &device, &queue);
+                            execdraw.onmousebutton_at(input.buttons(), Some(position), modifiers, &device, &queue);
+                        },
+                        None => {}
+                    }
+
+                    last_significant_change = time::Instant::now();
+                    redraw_tracker.invalidate();
+                    window.request_redraw();
+                },
+                winit::event::WindowEvent::CursorLeft { .. } => {
+                    cursor_in_window = false;
+                    input.cursor_left();
+                },
+                winit::event::WindowEvent::CursorEntered { .. } => {
+                    cursor_in_window = true;
+                    // the position comes with the first move, the one it left at is stale
+                    input.cursor_left();
+                }
+                _ => {}
+            }
+        } else if let winit::event::Event::DeviceEvent { device_id: _, event } = event {
+            match event {
+                winit::event::DeviceEvent::MouseMotion { delta } => {
+                    if cursor_in_window {
+                        execdraw.onmousemove(delta, input.buttons(), modifiers, &device, &queue);
+                    }
+                },
+                winit::event::DeviceEvent::Button { button, state } => {
+                    input.set_button(button, state == winit::event::ElementState::Pressed);
+
+                    if cursor_in_window {
+                        execdraw.onmousebutton_at(input.buttons(), input.cursor(), modifiers, &device, &queue);
+                    }
+                },
+                winit::event::DeviceEvent::Key(key_event) => {
+                    if let Some(widget_input) = &mut widget_input {
+                        widget_input.on_device_key(&window, &key_event);
+                    }
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+/// Key that switches between borderless fullscreen and the window, as does Alt+Enter, see `RunOptions::enable_fullscreen_toggle`
+pub const FULLSCREEN_KEY: keys::KeyBinding = ("F11", "Toggle fullscreen");
+
+/// Whether the key press toggles fullscreen, `FULLSCREEN_KEY` or Alt+Enter
+fn is_fullscreen_toggle(event: &winit::event::KeyEvent, modifiers: winit::keyboard::ModifiersState) -> bool {
+    let alt_enter = event.state == winit::event::ElementState::Pressed && !event.repeat && modifiers.alt_key()
+        && event.logical_key == winit::keyboard::Key::Named(winit::keyboard::NamedKey::Enter);
+
+    alt_enter || keys::pressed(event, &[FULLSCREEN_KEY]).is_some()
+}
+
+/// Borderless fullscreen on the window's monitor, or back to the window as it was before (`windowed`, taken when it went fullscreen).
+/// Started fullscreen, there's nothing to go back to, the window takes the size it was created with.
+/// Either way, the `Resized` event that follows resizes the clock
+fn toggle_fullscreen(window: &winit::window::Window, windowed: &mut Option<config::WindowGeometry>) {
+    if window.fullscreen().is_some() {
+        window.set_fullscreen(None);
+
+        if let Some(geometry) = windowed.take() {
+            geometry.restore(window);
+        }
+
+        log::debug!("Left fullscreen");
+    } else {
+        *windowed = Some(config::WindowGeometry::from_window(window));
+        window.set_fullscreen(Some(winit::window::Fullscreen::Borderless(window.current_monitor())));
+
+        log::debug!("Went fullscreen");
+    }
+}
+
+/// Key that saves the next frame as a screenshot, see `ExecDraw::on_screenshot_key`.
+/// Not S, which the polar clock's split screen has, nor Ctrl+S, which saves the edited palettes
+pub const SCREENSHOT_KEY: keys::KeyBinding = ("F12", "Save a screenshot");
+
+/// Where a screenshot taken at `now` goes by default, e.g. `screenshot-20240309-123456.png`
+pub fn screenshot_path<Tz: chrono::TimeZone>(now: &chrono::DateTime<Tz>) -> String where Tz::Offset: std::fmt::Display {
+    format!("screenshot-{}.png", now.format("%Y%m%d-%H%M%S"))
+}
+
+/// Save the top-left `width` x `height` pixels of a texture as a PNG file, e.g. a surface texture before it's presented.
+/// The texture must be BGRA8 or RGBA8 with `COPY_SRC` usage. Waits for the GPU to finish everything submitted so far
+pub async fn capture_frame_to_png(
+    device: &wgpu::Device,
+    queue:  &wgpu::Queue,
+    source: &wgpu::Texture,
+    width:  u32,
+    height: u32,
+    path:   &str
+) -> std::io::Result<()> {
+    let frame = headless::read_texture_region_rgba(source, width, height, device, queue).map_err(std::io::Error::other)?;
+    frame.save_with_format(path, image::ImageFormat::Png).map_err(std::io::Error::other)
+}
+
+pub fn load_png_rgba8(path: &str) -> Result<(u32, u32, Vec<u8>), String> {
+    let started = time::Instant::now();
+    let dynimage = ImageReader::open(path)
+        .map_err(|error| format!("Can't open the texture {}: {}", path, error))?
+        .decode()
+        .map_err(|error| format!("Can't decode the texture {}: {}", path, error))?;
+    let rgba8 = dynimage.to_rgba8();
+    let raw = rgba8.as_raw();
+
+    log::debug!("Loaded {} ({}x{}) in {:?}", path, rgba8.width(), rgba8.height(), started.elapsed());
+
+    Ok((rgba8.width(), rgba8.height(), raw.clone()))
+}
+
+/// Same as `load_png_rgba8` for a file already in memory, e.g. embedded with `include_bytes!` or downloaded.
+/// The format is guessed from the data
+pub fn decode_rgba8(data: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let dynimage = ImageReader::new(std::io::Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|error| format!("Can't read the texture: {}", error))?
+        .decode()
+        .map_err(|error| format!("Can't decode the texture: {}", error))?;
+    let rgba8 = dynimage.to_rgba8();
+
+    Ok((rgba8.width(), rgba8.height(), rgba8.into_raw()))
+}
+
+/// Read and compile a WGSL file, e.g. from the clock's resources folder
+pub fn load_shader(device: &wgpu::Device, path: &std::path::Path) -> Result<wgpu::ShaderModule, String> {
+    let source = read_shader_source(path)?;
+
+    Ok(device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  path.file_name().and_then(|name| name.to_str()),
+        source: wgpu::ShaderSource::Wgsl(source.into())
+    }))
+}
+
+/// The preprocessed source `load_shader` compiles, for clocks adapting it further first (see `SmallParams::shader_source`).
+/// Read from the file on native, the web build has them embedded instead (see `web::EMBEDDED_SHADERS`)
+pub fn read_shader_source(path: &std::path::Path) -> Result<String, String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let source = &std::fs::read_to_string(path).map_err(|error| format!("Can't read the shader {}: {}", path.display(), error))?;
+    #[cfg(target_arch = "wasm32")]
+    let source = web::embedded_shader(path).ok_or_else(|| format!("The shader {} isn't embedded into the web build", path.display()))?;
+
+    preprocess_shader(source).map_err(|error| format!("Can't preprocess the shader {}: {}", path.display(), error))
+}
+
+/// WGSL snippets shared by the shaders, by the name they're included with
+const SHADER_INCLUDES: &[(&str, &str)] = &[
+    ("color.wgsl",  include_str!("color.wgsl")),
+    ("digits.wgsl", include_str!("digits.wgsl")),
+    ("blur.wgsl",   include_str!("blur.wgsl"))
+];
+
+/// Expand the `#include "name"` lines (anything after the name is ignored) into the snippets of `SHADER_INCLUDES`.
+/// Done by `load_shader`, shaders compiled from sources of their own need it too if they include anything
+pub fn preprocess_shader(source: &str) -> Result<String, String> {
+    let mut expanded = String::with_capacity(source.len());
+
+    for line in source.lines() {
+        match line.trim_start().strip_prefix("#include") {
+            Some(rest) => {
+                let name = rest.trim_start().strip_prefix('"').and_then(|rest| rest.split_once('"')).map(|(name, _)| name);
+                let snippet = name
+                    .and_then(|name| SHADER_INCLUDES.iter().find(|(include, _)| *include == name))
+                    .ok_or_else(|| format!("Unknown include: {}", line.trim()))?;
+
+                expanded.push_str(snippet.1);
+            },
+            None => {
+                expanded.push_str(line);
+                expanded.push('\n');
+            }
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Basic read-only texture resource made from pixel data
+pub struct ResourceTexture {
+    pub texture: wgpu::Texture,
+    pub view:    wgpu::TextureView,
+    pub width:   u32,
+    pub height:  u32
+}
+
+impl ResourceTexture {
+    pub fn new(path: &str, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self, String> {
+        let (width, height, data) = load_png_rgba8(path)?;
+
+        Ok(Self::upload_rgba8(width, height, data.as_bytes(), device, queue))
+    }
+
+    /// From pixels already decoded, 4 bytes per pixel row by row, e.g. generated ones
+    pub fn from_rgba8(width: u32, height: u32, data: &[u8], device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        assert_eq!(data.len(), width as usize * height as usize * 4, "RGBA8 data of the wrong size for {}x{}", width, height);
+
+        Self::upload_rgba8(width, height, data, device, queue)
+    }
+
+    /// From an image file's bytes (PNG or any format the image crate is built with), without touching the file system
+    pub fn from_encoded_bytes(data: &[u8], device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self, String> {
+        let (width, height, data) = decode_rgba8(data)?;
+
+        Ok(Self::upload_rgba8(width, height, &data, device, queue))
+    }
+
+    fn upload_rgba8(width: u32, height: u32, data: &[u8], device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let texture = device.create_texture_with_data(queue, &wgpu::TextureDescriptor {
+            label:           None,
+            size:            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count:    1,
+            dimension:       wgpu::TextureDimension::D2,
+            format:          wgpu::TextureFormat::Rgba8Unorm,
+            usage:           wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats:    &[]
+        }, data);
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self { texture, view, width, height }
+    }
+
+    /// Same as `new`, with the full mip chain down to 1x1 generated on the GPU, for textures drawn smaller than they are.
+    /// Binds and samples the same way, with `BasicFilteringSampler` blending the levels
+    pub fn new_with_mipmaps(path: &str, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self, String> {
+        let (width, height, data) = load_png_rgba8(path)?;
+        let mip_level_count = Self::mip_level_count(width, height);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label:           None,
+            size:            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count,
+            sample_count:    1,
+            dimension:       wgpu::TextureDimension::D2,
+            format:          wgpu::TextureFormat::Rgba8Unorm,
+            usage:           wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
+            view_formats:    &[]
+        });
+
+        queue.write_texture(
+            texture.as_image_copy(),
+            &data,
+            wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(width * 4), rows_per_image: None },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 }
+        );
+
+        Self::generate_mipmaps(&texture, device, queue);
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Ok(Self { texture, view, width, height })
+    }
+
+    /// floor(log2(max(width, height))) + 1, the levels from full size down to 1x1
+    pub fn mip_level_count(width: u32, height: u32) -> u32 {
+        u32::BITS - width.max(height).max(1).leading_zeros()
+    }
+
+    /// Each level below the first drawn from the one above it
+    fn generate_mipmaps(texture: &wgpu::Texture, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label:   None,
+            entries: &[ Self::default_layout_entry(0), BasicFilteringSampler::default_layout_entry(1) ]
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Mipmaps"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("mipmap.wgsl").into())
+        });
+
+        let quad    = FullscreenQuadPipeline::new(device, &shader, "fs_main", &[ &layout ], &[], texture.format());
+        let sampler = BasicFilteringSampler::new(device);
+
+        let level_view = |level: u32| texture.create_view(&wgpu::TextureViewDescriptor {
+            base_mip_level:  level,
+            mip_level_count: Some(1),
+            ..Default::default()
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Mipmaps") });
+
+        for level in 1..texture.mip_level_count() {
+            let above = level_view(level - 1);
+            let view  = level_view(level);
+
+            let bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label:   None,
+                layout:  &layout,
+                entries: &[ wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&above) }, sampler.get_entry(1) ]
+            });
+
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    Some("Mipmap level"),
+                depth_stencil_attachment: None,
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                    view:           &view,
+                    resolve_target: None,
+                    ops:            wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), store: wgpu::StoreOp::Store }
+                })]
+            });
+
+            quad.draw(&mut rpass, &[ &bindgroup ], &[]);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Cubemap made from six square RGBA8 faces in the +X, -X, +Y, -Y, +Z, -Z order, e.g. for environment reflections
+    pub fn from_cube_faces(size: u32, faces: &[u8], device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let texture = device.create_texture_with_data(queue, &wgpu::TextureDescriptor {
+            label:           None,
+            size:            wgpu::Extent3d { width: size, height: size, depth_or_array_layers: 6 },
+            mip_level_count: 1,
+            sample_count:    1,
+            dimension:       wgpu::TextureDimension::D2,
+            format:          wgpu::TextureFormat::Rgba8Unorm,
+            usage:           wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats:    &[]
+        }, faces);
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        Self { texture, view, width: size, height: size }
+    }
+
+    /// 2D array with a layer per file, in the given order, e.g. the day and night variants of a lightmap.
+    /// The files must all be of the same size
+    pub fn array(paths: &[&str], device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self, String> {
+        let mut layers = paths.iter().map(|path| load_png_rgba8(path).map(|layer| (*path, layer)));
+
+        let (first, (width, height, mut data)) = layers.next().ok_or("A texture array needs at least one layer".to_string())??;
+
+        for layer in layers {
+            let (path, (layer_width, layer_height, layer_data)) = layer?;
+
+            if (layer_width, layer_height) != (width, height) {
+                return Err(format!(
+                    "The layers of a texture array must be of the same size, {} is {}x{} while {} is {}x{}",
+                    path, layer_width, layer_height, first, width, height
+                ));
+            }
+
+            data.extend_from_slice(&layer_data);
+        }
+
+        let texture = device.create_texture_with_data(queue, &wgpu::TextureDescriptor {
+            label:           None,
+            size:            wgpu::Extent3d { width, height, depth_or_array_layers: paths.len() as u32 },
+            mip_level_count: 1,
+            sample_count:    1,
+            dimension:       wgpu::TextureDimension::D2,
+            format:          wgpu::TextureFormat::Rgba8Unorm,
+            usage:           wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats:    &[]
+        }, &data);
+
+        // a single layer would make a plain 2D view by default
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+
+        Ok(Self { texture, view, width, height })
+    }
+
+    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding,
+            resource: wgpu::BindingResource::TextureView(&self.view)
+        }
+    }
+
+    /// For the textures made by `new` and the like, mipmapped or not: every level is filterable
+    pub fn default_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+        Self::layout_entry(binding, wgpu::TextureViewDimension::D2)
+    }
+
+    /// For the arrays made by `array`
+    pub fn array_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+        Self::layout_entry(binding, wgpu::TextureViewDimension::D2Array)
+    }
+
+    /// For the cubemaps made by `from_cube_faces`
+    pub fn cube_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+        Self::layout_entry(binding, wgpu::TextureViewDimension::Cube)
+    }
+
+    fn layout_entry(binding: u32, view_dimension: wgpu::TextureViewDimension) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled:   false,
+                sample_type:    wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension
+            },
+            count: None
+        }
+    }
+}
+
+/// Basic Linear filtering sampler with edge clipping
+pub struct BasicFilteringSampler {
+    pub sampler: wgpu::Sampler
+}
+
+impl BasicFilteringSampler {
+    pub fn new(device: &wgpu::Device) -> Self {
+        Self::with_address_mode(device, wgpu::AddressMode::ClampToEdge)
+    }
+
+    /// Same filtering, other edge handling, e.g. `Repeat` for tiling textures
+    pub fn with_address_mode(device: &wgpu::Device, address_mode: wgpu::AddressMode) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: None,
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter:     wgpu::FilterMode::Linear,
+            min_filter:     wgpu::FilterMode::Linear,
+            mipmap_filter:  wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self { sampler }
+    }
+
+    /// Only samples the mip levels within [min_lod..max_lod], see `clamp_lod_range`
+    pub fn new_with_lod_clamp(device: &wgpu::Device, min_lod: f32, max_lod: f32) -> Self {
+        let (lod_min_clamp, lod_max_clamp) = clamp_lod_range(min_lod, max_lod);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: None,
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter:     wgpu::FilterMode::Linear,
+            min_filter:     wgpu::FilterMode::Linear,
+            mipmap_filter:  wgpu::FilterMode::Linear,
+            lod_min_clamp,
+            lod_max_clamp,
+            ..Default::default()
+        });
+
+        Self { sampler }
+    }
+
+    /// Always samples the given mip level (blended with the next one for fractions), e.g. 0.0 for the sharpest
+    pub fn new_fixed_lod(device: &wgpu::Device, lod: f32) -> Self {
+        Self::new_with_lod_clamp(device, lod, lod)
+    }
+
+    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding,
+            resource: wgpu::BindingResource::Sampler(&self.sampler)
+        }
+    }
+
+    pub fn default_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None
+        }
+    }
+}
+
+/// The LOD range a sampler can take: wgpu refuses negative or reversed ones (and NaNs), so the minimum is raised to 0
+/// and the maximum to the minimum, with a warning
+pub fn clamp_lod_range(min_lod: f32, max_lod: f32) -> (f32, f32) {
+    let min_clamped = if min_lod >= 0.0 { min_lod } else { 0.0 };
+    let max_clamped = if max_lod >= min_clamped { max_lod } else { min_clamped };
+
+    if (min_clamped, max_clamped) != (min_lod, max_lod) {
+        log::warn!("LOD range [{}..{}] clamped to [{}..{}]", min_lod, max_lod, min_clamped, max_clamped);
+    }
+
+    (min_clamped, max_clamped)
+}
+
+/// Rewritable uniform buffer for a single struct/variable
+pub struct SingleUniformBuffer {
+    pub buffer: wgpu::Buffer,
+    pub stages: wgpu::ShaderStages
+}
+
+impl SingleUniformBuffer {
+    pub fn new<T>(device: &wgpu::Device, stages: wgpu::ShaderStages) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size:  std::mem::size_of::<T>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+
+        Self { buffer, stages }
+    }
+
+    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding,
+            resource: self.buffer.as_entire_binding()
+        }
+    }
+
+    pub fn default_layout_entry(binding: u32, sub: &Self) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: sub.stages,
+            ty: wgpu::BindingType::Buffer {
+                ty:                 wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size:   None
+            },
+            count: None
+        }
+    }
+}
+
+/// Read only storage buffer for array data.
+/// Either owns a whole buffer, or is a view of a sub-range of a buffer shared with others
+pub struct ImmutableStorageBuffer {
+    pub buffer: Arc<wgpu::Buffer>,
+    pub stages: wgpu::ShaderStages,
+    pub offset: u64,
+    pub size:   Option<NonZeroU64> // None => till the end of the buffer
+}
+
+impl ImmutableStorageBuffer {
+    #[allow(clippy::arc_with_non_send_sync)] // wgpu's buffers aren't Send on the web, the Arc is for sharing them, not threads
+    pub fn new(device: &wgpu::Device, stages: wgpu::ShaderStages, init: &[u8]) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label:    None,
+            contents: init,
+            usage:    wgpu::BufferUsages::STORAGE
+        });
+
+        Self { buffer: Arc::new(buffer), stages, offset: 0, size: None }
+    }
+
+    /// Bind only `size` bytes of a shared buffer starting from `offset`.
+    /// The offset must be a multiple of the device's `min_storage_buffer_offset_alignment` (256 at most)
+    pub fn view(buffer: Arc<wgpu::Buffer>, stages: wgpu::ShaderStages, offset: u64, size: u64) -> Self {
+        let size = NonZeroU64::new(size).expect("Storage buffer views can't be empty");
+        Self { buffer, stages, offset, size: Some(size) }
+    }
+
+    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &self.buffer,
+                offset: self.offset,
+                size:   self.size
+            })
+        }
+    }
+
+    /// A view's size is also enforced as the minimum binding size, so that any bind group
+    /// with a smaller range is rejected at creation instead of reading out of bounds in the shader
+    pub fn default_layout_entry(binding: u32, sub: &Self) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: sub.stages,
+            ty: wgpu::BindingType::Buffer {
+                ty:                 wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size:   sub.size
+            },
+            count: None
+        }
+    }
+}
+
+/// Generate the gblur look-up table:
+/// 1. The actual table storage buffer containing weights and offsets.
+/// 2. A single value uniform buffer for the count (table length).
+///
+/// Both welded into a single bindgroup, for the shaders including blur.wgsl (as `blur_table` and `blur_table_size`).
+/// (Could've put the count in the storage buffer at index 0, what was I thinking then? :P)
+pub fn create_blur_table_bindgroup(
+    radius:     i32,
+    sigma:      f32,
+    linear:     bool,
+    correction: bool,
+    device:     &wgpu::Device,
+    queue:      &wgpu::Queue
+) -> (wgpu::BindGroup, wgpu::BindGroupLayout) {
+    let weights_and_offsets = clocks::digital::create_blur_weights_and_offsets(radius, sigma, linear, correction).unwrap();
+
+    let stages = wgpu::ShaderStages::FRAGMENT;
+
+    let storage = ImmutableStorageBuffer::new(
+        device, stages,
+        cast_slice_to_u8_slice(weights_and_offsets.as_slice())
+    );
+
+    let uniform = SingleUniformBuffer::new::<u32>(device, stages);
+
+    let bindgroup_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: None,
+        entries: &[
+            ImmutableStorageBuffer::default_layout_entry(0, &storage),
+            SingleUniformBuffer::default_layout_entry(1, &uniform)
+        ]
+    });
+
+    let bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label:   None,
+        layout:  &bindgroup_layout,
+        entries: &[
+            storage.get_entry(0),
+            uniform.get_entry(1)
+        ]
+    });
+
+    let data: u32 = weights_and_offsets.len() as u32;
+    queue.write_buffer(&uniform.buffer, 0, cast_struct_to_u8_slice(&data));
+
+    (bindgroup, bindgroup_layout)
+}
+
+/// Read-write storage buffer for array data, rewritable from the CPU too, e.g. for compute shaders' working data.
+/// Shaders writing into it from the vertex stage need `VERTEX_WRITABLE_STORAGE`,
+/// from the fragment stage the `FRAGMENT_WRITABLE_STORAGE` downlevel flag
+pub struct MutableStorageBuffer {
+    pub buffer:   wgpu::Buffer,
+    pub stages:   wgpu::ShaderStages,
+    pub capacity: usize // in elements
+}
+
+impl MutableStorageBuffer {
+    /// Room for `capacity` elements of `T`, zeroed
+    pub fn new<T>(device: &wgpu::Device, stages: wgpu::ShaderStages, capacity: usize) -> Self {
+        let size = (capacity * std::mem::size_of::<T>()) as u64;
+        assert!(size > 0, "Storage buffers can't be empty");
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size:  size.div_ceil(wgpu::COPY_BUFFER_ALIGNMENT) * wgpu::COPY_BUFFER_ALIGNMENT,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+
+        Self { buffer, stages, capacity }
+    }
+
+    /// Overwrite the buffer from its start, the rest is left as it was.
+    /// `data` must fit, and be a multiple of 4 bytes long
+    pub fn write(self: &Self, queue: &wgpu::Queue, data: &[u8]) {
+        assert!(data.len() as u64 <= self.buffer.size(), "{} bytes don't fit into a storage buffer of {}", data.len(), self.buffer.size());
+        queue.write_buffer(&self.buffer, 0, data);
+    }
+
+    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding,
+            resource: self.buffer.as_entire_binding()
+        }
+    }
+
+    pub fn default_layout_entry(binding: u32, sub: &Self) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: sub.stages,
+            ty: wgpu::BindingType::Buffer {
+                ty:                 wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size:   NonZeroU64::new(sub.buffer.size())
+            },
+            count: None
+        }
+    }
+
+    /// For shaders that only read it, the CPU being the one to rewrite it. Needs no downlevel flags in any stage
+    pub fn read_only_layout_entry(binding: u32, sub: &Self) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            ty: wgpu::BindingType::Buffer {
+                ty:                 wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size:   NonZeroU64::new(sub.buffer.size())
+            },
+            ..Self::default_layout_entry(binding, sub)
+        }
+    }
+}
+
+/// Per-instance vertex data, e.g. transforms, colors or digit indices, stepped through once per instance
+/// instead of once per vertex. `T` must be `#[repr(C)]`, its fields are read as laid out
+pub struct InstanceBuffer<T: Copy> {
+    pub buffer:   wgpu::Buffer,
+    pub capacity: usize, // in instances
+    count:        usize,
+    _marker:      std::marker::PhantomData<T>
+}
+
+impl<T: Copy> InstanceBuffer<T> {
+    /// Room for as many instances as given, and no more
+    pub fn new(device: &wgpu::Device, instances: &[T]) -> Self {
+        assert!(std::mem::size_of_val(instances) > 0, "Instance buffers can't be empty");
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label:    None,
+            contents: cast_slice_to_u8_slice(instances),
+            usage:    wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST
+        });
+
+        Self { buffer, capacity: instances.len(), count: instances.len(), _marker: std::marker::PhantomData }
+    }
+
+    /// Replace the instances, at most `capacity` of them, `slice` and `count` then cover only these
+    pub fn update(self: &mut Self, queue: &wgpu::Queue, instances: &[T]) {
+        assert!(instances.len() <= self.capacity, "{} instances don't fit into a buffer of {}", instances.len(), self.capacity);
+
+        queue.write_buffer(&self.buffer, 0, cast_slice_to_u8_slice(instances));
+        self.count = instances.len();
+    }
+
+    /// Number of instances last written, the end of the instance range to draw
+    pub fn count(self: &Self) -> u32 {
+        self.count as u32
+    }
+
+    /// The instances last written, for `RenderPass::set_vertex_buffer`
+    pub fn slice(self: &Self) -> wgpu::BufferSlice<'_> {
+        self.buffer.slice(..(self.count * std::mem::size_of::<T>()) as u64)
+    }
+
+    /// Layout of one `T` per instance, `attributes` locating its fields.
+    /// Their shader locations mustn't clash with those of the per-vertex buffers in the same pipeline
+    pub fn as_vertex_buffer_layout(attributes: &[wgpu::VertexAttribute]) -> wgpu::VertexBufferLayout<'_> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<T>() as wgpu::BufferAddress,
+            step_mode:    wgpu::VertexStepMode::Instance,
+            attributes
+        }
+    }
+}
+
+/// Texture that can be rendered on in a pass and sampled from in a subsequent pass
+/// Usable for both color or depth targets
+/// Single sample, unless made by `multisampled`
 pub struct RenderTexture {
-    pub texture: wgpu::Texture,
-    pub view:    wgpu::TextureView,
-    pub format:  wgpu::TextureFormat,
-    pub width:   u32,
-    pub height:  u32
+    pub texture:      wgpu::Texture,
+    pub view:         wgpu::TextureView,
+    pub format:       wgpu::TextureFormat,
+    pub width:        u32,
+    pub height:       u32,
+    pub sample_count: u32
+}
+
+impl RenderTexture {
+    pub fn new(
+        size: (u32, u32), format: wgpu::TextureFormat,
+        bindable: bool, device: &wgpu::Device
+    ) -> Self {
+        let usage = match bindable {
+            false => wgpu::TextureUsages::RENDER_ATTACHMENT, // usually depth only targets
+            true  => wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING // usually color targets
+        };
+
+        Self::create(size, format, 1, usage, device)
+    }
+
+    /// Offscreen color output of a pass set up with `config`, to be sampled later.
+    /// Always single-sampled, multisampled passes resolve into it (see `target::ColorTarget`)
+    pub fn for_target(size: (u32, u32), config: &target::TargetConfig, device: &wgpu::Device) -> Self {
+        Self::new(size, config.color_format, true, device)
+    }
+
+    /// Drawn into only, a multisampled texture can't be sampled like the others
+    pub fn multisampled(size: (u32, u32), format: wgpu::TextureFormat, sample_count: u32, device: &wgpu::Device) -> Self {
+        Self::create(size, format, sample_count, wgpu::TextureUsages::RENDER_ATTACHMENT, device)
+    }
+
+    fn create(
+        size: (u32, u32), format: wgpu::TextureFormat, sample_count: u32,
+        usage: wgpu::TextureUsages, device: &wgpu::Device
+    ) -> Self {
+        let (width, height) = size;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label:           None,
+            size:            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count,
+            dimension:       wgpu::TextureDimension::D2,
+            view_formats:    &[],
+            usage,
+            format
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self { texture, view, format, width, height, sample_count }
+    }
+
+    pub fn is_multisampled(self: &Self) -> bool {
+        self.sample_count > 1
+    }
+
+    pub fn get_layout_entry(self: &Self, binding: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled:   false,
+                sample_type:    wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2
+            },
+            count: None
+        }
+    }
+
+    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding,
+            resource: wgpu::BindingResource::TextureView(&self.view)
+        }
+    }
+
+    pub fn default_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled:   false,
+                sample_type:    wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2
+            },
+            count: None
+        }
+    }
+}
+
+/// WGSL of a vertex shader (`vs_main`) covering the whole target with a quad, 4 vertices drawn as a triangle strip
+/// and no vertex buffer. The UV coordinates run from (0, 0) at the top left to (1, 1) at the bottom right, at location 0
+pub fn fullscreen_quad_vertex_shader_source() -> &'static str {
+    "struct FullscreenQuadOutput {
+    @builtin(position) pos: vec4f,
+    @location(0)        uv: vec2f
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenQuadOutput {
+    var vto: FullscreenQuadOutput;
+    vto.uv  = vec2f(f32(index & 1u), f32(index >> 1u));
+    vto.pos = vec4f(vto.uv.x * 2.0 - 1.0, 1.0 - vto.uv.y * 2.0, 0.0, 1.0);
+
+    return vto;
+}
+"
+}
+
+/// Pipeline for full screen passes (post-processing, backgrounds), only the fragment shader is up to the clock.
+/// Its input is the UV coordinates at location 0, see `fullscreen_quad_vertex_shader_source()`
+pub struct FullscreenQuadPipeline {
+    pub pipeline:         wgpu::RenderPipeline,
+    pub config:           target::TargetConfig,
+    push_constant_stages: wgpu::ShaderStages
+}
+
+impl FullscreenQuadPipeline {
+    pub fn new(
+        device:               &wgpu::Device,
+        fragment_module:      &wgpu::ShaderModule,
+        fragment_entry:       &str,
+        bind_group_layouts:   &[&wgpu::BindGroupLayout],
+        push_constant_ranges: &[wgpu::PushConstantRange],
+        surface_format:       wgpu::TextureFormat
+    ) -> Self {
+        Self::for_target(
+            device, fragment_module, fragment_entry, bind_group_layouts, push_constant_ranges,
+            &target::TargetConfig::new(surface_format, None)
+        )
+    }
+
+    /// Blended with what's in the target already, e.g. an overlay composited over the frame
+    pub fn blended(
+        device:               &wgpu::Device,
+        fragment_module:      &wgpu::ShaderModule,
+        fragment_entry:       &str,
+        bind_group_layouts:   &[&wgpu::BindGroupLayout],
+        push_constant_ranges: &[wgpu::PushConstantRange],
+        surface_format:       wgpu::TextureFormat,
+        blend:                wgpu::BlendState
+    ) -> Self {
+        Self::create(
+            device, fragment_module, fragment_entry, bind_group_layouts, push_constant_ranges,
+            &target::TargetConfig::new(surface_format, None), Some(blend)
+        )
+    }
+
+    /// `blended`, for passes with depth and/or multisampling, e.g. a shadow laid over the background
+    pub fn blended_for_target(
+        device:               &wgpu::Device,
+        fragment_module:      &wgpu::ShaderModule,
+        fragment_entry:       &str,
+        bind_group_layouts:   &[&wgpu::BindGroupLayout],
+        push_constant_ranges: &[wgpu::PushConstantRange],
+        config:               &target::TargetConfig,
+        blend:                wgpu::BlendState
+    ) -> Self {
+        Self::create(device, fragment_module, fragment_entry, bind_group_layouts, push_constant_ranges, config, Some(blend))
+    }
+
+    /// For passes with depth and/or multisampling, e.g. a background drawn before the scene.
+    /// The depth is neither tested nor written, so the quad doesn't hide anything drawn after it
+    pub fn for_target(
+        device:               &wgpu::Device,
+        fragment_module:      &wgpu::ShaderModule,
+        fragment_entry:       &str,
+        bind_group_layouts:   &[&wgpu::BindGroupLayout],
+        push_constant_ranges: &[wgpu::PushConstantRange],
+        config:               &target::TargetConfig
+    ) -> Self {
+        Self::create(device, fragment_module, fragment_entry, bind_group_layouts, push_constant_ranges, config, None)
+    }
+
+    fn create(
+        device:               &wgpu::Device,
+        fragment_module:      &wgpu::ShaderModule,
+        fragment_entry:       &str,
+        bind_group_layouts:   &[&wgpu::BindGroupLayout],
+        push_constant_ranges: &[wgpu::PushConstantRange],
+        config:               &target::TargetConfig,
+        blend:                Option<wgpu::BlendState>
+    ) -> Self {
+        let vertex_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Fullscreen quad"),
+            source: wgpu::ShaderSource::Wgsl(fullscreen_quad_vertex_shader_source().into())
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts,
+            push_constant_ranges
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label:         None,
+            layout:        Some(&pipeline_layout),
+            depth_stencil: config.depth_stencil_state(false, wgpu::CompareFunction::Always),
+            multisample:   config.multisample_state(),
+            multiview:     None,
+            vertex: wgpu::VertexState {
+                module:      &vertex_module,
+                entry_point: "vs_main",
+                buffers:     &[]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module:      fragment_module,
+                entry_point: fragment_entry,
+                targets:     &[ Some(wgpu::ColorTargetState { format: config.color_format, blend, write_mask: wgpu::ColorWrites::ALL }) ]
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology:     wgpu::PrimitiveTopology::TriangleStrip,
+                cull_mode:    None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..Default::default()
+            }
+        });
+
+        let push_constant_stages = push_constant_ranges.iter().fold(wgpu::ShaderStages::NONE, |stages, range| stages | range.stages);
+
+        Self { pipeline, config: *config, push_constant_stages }
+    }
+
+    /// Sets the pipeline, the bind groups (in order, from group 0) and the push constants (if any), then draws the quad
+    pub fn draw<'a>(self: &'a Self, rpass: &mut wgpu::RenderPass<'a>, bind_groups: &[&'a wgpu::BindGroup], push_constants: &[u8]) {
+        rpass.set_pipeline(&self.pipeline);
+
+        for (index, bind_group) in bind_groups.iter().enumerate() {
+            rpass.set_bind_group(index as u32, bind_group, &[]);
+        }
+
+        if !push_constants.is_empty() {
+            rpass.set_push_constants(self.push_constant_stages, 0, push_constants);
+        }
+
+        rpass.draw(0..4, 0..1);
+    }
+
+    /// A pass drawing into `destination` alone, cleared to black first, as the post-processing passes do
+    pub fn begin_pass<'a>(encoder: &'a mut wgpu::CommandEncoder, destination: &'a wgpu::TextureView, label: &str) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label:                    Some(label),
+            depth_stencil_attachment: None,
+            timestamp_writes:         None,
+            occlusion_query_set:      None,
+            color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                view:           destination,
+                resolve_target: None,
+                ops:            wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store }
+            })]
+        })
+    }
+}
+
+/// What a post-processing pass reads the frame through: the texture it's rendered into at binding 0 and a sampler at 1,
+/// followed by the pass' own entries (e.g. a uniform) if any
+pub struct SourceBinding {
+    pub layout: wgpu::BindGroupLayout,
+    sampler:    BasicFilteringSampler
+}
+
+impl SourceBinding {
+    /// `entries` are the pass' own, from binding 2 on
+    pub fn new(device: &wgpu::Device, entries: &[wgpu::BindGroupLayoutEntry]) -> Self {
+        let entries = [ RenderTexture::default_layout_entry(0), BasicFilteringSampler::default_layout_entry(1) ].into_iter()
+            .chain(entries.iter().copied())
+            .collect::<Vec<_>>();
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor { label: None, entries: &entries });
+
+        Self { layout, sampler: BasicFilteringSampler::new(device) }
+    }
+
+    /// The source for a pass' `apply`, the texture the frame is rendered into (as in `RenderTexture::new(.., true, ..)`).
+    /// `entries` are the pass' own, as laid out in `new`
+    pub fn bindgroup(self: &Self, source: &RenderTexture, entries: &[wgpu::BindGroupEntry], device: &wgpu::Device) -> wgpu::BindGroup {
+        let entries = [ source.get_entry(0), self.sampler.get_entry(1) ].into_iter()
+            .chain(entries.iter().cloned())
+            .collect::<Vec<_>>();
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor { label: None, layout: &self.layout, entries: &entries })
+    }
+}
+
+/// FXAA 3.11 post-processing: smooths the edges of a rendered frame in screen space,
+/// which is way cheaper than multisampling and needs no change to the render targets.
+/// The frame is drawn into a texture first, which `apply` then copies to the destination with the edges blended
+#[cfg(feature = "fxaa")]
+pub struct FxaaPass {
+    quad:   FullscreenQuadPipeline,
+    source: SourceBinding
+}
+
+#[cfg(feature = "fxaa")]
+impl FxaaPass {
+    /// The destination's format is `surface_format`
+    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        Self::for_target(device, &target::TargetConfig::new(surface_format, None))
+    }
+
+    /// For multisampled destinations, e.g. the runner's frame (`target::TargetConfig::frame`)
+    pub fn for_target(device: &wgpu::Device, config: &target::TargetConfig) -> Self {
+        let source = SourceBinding::new(device, &[]);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("FXAA"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("fxaa.wgsl").into())
+        });
+
+        let quad = FullscreenQuadPipeline::for_target(device, &shader, "fs_main", &[ &source.layout ], &[], config);
+
+        Self { quad, source }
+    }
+
+    /// The source for `apply`, see `SourceBinding::bindgroup`
+    pub fn source_bindgroup(self: &Self, source: &RenderTexture, device: &wgpu::Device) -> wgpu::BindGroup {
+        self.source.bindgroup(source, &[], device)
+    }
+
+    /// Draws the source onto `destination` anti-aliased, the pixel size is taken from the source texture
+    pub fn apply(self: &Self, encoder: &mut wgpu::CommandEncoder, source_bindgroup: &wgpu::BindGroup, destination: &wgpu::TextureView) {
+        let mut rpass = FullscreenQuadPipeline::begin_pass(encoder, destination, "FXAA");
+
+        self.quad.draw(&mut rpass, &[ source_bindgroup ], &[]);
+    }
+}
+
+/// Push constants of god_rays.wgsl
+#[cfg(feature = "god-rays")]
+#[repr(C)]
+struct GodRaysParams {
+    sun_pos:   glam::Vec2,
+    intensity: f32,
+    decay:     f32,
+    density:   f32
+}
+
+/// God rays post-processing: a radial blur of the frame's bright parts towards the sun,
+/// for light shafts streaming through the scenery around sunrise and sunset.
+/// Like `FxaaPass`, the frame is drawn into a texture first, which `apply` copies to the destination with the rays added
+#[cfg(feature = "god-rays")]
+pub struct GodRaysPass {
+    pipeline: FullscreenQuadPipeline,
+    source:   SourceBinding
+}
+
+#[cfg(feature = "god-rays")]
+impl GodRaysPass {
+    /// The destination's format is `surface_format`, needs push constants
+    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        let source = SourceBinding::new(device, &[]);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("God rays"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("god_rays.wgsl").into())
+        });
+
+        let pipeline = FullscreenQuadPipeline::new(
+            device, &shader, "fs_main",
+            &[ &source.layout ],
+            &[ wgpu::PushConstantRange { stages: wgpu::ShaderStages::FRAGMENT, range: 0..std::mem::size_of::<GodRaysParams>() as u32 } ],
+            surface_format
+        );
+
+        Self { pipeline, source }
+    }
+
+    /// The source for `apply`, see `SourceBinding::bindgroup`
+    pub fn source_bindgroup(self: &Self, source: &RenderTexture, device: &wgpu::Device) -> wgpu::BindGroup {
+        self.source.bindgroup(source, &[], device)
+    }
+
+    /// Draws the source onto `destination` with the rays, `sun_screen_pos` is in UV space ([0..1], y down) and may be off the screen.
+    /// Each of the 16 samples towards the sun weighs `decay` times the previous one, `density` is how far they reach (1 is up to the sun)
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply(
+        self:             &Self,
+        encoder:          &mut wgpu::CommandEncoder,
+        source_bindgroup: &wgpu::BindGroup,
+        destination:      &wgpu::TextureView,
+        sun_screen_pos:   glam::Vec2,
+        intensity:        f32,
+        decay:            f32,
+        density:          f32
+    ) {
+        let mut rpass = FullscreenQuadPipeline::begin_pass(encoder, destination, "God rays");
+
+        let params = GodRaysParams { sun_pos: sun_screen_pos, intensity, decay, density };
+        self.pipeline.draw(&mut rpass, &[ source_bindgroup ], cast_struct_to_u8_slice(&params));
+    }
+}
+
+/// Push constants of bloom.wgsl
+#[repr(C)]
+struct BloomParams {
+    threshold: f32,
+    intensity: f32
+}
+
+/// Format of the highlights, 8 bits per channel would band the blur's faint tails
+const BLOOM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Blur radius of the bloom in pixels by default, see `BloomPass::set_blur_radius`
+pub const BLOOM_BLUR_RADIUS: i32 = 24;
+
+/// Bloom post-processing: the frame's bright parts bleed light into their surroundings.
+/// What's brighter than a threshold is kept, blurred with the two-pass gaussian blur of blur.wgsl,
+/// and added over the frame, the way the digital clock glows, for any clock.
+/// Like `FxaaPass`, the frame is drawn into a texture first, which `apply` copies to the destination with the bloom
+pub struct BloomPass {
+    threshold_pipeline:     FullscreenQuadPipeline,
+    horizontal_pipeline:    FullscreenQuadPipeline,
+    vertical_pipeline:      FullscreenQuadPipeline,
+    composite_pipeline:     FullscreenQuadPipeline,
+    blur_table_bindgroup:   wgpu::BindGroup,
+    highlights:             RenderTexture, // the thresholded frame, then the blurred one
+    half_blurred:           RenderTexture, // only blurred horizontally so far
+    highlights_bindgroup:   wgpu::BindGroup,
+    half_blurred_bindgroup: wgpu::BindGroup,
+    source:                 SourceBinding // for the highlights as well
+}
+
+impl BloomPass {
+    /// The destination's format is `surface_format`, `size` is the frame's. Needs push constants
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, surface_format: wgpu::TextureFormat, size: (u32, u32)) -> Self {
+        let source = SourceBinding::new(device, &[]);
+        let layout = &source.layout;
+
+        let (blur_table_bindgroup, blur_table_layout) = Self::blur_table(BLOOM_BLUR_RADIUS, device, queue);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Bloom"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("bloom.wgsl").into())
+        });
+
+        let blur_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Bloom blur"),
+            source: wgpu::ShaderSource::Wgsl(preprocess_shader(include_str!("bloom_blur.wgsl")).unwrap().into())
+        });
+
+        let params = [ wgpu::PushConstantRange { stages: wgpu::ShaderStages::FRAGMENT, range: 0..std::mem::size_of::<BloomParams>() as u32 } ];
+
+        let threshold_pipeline  = FullscreenQuadPipeline::new(device, &shader, "fs_threshold", &[ layout ], &params, BLOOM_FORMAT);
+        let horizontal_pipeline = FullscreenQuadPipeline::new(device, &blur_shader, "fs_horizontal", &[ layout, &blur_table_layout ], &[], BLOOM_FORMAT);
+        let vertical_pipeline   = FullscreenQuadPipeline::new(device, &blur_shader, "fs_vertical",   &[ layout, &blur_table_layout ], &[], BLOOM_FORMAT);
+        let composite_pipeline  = FullscreenQuadPipeline::new(device, &shader, "fs_composite", &[ layout, layout ], &params, surface_format);
+
+        let highlights   = RenderTexture::new(size, BLOOM_FORMAT, true, device);
+        let half_blurred = RenderTexture::new(size, BLOOM_FORMAT, true, device);
+        let highlights_bindgroup   = source.bindgroup(&highlights, &[], device);
+        let half_blurred_bindgroup = source.bindgroup(&half_blurred, &[], device);
+
+        Self {
+            threshold_pipeline,
+            horizontal_pipeline,
+            vertical_pipeline,
+            composite_pipeline,
+            blur_table_bindgroup,
+            highlights,
+            half_blurred,
+            highlights_bindgroup,
+            half_blurred_bindgroup,
+            source
+        }
+    }
+
+    // sigma a quarter of the radius, as the digital clock's glow
+    fn blur_table(radius: i32, device: &wgpu::Device, queue: &wgpu::Queue) -> (wgpu::BindGroup, wgpu::BindGroupLayout) {
+        create_blur_table_bindgroup(radius, radius as f32 * 0.25, true, true, device, queue)
+    }
+
+    /// The source for `apply`, see `SourceBinding::bindgroup`
+    pub fn source_bindgroup(self: &Self, source: &RenderTexture, device: &wgpu::Device) -> wgpu::BindGroup {
+        self.source.bindgroup(source, &[], device)
+    }
+
+    /// The highlights are for frames of the given size
+    pub fn resize(self: &mut Self, size: (u32, u32), device: &wgpu::Device) {
+        self.highlights   = RenderTexture::new(size, BLOOM_FORMAT, true, device);
+        self.half_blurred = RenderTexture::new(size, BLOOM_FORMAT, true, device);
+        self.highlights_bindgroup   = self.source.bindgroup(&self.highlights, &[], device);
+        self.half_blurred_bindgroup = self.source.bindgroup(&self.half_blurred, &[], device);
+    }
+
+    /// How far the highlights bleed, in pixels of the frame (`BLOOM_BLUR_RADIUS` to start with), e.g. to follow the pixel density
+    pub fn set_blur_radius(self: &mut Self, radius: i32, device: &wgpu::Device, queue: &wgpu::Queue) {
+        // the layout is the same whatever the radius, the pipelines keep theirs
+        self.blur_table_bindgroup = Self::blur_table(radius.max(1), device, queue).0;
+    }
+
+    /// Draws the source onto `destination` with the bloom. What's brighter than `threshold` (in linear luminance)
+    /// blooms, `intensity` scales the blurred highlights added over the frame
+    pub fn apply(
+        self:             &Self,
+        encoder:          &mut wgpu::CommandEncoder,
+        source_bindgroup: &wgpu::BindGroup,
+        destination:      &wgpu::TextureView,
+        threshold:        f32,
+        intensity:        f32
+    ) {
+        let params = BloomParams { threshold, intensity };
+
+        // each pass is a fullscreen quad clearing its destination. GL keeps the push constants after the labels,
+        // a multiple of 4 bytes each keeps them aligned
+        let mut apply_pass = |label, pipeline: &FullscreenQuadPipeline, bind_groups: &[&wgpu::BindGroup], push_constants: &[u8], view| {
+            let mut rpass = FullscreenQuadPipeline::begin_pass(encoder, view, label);
+            pipeline.draw(&mut rpass, bind_groups, push_constants);
+        };
+
+        // source --> highlights --> half blurred --> highlights (blurred) --> over the source onto the destination
+        apply_pass("Bloom highlights",     &self.threshold_pipeline,  &[ source_bindgroup ], cast_struct_to_u8_slice(&params), &self.highlights.view);
+        apply_pass("Bloom blur x",         &self.horizontal_pipeline, &[ &self.highlights_bindgroup, &self.blur_table_bindgroup ], &[], &self.half_blurred.view);
+        apply_pass("Bloom blur y",         &self.vertical_pipeline,   &[ &self.half_blurred_bindgroup, &self.blur_table_bindgroup ], &[], &self.highlights.view);
+        apply_pass("Bloom composite pass", &self.composite_pipeline,  &[ source_bindgroup, &self.highlights_bindgroup ], cast_struct_to_u8_slice(&params), destination);
+    }
+}
+
+/// The look `CrtPass` gives the frame, the uniform of crt.wgsl
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct CrtParams {
+    pub scanline_spacing:   f32, // from one scanline to the next, in pixels
+    pub scanline_intensity: f32, // how dark the gaps between them are, 0 for none, 1 for black
+    pub blur_amount:        f32, // how far the phosphors' glow reaches up and down, in pixels
+    pub rgb_shift:          f32  // how far red and blue are off to either side, in pixels
+}
+
+impl Default for CrtParams {
+    fn default() -> Self {
+        Self { scanline_spacing: 3.0, scanline_intensity: 0.35, blur_amount: 0.75, rgb_shift: 0.6 }
+    }
+}
+
+/// CRT post-processing: scanlines, the phosphors' glow lingering between them and the color guns slightly off.
+/// The parameters are in a uniform buffer, so unlike the other passes it works without push constants.
+/// Like `FxaaPass`, the frame is drawn into a texture first, which `apply` copies to the destination
+pub struct CrtPass {
+    pipeline: FullscreenQuadPipeline,
+    uniform:  SingleUniformBuffer,
+    source:   SourceBinding // and the uniform
+}
+
+impl CrtPass {
+    /// The destination's format is `surface_format`, the parameters are `CrtParams::default()` until set
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, surface_format: wgpu::TextureFormat) -> Self {
+        Self::for_target(device, queue, &target::TargetConfig::new(surface_format, None))
+    }
+
+    /// For multisampled destinations, e.g. the runner's frame (`target::TargetConfig::frame`)
+    pub fn for_target(device: &wgpu::Device, queue: &wgpu::Queue, config: &target::TargetConfig) -> Self {
+        let uniform = SingleUniformBuffer::new::<CrtParams>(device, wgpu::ShaderStages::FRAGMENT);
+
+        let source  = SourceBinding::new(device, &[ SingleUniformBuffer::default_layout_entry(2, &uniform) ]);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("CRT"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("crt.wgsl").into())
+        });
+
+        let pipeline = FullscreenQuadPipeline::for_target(device, &shader, "fs_main", &[ &source.layout ], &[], config);
+
+        let pass = Self { pipeline, uniform, source };
+        pass.set_params(queue, &CrtParams::default());
+
+        pass
+    }
+
+    /// The source for `apply` along with the parameters' uniform, see `SourceBinding::bindgroup`
+    pub fn source_bindgroup(self: &Self, source: &RenderTexture, device: &wgpu::Device) -> wgpu::BindGroup {
+        self.source.bindgroup(source, &[ self.uniform.get_entry(2) ], device)
+    }
+
+    /// Taken from the next `apply` submitted on
+    pub fn set_params(self: &Self, queue: &wgpu::Queue, params: &CrtParams) {
+        queue.write_buffer(&self.uniform.buffer, 0, cast_struct_to_u8_slice(params));
+    }
+
+    /// Draws the source onto `destination` as a CRT would show it
+    pub fn apply(
+        self:             &Self,
+        encoder:          &mut wgpu::CommandEncoder,
+        source_bindgroup: &wgpu::BindGroup,
+        destination:      &wgpu::TextureView
+    ) {
+        let mut rpass = FullscreenQuadPipeline::begin_pass(encoder, destination, "CRT");
+
+        self.pipeline.draw(&mut rpass, &[ source_bindgroup ], &[]);
+    }
+}
+
+/// The curves `TonemapPass` brings HDR colors into the displayable range with, by their index in tonemap.wgsl
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ToneMapper {
+    #[default]
+    Linear     = 0, // clamped, anything brighter than 1 is white
+    Reinhard   = 1, // x / (1 + x), never quite white
+    AcesFilm   = 2, // the filmic curve of ACES, contrasty with a soft shoulder
+    Uncharted2 = 3  // John Hable's filmic curve, softer in the shadows
+}
+
+/// Push constants of tonemap.wgsl
+#[repr(C)]
+struct TonemapParams {
+    mapper:   i32,
+    exposure: f32
+}
+
+/// Tone mapping post-processing: the frame is rendered in linear HDR (e.g. `Rgba16Float`) and mapped into
+/// the destination's range with one of the `ToneMapper` curves, after being scaled by the exposure.
+/// Like `FxaaPass`, the frame is drawn into a texture first, which `apply` copies to the destination mapped
+pub struct TonemapPass {
+    pipeline: FullscreenQuadPipeline,
+    source:   SourceBinding
+}
+
+impl TonemapPass {
+    /// The destination's format is `surface_format`, needs push constants
+    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        let source = SourceBinding::new(device, &[]);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Tone mapping"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("tonemap.wgsl").into())
+        });
+
+        let pipeline = FullscreenQuadPipeline::new(
+            device, &shader, "fs_main",
+            &[ &source.layout ],
+            &[ wgpu::PushConstantRange { stages: wgpu::ShaderStages::FRAGMENT, range: 0..8 } ], // the mapper and the exposure
+            surface_format
+        );
+
+        Self { pipeline, source }
+    }
+
+    /// The source for `apply`, see `SourceBinding::bindgroup`
+    pub fn source_bindgroup(self: &Self, source: &RenderTexture, device: &wgpu::Device) -> wgpu::BindGroup {
+        self.source.bindgroup(source, &[], device)
+    }
+
+    /// Draws the source onto `destination` tone mapped, its colors multiplied by `exposure` first
+    pub fn apply(
+        self:             &Self,
+        encoder:          &mut wgpu::CommandEncoder,
+        source_bindgroup: &wgpu::BindGroup,
+        destination:      &wgpu::TextureView,
+        mapper:           ToneMapper,
+        exposure:         f32
+    ) {
+        let mut rpass = FullscreenQuadPipeline::begin_pass(encoder, destination, "Tone mapping");
+
+        let params = TonemapParams { mapper: mapper as i32, exposure };
+        self.pipeline.draw(&mut rpass, &[ source_bindgroup ], cast_struct_to_u8_slice(&params));
+    }
+}
+
+/// Sub-pixel offsets of the camera for temporal anti-aliasing, one per frame, in pixels within ±0.5:
+/// the first 8 points of the Halton sequence in bases 2 and 3, centered on the pixel.
+/// Any 8 frames in a row cover the pixel evenly, without the regular grid's repeating pattern
+#[cfg(feature = "taa")]
+#[allow(clippy::eq_op)] // 1/2 - 0.5, written like the others
+pub const HALTON_JITTER: [(f32, f32); 8] = [
+    (1.0 / 2.0  - 0.5, 1.0 / 3.0 - 0.5),
+    (1.0 / 4.0  - 0.5, 2.0 / 3.0 - 0.5),
+    (3.0 / 4.0  - 0.5, 1.0 / 9.0 - 0.5),
+    (1.0 / 8.0  - 0.5, 4.0 / 9.0 - 0.5),
+    (5.0 / 8.0  - 0.5, 7.0 / 9.0 - 0.5),
+    (3.0 / 8.0  - 0.5, 2.0 / 9.0 - 0.5),
+    (7.0 / 8.0  - 0.5, 5.0 / 9.0 - 0.5),
+    (1.0 / 16.0 - 0.5, 8.0 / 9.0 - 0.5)
+];
+
+/// How much of each new frame goes into the accumulated image by default, the rest is the history
+#[cfg(feature = "taa")]
+pub const TAA_CURRENT_WEIGHT: f32 = 0.1;
+
+/// Format of the accumulated history, 8 bits per channel would keep it from converging at small weights
+#[cfg(feature = "taa")]
+const TAA_HISTORY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Temporal anti-aliasing: each frame is rendered with the camera moved by a different sub-pixel offset
+/// (see `HALTON_JITTER` and `TaaPass::jitter`), and blended into the history of the previous frames,
+/// so that the edges converge to their coverage over a few frames. Unlike FXAA, that also settles the shimmering
+/// of thin, moving geometry. There are no motion vectors, the history is clamped to the colors around each pixel
+/// of the new frame instead, which keeps moving objects from leaving trails behind.
+/// Like `FxaaPass`, the frame is drawn into a texture first, which `apply` copies to the destination blended
+#[cfg(feature = "taa")]
+pub struct TaaPass {
+    history_texture:     RenderTexture,  // the previous frames, read by `apply`
+    next_history:        RenderTexture,  // written by `apply` along with the destination, then swapped with the above
+    history_bindgroup:   wgpu::BindGroup,
+    next_bindgroup:      wgpu::BindGroup,
+    history_valid:       bool,           // false until the first frame after a reset has been drawn
+    accumulate_pipeline: wgpu::RenderPipeline,
+    source:              SourceBinding,
+    history_layout:      wgpu::BindGroupLayout,
+    current_weight:      f32
+}
+
+#[cfg(feature = "taa")]
+impl TaaPass {
+    /// The destination's format is `surface_format`, `size` is the frame's. Needs push constants
+    pub fn new(size: (u32, u32), device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        let source = SourceBinding::new(device, &[]);
+
+        let history_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[ RenderTexture::default_layout_entry(0) ]
+        });
+
+        let vertex_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Fullscreen quad"),
+            source: wgpu::ShaderSource::Wgsl(fullscreen_quad_vertex_shader_source().into())
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("TAA"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("taa.wgsl").into())
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &source.layout, &history_layout ],
+            push_constant_ranges: &[ wgpu::PushConstantRange { stages: wgpu::ShaderStages::FRAGMENT, range: 0..4 } ] // the weight
+        });
+
+        // the blended frame goes to the destination and into the next history at once
+        let accumulate_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label:         Some("TAA"),
+            layout:        Some(&pipeline_layout),
+            depth_stencil: None,
+            multisample:   wgpu::MultisampleState::default(),
+            multiview:     None,
+            vertex: wgpu::VertexState {
+                module:      &vertex_module,
+                entry_point: "vs_main",
+                buffers:     &[]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module:      &shader,
+                entry_point: "fs_main",
+                targets:     &[
+                    Some(wgpu::ColorTargetState { format: surface_format,     blend: None, write_mask: wgpu::ColorWrites::ALL }),
+                    Some(wgpu::ColorTargetState { format: TAA_HISTORY_FORMAT, blend: None, write_mask: wgpu::ColorWrites::ALL })
+                ]
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology:     wgpu::PrimitiveTopology::TriangleStrip,
+                cull_mode:    None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..Default::default()
+            }
+        });
+
+        let history_texture = RenderTexture::new(size, TAA_HISTORY_FORMAT, true, device);
+        let next_history    = RenderTexture::new(size, TAA_HISTORY_FORMAT, true, device);
+        let history_bindgroup = Self::history_bindgroup(&history_texture, &history_layout, device);
+        let next_bindgroup    = Self::history_bindgroup(&next_history, &history_layout, device);
+
+        Self {
+            history_texture,
+            next_history,
+            history_bindgroup,
+            next_bindgroup,
+            history_valid: false,
+            accumulate_pipeline,
+            source,
+            history_layout,
+            current_weight: TAA_CURRENT_WEIGHT
+        }
+    }
+
+    fn history_bindgroup(history: &RenderTexture, layout: &wgpu::BindGroupLayout, device: &wgpu::Device) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   None,
+            layout,
+            entries: &[ history.get_entry(0) ]
+        })
+    }
+
+    /// The source for `apply`, see `SourceBinding::bindgroup`
+    pub fn source_bindgroup(self: &Self, source: &RenderTexture, device: &wgpu::Device) -> wgpu::BindGroup {
+        self.source.bindgroup(source, &[], device)
+    }
+
+    /// The history is for frames of the given size, it starts over
+    pub fn resize(self: &mut Self, size: (u32, u32), device: &wgpu::Device) {
+        self.history_texture   = RenderTexture::new(size, TAA_HISTORY_FORMAT, true, device);
+        self.next_history      = RenderTexture::new(size, TAA_HISTORY_FORMAT, true, device);
+        self.history_bindgroup = Self::history_bindgroup(&self.history_texture, &self.history_layout, device);
+        self.next_bindgroup    = Self::history_bindgroup(&self.next_history, &self.history_layout, device);
+        self.history_valid     = false;
+    }
+
+    /// Forgets the previous frames, e.g. when the pass was off for a while. The next frame is shown as it is
+    pub fn reset(self: &mut Self) {
+        self.history_valid = false;
+    }
+
+    pub fn current_weight(self: &Self) -> f32 {
+        self.current_weight
+    }
+
+    /// How much of each new frame goes into the result, within (0..1]: lower is smoother and slower to follow changes
+    pub fn set_current_weight(self: &mut Self, weight: f32) {
+        self.current_weight = weight.clamp(f32::EPSILON, 1.0);
+    }
+
+    /// Moves the clip space by `HALTON_JITTER[index]` (wrapping) pixels of a frame of the given size,
+    /// to be applied after the projection: `jitter * projection * view`
+    pub fn jitter(index: u32, resolution: glam::Vec2) -> glam::Mat4 {
+        let (x, y) = HALTON_JITTER[index as usize % HALTON_JITTER.len()];
+
+        // a pixel is 2 / resolution wide in NDC
+        glam::Mat4::from_translation(glam::Vec3::new(x * 2.0 / resolution.x, y * 2.0 / resolution.y, 0.0))
+    }
+
+    /// Draws the source blended with the history onto `destination`, the result becomes the next frame's history
+    pub fn apply(
+        self:             &mut Self,
+        encoder:          &mut wgpu::CommandEncoder,
+        source_bindgroup: &wgpu::BindGroup,
+        destination:      &wgpu::TextureView
+    ) {
+        let attachment = |view| Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops:            wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store }
+        });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    Some("TAA pass"), // GL keeps the push constants after it, a multiple of 4 bytes keeps them aligned
+                depth_stencil_attachment: None,
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[ attachment(destination), attachment(&self.next_history.view) ]
+            });
+
+            // without a history, the frame is taken as it is
+            let weight = if self.history_valid { self.current_weight } else { 1.0 };
+
+            rpass.set_pipeline(&self.accumulate_pipeline);
+            rpass.set_bind_group(0, source_bindgroup, &[]);
+            rpass.set_bind_group(1, &self.history_bindgroup, &[]);
+            rpass.set_push_constants(wgpu::ShaderStages::FRAGMENT, 0, cast_struct_to_u8_slice(&weight));
+            rpass.draw(0..4, 0..1);
+        }
+
+        std::mem::swap(&mut self.history_texture, &mut self.next_history);
+        std::mem::swap(&mut self.history_bindgroup, &mut self.next_bindgroup);
+        self.history_valid = true;
+    }
+}
+
+/// Collection of data that can be used for adapting with various window size and aspect ratio
+/// The WGPU shader coordinate system is [-1..1] in both axes, with origin (0, 0) in the middle.
+/// The system stretches/compresses as window gets resized, but we need uniform scaling for both axes.
+/// Also, the examples use units that can be out of the [-1..1] range.
+/// 
+/// As for such, introducing adaptive scaling system for x and y axis.
+/// There is a 2D measurement called `extent`, which is a rectangle,
+/// whose `width = 2 * extent.x` and `height = 2 * extent.y`.
+/// Thus the range is `[-extent.x..extent.x]` (horizontally) and `[-extent.y..extent.y]` (vertically)
+/// The center of the rectangle is the origin. Any point described within this extent is guaranteed to be on the screen/window.
+/// Though being 2D, it can also be applied to 3D transformation matrices as well.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C, align(8))]
+pub struct DrawspaceScales {
+    /// the scale that'll be applied to the vertices
+    pub scale: glam::Vec2,
+    /// the supplied extent
+    pub extent: glam::Vec2,
+    /// the supplied screen resolution
+    pub resolution: glam::Vec2,
+    /// amount of pixels per drawing unit, changes according to screen size
+    pub density: f32
+}
+
+/// How the extent fits into a window of another aspect ratio
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FitMode {
+    /// All of the extent is visible, with room to spare along one axis
+    #[default]
+    Contain,
+    /// The extent fills the window, cut off along one axis
+    Cover
+}
+
+impl DrawspaceScales {
+    pub fn new(resolution: glam::Vec2, extent: glam::Vec2) -> Self {
+        Self::with_fit(resolution, extent, FitMode::Contain)
+    }
+
+    pub fn with_fit(resolution: glam::Vec2, extent: glam::Vec2, fit: FitMode) -> Self {
+        let [width, height] = resolution.to_array();
+        let [ext_x, ext_y]  = extent.to_array();
+
+        let aspect_ratio_window = width / height;
+        let aspect_ratio_extent = ext_x / ext_y;
+
+        // covering is containing the other way round
+        if (aspect_ratio_window > aspect_ratio_extent) == (fit == FitMode::Contain) {
+            // the window's ceiling+floor touche the extent's ceiling+floor
+            let scale = glam::Vec2::new(aspect_ratio_window * ext_y, ext_y).recip();
+            let density = (height * 0.5) / ext_y;
+            Self { scale, extent, resolution, density }
+        } else {
+            // the window's side walls touche the extent's side walls
+            let scale = glam::Vec2::new(ext_x, (1.0 / aspect_ratio_window) * ext_x).recip();
+            let density = (width * 0.5) / ext_x;
+            Self { scale, extent, resolution, density }
+        }
+    }
+
+    /// The nearest position on a pixel boundary, so that edges placed there don't straddle pixels
+    pub fn snap_to_pixel_grid(self: &Self, world_pos: glam::Vec2) -> glam::Vec2 {
+        self.snap(world_pos, 0.0)
+    }
+
+    /// The nearest pixel center, e.g. for 1 pixel thin lines
+    pub fn snap_to_half_pixel_grid(self: &Self, world_pos: glam::Vec2) -> glam::Vec2 {
+        self.snap(world_pos, 0.5)
+    }
+
+    fn snap(self: &Self, world_pos: glam::Vec2, offset: f32) -> glam::Vec2 {
+        // pixels count from the window's edges, while the drawing space is centered,
+        // which puts its origin in the middle of a pixel for odd sizes
+        let half = self.resolution * 0.5;
+        let pixels = world_pos * self.density + half;
+
+        ((pixels - offset).round() + offset - half) / self.density
+    }
+}
+
+/// `DrawspaceScales` of a window spanning several monitors, one for each monitor's part of it,
+/// so that a clock fits into every monitor instead of straddling the gaps and bezels between them.
+/// Monitors are given as `(x, y, width, height)` in physical pixels, relative to the top-left corner of them all.
+/// Their bounding box (`total_resolution`) may have holes where the monitors don't line up
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiMonitorScales {
+    pub per_monitor:      Vec<DrawspaceScales>,
+    pub total_resolution: glam::Vec2,
+    viewports:            Vec<glam::Vec4> // x, y, width, height of each monitor within the bounding box
+}
+
+impl MultiMonitorScales {
+    pub fn new(monitors: Vec<(u32, u32, u32, u32)>, extent: glam::Vec2) -> Self {
+        let viewports: Vec<glam::Vec4> = monitors.iter()
+            .map(|&(x, y, width, height)| glam::Vec4::new(x as f32, y as f32, width.max(1) as f32, height.max(1) as f32))
+            .collect();
+
+        let per_monitor = viewports.iter()
+            .map(|viewport| DrawspaceScales::new(glam::Vec2::new(viewport.z, viewport.w), extent))
+            .collect();
+
+        let total_resolution = viewports.iter()
+            .fold(glam::Vec2::ZERO, |total, viewport| total.max(glam::Vec2::new(viewport.x + viewport.z, viewport.y + viewport.w)));
+
+        Self { per_monitor, total_resolution, viewports }
+    }
+
+    /// Every monitor the event loop knows of, each one's drawing space fitting `extent`.
+    /// The monitors are moved so that the top-left corner of them all is at (0, 0)
+    pub fn from_winit(event_loop: &winit::event_loop::EventLoop<()>, extent: glam::Vec2) -> Self {
+        let bounds: Vec<(i32, i32, u32, u32)> = event_loop.available_monitors()
+            .map(|monitor| (monitor.position().x, monitor.position().y, monitor.size().width, monitor.size().height))
+            .collect();
+
+        let left = bounds.iter().map(|bound| bound.0).min().unwrap_or(0);
+        let top  = bounds.iter().map(|bound| bound.1).min().unwrap_or(0);
+
+        for (index, (x, y, width, height)) in bounds.iter().enumerate() {
+            log::debug!("Monitor {}: {}x{} at ({}, {})", index, width, height, x, y);
+        }
+
+        Self::new(
+            bounds.iter().map(|&(x, y, width, height)| ((x - left) as u32, (y - top) as u32, width, height)).collect(),
+            extent
+        )
+    }
+
+    /// The scales of the monitor at the index, in the order the monitors were given
+    pub fn for_monitor(self: &Self, index: usize) -> &DrawspaceScales {
+        &self.per_monitor[index]
+    }
+
+    /// Where the monitor at the index is within a window of the given size covering all of them, as
+    /// `(x, y, width, height)` for `RenderPass::set_viewport`. Stretched along if the window isn't quite the total size
+    pub fn viewport(self: &Self, index: usize, window: glam::Vec2) -> glam::Vec4 {
+        let stretch = window / self.total_resolution.max(glam::Vec2::ONE);
+        self.viewports[index] * glam::Vec4::new(stretch.x, stretch.y, stretch.x, stretch.y)
+    }
+
+    pub fn len(self: &Self) -> usize {
+        self.per_monitor.len()
+    }
+
+    pub fn is_empty(self: &Self) -> bool {
+        self.per_monitor.is_empty()
+    }
+}
+
+/// The drawing space of a 2D clock on the GPU: `DrawspaceScales` in a uniform buffer, with the bind group
+/// (and its layout) for the pipelines. Visible to the vertex and fragment stages, at binding 0.
+/// The buffer is written on `resize()`, which the clock calls with the window's size
+pub struct Drawspace2D {
+    extent:     glam::Vec2,
+    fit:        FitMode,
+    scales:     DrawspaceScales,
+    buffer:     wgpu::Buffer,
+    layout:     wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup
+}
+
+impl Drawspace2D {
+    pub fn new(device: &wgpu::Device, extent: glam::Vec2, fit: FitMode) -> Self {
+        let uniform = SingleUniformBuffer::new::<DrawspaceScales>(device, wgpu::ShaderStages::VERTEX_FRAGMENT);
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Drawspace"),
+            entries: &[ SingleUniformBuffer::default_layout_entry(0, &uniform) ]
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   Some("Drawspace"),
+            layout:  &layout,
+            entries: &[ uniform.get_entry(0) ]
+        });
+
+        // a single pixel until the first resize
+        let scales = DrawspaceScales::with_fit(glam::Vec2::ONE, extent, fit);
+
+        Self { extent, fit, scales, buffer: uniform.buffer, layout, bind_group }
+    }
+
+    /// Recalculates the scales for the new resolution and uploads them
+    pub fn resize(self: &mut Self, queue: &wgpu::Queue, width: u32, height: u32) {
+        self.scales = DrawspaceScales::with_fit(glam::Vec2::new(width as f32, height as f32), self.extent, self.fit);
+        queue.write_buffer(&self.buffer, 0, cast_struct_to_u8_slice(&self.scales));
+    }
+
+    /// As last uploaded, e.g. for the pixel density
+    pub fn scales(self: &Self) -> &DrawspaceScales {
+        &self.scales
+    }
+
+    pub fn extent(self: &Self) -> glam::Vec2 {
+        self.extent
+    }
+
+    pub fn layout(self: &Self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn bind_group(self: &Self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
+/// The layout of a vertex buffer of `T`s, read by the shader at consecutive locations from 0
+fn vertex_layout<T>(attributes: &'static [wgpu::VertexAttribute]) -> wgpu::VertexBufferLayout<'static> {
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<T>() as wgpu::BufferAddress,
+        step_mode:    wgpu::VertexStepMode::Vertex,
+        attributes
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vtx2ID {
+    pub pos: glam::Vec2,
+    pub id:  u32
+}
+
+impl Vtx2ID {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = [
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 0, offset: std::mem::offset_of!(Self, pos) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Uint32,    shader_location: 1, offset: std::mem::offset_of!(Self, id) as u64 }
+    ];
+
+    pub fn wgpu_layout() -> wgpu::VertexBufferLayout<'static> {
+        vertex_layout::<Self>(&Self::ATTRIBUTES)
+    }
+}
+
+/// Position and UV, for flat textured quads
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vtx2UV {
+    pub pos: glam::Vec2,
+    pub uv:  glam::Vec2
+}
+
+impl Vtx2UV {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = [
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 0, offset: std::mem::offset_of!(Self, pos) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 1, offset: std::mem::offset_of!(Self, uv) as u64 }
+    ];
+
+    pub fn wgpu_layout() -> wgpu::VertexBufferLayout<'static> {
+        vertex_layout::<Self>(&Self::ATTRIBUTES)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct Vtx3UV {
+    pub pos: glam::Vec3,
+    pub uv:  glam::Vec2
+}
+
+impl Vtx3UV {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = [
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, shader_location: 0, offset: std::mem::offset_of!(Self, pos) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 1, offset: std::mem::offset_of!(Self, uv) as u64 }
+    ];
+
+    pub fn wgpu_layout() -> wgpu::VertexBufferLayout<'static> {
+        vertex_layout::<Self>(&Self::ATTRIBUTES)
+    }
+}
+
+/// Position, UV and a per-vertex color, read as the blend weights of up to 4 splatted textures.
+/// `Vec4` is 16-byte aligned with SIMD, the padding makes it so on every target and leaves no implicit gap
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct Vtx3ColorUV {
+    pub pos:           glam::Vec3,
+    pub uv:            glam::Vec2,
+    pub _padding:      [f32; 3],
+    pub blend_weights: glam::Vec4
+}
+
+impl Vtx3ColorUV {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = [
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, shader_location: 0, offset: std::mem::offset_of!(Self, pos) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 1, offset: std::mem::offset_of!(Self, uv) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, shader_location: 2, offset: std::mem::offset_of!(Self, blend_weights) as u64 }
+    ];
+
+    pub fn wgpu_layout() -> wgpu::VertexBufferLayout<'static> {
+        vertex_layout::<Self>(&Self::ATTRIBUTES)
+    }
+}
+
+/// Position, normal and UV, for lit meshes
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct Vtx3NUV {
+    pub pos:    glam::Vec3,
+    pub normal: glam::Vec3,
+    pub uv:     glam::Vec2
+}
+
+impl Vtx3NUV {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] = [
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, shader_location: 0, offset: std::mem::offset_of!(Self, pos) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, shader_location: 1, offset: std::mem::offset_of!(Self, normal) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 2, offset: std::mem::offset_of!(Self, uv) as u64 }
+    ];
+
+    const ATTRIBUTES_WITHOUT_NORMALS: [wgpu::VertexAttribute; 2] = [
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, shader_location: 0, offset: std::mem::offset_of!(Self, pos) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 1, offset: std::mem::offset_of!(Self, uv) as u64 }
+    ];
+
+    pub fn wgpu_layout() -> wgpu::VertexBufferLayout<'static> {
+        vertex_layout::<Self>(&Self::ATTRIBUTES)
+    }
+
+    /// The position and UV at the locations of `Vtx3UV`, the normals skipped over,
+    /// for the pipelines built for `PlyGeoBuffers` to draw `ObjGeoBuffers` as they are
+    pub fn wgpu_layout_without_normals() -> wgpu::VertexBufferLayout<'static> {
+        vertex_layout::<Self>(&Self::ATTRIBUTES_WITHOUT_NORMALS)
+    }
+}
+
+/// Position, normal, UV and tangent, for normal mapping.
+/// The tangent's `w` is the handedness of the tangent space, the bitangent is `cross(normal, tangent.xyz) * tangent.w`
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct Vtx3NUVT {
+    pub pos:     glam::Vec3,
+    pub normal:  glam::Vec3,
+    pub uv:      glam::Vec2,
+    pub tangent: glam::Vec4
+}
+
+impl Vtx3NUVT {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] = [
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, shader_location: 0, offset: std::mem::offset_of!(Self, pos) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, shader_location: 1, offset: std::mem::offset_of!(Self, normal) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x2, shader_location: 2, offset: std::mem::offset_of!(Self, uv) as u64 },
+        wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x4, shader_location: 3, offset: std::mem::offset_of!(Self, tangent) as u64 }
+    ];
+
+    pub fn wgpu_layout() -> wgpu::VertexBufferLayout<'static> {
+        vertex_layout::<Self>(&Self::ATTRIBUTES)
+    }
+}
+
+#[derive(Debug)]
+pub struct PlyMesh {
+    pub vertices: Vec<Vtx3UV>,
+    pub indices:  Vec<u16>,
+    pub colors:   Vec<glam::Vec4> // one per vertex if the PLY has red, green, blue and alpha properties, empty otherwise
+}
+
+impl PlyMesh {
+    pub fn new(path: &str) -> Result<Self, String> {
+        let started = time::Instant::now();
+        let file = std::fs::File::open(path).map_err(|error| format!("Can't open the mesh {}: {}", path, error))?;
+        let mesh = Self::from_reader(&mut std::io::BufReader::new(file)).map_err(|error| format!("Can't load the mesh {}: {}", path, error))?;
+
+        log::debug!("Loaded {} ({} vertices, {} indices) in {:?}", path, mesh.vertices.len(), mesh.indices.len(), started.elapsed());
+        return Ok(mesh);
+    }
+
+    /// Like `new`, but the vertex colors are required
+    pub fn new_colored(path: &str) -> Result<Self, String> {
+        let mesh = Self::new(path)?;
+
+        if mesh.colors.is_empty() {
+            return Err(format!("Can't load the mesh {}: no red, green, blue and alpha vertex properties", path));
+        }
+
+        return Ok(mesh);
+    }
+
+    /// Parse PLY data from anything readable, e.g. an in-memory buffer
+    pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<Self, &'static str> {
+        use ply_rs::ply::Property::{Float, UChar, ListUInt};
+
+        let parser = ply_rs::parser::Parser::<ply_rs::ply::DefaultElement>::new();
+        let ply = parser.read_ply(reader).map_err(|_| "Malformed PLY data")?;
+
+        let vertex_count = ply.header.elements["vertex"].count;
+        let face_count   = ply.header.elements["face"].count;
+
+        let mut vertices: Vec<Vtx3UV> = Vec::with_capacity(vertex_count);
+        let mut indices:  Vec<u16> = Vec::with_capacity(face_count * 3);
+
+        // all four channels or none, a missing alpha would be ambiguous for blend weights
+        const COLOR_KEYS: [&str; 4] = ["red", "green", "blue", "alpha"];
+        let has_colors = COLOR_KEYS.iter().all(|key| ply.header.elements["vertex"].properties.contains_key(*key));
+        let mut colors: Vec<glam::Vec4> = Vec::with_capacity(if has_colors { vertex_count } else { 0 });
+
+        let vertex_payload = &ply.payload["vertex"];
+        let face_payload   = &ply.payload["face"];
+
+
+        for item in vertex_payload {
+            let collect_f32 = |key| {
+                match item[key] {
+                    Float(val) => val,
+                    _ => f32::NAN
+                }
+            };
+
+            let pos = ["x", "y", "z"].map(collect_f32);
+            let uv  = ["s", "t"].map(collect_f32);
+
+            if pos.into_iter().any(|v| v.is_nan()) || uv.into_iter().any(|v| v.is_nan()) {
+                return Err("Illegal data type in vertex, expected float");
+            }
+
+            vertices.push(Vtx3UV {
+                pos: glam::Vec3::from_array(pos),
+                uv:  glam::Vec2::from_array(uv)
+            });
+
+            if has_colors {
+                // 8-bit channels are normalized, like Blender exports them
+                let color = COLOR_KEYS.map(|key| {
+                    match item[key] {
+                        UChar(val) => val as f32 / 255.0,
+                        Float(val) => val,
+                        _ => f32::NAN
+                    }
+                });
+
+                if color.into_iter().any(|v| v.is_nan()) {
+                    return Err("Illegal data type in vertex color, expected uchar or float");
+                }
+
+                colors.push(glam::Vec4::from_array(color));
+            }
+        }
+
+        for item in face_payload {
+            match &item["vertex_indices"] {
+                ListUInt(facedata) => {
+                    if facedata.len() != 3 {
+                        return Err("Illegal index count in face, expected 3");
+                    }
+
+                    indices.push(facedata[0] as u16);
+                    indices.push(facedata[1] as u16);
+                    indices.push(facedata[2] as u16);
+                },
+                _ => {
+                    return Err("Illegal data type in face, expected uint");
+                }
+            };
+        }
+
+        Ok(Self { vertices, indices, colors })
+    }
+
+    /// The vertices with their colors as blend weights, the mesh must have colors (see `new_colored`)
+    pub fn with_blend_weights(self: &Self) -> Vec<Vtx3ColorUV> {
+        self.vertices.iter().zip(&self.colors).map(|(vertex, color)| Vtx3ColorUV {
+            pos:           vertex.pos,
+            uv:            vertex.uv,
+            _padding:      [0.0; 3],
+            blend_weights: *color
+        }).collect()
+    }
+
+    /// The vertices with smooth normals, the adjacent faces' normals weighed by their areas.
+    /// The PLY files carry no normals, so they're derived from the faces
+    pub fn with_normals(self: &Self) -> Vec<Vtx3NUV> {
+        self.with_normals_weighted(NormalWeighting::Area)
+    }
+
+    /// `with_normals`, with the faces weighed as given
+    pub fn with_normals_weighted(self: &Self, weighting: NormalWeighting) -> Vec<Vtx3NUV> {
+        let indices: Vec<u32> = self.indices.iter().map(|&i| i as u32).collect();
+        let normals = compute_smooth_normals(&self.vertices, &indices, weighting);
+
+        self.vertices.iter().zip(normals).map(|(vertex, normal)| Vtx3NUV {
+            pos:    vertex.pos,
+            normal,
+            uv:     vertex.uv
+        }).collect()
+    }
+
+    /// A coarser version of the mesh with about `ratio` (0..1] of the vertices, for levels of detail.
+    /// Greedy edge collapse: the shortest edges (the least position error when their two vertices merge) go first,
+    /// interior ones merging into their midpoint, until the vertex count is down to the target or no edge can go.
+    /// Vertices on the mesh's border keep their place, so that the outline doesn't shrink, see `EdgeCollapser`.
+    /// Where three or more UV islands meet, the vertices can't go at all, so meshes with lots of seams bottom out
+    /// above the target (the terrain stops at about half of its vertices).
+    /// No normal flip or topology checks, it's meant for dense, smooth meshes like the terrain
+    pub fn simplified(self: &Self, ratio: f32) -> Self {
+        use std::collections::BinaryHeap;
+        use std::cmp::Reverse;
+
+        let vertex_count = self.vertices.len();
+        let target = ((vertex_count as f32 * ratio.clamp(0.0, 1.0)).ceil() as usize).max(3);
+
+        if target >= vertex_count {
+            return Self { vertices: self.vertices.clone(), indices: self.indices.clone(), colors: self.colors.clone() };
+        }
+
+        let mut collapser = EdgeCollapser::new(self);
+
+        // Candidates ordered by cost, the cheapest first. The costs are non-negative floats,
+        // whose bit patterns order the same way. Entries of vertices changed since are stale, told by the versions
+        let mut heap = BinaryHeap::new();
+        for (a, b) in collapser.edges() {
+            heap.push(Reverse((collapser.cost(a, b).to_bits(), a, b, 0u32, 0u32)));
+        }
+
+        while collapser.alive > target {
+            let Some(Reverse((_, a, b, version_a, version_b))) = heap.pop() else { break };
+
+            if !collapser.is_current(a, version_a) || !collapser.is_current(b, version_b) {
+                continue;
+            }
+
+            let Some(plan) = collapser.plan(a, b) else { continue };
+
+            for &(keep, gone, t) in &plan {
+                collapser.collapse(keep, gone, t);
+            }
+
+            for &(keep, _, _) in &plan {
+                for &n in &collapser.neighbors[keep as usize] {
+                    let versions = (collapser.versions[keep as usize], collapser.versions[n as usize]);
+                    heap.push(Reverse((collapser.cost(keep, n).to_bits(), keep, n, versions.0, versions.1)));
+                }
+            }
+        }
+
+        collapser.into_mesh(&self.indices)
+    }
+}
+
+/// State of `PlyMesh::simplified`'s edge collapses.
+/// Vertices are split along UV seams, so the seams are borders too as far as the faces tell. A border vertex only goes
+/// along a border edge, into its neighbor there, if the border runs (almost) straight through it: the corners stay.
+/// On a seam, the split copies of the vertex on the other side go along, so that the seam doesn't crack open
+struct EdgeCollapser {
+    positions:    Vec<glam::Vec3>,
+    uvs:          Vec<glam::Vec2>,
+    colors:       Vec<glam::Vec4>, // empty if the mesh has none
+    neighbors:    Vec<Vec<u32>>,
+    border:       Vec<bool>,
+    border_edges: std::collections::HashSet<(u32, u32)>,
+    /// Border vertices by position, for finding the split copies on seams. Border vertices never move
+    coincident:   std::collections::HashMap<[u32; 3], Vec<u32>>,
+    merged_into:  Vec<u32>, // the vertex itself while it's alive
+    versions:     Vec<u32>, // bumped whenever the vertex moves or its neighborhood changes
+    alive:        usize
+}
+
+impl EdgeCollapser {
+    /// Cosine of the angle between the two border edges of a vertex that may go, about 160 degrees
+    const STRAIGHT_BORDER: f32 = -0.94;
+
+    fn new(mesh: &PlyMesh) -> Self {
+        let vertex_count = mesh.vertices.len();
+
+        // how many faces share each edge, the ones with a single face are on the border
+        let mut edge_faces: std::collections::HashMap<(u32, u32), u32> = std::collections::HashMap::new();
+        for face in mesh.indices.chunks_exact(3) {
+            for (a, b) in [(face[0], face[1]), (face[1], face[2]), (face[2], face[0])] {
+                *edge_faces.entry(Self::edge(a as u32, b as u32)).or_insert(0) += 1;
+            }
+        }
+
+        let mut neighbors    = vec![Vec::new(); vertex_count];
+        let mut border       = vec![false; vertex_count];
+        let mut border_edges = std::collections::HashSet::new();
+
+        for (&(a, b), &count) in &edge_faces {
+            neighbors[a as usize].push(b);
+            neighbors[b as usize].push(a);
+
+            if count == 1 {
+                border[a as usize] = true;
+                border[b as usize] = true;
+                border_edges.insert((a, b));
+            }
+        }
+
+        let mut coincident: std::collections::HashMap<[u32; 3], Vec<u32>> = std::collections::HashMap::new();
+        for (v, vertex) in mesh.vertices.iter().enumerate().filter(|(v, _)| border[*v]) {
+            coincident.entry(vertex.pos.to_array().map(f32::to_bits)).or_default().push(v as u32);
+        }
+
+        Self {
+            positions: mesh.vertices.iter().map(|vertex| vertex.pos).collect(),
+            uvs:       mesh.vertices.iter().map(|vertex| vertex.uv).collect(),
+            colors:    mesh.colors.clone(),
+            neighbors, border, border_edges, coincident,
+            merged_into: (0..vertex_count as u32).collect(),
+            versions:    vec![0; vertex_count],
+            alive:       vertex_count
+        }
+    }
+
+    fn edge(a: u32, b: u32) -> (u32, u32) {
+        (a.min(b), a.max(b))
+    }
+
+    fn edges(self: &Self) -> Vec<(u32, u32)> {
+        self.neighbors.iter().enumerate()
+            .flat_map(|(a, list)| list.iter().filter(move |&&b| b > a as u32).map(move |&b| (a as u32, b)))
+            .collect()
+    }
+
+    fn cost(self: &Self, a: u32, b: u32) -> f32 {
+        self.positions[a as usize].distance_squared(self.positions[b as usize])
+    }
+
+    fn is_current(self: &Self, v: u32, version: u32) -> bool {
+        self.merged_into[v as usize] == v && self.versions[v as usize] == version
+    }
+
+    /// Neighbors along the border
+    fn border_ends(self: &Self, v: u32) -> Vec<u32> {
+        self.neighbors[v as usize].iter().copied()
+            .filter(|&n| self.border_edges.contains(&Self::edge(n, v)))
+            .collect()
+    }
+
+    /// Whether the border vertex can go without changing the outline much
+    fn removable(self: &Self, v: u32) -> bool {
+        let ends = self.border_ends(v);
+        let toward = |n: u32| (self.positions[n as usize] - self.positions[v as usize]).normalize_or_zero();
+
+        ends.len() == 2 && toward(ends[0]).dot(toward(ends[1])) < Self::STRAIGHT_BORDER
+    }
+
+    /// The collapses (kept vertex, gone vertex, where between them they merge) that take the edge out,
+    /// more than one for a seam. `None` if the edge has to stay
+    fn plan(self: &Self, a: u32, b: u32) -> Option<Vec<(u32, u32, f32)>> {
+        let (keep, gone) = match (self.border[a as usize], self.border[b as usize]) {
+            (false, false) => return Some(vec![(a, b, 0.5)]),
+            (true,  false) => return Some(vec![(a, b, 0.0)]),
+            (false, true)  => return Some(vec![(b, a, 0.0)]),
+            (true,  true)  if !self.border_edges.contains(&Self::edge(a, b)) => return None,
+            (true,  true)  if self.removable(b) => (a, b),
+            (true,  true)  if self.removable(a) => (b, a),
+            (true,  true)  => return None
+        };
+
+        let mut plan = vec![(keep, gone, 0.0)];
+
+        // the same edge on the other side(s) of a seam
+        let key = |v: u32| self.positions[v as usize].to_array().map(f32::to_bits);
+        let twins = self.coincident.get(&key(gone)).map(Vec::as_slice).unwrap_or(&[]);
+
+        for &twin in twins.iter().filter(|&&twin| twin != gone && self.merged_into[twin as usize] == twin) {
+            let twin_keep = self.border_ends(twin).into_iter().find(|&n| key(n) == key(keep));
+
+            match twin_keep {
+                Some(twin_keep) if self.removable(twin) => plan.push((twin_keep, twin, 0.0)),
+                _ => return None
+            }
+        }
+
+        Some(plan)
+    }
+
+    fn collapse(self: &mut Self, keep: u32, gone: u32, t: f32) {
+        let (k, g) = (keep as usize, gone as usize);
+
+        self.positions[k] = self.positions[k].lerp(self.positions[g], t);
+        self.uvs[k]       = self.uvs[k].lerp(self.uvs[g], t);
+        if !self.colors.is_empty() {
+            self.colors[k] = self.colors[k].lerp(self.colors[g], t);
+        }
+
+        self.merged_into[g] = keep;
+        self.versions[k] += 1;
+        self.alive -= 1;
+
+        // the gone vertex's border edges are the kept one's now
+        if self.border[g] {
+            for &n in &self.neighbors[g] {
+                if self.border_edges.remove(&Self::edge(n, gone)) && n != keep {
+                    self.border_edges.insert(Self::edge(n, keep));
+                }
+            }
+        }
+
+        // so are its neighbors, whose neighborhood changes
+        let gone_neighbors = std::mem::take(&mut self.neighbors[g]);
+        for &n in &gone_neighbors {
+            let list = &mut self.neighbors[n as usize];
+            list.retain(|&v| v != gone);
+            if n != keep && !list.contains(&keep) {
+                list.push(keep);
+            }
+            self.versions[n as usize] += 1;
+        }
+
+        let mut keep_neighbors = std::mem::take(&mut self.neighbors[k]);
+        keep_neighbors.extend(gone_neighbors);
+        keep_neighbors.retain(|&v| v != keep && v != gone);
+        keep_neighbors.sort_unstable();
+        keep_neighbors.dedup();
+
+        self.neighbors[k] = keep_neighbors;
+    }
+
+    /// The faces that didn't collapse, on the surviving vertices only
+    fn into_mesh(self: &Self, indices: &[u16]) -> PlyMesh {
+        let resolve = |mut v: u32| {
+            while self.merged_into[v as usize] != v {
+                v = self.merged_into[v as usize];
+            }
+            v
+        };
+
+        let mut remap = vec![u16::MAX; self.positions.len()];
+        let mut mesh  = PlyMesh { vertices: Vec::with_capacity(self.alive), indices: Vec::new(), colors: Vec::new() };
+
+        for face in indices.chunks_exact(3) {
+            let [i0, i1, i2] = [face[0], face[1], face[2]].map(|i| resolve(i as u32));
+
+            if i0 == i1 || i1 == i2 || i2 == i0 {
+                continue;
+            }
+
+            for v in [i0, i1, i2] {
+                if remap[v as usize] == u16::MAX {
+                    remap[v as usize] = mesh.vertices.len() as u16;
+                    mesh.vertices.push(Vtx3UV { pos: self.positions[v as usize], uv: self.uvs[v as usize] });
+                    if !self.colors.is_empty() {
+                        mesh.colors.push(self.colors[v as usize]);
+                    }
+                }
+
+                mesh.indices.push(remap[v as usize]);
+            }
+        }
+
+        mesh
+    }
+}
+
+/// How the faces around a vertex weigh into its smooth normal, see `compute_smooth_normals`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NormalWeighting {
+    /// By the faces' areas, large faces dominate and slivers barely count
+    #[default]
+    Area,
+    /// By the angle each face spans at the vertex, however the surface around it is split into triangles.
+    /// The creases between flat parts stay sharper
+    Angle,
+    /// Every face the same
+    Uniform
+}
+
+/// `area`, `angle` or `uniform`
+pub fn parse_normal_weighting(value: &str) -> Option<NormalWeighting> {
+    match value.to_ascii_lowercase().as_str() {
+        "area"    => Some(NormalWeighting::Area),
+        "angle"   => Some(NormalWeighting::Angle),
+        "uniform" => Some(NormalWeighting::Uniform),
+        _ => None
+    }
+}
+
+/// Area of each triangle, half the length of its two edges' cross product
+pub fn compute_face_areas(vertices: &[Vtx3UV], indices: &[u32]) -> Vec<f32> {
+    indices.chunks_exact(3).map(|face| {
+        let p0 = vertices[face[0] as usize].pos;
+        0.5 * (vertices[face[1] as usize].pos - p0).cross(vertices[face[2] as usize].pos - p0).length()
+    }).collect()
+}
+
+/// Per vertex normals, the sum of the adjacent faces' normals weighed as given, normalized.
+/// Degenerate faces are skipped, vertices without any face get a zero normal
+pub fn compute_smooth_normals(vertices: &[Vtx3UV], indices: &[u32], weighting: NormalWeighting) -> Vec<glam::Vec3> {
+    if weighting == NormalWeighting::Angle {
+        return compute_angle_weighted_normals(vertices, indices);
+    }
+
+    let areas = match weighting {
+        NormalWeighting::Area => compute_face_areas(vertices, indices),
+        _ => vec![1.0; indices.len() / 3]
+    };
+
+    let mut normals = vec![glam::Vec3::ZERO; vertices.len()];
+
+    for (face, weight) in indices.chunks_exact(3).zip(areas) {
+        let [i0, i1, i2] = [face[0] as usize, face[1] as usize, face[2] as usize];
+        let p0 = vertices[i0].pos;
+        let normal = (vertices[i1].pos - p0).cross(vertices[i2].pos - p0).normalize_or_zero() * weight;
+
+        normals[i0] += normal;
+        normals[i1] += normal;
+        normals[i2] += normal;
+    }
+
+    normals.into_iter().map(glam::Vec3::normalize_or_zero).collect()
+}
+
+/// `compute_smooth_normals` with each face weighed by the angle between its two edges at the vertex
+pub fn compute_angle_weighted_normals(vertices: &[Vtx3UV], indices: &[u32]) -> Vec<glam::Vec3> {
+    let mut normals = vec![glam::Vec3::ZERO; vertices.len()];
+
+    for face in indices.chunks_exact(3) {
+        let corners = [face[0] as usize, face[1] as usize, face[2] as usize];
+        let [p0, p1, p2] = corners.map(|i| vertices[i].pos);
+
+        // zero for degenerate faces, whose angles would be NaN
+        let normal = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+        if normal == glam::Vec3::ZERO {
+            continue;
+        }
+
+        for (corner, (from, to)) in corners.into_iter().zip([(p1 - p0, p2 - p0), (p2 - p1, p0 - p1), (p0 - p2, p1 - p2)]) {
+            normals[corner] += normal * from.angle_between(to);
+        }
+    }
+
+    normals.into_iter().map(glam::Vec3::normalize_or_zero).collect()
+}
+
+/// Per vertex tangents for normal mapping, using Lengyel's method:
+/// Each triangle's tangent and bitangent are solved from its edges and UV deltas, then accumulated per vertex.
+/// The tangents are orthonormalized against the normals (Gram-Schmidt),
+/// `w` holds the handedness (+1 or -1) that recovers the bitangent's direction.
+/// Triangles with degenerate UVs are skipped, vertices left without a tangent get an arbitrary one perpendicular to the normal.
+pub fn compute_tangents(vertices: &[Vtx3NUV], indices: &[u32]) -> Vec<glam::Vec4> {
+    let mut tangents   = vec![glam::Vec3::ZERO; vertices.len()];
+    let mut bitangents = vec![glam::Vec3::ZERO; vertices.len()];
+
+    for face in indices.chunks_exact(3) {
+        let [i0, i1, i2] = [face[0] as usize, face[1] as usize, face[2] as usize];
+        let (v0, v1, v2) = (&vertices[i0], &vertices[i1], &vertices[i2]);
+
+        let dp1 = v1.pos - v0.pos;
+        let dp2 = v2.pos - v0.pos;
+        let duv1 = v1.uv - v0.uv;
+        let duv2 = v2.uv - v0.uv;
+
+        // relative to the UV edges' lengths, so that finely mapped (small) triangles aren't taken for degenerate ones
+        let det = duv1.x * duv2.y - duv2.x * duv1.y;
+        if det.abs() <= f32::EPSILON * duv1.length() * duv2.length() {
+            continue;
+        }
+
+        let r = 1.0 / det;
+        let tangent   = (dp1 * duv2.y - dp2 * duv1.y) * r;
+        let bitangent = (dp2 * duv1.x - dp1 * duv2.x) * r;
+
+        for i in [i0, i1, i2] {
+            tangents[i]   += tangent;
+            bitangents[i] += bitangent;
+        }
+    }
+
+    vertices.iter().enumerate().map(|(i, vertex)| {
+        let normal  = vertex.normal;
+        let tangent = (tangents[i] - normal * normal.dot(tangents[i])).normalize_or_zero();
+
+        if tangent == glam::Vec3::ZERO {
+            return normal.any_orthonormal_vector().extend(1.0);
+        }
+
+        let handedness = if normal.cross(tangent).dot(bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+        tangent.extend(handedness)
+    }).collect()
+}
+
+/// Loads a PLY mesh on a background thread, handing over the vertices in batches as they're parsed,
+/// so that a large mesh can be shown progressively instead of after seconds of loading.
+/// The faces come last in a PLY file, so the indices only arrive once all the vertices did.
+/// Only ASCII PLY files with the layout `PlyMesh` expects can be streamed, indices are u32
+pub struct PlyMeshStreamer {
+    receiver:     mpsc::Receiver<Vec<Vtx3UV>>,
+    indices:      mpsc::Receiver<Vec<u32>>,
+    batch_size:   usize,
+    vertex_count: usize,
+    face_count:   usize
+}
+
+impl PlyMeshStreamer {
+    /// How many batches may pile up before the loader thread waits for `poll`
+    const QUEUED_BATCHES: usize = 16;
+
+    /// The header is parsed right away, so that the element counts are known up front.
+    /// The loader thread finishes once everything is sent, or the streamer is dropped
+    pub fn new(path: &str, batch_size: usize) -> Result<(Self, JoinHandle<()>), &'static str> {
+        let file = std::fs::File::open(path).map_err(|_| "Can't open the PLY file")?;
+        let mut reader = std::io::BufReader::new(file);
+        let header = PlyStreamHeader::read(&mut reader)?;
+
+        let batch_size = batch_size.max(1);
+        let (sender, receiver) = mpsc::sync_channel(Self::QUEUED_BATCHES);
+        let (indices_sender, indices) = mpsc::sync_channel(1);
+
+        let streamer = Self {
+            receiver, indices, batch_size,
+            vertex_count: header.vertex_count,
+            face_count:   header.face_count
+        };
+
+        let path = path.to_string();
+        let handle = std::thread::spawn(move || {
+            let started = time::Instant::now();
+
+            match header.stream(reader, batch_size, &sender, &indices_sender) {
+                Ok(()) => log::debug!("Streamed {} ({} vertices, {} faces) in {:?}", path, header.vertex_count, header.face_count, started.elapsed()),
+                Err(error) => log::error!("Streaming {} failed: {}", path, error)
+            }
+        });
+
+        Ok((streamer, handle))
+    }
+
+    /// Drains the batches that arrived since the last call, doesn't block
+    pub fn poll(self: &Self) -> Vec<Vec<Vtx3UV>> {
+        self.receiver.try_iter().collect()
+    }
+
+    /// The indices of all the faces, once they're loaded. Only ever returned once
+    pub fn poll_indices(self: &Self) -> Option<Vec<u32>> {
+        self.indices.try_recv().ok()
+    }
+
+    pub fn batch_size(self: &Self) -> usize {
+        self.batch_size
+    }
+
+    pub fn vertex_count(self: &Self) -> usize {
+        self.vertex_count
+    }
+
+    pub fn face_count(self: &Self) -> usize {
+        self.face_count
+    }
+}
+
+/// What the streamer needs from the header: the element counts, and where the used vertex properties are
+#[derive(Clone, Copy)]
+struct PlyStreamHeader {
+    vertex_count: usize,
+    face_count:   usize,
+    columns:      [usize; 5] // columns of x, y, z, s, t in the vertex lines
+}
+
+impl PlyStreamHeader {
+    fn read<R: std::io::BufRead>(reader: &mut R) -> Result<Self, &'static str> {
+        let mut line = String::new();
+        let mut next_line = |line: &mut String| {
+            line.clear();
+            match reader.read_line(line) {
+                Ok(0) | Err(_) => Err("Unexpected end of the PLY header"),
+                Ok(_) => Ok(())
+            }
+        };
+
+        next_line(&mut line)?;
+        if line.trim() != "ply" {
+            return Err("Not a PLY file");
+        }
+
+        let mut vertex_count = None;
+        let mut face_count   = None;
+        let mut properties: Vec<String> = Vec::new();
+        let mut element = String::new();
+
+        loop {
+            next_line(&mut line)?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            match words.as_slice() {
+                ["end_header"] => break,
+                ["format", format, _] if *format != "ascii" => return Err("Only ASCII PLY files can be streamed"),
+                ["element", name, count] => {
+                    let count = count.parse::<usize>().map_err(|_| "Malformed element count")?;
+                    match *name {
+                        "vertex" => vertex_count = Some(count),
+                        "face"   => face_count   = Some(count),
+                        _ => return Err("Unexpected element, expected vertex and face only")
+                    }
+                    element = name.to_string();
+                },
+                ["property", .., name] if element == "vertex" => properties.push(name.to_string()),
+                _ => {}
+            }
+        }
+
+        let column = |name| properties.iter().position(|property| property == name).ok_or("Missing vertex property");
+        let columns = [column("x")?, column("y")?, column("z")?, column("s")?, column("t")?];
+
+        Ok(Self {
+            vertex_count: vertex_count.ok_or("Missing vertex element")?,
+            face_count:   face_count.ok_or("Missing face element")?,
+            columns
+        })
+    }
+
+    /// Parse the rest of the file. Stops quietly if the receiving side is gone
+    fn stream<R: std::io::BufRead>(
+        self: &Self,
+        reader:     R,
+        batch_size: usize,
+        sender:     &mpsc::SyncSender<Vec<Vtx3UV>>,
+        indices:    &mpsc::SyncSender<Vec<u32>>
+    ) -> Result<(), &'static str> {
+        let mut lines = reader.lines();
+        let mut batch = Vec::with_capacity(batch_size);
+
+        for _ in 0..self.vertex_count {
+            let line = lines.next().and_then(|line| line.ok()).ok_or("Unexpected end of vertex data")?;
+            let values: Vec<&str> = line.split_whitespace().collect();
+
+            let value = |column: usize| values.get(column).and_then(|v| v.parse::<f32>().ok()).ok_or("Illegal data in vertex, expected float");
+            let [x, y, z, s, t] = self.columns;
+
+            batch.push(Vtx3UV {
+                pos: glam::Vec3::new(value(x)?, value(y)?, value(z)?),
+                uv:  glam::Vec2::new(value(s)?, value(t)?)
+            });
+
+            if batch.len() == batch_size && sender.send(std::mem::replace(&mut batch, Vec::with_capacity(batch_size))).is_err() {
+                return Ok(());
+            }
+        }
+
+        if !batch.is_empty() && sender.send(batch).is_err() {
+            return Ok(());
+        }
+
+        let mut face_indices = Vec::with_capacity(self.face_count * 3);
+
+        for _ in 0..self.face_count {
+            let line = lines.next().and_then(|line| line.ok()).ok_or("Unexpected end of face data")?;
+            let values: Vec<u32> = line.split_whitespace()
+                .map(|v| v.parse::<u32>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| "Illegal data in face, expected uint")?;
+
+            match values.as_slice() {
+                [3, i0, i1, i2] if (*i0.max(i1).max(i2) as usize) < self.vertex_count => face_indices.extend_from_slice(&[*i0, *i1, *i2]),
+                [3, ..] => return Err("Face index out of range"),
+                _ => return Err("Illegal index count in face, expected 3")
+            }
+        }
+
+        let _ = indices.send(face_indices);
+        Ok(())
+    }
+}
+
+/// Writes an ASCII PLY file in batches, in the layout `PlyMesh` and `PlyMeshStreamer` read (positions and UVs,
+/// triangles), e.g. for recording a sequence of deformed meshes for offline processing.
+/// The element counts go into the header up front, so exactly that many vertices, then faces, have to follow
+pub struct PlyWriter {
+    file:             std::io::BufWriter<std::fs::File>,
+    vertex_count:     usize,
+    face_count:       usize,
+    vertices_written: usize,
+    faces_written:    usize
+}
+
+impl PlyWriter {
+    pub fn new(path: &Path, vertex_count: usize, face_count: usize) -> std::io::Result<Self> {
+        use std::io::Write;
+
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+        writeln!(file, "ply\nformat ascii 1.0\ncomment Written by wgpu-clocks")?;
+        writeln!(file, "element vertex {}", vertex_count)?;
+        writeln!(file, "property float x\nproperty float y\nproperty float z\nproperty float s\nproperty float t")?;
+        writeln!(file, "element face {}", face_count)?;
+        writeln!(file, "property list uchar uint vertex_indices\nend_header")?;
+
+        Ok(Self { file, vertex_count, face_count, vertices_written: 0, faces_written: 0 })
+    }
+
+    /// Append vertices, up to the count given to `new` in total
+    pub fn write_vertex_batch(self: &mut Self, vertices: &[Vtx3UV]) -> std::io::Result<()> {
+        use std::io::Write;
+
+        if self.vertices_written + vertices.len() > self.vertex_count {
+            return Err(invalid_ply_input(format!("More than the {} vertices of the header", self.vertex_count)));
+        }
+
+        // the shortest representation that reads back as the same f32
+        for vertex in vertices {
+            writeln!(self.file, "{} {} {} {} {}", vertex.pos.x, vertex.pos.y, vertex.pos.z, vertex.uv.x, vertex.uv.y)?;
+        }
+
+        self.vertices_written += vertices.len();
+        Ok(())
+    }
+
+    /// Append triangles, three indices each, once all the vertices are written
+    pub fn write_face_batch(self: &mut Self, indices: &[u32]) -> std::io::Result<()> {
+        use std::io::Write;
+
+        if self.vertices_written < self.vertex_count {
+            return Err(invalid_ply_input(format!("Faces before all of the {} vertices", self.vertex_count)));
+        }
+
+        if !indices.len().is_multiple_of(3) {
+            return Err(invalid_ply_input(format!("{} indices don't make whole triangles", indices.len())));
+        }
+
+        if self.faces_written + indices.len() / 3 > self.face_count {
+            return Err(invalid_ply_input(format!("More than the {} faces of the header", self.face_count)));
+        }
+
+        if let Some(index) = indices.iter().find(|index| **index as usize >= self.vertex_count) {
+            return Err(invalid_ply_input(format!("Index {} is out of the {} vertices", index, self.vertex_count)));
+        }
+
+        for face in indices.chunks_exact(3) {
+            writeln!(self.file, "3 {} {} {}", face[0], face[1], face[2])?;
+        }
+
+        self.faces_written += indices.len() / 3;
+        Ok(())
+    }
+
+    /// Flush and close the file, fails if fewer vertices or faces were written than the header says
+    pub fn finalize(mut self: Self) -> std::io::Result<()> {
+        use std::io::Write;
+
+        if (self.vertices_written, self.faces_written) != (self.vertex_count, self.face_count) {
+            return Err(invalid_ply_input(format!(
+                "{} vertices and {} faces written, the header says {} and {}",
+                self.vertices_written, self.faces_written, self.vertex_count, self.face_count
+            )));
+        }
+
+        self.file.flush()
+    }
 }
 
-impl RenderTexture {
-    pub fn new(
-        size: (u32, u32), format: wgpu::TextureFormat,
-        bindable: bool, device: &wgpu::Device
-    ) -> Self {
-        let (width, height) = size;
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label:           None,
-            size:            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
-            mip_level_count: 1,
-            sample_count:    1,
-            dimension:       wgpu::TextureDimension::D2,
-            view_formats:    &[],
-            usage:           match bindable {
-                false => wgpu::TextureUsages::RENDER_ATTACHMENT, // usually depth only targets
-                true  => wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING // usually color targets
-            },
-            format
+fn invalid_ply_input(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, message)
+}
+
+#[allow(dead_code)]
+pub struct PlyGeoBuffers {
+    pub vbuffer: wgpu::Buffer,
+    pub ibuffer: wgpu::Buffer,
+    pub vcount:  usize,
+    pub icount:  usize,
+    pub index_format: wgpu::IndexFormat,
+    streamer: Option<PlyMeshStreamer> // while still loading, see `from_streamer`
+}
+
+impl PlyGeoBuffers {
+    pub fn new(device: &wgpu::Device, path: &str) -> Result<Self, String> {
+        Ok(Self::from_mesh(device, &PlyMesh::new(path)?))
+    }
+
+    /// Plain `Vtx3UV` vertices of an already loaded mesh
+    pub fn from_mesh(device: &wgpu::Device, mesh: &PlyMesh) -> Self {
+        let (vbuffer, ibuffer) = create_vertex_and_index_buffers(
+            device,
+            cast_slice_to_u8_slice(mesh.vertices.as_slice()),
+            cast_slice_to_u8_slice(mesh.indices.as_slice())
+        );
+
+        Self {
+            vbuffer, ibuffer,
+            vcount: mesh.vertices.len(),
+            icount: mesh.indices.len(),
+            index_format: wgpu::IndexFormat::Uint16,
+            streamer: None
+        }
+    }
+
+    /// Levels of detail generated from a single detailed mesh (see `PlyMesh::simplified`), one per ratio of the
+    /// vertices kept, e.g. `[1.0, 0.5, 0.25]` for the mesh as is, then halved twice. Each level is simplified
+    /// from the one before, so that they stay alike and the coarse levels come cheap
+    pub fn generate_lods(device: &wgpu::Device, mesh: PlyMesh, lod_ratios: &[f32]) -> Vec<Self> {
+        let vertex_count = mesh.vertices.len().max(1) as f32;
+        let mut level = mesh;
+
+        lod_ratios.iter().map(|&ratio| {
+            let started = time::Instant::now();
+            let relative = (vertex_count * ratio / level.vertices.len().max(1) as f32).min(1.0);
+            level = level.simplified(relative);
+
+            log::debug!("LOD {}: {} vertices, {} faces, simplified in {:?}", ratio, level.vertices.len(), level.indices.len() / 3, started.elapsed());
+            Self::from_mesh(device, &level)
+        }).collect()
+    }
+
+    /// Vertices with blend weights (`Vtx3ColorUV`) instead of plain `Vtx3UV` ones, for texture splatting
+    pub fn new_colored(device: &wgpu::Device, path: &str) -> Result<Self, String> {
+        let mesh = PlyMesh::new_colored(path)?;
+        let vertices = mesh.with_blend_weights();
+
+        let (vbuffer, ibuffer) = create_vertex_and_index_buffers(
+            device,
+            cast_slice_to_u8_slice(vertices.as_slice()),
+            cast_slice_to_u8_slice(mesh.indices.as_slice())
+        );
+
+        Ok(Self {
+            vbuffer, ibuffer,
+            vcount: vertices.len(),
+            icount: mesh.indices.len(),
+            index_format: wgpu::IndexFormat::Uint16,
+            streamer: None
+        })
+    }
+
+    /// Buffers sized for the whole mesh, filled by `update` as the streamer delivers.
+    /// Until then `vcount` is the number of vertices loaded so far, and `icount` stays 0 until the indices arrive
+    pub fn from_streamer(device: &wgpu::Device, queue: &wgpu::Queue, streamer: PlyMeshStreamer) -> Self {
+        let buffer = |size: usize, usage: wgpu::BufferUsages| device.create_buffer(&wgpu::BufferDescriptor {
+            label:              None,
+            size:               (size as u64).max(wgpu::COPY_BUFFER_ALIGNMENT),
+            usage:              usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
         });
 
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut buffers = Self {
+            vbuffer: buffer(streamer.vertex_count * std::mem::size_of::<Vtx3UV>(), wgpu::BufferUsages::VERTEX),
+            ibuffer: buffer(streamer.face_count * 3 * std::mem::size_of::<u32>(), wgpu::BufferUsages::INDEX),
+            vcount:  0,
+            icount:  0,
+            index_format: wgpu::IndexFormat::Uint32,
+            streamer: Some(streamer)
+        };
 
-        Self { texture, view, format, width, height }
+        buffers.update(queue);
+        buffers
     }
 
-    pub fn get_layout_entry(self: &Self, binding: u32) -> wgpu::BindGroupLayoutEntry {
-        wgpu::BindGroupLayoutEntry {
-            binding,
-            visibility: wgpu::ShaderStages::FRAGMENT,
-            ty: wgpu::BindingType::Texture {
-                multisampled:   false,
-                sample_type:    wgpu::TextureSampleType::Float { filterable: true },
-                view_dimension: wgpu::TextureViewDimension::D2
-            },
-            count: None
+    /// Upload whatever the streamer delivered since the last call, returns whether anything arrived
+    pub fn update(self: &mut Self, queue: &wgpu::Queue) -> bool {
+        let Some(streamer) = &self.streamer else {
+            return false;
+        };
+
+        // indices first: they're sent after the last batch, so if they're here, so are all the batches
+        let indices = streamer.poll_indices();
+        let batches = streamer.poll();
+        let arrived = !batches.is_empty() || indices.is_some();
+
+        for batch in batches {
+            let offset = (self.vcount * std::mem::size_of::<Vtx3UV>()) as wgpu::BufferAddress;
+            queue.write_buffer(&self.vbuffer, offset, cast_slice_to_u8_slice(batch.as_slice()));
+            self.vcount += batch.len();
         }
-    }
 
-    pub fn get_entry(self: &Self, binding: u32) -> wgpu::BindGroupEntry {
-        wgpu::BindGroupEntry {
-            binding,
-            resource: wgpu::BindingResource::TextureView(&self.view)
+        // the indices are the last to arrive, nothing else is coming after
+        if let Some(indices) = indices {
+            queue.write_buffer(&self.ibuffer, 0, cast_slice_to_u8_slice(indices.as_slice()));
+            self.icount   = indices.len();
+            self.streamer = None;
         }
+
+        arrived
     }
 
-    pub fn default_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
-        wgpu::BindGroupLayoutEntry {
-            binding,
-            visibility: wgpu::ShaderStages::FRAGMENT,
-            ty: wgpu::BindingType::Texture {
-                multisampled:   false,
-                sample_type:    wgpu::TextureSampleType::Float { filterable: true },
-                view_dimension: wgpu::TextureViewDimension::D2
-            },
-            count: None
-        }
+    /// Whether all of the mesh is uploaded
+    pub fn is_complete(self: &Self) -> bool {
+        self.streamer.is_none()
     }
 }
 
-/// Collection of data that can be used for adapting with various window size and aspect ratio
-/// The WGPU shader coordinate system is [-1..1] in both axes, with origin (0, 0) in the middle.
-/// The system stretches/compresses as window gets resized, but we need uniform scaling for both axes.
-/// Also, the examples use units that can be out of the [-1..1] range.
-/// 
-/// As for such, introducing adaptive scaling system for x and y axis.
-/// There is a 2D measurement called `extent`, which is a rectangle,
-/// whose `width = 2 * extent.x` and `height = 2 * extent.y`.
-/// Thus the range is `[-extent.x..extent.x]` (horizontally) and `[-extent.y..extent.y]` (vertically)
-/// The center of the rectangle is the origin. Any point described within this extent is guaranteed to be on the screen/window.
-/// Though being 2D, it can also be applied to 3D transformation matrices as well.
-#[repr(C, align(8))]
-pub struct DrawspaceScales {
-    /// the scale that'll be applied to the vertices
-    pub scale: glam::Vec2,
-    /// the supplied extent
-    pub extent: glam::Vec2,
-    /// the supplied screen resolution
-    pub resolution: glam::Vec2,
-    /// amount of pixels per drawing unit, changes according to screen size
-    pub density: f32
+/// Like `PlyGeoBuffers`, but with normals and tangents (`Vtx3NUVT`) for normal mapping.
+/// The indices are u32
+pub struct PlyGeoBuffersNUVT {
+    pub vbuffer: wgpu::Buffer,
+    pub ibuffer: wgpu::Buffer,
+    pub vcount:  usize,
+    pub icount:  usize
 }
 
-impl DrawspaceScales {
-    pub fn new(resolution: glam::Vec2, extent: glam::Vec2) -> Self {
-        let [width, height] = resolution.to_array();
-        let [ext_x, ext_y]  = extent.to_array();
+impl PlyGeoBuffersNUVT {
+    pub fn new(device: &wgpu::Device, path: &str) -> Result<Self, String> {
+        Self::new_with_weighting(device, path, NormalWeighting::default())
+    }
 
-        let aspect_ratio_window = width / height;
-        let aspect_ratio_extent = ext_x / ext_y;
+    /// With the normals' faces weighed as given, see `NormalWeighting`
+    pub fn new_with_weighting(device: &wgpu::Device, path: &str, weighting: NormalWeighting) -> Result<Self, String> {
+        let mesh = PlyMesh::new(path)?;
 
-        if aspect_ratio_window > aspect_ratio_extent {
-            // the window's ceiling+floor touche the extent's ceiling+floor
-            let scale = glam::Vec2::new(aspect_ratio_window * ext_y, ext_y).recip();
-            let density = (height * 0.5) / ext_y;
-            Self { scale, extent, resolution, density }
-        } else {
-            // the window's side walls touche the extent's side walls
-            let scale = glam::Vec2::new(ext_x, (1.0 / aspect_ratio_window) * ext_x).recip();
-            let density = (width * 0.5) / ext_x;
-            Self { scale, extent, resolution, density }
-        }
-    }
-}
+        let vertices = mesh.with_normals_weighted(weighting);
+        let indices: Vec<u32> = mesh.indices.iter().map(|&i| i as u32).collect();
+        let tangents = compute_tangents(&vertices, &indices);
 
-#[repr(C)]
-#[derive(Clone, Copy, Debug)]
-pub struct Vtx2ID {
-    pub pos: glam::Vec2,
-    pub id:  u32
-}
+        let vertices: Vec<Vtx3NUVT> = vertices.iter().zip(tangents).map(|(vertex, tangent)| Vtx3NUVT {
+            pos:    vertex.pos,
+            normal: vertex.normal,
+            uv:     vertex.uv,
+            tangent
+        }).collect();
 
-#[derive(Clone, Copy, Debug)]
-#[repr(C)]
-pub struct Vtx3UV {
-    pub pos: glam::Vec3,
-    pub uv:  glam::Vec2
+        let (vbuffer, ibuffer) = create_vertex_and_index_buffers(
+            device,
+            cast_slice_to_u8_slice(vertices.as_slice()),
+            cast_slice_to_u8_slice(indices.as_slice())
+        );
+
+        Ok(Self {
+            vbuffer, ibuffer,
+            vcount: vertices.len(),
+            icount: indices.len()
+        })
+    }
 }
 
+/// A triangle mesh from a Wavefront OBJ file, for models that aren't exported as PLY.
+/// Only positions, UVs and normals are read (`v`, `vt`, `vn` and `f`), materials, groups and the like are skipped.
+/// Each distinct position/UV/normal triplet of the faces becomes a vertex, a missing UV is (0, 0).
+/// Corners without a normal get a smooth one, derived as `PlyMesh::with_normals` does, the explicit normals are kept
 #[derive(Debug)]
-pub struct PlyMesh {
-    pub vertices: Vec<Vtx3UV>,
+pub struct ObjMesh {
+    pub vertices: Vec<Vtx3NUV>,
     pub indices:  Vec<u16>
 }
 
-impl PlyMesh {
-    pub fn new(path: &str) -> Result<Self, &str> {
-        use ply_rs::ply::Property::{Float, ListUInt};
-
-        let mut file = std::fs::File::open(path).unwrap();
-        let parser = ply_rs::parser::Parser::<ply_rs::ply::DefaultElement>::new();
-        let ply = parser.read_ply(&mut file);
+impl ObjMesh {
+    pub fn new(path: &str) -> Result<Self, String> {
+        let started = time::Instant::now();
+        let file = std::fs::File::open(path).map_err(|error| format!("Can't open the mesh {}: {}", path, error))?;
+        let mesh = Self::from_reader(std::io::BufReader::new(file)).map_err(|error| format!("Can't load the mesh {}: {}", path, error))?;
 
-        assert!(ply.is_ok());
-        let ply = ply.unwrap();
+        log::debug!("Loaded {} ({} vertices, {} indices) in {:?}", path, mesh.vertices.len(), mesh.indices.len(), started.elapsed());
+        return Ok(mesh);
+    }
 
-        let vertex_count = ply.header.elements["vertex"].count;
-        let face_count   = ply.header.elements["face"].count;
+    /// Parse OBJ data from anything readable line by line, e.g. an in-memory buffer.
+    /// Faces must be triangles, quads and larger polygons are refused rather than triangulated
+    pub fn from_reader<R: std::io::BufRead>(reader: R) -> Result<Self, String> {
+        let mut positions: Vec<glam::Vec3> = Vec::new();
+        let mut uvs:       Vec<glam::Vec2> = Vec::new();
+        let mut normals:   Vec<glam::Vec3> = Vec::new();
 
-        let mut vertices: Vec<Vtx3UV> = Vec::with_capacity(vertex_count);
-        let mut indices:  Vec<u16> = Vec::with_capacity(face_count * 3);
+        // (position, UV, normal) of the faces' corners, indices into the lists above
+        let mut corners: std::collections::HashMap<(usize, Option<usize>, Option<usize>), u16> = std::collections::HashMap::new();
+        let mut vertices: Vec<Vtx3NUV> = Vec::new();
+        let mut indices:  Vec<u16> = Vec::new();
+        let mut without_normals: Vec<usize> = Vec::new(); // the vertices to derive a normal for
 
-        let vertex_payload = &ply.payload["vertex"];
-        let face_payload   = &ply.payload["face"];
+        for (number, line) in reader.lines().enumerate() {
+            let line = line.map_err(|error| error.to_string())?;
+            let at_line = |message: &str| format!("line {}: {}", number + 1, message);
 
+            let mut fields = line.split_whitespace();
+            let keyword = fields.next();
+            let values: Vec<&str> = fields.collect();
 
-        for item in vertex_payload {
-            let collect_f32 = |key| {
-                match item[key] {
-                    Float(val) => val,
-                    _ => std::f32::NAN
+            let floats = |count: usize| -> Result<Vec<f32>, String> {
+                if values.len() < count {
+                    return Err(at_line(&format!("expected {} numbers", count)));
                 }
+
+                values[..count].iter()
+                    .map(|value| value.parse::<f32>().map_err(|_| at_line(&format!("'{}' isn't a number", value))))
+                    .collect()
             };
 
-            let pos = ["x", "y", "z"].map(collect_f32);
-            let uv  = ["s", "t"].map(collect_f32);
+            match keyword {
+                Some("v")  => positions.push(glam::Vec3::from_slice(&floats(3)?)),
+                Some("vt") => uvs.push(glam::Vec2::from_slice(&floats(2)?)),
+                Some("vn") => normals.push(glam::Vec3::from_slice(&floats(3)?)),
+                Some("f")  => {
+                    if values.len() != 3 {
+                        return Err(at_line(&format!("a face of {} vertices, only triangles are supported (triangulate the mesh when exporting)", values.len())));
+                    }
 
-            if pos.into_iter().any(|v| v.is_nan()) || uv.into_iter().any(|v| v.is_nan()) {
-                return Err("Illegal data type in vertex, expected float");
-            }
+                    for corner in values {
+                        let mut parts = corner.split('/');
 
-            vertices.push(Vtx3UV {
-                pos: glam::Vec3::from_array(pos),
-                uv:  glam::Vec2::from_array(uv)
-            });
-        }
+                        // 1-based, negative ones count back from the last one read so far
+                        let mut index = |count: usize, kind: &str| -> Result<Option<usize>, String> {
+                            match parts.next() {
+                                None | Some("") => Ok(None),
+                                Some(text) => {
+                                    let index: i64 = text.parse().map_err(|_| at_line(&format!("'{}' isn't a {} index", text, kind)))?;
+                                    let resolved = if index < 0 { count as i64 + index } else { index - 1 };
 
-        for item in face_payload {
-            match &item["vertex_indices"] {
-                ListUInt(facedata) => {
-                    if facedata.len() != 3 {
-                        return Err("Illegal index count in face, expected 3");
-                    }
+                                    if resolved < 0 || resolved >= count as i64 {
+                                        return Err(at_line(&format!("{} index {} out of range", kind, index)));
+                                    }
 
-                    indices.push(facedata[0] as u16);
-                    indices.push(facedata[1] as u16);
-                    indices.push(facedata[2] as u16);
+                                    Ok(Some(resolved as usize))
+                                }
+                            }
+                        };
+
+                        let position = index(positions.len(), "position")?.ok_or_else(|| at_line("a face corner without a position"))?;
+                        let uv       = index(uvs.len(), "UV")?;
+                        let normal   = index(normals.len(), "normal")?;
+
+                        let key = (position, uv, normal);
+                        let vertex_index = match corners.get(&key) {
+                            Some(&vertex_index) => vertex_index,
+                            None => {
+                                let vertex_index = u16::try_from(vertices.len()).map_err(|_| at_line("more than 65536 vertices"))?;
+
+                                if normal.is_none() {
+                                    without_normals.push(vertices.len());
+                                }
+
+                                vertices.push(Vtx3NUV {
+                                    pos:    positions[position],
+                                    normal: normal.map_or(glam::Vec3::ZERO, |normal| normals[normal]),
+                                    uv:     uv.map_or(glam::Vec2::ZERO, |uv| uvs[uv])
+                                });
+                                corners.insert(key, vertex_index);
+
+                                vertex_index
+                            }
+                        };
+
+                        indices.push(vertex_index);
+                    }
                 },
-                _ => {
-                    return Err("Illegal data type in face, expected uint");
-                }
-            };
+                _ => {} // comments, objects, groups, smoothing groups, materials
+            }
+        }
+
+        if !without_normals.is_empty() {
+            let plain: Vec<Vtx3UV> = vertices.iter().map(|vertex| Vtx3UV { pos: vertex.pos, uv: vertex.uv }).collect();
+            let wide:  Vec<u32> = indices.iter().map(|&i| i as u32).collect();
+            let smooth = compute_smooth_normals(&plain, &wide, NormalWeighting::Area);
+
+            for i in without_normals {
+                vertices[i].normal = smooth[i];
+            }
         }
 
         Ok(Self { vertices, indices })
     }
 }
 
-#[allow(dead_code)]
-pub struct PlyGeoBuffers {
+/// `PlyGeoBuffers` for OBJ meshes, the vertices are `Vtx3NUV` though.
+/// Pipelines built for `PlyGeoBuffers` draw them with `Vtx3NUV::wgpu_layout_without_normals`, which is all it takes
+/// to swap the loader in the portal or the mechanical counter
+pub struct ObjGeoBuffers {
     pub vbuffer: wgpu::Buffer,
     pub ibuffer: wgpu::Buffer,
     pub vcount:  usize,
-    pub icount:  usize
+    pub icount:  usize,
+    pub index_format: wgpu::IndexFormat
 }
 
-impl PlyGeoBuffers {
-    pub fn new(device: &wgpu::Device, path: &str) -> Self {
-        let mesh = PlyMesh::new(path).unwrap();
+impl ObjGeoBuffers {
+    pub fn new(device: &wgpu::Device, path: &str) -> Result<Self, String> {
+        Ok(Self::from_mesh(device, &ObjMesh::new(path)?))
+    }
 
+    pub fn from_mesh(device: &wgpu::Device, mesh: &ObjMesh) -> Self {
         let (vbuffer, ibuffer) = create_vertex_and_index_buffers(
             device,
             cast_slice_to_u8_slice(mesh.vertices.as_slice()),
@@ -558,7 +4582,8 @@ impl PlyGeoBuffers {
         Self {
             vbuffer, ibuffer,
             vcount: mesh.vertices.len(),
-            icount: mesh.indices.len()
+            icount: mesh.indices.len(),
+            index_format: wgpu::IndexFormat::Uint16
         }
     }
 }
@@ -573,7 +4598,7 @@ pub fn cast_struct_to_u8_slice<T>(data: &T) -> &[u8] {
 }
 
 pub fn cast_slice_to_u8_slice<T>(data: &[T]) -> &[u8] {
-    let len = std::mem::size_of::<T>() * data.len();
+    let len = std::mem::size_of_val(data);
 
     unsafe {
         let ptr = data.as_ptr() as *const u8;
@@ -582,10 +4607,11 @@ pub fn cast_slice_to_u8_slice<T>(data: &[T]) -> &[u8] {
 }
 
 pub fn create_vertex_and_index_buffers(device: &wgpu::Device, vdata: &[u8], idata: &[u8]) -> (wgpu::Buffer, wgpu::Buffer) {
+    // rewritable, e.g. for snapping the vertices to the pixel grid after resizing
     let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
         label:    None,
         contents: vdata,
-        usage:    wgpu::BufferUsages::VERTEX
+        usage:    wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST
     });
 
     let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -597,32 +4623,131 @@ pub fn create_vertex_and_index_buffers(device: &wgpu::Device, vdata: &[u8], idat
     (vertex_buffer, index_buffer)
 }
 
-pub fn get_resource_folder_for(sub_folder: &str) -> std::io::Result<PathBuf> {
-    use std::io::{Error, ErrorKind};
+static RESOURCE_FOLDER_OVERRIDE: std::sync::RwLock<Option<PathBuf>> = std::sync::RwLock::new(None);
+
+/// Environment variable naming a resources folder, looked into right after `--resources`
+pub const RESOURCES_ENV: &str = "WGPU_CLOCKS_RESOURCES";
+
+/// Folder name under the platform's data directory, for installed builds
+const DATA_FOLDER: &str = "wgpu-clocks";
+
+/// Make `get_resource_folder_for` look into the given resources folder before the default locations
+pub fn set_resource_folder_override(folder: PathBuf) {
+    *RESOURCE_FOLDER_OVERRIDE.write().unwrap() = Some(folder);
+}
+
+/// Where a clock's resources were found
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResourceLocation {
+    Override,         // --resources
+    Environment,      // WGPU_CLOCKS_RESOURCES
+    WorkingDirectory, // ./resources
+    Executable,       // resources next to the executable
+    DataDirectory     // $XDG_DATA_HOME, ~/Library/Application Support or %APPDATA%
+}
+
+impl std::fmt::Display for ResourceLocation {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ResourceLocation::Override         => "--resources",
+            ResourceLocation::Environment      => RESOURCES_ENV,
+            ResourceLocation::WorkingDirectory => "working directory",
+            ResourceLocation::Executable       => "executable's folder",
+            ResourceLocation::DataDirectory    => "data directory"
+        })
+    }
+}
+
+/// The folders holding the clocks' resource sub folders, in the order they're searched
+pub fn resource_folder_candidates() -> Vec<(ResourceLocation, PathBuf)> {
     #[allow(non_snake_case)]
     let RESOURCE_FOLDER = "resources";
 
-    let mut folder = std::env::current_dir()?;
-    folder.push(RESOURCE_FOLDER);
-    folder.push(sub_folder);
+    let mut candidates = Vec::new();
+
+    if let Some(folder) = RESOURCE_FOLDER_OVERRIDE.read().unwrap().clone() {
+        candidates.push((ResourceLocation::Override, folder));
+    }
 
-    if folder.exists() && folder.is_dir() {
-        return Ok(folder);
+    if let Some(folder) = std::env::var_os(RESOURCES_ENV).filter(|value| !value.is_empty()) {
+        candidates.push((ResourceLocation::Environment, PathBuf::from(folder)));
     }
 
-    let mut folder = std::env::current_exe()?;
-    folder.pop();
-    folder.push(RESOURCE_FOLDER);
-    folder.push(sub_folder);
+    if let Ok(folder) = std::env::current_dir() {
+        candidates.push((ResourceLocation::WorkingDirectory, folder.join(RESOURCE_FOLDER)));
+    }
 
-    if folder.exists() && folder.is_dir() {
-        return Ok(folder);
+    if let Some(folder) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(|parent| parent.join(RESOURCE_FOLDER))) {
+        candidates.push((ResourceLocation::Executable, folder));
     }
-    
-    return Err(Error::new(
-        ErrorKind::NotFound,
-        format!("The specified resource folder {}/{} wasn't found", RESOURCE_FOLDER, sub_folder))
-    );
+
+    if let Some(folder) = dirs::data_dir() {
+        candidates.push((ResourceLocation::DataDirectory, folder.join(DATA_FOLDER)));
+    }
+
+    return candidates;
+}
+
+/// The first candidate that has `sub_folder` in it, according to `is_dir`.
+/// Otherwise every path that was tried, in order
+pub fn search_resource_folder(
+    sub_folder: &str,
+    candidates: &[(ResourceLocation, PathBuf)],
+    is_dir:     impl Fn(&Path) -> bool
+) -> Result<(PathBuf, ResourceLocation), Vec<PathBuf>> {
+    let mut tried = Vec::with_capacity(candidates.len());
+
+    for (location, folder) in candidates {
+        let folder = folder.join(sub_folder);
+
+        if is_dir(&folder) {
+            return Ok((folder, *location));
+        }
+
+        tried.push(folder);
+    }
+
+    return Err(tried);
+}
+
+/// Find the clock's resources, see `resource_folder_candidates` for the search order
+pub fn locate_resource_folder(sub_folder: &str) -> std::io::Result<(PathBuf, ResourceLocation)> {
+    let candidates = resource_folder_candidates();
+
+    match search_resource_folder(sub_folder, &candidates, Path::is_dir) {
+        Ok(found) => Ok(found),
+        Err(tried) => Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "The resources of {} weren't found, tried:\n{}\n\
+                 Run the clock from the repository's root, copy the resources folder next to the executable \
+                 or into the data directory, or point --resources ({}) to it.",
+                sub_folder,
+                tried.iter().map(|path| format!("  {}", path.display())).collect::<Vec<_>>().join("\n"),
+                RESOURCES_ENV
+            )
+        ))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get_resource_folder_for(sub_folder: &str) -> std::io::Result<PathBuf> {
+    let (folder, location) = locate_resource_folder(sub_folder)?;
+
+    // an explicit folder that doesn't work out is worth mentioning, even though another location did
+    if location != ResourceLocation::Override && RESOURCE_FOLDER_OVERRIDE.read().unwrap().is_some() {
+        log::warn!("The resource folder override has no {}, fell back to the {}", sub_folder, location);
+    }
+
+    log::debug!("Resources of {} found at {} ({})", sub_folder, folder.display(), location);
+
+    return Ok(folder);
+}
+
+/// Nothing to search for on the web, the shaders are embedded (see `web::EMBEDDED_SHADERS`)
+#[cfg(target_arch = "wasm32")]
+pub fn get_resource_folder_for(sub_folder: &str) -> std::io::Result<PathBuf> {
+    Ok(Path::new(web::RESOURCES_ROOT).join(sub_folder))
 }
 
 pub const fn rgba32(r: u8, g: u8, b: u8, a: u8) -> u32 {
@@ -633,6 +4758,27 @@ pub const fn rgba32(r: u8, g: u8, b: u8, a: u8) -> u32 {
     return col;
 }
 
+/// `rgba32` from HSV, hue in degrees [0..360) (wrapped around), saturation and value in [0..1], see `palette::Hsv`
+pub fn hsva32(h: f32, s: f32, v: f32, a: u8) -> u32 {
+    palette::Hsv { hue: h, saturation: s.clamp(0.0, 1.0), value: v.clamp(0.0, 1.0), alpha: a }.to_u32_color()
+}
+
+/// `rgba32` from HSL, same ranges as `hsva32`. Lightness 0.5 is the hue at its most colorful, 1.0 is white whatever the hue
+pub fn hsla32(h: f32, s: f32, l: f32, a: u8) -> u32 {
+    let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+
+    let v = l + s * l.min(1.0 - l);
+    let s = if v == 0.0 { 0.0 } else { 2.0 * (1.0 - l / v) };
+
+    hsva32(h, s, v, a)
+}
+
+/// The hue, saturation and value of a 0xRRGGBBAA color, as taken by `hsva32`. Grays have a hue of 0
+pub fn u32_col_to_hsv(col: u32) -> (f32, f32, f32) {
+    let hsv = palette::Hsv::from_u32_color(col);
+    (hsv.hue, hsv.saturation, hsv.value)
+}
+
 pub fn lerp_u32_color(c0: u32, c1: u32, t: f64) -> u32 {
     let c0_r = ((c0 >> 24) & 0xFF) as f64;
     let c0_g = ((c0 >> 16) & 0xFF) as f64;
@@ -652,6 +4798,104 @@ pub fn lerp_u32_color(c0: u32, c1: u32, t: f64) -> u32 {
     return (r << 24) | (g << 16) | (b << 8) | a;
 }
 
+/// The color at `t` along a gradient of (position, 0xRRGGBBAA color) stops, sorted by their positions within [0..1].
+/// The two stops around `t` are interpolated with `lerp_u32_color`, before the first and past the last stop
+/// their colors stay as they are. Panics without stops
+pub fn gradient_u32_color(stops: &[(f64, u32)], t: f64) -> u32 {
+    // the first stop past t, the segment is the one ending there
+    let next = stops.partition_point(|(position, _)| *position <= t);
+
+    if next == 0 {
+        return stops[0].1;
+    }
+    if next == stops.len() {
+        return stops[next - 1].1;
+    }
+
+    let (p0, c0) = stops[next - 1];
+    let (p1, c1) = stops[next];
+
+    lerp_u32_color(c0, c1, (t - p0) / (p1 - p0))
+}
+
+/// `gradient_u32_color` through `u32_col_to_wgpu_col`
+pub fn gradient_wgpu_color(stops: &[(f64, u32)], t: f64) -> wgpu::Color {
+    u32_col_to_wgpu_col(gradient_u32_color(stops, t))
+}
+
+/// An sRGB encoded byte, as palettes and image editors have them, to the linear value the GPU blends in
+pub fn srgb_u8_to_linear_f64(value: u8) -> f64 {
+    let encoded = value as f64 / 255.0;
+
+    if encoded <= 0.04045 {
+        encoded / 12.92
+    } else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// `srgb_u8_to_linear_f64` in single precision
+pub fn srgb_u8_to_linear_f32(value: u8) -> f32 {
+    srgb_u8_to_linear_f64(value) as f32
+}
+
+/// Back from `srgb_u8_to_linear_f64`, rounded to the nearest byte, values outside [0..1] are clamped
+pub fn linear_to_srgb_u8(value: f64) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+
+    let encoded = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+
+    (encoded * 255.0).round() as u8
+}
+
+/// Linear sRGB to OKLab (Björn Ottosson), lightness in [0..1] and the two opponent axes
+fn linear_to_oklab([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s
+    ]
+}
+
+fn oklab_to_linear([lightness, a, b]: [f64; 3]) -> [f64; 3] {
+    let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+    [
+         4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s
+    ]
+}
+
+/// Like `lerp_u32_color`, through OKLab instead of the sRGB bytes: the lightness changes evenly
+/// and the hues in between don't turn muddy. The alpha is interpolated as it is
+pub fn lerp_u32_color_oklab(c0: u32, c1: u32, t: f64) -> u32 {
+    let to_oklab = |col: u32| linear_to_oklab([
+        srgb_u8_to_linear_f64((col >> 24) as u8),
+        srgb_u8_to_linear_f64((col >> 16) as u8),
+        srgb_u8_to_linear_f64((col >>  8) as u8)
+    ]);
+
+    let (lab0, lab1) = (to_oklab(c0), to_oklab(c1));
+    let [r, g, b] = oklab_to_linear(std::array::from_fn(|i| (1.0 - t) * lab0[i] + t * lab1[i]));
+
+    let a = ((1.0 - t) * (c0 & 0xFF) as f64 + t * (c1 & 0xFF) as f64) as u32;
+
+    (linear_to_srgb_u8(r) as u32) << 24 | (linear_to_srgb_u8(g) as u32) << 16 | (linear_to_srgb_u8(b) as u32) << 8 | a
+}
+
+/// A 0xRRGGBBAA color with its bytes divided by 255 as they are. Only right where the values are used as they are,
+/// e.g. non-sRGB targets. The colors of the clocks go through `u32_col_to_wgpu_col_linear`
 pub fn u32_col_to_wgpu_col(col: u32) -> wgpu::Color {
     let col_r = ((col >> 24) & 0xFF) as f64;
     let col_g = ((col >> 16) & 0xFF) as f64;
@@ -666,4 +4910,16 @@ pub fn u32_col_to_wgpu_col(col: u32) -> wgpu::Color {
         b: col_b * scale,
         a: col_a * scale
     };
-}
\ No newline at end of file
+}
+
+/// A 0xRRGGBBAA palette color (sRGB bytes, linear alpha) to the linear color clears and push constants expect.
+/// The sRGB targets encode it back, so a background cleared with it has the same bytes as the hex,
+/// and as the same color drawn by a shader (`color_u32_to_vec4f` of color.wgsl)
+pub fn u32_col_to_wgpu_col_linear(col: u32) -> wgpu::Color {
+    return wgpu::Color {
+        r: srgb_u8_to_linear_f64((col >> 24) as u8),
+        g: srgb_u8_to_linear_f64((col >> 16) as u8),
+        b: srgb_u8_to_linear_f64((col >>  8) as u8),
+        a: (col & 0xFF) as f64 / 255.0
+    };
+}
@@ -0,0 +1,50 @@
+//! Logging setup shared by the binaries.
+//!
+//! Everything goes through the `log` facade. Only warnings are shown by default,
+//! `RUST_LOG=clockutils=debug` (or any other `env_logger` filter) reveals the details.
+//! Windows GUI builds have no console to write to, so the log goes to `<binary>.log` next to the executable there.
+
+use std::path::PathBuf;
+
+/// Environment variable naming a folder to capture wgpu API traces into.
+/// Only effective when built with the `trace` feature
+pub const TRACE_ENV: &str = "WGPU_CLOCKS_TRACE";
+
+/// Install the logger, call this first thing in `main`
+pub fn init(name: &str) {
+    let mut builder = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"));
+
+    #[cfg(all(target_os = "windows", not(feature = "console")))]
+    {
+        let path = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|folder| folder.join(format!("{}.log", name))));
+
+        if let Some(file) = path.and_then(|path| std::fs::File::create(path).ok()) {
+            builder.target(env_logger::Target::Pipe(Box::new(file)));
+        }
+    }
+
+    // ignoring the error, a logger might've been installed already (e.g. by tests)
+    let _ = builder.try_init();
+
+    log::debug!("{} starting", name);
+}
+
+/// Folder to capture the wgpu API trace into, if requested via `WGPU_CLOCKS_TRACE`
+pub fn trace_path() -> Option<PathBuf> {
+    let folder = PathBuf::from(std::env::var_os(TRACE_ENV)?);
+
+    if cfg!(not(feature = "trace")) {
+        log::warn!("{} is set, but tracing is not compiled in (build with `--features trace`)", TRACE_ENV);
+        return None;
+    }
+
+    if let Err(error) = std::fs::create_dir_all(&folder) {
+        log::warn!("Can't create the trace folder {}: {}", folder.display(), error);
+        return None;
+    }
+
+    log::info!("Capturing wgpu API trace into {}", folder.display());
+    return Some(folder);
+}
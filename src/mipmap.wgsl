@@ -0,0 +1,13 @@
+// Mipmap generation: each level drawn with `FullscreenQuadPipeline` from the one above it, twice its size.
+// Sampled bilinearly in between the above level's pixels, which averages them 2x2.
+
+@group(0) @binding(0)
+var above: texture_2d<f32>;
+
+@group(0) @binding(1)
+var above_sampler: sampler;
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return textureSampleLevel(above, above_sampler, uv, 0.0);
+}
@@ -0,0 +1,195 @@
+//! On-screen frame statistics.
+//!
+//! The runner draws the overlay on top of whatever the clock drew, in a pass of its own,
+//! so it needs nothing from the clock's pipelines or bind groups. It's shown with `--stats` (`RunOptions::stats`),
+//! or by calling `enable()` from the clock's `setup`. The text is rendered with `font::BitmapFontRenderer`.
+
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use crate::FrameStats;
+use crate::font::{BitmapFontRenderer, TextAnchor};
+
+const COLUMNS: usize = 16;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Show the overlay even without `--stats`, meant to be called from a clock's `setup`
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub struct StatsOverlay {
+    text: BitmapFontRenderer
+}
+
+impl StatsOverlay {
+    pub fn new(config: &wgpu::SurfaceConfiguration, device: &wgpu::Device) -> Self {
+        let mut overlay = Self { text: BitmapFontRenderer::new(config.format, TextAnchor::TopLeft, device) };
+        overlay.resize(config.width, config.height);
+
+        overlay
+    }
+
+    pub fn resize(self: &mut Self, width: u32, height: u32) {
+        self.text.resize(width, height);
+    }
+
+    /// Format the stats into the text grid
+    pub fn update(self: &mut Self, stats: &FrameStats) {
+        self.text.clear(COLUMNS);
+
+        if let Some(mut line) = self.text.push_line() {
+            let _ = write!(line, "FPS {:>8.1}", stats.fps);
+        }
+
+        if let Some(mut line) = self.text.push_line() {
+            let _ = write!(line, "FRAME {:>6.2}ms", stats.frame_time.as_secs_f64() * 1000.0);
+        }
+
+        if let Some(mut line) = self.text.push_line() {
+            let _ = write!(line, "PRESENT {:>6.2}ms", stats.present_interval.as_secs_f64() * 1000.0);
+        }
+
+        if let Some(gpu_time) = stats.gpu_time {
+            if let Some(mut line) = self.text.push_line() {
+                let _ = write!(line, "GPU {:>8.2}ms", gpu_time.as_secs_f64() * 1000.0);
+            }
+        }
+
+        if let Some(latency) = stats.frame_latency {
+            if let Some(mut line) = self.text.push_line() {
+                let _ = write!(line, "LATENCY {:>8}", latency);
+            }
+        }
+    }
+
+    /// Draw on top of the already rendered frame
+    pub fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.text.draw(texview, device, queue);
+    }
+}
+
+/// Measures the GPU time of the clock's `draw` with a pair of timestamp queries around it.
+/// Needs `wgpu::Features::TIMESTAMP_QUERY`. The result is read back without blocking,
+/// so it lags a frame or two behind, and frames are skipped while a readback is pending
+pub(crate) struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve:   wgpu::Buffer,
+    readback:  wgpu::Buffer,
+    period:    f32, // nanoseconds per timestamp tick
+    mapped:    Arc<AtomicBool>,
+    in_flight: bool,
+    measuring: bool,
+    last:      Option<Duration>
+}
+
+impl GpuTimer {
+    const SIZE: u64 = 2 * std::mem::size_of::<u64>() as u64;
+
+    /// `None` if the device doesn't support timestamp queries
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("GPU timer"),
+            ty:    wgpu::QueryType::Timestamp,
+            count: 2
+        });
+
+        let resolve = device.create_buffer(&wgpu::BufferDescriptor {
+            label:              Some("GPU timer resolve"),
+            size:               Self::SIZE,
+            usage:              wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false
+        });
+
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label:              Some("GPU timer readback"),
+            size:               Self::SIZE,
+            usage:              wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false
+        });
+
+        Some(Self {
+            query_set, resolve, readback,
+            period:    queue.get_timestamp_period(),
+            mapped:    Arc::new(AtomicBool::new(false)),
+            in_flight: false,
+            measuring: false,
+            last:      None
+        })
+    }
+
+    /// The latest measurement
+    pub fn last(self: &Self) -> Option<Duration> {
+        self.last
+    }
+
+    pub fn begin(self: &mut Self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.collect(device);
+
+        if self.in_flight {
+            return;
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("GPU timer") });
+        encoder.write_timestamp(&self.query_set, 0);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        self.measuring = true;
+    }
+
+    pub fn end(self: &mut Self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if !self.measuring {
+            return;
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("GPU timer") });
+        encoder.write_timestamp(&self.query_set, 1);
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve, 0, &self.readback, 0, Self::SIZE);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let mapped = self.mapped.clone();
+        self.readback.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            if result.is_ok() {
+                mapped.store(true, Ordering::Release);
+            }
+        });
+
+        self.measuring = false;
+        self.in_flight = true;
+    }
+
+    /// Pick up the pending measurement, if the GPU is done with it
+    fn collect(self: &mut Self, device: &wgpu::Device) {
+        if !self.in_flight {
+            return;
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+
+        if !self.mapped.swap(false, Ordering::Acquire) {
+            return;
+        }
+
+        {
+            let data = self.readback.slice(..).get_mapped_range();
+            let start = u64::from_le_bytes(data[0..8].try_into().unwrap());
+            let end   = u64::from_le_bytes(data[8..16].try_into().unwrap());
+            let nanos = end.wrapping_sub(start) as f64 * self.period as f64;
+            self.last = Some(Duration::from_nanos(nanos as u64));
+        }
+
+        self.readback.unmap();
+        self.in_flight = false;
+    }
+}
@@ -0,0 +1,409 @@
+//! Color palettes of the 2D clocks, loaded from and saved to TOML, and edited live.
+//!
+//! A palette file lists the palettes in order, each one a table of slot names (which part of the clock is colored)
+//! and `#RRGGBB` or `#RRGGBBAA` colors, e.g. for the polar clock:
+//!
+//! ```toml
+//! [[palette]]
+//! hour = "#171738"
+//! minute = "#2e1760"
+//! ```
+//!
+//! Palettes and slots missing from the file keep the clock's built-in colors, so a file only needs what it changes.
+//! Colors are packed as `0xRRGGBBAA`, same as everywhere else.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Instant;
+use serde::{Serialize, Deserialize};
+use crate::keys::{self, KeyBinding};
+
+/// A color in HSV, hue in degrees [0..360), saturation and value in [0..1], alpha as is
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsv {
+    pub hue:        f32,
+    pub saturation: f32,
+    pub value:      f32,
+    pub alpha:      u8
+}
+
+impl Hsv {
+    pub fn from_u32_color(color: u32) -> Self {
+        let r = ((color >> 24) & 0xFF) as f32 / 255.0;
+        let g = ((color >> 16) & 0xFF) as f32 / 255.0;
+        let b = ((color >>  8) & 0xFF) as f32 / 255.0;
+
+        let max   = r.max(g).max(b);
+        let min   = r.min(g).min(b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0 // gray, any hue would do
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        Self {
+            hue,
+            saturation: if max == 0.0 { 0.0 } else { delta / max },
+            value:      max,
+            alpha:      (color & 0xFF) as u8
+        }
+    }
+
+    pub fn to_u32_color(self: &Self) -> u32 {
+        let chroma = self.value * self.saturation;
+        let sector = self.hue.rem_euclid(360.0) / 60.0;
+        let x      = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x)
+        };
+
+        let m = self.value - chroma;
+        let channel = |c: f32| ((c + m).clamp(0.0, 1.0) * 255.0).round() as u32;
+
+        (channel(r) << 24) | (channel(g) << 16) | (channel(b) << 8) | self.alpha as u32
+    }
+
+    /// Hue turned around the wheel, saturation and value moved within their ranges
+    pub fn adjusted(self: &Self, hue: f32, saturation: f32, value: f32) -> Self {
+        Self {
+            hue:        (self.hue + hue).rem_euclid(360.0),
+            saturation: (self.saturation + saturation).clamp(0.0, 1.0),
+            value:      (self.value + value).clamp(0.0, 1.0),
+            alpha:      self.alpha
+        }
+    }
+}
+
+/// `#RRGGBB` (opaque) or `#RRGGBBAA`, the `#` is optional
+pub fn parse_hex_color(text: &str) -> Option<u32> {
+    let digits = text.trim().trim_start_matches('#');
+
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    match digits.len() {
+        6 => u32::from_str_radix(digits, 16).ok().map(|rgb| (rgb << 8) | 0xFF),
+        8 => u32::from_str_radix(digits, 16).ok(),
+        _ => None
+    }
+}
+
+/// `#rrggbb` for opaque colors, `#rrggbbaa` for the rest
+pub fn format_hex_color(color: u32) -> String {
+    if color & 0xFF == 0xFF {
+        format!("#{:06x}", color >> 8)
+    } else {
+        format!("#{:08x}", color)
+    }
+}
+
+/// The color moved towards white if it's dark, towards black otherwise, by `amount` in [0..1].
+/// Makes the color stand out from itself, whatever it is
+pub fn highlight_u32_color(color: u32, amount: f32) -> u32 {
+    let r = ((color >> 24) & 0xFF) as f32;
+    let g = ((color >> 16) & 0xFF) as f32;
+    let b = ((color >>  8) & 0xFF) as f32;
+
+    let luminance = (0.2126 * r + 0.7152 * g + 0.0722 * b) / 255.0;
+    let target    = if luminance < 0.5 { 255.0 } else { 0.0 };
+    let channel   = |c: f32| ((c + (target - c) * amount.clamp(0.0, 1.0)).round() as u32).min(255);
+
+    (channel(r) << 24) | (channel(g) << 16) | (channel(b) << 8) | (color & 0xFF)
+}
+
+/// On-disk layout, see the module docs
+#[derive(Default, Serialize, Deserialize)]
+struct PaletteFile {
+    #[serde(default)]
+    palette: Vec<BTreeMap<String, String>>
+}
+
+/// A clock's palettes, each one a color per slot, in the order of the slot names
+#[derive(Clone, Debug)]
+pub struct PaletteSet {
+    slots:    &'static [&'static str],
+    palettes: Vec<Vec<u32>>,
+    path:     Option<PathBuf>
+}
+
+impl PaletteSet {
+    /// The built-in palettes, with no file to save to
+    pub fn new(slots: &'static [&'static str], defaults: Vec<Vec<u32>>) -> Self {
+        Self { slots, palettes: defaults, path: None }
+    }
+
+    /// The file's colors over the built-in palettes. The file is saved to even if it doesn't exist yet.
+    /// Never fails: a missing file means the built-in palettes, and so does a broken one (with a warning)
+    pub fn load(path: Option<PathBuf>, slots: &'static [&'static str], defaults: Vec<Vec<u32>>) -> Self {
+        let mut set = Self::new(slots, defaults);
+
+        let Some(path) = path else {
+            log::debug!("No palette file, using the built-in palettes");
+            return set;
+        };
+
+        match std::fs::read_to_string(&path) {
+            Err(_) => log::debug!("No palette file at {}, using the built-in palettes", path.display()),
+            Ok(text) => if let Err(error) = set.apply_toml(&text) {
+                log::warn!("Ignoring malformed palette file {}: {}", path.display(), error);
+            }
+        }
+
+        set.path = Some(path);
+        set
+    }
+
+    /// Overwrite the colors given in the TOML text, leaves the palettes untouched on error
+    pub fn apply_toml(self: &mut Self, text: &str) -> Result<(), String> {
+        let file: PaletteFile = toml::from_str(text).map_err(|error| error.to_string())?;
+
+        if file.palette.len() > self.palettes.len() {
+            log::warn!("Ignoring {} palette(s) past the {} the clock has", file.palette.len() - self.palettes.len(), self.palettes.len());
+        }
+
+        let mut palettes = self.palettes.clone();
+
+        for (index, (palette, colors)) in palettes.iter_mut().zip(file.palette.iter()).enumerate() {
+            for (name, value) in colors {
+                let slot = self.slots.iter().position(|slot| slot == name)
+                    .ok_or_else(|| format!("Palette {} has an unknown slot '{}', expected one of {}", index, name, self.slots.join(", ")))?;
+
+                palette[slot] = parse_hex_color(value)
+                    .ok_or_else(|| format!("Palette {} has an invalid color '{}' for {}, expected #RRGGBB or #RRGGBBAA", index, value, name))?;
+            }
+        }
+
+        self.palettes = palettes;
+        Ok(())
+    }
+
+    /// Every palette with every slot, ready to be loaded back
+    pub fn to_toml(self: &Self) -> String {
+        let file = PaletteFile {
+            palette: self.palettes.iter().map(|palette| {
+                self.slots.iter().zip(palette.iter())
+                    .map(|(slot, color)| (slot.to_string(), format_hex_color(*color)))
+                    .collect()
+            }).collect()
+        };
+
+        toml::to_string(&file).unwrap_or_default()
+    }
+
+    /// Write the palettes to where they were loaded from
+    pub fn save(self: &Self) -> std::io::Result<()> {
+        use std::io::{Error, ErrorKind};
+
+        let path = self.path.as_ref().ok_or_else(|| Error::new(
+            ErrorKind::NotFound,
+            "No palette file to save to, give one with --palette"
+        ))?;
+
+        if let Some(folder) = path.parent().filter(|folder| !folder.as_os_str().is_empty()) {
+            std::fs::create_dir_all(folder)?;
+        }
+
+        std::fs::write(path, self.to_toml())?;
+
+        log::info!("Saved the palettes to {}", path.display());
+        return Ok(());
+    }
+
+    pub fn path(self: &Self) -> Option<&std::path::Path> {
+        self.path.as_deref()
+    }
+
+    pub fn slots(self: &Self) -> &'static [&'static str] {
+        self.slots
+    }
+
+    pub fn len(self: &Self) -> usize {
+        self.palettes.len()
+    }
+
+    pub fn is_empty(self: &Self) -> bool {
+        self.palettes.is_empty()
+    }
+
+    /// Colors of the palette at the index, one per slot
+    pub fn get(self: &Self, index: usize) -> &[u32] {
+        &self.palettes[index]
+    }
+
+    pub fn set(self: &mut Self, index: usize, slot: usize, color: u32) {
+        self.palettes[index][slot] = color;
+    }
+}
+
+/// Keys of the edit mode, matched on by `PaletteEditor::onkey`
+pub const EDIT_KEYBINDINGS: &[KeyBinding] = &[
+    ("Ctrl+P",     "Enter/leave the palette edit mode"),
+    ("Escape",     "Leave the palette edit mode"),
+    ("Tab",        "Next color of the palette"),
+    ("ArrowLeft",  "Hue down"),
+    ("ArrowRight", "Hue up"),
+    ("ArrowUp",    "Brighter"),
+    ("ArrowDown",  "Darker"),
+    ("PageUp",     "More saturated"),
+    ("PageDown",   "Less saturated"),
+    ("Ctrl+S",     "Save the palettes")
+];
+
+/// Step of a single key press, hue in degrees
+pub const HUE_STEP:        f32 = 5.0;
+pub const SATURATION_STEP: f32 = 0.05;
+pub const VALUE_STEP:      f32 = 0.05;
+
+/// Period of the edited slot's pulsing or blinking
+const HIGHLIGHT_PERIOD_SECS: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EditState {
+    Idle,
+    /// The color being edited is kept as HSV, so that the hue survives a trip through gray or black
+    Editing { index: usize, slot: usize, hsv: Hsv }
+}
+
+/// Live editing of the shown palette.
+/// Ctrl+P enters the edit mode, Tab cycles through the slots, the arrow and page keys adjust the slot's color
+/// and Ctrl+S saves the palettes. Every key press goes to the editor while it's active, so that the clock's own
+/// shortcuts (which may share keys) stay out of the way; clocks should also suspend their palette transitions meanwhile
+#[derive(Clone, Debug)]
+pub struct PaletteEditor {
+    title:      &'static str, // of the error message, when saving fails
+    state:      EditState,
+    changed_at: Instant, // the highlight restarts on every change, so that the new color shows right away
+    unsaved:    bool
+}
+
+impl PaletteEditor {
+    pub fn new(title: &'static str) -> Self {
+        Self { title, state: EditState::Idle, changed_at: Instant::now(), unsaved: false }
+    }
+
+    pub fn is_editing(self: &Self) -> bool {
+        self.state != EditState::Idle
+    }
+
+    /// The slot being edited
+    pub fn slot(self: &Self) -> Option<usize> {
+        match self.state {
+            EditState::Idle => None,
+            EditState::Editing { slot, .. } => Some(slot)
+        }
+    }
+
+    /// Whether there are changes that weren't saved yet
+    pub fn is_unsaved(self: &Self) -> bool {
+        self.unsaved
+    }
+
+    /// Handle a key event, `index` is the shown palette, `None` if it can't be edited (e.g. it's animated).
+    /// Returns whether the key was taken, the clock shouldn't act on it then
+    pub fn onkey(self: &mut Self, event: &winit::event::KeyEvent, palettes: &mut PaletteSet, index: Option<usize>) -> bool {
+        match keys::pressed(event, EDIT_KEYBINDINGS) {
+            Some(key) => self.handle_key(key, palettes, index),
+            None      => self.is_editing() // everything else is swallowed too while editing, see the type's docs
+        }
+    }
+
+    /// Same as `onkey`, with the key's name from `EDIT_KEYBINDINGS`
+    pub fn handle_key(self: &mut Self, key: &str, palettes: &mut PaletteSet, index: Option<usize>) -> bool {
+        let key = Some(key);
+
+        let EditState::Editing { index: editing, slot, hsv } = self.state else {
+            if key != Some("Ctrl+P") {
+                return false;
+            }
+
+            match index {
+                Some(index) if index < palettes.len() => self.edit(palettes, index, 0),
+                _ => log::warn!("The shown palette can't be edited, pick another one first")
+            }
+            return true;
+        };
+
+        let adjust = |hue, saturation, value| Some(hsv.adjusted(hue, saturation, value));
+
+        let adjusted = match key {
+            Some("Ctrl+P") | Some("Escape") => {
+                if self.unsaved {
+                    log::info!("Left the palette edit mode with unsaved changes, Ctrl+S saves them");
+                }
+                self.state = EditState::Idle;
+                None
+            },
+            Some("Tab") => {
+                self.edit(palettes, editing, (slot + 1) % palettes.slots().len());
+                None
+            },
+            Some("ArrowLeft")  => adjust(-HUE_STEP, 0.0, 0.0),
+            Some("ArrowRight") => adjust( HUE_STEP, 0.0, 0.0),
+            Some("ArrowUp")    => adjust(0.0, 0.0,  VALUE_STEP),
+            Some("ArrowDown")  => adjust(0.0, 0.0, -VALUE_STEP),
+            Some("PageUp")     => adjust(0.0,  SATURATION_STEP, 0.0),
+            Some("PageDown")   => adjust(0.0, -SATURATION_STEP, 0.0),
+            Some("Ctrl+S") => {
+                match palettes.save() {
+                    Ok(()) => self.unsaved = false,
+                    Err(error) => crate::show_error(self.title, &format!("Failed to save the palettes: {}", error))
+                }
+                None
+            },
+            _ => None
+        };
+
+        if let Some(hsv) = adjusted {
+            palettes.set(editing, slot, hsv.to_u32_color());
+            self.state      = EditState::Editing { index: editing, slot, hsv };
+            self.changed_at = Instant::now();
+            self.unsaved    = true;
+        }
+
+        true
+    }
+
+    fn edit(self: &mut Self, palettes: &PaletteSet, index: usize, slot: usize) {
+        self.state = EditState::Editing { index, slot, hsv: Hsv::from_u32_color(palettes.get(index)[slot]) };
+        self.changed_at = Instant::now();
+    }
+
+    /// Where the edited slot's pulse is, in [0..1], starting at 0 right after a change
+    pub fn pulse(self: &Self) -> f32 {
+        use std::f32::consts::TAU;
+
+        let phase = self.changed_at.elapsed().as_secs_f32() / HIGHLIGHT_PERIOD_SECS;
+        0.5 - 0.5 * (TAU * phase).cos()
+    }
+
+    /// Whether the edited slot is shown in its blink, it's on for the first half of each period after a change
+    pub fn blink_on(self: &Self) -> bool {
+        let phase = self.changed_at.elapsed().as_secs_f32() / HIGHLIGHT_PERIOD_SECS;
+        phase.fract() < 0.5
+    }
+
+    /// What's being edited, for the window title
+    pub fn status(self: &Self, palettes: &PaletteSet) -> Option<String> {
+        match self.state {
+            EditState::Idle => None,
+            EditState::Editing { index, slot, .. } => Some(format!(
+                "Editing palette {} {}: {}{}",
+                index, palettes.slots()[slot], format_hex_color(palettes.get(index)[slot]),
+                if self.unsaved { " (unsaved)" } else { "" }
+            ))
+        }
+    }
+}
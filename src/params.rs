@@ -0,0 +1,179 @@
+//! Small per-draw parameters, sent via push constants where available and via a uniform buffer otherwise.
+//!
+//! Push constants are the cheapest way to hand a few bytes to a draw call, but they're a native-only feature:
+//! GL and WebGPU targets lack them. `SmallParams` hides the difference, the clock builds its pipeline layouts
+//! from `push_constant_ranges()` and `layout_entries()`, loads its shaders through `load_shader()`,
+//! and calls `set()` before each draw, the same way on either path.
+//!
+//! The fallback is a uniform buffer of `capacity` slots, bound with a dynamic offset at bind group `group`,
+//! which must come after the pipeline's own bind groups. Every `set()` writes the next slot (wrapping around),
+//! so up to `capacity` draws can be issued per submission without overwriting each other's parameters.
+//!
+//! Shaders are written against push constants, `var<push_constant>` is rewritten into the matching uniform
+//! declaration for the fallback. The parameter struct has to be valid in both address spaces then,
+//! which rules out arrays of scalars (their stride must be 16 bytes in uniforms).
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::num::NonZeroU64;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::cast_struct_to_u8_slice;
+
+static PUSH_CONSTANTS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Never request push constants unless a clock requires them, so that the uniform buffer fallback is taken
+/// on any adapter (`--no-push-constants`)
+pub fn disable_push_constants() {
+    PUSH_CONSTANTS_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Push constants the device is requested with when the adapter has them, on top of the clock's required features.
+/// GL (WebGL2 included) only emulates them with plain uniforms, which can't hold the `u32` fields of the parameters
+/// (wgpu panics setting them), the fallback is taken there as well
+pub(crate) fn optional_features(adapter: &wgpu::Adapter) -> wgpu::Features {
+    if PUSH_CONSTANTS_DISABLED.load(Ordering::Relaxed) || adapter.get_info().backend == wgpu::Backend::Gl {
+        wgpu::Features::empty()
+    } else {
+        adapter.features() & wgpu::Features::PUSH_CONSTANTS
+    }
+}
+
+/// Shader source for the uniform buffer fallback, the push constant is declared as a uniform at the given group
+pub fn fallback_shader_source(source: &str, group: u32) -> String {
+    source.replace("var<push_constant>", &format!("@group({}) @binding(0) var<uniform>", group))
+}
+
+/// Distance between the fallback's slots, the struct's size rounded up to the dynamic offset alignment
+pub fn slot_stride(size: u64, alignment: u64) -> u64 {
+    size.div_ceil(alignment) * alignment
+}
+
+struct UniformFallback {
+    group:      u32,
+    buffer:     wgpu::Buffer,
+    layout:     wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    stride:     u64,
+    capacity:   u32,
+    next_slot:  Cell<u32>
+}
+
+/// Parameters of type `T` for the draws of one or more pipelines sharing the same layout
+pub struct SmallParams<T> {
+    stages:   wgpu::ShaderStages,
+    fallback: Option<UniformFallback>,
+    _params:  PhantomData<T>
+}
+
+impl<T> SmallParams<T> {
+    /// Push constants if the device has them and `T` fits, otherwise a uniform buffer with `capacity` slots
+    /// bound at `group`
+    pub fn new(device: &wgpu::Device, stages: wgpu::ShaderStages, group: u32, capacity: u32) -> Self {
+        let size = std::mem::size_of::<T>() as u64;
+
+        let fits = device.features().contains(wgpu::Features::PUSH_CONSTANTS)
+            && size <= device.limits().max_push_constant_size as u64;
+
+        if fits {
+            return Self { stages, fallback: None, _params: PhantomData };
+        }
+
+        log::debug!("Sending {} ({} bytes) via a uniform buffer instead of push constants", std::any::type_name::<T>(), size);
+
+        let stride = slot_stride(size, device.limits().min_uniform_buffer_offset_alignment as u64);
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Small params"),
+            size:  stride * capacity as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Small params"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding:    0,
+                    visibility: stages,
+                    ty: wgpu::BindingType::Buffer {
+                        ty:                 wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size:   NonZeroU64::new(size)
+                    },
+                    count: None
+                }
+            ]
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:  Some("Small params"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding:  0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size:   NonZeroU64::new(size)
+                    })
+                }
+            ]
+        });
+
+        Self {
+            stages,
+            fallback: Some(UniformFallback { group, buffer, layout, bind_group, stride, capacity, next_slot: Cell::new(0) }),
+            _params:  PhantomData
+        }
+    }
+
+    pub fn uses_push_constants(self: &Self) -> bool {
+        self.fallback.is_none()
+    }
+
+    /// For the pipeline layout, empty on the fallback path
+    pub fn push_constant_ranges(self: &Self) -> Vec<wgpu::PushConstantRange> {
+        match self.fallback {
+            None => vec![wgpu::PushConstantRange { stages: self.stages, range: 0..std::mem::size_of::<T>() as u32 }],
+            Some(_) => vec![]
+        }
+    }
+
+    /// Bind group layouts to append to the pipeline layout's own ones, empty with push constants
+    pub fn layout_entries(self: &Self) -> Vec<&wgpu::BindGroupLayout> {
+        self.fallback.iter().map(|fallback| &fallback.layout).collect()
+    }
+
+    /// WGSL source declaring the parameters as `var<push_constant>`, adapted to the path taken
+    pub fn shader_source(self: &Self, source: &str) -> String {
+        match &self.fallback {
+            None => source.to_string(),
+            Some(fallback) => fallback_shader_source(source, fallback.group)
+        }
+    }
+
+    /// `load_shader`, with the source adapted to the path taken
+    pub fn load_shader(self: &Self, device: &wgpu::Device, path: &std::path::Path) -> Result<wgpu::ShaderModule, String> {
+        let source = crate::read_shader_source(path)?;
+
+        Ok(device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  path.file_name().and_then(|name| name.to_str()),
+            source: wgpu::ShaderSource::Wgsl(self.shader_source(&source).into())
+        }))
+    }
+
+    /// Parameters of the following draws, the pipeline must be set already
+    pub fn set<'a>(self: &'a Self, rpass: &mut wgpu::RenderPass<'a>, queue: &wgpu::Queue, params: &T) {
+        match &self.fallback {
+            None => rpass.set_push_constants(self.stages, 0, cast_struct_to_u8_slice(params)),
+            Some(fallback) => {
+                let slot = fallback.next_slot.get();
+                fallback.next_slot.set((slot + 1) % fallback.capacity);
+
+                let offset = slot as u64 * fallback.stride;
+                queue.write_buffer(&fallback.buffer, offset, cast_struct_to_u8_slice(params));
+                rpass.set_bind_group(fallback.group, &fallback.bind_group, &[offset as u32]);
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Recording the presented frames into an animated GIF or a numbered PNG sequence.
+//!
+//! Only available with the `recorder` feature. The runner owns a `Recorder` when `RunOptions::recorder` is set,
+//! the hotkey (F9) starts and stops a recording. Captured frames are read back on the render thread,
+//! then handed over to a worker thread through a bounded channel for scaling and encoding.
+//! If the worker falls behind, frames are dropped instead of making the clock wait.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Frames waiting for the encoder, anything beyond this is dropped
+const QUEUE_CAPACITY: usize = 8;
+
+/// Key that starts/stops recording
+pub const HOTKEY: winit::keyboard::NamedKey = winit::keyboard::NamedKey::F9;
+
+#[derive(Clone, Debug)]
+pub struct RecorderOptions {
+    /// `*.gif` records an animated GIF, anything else is a folder for the PNG sequence
+    pub output:       PathBuf,
+    /// Frames captured per second at most
+    pub fps:          u32,
+    /// Size of the recorded frames relative to the window
+    pub scale:        f32,
+    /// Stop automatically after this long
+    pub max_duration: Option<Duration>
+}
+
+impl Default for RecorderOptions {
+    fn default() -> Self {
+        Self {
+            output:       PathBuf::from("recording.gif"),
+            fps:          15,
+            scale:        0.5,
+            max_duration: None
+        }
+    }
+}
+
+impl RecorderOptions {
+    fn is_gif(self: &Self) -> bool {
+        self.output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gif"))
+    }
+
+    /// Output path of the n-th recording of this run, later ones get a numbered suffix to not overwrite the first
+    fn output_for(self: &Self, index: u32) -> PathBuf {
+        if index == 0 {
+            return self.output.clone();
+        }
+
+        let stem = self.output.file_stem().unwrap_or_default().to_string_lossy();
+        let name = match self.output.extension() {
+            Some(ext) => format!("{}-{}.{}", stem, index + 1, ext.to_string_lossy()),
+            None      => format!("{}-{}", stem, index + 1)
+        };
+
+        return self.output.with_file_name(name);
+    }
+}
+
+struct Session {
+    sender:       SyncSender<image::RgbaImage>,
+    worker:       JoinHandle<Result<u32, String>>,
+    started:      Instant,
+    last_capture: Option<Instant>,
+    dropped:      u32
+}
+
+pub struct Recorder {
+    options:  RecorderOptions,
+    session:  Option<Session>,
+    sessions: u32
+}
+
+impl Recorder {
+    pub fn new(options: RecorderOptions) -> Self {
+        Self { options, session: None, sessions: 0 }
+    }
+
+    pub fn is_recording(self: &Self) -> bool {
+        self.session.is_some()
+    }
+
+    /// Start a new recording, or finish the current one
+    pub fn toggle(self: &mut Self) {
+        if self.session.is_some() {
+            self.stop();
+        } else {
+            self.start();
+        }
+    }
+
+    fn start(self: &mut Self) {
+        let output = self.options.output_for(self.sessions);
+        let gif    = self.options.is_gif();
+        let fps    = self.options.fps.max(1);
+        let scale  = self.options.scale;
+
+        self.sessions += 1;
+
+        let (sender, receiver) = sync_channel::<image::RgbaImage>(QUEUE_CAPACITY);
+
+        log::info!("Recording into {}", output.display());
+
+        let worker = std::thread::spawn(move || {
+            let mut encoder: Box<dyn FrameSink> = if gif {
+                Box::new(GifSink::new(&output, fps))
+            } else {
+                Box::new(PngSequenceSink::new(&output)?)
+            };
+
+            let mut count = 0;
+
+            for frame in receiver {
+                encoder.write(&downscale(frame, scale))?;
+                count += 1;
+            }
+
+            encoder.finish()?;
+            return Ok(count);
+        });
+
+        self.session = Some(Session { sender, worker, started: Instant::now(), last_capture: None, dropped: 0 });
+    }
+
+    /// Finish the current recording, waits for the encoder to write out the queued frames
+    pub fn stop(self: &mut Self) {
+        let Some(session) = self.session.take() else {
+            return;
+        };
+
+        // closing the channel ends the worker's loop
+        drop(session.sender);
+
+        match session.worker.join() {
+            Ok(Ok(count)) => log::info!("Recorded {} frames in {:?} ({} dropped)", count, session.started.elapsed(), session.dropped),
+            Ok(Err(error)) => log::error!("Recording failed: {}", error),
+            Err(_) => log::error!("Recording failed: the encoder thread panicked")
+        }
+    }
+
+    /// Call after drawing a frame and before presenting it.
+    /// Captures the frame if it's time for the next one, the texture needs `COPY_SRC` usage
+    pub fn capture(self: &mut Self, texture: &wgpu::Texture, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+
+        if self.options.max_duration.is_some_and(|max| session.started.elapsed() >= max) {
+            self.stop();
+            return;
+        }
+
+        let interval = Duration::from_secs(1) / self.options.fps.max(1);
+        if session.last_capture.is_some_and(|last| last.elapsed() < interval) {
+            return;
+        }
+
+        session.last_capture = Some(Instant::now());
+
+        let frame = match crate::headless::read_texture_rgba(texture, device, queue) {
+            Ok(frame) => frame,
+            Err(error) => {
+                log::warn!("Failed to capture a frame: {}", error);
+                return;
+            }
+        };
+
+        match session.sender.try_send(frame) {
+            Ok(()) => {},
+            Err(TrySendError::Full(_)) => {
+                session.dropped += 1;
+                log::debug!("Encoder is behind, dropped a frame");
+            },
+            Err(TrySendError::Disconnected(_)) => {
+                // the worker has quit early, joining it reports why
+                self.stop();
+            }
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn downscale(frame: image::RgbaImage, scale: f32) -> image::RgbaImage {
+    if scale == 1.0 {
+        return frame;
+    }
+
+    let width  = ((frame.width()  as f32 * scale).round() as u32).max(1);
+    let height = ((frame.height() as f32 * scale).round() as u32).max(1);
+
+    return image::imageops::resize(&frame, width, height, image::imageops::FilterType::Triangle);
+}
+
+trait FrameSink {
+    fn write(&mut self, frame: &image::RgbaImage) -> Result<(), String>;
+    fn finish(&mut self) -> Result<(), String>;
+}
+
+struct PngSequenceSink {
+    folder: PathBuf,
+    index:  u32
+}
+
+impl PngSequenceSink {
+    fn new(folder: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(folder).map_err(|error| format!("Can't create {}: {}", folder.display(), error))?;
+        Ok(Self { folder: folder.to_path_buf(), index: 0 })
+    }
+}
+
+impl FrameSink for PngSequenceSink {
+    fn write(&mut self, frame: &image::RgbaImage) -> Result<(), String> {
+        let path = self.folder.join(format!("frame_{:05}.png", self.index));
+        self.index += 1;
+        frame.save(&path).map_err(|error| format!("Failed to save {}: {}", path.display(), error))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Levels per channel of the fixed GIF palette, a 6x7x6 color cube (252 colors).
+/// Green gets one more level, the eye is the most sensitive to it
+const CUBE_LEVELS: [u32; 3] = [6, 7, 6];
+
+/// The encoder is created with the first frame, since the GIF's size has to be known up front
+struct GifSink {
+    path:    PathBuf,
+    delay:   u16, // in 1/100 seconds
+    encoder: Option<gif::Encoder<std::io::BufWriter<std::fs::File>>>
+}
+
+impl GifSink {
+    fn new(path: &Path, fps: u32) -> Self {
+        Self { path: path.to_path_buf(), delay: (100 / fps).max(2) as u16, encoder: None }
+    }
+
+    fn palette() -> Vec<u8> {
+        let [lr, lg, lb] = CUBE_LEVELS;
+        let level = |value: u32, levels: u32| (value * 255 / (levels - 1)) as u8;
+
+        let mut palette = Vec::with_capacity((lr * lg * lb * 3) as usize);
+
+        for r in 0..lr {
+            for g in 0..lg {
+                for b in 0..lb {
+                    palette.extend_from_slice(&[level(r, lr), level(g, lg), level(b, lb)]);
+                }
+            }
+        }
+
+        return palette;
+    }
+
+    /// Nearest palette index of a color, straight from the cube coordinates
+    fn quantize(rgba: &[u8]) -> u8 {
+        let [lr, lg, lb] = CUBE_LEVELS;
+        let step = |value: u8, levels: u32| (value as u32 * (levels - 1) + 127) / 255;
+
+        return ((step(rgba[0], lr) * lg + step(rgba[1], lg)) * lb + step(rgba[2], lb)) as u8;
+    }
+}
+
+impl FrameSink for GifSink {
+    fn write(&mut self, frame: &image::RgbaImage) -> Result<(), String> {
+        let path  = self.path.clone();
+        let error = |error: gif::EncodingError| format!("Failed to encode {}: {}", path.display(), error);
+
+        if self.encoder.is_none() {
+            let file = std::fs::File::create(&path)
+                .map_err(|error| format!("Can't create {}: {}", path.display(), error))?;
+
+            let mut encoder = gif::Encoder::new(
+                std::io::BufWriter::new(file),
+                frame.width()  as u16,
+                frame.height() as u16,
+                &Self::palette()
+            ).map_err(error)?;
+
+            encoder.set_repeat(gif::Repeat::Infinite).map_err(error)?;
+            self.encoder = Some(encoder);
+        }
+
+        let indices: Vec<u8> = frame.as_raw().chunks_exact(4).map(Self::quantize).collect();
+
+        let gif_frame = gif::Frame {
+            width:  frame.width()  as u16,
+            height: frame.height() as u16,
+            delay:  self.delay,
+            buffer: std::borrow::Cow::Owned(indices),
+            ..gif::Frame::default()
+        };
+
+        self.encoder.as_mut().unwrap().write_frame(&gif_frame).map_err(error)
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        // the trailer is written when the encoder is dropped
+        self.encoder = None;
+        Ok(())
+    }
+}
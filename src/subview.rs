@@ -0,0 +1,170 @@
+//! Picture-in-picture: a second clock drawn into a corner of the first one's window, e.g. the digital clock over the portal.
+//!
+//! `SubView` owns the inner clock and an offscreen target a fraction of the window's size. The inner clock is set up
+//! as if it had a transparent window of that size of its own (`alpha_mode` premultiplied), draws into the target,
+//! which is then blended over the outer clock's frame in a viewport at the corner.
+//! Input isn't forwarded, the outer clock keeps it, so the inset only shows and follows its own settings.
+
+use crate::{BasicFilteringSampler, ExecDraw, FrameInfo, FullscreenQuadPipeline, RenderTexture};
+use crate::target::{self, TargetConfig};
+use crate::viewport::{RenderPassViewportExt, ViewportRect};
+
+/// Gap between the inset and the window's edges, a fraction of the window's shorter side
+pub const MARGIN: f32 = 0.02;
+
+/// Which corner of the window the inset sits in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight
+}
+
+/// Where the inset goes, as x, y, width and height in pixels, its size is `fraction` of the window's on both axes, at least 1x1
+pub fn subview_rect(window: (u32, u32), fraction: f32, corner: Corner) -> (u32, u32, u32, u32) {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let width    = ((window.0 as f32 * fraction).round() as u32).clamp(1, window.0.max(1));
+    let height   = ((window.1 as f32 * fraction).round() as u32).clamp(1, window.1.max(1));
+
+    let margin = (window.0.min(window.1) as f32 * MARGIN).round() as u32;
+    let right  = window.0.saturating_sub(width + margin);
+    let bottom = window.1.saturating_sub(height + margin);
+    let left   = margin.min(right);
+    let top    = margin.min(bottom);
+
+    match corner {
+        Corner::TopLeft     => (left,  top,    width, height),
+        Corner::TopRight    => (right, top,    width, height),
+        Corner::BottomLeft  => (left,  bottom, width, height),
+        Corner::BottomRight => (right, bottom, width, height)
+    }
+}
+
+pub struct SubView<T: ExecDraw> {
+    /// The inset clock, for forwarding what it should know about (e.g. the theme)
+    pub inner: T,
+    fraction:  f32,
+    corner:    Corner,
+    window:    (u32, u32),
+    target:    RenderTexture, // the inner clock's frame, premultiplied
+    quad:      FullscreenQuadPipeline,
+    layout:    wgpu::BindGroupLayout,
+    sampler:   BasicFilteringSampler,
+    bindgroup: wgpu::BindGroup
+}
+
+impl<T: ExecDraw> SubView<T> {
+    /// Sets up the inner clock for an inset of `fraction` of the window described by `config`
+    pub fn setup(
+        config:   &wgpu::SurfaceConfiguration,
+        adapter:  &wgpu::Adapter,
+        device:   &wgpu::Device,
+        queue:    &wgpu::Queue,
+        theme:    winit::window::Theme,
+        fraction: f32,
+        corner:   Corner
+    ) -> Result<Self, String> {
+        let window = (config.width, config.height);
+        let (_, _, width, height) = subview_rect(window, fraction, corner);
+
+        let inner_config = wgpu::SurfaceConfiguration {
+            width,
+            height,
+            alpha_mode: wgpu::CompositeAlphaMode::PreMultiplied,
+            ..config.clone()
+        };
+
+        // the inner clock draws into `target`, single-sampled however the outer clock's frame is
+        let frame_sample_count = target::frame_sample_count();
+        target::set_frame_sample_count(1);
+        let inner = T::setup(&inner_config, adapter, device, queue, theme);
+        target::set_frame_sample_count(frame_sample_count);
+        let inner = inner?;
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label:   Some("Subview"),
+            entries: &[
+                RenderTexture::default_layout_entry(0),
+                BasicFilteringSampler::default_layout_entry(1)
+            ]
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Subview"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("subview.wgsl").into())
+        });
+
+        let quad = FullscreenQuadPipeline::blended_for_target(
+            device, &shader, "fs_main", &[ &layout ], &[], &TargetConfig::frame(config.format, None),
+            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING
+        );
+
+        let sampler = BasicFilteringSampler::new_fixed_lod(device, 0.0);
+        let target  = RenderTexture::new((width, height), config.format, true, device);
+        let bindgroup = Self::create_bindgroup(&target, &layout, &sampler, device);
+
+        Ok(Self { inner, fraction, corner, window, target, quad, layout, sampler, bindgroup })
+    }
+
+    fn create_bindgroup(
+        target:  &RenderTexture,
+        layout:  &wgpu::BindGroupLayout,
+        sampler: &BasicFilteringSampler,
+        device:  &wgpu::Device
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   Some("Subview"),
+            layout,
+            entries: &[ target.get_entry(0), sampler.get_entry(1) ]
+        })
+    }
+
+    /// Where the inset is drawn within the window
+    pub fn rect(self: &Self) -> (u32, u32, u32, u32) {
+        subview_rect(self.window, self.fraction, self.corner)
+    }
+
+    /// Takes the window's new size, the inner clock is resized to its fraction of it
+    pub fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.window = (width, height);
+
+        let (_, _, width, height) = self.rect();
+        if (width, height) != (self.target.width, self.target.height) {
+            self.target    = RenderTexture::new((width, height), self.target.format, true, device);
+            self.bindgroup = Self::create_bindgroup(&self.target, &self.layout, &self.sampler, device);
+        }
+
+        self.inner.resize(width, height, device, queue);
+    }
+
+    /// Draws the inner clock (`frame` as for `ExecDraw::draw`), then blends it over what's in `texview` already, i.e. after the outer clock has drawn
+    pub fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, frame: &FrameInfo) {
+        self.inner.draw(&self.target.view, device, queue, frame);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Subview") });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    Some("Subview"),
+                depth_stencil_attachment: None,
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                    view: texview,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load:  wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store
+                    }
+                })]
+            });
+
+            if rpass.set_viewport_rect(ViewportRect::from(self.rect()), self.window) {
+                self.quad.draw(&mut rpass, &[ &self.bindgroup ], &[]);
+            }
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
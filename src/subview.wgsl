@@ -0,0 +1,13 @@
+// Picture-in-picture: the inset clock's frame, drawn with `FullscreenQuadPipeline` into a viewport at the window's corner.
+// The frame is premultiplied and blended as such, its transparent parts show the outer clock.
+
+@group(0) @binding(0)
+var frame: texture_2d<f32>;
+
+@group(0) @binding(1)
+var frame_sampler: sampler;
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return textureSampleLevel(frame, frame_sampler, uv, 0.0);
+}
@@ -0,0 +1,52 @@
+// Temporal anti-aliasing, the accumulation of the jittered frames into the history.
+// Drawn like `FullscreenQuadPipeline`, the UVs come from its vertex shader.
+// Nothing tells where a pixel was in the previous frame, so the history is taken from the same pixel, and clamped
+// to the range of the new frame's colors around it: where things moved, the history falls outside that range and
+// gets pulled to the new frame, while the edges' sub-pixel variations are within it and add up.
+
+struct TaaParams {
+    current_weight: f32 // share of the new frame, 1 ignores the history
+}
+
+struct TaaOutput {
+    @location(0) color:   vec4f, // to the destination
+    @location(1) history: vec4f  // for the next frame
+}
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>;
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+@group(1) @binding(0)
+var history_tex_2d: texture_2d<f32>;
+
+var<push_constant> params: TaaParams;
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> TaaOutput {
+    let size    = vec2f(textureDimensions(src_tex_2d));
+    let texel   = 1.0 / size;
+    let current = textureSampleLevel(src_tex_2d, tex_sampler, uv, 0.0);
+
+    // the range of the 3x3 neighborhood
+    var low  = current.rgb;
+    var high = current.rgb;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let neighbor = textureSampleLevel(src_tex_2d, tex_sampler, uv + vec2f(f32(x), f32(y)) * texel, 0.0).rgb;
+            low  = min(low, neighbor);
+            high = max(high, neighbor);
+        }
+    }
+
+    let pixel   = vec2u(clamp(uv * size, vec2f(0.0), size - 1.0));
+    let history = clamp(textureLoad(history_tex_2d, pixel, 0).rgb, low, high);
+
+    var out: TaaOutput;
+    out.color   = vec4f(mix(history, current.rgb, params.current_weight), current.a);
+    out.history = out.color;
+
+    return out;
+}
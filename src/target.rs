@@ -0,0 +1,365 @@
+//! Render targets and the pipelines drawing into them, kept consistent by a single `TargetConfig`.
+//!
+//! wgpu requires every attachment of a pass, and every pipeline used in it, to agree on the formats and the sample count.
+//! Creating all of them from the same `TargetConfig` guarantees that, and switching multisampling at runtime
+//! is just rebuilding them from a new one. Mixing up configs is caught by debug assertions, which name the culprit,
+//! before wgpu's validation (which would only say which descriptor it didn't like).
+//!
+//! Multisampled passes draw into the `ColorTarget`'s own multisampled texture, which is resolved into the
+//! actual (single-sampled) output at the end of the pass, so the outputs stay usable as textures or surfaces.
+//! Clocks run with `RunOptions::msaa_sample_count` get a multisampled frame from the runner instead, which it resolves
+//! into the surface itself: whatever draws into that frame is built for `frame_sample_count()`, see `TargetConfig::frame`.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use crate::RenderTexture;
+
+static REQUESTED_SAMPLE_COUNT: AtomicU32 = AtomicU32::new(1);
+
+/// Sample count the clocks that support multisampling start with (`--msaa`), they fall back to what's supported
+pub fn set_requested_sample_count(count: u32) {
+    REQUESTED_SAMPLE_COUNT.store(count.max(1), Ordering::Relaxed);
+}
+
+pub fn requested_sample_count() -> u32 {
+    REQUESTED_SAMPLE_COUNT.load(Ordering::Relaxed)
+}
+
+static FRAME_SAMPLE_COUNT: AtomicU32 = AtomicU32::new(1);
+
+/// Set by the runner before `ExecDraw::setup`, to the supported sample count of the frame it passes to `ExecDraw::draw`
+pub(crate) fn set_frame_sample_count(count: u32) {
+    FRAME_SAMPLE_COUNT.store(count.max(1), Ordering::Relaxed);
+}
+
+/// Sample count of the frame the clock draws into, 1 unless it was run with `RunOptions::msaa_sample_count`
+pub fn frame_sample_count() -> u32 {
+    FRAME_SAMPLE_COUNT.load(Ordering::Relaxed)
+}
+
+/// Formats and sample count shared by a pass's attachments and the pipelines drawing in it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TargetConfig {
+    pub color_format: wgpu::TextureFormat,
+    pub depth_format: Option<wgpu::TextureFormat>,
+    pub sample_count: u32
+}
+
+impl TargetConfig {
+    /// Single-sampled
+    pub fn new(color_format: wgpu::TextureFormat, depth_format: Option<wgpu::TextureFormat>) -> Self {
+        Self { color_format, depth_format, sample_count: 1 }
+    }
+
+    /// For passes drawing into the frame the runner passes to `ExecDraw::draw`, at `frame_sample_count()`
+    pub fn frame(color_format: wgpu::TextureFormat, depth_format: Option<wgpu::TextureFormat>) -> Self {
+        Self::new(color_format, depth_format).with_sample_count(frame_sample_count())
+    }
+
+    pub fn with_sample_count(self: &Self, sample_count: u32) -> Self {
+        Self { sample_count, ..*self }
+    }
+
+    pub fn is_multisampled(self: &Self) -> bool {
+        self.sample_count > 1
+    }
+
+    /// Whether both formats can be rendered with the given sample count.
+    /// 1 and 4 always can, anything else needs `TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES` and the adapter's support
+    pub fn supports(self: &Self, sample_count: u32, adapter: &wgpu::Adapter, device: &wgpu::Device) -> bool {
+        if sample_count == 1 || sample_count == 4 {
+            return true;
+        }
+
+        device.features().contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+            && std::iter::once(self.color_format).chain(self.depth_format).all(|format| {
+                adapter.get_texture_format_features(format).flags.sample_count_supported(sample_count)
+            })
+    }
+
+    /// The largest supported sample count up to the given one
+    pub fn with_supported_sample_count(self: &Self, sample_count: u32, adapter: &wgpu::Adapter, device: &wgpu::Device) -> Self {
+        let supported = [16, 8, 4, 2, 1].into_iter()
+            .find(|count| *count <= sample_count && self.supports(*count, adapter, device))
+            .unwrap_or(1);
+
+        if supported != sample_count {
+            log::warn!("{}x multisampling isn't supported, using {}x", sample_count, supported);
+        }
+
+        self.with_sample_count(supported)
+    }
+
+    pub fn multisample_state(self: &Self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState { count: self.sample_count, ..Default::default() }
+    }
+
+    /// `None` without a depth format, regardless of the arguments
+    pub fn depth_stencil_state(self: &Self, write: bool, compare: wgpu::CompareFunction) -> Option<wgpu::DepthStencilState> {
+        self.depth_format.map(|format| wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: write,
+            depth_compare:       compare,
+            stencil:             wgpu::StencilState::default(),
+            bias:                wgpu::DepthBiasState::default()
+        })
+    }
+
+    /// Panics (in debug builds) if something made for `other` is about to be used with this config
+    pub fn debug_assert_matches(self: &Self, other: &TargetConfig, what: &str) {
+        debug_assert!(
+            self == other,
+            "{} was made for {} but the pass is {}", what, other.describe(), self.describe()
+        );
+    }
+
+    fn describe(self: &Self) -> String {
+        match self.depth_format {
+            Some(depth) => format!("{:?} + {:?} at {}x", self.color_format, depth, self.sample_count),
+            None        => format!("{:?} (no depth) at {}x", self.color_format, self.sample_count)
+        }
+    }
+}
+
+/// Where a pass draws its colors: straight into the output when single-sampled,
+/// into a multisampled texture resolved into the output otherwise
+pub struct ColorTarget {
+    pub config: TargetConfig,
+    pub msaa:   Option<RenderTexture>
+}
+
+impl ColorTarget {
+    pub fn new(size: (u32, u32), config: &TargetConfig, device: &wgpu::Device) -> Self {
+        let msaa = config.is_multisampled().then(|| RenderTexture::multisampled(size, config.color_format, config.sample_count, device));
+
+        Self { config: *config, msaa }
+    }
+
+    /// For the runner's frame, which is multisampled already (if at all): drawn into directly, the runner resolves it
+    pub fn frame(config: &TargetConfig) -> Self {
+        Self { config: *config, msaa: None }
+    }
+
+    /// For the pass, the `output` ends up with the result either way. Its format must be the config's color format
+    pub fn attachment<'a>(
+        self:   &'a Self,
+        pass:   &TargetConfig,
+        output: &'a wgpu::TextureView,
+        load:   wgpu::LoadOp<wgpu::Color>
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        self.attachment_storing(pass, output, load, wgpu::StoreOp::Discard)
+    }
+
+    /// `attachment`, with the samples stored as well, for a following pass to `Load` and draw over.
+    /// Needed when that pass draws into a part of the target only (e.g. scissored): it resolves all of it regardless
+    pub fn attachment_kept<'a>(
+        self:   &'a Self,
+        pass:   &TargetConfig,
+        output: &'a wgpu::TextureView,
+        load:   wgpu::LoadOp<wgpu::Color>
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        self.attachment_storing(pass, output, load, wgpu::StoreOp::Store)
+    }
+
+    fn attachment_storing<'a>(
+        self:   &'a Self,
+        pass:   &TargetConfig,
+        output: &'a wgpu::TextureView,
+        load:   wgpu::LoadOp<wgpu::Color>,
+        store:  wgpu::StoreOp
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        pass.debug_assert_matches(&self.config, "The color target");
+
+        match &self.msaa {
+            None => wgpu::RenderPassColorAttachment {
+                view:           output,
+                resolve_target: None,
+                ops:            wgpu::Operations { load, store: wgpu::StoreOp::Store }
+            },
+            // unless asked for, only the resolved result is kept
+            Some(msaa) => wgpu::RenderPassColorAttachment {
+                view:           &msaa.view,
+                resolve_target: Some(output),
+                ops:            wgpu::Operations { load, store }
+            }
+        }
+    }
+}
+
+/// The frame `ExecDraw::draw` gets when the clock is run multisampled, resolved into the actual destination afterwards
+pub(crate) struct MultisampledFrame {
+    texture: RenderTexture
+}
+
+impl MultisampledFrame {
+    /// `None` for a sample count of 1, the clock draws into the destination directly then
+    pub fn new(size: (u32, u32), format: wgpu::TextureFormat, sample_count: u32, device: &wgpu::Device) -> Option<Self> {
+        (sample_count > 1).then(|| Self { texture: RenderTexture::multisampled(size, format, sample_count, device) })
+    }
+
+    /// Same sample count, `size` being the clock's
+    pub fn resize(self: &mut Self, size: (u32, u32), device: &wgpu::Device) {
+        if size != (self.texture.width, self.texture.height) {
+            self.texture = RenderTexture::multisampled(size, self.texture.format, self.texture.sample_count, device);
+        }
+    }
+
+    pub fn view(self: &Self) -> &wgpu::TextureView {
+        &self.texture.view
+    }
+
+    /// Resolves what was drawn into `destination`, single-sampled and of the same size and format
+    pub fn resolve(self: &Self, destination: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("MSAA resolve") });
+
+        // no draws, the pass only loads the samples and resolves them at its end
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label:                    Some("MSAA resolve"),
+            depth_stencil_attachment: None,
+            timestamp_writes:         None,
+            occlusion_query_set:      None,
+            color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                view:           &self.texture.view,
+                resolve_target: Some(destination),
+                ops:            wgpu::Operations { load: wgpu::LoadOp::Load, store: wgpu::StoreOp::Discard }
+            })]
+        });
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
+
+/// Depth buffer with the config's depth format and sample count
+pub struct DepthTexture {
+    pub config: TargetConfig,
+    pub texture: wgpu::Texture,
+    pub view:    wgpu::TextureView
+}
+
+impl DepthTexture {
+    pub fn new(size: (u32, u32), config: &TargetConfig, device: &wgpu::Device) -> Self {
+        let format = config.depth_format.expect("DepthTexture needs a TargetConfig with a depth format");
+        let (width, height) = size;
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label:           Some("Depth"),
+            size:            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count:    config.sample_count,
+            dimension:       wgpu::TextureDimension::D2,
+            format,
+            usage:           wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats:    &[]
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self { config: *config, texture, view }
+    }
+
+    /// Cleared to the far plane, kept only if `store`
+    pub fn attachment<'a>(self: &'a Self, pass: &TargetConfig, store: bool) -> wgpu::RenderPassDepthStencilAttachment<'a> {
+        pass.debug_assert_matches(&self.config, "The depth texture");
+
+        wgpu::RenderPassDepthStencilAttachment {
+            view: &self.view,
+            depth_ops: Some(wgpu::Operations {
+                load:  wgpu::LoadOp::Clear(1.0),
+                store: if store { wgpu::StoreOp::Store } else { wgpu::StoreOp::Discard }
+            }),
+            stencil_ops: None
+        }
+    }
+}
+
+/// A render pipeline, and the config it was built for
+pub struct TargetPipeline {
+    pub config:   TargetConfig,
+    pub pipeline: wgpu::RenderPipeline
+}
+
+impl TargetPipeline {
+    /// `set_pipeline`, checked against the pass's config
+    pub fn set<'a>(self: &'a Self, rpass: &mut wgpu::RenderPass<'a>, pass: &TargetConfig) {
+        pass.debug_assert_matches(&self.config, "The pipeline");
+        rpass.set_pipeline(&self.pipeline);
+    }
+}
+
+/// Render pipelines with a single color target, the formats and the sample count come from a `TargetConfig`
+pub struct PipelineBuilder<'a> {
+    config:      TargetConfig,
+    layout:      &'a wgpu::PipelineLayout,
+    module:      &'a wgpu::ShaderModule,
+    vs_entry:    &'a str,
+    fs_entry:    &'a str,
+    buffers:     &'a [wgpu::VertexBufferLayout<'a>],
+    primitive:   wgpu::PrimitiveState,
+    blend:       Option<wgpu::BlendState>,
+    depth_write: bool
+}
+
+impl<'a> PipelineBuilder<'a> {
+    /// `vs_main` and `fs_main` of the module, no vertex buffers, triangle lists without culling, no blending,
+    /// depth tested and written if the config has a depth format
+    pub fn new(config: &TargetConfig, layout: &'a wgpu::PipelineLayout, module: &'a wgpu::ShaderModule) -> Self {
+        Self {
+            config:      *config,
+            layout,
+            module,
+            vs_entry:    "vs_main",
+            fs_entry:    "fs_main",
+            buffers:     &[],
+            primitive:   wgpu::PrimitiveState::default(),
+            blend:       None,
+            depth_write: true
+        }
+    }
+
+    pub fn vertex(self: Self, entry: &'a str, buffers: &'a [wgpu::VertexBufferLayout<'a>]) -> Self {
+        Self { vs_entry: entry, buffers, ..self }
+    }
+
+    pub fn fragment(self: Self, entry: &'a str) -> Self {
+        Self { fs_entry: entry, ..self }
+    }
+
+    pub fn primitive(self: Self, primitive: wgpu::PrimitiveState) -> Self {
+        Self { primitive, ..self }
+    }
+
+    pub fn blend(self: Self, blend: wgpu::BlendState) -> Self {
+        Self { blend: Some(blend), ..self }
+    }
+
+    /// Depth tested, but not written, e.g. for transparent things
+    pub fn depth_read_only(self: Self) -> Self {
+        Self { depth_write: false, ..self }
+    }
+
+    pub fn build(self: Self, device: &wgpu::Device) -> TargetPipeline {
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label:         None,
+            layout:        Some(self.layout),
+            depth_stencil: self.config.depth_stencil_state(self.depth_write, wgpu::CompareFunction::Less),
+            multisample:   self.config.multisample_state(),
+            multiview:     None,
+            vertex: wgpu::VertexState {
+                module:      self.module,
+                entry_point: self.vs_entry,
+                buffers:     self.buffers
+            },
+            fragment: Some(wgpu::FragmentState {
+                module:      self.module,
+                entry_point: self.fs_entry,
+                targets:     &[ Some(wgpu::ColorTargetState {
+                    format:     self.config.color_format,
+                    blend:      self.blend,
+                    write_mask: wgpu::ColorWrites::ALL
+                }) ]
+            }),
+            primitive: self.primitive
+        });
+
+        TargetPipeline { config: self.config, pipeline }
+    }
+}
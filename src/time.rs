@@ -0,0 +1,266 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveTime, TimeDelta, Utc};
+use std::sync::RwLock;
+
+/// std's on native. On wasm32, where std's panics, the browser's clock (the one winit's `ControlFlow::WaitUntil` takes)
+pub use web_time::Instant;
+
+/// Where the clocks get their "current" time from.
+/// By default it's simply the system's local time. But it can also be shifted into another timezone,
+/// started from an arbitrary time of day, and made to run faster/slower than real time,
+/// which comes handy for demonstrations and for inspecting animations.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeSource {
+    timezone: Option<FixedOffset>, // None => system's local timezone
+    scale:    f64,
+    origin:   Option<(Instant, DateTime<FixedOffset>)> // None => real time, no simulation needed
+}
+
+impl TimeSource {
+    /// The system's local time, as is
+    pub const fn real() -> Self {
+        Self { timezone: None, scale: 1.0, origin: None }
+    }
+
+    /// `date` and `start` are the date and time of day the clock starts at, each defaulting to the current one
+    /// in the given timezone. `scale` is the speed multiplier, `1.0` being real time.
+    pub fn new(timezone: Option<FixedOffset>, date: Option<NaiveDate>, start: Option<NaiveTime>, scale: f64) -> Self {
+        let real_now = Self::with_timezone(Utc::now(), timezone);
+
+        let origin = if date.is_some() || start.is_some() {
+            let date  = date.unwrap_or(real_now.date_naive());
+            let start = start.unwrap_or(real_now.time());
+            Some((Instant::now(), date.and_time(start).and_local_timezone(*real_now.offset()).unwrap()))
+        } else if scale != 1.0 {
+            Some((Instant::now(), real_now))
+        } else {
+            None
+        };
+
+        Self { timezone, scale, origin }
+    }
+
+    pub fn now(self: &Self) -> DateTime<FixedOffset> {
+        match self.origin {
+            None => Self::with_timezone(Utc::now(), self.timezone),
+            Some((instant, datetime)) => {
+                let elapsed = instant.elapsed().as_secs_f64() * self.scale;
+                datetime + TimeDelta::nanoseconds((elapsed * 1_000_000_000.0) as i64)
+            }
+        }
+    }
+
+    /// Same timezone and speed, but continuing from `datetime` as of now
+    fn rebased(self: &Self, datetime: DateTime<FixedOffset>) -> Self {
+        Self { origin: Some((Instant::now(), datetime)), ..*self }
+    }
+
+    fn with_timezone(utc: DateTime<Utc>, timezone: Option<FixedOffset>) -> DateTime<FixedOffset> {
+        match timezone {
+            Some(offset) => utc.with_timezone(&offset),
+            None         => utc.with_timezone(&Local).fixed_offset()
+        }
+    }
+}
+
+impl Default for TimeSource {
+    fn default() -> Self {
+        Self::real()
+    }
+}
+
+/// A time source that can be paused, stepped and sped up while running, for inspecting animations.
+/// The shared source is one of these, its controls are bound to keys by the runner with `--debug-time`
+#[derive(Clone, Copy, Debug)]
+pub struct ControllableTime {
+    source: TimeSource,
+    paused: Option<DateTime<FixedOffset>> // the time it stopped at
+}
+
+impl ControllableTime {
+    /// Slowest and fastest speeds `set_speed` allows
+    pub const SPEED_RANGE: (f64, f64) = (1.0 / 64.0, 1024.0);
+
+    pub const fn new(source: TimeSource) -> Self {
+        Self { source, paused: None }
+    }
+
+    pub fn now(self: &Self) -> DateTime<FixedOffset> {
+        self.paused.unwrap_or_else(|| self.source.now())
+    }
+
+    pub fn is_paused(self: &Self) -> bool {
+        self.paused.is_some()
+    }
+
+    pub fn speed(self: &Self) -> f64 {
+        self.source.scale
+    }
+
+    pub fn pause(self: &mut Self) {
+        if self.paused.is_none() {
+            self.paused = Some(self.source.now());
+        }
+    }
+
+    /// Continues from where it was paused, not from where it would be by now
+    pub fn resume(self: &mut Self) {
+        if let Some(datetime) = self.paused.take() {
+            self.source = self.source.rebased(datetime);
+        }
+    }
+
+    /// Jump forward (or backward, with a negative `delta`), works while paused as well
+    pub fn step(self: &mut Self, delta: TimeDelta) {
+        match &mut self.paused {
+            Some(datetime) => *datetime += delta,
+            None => self.source = self.source.rebased(self.source.now() + delta)
+        }
+    }
+
+    pub fn set_speed(self: &mut Self, scale: f64) {
+        let now = self.now();
+        self.source.scale = scale.clamp(Self::SPEED_RANGE.0, Self::SPEED_RANGE.1);
+
+        if self.paused.is_none() {
+            self.source = self.source.rebased(now);
+        }
+    }
+
+    /// The fixed offset shown, `None` for the system's local timezone
+    pub fn timezone(self: &Self) -> Option<FixedOffset> {
+        self.source.timezone
+    }
+
+    /// Show the same instant in another timezone, works while paused as well
+    pub fn set_timezone(self: &mut Self, timezone: FixedOffset) {
+        let now = self.now().with_timezone(&timezone);
+        self.source.timezone = Some(timezone);
+
+        match &mut self.paused {
+            Some(datetime) => *datetime = now,
+            None if self.source.origin.is_some() => self.source = self.source.rebased(now),
+            None => {}
+        }
+    }
+
+    /// Back to real time (in the same timezone) at normal speed
+    pub fn reset(self: &mut Self) {
+        *self = Self::new(TimeSource::new(self.source.timezone, None, None, 1.0));
+    }
+}
+
+static SOURCE: RwLock<ControllableTime> = RwLock::new(ControllableTime::new(TimeSource::real()));
+
+/// Replace the time source shared by everything that calls `now()`
+pub fn set_source(source: TimeSource) {
+    *SOURCE.write().unwrap() = ControllableTime::new(source);
+}
+
+/// Current time according to the shared time source.
+/// Clocks should call this instead of `chrono::Local::now()`
+pub fn now() -> DateTime<FixedOffset> {
+    SOURCE.read().unwrap().now()
+}
+
+/// The real instant by which `delta` of the shared source's time will have passed, counting from the real `now`.
+/// `None` while paused (or for a negative `delta`), as that time is never reached then
+pub fn deadline_after(now: Instant, delta: TimeDelta) -> Option<Instant> {
+    let time = SOURCE.read().unwrap();
+
+    if time.is_paused() {
+        return None;
+    }
+
+    delta.to_std().ok().map(|delta| now + delta.div_f64(time.speed()))
+}
+
+/// Pause, step or speed up the shared time source
+pub fn control<R>(f: impl FnOnce(&mut ControllableTime) -> R) -> R {
+    f(&mut SOURCE.write().unwrap())
+}
+
+/// The `--debug-time` key bindings: `P` pause/resume, `,`/`.` step -/+1 s, `<`/`>` halve/double the speed, `0` back to real time.
+/// Returns whether the key was one of them
+pub(crate) fn handle_debug_key(event: &winit::event::KeyEvent) -> bool {
+    use winit::keyboard::Key;
+
+    if event.state != winit::event::ElementState::Pressed {
+        return false;
+    }
+
+    // `<` and `>` are shifted keys, so the logical key is matched rather than the one without modifiers
+    let handled = match event.logical_key.as_ref() {
+        Key::Character("p") | Key::Character("P") if !event.repeat => {
+            control(|time| if time.is_paused() { time.resume() } else { time.pause() });
+            true
+        },
+        Key::Character(",") => { control(|time| time.step(TimeDelta::seconds(-1))); true },
+        Key::Character(".") => { control(|time| time.step(TimeDelta::seconds(1)));  true },
+        Key::Character("<") => { control(|time| time.set_speed(time.speed() / 2.0)); true },
+        Key::Character(">") => { control(|time| time.set_speed(time.speed() * 2.0)); true },
+        Key::Character("0") if !event.repeat => { control(|time| time.reset()); true },
+        _ => false
+    };
+
+    if handled {
+        let time = *SOURCE.read().unwrap();
+        log::info!(
+            "Time: {}, speed x{}{}",
+            time.now().format("%Y-%m-%d %H:%M:%S"), time.speed(), if time.is_paused() { ", paused" } else { "" }
+        );
+    }
+
+    handled
+}
+
+/// `offset` moved by the given minutes, kept within the offsets `parse_timezone` takes (UTC-14:00 to UTC+14:00)
+pub fn shift_timezone(offset: FixedOffset, minutes: i32) -> FixedOffset {
+    const LIMIT: i32 = 14 * 3600;
+
+    let seconds = (offset.local_minus_utc() + minutes * 60).clamp(-LIMIT, LIMIT);
+    FixedOffset::east_opt(seconds).unwrap()
+}
+
+/// Parse a fixed UTC offset like `UTC`, `UTC+6`, `+05:30`, `-0800` or `local`.
+/// `Ok(None)` means the system's local timezone.
+pub fn parse_timezone(text: &str) -> Result<Option<FixedOffset>, String> {
+    let lowered = text.trim().to_ascii_lowercase();
+
+    if lowered == "local" {
+        return Ok(None);
+    }
+
+    let offset = lowered
+        .strip_prefix("utc")
+        .or_else(|| lowered.strip_prefix("gmt"))
+        .unwrap_or(&lowered);
+
+    if offset.is_empty() {
+        return Ok(Some(FixedOffset::east_opt(0).unwrap()));
+    }
+
+    let invalid = || format!("\"{}\" is not a UTC offset (expected e.g. UTC, UTC+6, +05:30 or local)", text);
+
+    let (sign, digits) = match offset.split_at(1) {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return Err(invalid())
+    };
+
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if digits.len() == 4 => digits.split_at(2),
+        None => (digits, "0")
+    };
+
+    let hours:   i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+
+    if hours > 14 || minutes > 59 {
+        return Err(invalid());
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+        .map(Some)
+        .ok_or_else(invalid)
+}
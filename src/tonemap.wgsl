@@ -0,0 +1,66 @@
+// Tone mapping, the linear colors of an HDR frame brought into the displayable range.
+// Drawn with `FullscreenQuadPipeline`, the UVs come from its vertex shader.
+// The operator is picked per draw, the indices are those of `ToneMapper`
+
+struct TonemapParams {
+    mapper:   i32, // 0 linear (clamped), 1 Reinhard, 2 ACES filmic, 3 Uncharted 2; signed, GL can't emulate u32 push constants
+    exposure: f32  // the frame's colors are multiplied with it first
+}
+
+@group(0) @binding(0)
+var src_tex_2d: texture_2d<f32>; // the frame, in linear HDR
+
+@group(0) @binding(1)
+var tex_sampler: sampler;
+
+var<push_constant> params: TonemapParams;
+
+fn reinhard(color: vec3f) -> vec3f {
+    return color / (1.0 + color);
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+fn aces_film(color: vec3f) -> vec3f {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+
+    return (color * (a * color + b)) / (color * (c * color + d) + e);
+}
+
+// John Hable's curve, as in Uncharted 2
+fn hable(x: vec3f) -> vec3f {
+    let a = 0.15; // shoulder strength
+    let b = 0.50; // linear strength
+    let c = 0.10; // linear angle
+    let d = 0.20; // toe strength
+    let e = 0.02; // toe numerator
+    let f = 0.30; // toe denominator
+
+    return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+fn uncharted2(color: vec3f) -> vec3f {
+    let exposure_bias = 2.0;
+    let white = 11.2; // the linear value that ends up white
+
+    return hable(color * exposure_bias) / hable(vec3f(white));
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    let source = textureSample(src_tex_2d, tex_sampler, uv);
+    let color  = max(source.rgb * params.exposure, vec3f(0.0));
+
+    var mapped: vec3f;
+    switch params.mapper {
+        case 1:  { mapped = reinhard(color); }
+        case 2:  { mapped = aces_film(color); }
+        case 3:  { mapped = uncharted2(color); }
+        default:  { mapped = color; }
+    }
+
+    return vec4f(clamp(mapped, vec3f(0.0), vec3f(1.0)), source.a);
+}
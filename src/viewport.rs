@@ -0,0 +1,111 @@
+//! Rendering into a part of the target: a rectangle in pixels, and the render pass helpers that restrict drawing to it.
+//!
+//! Rectangles are in framebuffer coordinates, as `set_viewport` and `set_scissor_rect` take them on every backend:
+//! the origin is the top-left corner and y grows downwards. The drawing space of `DrawspaceScales` is centered
+//! with y growing upwards, `ViewportRect::from_drawspace` flips it over.
+//!
+//! wgpu fails validation on rectangles reaching past the attachment, which happens for a frame when a pass is
+//! set up with the previous size during a resize. The helpers clamp to the attachment's size instead,
+//! and tell when nothing of the rectangle is left to draw into.
+
+use crate::DrawspaceScales;
+
+/// A rectangle of the render target in pixels, from its top-left corner
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ViewportRect {
+    pub x:      u32,
+    pub y:      u32,
+    pub width:  u32,
+    pub height: u32
+}
+
+impl ViewportRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// All of a target of the given size
+    pub fn full(size: (u32, u32)) -> Self {
+        Self::new(0, 0, size.0, size.1)
+    }
+
+    /// The pixels covering a rectangle of the drawing space, given by two opposite corners in drawing units.
+    /// Partially covered pixels are included, the parts off the window are cut off
+    pub fn from_drawspace(scales: &DrawspaceScales, corner0: glam::Vec2, corner1: glam::Vec2) -> Self {
+        let half = scales.resolution * 0.5;
+
+        // y flipped, the drawing space's top is the framebuffer's row 0
+        let to_pixels = |pos: glam::Vec2| glam::Vec2::new(half.x + pos.x * scales.density, half.y - pos.y * scales.density);
+        let (a, b) = (to_pixels(corner0), to_pixels(corner1));
+
+        let min = a.min(b).floor().max(glam::Vec2::ZERO);
+        let max = a.max(b).ceil().min(scales.resolution).max(min);
+
+        Self::new(min.x as u32, min.y as u32, (max.x - min.x) as u32, (max.y - min.y) as u32)
+    }
+
+    /// The part within a target of the given size, `None` if there's none
+    pub fn clamped(self: &Self, size: (u32, u32)) -> Option<Self> {
+        let x = self.x.min(size.0);
+        let y = self.y.min(size.1);
+        let width  = self.x.saturating_add(self.width).min(size.0) - x;
+        let height = self.y.saturating_add(self.height).min(size.1) - y;
+
+        (width > 0 && height > 0).then_some(Self::new(x, y, width, height))
+    }
+
+    /// The left and the right half, the right one takes the odd pixel
+    pub fn split_horizontally(self: &Self) -> (Self, Self) {
+        let left = self.width / 2;
+        (Self::new(self.x, self.y, left, self.height), Self::new(self.x + left, self.y, self.width - left, self.height))
+    }
+
+    /// The top and the bottom half, the bottom one takes the odd pixel
+    pub fn split_vertically(self: &Self) -> (Self, Self) {
+        let top = self.height / 2;
+        (Self::new(self.x, self.y, self.width, top), Self::new(self.x, self.y + top, self.width, self.height - top))
+    }
+
+    pub fn contains(self: &Self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+}
+
+impl From<(u32, u32, u32, u32)> for ViewportRect {
+    /// From `(x, y, width, height)`
+    fn from((x, y, width, height): (u32, u32, u32, u32)) -> Self {
+        Self::new(x, y, width, height)
+    }
+}
+
+/// `set_viewport` and `set_scissor_rect` with a `ViewportRect`, clamped to the size of the pass's attachments.
+/// Both return `false` when nothing is left of the rectangle, the pass is left as it was then and the draws
+/// meant for the rectangle should be skipped
+pub trait RenderPassViewportExt {
+    /// Maps the clip space onto the rectangle, everything drawn is scaled into it.
+    /// Clamped, it's squeezed into what's left of the rectangle, e.g. for the frame drawn during a resize
+    fn set_viewport_rect(self: &mut Self, rect: ViewportRect, target: (u32, u32)) -> bool;
+
+    /// Keeps the pixels outside the rectangle, everything drawn is cut off at its edges
+    fn set_scissor(self: &mut Self, rect: ViewportRect, target: (u32, u32)) -> bool;
+}
+
+impl RenderPassViewportExt for wgpu::RenderPass<'_> {
+    fn set_viewport_rect(self: &mut Self, rect: ViewportRect, target: (u32, u32)) -> bool {
+        let Some(rect) = rect.clamped(target) else {
+            return false;
+        };
+
+        self.set_viewport(rect.x as f32, rect.y as f32, rect.width as f32, rect.height as f32, 0.0, 1.0);
+        true
+    }
+
+    fn set_scissor(self: &mut Self, rect: ViewportRect, target: (u32, u32)) -> bool {
+        let Some(rect) = rect.clamped(target) else {
+            return false;
+        };
+
+        self.set_scissor_rect(rect.x, rect.y, rect.width, rect.height);
+        true
+    }
+}
@@ -0,0 +1,51 @@
+//! The web build: the clocks on a page, drawn with WebGL2 into a canvas.
+//!
+//! `trunk serve` builds the clock `index.html` names for wasm32 and serves it. There's no filesystem there,
+//! `get_resource_folder_for` points into `RESOURCES_ROOT` instead, which `load_shader` looks up in `EMBEDDED_SHADERS`.
+//! Only the polar clock is embedded so far, the others need textures and meshes too.
+//!
+//! The rest is the runner's: the surface is created from the window's canvas (see `attach_canvas`),
+//! and the clock's future is spawned on the browser's event loop instead of blocked on (see `launch`).
+//! WebGL2 has neither push constants nor storage buffers, the clocks fall back to uniform buffers for both.
+
+use std::path::Path;
+
+/// The resources folder of the web build, there's nothing in it besides `EMBEDDED_SHADERS`
+pub const RESOURCES_ROOT: &str = "resources";
+
+/// Id of the page's element the canvas is put into, the body without one
+pub const CANVAS_PARENT_ID: &str = "clock";
+
+/// Shaders of the clocks the web build runs, by their path within the resources folder
+pub const EMBEDDED_SHADERS: &[(&str, &str)] = &[
+    ("polar/shaders/disk.wgsl", include_str!("../resources/polar/shaders/disk.wgsl")),
+    ("polar/shaders/ring.wgsl", include_str!("../resources/polar/shaders/ring.wgsl")),
+    ("polar/shaders/star.wgsl", include_str!("../resources/polar/shaders/star.wgsl"))
+];
+
+/// Embedded source of the shader at the given path within `RESOURCES_ROOT`, as the clocks load them
+pub fn embedded_shader(path: &Path) -> Option<&'static str> {
+    let path = path.strip_prefix(RESOURCES_ROOT).ok()?;
+
+    EMBEDDED_SHADERS.iter()
+        .find(|(name, _)| Path::new(name) == path)
+        .map(|(_, source)| *source)
+}
+
+/// Put the window's canvas on the page, into the element with the id `CANVAS_PARENT_ID` (or the body), for the surface
+#[cfg(target_arch = "wasm32")]
+pub fn attach_canvas(window: &winit::window::Window) -> Result<web_sys::HtmlCanvasElement, String> {
+    use winit::platform::web::WindowExtWebSys;
+
+    let canvas   = window.canvas().ok_or("The window has no canvas")?;
+    let document = web_sys::window().and_then(|window| window.document()).ok_or("There's no page to put the canvas on")?;
+
+    let parent: web_sys::Element = match document.get_element_by_id(CANVAS_PARENT_ID) {
+        Some(element) => element,
+        None => document.body().ok_or("The page has no body to put the canvas into")?.into()
+    };
+
+    parent.append_child(&canvas).map_err(|error| format!("Can't put the canvas on the page: {:?}", error))?;
+
+    return Ok(canvas);
+}
@@ -0,0 +1,316 @@
+//! Desktop widget mode: a borderless, transparent, always on top window, dragged around with a modifier held.
+//! It can be pinned to a corner of its monitor instead, see `ScreenCorner`.
+//!
+//! Clicks can pass through the widget as well, see `WidgetOptions::click_through`. With hit-testing off the window
+//! gets no input at all, not even key presses, so the modifier is watched via device events, which the event loop
+//! delivers regardless of focus (`DeviceEvents::Always`). While it's held the window takes input again.
+//!
+//! The widget can be ghosted with `RunOptions::opacity`: the clock then draws into an intermediate texture,
+//! which `OpacityPass` composites onto the surface, respecting how the surface expects alpha.
+
+use winit::keyboard::{KeyCode, PhysicalKey};
+use crate::{cast_struct_to_u8_slice, RenderTexture, SingleUniformBuffer};
+
+/// Key that lets the widget be dragged, and makes a click-through widget take input, while held
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WidgetModifier {
+    #[default]
+    Alt,
+    Ctrl,
+    Shift,
+    Super
+}
+
+impl WidgetModifier {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "alt"                    => Some(WidgetModifier::Alt),
+            "ctrl" | "control"       => Some(WidgetModifier::Ctrl),
+            "shift"                  => Some(WidgetModifier::Shift),
+            "super" | "win" | "cmd"  => Some(WidgetModifier::Super),
+            _ => None
+        }
+    }
+
+    /// Index of the key if it's the left (0) or the right (1) variant of the modifier
+    fn side(self: Self, key: KeyCode) -> Option<usize> {
+        let (left, right) = match self {
+            WidgetModifier::Alt   => (KeyCode::AltLeft,     KeyCode::AltRight),
+            WidgetModifier::Ctrl  => (KeyCode::ControlLeft, KeyCode::ControlRight),
+            WidgetModifier::Shift => (KeyCode::ShiftLeft,   KeyCode::ShiftRight),
+            WidgetModifier::Super => (KeyCode::SuperLeft,   KeyCode::SuperRight)
+        };
+
+        if key == left {
+            Some(0)
+        } else if key == right {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+/// The widget preset, see `RunOptions::widget`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct WidgetOptions {
+    /// Dragging the window with the left button moves it while this is held
+    pub modifier:      WidgetModifier,
+    /// Let clicks pass through to whatever is beneath the window, except while the modifier is held
+    pub click_through: bool
+}
+
+/// The widget's window: borderless, transparent and always on top, left out of the taskbar where that's possible
+pub fn apply_to_window(mut builder: winit::window::WindowBuilder) -> winit::window::WindowBuilder {
+    builder = builder
+        .with_decorations(false)
+        .with_transparent(true)
+        .with_window_level(winit::window::WindowLevel::AlwaysOnTop);
+
+    #[cfg(target_os = "windows")]
+    {
+        use winit::platform::windows::WindowBuilderExtWindows;
+        // OLE drag and drop conflicts with the widget's own input handling
+        builder = builder.with_drag_and_drop(false).with_skip_taskbar(true);
+    }
+
+    // utility windows are left out of the taskbar by most X11 window managers
+    #[cfg(all(unix, not(target_os = "macos"), not(target_os = "android")))]
+    {
+        use winit::platform::x11::{WindowBuilderExtX11, XWindowType};
+        builder = builder.with_x11_window_type(vec![XWindowType::Utility]);
+    }
+
+    builder
+}
+
+/// Distance of a window pinned to a corner from the monitor's edges, in logical pixels
+pub const CORNER_MARGIN: f64 = 16.0;
+
+/// Corner of the monitor a widget is pinned to, see `RunOptions::corner`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScreenCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight
+}
+
+impl ScreenCorner {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "top-left"     => Some(ScreenCorner::TopLeft),
+            "top-right"    => Some(ScreenCorner::TopRight),
+            "bottom-left"  => Some(ScreenCorner::BottomLeft),
+            "bottom-right" => Some(ScreenCorner::BottomRight),
+            _ => None
+        }
+    }
+
+    /// Outer position of a window of `window_size` in this corner of the monitor, `margin` away from its edges.
+    /// All in physical pixels, the monitor's position is within the virtual desktop
+    pub fn position(self: Self, monitor_position: (i32, i32), monitor_size: (u32, u32), window_size: (u32, u32), margin: u32) -> (i32, i32) {
+        let left   = monitor_position.0 + margin as i32;
+        let top    = monitor_position.1 + margin as i32;
+        let right  = monitor_position.0 + monitor_size.0 as i32 - window_size.0 as i32 - margin as i32;
+        let bottom = monitor_position.1 + monitor_size.1 as i32 - window_size.1 as i32 - margin as i32;
+
+        match self {
+            ScreenCorner::TopLeft     => (left,  top),
+            ScreenCorner::TopRight    => (right, top),
+            ScreenCorner::BottomLeft  => (left,  bottom),
+            ScreenCorner::BottomRight => (right, bottom)
+        }
+    }
+}
+
+/// Move the window into the corner of the monitor it's on
+pub(crate) fn pin_to_corner(window: &winit::window::Window, corner: ScreenCorner) {
+    let Some(monitor) = window.current_monitor().or_else(|| window.primary_monitor()) else {
+        log::warn!("The window can't be pinned to the {:?} corner, there's no monitor to tell where that is", corner);
+        return;
+    };
+
+    let margin = (CORNER_MARGIN * monitor.scale_factor()).round() as u32;
+    let (position, monitor_size, size) = (monitor.position(), monitor.size(), window.outer_size());
+    let (x, y) = corner.position((position.x, position.y), (monitor_size.width, monitor_size.height), (size.width, size.height), margin);
+
+    log::debug!("Pinned to the {:?} corner at {}, {}", corner, x, y);
+    window.set_outer_position(winit::dpi::PhysicalPosition { x, y });
+}
+
+/// Watches the modifier the widget is dragged by, and turns the hit-testing of a click-through widget on while it's held
+pub(crate) struct WidgetInput {
+    options: WidgetOptions,
+    held:    [bool; 2] // left, right
+}
+
+impl WidgetInput {
+    pub fn new(window: &winit::window::Window, options: WidgetOptions) -> Self {
+        if options.click_through {
+            if let Err(error) = window.set_cursor_hittest(false) {
+                log::warn!("Clicks can't pass through the window here: {}", error);
+            }
+        }
+
+        Self { options, held: [false; 2] }
+    }
+
+    pub fn is_modifier_held(self: &Self) -> bool {
+        self.held[0] || self.held[1]
+    }
+
+    pub fn on_device_key(self: &mut Self, window: &winit::window::Window, event: &winit::event::RawKeyEvent) {
+        let PhysicalKey::Code(code) = event.physical_key else {
+            return;
+        };
+
+        let Some(side) = self.options.modifier.side(code) else {
+            return;
+        };
+
+        let was_held = self.is_modifier_held();
+        self.held[side] = event.state == winit::event::ElementState::Pressed;
+
+        if self.options.click_through && was_held != self.is_modifier_held() {
+            log::debug!("Widget hit-testing {}", if self.is_modifier_held() { "on" } else { "off" });
+            let _ = window.set_cursor_hittest(self.is_modifier_held());
+        }
+    }
+}
+
+/// Mirrors `CompositeInfo` in widget.wgsl
+#[repr(C, align(8))]
+struct CompositeInfo {
+    opacity:    f32,
+    alpha_mode: u32 // 1: premultiplied, 2: postmultiplied
+}
+
+/// Copies the clock's frame onto the surface with the opacity applied
+pub(crate) struct OpacityPass {
+    pipeline:         wgpu::RenderPipeline,
+    bindgroup_layout: wgpu::BindGroupLayout,
+    uniform:          SingleUniformBuffer,
+    target:           RenderTexture,
+    bindgroup:        wgpu::BindGroup,
+    format:           wgpu::TextureFormat // of the surface, the clock's target has the same
+}
+
+impl OpacityPass {
+    /// `None` if the surface is opaque, there's nothing to see through then
+    pub fn new(config: &wgpu::SurfaceConfiguration, opacity: f32, device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        let alpha_mode = match config.alpha_mode {
+            wgpu::CompositeAlphaMode::PreMultiplied  => 1,
+            wgpu::CompositeAlphaMode::PostMultiplied => 2,
+            _ => {
+                log::warn!("The opacity needs a transparent surface, it's ignored");
+                return None;
+            }
+        };
+
+        let uniform = SingleUniformBuffer::new::<CompositeInfo>(device, wgpu::ShaderStages::FRAGMENT);
+        queue.write_buffer(&uniform.buffer, 0, cast_struct_to_u8_slice(&CompositeInfo { opacity, alpha_mode }));
+
+        let bindgroup_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Widget opacity"),
+            entries: &[
+                RenderTexture::default_layout_entry(0),
+                SingleUniformBuffer::default_layout_entry(1, &uniform)
+            ]
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                Some("Widget opacity"),
+            bind_group_layouts:   &[ &bindgroup_layout ],
+            push_constant_ranges: &[]
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("Widget opacity"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("widget.wgsl").into())
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label:         Some("Widget opacity"),
+            layout:        Some(&pipeline_layout),
+            depth_stencil: None,
+            multisample:   wgpu::MultisampleState::default(),
+            multiview:     None,
+            vertex: wgpu::VertexState {
+                module:      &shader,
+                entry_point: "vs_main",
+                buffers:     &[]
+            },
+            fragment: Some(wgpu::FragmentState {
+                module:      &shader,
+                entry_point: "fs_main",
+                targets:     &[ Some(config.format.into()) ]
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology:     wgpu::PrimitiveTopology::TriangleStrip,
+                cull_mode:    None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                ..Default::default()
+            }
+        });
+
+        let (target, bindgroup) = Self::create_target((config.width, config.height), config.format, &bindgroup_layout, &uniform, device);
+
+        Some(Self { pipeline, bindgroup_layout, uniform, target, bindgroup, format: config.format })
+    }
+
+    fn create_target(
+        size:    (u32, u32),
+        format:  wgpu::TextureFormat,
+        layout:  &wgpu::BindGroupLayout,
+        uniform: &SingleUniformBuffer,
+        device:  &wgpu::Device
+    ) -> (RenderTexture, wgpu::BindGroup) {
+        let target = RenderTexture::new(size, format, true, device);
+
+        let bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label:   Some("Widget opacity"),
+            layout,
+            entries: &[ target.get_entry(0), uniform.get_entry(1) ]
+        });
+
+        (target, bindgroup)
+    }
+
+    pub fn resize(self: &mut Self, width: u32, height: u32, device: &wgpu::Device) {
+        (self.target, self.bindgroup) = Self::create_target((width, height), self.format, &self.bindgroup_layout, &self.uniform, device);
+    }
+
+    /// What the clock draws into instead of the surface
+    pub fn target(self: &Self) -> &wgpu::TextureView {
+        &self.target.view
+    }
+
+    /// Composite the clock's frame onto the surface
+    pub fn apply(self: &Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Widget opacity") });
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    Some("Widget opacity"),
+                depth_stencil_attachment: None,
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+                    view: texview,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load:  wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store
+                    }
+                })]
+            });
+
+            rpass.set_pipeline(&self.pipeline);
+            rpass.set_bind_group(0, &self.bindgroup, &[]);
+            rpass.draw(0..4, 0..1);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
@@ -0,0 +1,37 @@
+// Widget opacity: the clock's frame copied onto the surface, faded according to the surface's alpha mode.
+// The frame and the surface have the same size, so the texels are loaded 1:1 without a sampler.
+
+struct CompositeInfo {
+    opacity:    f32,
+    alpha_mode: u32 // 1: premultiplied, 2: postmultiplied
+}
+
+@group(0) @binding(0)
+var frame: texture_2d<f32>;
+
+@group(0) @binding(1)
+var<uniform> info: CompositeInfo;
+
+@vertex
+fn vs_main(@builtin(vertex_index) VertexIndex: u32) -> @builtin(position) vec4f {
+    var corners = array<vec2f, 4>(
+        vec2f(-1.0,  1.0),
+        vec2f( 1.0,  1.0),
+        vec2f(-1.0, -1.0),
+        vec2f( 1.0, -1.0)
+    );
+
+    return vec4f(corners[VertexIndex], 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4f) -> @location(0) vec4f {
+    let color = textureLoad(frame, vec2i(pos.xy), 0);
+
+    // premultiplied colors fade along with the alpha, straight ones keep their color
+    if info.alpha_mode == 1u {
+        return color * info.opacity;
+    }
+
+    return vec4f(color.rgb, color.a * info.opacity);
+}
@@ -0,0 +1,83 @@
+//! The analog clock's hands: where they point at a given time, and the vertices of each shape
+
+use std::f32::consts::TAU;
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta};
+use clockutils::clocks::analog::{calc_hand_angles, cap_vertices, HandGeometry, HandShape, DIAL_RADIUS};
+
+fn at(hour: u32, minute: u32, second: u32, nanos: i64) -> DateTime<FixedOffset> {
+    NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+        .and_hms_opt(hour, minute, second).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(0).unwrap()).unwrap() + TimeDelta::nanoseconds(nanos)
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-4, "{} != {}", actual, expected);
+}
+
+#[test]
+fn hands_point_at_the_time() {
+    // 15:00 is a quarter turn of the hours' hand, the others at 12
+    let angles = calc_hand_angles(&at(15, 0, 0, 0));
+    assert_close(angles.hours,   TAU * 0.25);
+    assert_close(angles.minutes, 0.0);
+    assert_close(angles.seconds, 0.0);
+
+    // 10:30:45, the hours' hand halfway to 11
+    let angles = calc_hand_angles(&at(10, 30, 45, 0));
+    assert_close(angles.hours,   TAU * (10.5 + 0.75 / 60.0) / 12.0);
+    assert_close(angles.minutes, TAU * 30.75 / 60.0);
+    assert_close(angles.seconds, TAU * 0.75);
+}
+
+#[test]
+fn seconds_sweep_between_ticks() {
+    let angles: Vec<f32> = (0..4).map(|quarter| calc_hand_angles(&at(12, 0, 10, quarter * 250_000_000)).seconds).collect();
+
+    // evenly on within the second
+    for (quarter, angle) in angles.iter().enumerate() {
+        assert_close(*angle, TAU * (10.0 + quarter as f32 * 0.25) / 60.0);
+    }
+}
+
+#[test]
+fn shapes_cycle() {
+    let mut shape = HandShape::Baton;
+    let mut seen = Vec::new();
+
+    for _ in 0..HandShape::ALL.len() {
+        seen.push(shape);
+        shape = shape.next();
+    }
+
+    assert_eq!(shape, HandShape::Baton);
+    assert_eq!(seen, HandShape::ALL);
+}
+
+#[test]
+fn hands_are_quads_within_the_dial() {
+    for shape in HandShape::ALL {
+        let geometry = HandGeometry::new(shape);
+
+        // one after the other, covering the buffer
+        assert_eq!(geometry.hours.start, 0, "{:?}", shape);
+        assert_eq!(geometry.hours.end, geometry.minutes.start, "{:?}", shape);
+        assert_eq!(geometry.minutes.end, geometry.seconds.start, "{:?}", shape);
+        assert_eq!(geometry.seconds.end as usize, geometry.vertices.len(), "{:?}", shape);
+
+        for range in [&geometry.hours, &geometry.minutes, &geometry.seconds] {
+            assert!(!range.is_empty() && range.len() % 6 == 0, "{:?}: {:?}", shape, range);
+        }
+
+        // pointing at 12, the tips shorter than the dial
+        assert!(geometry.vertices.iter().all(|vertex| vertex.pos.length() < DIAL_RADIUS), "{:?}", shape);
+        assert!(geometry.vertices.iter().map(|vertex| vertex.pos.y).fold(0.0, f32::max) > DIAL_RADIUS * 0.5, "{:?}", shape);
+    }
+
+    // only the lollipops have round parts
+    let round = |shape: HandShape| HandGeometry::new(shape).vertices.iter().any(|vertex| vertex.uv != glam::Vec2::ZERO);
+    assert!(!round(HandShape::Baton));
+    assert!(!round(HandShape::Spade));
+    assert!(round(HandShape::Lollipop));
+
+    assert_eq!(cap_vertices().len(), 6);
+}
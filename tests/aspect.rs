@@ -0,0 +1,39 @@
+//! The letterbox of `RunOptions::aspect_ratio`: the clock's size snapped to the ratio, and centered within the window
+
+use clockutils::aspect::{letterbox_offset, snap_to_aspect};
+
+#[test]
+fn snapped_sizes_fit_into_the_window() {
+    // wider than square, taller than square, exactly square
+    assert_eq!(snap_to_aspect((800, 600), 1.0), (600, 600));
+    assert_eq!(snap_to_aspect((600, 800), 1.0), (600, 600));
+    assert_eq!(snap_to_aspect((512, 512), 1.0), (512, 512));
+
+    assert_eq!(snap_to_aspect((1920, 1200), 16.0 / 9.0), (1920, 1080));
+    assert_eq!(snap_to_aspect((1000, 1080), 16.0 / 9.0), (1000, 563));
+
+    for (width, height) in [(1, 1), (3, 1000), (1000, 3), (1366, 768), (333, 777)] {
+        for aspect in [0.25, 1.0, 4.0 / 3.0, 21.0 / 9.0] {
+            let (snapped_width, snapped_height) = snap_to_aspect((width, height), aspect);
+            assert!(snapped_width  <= width  && snapped_width  >= 1, "{}x{} at {}", width, height, aspect);
+            assert!(snapped_height <= height && snapped_height >= 1, "{}x{} at {}", width, height, aspect);
+            assert!(snapped_width == width || snapped_height == height, "{}x{} at {} doesn't fill the window", width, height, aspect);
+        }
+    }
+}
+
+#[test]
+fn minimized_windows_snap_to_a_pixel() {
+    assert_eq!(snap_to_aspect((0, 0), 1.0), (1, 1));
+    assert_eq!(snap_to_aspect((0, 600), 2.0), (1, 1));
+}
+
+#[test]
+fn content_is_centered() {
+    assert_eq!(letterbox_offset((800, 600), (600, 600)), (100, 0));
+    assert_eq!(letterbox_offset((600, 801), (600, 600)), (0, 100));
+    assert_eq!(letterbox_offset((512, 512), (512, 512)), (0, 0));
+
+    // never past the window's corner
+    assert_eq!(letterbox_offset((100, 100), (200, 50)), (0, 25));
+}
@@ -0,0 +1,59 @@
+//! The binary clock's LEDs: the BCD bits lit at a given time, and the islands they're drawn as
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use clockutils::clocks::binary::{bcd_digits, calculate_binary_data, island_id, led_center, led_vertices_and_indices, COLUMNS, EXTENT, LED_COUNT, LED_RADIUS, ROWS};
+
+fn at(hour: u32, minute: u32, second: u32) -> DateTime<FixedOffset> {
+    NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+        .and_hms_opt(hour, minute, second).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(0).unwrap()).unwrap()
+}
+
+#[test]
+fn columns_hold_the_digits() {
+    assert_eq!(bcd_digits(&at(21, 47, 59), false), [2, 1, 4, 7, 5, 9]);
+
+    // 12-hour format, midnight is 12
+    assert_eq!(bcd_digits(&at(21, 47, 59), true), [0, 9, 4, 7, 5, 9]);
+    assert_eq!(bcd_digits(&at(0, 5, 0), true), [1, 2, 0, 5, 0, 0]);
+    assert_eq!(bcd_digits(&at(0, 5, 0), false), [0, 0, 0, 5, 0, 0]);
+}
+
+#[test]
+fn flags_light_the_digits_bits() {
+    let data = calculate_binary_data(&at(21, 47, 59), false, 0xFF0000_FF, 0x200000_FF);
+    assert_eq!(data.flags, 0x9574_12);
+    assert_eq!((data.on_color, data.off_color), (0xFF0000_FF, 0x200000_FF));
+
+    // the 8's bit of the seconds' ones, the top of the last column
+    assert_ne!(data.flags & (1 << island_id(5, 3)), 0);
+    assert_eq!(data.flags >> LED_COUNT, 0);
+
+    assert_eq!(calculate_binary_data(&at(0, 0, 0), false, 0, 0).flags, 0);
+}
+
+#[test]
+fn every_led_is_an_island() {
+    let (vertices, indices) = led_vertices_and_indices();
+
+    // triangles within the buffer, each one of a single island
+    assert_eq!(indices.len() % 3, 0);
+    assert!(indices.iter().all(|&index| (index as usize) < vertices.len()));
+    assert!(indices.chunks(3).all(|triangle| triangle.iter().all(|&index| vertices[index as usize].id == vertices[triangle[0] as usize].id)));
+
+    for column in 0..COLUMNS {
+        for row in 0..ROWS {
+            let id     = island_id(column, row);
+            let center = led_center(column, row);
+            let island: Vec<_> = vertices.iter().filter(|vertex| vertex.id == id).collect();
+
+            assert!(!island.is_empty(), "{}", id);
+            assert!(island.iter().all(|vertex| vertex.pos.distance(center) <= LED_RADIUS + 1e-4), "{}", id);
+        }
+    }
+
+    // all of them within the visible area, the 8's at the top
+    assert!(vertices.iter().all(|vertex| vertex.pos.abs().cmplt(EXTENT).all()));
+    assert!(led_center(0, 3).y > led_center(0, 0).y);
+    assert_eq!(vertices.iter().map(|vertex| vertex.id).max(), Some(LED_COUNT - 1));
+}
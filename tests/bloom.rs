@@ -0,0 +1,60 @@
+//! Bloom around a bright square: the light bleeds out of it, the dim parts neither bloom nor change.
+//!
+//! Needs a GPU with push constants, skipped when there's none
+
+mod common;
+
+use clockutils::{headless::read_texture_rgba, BloomPass, FullscreenQuadPipeline, RenderTexture};
+
+// a white square in the middle, on a dim gray that stays under the threshold
+const SCENE_SHADER: &str = "
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    let inside = all(abs(uv - 0.5) < vec2f(0.125));
+    return select(vec4f(0.2, 0.2, 0.2, 1.0), vec4f(1.0), inside);
+}
+";
+
+const SIZE: u32 = 64;
+
+#[test]
+fn bright_parts_bleed_out() {
+    let Some(adapter) = common::adapter_with(wgpu::Features::PUSH_CONSTANTS) else {
+        return;
+    };
+
+    // the blur table is a storage buffer
+    let (device, queue) = common::device_with_limits(&adapter, wgpu::Features::PUSH_CONSTANTS, wgpu::Limits::downlevel_defaults());
+
+    // linear, so that the values read back are the shader's
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+
+    let scene_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(SCENE_SHADER.into())
+    });
+    let scene = FullscreenQuadPipeline::new(&device, &scene_shader, "fs_main", &[], &[], format);
+
+    let source = RenderTexture::new((SIZE, SIZE), format, true, &device);
+    let (output, output_view) = common::readable_target(&device, (SIZE, SIZE), format);
+
+    let mut bloom = BloomPass::new(&device, &queue, format, (SIZE, SIZE));
+    bloom.set_blur_radius(8, &device, &queue);
+    let source_bindgroup = bloom.source_bindgroup(&source, &device);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    scene.draw(&mut common::render_pass(&mut encoder, &source.view, wgpu::Color::BLACK), &[], &[]);
+    bloom.apply(&mut encoder, &source_bindgroup, &output_view, 0.5, 4.0);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let image = read_texture_rgba(&output, &device, &queue).unwrap();
+    let gray = (0.2f32 * 255.0).round() as u8;
+
+    // the square stays white, the corners are out of the bloom's reach
+    assert_eq!(image.get_pixel(SIZE / 2, SIZE / 2).0, [255, 255, 255, 255]);
+    assert_eq!(image.get_pixel(1, 1).0, [gray, gray, gray, 255]);
+
+    // just outside the square, the gray is lit up
+    let next_to_the_square = image.get_pixel(SIZE / 2, SIZE / 2 + SIZE / 8 + 1).0;
+    assert!(next_to_the_square[0] > gray + 10, "No bloom next to the square: {:?}", next_to_the_square);
+}
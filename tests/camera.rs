@@ -0,0 +1,63 @@
+//! The extent's corners around the camera's target land on the clip-space corners, in both projections
+
+use glam::{Vec2, Vec3, Vec4Swizzles};
+use clockutils::camera::{view_projection, Projection};
+
+const EYE:    Vec3 = Vec3::new(0.0, 0.0, 8.0);
+const EXTENT: Vec2 = Vec2::new(8.0, 4.0);
+const DEPTH:  (f32, f32) = (1.0, 100.0);
+
+/// Normalized device coordinates of a world space point, as seen with a resolution of the given size
+fn project(projection: Projection, resolution: Vec2, point: Vec3) -> Vec3 {
+    let clip = view_projection(projection, EYE, Vec3::ZERO, Vec3::Y, resolution, EXTENT, DEPTH) * point.extend(1.0);
+    clip.xyz() / clip.w
+}
+
+fn assert_near(actual: Vec2, expected: Vec2) {
+    assert!(actual.abs_diff_eq(expected, 1e-5), "{} != {}", actual, expected);
+}
+
+#[test]
+fn corners_fill_a_matching_window() {
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        for (x, y) in [(1.0, 1.0), (-1.0, 1.0), (1.0, -1.0), (-1.0, -1.0)] {
+            let corner = Vec3::new(EXTENT.x * x, EXTENT.y * y, 0.0);
+            assert_near(project(projection, Vec2::new(1024.0, 512.0), corner).truncate(), Vec2::new(x, y));
+        }
+    }
+}
+
+#[test]
+fn corners_fit_other_aspect_ratios() {
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        // taller than the extent, the sides touch, there's more to see above and below
+        let ndc = project(projection, Vec2::new(512.0, 512.0), EXTENT.extend(0.0));
+        assert_near(ndc.truncate(), Vec2::new(1.0, 0.5));
+
+        // wider, the top and the bottom touch
+        let ndc = project(projection, Vec2::new(2048.0, 512.0), EXTENT.extend(0.0));
+        assert_near(ndc.truncate(), Vec2::new(0.5, 1.0));
+    }
+}
+
+#[test]
+fn only_perspective_shrinks_with_depth() {
+    let behind = Vec3::new(EXTENT.x, EXTENT.y, -8.0); // twice as far from the eye as the target
+
+    let ndc = project(Projection::Perspective, Vec2::new(1024.0, 512.0), behind);
+    assert_near(ndc.truncate(), Vec2::new(0.5, 0.5));
+
+    let ndc = project(Projection::Orthographic, Vec2::new(1024.0, 512.0), behind);
+    assert_near(ndc.truncate(), Vec2::new(1.0, 1.0));
+}
+
+#[test]
+fn depth_stays_in_range() {
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        let near = project(projection, Vec2::new(1024.0, 512.0), Vec3::new(0.0, 0.0, EYE.z - DEPTH.0));
+        let far  = project(projection, Vec2::new(1024.0, 512.0), Vec3::new(0.0, 0.0, EYE.z - DEPTH.1));
+
+        assert!((near.z - 0.0).abs() < 1e-5, "{:?} near plane at {}", projection, near.z);
+        assert!((far.z  - 1.0).abs() < 1e-5, "{:?} far plane at {}", projection, far.z);
+    }
+}
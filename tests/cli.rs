@@ -0,0 +1,79 @@
+//! The command line parser: typos get a suggestion, bad values name the flag, and the clock's own flags come through
+
+use clockutils::cli::{AppArgs, Cli, CliError, CommonArgs};
+
+fn parse(cli: &Cli, args: &[&str]) -> Result<(CommonArgs, AppArgs), CliError> {
+    cli.try_parse(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn typos_get_a_suggestion() {
+    let cli = Cli::new("test", "Test clock");
+
+    let error = parse(&cli, &["--fulscreen"]).unwrap_err();
+    assert!(matches!(error, CliError::Unknown { ref flag, suggestion: Some("--fullscreen") } if flag == "--fulscreen"), "{:?}", error);
+    assert_eq!(error.to_string(), "unknown option '--fulscreen', did you mean '--fullscreen'?");
+
+    // the clock's own flags are suggested too
+    let cli = cli.switch("--seconds", "Show the seconds");
+    assert!(matches!(parse(&cli, &["--second"]), Err(CliError::Unknown { suggestion: Some("--seconds"), .. })));
+}
+
+#[test]
+fn far_off_flags_get_no_suggestion() {
+    let cli = Cli::new("test", "Test clock");
+
+    let error = parse(&cli, &["--xyzzy-plugh"]).unwrap_err();
+    assert!(matches!(error, CliError::Unknown { suggestion: None, .. }), "{:?}", error);
+    assert_eq!(error.to_string(), "unknown option '--xyzzy-plugh', see --help");
+
+    // not a flag at all
+    assert!(matches!(parse(&cli, &["fullscreen"]), Err(CliError::Unexpected(arg)) if arg == "fullscreen"));
+}
+
+#[test]
+fn bad_values_name_the_flag() {
+    let cli = Cli::new("test", "Test clock");
+    let invalid = |args: &[&str]| match parse(&cli, args) {
+        Err(CliError::InvalidValue { flag, .. }) => flag,
+        other => panic!("{:?} parsed as {:?}", args, other.map(|_| ()))
+    };
+
+    // sizes
+    assert_eq!(invalid(&["--size", "10x"]), "--size");
+    assert_eq!(invalid(&["--size=0x600"]), "--size");
+    // choices
+    assert_eq!(invalid(&["--vsync", "maybe"]), "--vsync");
+    assert_eq!(invalid(&["--corner", "middle"]), "--corner");
+    // numbers, out of range or not a number
+    assert_eq!(invalid(&["--msaa", "3"]), "--msaa");
+    assert_eq!(invalid(&["--opacity", "1.5"]), "--opacity");
+    assert_eq!(invalid(&["--max-fps", "fast"]), "--max-fps");
+    // times and dates
+    assert_eq!(invalid(&["--time", "25:00"]), "--time");
+    assert_eq!(invalid(&["--date", "2024-13-01"]), "--date");
+    // a value for a switch
+    assert_eq!(invalid(&["--stats=yes"]), "--stats");
+
+    // and none for an option
+    assert!(matches!(parse(&cli, &["--size"]), Err(CliError::MissingValue("--size"))));
+}
+
+#[test]
+fn app_flags_come_through_as_app_args() {
+    let cli = Cli::new("test", "Test clock")
+        .switch("--seconds", "Show the seconds")
+        .option("--face", "NAME", "Clock face");
+
+    let (common, app) = parse(&cli, &["--seconds", "--stats", "--face", "roman", "--size=640x480", "--face=arabic"]).unwrap();
+
+    // the common ones are taken out
+    assert!(common.stats);
+    assert_eq!(common.size, Some((640, 480)));
+
+    assert!(app.flag("--seconds"));
+    assert!(app.flag("--face"));
+    assert_eq!(app.value("--face"), Some("arabic"), "the last one wins");
+    assert!(!app.flag("--stats"));
+    assert_eq!(app.value("--seconds"), None);
+}
@@ -0,0 +1,162 @@
+//! The single color convention: palettes are sRGB bytes, decoded to linear for clears (`u32_col_to_wgpu_col_linear`)
+//! and in shaders (color.wgsl), so that both end up as the same bytes on the sRGB targets.
+//!
+//! The rendering test needs a GPU, skipped when there's none
+
+mod common;
+
+use clockutils::{
+    headless::read_texture_rgba, preprocess_shader, srgb_u8_to_linear_f32, srgb_u8_to_linear_f64, u32_col_to_wgpu_col_linear,
+    linear_to_srgb_u8, lerp_u32_color, lerp_u32_color_oklab, gradient_u32_color, gradient_wgpu_color, rgba32, hsva32, hsla32, u32_col_to_hsv,
+    FullscreenQuadPipeline, SingleUniformBuffer, cast_struct_to_u8_slice, SURFACE_FORMAT
+};
+
+// the color comes in a uniform rather than a push constant, so that it runs on every adapter
+const QUAD_SHADER: &str = "
+#include \"color.wgsl\"
+
+@group(0) @binding(0)
+var<uniform> color: vec4<u32>; // only x, uniforms are 16 bytes at least
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return color_u32_to_vec4f(color.x);
+}
+";
+
+// a polar background, digits of the digital clock, a mid gray and the extremes
+const COLORS: [u32; 6] = [0x171738_FF, 0x0F52FF_FF, 0xFF4D00_FF, 0x808080_FF, 0x000000_FF, 0xFFFFFF_FF];
+
+const SIZE: u32 = 4;
+
+#[test]
+fn srgb_decoding() {
+    assert_eq!(srgb_u8_to_linear_f64(0),   0.0);
+    assert_eq!(srgb_u8_to_linear_f64(255), 1.0);
+    assert!((srgb_u8_to_linear_f64(128) - 0.2158605).abs() < 1e-6);
+    assert!((srgb_u8_to_linear_f32(10) - 10.0 / 255.0 / 12.92).abs() < 1e-7, "the linear segment near black");
+
+    assert!((1..=255).all(|value| srgb_u8_to_linear_f64(value) > srgb_u8_to_linear_f64(value - 1)));
+
+    // alpha isn't encoded
+    let color = u32_col_to_wgpu_col_linear(0x808080_80);
+    assert_eq!(color.r, srgb_u8_to_linear_f64(0x80));
+    assert_eq!(color.a, 128.0 / 255.0);
+}
+
+#[test]
+fn srgb_encoding_round_trips() {
+    assert!((0..=255).all(|value| linear_to_srgb_u8(srgb_u8_to_linear_f64(value)) == value));
+    assert_eq!(linear_to_srgb_u8(-1.0), 0);
+    assert_eq!(linear_to_srgb_u8(2.0), 255);
+}
+
+#[test]
+fn oklab_transitions() {
+    for (from, to) in [(0x000000_FF, 0xFFFFFF_FF), (0xFF0000_FF, 0x00FF00_80), (0x171738_FF, 0xCFFCFF_FF)] {
+        assert_eq!(lerp_u32_color_oklab(from, to, 0.0), from);
+        assert_eq!(lerp_u32_color_oklab(from, to, 1.0), to);
+    }
+
+    // grays stay gray, and the middle is perceptually halfway: darker than the bytes' average
+    let gray = lerp_u32_color_oklab(0x000000_FF, 0xFFFFFF_FF, 0.5);
+    assert_eq!(gray >> 24, (gray >> 16) & 0xFF);
+    assert_eq!(gray >> 24, (gray >> 8) & 0xFF);
+    assert!(gray >> 24 < lerp_u32_color(0x000000_FF, 0xFFFFFF_FF, 0.5) >> 24, "{:08X}", gray);
+
+    // the alpha is interpolated as it is
+    assert_eq!(lerp_u32_color_oklab(0xFF0000_00, 0xFF0000_FF, 0.5) & 0xFF, 127);
+}
+
+#[test]
+fn gradients_interpolate_between_stops() {
+    // night, dawn and day
+    let stops = [(0.0, 0x000020_FF), (0.5, 0xFF8000_FF), (1.0, 0x80C0FF_FF)];
+
+    // at the stops, their colors
+    for (position, color) in stops {
+        assert_eq!(gradient_u32_color(&stops, position), color);
+    }
+
+    // within a segment, only its two stops count
+    assert_eq!(gradient_u32_color(&stops, 0.25), lerp_u32_color(0x000020_FF, 0xFF8000_FF, 0.5));
+    assert_eq!(gradient_u32_color(&stops, 0.75), lerp_u32_color(0xFF8000_FF, 0x80C0FF_FF, 0.5));
+
+    // outside, the nearest end
+    assert_eq!(gradient_u32_color(&stops, -1.0), 0x000020_FF);
+    assert_eq!(gradient_u32_color(&stops, 2.0),  0x80C0FF_FF);
+    assert_eq!(gradient_u32_color(&[(0.3, 0x123456_78)], 0.9), 0x123456_78);
+
+    assert_eq!(gradient_wgpu_color(&stops, 1.0), wgpu::Color { r: 128.0 / 255.0, g: 192.0 / 255.0, b: 1.0, a: 1.0 });
+}
+
+#[test]
+fn hue_constructors() {
+    assert_eq!(hsva32(0.0,   1.0, 1.0, 255), rgba32(255, 0, 0, 255));
+    assert_eq!(hsva32(120.0, 1.0, 0.5, 128), rgba32(0, 128, 0, 128));
+    assert_eq!(hsva32(-120.0, 1.0, 1.0, 255), hsva32(240.0, 1.0, 1.0, 255));
+
+    // the same colors in HSL, full lightness is white whatever the hue
+    assert_eq!(hsla32(0.0,   1.0, 0.5,  255), rgba32(255, 0, 0, 255));
+    assert_eq!(hsla32(120.0, 1.0, 0.25, 128), rgba32(0, 128, 0, 128));
+    assert_eq!(hsla32(200.0, 0.7, 1.0,  255), rgba32(255, 255, 255, 255));
+    assert_eq!(hsla32(200.0, 0.0, 0.5,  255), rgba32(128, 128, 128, 255));
+
+    let (h, s, v) = u32_col_to_hsv(rgba32(0, 128, 255, 255));
+    assert!((h - 210.0).abs() < 0.5 && s == 1.0 && v == 1.0, "{:?}", (h, s, v));
+    assert_eq!(hsva32(h, s, v, 255), rgba32(0, 128, 255, 255));
+}
+
+#[test]
+fn includes_are_expanded() {
+    let source = preprocess_shader(QUAD_SHADER).unwrap();
+    assert!(!source.lines().any(|line| line.starts_with("#include")));
+    assert!(source.contains("fn color_u32_to_vec4f"));
+
+    assert!(preprocess_shader("#include \"missing.wgsl\"\n").unwrap_err().contains("missing.wgsl"));
+}
+
+#[test]
+fn cleared_background_matches_the_drawn_color() {
+    let Some((device, queue)) = common::gpu() else {
+        return;
+    };
+
+    let uniform = SingleUniformBuffer::new::<[u32; 4]>(&device, wgpu::ShaderStages::FRAGMENT);
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label:   None,
+        entries: &[ SingleUniformBuffer::default_layout_entry(0, &uniform) ]
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label:   None,
+        layout:  &layout,
+        entries: &[ uniform.get_entry(0) ]
+    });
+
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(preprocess_shader(QUAD_SHADER).unwrap().into())
+    });
+    let quad = FullscreenQuadPipeline::new(&device, &module, "fs_main", &[&layout], &[], SURFACE_FORMAT);
+
+    let (cleared, cleared_view) = common::readable_target(&device, (SIZE, SIZE), SURFACE_FORMAT);
+    let (drawn, drawn_view)     = common::readable_target(&device, (SIZE, SIZE), SURFACE_FORMAT);
+
+    for color in COLORS {
+        queue.write_buffer(&uniform.buffer, 0, cast_struct_to_u8_slice(&[color, 0, 0, 0]));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        common::render_pass(&mut encoder, &cleared_view, u32_col_to_wgpu_col_linear(color));
+        quad.draw(&mut common::render_pass(&mut encoder, &drawn_view, wgpu::Color::BLACK), &[&bind_group], &[]);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let background = read_texture_rgba(&cleared, &device, &queue).unwrap().get_pixel(SIZE / 2, SIZE / 2).0;
+        let foreground = read_texture_rgba(&drawn,   &device, &queue).unwrap().get_pixel(SIZE / 2, SIZE / 2).0;
+
+        let hex = color.to_be_bytes();
+        for channel in 0..3 {
+            assert!(background[channel].abs_diff(foreground[channel]) <= 1, "{:08X}: cleared {:?}, drawn {:?}", color, background, foreground);
+            assert!(background[channel].abs_diff(hex[channel]) <= 1, "{:08X}: cleared {:?}", color, background);
+        }
+    }
+}
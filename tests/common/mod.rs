@@ -0,0 +1,84 @@
+//! GPU setup shared by the tests, `mod common;` in those that need it.
+//!
+//! The devices get WebGL2's limits (as far as the adapter's resolution goes), so that whatever passes here runs
+//! on the web as well. Without a fitting adapter the helpers say so and give `None`, the test is skipped then
+#![allow(dead_code)] // every test uses its own share of it
+
+/// The default adapter, `None` on machines without a GPU (or a software fallback)
+pub fn adapter() -> Option<wgpu::Adapter> {
+    pollster::block_on(wgpu::Instance::default().request_adapter(&wgpu::RequestAdapterOptions::default()))
+}
+
+/// A device with `features` on WebGL2's limits, push constants get 64 bytes
+pub fn device(adapter: &wgpu::Adapter, features: wgpu::Features) -> (wgpu::Device, wgpu::Queue) {
+    device_with_limits(adapter, features, wgpu::Limits::downlevel_webgl2_defaults())
+}
+
+/// As `device`, on other limits than WebGL2's
+pub fn device_with_limits(adapter: &wgpu::Adapter, features: wgpu::Features, limits: wgpu::Limits) -> (wgpu::Device, wgpu::Queue) {
+    let mut limits = limits.using_resolution(adapter.limits());
+    if features.contains(wgpu::Features::PUSH_CONSTANTS) {
+        limits.max_push_constant_size = 64;
+    }
+
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor { label: None, features, limits }, None)).unwrap()
+}
+
+/// The default adapter if it has `features`, `None` (and the test is skipped) otherwise
+pub fn adapter_with(features: wgpu::Features) -> Option<wgpu::Adapter> {
+    let adapter = adapter().filter(|adapter| adapter.features().contains(features));
+    match adapter {
+        None if features.is_empty() => eprintln!("No adapter, skipped"),
+        None                        => eprintln!("No adapter with {:?}, skipped", features),
+        Some(_)                     => ()
+    }
+
+    adapter
+}
+
+/// For tests that set up the GPU themselves (e.g. through a clock), `if common::skip_without_gpu() { return; }`
+pub fn skip_without_gpu() -> bool {
+    adapter_with(wgpu::Features::empty()).is_none()
+}
+
+/// A device without any features, `None` without an adapter
+pub fn gpu() -> Option<(wgpu::Device, wgpu::Queue)> {
+    adapter_with(wgpu::Features::empty()).map(|adapter| device(&adapter, wgpu::Features::empty()))
+}
+
+/// A device with push constants, `None` without an adapter that supports them
+pub fn gpu_with_push_constants() -> Option<(wgpu::Device, wgpu::Queue)> {
+    adapter_with(wgpu::Features::PUSH_CONSTANTS).map(|adapter| device(&adapter, wgpu::Features::PUSH_CONSTANTS))
+}
+
+/// A texture to render into and read back with `headless::read_texture_rgba`, and its view
+pub fn readable_target(device: &wgpu::Device, (width, height): (u32, u32), format: wgpu::TextureFormat) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label:           None,
+        size:            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count:    1,
+        dimension:       wgpu::TextureDimension::D2,
+        format,
+        usage:           wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats:    &[]
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    (texture, view)
+}
+
+/// A pass drawing into `view`, cleared to `clear` first
+pub fn render_pass<'a>(encoder: &'a mut wgpu::CommandEncoder, view: &'a wgpu::TextureView, clear: wgpu::Color) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label:                    None,
+        depth_stencil_attachment: None,
+        timestamp_writes:         None,
+        occlusion_query_set:      None,
+        color_attachments:        &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops:            wgpu::Operations { load: wgpu::LoadOp::Clear(clear), store: wgpu::StoreOp::Store }
+        })]
+    })
+}
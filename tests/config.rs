@@ -0,0 +1,97 @@
+//! Configuration files: written and read back, missing, or broken, in a folder of the target directory
+
+use clockutils::config::{ClockConfig, WindowGeometry};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    seconds: bool,
+    face:    String
+}
+
+/// A fresh file path for each test, in a folder that doesn't exist yet
+fn path(name: &str) -> PathBuf {
+    let folder = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("config").join(name);
+    let _ = std::fs::remove_dir_all(&folder);
+
+    folder.join("clock.toml")
+}
+
+/// The warnings logged, to tell a broken file from a missing one
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct WarningLogger;
+
+impl log::Log for WarningLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            WARNINGS.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn warnings_about(path: &std::path::Path) -> usize {
+    let _ = log::set_logger(&WarningLogger).map(|_| log::set_max_level(log::LevelFilter::Warn));
+    WARNINGS.lock().unwrap().iter().filter(|warning| warning.contains(&path.display().to_string())).count()
+}
+
+#[test]
+fn saved_configs_load_back() {
+    let path = path("round_trip");
+
+    let mut config = ClockConfig::<Settings>::load_from(path.clone());
+    config.window   = WindowGeometry { size: Some((640, 480)), position: Some((-20, 35)) };
+    config.settings = Settings { seconds: true, face: "roman".into() };
+    config.extras.insert("hue".into(), toml::Value::Float(0.25));
+
+    // the folder is created along the way
+    config.save().unwrap();
+
+    let loaded = ClockConfig::<Settings>::load_from(path);
+    assert_eq!(loaded.window.size, Some((640, 480)));
+    assert_eq!(loaded.window.position, Some((-20, 35)));
+    assert_eq!(loaded.settings, config.settings);
+    assert_eq!(loaded.extras, config.extras);
+}
+
+#[test]
+fn missing_files_give_the_defaults() {
+    let path = path("missing");
+    warnings_about(&path);
+
+    let config = ClockConfig::<Settings>::load_from(path.clone());
+    assert_eq!(config.settings, Settings::default());
+    assert_eq!(config.window.size, None);
+    assert!(config.extras.is_empty());
+
+    // quietly
+    assert_eq!(warnings_about(&path), 0);
+    assert!(!path.exists());
+}
+
+#[test]
+fn malformed_files_give_the_defaults_with_a_warning() {
+    let path = path("malformed");
+    warnings_about(&path);
+
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "[settings\nseconds = yes").unwrap();
+
+    let config = ClockConfig::<Settings>::load_from(path.clone());
+    assert_eq!(config.settings, Settings::default());
+    assert_eq!(warnings_about(&path), 1);
+
+    // and saving replaces the broken file
+    config.save().unwrap();
+    assert_eq!(ClockConfig::<Settings>::load_from(path.clone()).settings, Settings::default());
+    assert_eq!(warnings_about(&path), 1);
+}
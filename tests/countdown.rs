@@ -0,0 +1,86 @@
+//! The mechanical counter's countdown: parsing `--countdown`, the time left on the wheels, and the colors running out of it
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta};
+use clockutils::clocks::mcounter::{
+    parse_countdown, urgency_color, Countdown, CountdownState, FLASH_SECONDS, URGENCY_RED, URGENCY_YELLOW, WHEEL_COLOR
+};
+
+fn start() -> DateTime<FixedOffset> {
+    NaiveDate::from_ymd_opt(2024, 3, 15).unwrap().and_hms_opt(10, 8, 42).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(0).unwrap()).unwrap()
+}
+
+fn after(millis: i64) -> DateTime<FixedOffset> {
+    start() + TimeDelta::milliseconds(millis)
+}
+
+fn angle(digit: f32) -> f32 {
+    digit * 0.1 * std::f32::consts::TAU
+}
+
+#[test]
+fn durations_are_parsed() {
+    assert_eq!(parse_countdown("00:05:00"), Some(TimeDelta::minutes(5)));
+    assert_eq!(parse_countdown("1:02:03"),  Some(TimeDelta::seconds(3723)));
+    assert_eq!(parse_countdown("99:59:59"), Some(TimeDelta::seconds(359999)));
+
+    for invalid in ["00:00:00", "100:00:00", "00:60:00", "00:00:60", "05:00", "-1:00:00", "a:b:c", ""] {
+        assert_eq!(parse_countdown(invalid), None, "{}", invalid);
+    }
+}
+
+#[test]
+fn countdowns_run_flash_and_stop() {
+    let countdown = Countdown::new(TimeDelta::seconds(90), false, &start());
+
+    assert_eq!(countdown.state(&after(0)),      CountdownState::Running(TimeDelta::seconds(90)));
+    assert_eq!(countdown.state(&after(89_500)), CountdownState::Running(TimeDelta::milliseconds(500)));
+    assert_eq!(countdown.state(&after(91_000)), CountdownState::Flashing(TimeDelta::seconds(1)));
+    assert_eq!(countdown.state(&after((90 + FLASH_SECONDS) * 1000)), CountdownState::Stopped);
+    assert_eq!(countdown.until_next_change(&after(200_000)), None);
+}
+
+#[test]
+fn looping_countdowns_start_over() {
+    let countdown = Countdown::new(TimeDelta::seconds(90), true, &start());
+    let round     = (90 + FLASH_SECONDS) * 1000;
+
+    assert_eq!(countdown.state(&after(round)),          CountdownState::Running(TimeDelta::seconds(90)));
+    assert_eq!(countdown.state(&after(round * 2 + 10)), CountdownState::Running(TimeDelta::milliseconds(89_990)));
+}
+
+#[test]
+fn wheels_turn_backwards() {
+    let countdown = Countdown::new(TimeDelta::seconds(90), false, &start());
+
+    // 00:01:30 at the start, without turning in from 00:01:31
+    assert_eq!(countdown.wheel_angles(&after(0)), [0.0, 0.0, 0.0, angle(1.0), angle(3.0), 0.0]);
+
+    // 00:01:30 -> 00:01:29, halfway through the turn: the last wheel goes from 0 to -1 rather than to 9
+    let angles = countdown.wheel_angles(&after(1_250));
+    assert!((angles[5] - angle(-0.5)).abs() < 1e-5, "{:?}", angles);
+    assert!((angles[4] - angle(2.5)).abs() < 1e-5,  "{:?}", angles);
+
+    // standing at 00:01:29 for the rest of the second
+    assert_eq!(countdown.wheel_angles(&after(1_800)), [0.0, 0.0, 0.0, angle(1.0), angle(2.0), angle(9.0)]);
+
+    // zero stays put
+    assert_eq!(countdown.wheel_angles(&after(95_000)), [0.0; 6]);
+}
+
+#[test]
+fn colors_turn_urgent() {
+    let running = |seconds: f64| CountdownState::Running(TimeDelta::milliseconds((seconds * 1000.0) as i64));
+
+    assert_eq!(urgency_color(running(61.0)), WHEEL_COLOR);
+    assert_eq!(urgency_color(running(30.0)), URGENCY_YELLOW);
+    assert_eq!(urgency_color(CountdownState::Flashing(TimeDelta::zero())), URGENCY_RED);
+    assert_eq!(urgency_color(CountdownState::Stopped), WHEEL_COLOR);
+
+    // pulsing over the last seconds: dimmer than red at times, never brighter, always opaque
+    let red = |color: u32| color >> 24;
+    let pulse: Vec<u32> = (0..10).map(|step| urgency_color(running(5.0 + step as f64 * 0.01))).collect();
+
+    assert!(pulse.iter().any(|&color| red(color) < 0xC0), "{:08X?}", pulse);
+    assert!(pulse.iter().all(|&color| red(color) <= 0xFF && color & 0xFF == 0xFF), "{:08X?}", pulse);
+}
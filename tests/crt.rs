@@ -0,0 +1,96 @@
+//! The CRT look: scanlines repeating at their spacing, the color guns shifted apart, nothing changed without either.
+//!
+//! Needs a GPU (any backend, the parameters go via a uniform), skipped when no adapter is found
+
+mod common;
+
+use clockutils::{headless::read_texture_rgba, CrtParams, CrtPass, FullscreenQuadPipeline, RenderTexture};
+
+const SIZE: u32 = 64;
+
+// flat gray, for the scanlines
+const GRAY_SHADER: &str = "
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return vec4f(0.5, 0.5, 0.5, 1.0);
+}
+";
+
+// a white column two pixels wide in the middle, on black
+const COLUMN_SHADER: &str = "
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return select(vec4f(0.0, 0.0, 0.0, 1.0), vec4f(1.0), abs(uv.x - 0.5) < 1.0 / 64.0);
+}
+";
+
+const NEUTRAL: CrtParams = CrtParams { scanline_spacing: 4.0, scanline_intensity: 0.0, blur_amount: 0.0, rgb_shift: 0.0 };
+
+/// The scene drawn by `shader` through the CRT pass, `None` without an adapter
+fn render(shader: &str, params: &CrtParams) -> Option<image::RgbaImage> {
+    let (device, queue) = common::gpu()?;
+
+    // linear, so that the values read back are the shader's
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+
+    let scene_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(shader.into())
+    });
+    let scene = FullscreenQuadPipeline::new(&device, &scene_shader, "fs_main", &[], &[], format);
+
+    let source = RenderTexture::new((SIZE, SIZE), format, true, &device);
+    let (output, output_view) = common::readable_target(&device, (SIZE, SIZE), format);
+
+    let crt = CrtPass::new(&device, &queue, format);
+    crt.set_params(&queue, params);
+    let source_bindgroup = crt.source_bindgroup(&source, &device);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut rpass = common::render_pass(&mut encoder, &source.view, wgpu::Color::BLACK);
+        scene.draw(&mut rpass, &[], &[]);
+    }
+    crt.apply(&mut encoder, &source_bindgroup, &output_view);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    Some(read_texture_rgba(&output, &device, &queue).unwrap())
+}
+
+#[test]
+fn neutral_params_keep_the_frame() {
+    let Some(image) = render(GRAY_SHADER, &NEUTRAL) else { return };
+
+    for pixel in image.pixels() {
+        assert!(pixel.0[0].abs_diff(128) <= 1, "{:?}", pixel.0);
+        assert_eq!(pixel.0[3], 255);
+    }
+}
+
+#[test]
+fn scanlines_repeat_at_their_spacing() {
+    let Some(image) = render(GRAY_SHADER, &CrtParams { scanline_intensity: 1.0, ..NEUTRAL }) else { return };
+
+    let row = |y| image.get_pixel(SIZE / 2, y).0[0];
+
+    // the scanlines' middles are bright, the rows halfway to the next one dark
+    assert!(row(0) > row(1) + 40, "{} vs {}", row(0), row(1));
+    assert!(row(3) > row(2) + 40, "{} vs {}", row(3), row(2));
+
+    for y in 0..SIZE - 4 {
+        assert!(row(y).abs_diff(row(y + 4)) <= 1, "Rows {} and {} differ", y, y + 4);
+    }
+}
+
+#[test]
+fn red_and_blue_move_apart() {
+    let Some(image) = render(COLUMN_SHADER, &CrtParams { rgb_shift: 2.0, ..NEUTRAL }) else { return };
+
+    let pixel = |x| image.get_pixel(x, SIZE / 2).0;
+
+    // the column covers 31 and 32, red is seen two pixels to the right, blue two to the left, only green stays
+    assert_eq!(pixel(31), [0, 255, 0, 255]);
+    assert_eq!(pixel(34), [255, 0, 0, 255]);
+    assert_eq!(pixel(29), [0, 0, 255, 255]);
+    assert_eq!(pixel(35), [0, 0, 0, 255]);
+}
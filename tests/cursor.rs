@@ -0,0 +1,26 @@
+//! The cursor's position over the window, and the moves derived from it across leaving and re-entering
+
+use clockutils::CursorTracker;
+
+#[test]
+fn moves_are_measured_from_the_last_position() {
+    let mut cursor = CursorTracker::new();
+    assert_eq!(cursor.position(), None);
+
+    assert_eq!(cursor.moved((100.0, 50.0)), (0.0, 0.0));
+    assert_eq!(cursor.moved((110.0, 45.0)), (10.0, -5.0));
+    assert_eq!(cursor.position(), Some((110.0, 45.0)));
+}
+
+#[test]
+fn reentering_is_not_a_jump() {
+    let mut cursor = CursorTracker::new();
+    cursor.moved((5.0, 5.0));
+
+    // out on the left, back in on the right
+    cursor.left();
+    assert_eq!(cursor.position(), None);
+
+    assert_eq!(cursor.moved((790.0, 300.0)), (0.0, 0.0));
+    assert_eq!(cursor.moved((789.0, 300.0)), (-1.0, 0.0));
+}
@@ -0,0 +1,157 @@
+//! The digital clock's LED bit patterns for fixed times, against the clock layout's islands
+
+use chrono::{DateTime, FixedOffset, NaiveDate, Weekday};
+use clockutils::clocks::digital::{
+    calculate_clock_data, format_animation_speed, parse_first_day, parse_glow_blend, segment_transition, step_animation_speed,
+    transition_duration_ms, ColonStyle, GlowBlend, IndicatorLayout, INDICATOR_LAYOUT
+};
+
+const AM:    u32 = 1 << 7;
+const PM:    u32 = 1 << 8;
+const COLON: u32 = 1 << 9;
+
+/// The given day of March 2024 (the 10th is a Sunday) and time, UTC
+fn at(day: u32, hour: u32, minute: u32, millis: u32) -> DateTime<FixedOffset> {
+    NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+        .and_hms_milli_opt(hour, minute, 0, millis).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(0).unwrap()).unwrap()
+}
+
+/// Both flagsets, on a Sunday with the colon off
+fn flags(hour: u32, minute: u32, hr12: bool) -> [u32; 2] {
+    calculate_clock_data(&at(10, hour, minute, 0), hr12, 0, ColonStyle::Blink, &INDICATOR_LAYOUT).flagset
+}
+
+#[test]
+fn bundled_layout_is_valid() {
+    assert_eq!(INDICATOR_LAYOUT.validate(), Ok(()));
+    assert_eq!(INDICATOR_LAYOUT.with_first_day(Weekday::Mon).validate(), Ok(()));
+}
+
+#[test]
+fn weekdays_light_their_labels() {
+    // SUN MON TUE WED THU FRI SAT
+    for (offset, weekday) in [Weekday::Sun, Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat].into_iter().enumerate() {
+        let now = at(10 + offset as u32, 10, 8, 0);
+        assert_eq!(chrono::Datelike::weekday(&now), weekday);
+
+        let data = calculate_clock_data(&now, false, 0, ColonStyle::Blink, &INDICATOR_LAYOUT);
+        assert_eq!(data.flagset[1], 1 << offset, "{}", weekday);
+    }
+}
+
+#[test]
+fn first_day_shifts_the_labels() {
+    // MON TUE WED THU FRI SAT SUN
+    let layout = INDICATOR_LAYOUT.with_first_day(Weekday::Mon);
+
+    for (offset, weekday) in [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun].into_iter().enumerate() {
+        let data = calculate_clock_data(&at(11 + offset as u32, 10, 8, 0), false, 0, ColonStyle::Blink, &layout);
+        assert_eq!(data.flagset[1], 1 << offset, "{}", weekday);
+    }
+}
+
+#[test]
+fn first_day_values() {
+    assert_eq!(parse_first_day("monday"), Some(Weekday::Mon));
+    assert_eq!(parse_first_day("Sunday"), Some(Weekday::Sun));
+    assert_eq!(parse_first_day("wednesday"), None);
+}
+
+#[test]
+fn glow_blend_values() {
+    assert_eq!(parse_glow_blend("add"), Some(GlowBlend::Add));
+    assert_eq!(parse_glow_blend("Screen"), Some(GlowBlend::Screen));
+    assert_eq!(parse_glow_blend("multiply"), None);
+
+    // blend_mode of composite.wgsl
+    assert_eq!(GlowBlend::default().shader_value(), 0);
+    assert_eq!(GlowBlend::Screen.shader_value(), 1);
+}
+
+#[test]
+fn around_midnight() {
+    // 00:05, the hour tens digit is blank
+    assert_eq!(flags(0, 5, false), [0x7bdfb80, 1]);
+    // 12:05 AM
+    assert_eq!(flags(0, 5, true),  [0x7bdedc4, 1 | AM]);
+}
+
+#[test]
+fn around_noon() {
+    assert_eq!(flags(12, 7, false), [0xa9dedc4, 1]);
+    // 12:07 PM, not 00:07 PM
+    assert_eq!(flags(12, 7, true),  [0xa9dedc4, 1 | PM]);
+    // the minute before is still AM
+    assert_eq!(flags(11, 59, true), [0xfaf6244, 1 | AM]);
+}
+
+#[test]
+fn evening() {
+    assert_eq!(flags(23, 59, false), [0xfaf6edb, 1]);
+    // 11:59 PM
+    assert_eq!(flags(23, 59, true),  [0xfaf6244, 1 | PM]);
+}
+
+#[test]
+fn colon_blinks_in_the_second_half() {
+    let data = calculate_clock_data(&at(10, 10, 8, 750), false, 0, ColonStyle::Blink, &INDICATOR_LAYOUT);
+    assert_eq!(data.flagset[1], 1 | COLON);
+}
+
+#[test]
+fn invalid_layouts_are_rejected() {
+    let duplicate = IndicatorLayout { pm_bit: 7, ..INDICATOR_LAYOUT };
+    assert!(duplicate.validate().unwrap_err().contains("PM's bit 7 is already taken"));
+
+    let out_of_range = IndicatorLayout { weekday_bits: [0, 1, 2, 3, 4, 5, 32], ..INDICATOR_LAYOUT };
+    assert!(out_of_range.validate().unwrap_err().contains("out of the flagset"));
+
+    let moved_colon = IndicatorLayout { colon_bit: 10, ..INDICATOR_LAYOUT };
+    assert!(moved_colon.validate().unwrap_err().contains("forward.wgsl"));
+}
+
+#[test]
+fn no_transition_by_default() {
+    let data = calculate_clock_data(&at(10, 10, 8, 0), false, 0, ColonStyle::Blink, &INDICATOR_LAYOUT);
+    assert_eq!(data.previous_flagset, data.flagset);
+    assert_eq!(data.transition, 1.0);
+}
+
+#[test]
+fn segments_fade_into_the_minute() {
+    // 50ms into 10:09, a quarter of the way at the normal speed
+    let (before, progress) = segment_transition(&at(10, 10, 9, 50), 1.0).unwrap();
+    assert_eq!(before, at(10, 10, 8, 0) + chrono::TimeDelta::nanoseconds(59_999_999_999));
+    assert!((progress - 0.25).abs() < 1e-4);
+
+    // the LEDs faded from are the previous minute's
+    assert_eq!(calculate_clock_data(&before, false, 0, ColonStyle::Blink, &INDICATOR_LAYOUT).flagset[0], flags(10, 8, false)[0]);
+
+    // twice as fast, halfway
+    let (_, progress) = segment_transition(&at(10, 10, 9, 50), 2.0).unwrap();
+    assert!((progress - 0.5).abs() < 1e-4);
+
+    // over, or off altogether
+    assert!(segment_transition(&at(10, 10, 9, 200), 1.0).is_none());
+    assert!(segment_transition(&at(10, 10, 9, 0), 0.0).is_none());
+}
+
+#[test]
+fn animation_speeds() {
+    assert_eq!(transition_duration_ms(1.0), Some(200.0));
+    assert_eq!(transition_duration_ms(2.0), Some(100.0));
+    assert_eq!(transition_duration_ms(0.0), None);
+
+    assert_eq!(step_animation_speed(1.0, true),  1.5);
+    assert_eq!(step_animation_speed(1.0, false), 0.5);
+    // stays at the ends, off is the slowest
+    assert_eq!(step_animation_speed(0.0, false), 0.0);
+    assert_eq!(step_animation_speed(4.0, true),  4.0);
+    // speeds in between snap to the steps
+    assert_eq!(step_animation_speed(1.2, true),  1.5);
+    assert_eq!(step_animation_speed(1.2, false), 1.0);
+
+    assert_eq!(format_animation_speed(1.0),  "1.0x");
+    assert_eq!(format_animation_speed(0.25), "0.25x");
+}
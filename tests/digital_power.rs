@@ -0,0 +1,55 @@
+//! The digital clock's power estimate: lit LEDs counted off the flagsets, and their average over the last day
+
+use clockutils::clocks::digital::{lit_segments, power_mw, PowerHistory, POWER_HISTORY_SECONDS, SEGMENT_POWER_MW};
+
+#[test]
+fn lit_leds_are_counted_across_both_flagsets() {
+    assert_eq!(lit_segments([0, 0]), 0);
+    // "8" in the hour ones, a weekday and the colon
+    assert_eq!(lit_segments([0b1111111 << 7, (1 << 3) | (1 << 9)]), 9);
+    assert_eq!(power_mw(25), 25.0 * SEGMENT_POWER_MW);
+}
+
+#[test]
+fn average_of_the_recorded_seconds() {
+    let mut history = PowerHistory::new();
+    assert_eq!(history.average_mw(), None);
+    assert_eq!(history.latest(), None);
+
+    history.record(1000, 10);
+    history.record(1000, 30); // the same second again
+    history.record(1001, 20);
+
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.latest(), Some(20));
+    assert_eq!(history.average_mw(), Some(15.0 * SEGMENT_POWER_MW));
+
+    // skipped seconds count as the one they were skipped to
+    history.record(1004, 4);
+    assert_eq!(history.len(), 5);
+    assert_eq!(history.average_mw(), Some(((10 + 20 + 4 * 3) as f32 / 5.0) * SEGMENT_POWER_MW));
+
+    // back in time starts over
+    history.record(900, 8);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.average_mw(), Some(8.0 * SEGMENT_POWER_MW));
+}
+
+#[test]
+fn seconds_older_than_a_day_roll_off() {
+    let day = POWER_HISTORY_SECONDS as i64;
+    let mut history = PowerHistory::new();
+
+    history.record(0, 30);
+    history.record(day - 1, 30);
+    assert_eq!(history.len(), POWER_HISTORY_SECONDS);
+
+    history.record(day - 1 + day / 2, 10);
+    assert_eq!(history.len(), POWER_HISTORY_SECONDS);
+    assert_eq!(history.latest(), Some(10));
+    assert_eq!(history.average_mw(), Some(20.0 * SEGMENT_POWER_MW));
+
+    // a gap of more than a day replaces all of it
+    history.record(10 * day, 2);
+    assert_eq!(history.average_mw(), Some(2.0 * SEGMENT_POWER_MW));
+}
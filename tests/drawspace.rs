@@ -0,0 +1,113 @@
+//! Fitting the drawing space into the window, snapping positions onto the pixel grid, its uniform buffer,
+//! and a drawing space per monitor of a window spanning several
+
+mod common;
+
+use glam::{Vec2, Vec4};
+use clockutils::{Drawspace2D, DrawspaceScales, FitMode, MultiMonitorScales};
+
+/// Pixel coordinates of a drawing space position, counted from the window's bottom-left corner
+fn to_pixels(dscales: &DrawspaceScales, pos: Vec2) -> Vec2 {
+    pos * dscales.density + dscales.resolution * 0.5
+}
+
+fn assert_fraction(pixels: Vec2, fraction: f32) {
+    let off = (pixels - pixels.floor() - Vec2::splat(fraction)).abs();
+    assert!(off.max_element() < 1e-3, "{} is not at {} of a pixel", pixels, fraction);
+}
+
+#[test]
+fn snaps_onto_pixel_boundaries() {
+    // even and odd sizes, the latter put the origin in the middle of a pixel
+    for resolution in [Vec2::new(1280.0, 720.0), Vec2::new(1281.0, 721.0)] {
+        let dscales = DrawspaceScales::new(resolution, Vec2::new(2.5, 1.40625));
+
+        for pos in [Vec2::new(-2.095772, -0.643742), Vec2::new(0.0, 0.0), Vec2::new(1.3, 0.77)] {
+            let snapped = dscales.snap_to_pixel_grid(pos);
+
+            assert_fraction(to_pixels(&dscales, snapped), 0.0);
+            // never more than half a pixel away
+            assert!((to_pixels(&dscales, snapped) - to_pixels(&dscales, pos)).abs().max_element() <= 0.5 + 1e-3);
+        }
+    }
+}
+
+#[test]
+fn snaps_onto_pixel_centers() {
+    for resolution in [Vec2::new(1280.0, 720.0), Vec2::new(1281.0, 721.0)] {
+        let dscales = DrawspaceScales::new(resolution, Vec2::new(16.0, 16.0));
+
+        for pos in [Vec2::new(-13.0, 5.2), Vec2::new(0.0, 0.0), Vec2::new(0.01, -0.01)] {
+            assert_fraction(to_pixels(&dscales, dscales.snap_to_half_pixel_grid(pos)), 0.5);
+        }
+    }
+}
+
+#[test]
+fn positions_on_the_grid_stay() {
+    let dscales = DrawspaceScales::new(Vec2::new(800.0, 600.0), Vec2::new(4.0, 3.0));
+    let on_grid = Vec2::new(37.0, -12.0) / dscales.density;
+
+    assert!(dscales.snap_to_pixel_grid(on_grid).abs_diff_eq(on_grid, 1e-6));
+}
+
+#[test]
+fn contain_shows_all_of_the_extent() {
+    // wider than the extent: its height fills the window
+    let dscales = DrawspaceScales::with_fit(Vec2::new(1600.0, 600.0), Vec2::new(4.0, 3.0), FitMode::Contain);
+    assert_eq!(dscales.density, 100.0);
+    assert_eq!(dscales, DrawspaceScales::new(Vec2::new(1600.0, 600.0), Vec2::new(4.0, 3.0)));
+}
+
+#[test]
+fn cover_fills_the_window() {
+    // wider than the extent: its width fills the window, the top and bottom are cut off
+    let dscales = DrawspaceScales::with_fit(Vec2::new(1600.0, 600.0), Vec2::new(4.0, 3.0), FitMode::Cover);
+    assert_eq!(dscales.density, 200.0);
+    assert!((dscales.scale * Vec2::new(4.0, 3.0)).abs_diff_eq(Vec2::new(1.0, 2.0), 1e-6));
+
+    // the same aspect ratio either way
+    assert_eq!(
+        DrawspaceScales::with_fit(Vec2::new(800.0, 600.0), Vec2::new(4.0, 3.0), FitMode::Cover).density,
+        DrawspaceScales::new(Vec2::new(800.0, 600.0), Vec2::new(4.0, 3.0)).density
+    );
+}
+
+#[test]
+fn drawspace_uploads_the_scales() {
+    let Some((device, queue)) = common::gpu() else {
+        return;
+    };
+
+    let extent = Vec2::new(2.5, 1.40625);
+    let mut drawspace = Drawspace2D::new(&device, extent, FitMode::Contain);
+    drawspace.resize(&queue, 1281, 721);
+
+    // the same scales the clocks wrote themselves before
+    assert_eq!(*drawspace.scales(), DrawspaceScales::new(Vec2::new(1281.0, 721.0), extent));
+    assert_eq!(drawspace.extent(), extent);
+}
+
+#[test]
+fn every_monitor_fits_its_own_clock() {
+    // a landscape monitor with a portrait one to its right, lower down
+    let extent   = Vec2::new(16.0, 16.0);
+    let monitors = MultiMonitorScales::new(vec![(0, 0, 1920, 1080), (1920, 200, 1080, 1920)], extent);
+
+    assert_eq!(monitors.len(), 2);
+    assert_eq!(monitors.total_resolution, Vec2::new(3000.0, 2120.0));
+    assert_eq!(*monitors.for_monitor(0), DrawspaceScales::new(Vec2::new(1920.0, 1080.0), extent));
+    assert_eq!(*monitors.for_monitor(1), DrawspaceScales::new(Vec2::new(1080.0, 1920.0), extent));
+
+    assert_eq!(monitors.viewport(1, monitors.total_resolution), Vec4::new(1920.0, 200.0, 1080.0, 1920.0));
+    // a window the window manager made smaller
+    assert_eq!(monitors.viewport(1, monitors.total_resolution * 0.5), Vec4::new(960.0, 100.0, 540.0, 960.0));
+}
+
+#[test]
+fn no_monitors_no_scales() {
+    let monitors = MultiMonitorScales::new(Vec::new(), Vec2::ONE);
+
+    assert!(monitors.is_empty());
+    assert_eq!(monitors.total_resolution, Vec2::ZERO);
+}
@@ -0,0 +1,49 @@
+//! The split-flap clock's flaps: which ones fall at a given time, and how far they are
+
+use std::f32::consts::PI;
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta};
+use clockutils::clocks::flip::{calc_slot_flips, flip_ease, is_flipping, until_next_flip, FLIP_DURATION};
+
+fn at(hour: u32, minute: u32, second: u32, nanos: i64) -> DateTime<FixedOffset> {
+    NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+        .and_hms_opt(hour, minute, second).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(0).unwrap()).unwrap() + TimeDelta::nanoseconds(nanos)
+}
+
+#[test]
+fn changed_digits_flip_once() {
+    let flips = calc_slot_flips(&(at(23, 59, 59, 0) + TimeDelta::seconds(1) + TimeDelta::nanoseconds(FLIP_DURATION as i64 / 2)));
+
+    // 23:59:59 -> 00:00:00, every slot flips once, straight to the new digit
+    for (flip, (from, to)) in flips.iter().zip([(2, 0), (3, 0), (5, 0), (9, 0), (5, 0), (9, 0)]) {
+        assert_eq!((flip.from, flip.to), (from, to));
+        assert!(flip.angle > 0.0 && flip.angle < PI, "{:?}", flip);
+    }
+
+    // only the seconds' ones change, the rest lie still
+    let flips = calc_slot_flips(&at(10, 8, 42, 100_000_000));
+    assert_eq!((flips[5].from, flips[5].to), (1, 2));
+    assert!(flips[..5].iter().all(|flip| flip.from == flip.to && flip.angle == PI), "{:?}", flips);
+}
+
+#[test]
+fn flaps_rest_after_falling() {
+    let now = at(10, 8, 42, FLIP_DURATION as i64 + 1);
+    assert!(!is_flipping(&now));
+    assert!(calc_slot_flips(&now).iter().all(|flip| flip.from == flip.to && flip.angle == PI));
+    assert_eq!(calc_slot_flips(&now)[5].to, 2);
+
+    assert_eq!(until_next_flip(&at(10, 8, 42, 250_000_000)), TimeDelta::milliseconds(750));
+}
+
+#[test]
+fn flaps_fall_fast_then_slow() {
+    assert_eq!(flip_ease(0.0), 0.0);
+    assert_eq!(flip_ease(1.0), 1.0);
+
+    // most of the way in the first half, always onwards
+    assert!(flip_ease(0.5) > 0.8);
+    let steps: Vec<f32> = (0..=10).map(|step| flip_ease(step as f32 / 10.0)).collect();
+    assert!(steps.windows(2).all(|pair| pair[1] > pair[0]), "{:?}", steps);
+    assert!(steps[1] - steps[0] > steps[10] - steps[9]);
+}
@@ -0,0 +1,67 @@
+//! The library's fullscreen quad, drawn without a vertex buffer.
+//!
+//! Needs a GPU (any backend), skipped when no adapter is found
+
+mod common;
+
+use clockutils::{headless::read_texture_rgba, FullscreenQuadPipeline};
+
+// the UV coordinates as colors, plus a push constant in blue
+const FRAGMENT_SHADER: &str = "
+struct Params {
+    blue: f32
+}
+
+var<push_constant> params: Params;
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return vec4f(uv, params.blue, 1.0);
+}
+";
+
+const SIZE: u32 = 16;
+
+#[test]
+fn covers_the_target_with_uvs() {
+    let Some((device, queue)) = common::gpu_with_push_constants() else {
+        return;
+    };
+
+    // linear, so that the colors are the UVs as they are
+    let format = wgpu::TextureFormat::Bgra8Unorm;
+
+    let fragment = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(FRAGMENT_SHADER.into())
+    });
+
+    let quad = FullscreenQuadPipeline::new(
+        &device, &fragment, "fs_main",
+        &[],
+        &[ wgpu::PushConstantRange { stages: wgpu::ShaderStages::FRAGMENT, range: 0..4 } ],
+        format
+    );
+
+    let (target, view) = common::readable_target(&device, (SIZE, SIZE), format);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut rpass = common::render_pass(&mut encoder, &view, wgpu::Color::BLACK);
+
+        quad.draw(&mut rpass, &[], &1.0_f32.to_ne_bytes());
+    }
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let image = read_texture_rgba(&target, &device, &queue).unwrap();
+    let uv = |x: u32, y: u32| {
+        let [u, v, _, _] = image.get_pixel(x, y).0;
+        (u, v)
+    };
+
+    // every pixel is covered, u grows to the right and v downwards
+    assert!(image.pixels().all(|pixel| pixel.0[2] == 255));
+    assert!(uv(0, 0).0 < 16 && uv(0, 0).1 < 16);
+    assert!(uv(SIZE - 1, 0).0 > 240 && uv(SIZE - 1, 0).1 < 16);
+    assert!(uv(0, SIZE - 1).0 < 16 && uv(0, SIZE - 1).1 > 240);
+}
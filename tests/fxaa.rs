@@ -0,0 +1,70 @@
+//! FXAA over a hard edge: the stair steps get blended, the flat areas stay as they are.
+//!
+//! Needs a GPU, skipped when there's none. Only built with the `fxaa` feature
+#![cfg(feature = "fxaa")]
+
+mod common;
+
+use clockutils::{headless::read_texture_rgba, FullscreenQuadPipeline, FxaaPass, RenderTexture};
+
+// white below a shallow slope, no antialiasing of its own
+const EDGE_SHADER: &str = "
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return select(vec4f(0.0, 0.0, 0.0, 1.0), vec4f(1.0), uv.y > uv.x * 0.3 + 0.35);
+}
+";
+
+const SIZE: u32 = 64;
+
+fn is_blended(value: u8) -> bool {
+    value != 0 && value != 255
+}
+
+#[test]
+fn smooths_the_edge_only() {
+    let Some((device, queue)) = common::gpu() else {
+        return;
+    };
+
+    // linear, so that the blend is visible as is
+    let format = wgpu::TextureFormat::Bgra8Unorm;
+
+    let edge_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(EDGE_SHADER.into())
+    });
+    let edge = FullscreenQuadPipeline::new(&device, &edge_shader, "fs_main", &[], &[], format);
+
+    let source = RenderTexture::new((SIZE, SIZE), format, true, &device);
+    let (output, output_view) = common::readable_target(&device, (SIZE, SIZE), format);
+
+    let fxaa = FxaaPass::new(&device, format);
+    let source_bindgroup = fxaa.source_bindgroup(&source, &device);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    edge.draw(&mut common::render_pass(&mut encoder, &source.view, wgpu::Color::BLACK), &[], &[]);
+    fxaa.apply(&mut encoder, &source_bindgroup, &output_view);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let image = read_texture_rgba(&output, &device, &queue).unwrap();
+
+    // far from the edge, the colors are untouched
+    assert_eq!(image.get_pixel(2, 2).0, [0, 0, 0, 255]);
+    assert_eq!(image.get_pixel(SIZE - 3, SIZE - 3).0, [255, 255, 255, 255]);
+
+    // the edge is blended, and only around it
+    let mut blended = 0;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if !is_blended(pixel.0[0]) {
+            continue;
+        }
+
+        blended += 1;
+
+        let u = (x as f32 + 0.5) / SIZE as f32;
+        let edge_y = (u * 0.3 + 0.35) * SIZE as f32;
+        assert!((y as f32 + 0.5 - edge_y).abs() < 2.0, "Blended pixel at {}, {} is away from the edge", x, y);
+    }
+    assert!(blended >= SIZE as usize / 2, "Only {} blended pixels", blended);
+}
@@ -0,0 +1,69 @@
+//! God rays from a white sun on black: they stream out of it, within its reach, and not at all without intensity.
+//!
+//! Needs a GPU with push constants, skipped when there's none. Only built with the `god-rays` feature
+#![cfg(feature = "god-rays")]
+
+mod common;
+
+use clockutils::{headless::read_texture_rgba, FullscreenQuadPipeline, GodRaysPass, RenderTexture};
+
+// a white disk around the center, with a black cross over it for the rays to be missing behind
+const SUN_SHADER: &str = "
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    let sun   = distance(uv, vec2f(0.5)) < 0.05;
+    let cross = min(abs(uv.x - 0.5), abs(uv.y - 0.5)) < 0.01;
+    return select(vec4f(0.0, 0.0, 0.0, 1.0), vec4f(1.0), sun && !cross);
+}
+";
+
+const SIZE: u32 = 64;
+
+#[test]
+fn rays_stream_out_of_the_sun() {
+    let Some((device, queue)) = common::gpu_with_push_constants() else {
+        return;
+    };
+
+    let format = wgpu::TextureFormat::Bgra8Unorm;
+
+    let sun_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(SUN_SHADER.into())
+    });
+    let sun = FullscreenQuadPipeline::new(&device, &sun_shader, "fs_main", &[], &[], format);
+
+    let source = RenderTexture::new((SIZE, SIZE), format, true, &device);
+    let (output, output_view) = common::readable_target(&device, (SIZE, SIZE), format);
+
+    let god_rays = GodRaysPass::new(&device, format);
+    let source_bindgroup = god_rays.source_bindgroup(&source, &device);
+
+    let render = |intensity: f32| {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        sun.draw(&mut common::render_pass(&mut encoder, &source.view, wgpu::Color::BLACK), &[], &[]);
+        god_rays.apply(&mut encoder, &source_bindgroup, &output_view, glam::Vec2::splat(0.5), intensity, 0.95, 0.9);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        read_texture_rgba(&output, &device, &queue).unwrap()
+    };
+
+    let brightness = |image: &image::RgbaImage, x: u32, y: u32| image.get_pixel(x, y).0[0];
+
+    // on a diagonal just off the sun (its radius is 3.2 pixels), behind the cross, and far away
+    let (lit_x, lit_y)         = (SIZE / 2 + 4, SIZE / 2 + 4);
+    let (blocked_x, blocked_y) = (SIZE / 2 + 6, SIZE / 2);
+    let (far_x, far_y)         = (2, 2);
+
+    let without = render(0.0);
+    assert_eq!(brightness(&without, lit_x, lit_y), 0);
+    assert_eq!(brightness(&without, SIZE / 2 + 1, SIZE / 2 + 1), 255, "the sun is copied as is");
+
+    let with = render(4.0);
+    assert!(brightness(&with, lit_x, lit_y) > 0, "no rays next to the sun");
+    assert!(
+        brightness(&with, blocked_x, blocked_y) < brightness(&with, lit_x, lit_y),
+        "the cross doesn't block the rays"
+    );
+    assert_eq!(brightness(&with, far_x, far_y), 0, "rays beyond the sun's reach");
+}
@@ -0,0 +1,171 @@
+//! Golden-image regression tests.
+//!
+//! Every clock is rendered offscreen (via its `--snapshot` option) at a fixed date, time and resolution,
+//! then compared against the reference images in `tests/golden`.
+//! Rendering needs a GPU, which CI runners usually lack, so these only run when asked for:
+//!
+//! * `WGPU_CLOCKS_GOLDEN=1 cargo test --test golden` compares against the references,
+//!   on failure a diff image is written next to the test's temporary output
+//! * `WGPU_CLOCKS_BLESS=1 cargo test --test golden` (re)generates the references instead,
+//!   for when a change in the output is intended
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use image::{RgbaImage, imageops::FilterType};
+
+const SNAPSHOT_ARGS: &[&str] = &[
+    "--date",       "2024-03-15",
+    "--time",       "10:08:42",
+    "--time-scale", "0", // frozen, animations included
+    "--timezone",   "UTC"
+];
+
+/// Comparisons are done on images downsampled by this factor,
+/// which irons out the rasterization and filtering differences between GPUs
+const DOWNSAMPLE: u32 = 4;
+
+/// Largest per-channel difference (0-255) a pixel can have while still counting as equal
+const CHANNEL_TOLERANCE: u8 = 8;
+
+/// Fraction of the pixels allowed to differ beyond `CHANNEL_TOLERANCE`
+const MAX_DIFFERING_FRACTION: f64 = 0.005;
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn output_dir() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+fn snapshot(exe: &str, name: &str, size: &str) -> RgbaImage {
+    std::fs::create_dir_all(output_dir()).unwrap();
+    let path = output_dir().join(format!("{}.png", name));
+
+    let output = Command::new(exe)
+        .args(SNAPSHOT_ARGS)
+        .args(["--size", size, "--snapshot"])
+        .arg(&path)
+        .current_dir(env!("CARGO_MANIFEST_DIR")) // for the resources folder
+        .output()
+        .expect("Failed to launch the clock");
+
+    assert!(
+        output.status.success(),
+        "{} failed to render:\n{}", name, String::from_utf8_lossy(&output.stderr)
+    );
+
+    image::open(&path).expect("Failed to read the snapshot back").to_rgba8()
+}
+
+fn downsample(image: &RgbaImage) -> RgbaImage {
+    image::imageops::resize(image, image.width() / DOWNSAMPLE, image.height() / DOWNSAMPLE, FilterType::Triangle)
+}
+
+/// Returns the fraction of differing pixels, and an image highlighting them in red over a dimmed reference
+fn compare(actual: &RgbaImage, reference: &RgbaImage) -> (f64, RgbaImage) {
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+    let mut differing = 0_usize;
+
+    for ((a, r), d) in actual.pixels().zip(reference.pixels()).zip(diff.pixels_mut()) {
+        let delta = a.0.iter().zip(r.0.iter()).map(|(x, y)| x.abs_diff(*y)).max().unwrap();
+
+        if delta > CHANNEL_TOLERANCE {
+            differing += 1;
+            *d = image::Rgba([255, 0, 0, 255]);
+        } else {
+            *d = image::Rgba([r[0] / 4, r[1] / 4, r[2] / 4, 255]);
+        }
+    }
+
+    let fraction = differing as f64 / (actual.width() * actual.height()) as f64;
+    return (fraction, diff);
+}
+
+fn check(exe: &str, name: &str, size: &str) {
+    let bless = env_flag("WGPU_CLOCKS_BLESS");
+
+    if !bless && !env_flag("WGPU_CLOCKS_GOLDEN") {
+        eprintln!("Skipping the golden-image test of {}, set WGPU_CLOCKS_GOLDEN=1 to run it", name);
+        return;
+    }
+
+    let actual = downsample(&snapshot(exe, name, size));
+    let reference_path = golden_dir().join(format!("{}.png", name));
+
+    if bless {
+        std::fs::create_dir_all(golden_dir()).unwrap();
+        actual.save(&reference_path).unwrap();
+        eprintln!("Blessed {}", reference_path.display());
+        return;
+    }
+
+    let reference = image::open(&reference_path)
+        .unwrap_or_else(|_| panic!(
+            "No reference image at {}, generate it with WGPU_CLOCKS_BLESS=1", reference_path.display()
+        ))
+        .to_rgba8();
+
+    assert_eq!(actual.dimensions(), reference.dimensions(), "{}: size differs from the reference", name);
+
+    let (fraction, diff) = compare(&actual, &reference);
+
+    if fraction > MAX_DIFFERING_FRACTION {
+        let diff_path = output_dir().join(format!("{}.diff.png", name));
+        diff.save(&diff_path).unwrap();
+
+        panic!(
+            "{}: {:.2}% of the pixels differ from the reference (at most {:.2}% allowed), see {}",
+            name, fraction * 100.0, MAX_DIFFERING_FRACTION * 100.0, diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn polar() {
+    check(env!("CARGO_BIN_EXE_polar"), "polar", "512x512");
+}
+
+#[test]
+fn digital() {
+    check(env!("CARGO_BIN_EXE_digital"), "digital", "1024x576");
+}
+
+#[test]
+fn mcounter() {
+    check(env!("CARGO_BIN_EXE_mcounter"), "mcounter", "1024x512");
+}
+
+#[test]
+fn portal() {
+    check(env!("CARGO_BIN_EXE_portal"), "portal", "512x512");
+}
+
+#[test]
+fn analog() {
+    check(env!("CARGO_BIN_EXE_analog"), "analog", "512x512");
+}
+
+#[test]
+fn binary() {
+    check(env!("CARGO_BIN_EXE_binary"), "binary", "800x500");
+}
+
+#[test]
+fn wordclock() {
+    check(env!("CARGO_BIN_EXE_wordclock"), "wordclock", "660x600");
+}
+
+#[test]
+fn flip() {
+    check(env!("CARGO_BIN_EXE_flip"), "flip", "1024x512");
+}
+
+#[test]
+fn nixie() {
+    check(env!("CARGO_BIN_EXE_nixie"), "nixie", "960x400");
+}
@@ -0,0 +1,77 @@
+//! The graphics adapter report of `--gpu-info`, and what it flags as keeping the clocks from running
+
+use clockutils::{cli::Cli, gpu_info::adapter_issues, gpu_report, select_surface_format, SURFACE_FORMAT};
+
+fn capable_limits() -> wgpu::Limits {
+    wgpu::Limits { max_push_constant_size: 128, ..wgpu::Limits::default() }
+}
+
+#[test]
+fn capable_adapters_have_no_issues() {
+    let issues = adapter_issues(wgpu::Features::PUSH_CONSTANTS, &capable_limits(), Some(&[SURFACE_FORMAT, wgpu::TextureFormat::Rgba8Unorm]));
+    assert!(issues.is_empty(), "{:?}", issues);
+
+    // nothing to tell about the surface without one
+    assert!(adapter_issues(wgpu::Features::PUSH_CONSTANTS, &capable_limits(), None).is_empty());
+}
+
+#[test]
+fn missing_capabilities_are_flagged() {
+    let issues = adapter_issues(wgpu::Features::empty(), &wgpu::Limits::downlevel_webgl2_defaults(), Some(&[]));
+
+    assert_eq!(issues.len(), 4, "{:?}", issues);
+    assert!(issues[0].contains("only the polar clock"));
+    assert!(issues[1].contains("storage buffers per shader stage"));
+    assert!(issues[2].contains("Storage buffer bindings"));
+    assert!(issues[3].contains("Can't present"));
+
+    let issues = adapter_issues(wgpu::Features::PUSH_CONSTANTS, &wgpu::Limits { max_push_constant_size: 32, ..capable_limits() }, Some(&[wgpu::TextureFormat::Rgb10a2Unorm]));
+    assert_eq!(issues.len(), 2, "{:?}", issues);
+    assert!(issues[0].contains("limited to 32 bytes"));
+    assert!(issues[1].contains("Bgra8UnormSrgb"));
+
+    // any of the fallback formats will do
+    assert!(adapter_issues(wgpu::Features::PUSH_CONSTANTS, &capable_limits(), Some(&[wgpu::TextureFormat::Rgba8Unorm])).is_empty());
+}
+
+#[test]
+fn surface_formats_fall_back_in_order() {
+    let caps = |formats: &[wgpu::TextureFormat]| wgpu::SurfaceCapabilities { formats: formats.to_vec(), ..Default::default() };
+
+    assert_eq!(select_surface_format(&caps(&[wgpu::TextureFormat::Rgba8Unorm, SURFACE_FORMAT])), SURFACE_FORMAT);
+    assert_eq!(
+        select_surface_format(&caps(&[wgpu::TextureFormat::Bgra8Unorm, wgpu::TextureFormat::Rgba8UnormSrgb])),
+        wgpu::TextureFormat::Rgba8UnormSrgb
+    );
+    assert_eq!(select_surface_format(&caps(&[wgpu::TextureFormat::Rgba16Float, wgpu::TextureFormat::Rgba8Unorm])), wgpu::TextureFormat::Rgba8Unorm);
+
+    // none of the known ones, whatever the surface prefers
+    assert_eq!(select_surface_format(&caps(&[wgpu::TextureFormat::Rgb10a2Unorm])), wgpu::TextureFormat::Rgb10a2Unorm);
+}
+
+#[test]
+fn report_lists_every_adapter() {
+    let instance = wgpu::Instance::default();
+    let report = gpu_report(&instance);
+
+    let adapters = instance.enumerate_adapters(wgpu::Backends::all()).count();
+    if adapters == 0 {
+        assert!(report.starts_with("No graphics adapters found"));
+        return;
+    }
+
+    assert_eq!(report.lines().filter(|line| line.starts_with("Adapter ")).count(), adapters);
+    assert!(report.contains("Push constants:"));
+    assert!(report.contains("no window to check against"));
+}
+
+#[test]
+fn gpu_info_flag() {
+    let cli = Cli::new("test", "Test clock");
+
+    let (common, _) = cli.try_parse(["--gpu-info".to_string()]).unwrap();
+    assert!(common.gpu_info);
+
+    let (common, _) = cli.try_parse(Vec::<String>::new()).unwrap();
+    assert!(!common.print_gpu_info());
+}
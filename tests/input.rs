@@ -0,0 +1,51 @@
+//! What's held down between frames: keys by their place on the keyboard, mouse buttons, and forgetting them on focus loss
+
+use clockutils::InputState;
+use winit::keyboard::KeyCode;
+
+#[test]
+fn keys_are_held_until_released() {
+    let mut input = InputState::new();
+    assert!(!input.is_down(KeyCode::ArrowUp));
+
+    // together, released one after the other
+    input.set_key(KeyCode::ArrowUp, true);
+    input.set_key(KeyCode::ShiftLeft, true);
+    assert!(input.is_down(KeyCode::ArrowUp) && input.is_down(KeyCode::ShiftLeft));
+
+    input.set_key(KeyCode::ArrowUp, false);
+    assert!(!input.is_down(KeyCode::ArrowUp));
+    assert!(input.is_down(KeyCode::ShiftLeft));
+
+    // repeats are presses of a key already held
+    input.set_key(KeyCode::ShiftLeft, true);
+    input.set_key(KeyCode::ShiftLeft, false);
+    assert!(!input.is_down(KeyCode::ShiftLeft));
+}
+
+#[test]
+fn focus_loss_releases_the_keys_and_buttons() {
+    let mut input = InputState::new();
+    input.set_key(KeyCode::KeyW, true);
+    input.set_button(2, true);
+    input.cursor_moved((10.0, 20.0));
+
+    input.focus_lost();
+    assert!(!input.is_down(KeyCode::KeyW));
+    assert_eq!(input.buttons(), 0);
+    assert_eq!(input.cursor(), Some((10.0, 20.0)));
+}
+
+#[test]
+fn buttons_are_bits() {
+    let mut input = InputState::new();
+    input.set_button(0, true);
+    input.set_button(2, true);
+    assert_eq!(input.buttons(), 0b101);
+
+    input.set_button(0, false);
+    assert_eq!(input.buttons(), 0b100);
+
+    input.cursor_left();
+    assert_eq!(input.cursor(), None);
+}
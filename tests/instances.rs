@@ -0,0 +1,114 @@
+//! Instance buffers: their layout, and per-instance data drawn, rewritten and drawn again.
+//!
+//! Needs a GPU (any backend), skipped when no adapter is found
+
+mod common;
+
+use clockutils::{headless::read_texture_rgba, InstanceBuffer};
+
+/// One column of the target, in a color
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Column {
+    x:     f32,
+    color: [f32; 3]
+}
+
+// a quad one column wide and as tall as the target per instance, at the instance's x
+const SHADER: &str = "
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+    @location(0)       color:    vec3f
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, @location(0) x: f32, @location(1) color: vec3f) -> VertexOutput {
+    let corner = vec2f(f32(index & 1u), f32(index >> 1u));
+    let left   = x * 2.0 / 4.0 - 1.0;
+
+    var out: VertexOutput;
+    out.position = vec4f(left + corner.x * 2.0 / 4.0, corner.y * 2.0 - 1.0, 0.0, 1.0);
+    out.color    = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    return vec4f(in.color, 1.0);
+}
+";
+
+const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![0 => Float32, 1 => Float32x3];
+
+#[test]
+fn layout_steps_per_instance() {
+    let layout = InstanceBuffer::<Column>::as_vertex_buffer_layout(&ATTRIBUTES);
+
+    assert_eq!(layout.array_stride, 16);
+    assert_eq!(layout.step_mode, wgpu::VertexStepMode::Instance);
+    assert_eq!(layout.attributes.len(), 2);
+}
+
+#[test]
+fn draws_one_quad_per_instance() {
+    let Some((device, queue)) = common::gpu() else {
+        return;
+    };
+
+    let format = wgpu::TextureFormat::Bgra8Unorm;
+
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(SHADER.into())
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label:  None,
+        layout: None,
+        vertex: wgpu::VertexState {
+            module:      &module,
+            entry_point: "vs_main",
+            buffers:     &[ InstanceBuffer::<Column>::as_vertex_buffer_layout(&ATTRIBUTES) ]
+        },
+        fragment: Some(wgpu::FragmentState {
+            module:      &module,
+            entry_point: "fs_main",
+            targets:     &[Some(format.into())]
+        }),
+        primitive:     wgpu::PrimitiveState { topology: wgpu::PrimitiveTopology::TriangleStrip, ..Default::default() },
+        depth_stencil: None,
+        multisample:   wgpu::MultisampleState::default(),
+        multiview:     None
+    });
+
+    let (target, view) = common::readable_target(&device, (4, 1), format);
+
+    let draw = |instances: &InstanceBuffer<Column>| {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut rpass = common::render_pass(&mut encoder, &view, wgpu::Color::BLACK);
+
+            rpass.set_pipeline(&pipeline);
+            rpass.set_vertex_buffer(0, instances.slice());
+            rpass.draw(0..4, 0..instances.count());
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let image = read_texture_rgba(&target, &device, &queue).unwrap();
+        (0..4).map(|x| image.get_pixel(x, 0).0).collect::<Vec<_>>()
+    };
+
+    let mut instances = InstanceBuffer::new(&device, &[
+        Column { x: 0.0, color: [1.0, 0.0, 0.0] },
+        Column { x: 1.0, color: [0.0, 1.0, 0.0] },
+        Column { x: 3.0, color: [0.0, 0.0, 1.0] }
+    ]);
+    assert_eq!((instances.capacity, instances.count()), (3, 3));
+
+    assert_eq!(draw(&instances), [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 0, 255], [0, 0, 255, 255]]);
+
+    // fewer instances than there's room for, the ones left over aren't drawn
+    instances.update(&queue, &[ Column { x: 2.0, color: [1.0, 1.0, 1.0] } ]);
+    assert_eq!(instances.count(), 1);
+
+    assert_eq!(draw(&instances), [[0, 0, 0, 255], [0, 0, 0, 255], [255, 255, 255, 255], [0, 0, 0, 255]]);
+}
@@ -0,0 +1,42 @@
+//! The nixie clock's tubes: which cathodes are lit at a given time, and which are still flickering
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta};
+use clockutils::clocks::nixie::{calc_nixie_data, is_flickering, until_next_change, FLICKER_DURATION};
+
+fn at(hour: u32, minute: u32, second: u32, nanos: i64) -> DateTime<FixedOffset> {
+    NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+        .and_hms_opt(hour, minute, second).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(0).unwrap()).unwrap() + TimeDelta::nanoseconds(nanos)
+}
+
+const SETTLED: f32 = FLICKER_DURATION as f32 / 1_000_000_000.0;
+
+#[test]
+fn changed_tubes_flicker() {
+    // 23:59:59 -> 00:00:00, every tube switched 50ms ago
+    let data = calc_nixie_data(&at(0, 0, 0, 50_000_000));
+    for (tube, previous) in [2, 3, 5, 9, 5, 9].into_iter().enumerate() {
+        assert_eq!(data.digits[tube], previous << 8, "{}", tube);
+        assert!((data.since_change[tube] - 0.05).abs() < 1e-6, "{}", tube);
+    }
+
+    // only the seconds' ones switched
+    let data = calc_nixie_data(&at(10, 8, 42, 10_000_000));
+    assert_eq!(data.digits[5], 2 | 1 << 8);
+    assert!(data.since_change[5] < SETTLED);
+    assert_eq!(&data.digits[..5], &[1, 0, 0, 8, 4].map(|digit| digit | digit << 8));
+    assert!(data.since_change[..5].iter().all(|&since| since == SETTLED));
+}
+
+#[test]
+fn tubes_settle_until_the_next_second() {
+    let now = at(10, 8, 42, FLICKER_DURATION as i64);
+    assert!(!is_flickering(&now));
+
+    let data = calc_nixie_data(&now);
+    assert_eq!(&data.digits[..6], &[1, 0, 0, 8, 4, 2].map(|digit| digit | digit << 8));
+    assert!(data.since_change[..6].iter().all(|&since| since == SETTLED));
+    assert!((data.timestamp - 42.15).abs() < 1e-4);
+
+    assert_eq!(until_next_change(&at(10, 8, 42, 250_000_000)), TimeDelta::milliseconds(750));
+}
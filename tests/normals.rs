@@ -0,0 +1,88 @@
+//! Smooth normals derived from the faces, with each weighting, on the corner of a cube whose bottom is split in two
+
+use clockutils::{compute_face_areas, compute_smooth_normals, parse_normal_weighting, NormalWeighting, PlyMesh, Vtx3UV};
+
+fn vertex(x: f32, y: f32, z: f32) -> Vtx3UV {
+    Vtx3UV { pos: glam::Vec3::new(x, y, z), uv: glam::Vec2::ZERO }
+}
+
+/// The corner at the origin: one triangle on each side wall (x = 0 and y = 0), two on the bottom (z = 0),
+/// all wound to face the inside of the cube
+fn corner() -> (Vec<Vtx3UV>, Vec<u32>) {
+    let vertices = vec![
+        vertex(0.0, 0.0, 0.0),
+        vertex(1.0, 0.0, 0.0),
+        vertex(1.0, 1.0, 0.0),
+        vertex(0.0, 1.0, 0.0),
+        vertex(0.0, 0.0, 1.0)
+    ];
+
+    let indices = vec![
+        0, 1, 2, // bottom, 45 degrees at the corner
+        0, 2, 3, // bottom, 45 degrees
+        0, 3, 4, // x = 0, 90 degrees
+        0, 4, 1  // y = 0, 90 degrees
+    ];
+
+    (vertices, indices)
+}
+
+fn assert_near(actual: glam::Vec3, expected: glam::Vec3) {
+    assert!(actual.abs_diff_eq(expected, 1e-5), "{} is not {}", actual, expected);
+}
+
+#[test]
+fn face_areas() {
+    let (vertices, indices) = corner();
+    assert_eq!(compute_face_areas(&vertices, &indices), vec![0.5; 4]);
+
+    // degenerate
+    assert_eq!(compute_face_areas(&vertices, &[0, 1, 1]), vec![0.0]);
+}
+
+#[test]
+fn angles_dont_depend_on_the_triangulation() {
+    let (vertices, indices) = corner();
+
+    // the bottom's two triangles add up to the same angle as each wall's one
+    let angle = compute_smooth_normals(&vertices, &indices, NormalWeighting::Angle);
+    assert_near(angle[0], glam::Vec3::ONE.normalize());
+
+    // whereas by area and by count, the bottom weighs twice as much
+    let expected = glam::Vec3::new(1.0, 1.0, 2.0).normalize();
+    assert_near(compute_smooth_normals(&vertices, &indices, NormalWeighting::Area)[0],    expected);
+    assert_near(compute_smooth_normals(&vertices, &indices, NormalWeighting::Uniform)[0], expected);
+}
+
+#[test]
+fn flat_surfaces_and_lone_vertices() {
+    let (mut vertices, indices) = corner();
+    vertices.push(vertex(5.0, 5.0, 5.0)); // in no face
+
+    for weighting in [NormalWeighting::Area, NormalWeighting::Angle, NormalWeighting::Uniform] {
+        let normals = compute_smooth_normals(&vertices, &indices[..6], weighting);
+
+        // only on the bottom
+        assert_near(normals[2], glam::Vec3::Z);
+        assert_near(normals[3], glam::Vec3::Z);
+        assert_eq!(normals[5], glam::Vec3::ZERO, "{:?}", weighting);
+    }
+}
+
+#[test]
+fn meshes_keep_area_weighted_normals_by_default() {
+    let (vertices, indices) = corner();
+    let mesh = PlyMesh { vertices, indices: indices.iter().map(|&i| i as u16).collect(), colors: Vec::new() };
+
+    assert_eq!(NormalWeighting::default(), NormalWeighting::Area);
+    assert_near(mesh.with_normals()[0].normal, glam::Vec3::new(1.0, 1.0, 2.0).normalize());
+    assert_near(mesh.with_normals_weighted(NormalWeighting::Angle)[0].normal, glam::Vec3::ONE.normalize());
+}
+
+#[test]
+fn weighting_names() {
+    assert_eq!(parse_normal_weighting("area"),    Some(NormalWeighting::Area));
+    assert_eq!(parse_normal_weighting("Angle"),   Some(NormalWeighting::Angle));
+    assert_eq!(parse_normal_weighting("uniform"), Some(NormalWeighting::Uniform));
+    assert_eq!(parse_normal_weighting("smooth"),  None);
+}
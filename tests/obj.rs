@@ -0,0 +1,76 @@
+//! OBJ meshes: triangles only, each distinct position/UV/normal triplet a vertex of its own
+
+use clockutils::{ObjMesh, Vtx3NUV};
+
+fn parse(data: &str) -> Result<ObjMesh, String> {
+    ObjMesh::from_reader(data.as_bytes())
+}
+
+#[test]
+fn corners_become_vertices() {
+    // a unit square in two triangles, sharing the diagonal's corners
+    let mesh = parse("# square
+o Square
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+usemtl None
+s off
+f 1/1/1 2/2/1 3/3/1
+f -4/-4/-1 3/3/1 4/4/1
+").unwrap();
+
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+    assert_eq!(mesh.vertices[2].pos, glam::Vec3::new(1.0, 1.0, 0.0));
+    assert_eq!(mesh.vertices[3].uv, glam::Vec2::new(0.0, 1.0));
+    assert!(mesh.vertices.iter().all(|vertex| vertex.normal == glam::Vec3::Z));
+}
+
+#[test]
+fn seams_split_vertices_and_normals_are_derived() {
+    // the same position with two UVs, no normals in the file
+    let mesh = parse("v 0 0 0\nv 1 0 0\nv 0 0 -1\nvt 0 0\nvt 1 0\nvt 0 1\nvt 0.5 0.5\nf 1/1 2/2 3/3\nf 1/4 2/2 3/3\n").unwrap();
+
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.indices, vec![0, 1, 2, 3, 1, 2]);
+
+    // counter-clockwise seen from above
+    assert!(mesh.vertices.iter().all(|vertex: &Vtx3NUV| vertex.normal.abs_diff_eq(glam::Vec3::Y, 1e-6)));
+
+    // positions alone, the UVs default to the corner
+    let mesh = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+    assert_eq!(mesh.vertices[1].uv, glam::Vec2::ZERO);
+    assert!(mesh.vertices[1].normal.abs_diff_eq(glam::Vec3::Z, 1e-6));
+}
+
+#[test]
+fn explicit_normals_survive_faces_without() {
+    // a face with normals pointing off the plane on purpose (as an artist's tweak would), and one without any
+    let mesh = parse("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvn 0 0.6 0.8\nf 1//1 2//1 3//1\nf 1 3 4\n").unwrap();
+
+    // the shared corners are vertices of their own for each face
+    assert_eq!(mesh.vertices.len(), 6);
+    assert_eq!(mesh.indices, vec![0, 1, 2, 3, 4, 5]);
+
+    assert!(mesh.vertices[..3].iter().all(|vertex| vertex.normal == glam::Vec3::new(0.0, 0.6, 0.8)));
+    assert!(mesh.vertices[3..].iter().all(|vertex| vertex.normal.abs_diff_eq(glam::Vec3::Z, 1e-6)));
+}
+
+#[test]
+fn quads_and_bad_indices_are_refused() {
+    let quad = parse("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n").unwrap_err();
+    assert!(quad.starts_with("line 5:") && quad.contains("only triangles"), "{}", quad);
+
+    let out_of_range = parse("v 0 0 0\nv 1 0 0\nf 1 2 3\n").unwrap_err();
+    assert!(out_of_range.contains("position index 3 out of range"), "{}", out_of_range);
+
+    assert!(parse("v 0 zero 0\n").unwrap_err().contains("isn't a number"));
+    assert!(ObjMesh::new("no/such/mesh.obj").unwrap_err().contains("no/such/mesh.obj"));
+}
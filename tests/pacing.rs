@@ -0,0 +1,133 @@
+//! Idle wakeups: the runner's pacing interval merged with the clocks' deadlines, and the deadlines themselves.
+//! Also the frame latency limit, as given on the command line, and the frame timing handed to the clocks
+
+use std::time::{Duration, Instant};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta};
+use clockutils::{clamp_frame_latency, cli::Cli, next_wakeup, time, FrameInfo, FrameLimiter, FIRST_FRAME_DELTA, MAX_FRAME_LATENCY, clocks::{digital, mcounter}};
+
+const INTERVAL: Duration = Duration::from_millis(1000);
+
+/// 12:34:56 and the given milliseconds, UTC
+fn at(millis: u32) -> DateTime<FixedOffset> {
+    NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+        .and_hms_milli_opt(12, 34, 56, millis).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(0).unwrap()).unwrap()
+}
+
+#[test]
+fn without_deadline_waits_the_interval() {
+    let now = Instant::now();
+    assert_eq!(next_wakeup(now, INTERVAL, None), now + INTERVAL);
+}
+
+#[test]
+fn sooner_deadline_wins() {
+    let now = Instant::now();
+    let deadline = now + Duration::from_millis(250);
+    assert_eq!(next_wakeup(now, INTERVAL, Some(deadline)), deadline);
+}
+
+#[test]
+fn later_deadline_waits_the_interval() {
+    let now = Instant::now();
+    assert_eq!(next_wakeup(now, INTERVAL, Some(now + Duration::from_secs(5))), now + INTERVAL);
+}
+
+#[test]
+fn passed_deadline_wakes_right_away() {
+    let now = Instant::now() + Duration::from_secs(1);
+    assert_eq!(next_wakeup(now, INTERVAL, Some(now - Duration::from_millis(10))), now);
+}
+
+#[test]
+fn digital_wakes_on_half_seconds() {
+    assert_eq!(digital::until_next_half_second(&at(200)), TimeDelta::milliseconds(300));
+    assert_eq!(digital::until_next_half_second(&at(750)), TimeDelta::milliseconds(250));
+    // exactly on the boundary, it's the next one that's interesting
+    assert_eq!(digital::until_next_half_second(&at(0)),   TimeDelta::milliseconds(500));
+    assert_eq!(digital::until_next_half_second(&at(500)), TimeDelta::milliseconds(500));
+}
+
+#[test]
+fn mcounter_wakes_when_the_wheels_start_turning() {
+    assert_eq!(mcounter::until_next_animation(&at(300)), TimeDelta::milliseconds(700));
+    assert_eq!(mcounter::until_next_animation(&at(0)),   TimeDelta::seconds(1));
+
+    assert!(mcounter::is_animating(&at(100)));
+    assert!(!mcounter::is_animating(&at(600)));
+}
+
+#[test]
+fn deadlines_follow_the_time_source() {
+    // the shared source is left at real time by the tests, so clock time is real time
+    let now = Instant::now();
+    assert_eq!(time::deadline_after(now, TimeDelta::milliseconds(250)), Some(now + Duration::from_millis(250)));
+    assert_eq!(time::deadline_after(now, TimeDelta::milliseconds(-250)), None);
+}
+
+#[test]
+fn frame_latency_is_clamped() {
+    assert_eq!(clamp_frame_latency(0), 1);
+    assert_eq!(clamp_frame_latency(2), 2);
+    assert_eq!(clamp_frame_latency(MAX_FRAME_LATENCY), MAX_FRAME_LATENCY);
+    assert_eq!(clamp_frame_latency(16), MAX_FRAME_LATENCY);
+}
+
+#[test]
+fn frame_latency_flag() {
+    let cli  = Cli::new("test", "Test clock");
+    let args = |args: &[&str]| cli.try_parse(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(args(&[]).unwrap().0.run_options().frame_latency, None);
+    assert_eq!(args(&["--frame-latency", "1"]).unwrap().0.run_options().frame_latency, Some(1));
+    // clamped by the runner, with a warning
+    assert_eq!(args(&["--frame-latency", "9"]).unwrap().0.run_options().frame_latency, Some(9));
+    assert!(args(&["--frame-latency", "two"]).is_err());
+}
+
+#[test]
+fn frame_limiter_keeps_the_rate() {
+    let start    = Instant::now();
+    let interval = Duration::from_millis(20);
+    let mut limiter = FrameLimiter::new(50);
+
+    assert_eq!(limiter.wait_until(start), None);
+    limiter.drawn(start);
+    assert_eq!(limiter.wait_until(start + Duration::from_millis(5)), Some(start + interval));
+
+    // woken up late every time, the slots stay on the 20ms grid
+    let mut now = start;
+    for frame in 1..=100 {
+        now = start + interval * frame + Duration::from_millis(3);
+        assert_eq!(limiter.wait_until(now), None);
+        limiter.drawn(now);
+    }
+    assert_eq!(limiter.wait_until(now), Some(start + interval * 101));
+
+    // after a pause, no burst of frames to catch up
+    let later = now + Duration::from_secs(1);
+    limiter.drawn(later);
+    assert_eq!(limiter.wait_until(later + Duration::from_millis(1)), Some(later + interval));
+}
+
+#[test]
+fn max_fps_flag() {
+    let cli  = Cli::new("test", "Test clock");
+    let args = |args: &[&str]| cli.try_parse(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(args(&[]).unwrap().0.run_options().max_fps, None);
+    assert_eq!(args(&["--max-fps", "30"]).unwrap().0.run_options().max_fps, Some(30));
+    assert!(args(&["--max-fps", "0"]).is_err());
+}
+
+#[test]
+fn frames_add_up_their_deltas() {
+    let first = FrameInfo::first();
+    assert_eq!(first, FrameInfo { delta: FIRST_FRAME_DELTA, elapsed: 0.0, frame_index: 0 });
+
+    // a second at 144 Hz, after the first frame
+    let last = (0..144).fold(first, |frame, _| frame.next(1.0 / 144.0));
+    assert_eq!(last.frame_index, 144);
+    assert_eq!(last.delta, 1.0 / 144.0);
+    assert!((last.elapsed - 1.0).abs() < 1e-5, "{}", last.elapsed);
+}
@@ -0,0 +1,162 @@
+//! Palette files, the HSV conversion behind the palette editor, and the editor's key handling
+
+use clockutils::palette::{format_hex_color, highlight_u32_color, parse_hex_color, Hsv, PaletteEditor, PaletteSet};
+use clockutils::clocks::polar::{ColorCombo, PALETTE_SLOTS};
+
+const SLOTS: &[&str] = &["digits", "colon", "labels"];
+
+fn palettes() -> PaletteSet {
+    PaletteSet::new(SLOTS, vec![vec![0x0F52FF_FF; 3], vec![0x1AE61A_FF; 3]])
+}
+
+#[test]
+fn hsv_round_trips_every_channel_value() {
+    for value in (0..=255u32).step_by(5) {
+        for color in [(value << 24) | 0x40_80_FF, 0xFF_00_00_00 | (value << 16) | 0x7F, 0x10_20_00_80 | (value << 8), (value * 0x01_01_01_00) | 0xFF] {
+            assert_eq!(Hsv::from_u32_color(color).to_u32_color(), color, "{:08x}", color);
+        }
+    }
+}
+
+#[test]
+fn hsv_of_primaries() {
+    let red   = Hsv::from_u32_color(0xFF0000_FF);
+    let green = Hsv::from_u32_color(0x00FF00_FF);
+    let blue  = Hsv::from_u32_color(0x0000FF_80);
+
+    assert_eq!((red.hue, red.saturation, red.value), (0.0, 1.0, 1.0));
+    assert_eq!(green.hue, 120.0);
+    assert_eq!((blue.hue, blue.alpha), (240.0, 0x80));
+}
+
+#[test]
+fn adjusting_wraps_the_hue_and_clamps_the_rest() {
+    let adjusted = Hsv::from_u32_color(0xFF0000_FF).adjusted(-30.0, 0.5, -1.5);
+
+    assert_eq!(adjusted.hue, 330.0);
+    assert_eq!(adjusted.saturation, 1.0);
+    assert_eq!(adjusted.value, 0.0);
+}
+
+#[test]
+fn hex_colors() {
+    assert_eq!(parse_hex_color("#171738"), Some(0x171738_FF));
+    assert_eq!(parse_hex_color("aaefdf80"), Some(0xAAEFDF_80));
+    assert_eq!(parse_hex_color("#12345"), None);
+    assert_eq!(parse_hex_color("#+12345"), None);
+    assert_eq!(parse_hex_color("#12345g"), None);
+
+    assert_eq!(format_hex_color(0x171738_FF), "#171738");
+    assert_eq!(format_hex_color(0xAAEFDF_80), "#aaefdf80");
+}
+
+#[test]
+fn highlight_moves_away_from_the_color() {
+    assert_eq!(highlight_u32_color(0x000000_FF, 1.0), 0xFFFFFF_FF);
+    assert_eq!(highlight_u32_color(0xFFFFFF_80, 1.0), 0x000000_80);
+    assert_eq!(highlight_u32_color(0x123456_FF, 0.0), 0x123456_FF);
+}
+
+#[test]
+fn file_overrides_only_what_it_lists() {
+    let mut set = palettes();
+    set.apply_toml("[[palette]]\ncolon = \"#ff0000\"\n\n[[palette]]\nlabels = \"#00000080\"\n").unwrap();
+
+    assert_eq!(set.get(0), &[0x0F52FF_FF, 0xFF0000_FF, 0x0F52FF_FF]);
+    assert_eq!(set.get(1), &[0x1AE61A_FF, 0x1AE61A_FF, 0x000000_80]);
+}
+
+#[test]
+fn malformed_file_changes_nothing() {
+    for text in ["[[palette]]\ncolon = \"red\"\n", "[[palette]]\nsegments = \"#ff0000\"\n", "palette = 3"] {
+        let mut set = palettes();
+        assert!(set.apply_toml(text).is_err(), "{}", text);
+        assert_eq!(set.get(0), palettes().get(0));
+    }
+}
+
+#[test]
+fn saved_file_loads_back() {
+    let path = std::env::temp_dir().join(format!("wgpu-clocks-palette-{}.toml", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut set = PaletteSet::load(Some(path.clone()), SLOTS, vec![vec![0x0F52FF_FF; 3], vec![0x1AE61A_FF; 3]]);
+    set.set(1, 2, 0xAB_CD_EF_42);
+    set.save().unwrap();
+
+    let loaded = PaletteSet::load(Some(path.clone()), SLOTS, vec![vec![0; 3], vec![0; 3]]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.get(0), set.get(0));
+    assert_eq!(loaded.get(1), set.get(1));
+}
+
+#[test]
+fn saving_without_a_file_fails() {
+    assert!(palettes().save().is_err());
+}
+
+#[test]
+fn polar_slots_match_the_color_combo() {
+    let combo = ColorCombo { hour: 1, minute: 2, second: 3, disk: 4, background: 5 };
+
+    assert_eq!(combo.to_slots().len(), PALETTE_SLOTS.len());
+    assert_eq!(ColorCombo::from_slots(&combo.to_slots()), combo);
+}
+
+#[test]
+fn editor_enters_cycles_and_leaves() {
+    let mut set    = palettes();
+    let mut editor = PaletteEditor::new("Test");
+
+    // nothing but the edit key is taken until editing
+    assert!(!editor.handle_key("Tab", &mut set, Some(1)));
+    assert!(editor.handle_key("Ctrl+P", &mut set, Some(1)));
+    assert_eq!(editor.slot(), Some(0));
+    assert!(editor.status(&set).unwrap().contains("digits"));
+
+    for slot in [1, 2, 0] {
+        assert!(editor.handle_key("Tab", &mut set, Some(1)));
+        assert_eq!(editor.slot(), Some(slot));
+    }
+
+    assert!(editor.handle_key("Escape", &mut set, Some(1)));
+    assert!(!editor.is_editing());
+    assert_eq!(editor.status(&set), None);
+}
+
+#[test]
+fn editor_adjusts_the_shown_palette() {
+    let mut set    = palettes();
+    let mut editor = PaletteEditor::new("Test");
+
+    editor.handle_key("Ctrl+P", &mut set, Some(1));
+    editor.handle_key("Tab", &mut set, Some(1));
+    editor.handle_key("ArrowRight", &mut set, Some(1));
+
+    let edited = Hsv::from_u32_color(set.get(1)[1]);
+    let before = Hsv::from_u32_color(0x1AE61A_FF);
+
+    assert!((edited.hue - before.hue - 5.0).abs() < 1.0);
+    assert_eq!(set.get(1)[0], 0x1AE61A_FF);
+    assert_eq!(set.get(0), palettes().get(0));
+    assert!(editor.is_unsaved());
+
+    // the hue survives going through black
+    for _ in 0..30 { editor.handle_key("ArrowDown", &mut set, Some(1)); }
+    assert_eq!(set.get(1)[1], 0x000000_FF);
+    for _ in 0..30 { editor.handle_key("ArrowUp", &mut set, Some(1)); }
+    assert!((Hsv::from_u32_color(set.get(1)[1]).hue - edited.hue).abs() < 1.0);
+
+    // the clock's own keys stay out of the way while editing
+    assert!(editor.handle_key("Space", &mut set, Some(1)));
+}
+
+#[test]
+fn editor_refuses_uneditable_palettes() {
+    let mut set    = palettes();
+    let mut editor = PaletteEditor::new("Test");
+
+    assert!(editor.handle_key("Ctrl+P", &mut set, None));
+    assert!(!editor.is_editing());
+}
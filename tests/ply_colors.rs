@@ -0,0 +1,83 @@
+//! Vertex colors of PLY meshes, read as the blend weights of splatted textures
+
+use clockutils::PlyMesh;
+
+const HEADER: &str = "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property float s
+property float t
+";
+
+const FACES: &str = "element face 1
+property list uchar uint vertex_indices
+end_header
+";
+
+fn parse(color_properties: &str, vertices: &str) -> Result<PlyMesh, &'static str> {
+    let data = format!("{}{}{}{}3 0 1 2\n", HEADER, color_properties, FACES, vertices);
+    PlyMesh::from_reader(&mut data.as_bytes())
+}
+
+#[test]
+fn uchar_colors_are_normalized() {
+    let mesh = parse(
+        "property uchar red\nproperty uchar green\nproperty uchar blue\nproperty uchar alpha\n",
+        "0 0 0 0 0 255 0 0 0\n1 0 0 1 0 0 255 0 0\n0 1 0 0 1 0 51 102 102\n"
+    ).unwrap();
+
+    let weights: Vec<glam::Vec4> = mesh.with_blend_weights().iter().map(|vertex| vertex.blend_weights).collect();
+    assert_eq!(weights[0], glam::Vec4::new(1.0, 0.0, 0.0, 0.0));
+    assert_eq!(weights[1], glam::Vec4::new(0.0, 1.0, 0.0, 0.0));
+    assert!(weights[2].abs_diff_eq(glam::Vec4::new(0.0, 0.2, 0.4, 0.4), 1e-6));
+
+    // the rest is read as usual
+    assert_eq!(mesh.with_blend_weights()[1].pos, glam::Vec3::new(1.0, 0.0, 0.0));
+    assert_eq!(mesh.indices, vec![0, 1, 2]);
+}
+
+#[test]
+fn float_colors_are_taken_as_is() {
+    let mesh = parse(
+        "property float red\nproperty float green\nproperty float blue\nproperty float alpha\n",
+        "0 0 0 0 0 0.5 0.5 0 0\n1 0 0 1 0 0 0 0.25 0.75\n0 1 0 0 1 0 0 0 1\n"
+    ).unwrap();
+
+    assert_eq!(mesh.colors[0], glam::Vec4::new(0.5, 0.5, 0.0, 0.0));
+    assert_eq!(mesh.colors[1], glam::Vec4::new(0.0, 0.0, 0.25, 0.75));
+}
+
+#[test]
+fn colors_need_all_four_channels() {
+    // without alpha, the weights would be ambiguous, so there are none
+    let mesh = parse(
+        "property uchar red\nproperty uchar green\nproperty uchar blue\n",
+        "0 0 0 0 0 255 0 0\n1 0 0 1 0 0 255 0\n0 1 0 0 1 0 0 255\n"
+    ).unwrap();
+
+    assert!(mesh.colors.is_empty());
+    assert_eq!(mesh.vertices.len(), 3);
+}
+
+#[test]
+fn missing_colors_are_rejected_when_required() {
+    let path = std::env::temp_dir().join(format!("wgpu-clocks-uncolored-{}.ply", std::process::id()));
+    std::fs::write(&path, format!("{}{}0 0 0 0 0\n1 0 0 1 0\n0 1 0 0 1\n3 0 1 2\n", HEADER, FACES)).unwrap();
+
+    let result = PlyMesh::new_colored(path.to_str().unwrap());
+    let _ = std::fs::remove_file(&path);
+
+    assert!(result.unwrap_err().contains("no red, green, blue and alpha"));
+}
+
+#[test]
+fn blended_terrain_has_weights() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/portal/meshes/terrain_geo_blended.ply");
+    let mesh = PlyMesh::new_colored(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(mesh.colors.len(), mesh.vertices.len());
+    assert!(mesh.colors.iter().all(|weights| (weights.dot(glam::Vec4::ONE) - 1.0).abs() < 1e-3));
+}
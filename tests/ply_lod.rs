@@ -0,0 +1,123 @@
+//! Levels of detail simplified from a single mesh, on a flat grid and the portal's terrain.
+//!
+//! Uploading them needs a GPU (any backend), skipped when no adapter is found
+
+mod common;
+
+use clockutils::{PlyGeoBuffers, PlyMesh, Vtx3UV};
+
+/// `n` x `n` quads on the z = 0 plane, spanning [-1..1], with a color per vertex
+fn grid(n: u16) -> PlyMesh {
+    let mut mesh = PlyMesh { vertices: Vec::new(), indices: Vec::new(), colors: Vec::new() };
+
+    for y in 0..=n {
+        for x in 0..=n {
+            let (u, v) = (x as f32 / n as f32, y as f32 / n as f32);
+            mesh.vertices.push(Vtx3UV { pos: glam::Vec3::new(u * 2.0 - 1.0, v * 2.0 - 1.0, 0.0), uv: glam::Vec2::new(u, v) });
+            mesh.colors.push(glam::Vec4::new(u, v, 0.0, 1.0));
+        }
+    }
+
+    for y in 0..n {
+        for x in 0..n {
+            let i = y * (n + 1) + x;
+            mesh.indices.extend([i, i + 1, i + n + 1, i + 1, i + n + 2, i + n + 1]);
+        }
+    }
+
+    mesh
+}
+
+fn assert_well_formed(mesh: &PlyMesh) {
+    assert_eq!(mesh.indices.len() % 3, 0);
+    assert!(!mesh.indices.is_empty());
+
+    for face in mesh.indices.chunks_exact(3) {
+        assert!(face.iter().all(|&i| (i as usize) < mesh.vertices.len()));
+        assert!(face[0] != face[1] && face[1] != face[2] && face[2] != face[0], "degenerate face {:?}", face);
+    }
+
+    // no vertex left unused
+    let mut used = vec![false; mesh.vertices.len()];
+    mesh.indices.iter().for_each(|&i| used[i as usize] = true);
+    assert!(used.into_iter().all(|used| used));
+}
+
+#[test]
+fn full_ratio_keeps_the_mesh() {
+    let mesh = grid(8);
+    let same = mesh.simplified(1.0);
+
+    assert_eq!(same.vertices.len(), mesh.vertices.len());
+    assert_eq!(same.indices, mesh.indices);
+}
+
+#[test]
+fn halves_the_vertices() {
+    let mesh = grid(32);
+    let half = mesh.simplified(0.5);
+
+    assert_well_formed(&half);
+    assert!(half.vertices.len() <= mesh.vertices.len().div_ceil(2), "{} vertices left", half.vertices.len());
+    assert!(half.vertices.len() * 3 > mesh.vertices.len(), "{} vertices left", half.vertices.len());
+    assert!(half.indices.len() < mesh.indices.len());
+    assert_eq!(half.colors.len(), half.vertices.len());
+}
+
+#[test]
+fn keeps_the_outline_and_the_plane() {
+    let mesh = grid(32);
+    let quarter = mesh.simplified(0.25);
+
+    assert_well_formed(&quarter);
+
+    // the border vertices don't move inwards, the corners stay
+    let min = quarter.vertices.iter().fold(glam::Vec3::MAX, |min, vertex| min.min(vertex.pos));
+    let max = quarter.vertices.iter().fold(glam::Vec3::MIN, |max, vertex| max.max(vertex.pos));
+    assert_eq!((min, max), (glam::Vec3::new(-1.0, -1.0, 0.0), glam::Vec3::new(1.0, 1.0, 0.0)));
+
+    for corner in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+        assert!(quarter.vertices.iter().any(|vertex| vertex.pos.truncate() == glam::Vec2::from(corner)), "{:?} is gone", corner);
+    }
+
+    // the grid covers the same area, without overlaps
+    let area: f32 = quarter.indices.chunks_exact(3).map(|face| {
+        let [p0, p1, p2] = [0, 1, 2].map(|k| quarter.vertices[face[k] as usize].pos);
+        (p1 - p0).cross(p2 - p0).z * 0.5
+    }).sum();
+    assert!((area - 4.0).abs() < 1e-3, "area {}", area);
+}
+
+#[test]
+fn simplifies_the_terrain() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/resources/portal/meshes/terrain_geo.ply");
+    let mesh = PlyMesh::new(path).unwrap();
+
+    let half = mesh.simplified(0.5);
+    assert_well_formed(&half);
+    assert!(half.vertices.len() * 5 < mesh.vertices.len() * 3, "{} of {} vertices left", half.vertices.len(), mesh.vertices.len());
+
+    // the UV seams' junctions don't go, so it can't get much coarser than that
+    let quarter = half.simplified(0.5);
+    assert_well_formed(&quarter);
+    assert!(quarter.vertices.len() <= half.vertices.len());
+}
+
+#[test]
+fn generates_coarser_and_coarser_buffers() {
+    let Some((device, _queue)) = common::gpu() else {
+        return;
+    };
+
+    let mesh = grid(32);
+    let vertex_count = mesh.vertices.len();
+    let lods = PlyGeoBuffers::generate_lods(&device, mesh, &[1.0, 0.5, 0.25]);
+
+    assert_eq!(lods.len(), 3);
+    assert_eq!(lods[0].vcount, vertex_count);
+
+    for (lod, ratio) in lods.iter().zip([1.0, 0.5, 0.25]) {
+        assert!(lod.vcount as f32 <= (vertex_count as f32 * ratio).ceil(), "{} vertices at {}", lod.vcount, ratio);
+        assert_eq!(lod.index_format, wgpu::IndexFormat::Uint16);
+    }
+}
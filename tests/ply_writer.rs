@@ -0,0 +1,71 @@
+//! Writing PLY files in batches, read back by the loader and the streamer
+
+use std::path::PathBuf;
+use clockutils::{PlyMesh, PlyMeshStreamer, PlyWriter, Vtx3UV};
+
+/// A file in the temp folder, named after the test so that parallel tests don't collide
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("wgpu-clocks-{}-{}.ply", name, std::process::id()))
+}
+
+fn vertex(x: f32, y: f32, z: f32, s: f32, t: f32) -> Vtx3UV {
+    Vtx3UV { pos: glam::Vec3::new(x, y, z), uv: glam::Vec2::new(s, t) }
+}
+
+/// A unit quad with awkward floats, in two triangles
+fn quad() -> (Vec<Vtx3UV>, Vec<u32>) {
+    (
+        vec![
+            vertex(0.0, 0.0, 0.1, 0.0, 0.0),
+            vertex(1.0 / 3.0, 0.0, -1e-7, 1.0, 0.0),
+            vertex(1.0 / 3.0, 2.5e6, 0.0, 1.0, 1.0),
+            vertex(0.0, 2.5e6, f32::MIN_POSITIVE, 0.0, 1.0)
+        ],
+        vec![0, 1, 2, 0, 2, 3]
+    )
+}
+
+#[test]
+fn batches_read_back_exactly() {
+    let path = temp_path("writer-round-trip");
+    let (vertices, indices) = quad();
+
+    let mut writer = PlyWriter::new(&path, vertices.len(), indices.len() / 3).unwrap();
+    writer.write_vertex_batch(&vertices[..1]).unwrap();
+    writer.write_vertex_batch(&vertices[1..]).unwrap();
+    writer.write_face_batch(&indices[..3]).unwrap();
+    writer.write_face_batch(&indices[3..]).unwrap();
+    writer.finalize().unwrap();
+
+    let mesh = PlyMesh::new(path.to_str().unwrap()).unwrap();
+    assert_eq!(mesh.vertices, vertices);
+    assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3]);
+
+    // the streamer takes it as well
+    let (streamer, loader) = PlyMeshStreamer::new(path.to_str().unwrap(), 3).unwrap();
+    loader.join().unwrap();
+    assert_eq!(streamer.poll().concat(), vertices);
+    assert_eq!(streamer.poll_indices(), Some(indices));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn counts_must_match_the_header() {
+    let path = temp_path("writer-counts");
+    let (vertices, indices) = quad();
+
+    let mut writer = PlyWriter::new(&path, 3, 1).unwrap();
+    assert!(writer.write_face_batch(&indices[..3]).is_err(), "faces before the vertices");
+    assert!(writer.write_vertex_batch(&vertices).is_err(), "too many vertices");
+
+    writer.write_vertex_batch(&vertices[..3]).unwrap();
+    assert!(writer.write_face_batch(&indices[..2]).is_err(), "not a triangle");
+    assert!(writer.write_face_batch(&[0, 1, 3]).is_err(), "index out of range");
+    assert!(writer.write_face_batch(&indices).is_err(), "too many faces");
+
+    // nothing of the refused batches went in
+    assert!(writer.finalize().is_err(), "the face is missing");
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,86 @@
+//! SVG export of the polar clock, checked for well-formedness and for the expected shapes
+
+use chrono::{FixedOffset, NaiveDate};
+use clockutils::clocks::polar::{calc_angles_and_positions, export_svg, svg_document, AnglesAndPositions, ColorCombo};
+
+const COLORS: ColorCombo = ColorCombo {
+    hour:       0x171738_FF,
+    minute:     0x2E1760_FF,
+    second:     0x3423A6_80,
+    disk:       0xFFFFFF_FF,
+    background: 0x000000_FF
+};
+
+fn at(hour: u32, minute: u32, second: u32) -> AnglesAndPositions {
+    let time = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+        .and_hms_opt(hour, minute, second).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(0).unwrap()).unwrap();
+
+    calc_angles_and_positions(&time)
+}
+
+fn count(document: &roxmltree::Document, tag: &str) -> usize {
+    document.descendants().filter(|node| node.has_tag_name(tag)).count()
+}
+
+#[test]
+fn is_well_formed_svg() {
+    let svg = svg_document(&at(10, 10, 30), &COLORS, 1280, 720, true);
+    let document = roxmltree::Document::parse(&svg).expect("not well-formed XML");
+
+    let root = document.root_element();
+    assert_eq!(root.tag_name().name(), "svg");
+    assert_eq!(root.tag_name().namespace(), Some("http://www.w3.org/2000/svg"));
+    assert_eq!(root.attribute("version"), Some("1.1"));
+    assert_eq!(root.attribute("width"), Some("1280"));
+
+    // the extent fits the height of a wide window, the sides show more
+    let view_box: Vec<f32> = root.attribute("viewBox").unwrap().split(' ').map(|n| n.parse().unwrap()).collect();
+    assert_eq!(view_box.len(), 4);
+    assert!((view_box[3] - 32.0).abs() < 1e-3);
+    assert!((view_box[2] - 32.0 * 1280.0 / 720.0).abs() < 1e-3);
+
+    assert_eq!(count(&document, "rect"),   1); // background
+    assert_eq!(count(&document, "path"),   3); // arcs
+    assert_eq!(count(&document, "circle"), 3); // disks
+}
+
+#[test]
+fn colors_are_encoded_with_an_opacity() {
+    let svg = svg_document(&at(10, 10, 30), &COLORS, 512, 512, true);
+    let document = roxmltree::Document::parse(&svg).unwrap();
+
+    let seconds = document.descendants().rfind(|node| node.has_tag_name("path")).unwrap();
+    // sRGB encoded, like on screen
+    assert_eq!(seconds.attribute("stroke"), Some("#7D68D3"));
+    assert_eq!(seconds.attribute("stroke-opacity"), Some("0.502"));
+}
+
+#[test]
+fn empty_and_full_arcs_are_circles() {
+    // 00:00:00, all the arcs are empty, only their rounded ends show
+    let svg = svg_document(&at(0, 0, 0), &COLORS, 512, 512, false);
+    let document = roxmltree::Document::parse(&svg).unwrap();
+
+    assert_eq!(count(&document, "rect"),   0);
+    assert_eq!(count(&document, "path"),   0);
+    assert_eq!(count(&document, "circle"), 6);
+
+    let full = AnglesAndPositions { seconds_angle: std::f32::consts::TAU, ..at(0, 0, 0) };
+    let svg = svg_document(&full, &COLORS, 512, 512, false);
+    let document = roxmltree::Document::parse(&svg).unwrap();
+
+    let ring = document.descendants().find(|node| node.has_tag_name("circle") && node.attribute("fill") == Some("none"));
+    assert_eq!(ring.and_then(|node| node.attribute("r")), Some("13.0000"));
+}
+
+#[test]
+fn writes_the_file() {
+    let path = std::env::temp_dir().join(format!("wgpu-clocks-polar-{}.svg", std::process::id()));
+
+    export_svg(&path, &at(10, 10, 30), &COLORS, 800, 600, true).unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(roxmltree::Document::parse(&written).is_ok());
+}
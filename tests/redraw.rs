@@ -0,0 +1,176 @@
+//! Skipping unchanged frames: when the runner draws regardless of the clock's version, how soon it draws the next one,
+//! what it does when the surface fails it or the window is hidden,
+//! and the versions of the digital and polar clocks, which stay the same between visible changes
+
+use std::time::{Duration, Instant};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta};
+use clockutils::{RedrawPolicy, RedrawTracker, SurfaceRecovery, WindowVisibility};
+use winit::event_loop::ControlFlow;
+use clockutils::clocks::{digital, polar};
+
+/// 12:34:56 and the given milliseconds, UTC
+fn at(millis: i64) -> DateTime<FixedOffset> {
+    NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+        .and_hms_opt(12, 34, 56).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(0).unwrap()).unwrap() + TimeDelta::milliseconds(millis)
+}
+
+/// A tracker past its first frame, which is always drawn, presented with version 1
+fn presented(now: Instant) -> RedrawTracker {
+    let mut tracker = RedrawTracker::new();
+    assert!(tracker.should_draw(Some(1), now));
+    tracker.presented(Some(1), now);
+
+    tracker
+}
+
+#[test]
+fn unchanged_frames_are_skipped() {
+    let now = Instant::now();
+    let mut tracker = presented(now);
+
+    tracker.request();
+    assert!(!tracker.should_draw(Some(1), now + Duration::from_millis(33)));
+
+    tracker.request();
+    assert!(tracker.should_draw(Some(2), now + Duration::from_millis(66)));
+
+    // unversioned clocks draw every frame
+    tracker.presented(None, now);
+    tracker.request();
+    assert!(tracker.should_draw(None, now));
+}
+
+#[test]
+fn invalidated_windows_are_redrawn() {
+    let now = Instant::now();
+    let mut tracker = presented(now);
+
+    // resized, uncovered etc.
+    tracker.invalidate();
+    tracker.request();
+    assert!(tracker.should_draw(Some(1), now));
+
+    // until presented
+    assert!(tracker.should_draw(Some(1), now));
+    tracker.presented(Some(1), now);
+    tracker.request();
+    assert!(!tracker.should_draw(Some(1), now));
+
+    // the windowing system asking for a redraw, e.g. an expose
+    assert!(tracker.should_draw(Some(1), now));
+}
+
+#[test]
+fn skipping_has_a_limit() {
+    let now = Instant::now();
+    let mut tracker = presented(now);
+
+    tracker.request();
+    assert!(!tracker.should_draw(Some(1), now + RedrawTracker::MAX_SKIPPED - Duration::from_millis(1)));
+
+    tracker.request();
+    assert!(tracker.should_draw(Some(1), now + RedrawTracker::MAX_SKIPPED));
+}
+
+#[test]
+fn policies_pace_the_frames() {
+    let now  = Instant::now();
+    let idle = Duration::from_millis(33);
+    let half = Duration::from_millis(500);
+
+    // continuous until idle
+    assert_eq!(RedrawPolicy::Continuous.control_flow(now, false, idle, None), None);
+    assert_eq!(RedrawPolicy::Continuous.control_flow(now, true, idle, None), Some(ControlFlow::WaitUntil(now + idle)));
+
+    // busy or not
+    for busy in [false, true] {
+        assert_eq!(RedrawPolicy::EveryDuration(half).control_flow(now, busy, idle, None), Some(ControlFlow::WaitUntil(now + half)));
+        assert_eq!(RedrawPolicy::OnEvent.control_flow(now, busy, idle, None), Some(ControlFlow::Wait));
+    }
+
+    // the clock's deadlines come first, one already passed is right away
+    let deadline = now + Duration::from_millis(120);
+    assert_eq!(RedrawPolicy::EveryDuration(half).control_flow(now, false, idle, Some(deadline)), Some(ControlFlow::WaitUntil(deadline)));
+    assert_eq!(RedrawPolicy::OnEvent.control_flow(now, false, idle, Some(deadline)), Some(ControlFlow::WaitUntil(deadline)));
+    assert_eq!(RedrawPolicy::OnEvent.control_flow(deadline, false, idle, Some(now)), Some(ControlFlow::WaitUntil(deadline)));
+}
+
+#[test]
+fn only_running_out_of_memory_is_fatal() {
+    assert_eq!(SurfaceRecovery::for_error(&wgpu::SurfaceError::Lost),        SurfaceRecovery::Reconfigure);
+    assert_eq!(SurfaceRecovery::for_error(&wgpu::SurfaceError::Outdated),    SurfaceRecovery::Reconfigure);
+    assert_eq!(SurfaceRecovery::for_error(&wgpu::SurfaceError::Timeout),     SurfaceRecovery::SkipFrame);
+    assert_eq!(SurfaceRecovery::for_error(&wgpu::SurfaceError::OutOfMemory), SurfaceRecovery::Abort);
+}
+
+#[test]
+fn hidden_windows_are_resized_when_back() {
+    let mut visibility = WindowVisibility::new();
+    assert!(!visibility.is_hidden());
+    assert!(!visibility.take_pending_resize());
+
+    // minimized, then restored by a resize, which the runner handles right away
+    assert!(!visibility.resized(0, 0));
+    assert!(visibility.is_hidden());
+    assert!(!visibility.take_pending_resize());
+    assert!(visibility.resized(640, 480));
+    assert!(!visibility.is_hidden());
+    assert!(!visibility.take_pending_resize());
+
+    // covered and uncovered at the same size, resized before the next frame, once
+    visibility.occluded(true);
+    assert!(visibility.is_hidden());
+    assert!(!visibility.take_pending_resize());
+    visibility.occluded(false);
+    assert!(visibility.take_pending_resize());
+    assert!(!visibility.take_pending_resize());
+
+    // minimized while covered, hidden until both are over
+    visibility.occluded(true);
+    assert!(!visibility.resized(640, 0));
+    visibility.occluded(false);
+    assert!(visibility.is_hidden());
+    assert!(visibility.resized(800, 600));
+    assert!(!visibility.take_pending_resize());
+}
+
+#[test]
+fn digital_versions_follow_the_leds() {
+    let version = |now: &DateTime<FixedOffset>, style: digital::ColonStyle, second: Option<i64>| {
+        let data = digital::calculate_clock_data(now, false, 0, style, &digital::INDICATOR_LAYOUT);
+        digital::content_version(&data, second)
+    };
+
+    let blink = digital::ColonStyle::Blink;
+
+    // the colon blinks on the half second, nothing else changes within the minute
+    assert_eq!(version(&at(100), blink, None), version(&at(400), blink, None));
+    assert_ne!(version(&at(400), blink, None), version(&at(600), blink, None));
+    assert_eq!(version(&at(600), blink, None), version(&at(2600), blink, None));
+
+    // a solid colon, the same frame until the minute changes
+    let solid = digital::ColonStyle::Solid;
+    assert_eq!(version(&at(0), solid, None), version(&at(3_999), solid, None));
+    assert_ne!(version(&at(0), solid, None), version(&at(4_000), solid, None));
+
+    // with the second, every second
+    assert_ne!(version(&at(0), solid, Some(at(0).timestamp())), version(&at(1_000), solid, Some(at(1_000).timestamp())));
+}
+
+#[test]
+fn polar_angles_change_by_the_pixel() {
+    let quantized = |millis: i64, density: f32| polar::quantized_angles(&polar::calc_angles_and_positions(&at(millis)), density);
+
+    // 16 pixels per unit, the seconds' ring has a radius of 208 pixels: a pixel of its sweep takes ~46ms
+    assert_eq!(quantized(0, 16.0), quantized(10, 16.0));
+    assert_ne!(quantized(0, 16.0), quantized(100, 16.0));
+
+    // the sweep's pixels one after the other, none left out
+    let seconds: Vec<u32> = (0..25).map(|step| quantized(step * 40, 16.0)[2]).collect();
+    assert!(seconds.windows(2).all(|pair| pair[1] - pair[0] <= 1), "{:?}", seconds);
+    assert_eq!(seconds[24] - seconds[0], 21, "{:?}", seconds);
+
+    // the hours' ring barely moves within a second
+    assert_eq!(quantized(0, 16.0)[0], quantized(999, 16.0)[0]);
+}
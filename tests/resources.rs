@@ -0,0 +1,55 @@
+//! Search order of the resources folder, checked against made-up folder layouts instead of the real filesystem
+
+use std::path::{Path, PathBuf};
+use clockutils::{search_resource_folder, ResourceLocation};
+
+fn candidates() -> Vec<(ResourceLocation, PathBuf)> {
+    vec![
+        (ResourceLocation::Override,         PathBuf::from("/override")),
+        (ResourceLocation::Environment,      PathBuf::from("/env")),
+        (ResourceLocation::WorkingDirectory, PathBuf::from("/cwd/resources")),
+        (ResourceLocation::Executable,       PathBuf::from("/bin/resources")),
+        (ResourceLocation::DataDirectory,    PathBuf::from("/data/wgpu-clocks"))
+    ]
+}
+
+/// Only the given folders exist
+fn layout(existing: &'static [&'static str]) -> impl Fn(&Path) -> bool {
+    move |path| existing.iter().any(|folder| Path::new(folder) == path)
+}
+
+#[test]
+fn override_comes_first() {
+    let found = search_resource_folder("digital", &candidates(), layout(&["/override/digital", "/cwd/resources/digital"]));
+    assert_eq!(found, Ok((PathBuf::from("/override/digital"), ResourceLocation::Override)));
+}
+
+#[test]
+fn environment_before_default_locations() {
+    let found = search_resource_folder("digital", &candidates(), layout(&["/env/digital", "/bin/resources/digital"]));
+    assert_eq!(found, Ok((PathBuf::from("/env/digital"), ResourceLocation::Environment)));
+}
+
+#[test]
+fn working_directory_before_executable() {
+    let found = search_resource_folder("polar", &candidates(), layout(&["/cwd/resources/polar", "/bin/resources/polar"]));
+    assert_eq!(found, Ok((PathBuf::from("/cwd/resources/polar"), ResourceLocation::WorkingDirectory)));
+}
+
+#[test]
+fn data_directory_last() {
+    let found = search_resource_folder("portal", &candidates(), layout(&["/data/wgpu-clocks/portal", "/cwd/resources/polar"]));
+    assert_eq!(found, Ok((PathBuf::from("/data/wgpu-clocks/portal"), ResourceLocation::DataDirectory)));
+}
+
+#[test]
+fn failure_lists_every_path_tried() {
+    let tried = search_resource_folder("mcounter", &candidates(), layout(&["/cwd/resources/polar"])).unwrap_err();
+    assert_eq!(tried, [
+        "/override/mcounter",
+        "/env/mcounter",
+        "/cwd/resources/mcounter",
+        "/bin/resources/mcounter",
+        "/data/wgpu-clocks/mcounter"
+    ].map(PathBuf::from));
+}
@@ -0,0 +1,86 @@
+//! The runner settings: their defaults, the ones given on the command line, and the present modes the surface gets
+
+use clockutils::{cli::Cli, supported_present_mode, widget::{ScreenCorner, WidgetModifier, WidgetOptions}, RunOptions};
+
+#[test]
+fn defaults_leave_the_choices_to_the_system() {
+    let options = RunOptions::default();
+
+    assert_eq!(options.present_mode, wgpu::PresentMode::Fifo);
+    assert_eq!(options.power_preference, wgpu::PowerPreference::default());
+    assert!(options.required_limits.is_none(), "the clocks' own limits");
+    assert_eq!(options.surface_usage, wgpu::TextureUsages::RENDER_ATTACHMENT);
+    assert!(options.enable_fullscreen_toggle);
+}
+
+#[test]
+fn widgets_stay_out_of_fullscreen() {
+    let cli  = Cli::new("test", "Test clock");
+    let args = |args: &[&str]| cli.try_parse(args.iter().map(|arg| arg.to_string())).unwrap().0.run_options();
+
+    assert!(args(&[]).enable_fullscreen_toggle);
+    assert!(args(&["--fullscreen"]).enable_fullscreen_toggle);
+    assert!(!args(&["--widget"]).enable_fullscreen_toggle);
+}
+
+#[test]
+fn widgets_click_through_only_when_asked() {
+    let cli  = Cli::new("test", "Test clock");
+    let args = |args: &[&str]| cli.try_parse(args.iter().map(|arg| arg.to_string())).unwrap().0.run_options();
+
+    assert_eq!(args(&[]).widget, None);
+    assert_eq!(args(&["--widget"]).widget, Some(WidgetOptions { modifier: WidgetModifier::Alt, click_through: false }));
+    assert_eq!(
+        args(&["--widget", "--click-through", "--widget-key", "ctrl"]).widget,
+        Some(WidgetOptions { modifier: WidgetModifier::Ctrl, click_through: true })
+    );
+
+    // not a widget without --widget
+    assert_eq!(args(&["--click-through"]).widget, None);
+    assert!(args(&["--widget"]).transparent);
+}
+
+#[test]
+fn power_flag() {
+    let cli  = Cli::new("test", "Test clock");
+    let args = |args: &[&str]| cli.try_parse(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(args(&[]).unwrap().0.run_options().power_preference, wgpu::PowerPreference::default());
+    assert_eq!(args(&["--power", "low"]).unwrap().0.run_options().power_preference, wgpu::PowerPreference::LowPower);
+    assert_eq!(args(&["--power=high"]).unwrap().0.run_options().power_preference, wgpu::PowerPreference::HighPerformance);
+    assert!(args(&["--power", "medium"]).is_err());
+
+    // the rest of the options stay as they were
+    let options = args(&["--power", "low", "--vsync", "off"]).unwrap().0.run_options();
+    assert_eq!(options.present_mode, wgpu::PresentMode::AutoNoVsync);
+    assert!(options.required_limits.is_none());
+}
+
+#[test]
+fn unsupported_present_modes_fall_back_to_fifo() {
+    let supported = [wgpu::PresentMode::Fifo, wgpu::PresentMode::Immediate];
+
+    assert_eq!(supported_present_mode(wgpu::PresentMode::Immediate, &supported), wgpu::PresentMode::Immediate);
+    assert_eq!(supported_present_mode(wgpu::PresentMode::Mailbox, &supported), wgpu::PresentMode::Fifo);
+
+    // left to wgpu, whatever the surface lists
+    assert_eq!(supported_present_mode(wgpu::PresentMode::AutoNoVsync, &[]), wgpu::PresentMode::AutoNoVsync);
+}
+
+#[test]
+fn widgets_pin_to_corners() {
+    let cli  = Cli::new("test", "Test clock");
+    let args = |args: &[&str]| cli.try_parse(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(args(&[]).unwrap().0.run_options().corner, None);
+    assert_eq!(args(&["--widget", "--corner", "bottom-right"]).unwrap().0.run_options().corner, Some(ScreenCorner::BottomRight));
+    assert!(args(&["--corner", "middle"]).is_err());
+
+    // a 200x100 window on the second of two 1920x1080 monitors, side by side
+    let place = |corner: ScreenCorner| corner.position((1920, 0), (1920, 1080), (200, 100), 16);
+
+    assert_eq!(place(ScreenCorner::TopLeft),     (1936, 16));
+    assert_eq!(place(ScreenCorner::TopRight),    (3624, 16));
+    assert_eq!(place(ScreenCorner::BottomLeft),  (1936, 964));
+    assert_eq!(place(ScreenCorner::BottomRight), (3624, 964));
+}
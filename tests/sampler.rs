@@ -0,0 +1,40 @@
+//! Samplers with a clamped LOD range, like the portal's render texture one.
+//!
+//! Creating them needs a GPU, skipped when there's none
+
+mod common;
+
+use clockutils::{clamp_lod_range, BasicFilteringSampler};
+
+#[test]
+fn lod_ranges_are_clamped() {
+    assert_eq!(clamp_lod_range(0.0, 0.0), (0.0, 0.0));
+    assert_eq!(clamp_lod_range(1.0, 4.0), (1.0, 4.0));
+    // negative minimum
+    assert_eq!(clamp_lod_range(-2.0, 3.0), (0.0, 3.0));
+    // reversed
+    assert_eq!(clamp_lod_range(2.0, 1.0), (2.0, 2.0));
+    assert_eq!(clamp_lod_range(f32::NAN, f32::NAN), (0.0, 0.0));
+}
+
+#[test]
+fn clamped_samplers_pass_validation() {
+    let Some((device, _queue)) = common::gpu() else {
+        return;
+    };
+
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label:   None,
+        entries: &[ BasicFilteringSampler::default_layout_entry(0) ]
+    });
+
+    // wgpu panics on an invalid descriptor, the reversed range included
+    for sampler in [
+        BasicFilteringSampler::new_fixed_lod(&device, 0.0),
+        BasicFilteringSampler::new_fixed_lod(&device, 2.0),
+        BasicFilteringSampler::new_with_lod_clamp(&device, 1.0, 3.0),
+        BasicFilteringSampler::new_with_lod_clamp(&device, 3.0, -1.0)
+    ] {
+        device.create_bind_group(&wgpu::BindGroupDescriptor { label: None, layout: &layout, entries: &[ sampler.get_entry(0) ] });
+    }
+}
@@ -0,0 +1,28 @@
+//! Scale factor changes: the window keeps its logical size, the drawspace follows the physical one
+
+use clockutils::{rescaled_size, DrawspaceScales};
+use winit::dpi::PhysicalSize;
+
+#[test]
+fn logical_size_is_kept() {
+    // 100% to 150%, and back
+    assert_eq!(rescaled_size(PhysicalSize::new(800, 600), 1.0, 1.5), PhysicalSize::new(1200, 900));
+    assert_eq!(rescaled_size(PhysicalSize::new(1200, 900), 1.5, 1.0), PhysicalSize::new(800, 600));
+
+    // rounded to whole pixels
+    assert_eq!(rescaled_size(PhysicalSize::new(801, 601), 1.0, 1.25), PhysicalSize::new(1001, 751));
+    assert_eq!(rescaled_size(PhysicalSize::new(0, 0), 1.0, 2.0), PhysicalSize::new(0, 0));
+}
+
+#[test]
+fn density_follows_the_pixels() {
+    let extent = glam::Vec2::new(4.0, 3.0);
+    let before = DrawspaceScales::new(glam::Vec2::new(800.0, 600.0), extent);
+
+    let size  = rescaled_size(PhysicalSize::new(800, 600), 1.0, 1.5);
+    let after = DrawspaceScales::new(glam::Vec2::new(size.width as f32, size.height as f32), extent);
+
+    // as many pixels more per drawing unit as the scale grew, what's drawn covers the same part of the window
+    assert!((after.density - before.density * 1.5).abs() < 1e-4);
+    assert_eq!(after.scale, before.scale);
+}
@@ -0,0 +1,45 @@
+//! Screenshots: frames of either surface channel order saved as PNG files, and where they go by default.
+//!
+//! Needs a GPU (any backend) for the captures, skipped when no adapter is found
+
+mod common;
+
+use chrono::{FixedOffset, NaiveDate};
+use clockutils::{capture_frame_to_png, screenshot_path};
+
+#[test]
+fn named_after_the_time() {
+    let now = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+        .and_hms_opt(12, 34, 56).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(3600).unwrap()).unwrap();
+
+    assert_eq!(screenshot_path(&now), "screenshot-20240309-123456.png");
+}
+
+#[test]
+fn captures_match_the_cleared_color() {
+    let Some((device, queue)) = common::gpu() else {
+        return;
+    };
+
+    for format in [wgpu::TextureFormat::Bgra8Unorm, wgpu::TextureFormat::Rgba8Unorm] {
+        let (texture, view) = common::readable_target(&device, (40, 30), format);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        common::render_pass(&mut encoder, &view, wgpu::Color { r: 1.0, g: 0.0, b: 0.0, a: 1.0 });
+        queue.submit(std::iter::once(encoder.finish()));
+
+        // a part of the texture only, rows narrower than the copy alignment
+        let path = std::env::temp_dir().join(format!("wgpu-clocks-screenshot-{:?}.png", format));
+        pollster::block_on(capture_frame_to_png(&device, &queue, &texture, 25, 10, path.to_str().unwrap())).unwrap();
+
+        let image = image::open(&path).unwrap().to_rgba8();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(image.dimensions(), (25, 10));
+        assert!(image.pixels().all(|pixel| pixel.0 == [255, 0, 0, 255]), "{:?}", format);
+
+        // larger than the texture
+        assert!(pollster::block_on(capture_frame_to_png(&device, &queue, &texture, 41, 30, path.to_str().unwrap())).is_err());
+    }
+}
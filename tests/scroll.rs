@@ -0,0 +1,59 @@
+//! Wheel deltas as the clocks get them, whatever winit reports, and the timezone nudged by horizontal scrolling.
+//! Pinned down so that a winit upgrade flipping a direction shows up here rather than in the portal's orbit
+
+use chrono::{FixedOffset, NaiveDate, NaiveTime, Timelike};
+use clockutils::{scroll_delta_in_lines, SCROLL_PIXELS_PER_LINE, time::{shift_timezone, ControllableTime, TimeSource}};
+use winit::{dpi::PhysicalPosition, event::MouseScrollDelta};
+
+fn offset(hours: i32, minutes: i32) -> FixedOffset {
+    FixedOffset::east_opt(hours * 3600 + minutes * 60).unwrap()
+}
+
+#[test]
+fn wheel_notches() {
+    // winit: positive y moves the content down, i.e. the wheel went up
+    assert_eq!(scroll_delta_in_lines(MouseScrollDelta::LineDelta(0.0, 1.0)),  (0.0,  1.0));
+    assert_eq!(scroll_delta_in_lines(MouseScrollDelta::LineDelta(0.0, -2.0)), (0.0, -2.0));
+
+    // winit: positive x moves the content right, i.e. the wheel was tilted left
+    assert_eq!(scroll_delta_in_lines(MouseScrollDelta::LineDelta(1.0, 0.0)),  (-1.0, 0.0));
+    assert_eq!(scroll_delta_in_lines(MouseScrollDelta::LineDelta(-1.0, 0.0)), ( 1.0, 0.0));
+}
+
+#[test]
+fn pixel_deltas_take_the_same_directions() {
+    let line = SCROLL_PIXELS_PER_LINE;
+
+    for (x, y) in [(0.0, 1.0), (0.0, -1.0), (1.0, 0.0), (-1.0, 0.0), (0.5, -0.25)] {
+        let lines  = scroll_delta_in_lines(MouseScrollDelta::LineDelta(x as f32, y as f32));
+        let pixels = scroll_delta_in_lines(MouseScrollDelta::PixelDelta(PhysicalPosition::new(x * line, y * line)));
+
+        assert_eq!(lines, pixels, "({}, {})", x, y);
+    }
+}
+
+#[test]
+fn timezone_shifts_are_kept_in_range() {
+    assert_eq!(shift_timezone(offset(5, 30), 15), offset(5, 45));
+    assert_eq!(shift_timezone(offset(0, 0), -15), offset(0, -15));
+    assert_eq!(shift_timezone(offset(13, 45), 30), offset(14, 0));
+    assert_eq!(shift_timezone(offset(-14, 0), -15), offset(-14, 0));
+}
+
+#[test]
+fn another_timezone_shows_the_same_instant() {
+    let date  = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+    let start = NaiveTime::from_hms_opt(10, 8, 42).unwrap();
+
+    let mut time = ControllableTime::new(TimeSource::new(Some(offset(0, 0)), Some(date), Some(start), 0.0));
+    assert_eq!(time.timezone(), Some(offset(0, 0)));
+
+    time.set_timezone(offset(1, 15));
+    assert_eq!(time.timezone(), Some(offset(1, 15)));
+    assert_eq!((time.now().hour(), time.now().minute(), time.now().second()), (11, 23, 42));
+
+    // paused as well
+    time.pause();
+    time.set_timezone(offset(-2, 0));
+    assert_eq!((time.now().hour(), time.now().minute(), time.now().second()), (8, 8, 42));
+}
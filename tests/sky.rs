@@ -0,0 +1,80 @@
+//! The portal clock's sky: the sun's altitude and the colors it's mapped to
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use clockutils::{
+    clocks::portal::{parse_location, sky_color_from_altitude, sky_gradient, solar_altitude, timezone_longitude, DAY_SKY, NIGHT_SKY, SUNSET_SKY},
+    u32_col_to_wgpu_col_linear
+};
+
+fn at(month: u32, day: u32, hour: u32, minute: u32, offset_hours: i32) -> DateTime<FixedOffset> {
+    NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+        .and_hms_opt(hour, minute, 0).unwrap()
+        .and_local_timezone(FixedOffset::east_opt(offset_hours * 3600).unwrap()).unwrap()
+}
+
+fn assert_near(actual: f32, expected: f32, tolerance: f32) {
+    assert!((actual - expected).abs() <= tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+}
+
+#[test]
+fn noon_altitude_follows_the_seasons() {
+    // Greenwich, solar noon is a few minutes off 12:00 UTC (equation of time)
+    assert_near(solar_altitude(&at(3, 20, 12, 7, 0),  51.48, 0.0), 90.0 - 51.48, 1.0);
+    assert_near(solar_altitude(&at(6, 20, 12, 2, 0),  51.48, 0.0), 90.0 - 51.48 + 23.44, 1.0);
+    assert_near(solar_altitude(&at(12, 21, 11, 58, 0), 51.48, 0.0), 90.0 - 51.48 - 23.44, 1.0);
+}
+
+#[test]
+fn midnight_is_below_the_horizon() {
+    assert!(solar_altitude(&at(6, 20, 0, 0, 0), 51.48, 0.0) < -10.0);
+    // the equator at the equinox: straight down
+    assert_near(solar_altitude(&at(3, 20, 0, 7, 0), 0.0, 0.0), -90.0, 1.5);
+}
+
+#[test]
+fn local_time_is_taken_as_the_instant_it_is() {
+    // 14:00 in UTC+2 is noon in UTC, the same altitude from the same place
+    assert_near(
+        solar_altitude(&at(3, 20, 14, 7, 2), 51.48, 0.0),
+        solar_altitude(&at(3, 20, 12, 7, 0), 51.48, 0.0),
+        1e-3
+    );
+
+    assert_eq!(timezone_longitude(&at(3, 20, 12, 0, 2)), 30.0);
+    assert_eq!(timezone_longitude(&at(3, 20, 12, 0, -5)), -75.0);
+}
+
+#[test]
+fn colors_at_the_stops() {
+    assert_eq!(sky_color_from_altitude(-45.0), u32_col_to_wgpu_col_linear(NIGHT_SKY));
+    assert_eq!(sky_color_from_altitude(2.0),   u32_col_to_wgpu_col_linear(SUNSET_SKY));
+    assert_eq!(sky_color_from_altitude(60.0),  u32_col_to_wgpu_col_linear(DAY_SKY));
+}
+
+#[test]
+fn sunset_is_orange_in_between() {
+    let color = sky_color_from_altitude(1.0);
+    assert!(color.r > color.g && color.g > color.b);
+
+    // the twilight fades to night
+    let twilight = sky_color_from_altitude(-3.0);
+    assert!(twilight.r < color.r && twilight.r > u32_col_to_wgpu_col_linear(NIGHT_SKY).r);
+}
+
+#[test]
+fn zenith_is_ahead_of_the_horizon() {
+    let (horizon, zenith) = sky_gradient(2.0);
+    assert_eq!(horizon, u32_col_to_wgpu_col_linear(SUNSET_SKY));
+    assert_eq!(zenith, sky_color_from_altitude(17.0));
+
+    let (horizon, zenith) = sky_gradient(-40.0);
+    assert_eq!(horizon, zenith);
+}
+
+#[test]
+fn locations() {
+    assert_eq!(parse_location("51.5,-0.1"), Some((51.5, -0.1)));
+    assert_eq!(parse_location(" -33.9 , 151.2 "), Some((-33.9, 151.2)));
+    assert_eq!(parse_location("91,0"), None);
+    assert_eq!(parse_location("51.5"), None);
+}
@@ -0,0 +1,157 @@
+//! Per-draw parameters via push constants and via the uniform buffer fallback.
+//!
+//! The rendering tests need a GPU (any backend), they're skipped when no adapter is found
+
+mod common;
+
+use std::process::Command;
+use clockutils::{headless::read_texture_rgba, params::{fallback_shader_source, slot_stride, SmallParams}, SURFACE_FORMAT};
+
+const SHADER: &str = "
+struct Params {
+    color: vec4<f32>
+}
+
+var<push_constant> params: Params;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return params.color;
+}
+";
+
+#[repr(C, align(16))]
+struct Params {
+    color: [f32; 4]
+}
+
+/// Left half red, right half blue, drawn in one pass with a `set()` before each draw
+fn render_halves(device: &wgpu::Device, queue: &wgpu::Queue, params: &SmallParams<Params>) -> image::RgbaImage {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(params.shader_source(SHADER).into())
+    });
+
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label:                None,
+        bind_group_layouts:   &params.layout_entries(),
+        push_constant_ranges: &params.push_constant_ranges()
+    });
+
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label:         None,
+        layout:        Some(&layout),
+        vertex:        wgpu::VertexState { module: &shader, entry_point: "vs_main", buffers: &[] },
+        fragment:      Some(wgpu::FragmentState {
+            module:      &shader,
+            entry_point: "fs_main",
+            targets:     &[ Some(SURFACE_FORMAT.into()) ]
+        }),
+        primitive:     wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample:   wgpu::MultisampleState::default(),
+        multiview:     None
+    });
+
+    let (target, view) = common::readable_target(device, (64, 64), SURFACE_FORMAT);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut rpass = common::render_pass(&mut encoder, &view, wgpu::Color::BLACK);
+
+        rpass.set_pipeline(&pipeline);
+
+        params.set(&mut rpass, queue, &Params { color: [1.0, 0.0, 0.0, 1.0] });
+        rpass.set_scissor_rect(0, 0, 32, 64);
+        rpass.draw(0..3, 0..1);
+
+        params.set(&mut rpass, queue, &Params { color: [0.0, 0.0, 1.0, 1.0] });
+        rpass.set_scissor_rect(32, 0, 32, 64);
+        rpass.draw(0..3, 0..1);
+    }
+    queue.submit(std::iter::once(encoder.finish()));
+
+    read_texture_rgba(&target, device, queue).unwrap()
+}
+
+fn assert_halves(image: &image::RgbaImage) {
+    assert_eq!(image.get_pixel(8, 32).0,  [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(56, 32).0, [0, 0, 255, 255]);
+}
+
+#[test]
+fn fallback_declares_a_uniform() {
+    let source = fallback_shader_source(SHADER, 2);
+
+    assert!(!source.contains("var<push_constant>"));
+    assert!(source.contains("@group(2) @binding(0) var<uniform> params: Params;"));
+}
+
+#[test]
+fn slots_follow_the_offset_alignment() {
+    assert_eq!(slot_stride(32, 256), 256);
+    assert_eq!(slot_stride(256, 256), 256);
+    assert_eq!(slot_stride(260, 256), 512);
+}
+
+#[test]
+fn falls_back_without_the_feature() {
+    let Some((device, queue)) = common::gpu() else {
+        return;
+    };
+    let params = SmallParams::<Params>::new(&device, wgpu::ShaderStages::FRAGMENT, 0, 2);
+
+    assert!(!params.uses_push_constants());
+    assert!(params.push_constant_ranges().is_empty());
+    assert_eq!(params.layout_entries().len(), 1);
+
+    // each draw gets its own slot
+    assert_halves(&render_halves(&device, &queue, &params));
+}
+
+#[test]
+fn uses_push_constants_when_available() {
+    let Some((device, queue)) = common::gpu_with_push_constants() else {
+        return;
+    };
+    let params = SmallParams::<Params>::new(&device, wgpu::ShaderStages::FRAGMENT, 0, 2);
+
+    assert!(params.uses_push_constants());
+    assert!(params.layout_entries().is_empty());
+
+    assert_halves(&render_halves(&device, &queue, &params));
+}
+
+#[test]
+fn polar_looks_the_same_without_push_constants() {
+    if common::skip_without_gpu() {
+        return;
+    }
+
+    let snapshot = |name: &str, extra: &[&str]| {
+        let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("polar-{}.png", name));
+
+        let output = Command::new(env!("CARGO_BIN_EXE_polar"))
+            .args(["--date", "2024-03-15", "--time", "10:08:42", "--time-scale", "0", "--timezone", "UTC"])
+            .args(extra)
+            .arg("--snapshot")
+            .arg(&path)
+            .current_dir(env!("CARGO_MANIFEST_DIR")) // for the resources folder
+            .output()
+            .expect("Failed to launch the clock");
+
+        assert!(output.status.success(), "{} failed to render:\n{}", name, String::from_utf8_lossy(&output.stderr));
+        image::open(&path).unwrap().to_rgba8()
+    };
+
+    let default  = snapshot("default", &[]);
+    let fallback = snapshot("fallback", &["--no-push-constants"]);
+
+    assert!(default == fallback, "The uniform buffer fallback renders differently");
+}
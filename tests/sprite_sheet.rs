@@ -0,0 +1,24 @@
+//! The sprite sheets dropped onto the mechanical counter: what can hold the ten digits and what's refused
+
+use clockutils::clocks::mcounter::check_sprite_sheet;
+
+#[test]
+fn takes_sheets_the_gpu_can_hold() {
+    assert!(check_sprite_sheet(1280, 200, 8192).is_ok());
+    assert!(check_sprite_sheet(10, 1, 8192).is_ok());
+    assert!(check_sprite_sheet(8192, 8192, 8192).is_ok());
+}
+
+#[test]
+fn refuses_sheets_too_small_for_ten_digits() {
+    assert!(check_sprite_sheet(9, 100, 8192).is_err());
+    assert!(check_sprite_sheet(100, 0, 8192).is_err());
+}
+
+#[test]
+fn refuses_sheets_beyond_the_texture_limit() {
+    let error = check_sprite_sheet(16384, 256, 8192).unwrap_err();
+    assert!(error.contains("16384x256"), "{}", error);
+
+    assert!(check_sprite_sheet(1280, 9000, 8192).is_err());
+}
@@ -0,0 +1,118 @@
+//! Read-write storage buffers: their sizes, their bindings, and a compute shader updating one in place.
+//!
+//! Needs an adapter that can run compute shaders, skipped when there's none
+
+use clockutils::{cast_slice_to_u8_slice, compute::ComputeRunner, MutableStorageBuffer};
+
+// counts up every element of `counters` by its index + 1, then copies them into the output
+const INCREMENT_SHADER: &str = "
+@group(0) @binding(0) var<storage, read_write> counters: array<u32>;
+@group(0) @binding(1) var<storage, read_write> output:   array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3u) {
+    if id.x >= arrayLength(&counters) { return; }
+
+    counters[id.x] += id.x + 1u;
+    output[id.x] = counters[id.x];
+}
+";
+
+#[test]
+fn buffers_are_sized_by_their_elements() {
+    let Ok(runner) = ComputeRunner::new() else {
+        eprintln!("No adapter with compute shaders, skipped");
+        return;
+    };
+
+    let stages = wgpu::ShaderStages::COMPUTE;
+
+    let pairs = MutableStorageBuffer::new::<[f32; 2]>(&runner.device, stages, 3);
+    assert_eq!((pairs.capacity, pairs.buffer.size()), (3, 24));
+
+    // rounded up to whole words, as buffer copies need them
+    let halves = MutableStorageBuffer::new::<u16>(&runner.device, stages, 3);
+    assert_eq!(halves.buffer.size(), 8);
+
+    let entry = MutableStorageBuffer::default_layout_entry(2, &pairs);
+    assert_eq!(entry.binding, 2);
+    assert_eq!(entry.visibility, stages);
+    assert!(matches!(entry.ty, wgpu::BindingType::Buffer {
+        ty: wgpu::BufferBindingType::Storage { read_only: false }, min_binding_size: Some(size), ..
+    } if size.get() == 24));
+}
+
+#[test]
+fn compute_shaders_update_buffers_in_place() {
+    let Ok(runner) = ComputeRunner::new() else {
+        eprintln!("No adapter with compute shaders, skipped");
+        return;
+    };
+    let device = &runner.device;
+
+    let counters = MutableStorageBuffer::new::<u32>(device, wgpu::ShaderStages::COMPUTE, 100);
+    let output   = device.create_buffer(&wgpu::BufferDescriptor {
+        label:              None,
+        size:               400,
+        usage:              wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false
+    });
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label:              None,
+        size:               400,
+        usage:              wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false
+    });
+
+    // the output is as large as the counters, their layout entries are the same
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label:   None,
+        entries: &[ MutableStorageBuffer::default_layout_entry(0, &counters), MutableStorageBuffer::default_layout_entry(1, &counters) ]
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label:   None,
+        layout:  &layout,
+        entries: &[ counters.get_entry(0), wgpu::BindGroupEntry { binding: 1, resource: output.as_entire_binding() } ]
+    });
+
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(INCREMENT_SHADER.into())
+    });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label:       None,
+        layout:      Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[ &layout ],
+            push_constant_ranges: &[]
+        })),
+        module:      &module,
+        entry_point: "main"
+    });
+
+    // starting from 1000 each, incremented twice on the GPU, the second time seeing what the first one left
+    counters.write(&runner.queue, cast_slice_to_u8_slice(&[1000_u32; 100]));
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    for _ in 0..2 {
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+        cpass.set_pipeline(&pipeline);
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.dispatch_workgroups(2, 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, 400);
+    runner.queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = readback.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+
+    let values: Vec<u32> = slice.get_mapped_range()
+        .chunks_exact(4)
+        .map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()))
+        .collect();
+
+    for (index, value) in values.iter().enumerate() {
+        assert_eq!(*value, 1000 + 2 * (index as u32 + 1), "element {}", index);
+    }
+}
@@ -0,0 +1,33 @@
+//! Where the picture-in-picture inset goes: its share of the window, the margin to the edges, and windows too small for either
+
+use clockutils::subview::{subview_rect, Corner, MARGIN};
+
+#[test]
+fn insets_sit_in_their_corners() {
+    let window = (1000, 500);
+    let margin = (500.0 * MARGIN) as u32;
+
+    assert_eq!(subview_rect(window, 0.3, Corner::TopLeft),     (margin, margin, 300, 150));
+    assert_eq!(subview_rect(window, 0.3, Corner::TopRight),    (1000 - 300 - margin, margin, 300, 150));
+    assert_eq!(subview_rect(window, 0.3, Corner::BottomLeft),  (margin, 500 - 150 - margin, 300, 150));
+    assert_eq!(subview_rect(window, 0.3, Corner::BottomRight), (1000 - 300 - margin, 500 - 150 - margin, 300, 150));
+}
+
+#[test]
+fn insets_stay_within_the_window() {
+    for corner in [Corner::TopLeft, Corner::TopRight, Corner::BottomLeft, Corner::BottomRight] {
+        for window in [(1, 1), (7, 3), (640, 480), (3840, 2160)] {
+            for fraction in [0.0, 0.1, 0.5, 1.0, 2.0] {
+                let (x, y, width, height) = subview_rect(window, fraction, corner);
+
+                assert!(width >= 1 && height >= 1, "{:?} {:?} {}", window, corner, fraction);
+                assert!(x + width <= window.0 && y + height <= window.1, "{:?} {:?} {}", window, corner, fraction);
+            }
+        }
+    }
+}
+
+#[test]
+fn the_whole_window_leaves_no_margin() {
+    assert_eq!(subview_rect((640, 480), 1.0, Corner::BottomRight), (0, 0, 640, 480));
+}
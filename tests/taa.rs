@@ -0,0 +1,115 @@
+//! TAA: the camera's sub-pixel offsets, and the frames adding up in the history.
+//!
+//! The accumulation needs a GPU with push constants, skipped when there's none. Only built with the `taa` feature
+#![cfg(feature = "taa")]
+
+mod common;
+
+use clockutils::{headless::read_texture_rgba, FullscreenQuadPipeline, RenderTexture, TaaPass, HALTON_JITTER, TAA_CURRENT_WEIGHT};
+
+// one-pixel wide stripes, white on the even columns or on the odd ones
+const STRIPES_SHADER: &str = "
+const SIZE: f32 = 16.0;
+
+fn stripe(uv: vec2f, parity: u32) -> vec4f {
+    return select(vec4f(0.0, 0.0, 0.0, 1.0), vec4f(1.0), u32(uv.x * SIZE) % 2u == parity);
+}
+
+@fragment
+fn fs_even(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return stripe(uv, 0u);
+}
+
+@fragment
+fn fs_odd(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return stripe(uv, 1u);
+}
+";
+
+const SIZE: u32 = 16;
+
+#[test]
+fn jitter_stays_within_the_pixel() {
+    for (x, y) in HALTON_JITTER {
+        assert!((-0.5..0.5).contains(&x) && (-0.5..0.5).contains(&y), "({}, {})", x, y);
+    }
+
+    // no offset twice, and centered on the pixel over all of them
+    for (index, offset) in HALTON_JITTER.iter().enumerate() {
+        assert!(!HALTON_JITTER[index + 1..].contains(offset), "{:?} repeats", offset);
+    }
+
+    let sum = HALTON_JITTER.iter().fold((0.0, 0.0), |sum, (x, y)| (sum.0 + x, sum.1 + y));
+    assert!(sum.0.abs() < 0.5 && sum.1.abs() < 0.5, "{:?}", sum);
+}
+
+#[test]
+fn jitter_moves_by_pixels() {
+    let resolution = glam::Vec2::new(800.0, 400.0);
+    let projection = glam::Mat4::perspective_rh(1.0, 2.0, 0.1, 100.0);
+    let point      = glam::Vec4::new(1.0, -2.0, -10.0, 1.0);
+
+    let to_pixels = |matrix: glam::Mat4| {
+        let clip = matrix * point;
+        (clip.truncate().truncate() / clip.w) * resolution * 0.5
+    };
+
+    for index in 0..8 {
+        let moved = to_pixels(TaaPass::jitter(index, resolution) * projection) - to_pixels(projection);
+        let (x, y) = HALTON_JITTER[index as usize];
+
+        assert!((moved - glam::Vec2::new(x, y)).length() < 1e-3, "{}: {:?}", index, moved);
+    }
+
+    // cycling through them
+    assert_eq!(TaaPass::jitter(9, resolution), TaaPass::jitter(1, resolution));
+}
+
+#[test]
+fn frames_accumulate() {
+    let Some((device, queue)) = common::gpu_with_push_constants() else {
+        return;
+    };
+
+    // linear, so that the blend is visible as is
+    let format = wgpu::TextureFormat::Bgra8Unorm;
+
+    let stripes_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(STRIPES_SHADER.into())
+    });
+    let even = FullscreenQuadPipeline::new(&device, &stripes_shader, "fs_even", &[], &[], format);
+    let odd  = FullscreenQuadPipeline::new(&device, &stripes_shader, "fs_odd", &[], &[], format);
+
+    let source = RenderTexture::new((SIZE, SIZE), format, true, &device);
+    let (output, output_view) = common::readable_target(&device, (SIZE, SIZE), format);
+
+    let mut taa = TaaPass::new((SIZE, SIZE), &device, format);
+    let source_bindgroup = taa.source_bindgroup(&source, &device);
+
+    // draws a frame of stripes through the pass, the first column of the result
+    let frame = |stripes: &FullscreenQuadPipeline, taa: &mut TaaPass| {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            stripes.draw(&mut common::render_pass(&mut encoder, &source.view, wgpu::Color::BLACK), &[], &[]);
+        }
+        taa.apply(&mut encoder, &source_bindgroup, &output_view);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let image = read_texture_rgba(&output, &device, &queue).unwrap();
+        (image.get_pixel(4, 8).0[0], image.get_pixel(5, 8).0[0])
+    };
+
+    // the first frame has no history to blend with
+    assert_eq!(frame(&even, &mut taa), (255, 0));
+
+    // the stripes swapped, the history keeps most of them, as both colors are around each pixel
+    let blended = |from: f32, to: f32| ((from + (to - from) * TAA_CURRENT_WEIGHT) * 255.0).round() as i32;
+    let (white, black) = frame(&odd, &mut taa);
+    assert!((white as i32 - blended(1.0, 0.0)).abs() <= 1, "{}", white);
+    assert!((black as i32 - blended(0.0, 1.0)).abs() <= 1, "{}", black);
+
+    // started over, the new frame as it is
+    taa.reset();
+    assert_eq!(frame(&odd, &mut taa), (0, 255));
+}
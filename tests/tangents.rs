@@ -0,0 +1,74 @@
+//! Tangents for normal mapping, on a quad in the XY plane facing +Z
+
+use clockutils::{compute_tangents, Vtx3NUV};
+
+fn vertex(pos: glam::Vec3, normal: glam::Vec3, u: f32, v: f32) -> Vtx3NUV {
+    Vtx3NUV { pos, normal, uv: glam::Vec2::new(u, v) }
+}
+
+/// The unit quad, `uv` maps its corners (counter clockwise from the origin) to UVs
+fn quad(uv: [(f32, f32); 4]) -> (Vec<Vtx3NUV>, Vec<u32>) {
+    let corners = [glam::Vec3::ZERO, glam::Vec3::X, glam::Vec3::new(1.0, 1.0, 0.0), glam::Vec3::Y];
+    let vertices = corners.into_iter().zip(uv).map(|(pos, (u, v))| vertex(pos, glam::Vec3::Z, u, v)).collect();
+
+    (vertices, vec![0, 1, 2, 0, 2, 3])
+}
+
+fn assert_near(actual: glam::Vec4, expected: glam::Vec4) {
+    assert!(actual.abs_diff_eq(expected, 1e-5), "{} is not {}", actual, expected);
+}
+
+#[test]
+fn u_along_x_gives_x() {
+    let (vertices, indices) = quad([(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+
+    for tangent in compute_tangents(&vertices, &indices) {
+        assert_near(tangent, glam::Vec4::new(1.0, 0.0, 0.0, 1.0));
+    }
+}
+
+#[test]
+fn mirrored_u_flips_the_handedness() {
+    let (vertices, indices) = quad([(1.0, 0.0), (0.0, 0.0), (0.0, 1.0), (1.0, 1.0)]);
+
+    for tangent in compute_tangents(&vertices, &indices) {
+        assert_near(tangent, glam::Vec4::new(-1.0, 0.0, 0.0, -1.0));
+    }
+}
+
+#[test]
+fn finely_mapped_faces_arent_degenerate() {
+    // a ten thousandth of the texture across the quad, the determinant (1e-8) is below f32::EPSILON
+    let (vertices, indices) = quad([(0.0, 0.0), (0.0001, 0.0), (0.0001, 0.0001), (0.0, 0.0001)]);
+
+    for tangent in compute_tangents(&vertices, &indices) {
+        assert_near(tangent, glam::Vec4::new(1.0, 0.0, 0.0, 1.0));
+    }
+}
+
+#[test]
+fn degenerate_uvs_fall_back_to_any_perpendicular() {
+    // all corners on one UV, and on a line
+    for uv in [[(0.5, 0.5); 4], [(0.0, 0.0), (0.5, 0.5), (1.0, 1.0), (0.25, 0.25)]] {
+        let (vertices, indices) = quad(uv);
+
+        for tangent in compute_tangents(&vertices, &indices) {
+            assert_near(tangent, glam::Vec3::Z.any_orthonormal_vector().extend(1.0));
+        }
+    }
+}
+
+#[test]
+fn tangents_are_perpendicular_to_tilted_normals() {
+    // the quad's UVs as before, with normals leaning off its plane
+    let (mut vertices, indices) = quad([(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+    for vertex in &mut vertices {
+        vertex.normal = glam::Vec3::new(0.3, -0.4, 1.0).normalize();
+    }
+
+    for (vertex, tangent) in vertices.iter().zip(compute_tangents(&vertices, &indices)) {
+        assert!(vertex.normal.dot(tangent.truncate()).abs() < 1e-5, "{} is not perpendicular to {}", tangent, vertex.normal);
+        assert!((tangent.truncate().length() - 1.0).abs() < 1e-5);
+        assert!(tangent.x > 0.9 && tangent.w == 1.0, "{} leans off +X", tangent);
+    }
+}
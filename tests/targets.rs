@@ -0,0 +1,202 @@
+//! Render targets and pipelines built from a shared `TargetConfig`, switching the sample count at runtime,
+//! and the runner's multisampled frame.
+//!
+//! The rendering tests need a GPU (any backend), they're skipped when no adapter is found
+
+mod common;
+
+use clockutils::{
+    headless::{read_texture_rgba, render_frame},
+    target::{ColorTarget, DepthTexture, PipelineBuilder, TargetConfig, TargetPipeline},
+    ExecDraw, FrameInfo, SURFACE_FORMAT
+};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// a white triangle covering the lower left half, its hypotenuse runs diagonally across every pixel row
+const SHADER: &str = "
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    var corners = array<vec2<f32>, 3>(vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(-1.0, 1.0));
+    return vec4<f32>(corners[index], 0.5, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+";
+
+const SIZE: u32 = 32;
+
+/// Builds the targets and the pipeline from scratch for `config`, as a clock does when switching, then draws
+fn render(device: &wgpu::Device, queue: &wgpu::Queue, config: &TargetConfig) -> image::RgbaImage {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(SHADER.into())
+    });
+
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label:                None,
+        bind_group_layouts:   &[],
+        push_constant_ranges: &[]
+    });
+
+    let pipeline     = PipelineBuilder::new(config, &layout, &shader).build(device);
+    let color_target = ColorTarget::new((SIZE, SIZE), config, device);
+    let depth        = DepthTexture::new((SIZE, SIZE), config, device);
+
+    let (output, view) = common::readable_target(device, (SIZE, SIZE), config.color_format);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label:                    None,
+            depth_stencil_attachment: Some(depth.attachment(config, false)),
+            timestamp_writes:         None,
+            occlusion_query_set:      None,
+            color_attachments:        &[Some(color_target.attachment(config, &view, wgpu::LoadOp::Clear(wgpu::Color::BLACK)))]
+        });
+
+        pipeline.set(&mut rpass, config);
+        rpass.draw(0..3, 0..1);
+    }
+    queue.submit(std::iter::once(encoder.finish()));
+
+    read_texture_rgba(&output, device, queue).unwrap()
+}
+
+/// Pixels that are neither black nor white, i.e. partially covered ones blended at the edge
+fn blended_pixels(image: &image::RgbaImage) -> usize {
+    image.pixels().filter(|pixel| pixel.0[0] != 0 && pixel.0[0] != 255).count()
+}
+
+#[test]
+fn states_follow_the_config() {
+    let config = TargetConfig::new(SURFACE_FORMAT, Some(DEPTH_FORMAT)).with_sample_count(4);
+
+    assert!(config.is_multisampled());
+    assert_eq!(config.multisample_state().count, 4);
+
+    let depth = config.depth_stencil_state(false, wgpu::CompareFunction::LessEqual).unwrap();
+    assert_eq!(depth.format, DEPTH_FORMAT);
+    assert!(!depth.depth_write_enabled);
+    assert_eq!(depth.depth_compare, wgpu::CompareFunction::LessEqual);
+}
+
+#[test]
+fn no_depth_without_a_depth_format() {
+    let config = TargetConfig::new(SURFACE_FORMAT, None);
+
+    assert!(!config.is_multisampled());
+    assert!(config.depth_stencil_state(true, wgpu::CompareFunction::Less).is_none());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "The pipeline was made for")]
+fn mismatched_configs_are_caught() {
+    let single = TargetConfig::new(SURFACE_FORMAT, Some(DEPTH_FORMAT));
+    single.debug_assert_matches(&single.with_sample_count(4), "The pipeline");
+}
+
+#[test]
+fn switching_sample_counts_rebuilds_coherently() {
+    let Some(adapter) = common::adapter_with(wgpu::Features::empty()) else {
+        return;
+    };
+
+    let (device, queue) = common::device(&adapter, wgpu::Features::empty());
+
+    let base = TargetConfig::new(SURFACE_FORMAT, Some(DEPTH_FORMAT));
+    assert!(base.supports(4, &adapter, &device));
+
+    // back and forth, as the portal clock does with its M key. Any mismatch between the pieces is a validation error
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let images: Vec<image::RgbaImage> = [1, 4, 1].into_iter()
+        .map(|count| render(&device, &queue, &base.with_sample_count(count)))
+        .collect();
+    let error = pollster::block_on(device.pop_error_scope());
+    assert!(error.is_none(), "Validation error: {}", error.unwrap());
+
+    // inside and outside are the same either way
+    for image in &images {
+        assert_eq!(image.get_pixel(2, SIZE - 3).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(SIZE - 3, 2).0, [0, 0, 0, 255]);
+    }
+
+    // only the multisampled one has a smooth edge, and switching back restores the aliased one
+    assert_eq!(blended_pixels(&images[0]), 0);
+    assert!(blended_pixels(&images[1]) >= SIZE as usize / 2);
+    assert!(images[0] == images[2]);
+}
+
+/// Draws the triangle into whatever frame the runner hands it, built for its sample count like the clocks are
+struct FrameClock {
+    config:   TargetConfig,
+    pipeline: TargetPipeline
+}
+
+impl ExecDraw for FrameClock {
+    fn setup(
+        config:   &wgpu::SurfaceConfiguration,
+        _adapter: &wgpu::Adapter,
+        device:   &wgpu::Device,
+        _queue:   &wgpu::Queue,
+        _theme:   winit::window::Theme
+    ) -> Result<Self, String> {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  None,
+            source: wgpu::ShaderSource::Wgsl(SHADER.into())
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label:                None,
+            bind_group_layouts:   &[],
+            push_constant_ranges: &[]
+        });
+
+        let config   = TargetConfig::frame(config.format, None);
+        let pipeline = PipelineBuilder::new(&config, &layout, &shader).build(device);
+
+        Ok(Self { config, pipeline })
+    }
+
+    fn resize(self: &mut Self, _width: u32, _height: u32, _device: &wgpu::Device, _queue: &wgpu::Queue) {}
+
+    fn draw(self: &mut Self, texview: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue, _frame: &FrameInfo) {
+        let color_target = ColorTarget::frame(&self.config);
+        let mut encoder  = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label:                    None,
+                depth_stencil_attachment: None,
+                timestamp_writes:         None,
+                occlusion_query_set:      None,
+                color_attachments:        &[Some(color_target.attachment(&self.config, texview, wgpu::LoadOp::Clear(wgpu::Color::BLACK)))]
+            });
+
+            self.pipeline.set(&mut rpass, &self.config);
+            rpass.draw(0..3, 0..1);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
+
+#[test]
+fn the_runner_resolves_its_multisampled_frame() {
+    if common::skip_without_gpu() {
+        return;
+    }
+
+    let single = pollster::block_on(render_frame::<FrameClock>(SIZE, SIZE, None, 1)).unwrap();
+    let multi  = pollster::block_on(render_frame::<FrameClock>(SIZE, SIZE, None, 4)).unwrap();
+
+    for image in [&single, &multi] {
+        assert_eq!(image.get_pixel(2, SIZE - 3).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(SIZE - 3, 2).0, [0, 0, 0, 255]);
+    }
+
+    assert_eq!(blended_pixels(&single), 0);
+    assert!(blended_pixels(&multi) >= SIZE as usize / 2);
+}
@@ -0,0 +1,60 @@
+//! Texture arrays made of PNG files, a layer per file, like the portal's day/night lightmaps.
+//!
+//! Needs a GPU, skipped when there's none
+
+mod common;
+
+use clockutils::ResourceTexture;
+
+/// A solid PNG in the temp folder, named after the test so that parallel tests don't collide
+fn write_png(name: &str, width: u32, height: u32, color: [u8; 4]) -> String {
+    let path = std::env::temp_dir().join(format!("wgpu-clocks-{}-{}.png", name, std::process::id()));
+    image::RgbaImage::from_pixel(width, height, image::Rgba(color)).save(&path).unwrap();
+
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn a_layer_per_file() {
+    let Some((device, queue)) = common::gpu() else {
+        return;
+    };
+
+    let day   = write_png("array-day", 8, 4, [255, 255, 255, 255]);
+    let night = write_png("array-night", 8, 4, [0, 0, 64, 255]);
+
+    let array = ResourceTexture::array(&[ &day, &night ], &device, &queue).unwrap();
+
+    assert_eq!((array.width, array.height), (8, 4));
+    assert_eq!(array.texture.depth_or_array_layers(), 2);
+
+    // binds as an array
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label:   None,
+        entries: &[ ResourceTexture::array_layout_entry(0) ]
+    });
+    device.create_bind_group(&wgpu::BindGroupDescriptor { label: None, layout: &layout, entries: &[ array.get_entry(0) ] });
+
+    for path in [day, night] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn layers_of_different_sizes_are_refused() {
+    let Some((device, queue)) = common::gpu() else {
+        return;
+    };
+
+    let small = write_png("array-small", 4, 4, [255, 0, 0, 255]);
+    let large = write_png("array-large", 8, 8, [0, 255, 0, 255]);
+
+    let error = ResourceTexture::array(&[ &small, &large ], &device, &queue).err().unwrap();
+    assert!(error.contains("same size"), "{}", error);
+
+    assert!(ResourceTexture::array(&[], &device, &queue).is_err());
+
+    for path in [small, large] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,47 @@
+//! Textures made from memory rather than files: raw RGBA8 pixels and encoded images.
+//!
+//! Needs a GPU, skipped when there's none
+
+mod common;
+
+use clockutils::{decode_rgba8, ResourceTexture};
+
+/// A 3x2 PNG, red with a green bottom-right pixel
+fn encoded_png() -> Vec<u8> {
+    let mut image = image::RgbaImage::from_pixel(3, 2, image::Rgba([255, 0, 0, 255]));
+    image.put_pixel(2, 1, image::Rgba([0, 255, 0, 255]));
+
+    let mut png = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+
+    png
+}
+
+#[test]
+fn encoded_images_decode_to_rgba8() {
+    let (width, height, data) = decode_rgba8(&encoded_png()).unwrap();
+
+    assert_eq!((width, height), (3, 2));
+    assert_eq!(&data[0..4], &[255, 0, 0, 255]);
+    assert_eq!(&data[20..24], &[0, 255, 0, 255]);
+
+    let error = decode_rgba8(b"not an image").err().unwrap();
+    assert!(error.contains("texture"), "{}", error);
+}
+
+#[test]
+fn textures_from_memory() {
+    let Some((device, queue)) = common::gpu() else {
+        return;
+    };
+
+    let raw = ResourceTexture::from_rgba8(2, 2, &[255; 16], &device, &queue);
+    assert_eq!((raw.width, raw.height), (2, 2));
+
+    let encoded = ResourceTexture::from_encoded_bytes(&encoded_png(), &device, &queue).unwrap();
+    assert_eq!((encoded.width, encoded.height), (3, 2));
+    assert_eq!(encoded.texture.format(), wgpu::TextureFormat::Rgba8Unorm);
+
+    assert!(ResourceTexture::from_encoded_bytes(&[], &device, &queue).is_err());
+}
+
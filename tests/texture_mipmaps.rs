@@ -0,0 +1,85 @@
+//! Mipmapped textures: how many levels they get, and the levels averaging the ones above them.
+//!
+//! Needs a GPU for the levels, skipped when there's none
+
+mod common;
+
+use clockutils::{headless::read_texture_rgba, BasicFilteringSampler, FullscreenQuadPipeline, ResourceTexture};
+
+// the given level at the texture's center
+const SHADER: &str = "
+@group(0) @binding(0) var tex: texture_2d<f32>;
+@group(0) @binding(1) var tex_sampler: sampler;
+
+var<push_constant> level: f32;
+
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return textureSampleLevel(tex, tex_sampler, vec2f(0.5), level);
+}
+";
+
+#[test]
+fn levels_down_to_1x1() {
+    assert_eq!(ResourceTexture::mip_level_count(1, 1), 1);
+    assert_eq!(ResourceTexture::mip_level_count(256, 256), 9);
+    assert_eq!(ResourceTexture::mip_level_count(300, 20), 9);
+    assert_eq!(ResourceTexture::mip_level_count(3, 512), 10);
+}
+
+#[test]
+fn levels_average_the_ones_above() {
+    let Some((device, queue)) = common::gpu_with_push_constants() else {
+        return;
+    };
+
+    // white and black columns
+    let path  = std::env::temp_dir().join(format!("wgpu-clocks-mipmaps-{}.png", std::process::id()));
+    let image = image::RgbaImage::from_fn(8, 4, |x, _| if x % 2 == 0 { image::Rgba([255; 4]) } else { image::Rgba([0, 0, 0, 255]) });
+    image.save(&path).unwrap();
+
+    let texture = ResourceTexture::new_with_mipmaps(path.to_str().unwrap(), &device, &queue).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(texture.texture.mip_level_count(), 4);
+
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label:   None,
+        entries: &[ ResourceTexture::default_layout_entry(0), BasicFilteringSampler::default_layout_entry(1) ]
+    });
+    let sampler   = BasicFilteringSampler::new(&device);
+    let bindgroup = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label:   None,
+        layout:  &layout,
+        entries: &[ texture.get_entry(0), sampler.get_entry(1) ]
+    });
+
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor { label: None, source: wgpu::ShaderSource::Wgsl(SHADER.into()) });
+    let format = wgpu::TextureFormat::Bgra8Unorm;
+    let quad   = FullscreenQuadPipeline::new(
+        &device, &module, "fs_main", &[ &layout ],
+        &[ wgpu::PushConstantRange { stages: wgpu::ShaderStages::FRAGMENT, range: 0..4 } ],
+        format
+    );
+
+    let (target, view) = common::readable_target(&device, (1, 1), format);
+
+    let sample = |level: f32| {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut rpass = common::render_pass(&mut encoder, &view, wgpu::Color::BLACK);
+
+            quad.draw(&mut rpass, &[ &bindgroup ], &level.to_ne_bytes());
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+
+        read_texture_rgba(&target, &device, &queue).unwrap().get_pixel(0, 0).0
+    };
+
+    // every level below the first is gray, down to the single pixel
+    for level in 1..4 {
+        let [r, g, b, a] = sample(level as f32);
+        assert!((r as i32 - 128).abs() <= 2 && r == g && g == b, "level {}: {:?}", level, [r, g, b, a]);
+        assert_eq!(a, 255);
+    }
+}
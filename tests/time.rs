@@ -0,0 +1,95 @@
+//! The `--debug-time` controls: pausing, stepping and changing the speed, on a clock started just before midnight
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeDelta};
+use clockutils::time::{ControllableTime, TimeSource};
+use std::time::Duration;
+
+fn utc() -> FixedOffset {
+    FixedOffset::east_opt(0).unwrap()
+}
+
+fn at(text: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(text).unwrap()
+}
+
+/// 2024-12-31 23:59:30 UTC, standing still (at a speed of 0) until told otherwise
+fn before_midnight() -> ControllableTime {
+    let date  = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+    let start = NaiveTime::from_hms_opt(23, 59, 30).unwrap();
+
+    ControllableTime::new(TimeSource::new(Some(utc()), Some(date), Some(start), 0.0))
+}
+
+#[test]
+fn pausing_keeps_the_time() {
+    let mut time = ControllableTime::new(TimeSource::new(Some(utc()), None, None, 1.0));
+
+    time.pause();
+    assert!(time.is_paused());
+    let paused = time.now();
+
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(time.now(), paused);
+
+    // pausing again doesn't move it either
+    time.pause();
+    assert_eq!(time.now(), paused);
+
+    // and it continues from there
+    time.resume();
+    assert!(!time.is_paused());
+    assert!(time.now() >= paused && time.now() - paused < TimeDelta::seconds(1));
+}
+
+#[test]
+fn steps_cross_midnight() {
+    let mut time = before_midnight();
+
+    for _ in 0..30 {
+        time.step(TimeDelta::seconds(1));
+    }
+    assert_eq!(time.now(), at("2025-01-01T00:00:00+00:00"));
+
+    time.step(TimeDelta::seconds(-1));
+    assert_eq!(time.now(), at("2024-12-31T23:59:59+00:00"));
+
+    time.step(TimeDelta::minutes(1));
+    assert_eq!(time.now(), at("2025-01-01T00:00:59+00:00"));
+
+    // back across it, while paused
+    time.pause();
+    time.step(TimeDelta::minutes(-1));
+    time.step(TimeDelta::minutes(-1));
+    assert_eq!(time.now(), at("2024-12-31T23:58:59+00:00"));
+}
+
+#[test]
+fn speed_scales_the_elapsed_time() {
+    let mut time = before_midnight();
+    assert_eq!(time.speed(), 0.0);
+
+    // standing still
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(time.now(), at("2024-12-31T23:59:30+00:00"));
+
+    // at the fastest, 20 ms are over 20 s. The speed changes from where the time is, it doesn't jump
+    time.set_speed(1e9);
+    assert_eq!(time.speed(), ControllableTime::SPEED_RANGE.1);
+    assert!(time.now() - at("2024-12-31T23:59:30+00:00") < TimeDelta::minutes(1));
+
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(time.now() - at("2024-12-31T23:59:30+00:00") >= TimeDelta::milliseconds(20_480));
+
+    // at the slowest, barely moving
+    time.set_speed(0.0);
+    assert_eq!(time.speed(), ControllableTime::SPEED_RANGE.0);
+    let slow = time.now();
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(time.now() - slow < TimeDelta::milliseconds(20));
+
+    // a paused clock keeps the speed for later, without moving
+    time.pause();
+    let paused = time.now();
+    time.set_speed(2.0);
+    assert_eq!((time.now(), time.speed()), (paused, 2.0));
+}
@@ -0,0 +1,91 @@
+//! Tone mapping an HDR gray: each curve maps it to its own value, black stays black.
+//!
+//! Needs a GPU with push constants, skipped when there's none
+
+mod common;
+
+use clockutils::{headless::read_texture_rgba, FullscreenQuadPipeline, RenderTexture, ToneMapper, TonemapPass};
+
+// twice as bright as white on the left half, black on the right one
+const SCENE_SHADER: &str = "
+@fragment
+fn fs_main(@location(0) uv: vec2f) -> @location(0) vec4f {
+    return select(vec4f(0.0, 0.0, 0.0, 1.0), vec4f(2.0, 2.0, 2.0, 1.0), uv.x < 0.5);
+}
+";
+
+const SIZE: u32 = 16;
+
+/// The left and right halves' red, after tone mapping the scene with each of the given mappers and exposures
+fn tone_map(cases: &[(ToneMapper, f32)]) -> Option<Vec<(u8, u8)>> {
+    let (device, queue) = common::gpu_with_push_constants()?;
+
+    let hdr_format = wgpu::TextureFormat::Rgba16Float;
+    // linear, so that the values read back are the shader's
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+
+    let scene_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label:  None,
+        source: wgpu::ShaderSource::Wgsl(SCENE_SHADER.into())
+    });
+    let scene = FullscreenQuadPipeline::new(&device, &scene_shader, "fs_main", &[], &[], hdr_format);
+
+    let source = RenderTexture::new((SIZE, SIZE), hdr_format, true, &device);
+    let (output, output_view) = common::readable_target(&device, (SIZE, SIZE), format);
+
+    let tonemap = TonemapPass::new(&device, format);
+    let source_bindgroup = tonemap.source_bindgroup(&source, &device);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut rpass = common::render_pass(&mut encoder, &source.view, wgpu::Color::BLACK);
+        scene.draw(&mut rpass, &[], &[]);
+    }
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let results = cases.iter().map(|(mapper, exposure)| {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        tonemap.apply(&mut encoder, &source_bindgroup, &output_view, *mapper, *exposure);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let image = read_texture_rgba(&output, &device, &queue).unwrap();
+        (image.get_pixel(SIZE / 4, SIZE / 2).0[0], image.get_pixel(SIZE * 3 / 4, SIZE / 2).0[0])
+    }).collect();
+
+    Some(results)
+}
+
+#[test]
+fn curves_map_hdr_into_range() {
+    let Some(results) = tone_map(&[
+        (ToneMapper::Linear,     1.0),
+        (ToneMapper::Linear,     0.25),
+        (ToneMapper::Reinhard,   1.0),
+        (ToneMapper::AcesFilm,   1.0),
+        (ToneMapper::Uncharted2, 1.0)
+    ]) else {
+        return;
+    };
+
+    let close = |value: u8, expected: f32| (value as f32 - expected * 255.0).abs() <= 1.0;
+
+    // clamped, then scaled down by the exposure
+    assert_eq!(results[0].0, 255);
+    assert!(close(results[1].0, 0.5), "{:?}", results[1]);
+    // 2 / (1 + 2)
+    assert!(close(results[2].0, 2.0 / 3.0), "{:?}", results[2]);
+    // (2 * (2.51 * 2 + 0.03)) / (2 * (2.43 * 2 + 0.59) + 0.14)
+    assert!(close(results[3].0, 10.1 / 11.04), "{:?}", results[3]);
+    // short of white, brighter than Reinhard's
+    assert!(results[4].0 < 255 && results[4].0 > results[2].0, "{:?}", results[4]);
+
+    for (_, black) in results {
+        assert_eq!(black, 0);
+    }
+}
+
+#[test]
+fn linear_is_the_default() {
+    assert_eq!(ToneMapper::default(), ToneMapper::Linear);
+    assert_eq!(ToneMapper::Uncharted2 as u32, 3);
+}
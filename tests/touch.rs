@@ -0,0 +1,97 @@
+//! Fingers on a touch screen made into the mouse's gestures: drags, pinches and taps, in whatever order they come and go
+
+use clockutils::{TouchGesture, TouchTracker, SCROLL_PIXELS_PER_LINE, TAP_SLOP};
+use winit::event::TouchPhase::{Cancelled, Ended, Moved, Started};
+
+#[test]
+fn a_single_finger_drags() {
+    let mut touches = TouchTracker::new();
+    assert_eq!(touches.touch(Started, 1, (100.0, 100.0)), None);
+
+    assert_eq!(touches.touch(Moved, 1, (130.0, 90.0)), Some(TouchGesture::Drag((30.0, -10.0))));
+    assert_eq!(touches.touch(Moved, 1, (135.0, 90.0)), Some(TouchGesture::Drag((5.0, 0.0))));
+
+    // too far for a tap
+    assert_eq!(touches.touch(Ended, 1, (135.0, 90.0)), None);
+    assert_eq!(touches.count(), 0);
+}
+
+#[test]
+fn taps_stay_close() {
+    let mut touches = TouchTracker::new();
+    touches.touch(Started, 7, (50.0, 60.0));
+    touches.touch(Moved, 7, (50.0 + TAP_SLOP * 0.5, 60.0));
+    assert_eq!(touches.touch(Ended, 7, (50.0 + TAP_SLOP * 0.5, 60.0)), Some(TouchGesture::Tap((50.0 + TAP_SLOP * 0.5, 60.0))));
+
+    // straying and coming back doesn't make a tap
+    touches.touch(Started, 8, (50.0, 60.0));
+    touches.touch(Moved, 8, (50.0 + TAP_SLOP * 2.0, 60.0));
+    touches.touch(Moved, 8, (50.0, 60.0));
+    assert_eq!(touches.touch(Ended, 8, (50.0, 60.0)), None);
+
+    // neither does a cancelled touch
+    touches.touch(Started, 9, (50.0, 60.0));
+    assert_eq!(touches.touch(Cancelled, 9, (50.0, 60.0)), None);
+}
+
+#[test]
+fn two_fingers_pinch() {
+    let mut touches = TouchTracker::new();
+    touches.touch(Started, 1, (100.0, 100.0));
+    touches.touch(Started, 2, (200.0, 100.0));
+
+    // apart is up, as zooming in with the wheel
+    let line = SCROLL_PIXELS_PER_LINE;
+    assert_eq!(touches.touch(Moved, 2, (200.0 + line, 100.0)), Some(TouchGesture::Pinch(1.0)));
+    assert_eq!(touches.touch(Moved, 1, (100.0 + line * 2.0, 100.0)), Some(TouchGesture::Pinch(-2.0)));
+
+    // turning around the other finger keeps the distance, from its right to right below it
+    let apart = 100.0 - line;
+    assert_eq!(touches.touch(Moved, 2, (100.0 + line * 2.0, 100.0 + apart)), None);
+
+    // a third finger is along for the ride
+    touches.touch(Started, 3, (300.0, 300.0));
+    assert_eq!(touches.touch(Moved, 3, (350.0, 300.0)), None);
+}
+
+#[test]
+fn a_second_finger_takes_over_the_drag() {
+    let mut touches = TouchTracker::new();
+    touches.touch(Started, 1, (100.0, 100.0));
+    assert_eq!(touches.touch(Moved, 1, (120.0, 100.0)), Some(TouchGesture::Drag((20.0, 0.0))));
+
+    // mid-drag, the second finger makes it a pinch
+    touches.touch(Started, 2, (220.0, 100.0));
+    assert!(matches!(touches.touch(Moved, 1, (110.0, 100.0)), Some(TouchGesture::Pinch(_))));
+
+    // the first finger lifts, the second one drags on from where it is, without jumping
+    assert_eq!(touches.touch(Ended, 1, (110.0, 100.0)), None);
+    assert_eq!(touches.touch(Moved, 2, (225.0, 103.0)), Some(TouchGesture::Drag((5.0, 3.0))));
+
+    // the gesture had two fingers, lifting the last one isn't a tap
+    assert_eq!(touches.touch(Ended, 2, (225.0, 103.0)), None);
+}
+
+#[test]
+fn fingers_lift_in_any_order() {
+    let mut touches = TouchTracker::new();
+    touches.touch(Started, 1, (0.0, 0.0));
+    touches.touch(Started, 2, (100.0, 0.0));
+    touches.touch(Started, 3, (0.0, 100.0));
+
+    // the first two pinched, with the first one gone the second and third do
+    touches.touch(Ended, 1, (0.0, 0.0));
+    assert_eq!(touches.count(), 2);
+    assert!(matches!(touches.touch(Moved, 3, (0.0, 120.0)), Some(TouchGesture::Pinch(_))));
+
+    touches.touch(Ended, 3, (0.0, 120.0));
+    assert_eq!(touches.touch(Moved, 2, (90.0, 0.0)), Some(TouchGesture::Drag((-10.0, 0.0))));
+    touches.touch(Ended, 2, (90.0, 0.0));
+
+    // events of fingers it never saw come to nothing, the next touch starts afresh
+    assert_eq!(touches.touch(Moved, 4, (10.0, 10.0)), None);
+    assert_eq!(touches.touch(Ended, 4, (10.0, 10.0)), None);
+
+    touches.touch(Started, 5, (10.0, 10.0));
+    assert_eq!(touches.touch(Ended, 5, (10.0, 10.0)), Some(TouchGesture::Tap((10.0, 10.0))));
+}
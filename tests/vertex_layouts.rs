@@ -0,0 +1,39 @@
+//! The vertex types' buffer layouts: strides and offsets matching the structs, with no gaps bytemuck would refuse
+
+use clockutils::{Vtx2ID, Vtx2UV, Vtx3ColorUV, Vtx3NUV, Vtx3NUVT, Vtx3UV};
+
+/// (stride, offsets by location)
+fn layout(layout: wgpu::VertexBufferLayout) -> (u64, Vec<u64>) {
+    assert_eq!(layout.step_mode, wgpu::VertexStepMode::Vertex);
+    assert!(layout.attributes.iter().enumerate().all(|(location, attribute)| attribute.shader_location == location as u32));
+
+    (layout.array_stride, layout.attributes.iter().map(|attribute| attribute.offset).collect())
+}
+
+#[test]
+fn offsets_follow_the_fields() {
+    assert_eq!(layout(Vtx2ID::wgpu_layout()),      (12, vec![0, 8]));
+    assert_eq!(layout(Vtx2UV::wgpu_layout()),      (16, vec![0, 8]));
+    assert_eq!(layout(Vtx3UV::wgpu_layout()),      (20, vec![0, 12]));
+    assert_eq!(layout(Vtx3NUV::wgpu_layout()),     (32, vec![0, 12, 24]));
+    assert_eq!(layout(Vtx3NUVT::wgpu_layout()),    (48, vec![0, 12, 24, 32]));
+
+    // as `Vtx3UV` is read, the normals skipped
+    assert_eq!(layout(Vtx3NUV::wgpu_layout_without_normals()), (32, vec![0, 24]));
+
+    // the blend weights past the padding
+    assert_eq!(layout(Vtx3ColorUV::wgpu_layout()), (48, vec![0, 12, 32]));
+}
+
+#[test]
+fn vertices_cast_to_bytes() {
+    let vertices = [
+        Vtx2UV { pos: glam::Vec2::new(-1.0, 1.0), uv: glam::Vec2::new(0.0, 0.0) },
+        Vtx2UV { pos: glam::Vec2::new(1.0, -1.0), uv: glam::Vec2::new(1.0, 1.0) }
+    ];
+    let bytes: &[u8] = bytemuck::cast_slice(&vertices);
+
+    assert_eq!(bytes.len(), 32);
+    assert_eq!(&bytes[16..20], &1.0_f32.to_ne_bytes());
+    assert_eq!(bytemuck::cast_slice::<u8, Vtx2UV>(bytes), &vertices);
+}
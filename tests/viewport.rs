@@ -0,0 +1,52 @@
+//! Rectangles for partial-surface rendering: from the drawing space with its y flipped, clamped to the target, and split
+
+use clockutils::{viewport::ViewportRect, DrawspaceScales};
+
+#[test]
+fn drawspace_rectangles_are_flipped() {
+    // 16 units across 800x400, 25 pixels per unit, the origin in the middle
+    let scales = DrawspaceScales::new(glam::Vec2::new(800.0, 400.0), glam::Vec2::new(16.0, 8.0));
+
+    // the drawing space's upper half is the framebuffer's upper rows
+    let top = ViewportRect::from_drawspace(&scales, glam::Vec2::new(-2.0, 0.0), glam::Vec2::new(2.0, 4.0));
+    assert_eq!(top, ViewportRect::new(350, 100, 100, 100));
+
+    // either pair of opposite corners
+    let bottom = ViewportRect::from_drawspace(&scales, glam::Vec2::new(2.0, -8.0), glam::Vec2::new(-2.0, 0.0));
+    assert_eq!(bottom, ViewportRect::new(350, 200, 100, 200));
+
+    // partially covered pixels are in, what's off the window is cut off
+    let edge = ViewportRect::from_drawspace(&scales, glam::Vec2::new(15.01, -1.01), glam::Vec2::new(20.0, 1.01));
+    assert_eq!(edge, ViewportRect::new(775, 174, 25, 52));
+}
+
+#[test]
+fn rectangles_are_clamped_to_the_target() {
+    let rect = ViewportRect::new(600, 300, 400, 200);
+
+    assert_eq!(rect.clamped((1024, 768)), Some(rect));
+    assert_eq!(rect.clamped((800, 400)),  Some(ViewportRect::new(600, 300, 200, 100)));
+    assert_eq!(rect.clamped((600, 400)),  None, "left of it, e.g. the window shrunk meanwhile");
+    assert_eq!(ViewportRect::new(0, 0, 0, 10).clamped((100, 100)), None);
+    assert_eq!(ViewportRect::new(u32::MAX, 0, u32::MAX, 1).clamped((100, 100)), None);
+}
+
+#[test]
+fn halves_cover_the_rectangle() {
+    let rect = ViewportRect::new(10, 20, 101, 51);
+
+    let (left, right) = rect.split_horizontally();
+    assert_eq!((left, right), (ViewportRect::new(10, 20, 50, 51), ViewportRect::new(60, 20, 51, 51)));
+
+    let (top, bottom) = rect.split_vertically();
+    assert_eq!((top, bottom), (ViewportRect::new(10, 20, 101, 25), ViewportRect::new(10, 45, 101, 26)));
+
+    for (x, y) in [(10, 20), (59, 70), (60, 20), (110, 70)] {
+        assert!(rect.contains(x, y));
+        assert!(left.contains(x, y) != right.contains(x, y), "({}, {})", x, y);
+        assert!(top.contains(x, y) != bottom.contains(x, y), "({}, {})", x, y);
+    }
+
+    assert!(!rect.contains(111, 20) && !rect.contains(10, 71) && !rect.contains(9, 20));
+    assert_eq!(ViewportRect::full((640, 480)), ViewportRect::from((0, 0, 640, 480)));
+}
@@ -0,0 +1,25 @@
+//! The shaders embedded into the web build, which has no resources folder to load them from
+
+use std::path::Path;
+use clockutils::{preprocess_shader, web::{embedded_shader, EMBEDDED_SHADERS, RESOURCES_ROOT}};
+
+#[test]
+fn embedded_shaders_are_the_resources() {
+    for (name, source) in EMBEDDED_SHADERS {
+        let file = std::fs::read_to_string(Path::new("resources").join(name)).unwrap();
+        assert_eq!(*source, file, "{}", name);
+        assert!(preprocess_shader(source).is_ok(), "{}", name);
+    }
+}
+
+#[test]
+fn embedded_shaders_are_found_where_the_clocks_load_them() {
+    let folder = Path::new(RESOURCES_ROOT).join("polar");
+
+    for shader in ["ring.wgsl", "disk.wgsl", "star.wgsl"] {
+        assert!(embedded_shader(&folder.join("shaders").join(shader)).is_some(), "{}", shader);
+    }
+
+    assert!(embedded_shader(&folder.join("shaders/missing.wgsl")).is_none());
+    assert!(embedded_shader(Path::new("polar/shaders/ring.wgsl")).is_none());
+}
@@ -0,0 +1,137 @@
+//! The shaders' shared math, run on the GPU by `ComputeRunner`: the blur kernel against a convolution on the CPU,
+//! and where the digit sprite sheets are read from.
+//!
+//! Skipped when there's no adapter that can run compute shaders
+
+use clockutils::{clocks::digital::{create_blur_weights_and_offsets, BlurWO}, compute::ComputeRunner};
+
+fn runner() -> Option<ComputeRunner> {
+    ComputeRunner::new().map_err(|error| eprintln!("{}, skipped", error)).ok()
+}
+
+// a row of pixels read through `blur_sample` the way a texture with a linear filter and clamped edges is
+const BLUR_SHADER: &str = "
+#include \"blur.wgsl\"
+
+@group(0) @binding(0) var<storage, read>       image:           array<f32>;
+@group(0) @binding(1) var<storage, read>       blur_table:      array<BlurWO>;
+@group(0) @binding(2) var<storage, read>       blur_table_size: u32;
+@group(0) @binding(3) var<storage, read_write> output:          array<f32>;
+
+fn pixel(index: i32) -> f32 {
+    return image[clamp(index, 0, i32(arrayLength(&image)) - 1)];
+}
+
+fn blur_sample(uv: vec2f) -> vec4f {
+    let x     = uv.x * f32(arrayLength(&image)) - 0.5;
+    let left  = floor(x);
+    let value = mix(pixel(i32(left)), pixel(i32(left) + 1), x - left);
+
+    return vec4f(vec3f(value), 1.0);
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3u) {
+    if id.x >= arrayLength(&output) { return; }
+
+    let texel = 1.0 / f32(arrayLength(&image));
+    output[id.x] = blur(vec2f(1.0, 0.0), vec2f((f32(id.x) + 0.5) * texel, 0.5), vec2f(texel, 1.0)).r;
+}
+";
+
+// dark on the left half, lit on the right one
+fn step_image(width: usize) -> Vec<f32> {
+    (0..width).map(|x| if x < width / 2 { 0.0 } else { 1.0 }).collect()
+}
+
+fn convolve(image: &[f32], kernel: &[BlurWO]) -> Vec<f32> {
+    let last = image.len() as i32 - 1;
+
+    (0..image.len() as i32).map(|x| {
+        kernel.iter().map(|tap| image[(x + tap.offset as i32).clamp(0, last) as usize] * tap.weight).sum()
+    }).collect()
+}
+
+fn gpu_blur(runner: &ComputeRunner, image: &[f32], table: &[BlurWO]) -> Vec<f32> {
+    let size = [table.len() as u32];
+    let inputs = [
+        clockutils::cast_slice_to_u8_slice(image),
+        clockutils::cast_slice_to_u8_slice(table),
+        clockutils::cast_slice_to_u8_slice(&size)
+    ];
+
+    runner.run(BLUR_SHADER, &inputs, image.len()).unwrap()
+}
+
+#[test]
+fn blur_matches_the_convolution() {
+    let Some(runner) = runner() else { return; };
+
+    let image = step_image(96);
+
+    for (radius, sigma) in [(4, 2.0), (9, 4.5), (16, 8.0)] {
+        let kernel    = create_blur_weights_and_offsets(radius, sigma, false, true).unwrap();
+        let reference = convolve(&image, &kernel);
+
+        // the linear offsets halve the taps, the interpolation between the pixel pairs makes up for them
+        for linear in [false, true] {
+            let table   = create_blur_weights_and_offsets(radius, sigma, linear, true).unwrap();
+            let blurred = gpu_blur(&runner, &image, &table);
+
+            for (x, (gpu, cpu)) in blurred.iter().zip(&reference).enumerate() {
+                assert!((gpu - cpu).abs() < 2e-3, "radius {}, linear {}, pixel {}: {} vs {}", radius, linear, x, gpu, cpu);
+            }
+        }
+
+        // flat far off the edge, a ramp across it, symmetric about it
+        let middle = image.len() / 2;
+        assert!(reference[0] < 1e-6 && reference[image.len() - 1] > 1.0 - 1e-6);
+        assert!(reference.windows(2).all(|pair| pair[0] <= pair[1] + 1e-6));
+        assert!((reference[middle - 1] + reference[middle] - 1.0).abs() < 1e-3, "{:?}", &reference[middle - 2..middle + 2]);
+    }
+}
+
+#[test]
+fn digit_cells_cover_the_sheet() {
+    let Some(runner) = runner() else { return; };
+
+    let shader = "
+        #include \"digits.wgsl\"
+
+        @group(0) @binding(0) var<storage, read>       uvs:    array<vec2f>;
+        @group(0) @binding(1) var<storage, read>       digits: array<u32>;
+        @group(0) @binding(2) var<storage, read_write> output: array<vec2f>;
+
+        @compute @workgroup_size(64)
+        fn main(@builtin(global_invocation_id) id: vec3u) {
+            if id.x >= arrayLength(&output) { return; }
+            output[id.x] = digit_cell_uv(uvs[id.x], digits[id.x]);
+        }
+    ";
+
+    // the corners of every digit's cell, and a point inside it
+    let corners = [[0.0_f32, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0], [0.25, 0.75]];
+    let (uvs, digits): (Vec<[f32; 2]>, Vec<u32>) = (0..10_u32)
+        .flat_map(|digit| corners.iter().map(move |&uv| (uv, digit)))
+        .unzip();
+
+    let sheet: Vec<[f32; 2]> = runner.run(
+        shader,
+        &[ clockutils::cast_slice_to_u8_slice(&uvs), clockutils::cast_slice_to_u8_slice(&digits) ],
+        uvs.len()
+    ).unwrap();
+
+    for ((uv, digit), result) in uvs.iter().zip(&digits).zip(&sheet) {
+        let left = *digit as f32 * 0.1;
+
+        assert!((result[0] - (left + uv[0] * 0.1)).abs() < 1e-6, "digit {} at {:?}: {:?}", digit, uv, result);
+        assert_eq!(result[1], uv[1], "digit {} at {:?}", digit, uv);
+    }
+
+    // each cell ends where the next one starts, the last one at the sheet's right edge
+    for digit in 0..9 {
+        assert_eq!(sheet[digit * 5 + 1][0], sheet[(digit + 1) * 5][0], "between {} and {}", digit, digit + 1);
+    }
+    assert_eq!(sheet[0][0], 0.0);
+    assert!((sheet[9 * 5 + 3][0] - 1.0).abs() < 1e-6);
+}
@@ -0,0 +1,65 @@
+//! The word clock's grid: the words telling the time, and the letters they light
+
+use clockutils::clocks::wordclock::{
+    cell_index, grid_codepoints, lit_flags, words_for, Word, A, AM, CELL_COUNT, COLUMNS, FIVE, GRID, HALF, HOURS, IS, IT, OCLOCK,
+    PAST, PM, QUARTER, TEN, TO, TWENTY
+};
+
+/// The letters a word lights, as read off the grid
+fn spelled(word: &Word) -> String {
+    GRID[word.row][word.column..word.column + word.len].to_string()
+}
+
+fn sentence(hour: u32, minute: u32) -> String {
+    words_for(hour, minute).iter().map(spelled).collect::<Vec<_>>().join(" ")
+}
+
+#[test]
+fn words_tell_the_time() {
+    assert_eq!(sentence(10, 0), "IT IS TEN OCLOCK AM");
+    assert_eq!(sentence(10, 4), "IT IS TEN OCLOCK AM");
+    assert_eq!(sentence(10, 15), "IT IS A QUARTER PAST TEN AM");
+    assert_eq!(sentence(13, 25), "IT IS TWENTY FIVE PAST ONE PM");
+    assert_eq!(sentence(13, 30), "IT IS HALF PAST ONE PM");
+
+    // counting down to the next hour, which can be on the next day
+    assert_eq!(sentence(10, 38), "IT IS TWENTY FIVE TO ELEVEN AM");
+    assert_eq!(sentence(11, 45), "IT IS A QUARTER TO TWELVE PM");
+    assert_eq!(sentence(23, 59), "IT IS FIVE TO TWELVE AM");
+    assert_eq!(sentence(0, 10), "IT IS TEN PAST TWELVE AM");
+}
+
+#[test]
+fn words_are_on_the_grid() {
+    assert!(GRID.iter().all(|row| row.len() == COLUMNS && row.chars().all(|c| c.is_ascii_uppercase())));
+
+    let names = [
+        (IT, "IT"), (IS, "IS"), (AM, "AM"), (PM, "PM"), (A, "A"), (QUARTER, "QUARTER"), (TWENTY, "TWENTY"), (FIVE, "FIVE"),
+        (HALF, "HALF"), (TEN, "TEN"), (TO, "TO"), (PAST, "PAST"), (OCLOCK, "OCLOCK")
+    ];
+    for (word, name) in names {
+        assert_eq!(spelled(&word), name);
+    }
+
+    let hours = ["ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE", "TEN", "ELEVEN", "TWELVE"];
+    for (word, name) in HOURS.iter().zip(hours) {
+        assert_eq!(spelled(word), name);
+    }
+}
+
+#[test]
+fn flags_light_the_words_letters() {
+    let codepoints = grid_codepoints();
+    assert_eq!(codepoints.len(), CELL_COUNT);
+    assert_eq!(codepoints[cell_index(9, 10)], 'K' as u32);
+
+    let flags = lit_flags(13, 30);
+    let lit: String = flags.iter().zip(&codepoints)
+        .filter(|(&flag, _)| flag == 1)
+        .map(|(_, &c)| char::from_u32(c).unwrap())
+        .collect();
+
+    // row by row: IT IS PM, HALF, PAST, ONE
+    assert_eq!(lit, "ITISPMHALFPASTONE");
+    assert!(flags.iter().all(|&flag| flag <= 1));
+}